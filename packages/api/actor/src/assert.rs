@@ -53,6 +53,7 @@ pub async fn server_for_env(
 
     // Step 5: Check authorization for current user/context to access this server.
     authorize_access(ctx, &server).await?;
+    ctx.auth().check_actor(server.datacenter_id, &server.tags)?;
 
     // Step 6: Additional validations can be performed here.
     // For example, validate server status, version, or endpoint compatibility.
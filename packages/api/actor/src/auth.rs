@@ -1,9 +1,11 @@
+use std::collections::HashMap;
+
 use api_helper::{
     auth::{ApiAuth, AuthRateLimitCtx},
     util::{as_auth_expired, basic_rate_limit},
 };
 use proto::claims::Claims;
-use tivet_claims::ClaimsDecode;
+use tivet_claims::{ent::EnvServiceScope, ClaimsDecode};
 use tivet_operation::prelude::*;
 use tracing::{info, warn, error};
 
@@ -16,6 +18,9 @@ pub struct Auth {
 pub struct CheckOpts<'a> {
     pub query: &'a GlobalQuery,
     pub allow_service_token: bool,
+    /// Scope required for scoped service tokens. Scoped service tokens can always access
+    /// endpoints without a scope that allow service tokens.
+    pub scope: Option<EnvServiceScope>,
     pub opt_auth: bool,
 }
 
@@ -36,7 +41,7 @@ impl ApiAuth for Auth {
         let claims = match api_token {
             Some(token) => {
                 info!("Decoding API token...");
-                let decoded = tivet_claims::decode(&config.server()?.jwt.public, &token)?;
+                let decoded = tivet_claims::decode(&config.server()?.jwt, &token)?;
                 Some(as_auth_expired(decoded)?)
             }
             None => {
@@ -143,7 +148,7 @@ impl Auth {
             self.check_cloud_token(cloud_ent, game_id)?;
             Ok(output)
         } else if let Ok(service_ent) = claims.as_env_service() {
-            self.check_service_token(service_ent, env_id, opts.allow_service_token)?;
+            self.check_service_token(ctx, service_ent, env_id, &opts).await?;
             Ok(output)
         } else if let Ok(user_ent) = claims.as_user() {
             self.check_user_token(ctx, user_ent, game_id).await?;
@@ -167,28 +172,87 @@ impl Auth {
         Ok(())
     }
 
-    fn check_service_token(
+    async fn check_service_token(
         &self,
+        ctx: &OperationContext<()>,
         service_ent: tivet_claims::ent::EnvService,
         env_id: Uuid,
-        allow_service_token: bool,
+        opts: &CheckOpts<'_>,
     ) -> GlobalResult<()> {
         info!("Checking service token for env_id: {}", env_id);
 
-        ensure_with!(
-            allow_service_token,
-            API_FORBIDDEN,
-            reason = "Cannot use service token for this endpoint."
-        );
+        check_service_token_access(&service_ent, env_id, opts)?;
+
+        if !service_ent.is_scoped() {
+            return Ok(());
+        }
+
+        // Scoped tokens can be revoked through the cloud API
+        let jti = unwrap_ref!(self.claims()?.jti);
+        let token_res = op!([ctx] token_get {
+            jtis: vec![*jti],
+        })
+        .await?;
+        let token = unwrap_with!(token_res.tokens.first(), TOKEN_REVOKED);
+        ensure_with!(token.revoke_ts.is_none(), TOKEN_REVOKED);
+
+        Ok(())
+    }
+
+    fn service_ent(&self) -> Option<tivet_claims::ent::EnvService> {
+        self.claims
+            .as_ref()
+            .and_then(|claims| claims.as_env_service().ok())
+    }
 
+    /// Returns false if a scoped service token restricts access to the given actor.
+    pub fn can_access_actor(&self, datacenter_id: Uuid, tags: &HashMap<String, String>) -> bool {
+        self.service_ent()
+            .map_or(true, |ent| ent.can_access_actor(datacenter_id, tags))
+    }
+
+    /// Validates that a scoped service token can access the given actor.
+    pub fn check_actor(
+        &self,
+        datacenter_id: Uuid,
+        tags: &HashMap<String, String>,
+    ) -> GlobalResult<()> {
         ensure_with!(
-            service_ent.env_id == env_id,
+            self.can_access_actor(datacenter_id, tags),
             API_FORBIDDEN,
-            reason = "Service token cannot write to this environment",
+            reason = "Service token cannot access this actor.",
         );
+
         Ok(())
     }
 
+    /// If a scoped service token restricts actors to certain datacenters.
+    pub fn restricts_datacenters(&self) -> bool {
+        self.service_ent()
+            .map_or(false, |ent| !ent.datacenter_ids.is_empty())
+    }
+
+    /// Adds the actor tag restrictions of a scoped service token to a tag filter. Returns `None`
+    /// if the filter conflicts with the restrictions and can never match.
+    pub fn restrict_actor_tags(
+        &self,
+        mut tags: HashMap<String, String>,
+    ) -> Option<HashMap<String, String>> {
+        if let Some(service_ent) = self.service_ent() {
+            for (k, v) in service_ent.actor_tags {
+                match tags.get(&k) {
+                    Some(x) if *x != v => return None,
+                    Some(_) => {}
+                    None => {
+                        tags.insert(k, v);
+                    }
+                }
+            }
+        }
+
+        Some(tags)
+    }
+
     async fn check_user_token(
         &self,
         ctx: &OperationContext<()>,
@@ -250,3 +314,210 @@ impl Auth {
         Ok(())
     }
 }
+
+/// Checks the environment and scopes of a service token. Does not check if the token was revoked.
+fn check_service_token_access(
+    service_ent: &tivet_claims::ent::EnvService,
+    env_id: Uuid,
+    opts: &CheckOpts<'_>,
+) -> GlobalResult<()> {
+    ensure_with!(
+        service_ent.env_id == env_id,
+        API_FORBIDDEN,
+        reason = "Service token cannot write to this environment",
+    );
+
+    // Tokens issued before scopes existed have access to every endpoint that allows service
+    // tokens
+    match opts.scope {
+        Some(scope) if service_ent.is_scoped() => ensure_with!(
+            service_ent.has_scope(scope),
+            API_FORBIDDEN,
+            reason = format!("Service token is missing the `{}` scope.", scope.as_str()),
+        ),
+        _ => ensure_with!(
+            opts.allow_service_token,
+            API_FORBIDDEN,
+            reason = "Cannot use service token for this endpoint."
+        ),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use proto::claims::{entitlement, Claims, Entitlement};
+    use tivet_claims::ent::{EnvService, EnvServiceScope};
+    use tivet_operation::prelude::*;
+
+    use super::{check_service_token_access, Auth, CheckOpts};
+    use crate::route::GlobalQuery;
+
+    fn tags(tags: &[(&str, &str)]) -> HashMap<String, String> {
+        tags.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn service_ent(
+        env_id: Uuid,
+        scopes: Vec<EnvServiceScope>,
+        actor_tags: HashMap<String, String>,
+        datacenter_ids: Vec<Uuid>,
+    ) -> EnvService {
+        EnvService {
+            env_id,
+            scopes,
+            actor_tags,
+            datacenter_ids,
+        }
+    }
+
+    fn service_auth(ent: &EnvService) -> Auth {
+        let kind = entitlement::Kind::EnvService(entitlement::EnvService {
+            env_id: Some(ent.env_id.into()),
+            scopes: ent.scopes.iter().map(|x| i32::from(*x)).collect(),
+            actor_tags: ent.actor_tags.clone(),
+            datacenter_ids: ent.datacenter_ids.iter().map(|x| (*x).into()).collect(),
+        });
+
+        Auth {
+            claims: Some(Claims {
+                exp: None,
+                iat: 0,
+                jti: Some(Uuid::new_v4().into()),
+                entitlements: vec![Entitlement { kind: Some(kind) }],
+            }),
+        }
+    }
+
+    fn check(
+        ent: &EnvService,
+        env_id: Uuid,
+        allow_service_token: bool,
+        scope: Option<EnvServiceScope>,
+    ) -> bool {
+        let query = GlobalQuery {
+            project: None,
+            environment: None,
+        };
+        let opts = CheckOpts {
+            query: &query,
+            allow_service_token,
+            scope,
+            opt_auth: false,
+        };
+
+        check_service_token_access(ent, env_id, &opts).is_ok()
+    }
+
+    #[test]
+    fn service_token_access() {
+        let env_id = Uuid::new_v4();
+
+        // Unscoped tokens can use every endpoint that allows service tokens
+        let unscoped = service_ent(env_id, Vec::new(), HashMap::new(), Vec::new());
+        assert!(check(&unscoped, env_id, true, None));
+        assert!(check(&unscoped, env_id, true, Some(EnvServiceScope::BuildsWrite)));
+        assert!(!check(&unscoped, env_id, false, None));
+        assert!(!check(&unscoped, env_id, false, Some(EnvServiceScope::BuildsWrite)));
+
+        // Scoped tokens need the endpoint's scope
+        let scoped = service_ent(
+            env_id,
+            vec![EnvServiceScope::ActorsRead],
+            HashMap::new(),
+            Vec::new(),
+        );
+        assert!(check(&scoped, env_id, true, Some(EnvServiceScope::ActorsRead)));
+        assert!(check(&scoped, env_id, false, Some(EnvServiceScope::ActorsRead)));
+        assert!(!check(&scoped, env_id, true, Some(EnvServiceScope::ActorsWrite)));
+        assert!(check(&scoped, env_id, true, None));
+        assert!(!check(&scoped, env_id, false, None));
+
+        // Other environment
+        assert!(!check(&unscoped, Uuid::new_v4(), true, None));
+        assert!(!check(
+            &scoped,
+            Uuid::new_v4(),
+            true,
+            Some(EnvServiceScope::ActorsRead)
+        ));
+    }
+
+    #[test]
+    fn can_access_actor() {
+        let env_id = Uuid::new_v4();
+        let dc_a = Uuid::new_v4();
+        let dc_b = Uuid::new_v4();
+
+        // Other tokens are not restricted
+        let auth = Auth { claims: None };
+        assert!(auth.can_access_actor(dc_a, &HashMap::new()));
+
+        let auth = service_auth(&service_ent(env_id, Vec::new(), HashMap::new(), Vec::new()));
+        assert!(auth.can_access_actor(dc_a, &HashMap::new()));
+        assert!(!auth.restricts_datacenters());
+
+        let auth = service_auth(&service_ent(
+            env_id,
+            vec![EnvServiceScope::ActorsRead],
+            tags(&[("team", "a"), ("env", "prod")]),
+            vec![dc_a],
+        ));
+        assert!(auth.restricts_datacenters());
+        assert!(auth.can_access_actor(dc_a, &tags(&[("team", "a"), ("env", "prod")])));
+        assert!(auth.can_access_actor(
+            dc_a,
+            &tags(&[("team", "a"), ("env", "prod"), ("extra", "x")])
+        ));
+        assert!(auth.check_actor(dc_a, &tags(&[("team", "a"), ("env", "prod")])).is_ok());
+
+        // Wrong datacenter
+        assert!(!auth.can_access_actor(dc_b, &tags(&[("team", "a"), ("env", "prod")])));
+
+        // Missing or mismatched tags
+        assert!(!auth.can_access_actor(dc_a, &tags(&[("team", "a")])));
+        assert!(!auth.can_access_actor(dc_a, &tags(&[("team", "b"), ("env", "prod")])));
+        assert!(auth.check_actor(dc_a, &tags(&[("team", "b")])).is_err());
+    }
+
+    #[test]
+    fn restrict_actor_tags() {
+        let env_id = Uuid::new_v4();
+
+        // Other tokens are not restricted
+        let auth = Auth { claims: None };
+        assert_eq!(
+            Some(tags(&[("a", "1")])),
+            auth.restrict_actor_tags(tags(&[("a", "1")]))
+        );
+
+        let auth = service_auth(&service_ent(
+            env_id,
+            vec![EnvServiceScope::ActorsRead],
+            tags(&[("team", "a")]),
+            Vec::new(),
+        ));
+
+        // Restrictions are added to the filter
+        assert_eq!(
+            Some(tags(&[("team", "a")])),
+            auth.restrict_actor_tags(HashMap::new())
+        );
+        assert_eq!(
+            Some(tags(&[("team", "a"), ("b", "2")])),
+            auth.restrict_actor_tags(tags(&[("b", "2")]))
+        );
+        assert_eq!(
+            Some(tags(&[("team", "a")])),
+            auth.restrict_actor_tags(tags(&[("team", "a")]))
+        );
+
+        // Conflicting filter can never match
+        assert_eq!(None, auth.restrict_actor_tags(tags(&[("team", "b")])));
+    }
+}
@@ -4,8 +4,10 @@ use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
 use futures_util::{StreamExt, TryStreamExt};
 use proto::backend;
 use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
 use tivet_convert::{ApiInto, ApiTryInto};
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_json::json;
 
@@ -17,10 +19,11 @@ use crate::{
 
 use super::GlobalQuery;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct GlobalEndpointTypeQuery {
 	#[serde(flatten)]
 	global: GlobalQuery,
+	#[schemars(with = "Option<String>")]
 	endpoint_type: Option<models::ActorEndpointType>,
 }
 
@@ -47,6 +50,7 @@ async fn get_inner(
 			CheckOpts {
 				query: &query.global,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
 				opt_auth: false,
 			},
 		)
@@ -71,6 +75,7 @@ async fn get_inner(
 
 	// Validate token can access server
 	ensure_with!(server.env_id == env_id, ACTOR_NOT_FOUND);
+	ctx.auth().check_actor(server.datacenter_id, &server.tags)?;
 
 	Ok(models::ActorGetActorResponse {
 		actor: Box::new(ds::types::convert_actor_to_api(server.clone(), dc)?),
@@ -126,7 +131,7 @@ pub async fn get_deprecated(
 // MARK: POST /actors
 pub async fn create(
 	ctx: Ctx<Auth>,
-	body: models::ActorCreateActorRequest,
+	mut body: models::ActorCreateActorRequest,
 	query: GlobalEndpointTypeQuery,
 ) -> GlobalResult<models::ActorCreateActorResponse> {
 	let CheckOutput { game_id, env_id } = ctx
@@ -136,53 +141,25 @@ pub async fn create(
 			CheckOpts {
 				query: &query.global,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
 				opt_auth: false,
 			},
 		)
 		.await?;
 
-	let (clusters_res, game_configs_res, build) = tokio::try_join!(
-		ctx.op(cluster::ops::get_for_game::Input {
-			game_ids: vec![game_id],
-		}),
-		ctx.op(ds::ops::game_config::get::Input {
-			game_ids: vec![game_id],
-		}),
-		resolve_build(&ctx, game_id, env_id, body.build, body.build_tags.flatten()),
-	)?;
-	let cluster_id = unwrap!(clusters_res.games.first()).cluster_id;
-	let game_config = unwrap!(game_configs_res.game_configs.first());
-
-	let datacenter_id = resolve_dc_id(&ctx, cluster_id, body.region.clone()).await?;
-
-	let tags = unwrap_with!(
-		serde_json::from_value(body.tags.unwrap_or_default()).ok(),
-		API_BAD_BODY,
-		error = "`tags` must be `Map<String, String>`"
-	);
-
-	let resources = match build.kind {
-		build::types::BuildKind::DockerImage | build::types::BuildKind::OciBundle => {
-			let resources = unwrap_with!(
-				body.resources,
-				API_BAD_BODY,
-				error = "`resources` must be set for actors using Docker builds"
-			);
-
-			(*resources).api_into()
-		}
-		build::types::BuildKind::JavaScript => {
-			ensure_with!(
-				body.resources.is_none(),
-				API_BAD_BODY,
-				error = "actors using JavaScript builds cannot set `resources`"
-			);
+	let build = resolve_build(
+		&ctx,
+		game_id,
+		env_id,
+		body.build,
+		body.build_tags.take().flatten(),
+	)
+	.await?;
 
-			ds::types::ServerResources::default_isolate()
-		}
-	};
+	let volumes = body.volumes.take().unwrap_or_default();
+	let template = resolve_actor_template(&ctx, game_id, build.kind, body).await?;
 
-	tracing::info!(?tags, "creating server with tags");
+	tracing::info!(tags=?template.tags, "creating server with tags");
 
 	let server_id = Uuid::new_v4();
 
@@ -196,91 +173,9 @@ pub async fn create(
 		.subscribe::<ds::workflows::server::DestroyStarted>(("server_id", server_id))
 		.await?;
 
-	let network = body.network.unwrap_or_default();
-
 	ctx.workflow(ds::workflows::server::Input {
-		server_id,
-		env_id,
-		datacenter_id,
-		cluster_id,
-		runtime: game_config.runtime,
-		tags,
-		resources,
-		lifecycle: body.lifecycle.map(|x| (*x).api_into()).unwrap_or_else(|| {
-			ds::types::ServerLifecycle {
-				kill_timeout_ms: 0,
-				durable: false,
-			}
-		}),
-		image_id: build.build_id,
-		root_user_enabled: game_config.root_user_enabled,
-		// args: body.runtime.arguments.unwrap_or_default(),
-		args: Vec::new(),
-		network_mode: network.mode.unwrap_or_default().api_into(),
-		environment: body.runtime.and_then(|r| r.environment).unwrap_or_default(),
-		network_ports: unwrap!(network
-			.ports
-			.unwrap_or_default()
-			.into_iter()
-			.map(|(s, p)| GlobalResult::Ok((
-				s.clone(),
-				ds::workflows::server::Port {
-					internal_port: p.internal_port.map(TryInto::try_into).transpose()?,
-					routing: if let Some(routing) = p.routing {
-						match *routing {
-							models::ActorPortRouting {
-								guard: Some(_gg),
-								host: None,
-							} => ds::types::Routing::GameGuard {
-								protocol: p.protocol.api_into(),
-								// Temporarily disabled
-								// authorization: match gg.authorization.as_deref() {
-								// 	Some(models::ActorPortAuthorization {
-								// 		bearer: Some(token),
-								// 		..
-								// 	}) => ds::types::PortAuthorization::Bearer(token.clone()),
-								// 	Some(models::ActorPortAuthorization {
-								// 		query: Some(query),
-								// 		..
-								// 	}) => ds::types::PortAuthorization::Query(
-								// 		query.key.clone(),
-								// 		query.value.clone(),
-								// 	),
-								// 	_ => ds::types::PortAuthorization::None,
-								// },
-								authorization: ds::types::PortAuthorization::None,
-							},
-							models::ActorPortRouting {
-								guard: None,
-								host: Some(_),
-							} => ds::types::Routing::Host {
-								protocol: match p.protocol.api_try_into() {
-									Err(err) if GlobalError::is(&err, formatted_error::code::ACTOR_FAILED_TO_CREATE) => {
-										// Add location
-										bail_with!(
-											ACTOR_FAILED_TO_CREATE,
-											error = format!("network.ports[{s:?}].protocol: Host port protocol must be either TCP or UDP.")
-										);
-									}
-									x => x?,
-								},
-							},
-							models::ActorPortRouting { .. } => {
-								bail_with!(
-									ACTOR_FAILED_TO_CREATE,
-									error = format!("network.ports[{s:?}].routing: Must specify either `guard` or `host` routing type.")
-								);
-							}
-						}
-					} else {
-						ds::types::Routing::GameGuard {
-							protocol: p.protocol.api_into(),
-							authorization: ds::types::PortAuthorization::None,
-						}
-					}
-				}
-			)))
-			.collect::<GlobalResult<HashMap<_, _>>>()),
+		volumes: volumes.into_iter().map(ApiInto::api_into).collect(),
+		..template.into_server_input(server_id, env_id, build.build_id)
 	})
 	.tag("server_id", server_id)
 	.dispatch()
@@ -394,13 +289,21 @@ pub async fn create_deprecated(
 			resources: Some(Box::new(models::ActorResources {
 				cpu: body.resources.cpu,
 				memory: body.resources.memory,
+				pids: None,
+				io_weight: None,
+				disk_read_bandwidth: None,
+				disk_write_bandwidth: None,
+				nofile: None,
+				egress_bandwidth: None,
 			})),
 			runtime: Some(Box::new(models::ActorCreateActorRuntimeRequest {
 				environment: body.runtime.environment,
+				secrets: None,
 			})),
 			build: Some(body.runtime.build),
 			build_tags: None,
 			tags: body.tags,
+			volumes: None,
 		},
 		GlobalEndpointTypeQuery {
 			global,
@@ -415,7 +318,7 @@ pub async fn create_deprecated(
 }
 
 // MARK: DELETE /actors/{}
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct DeleteQuery {
 	#[serde(flatten)]
 	global: GlobalQuery,
@@ -434,6 +337,7 @@ pub async fn destroy(
 			CheckOpts {
 				query: &query.global,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
 				opt_auth: false,
 			},
 		)
@@ -509,6 +413,7 @@ pub async fn upgrade(
 			CheckOpts {
 				query: &query,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
 				opt_auth: false,
 			},
 		)
@@ -549,6 +454,7 @@ pub async fn upgrade_all(
 			CheckOpts {
 				query: &query,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
 				opt_auth: false,
 			},
 		)
@@ -596,6 +502,10 @@ pub async fn upgrade_all(
 
 	let build = resolve_build(&ctx, game_id, env_id, body.build, body.build_tags.flatten()).await?;
 
+	let Some(tags) = ctx.auth().restrict_actor_tags(tags) else {
+		return Ok(models::ActorUpgradeAllActorsResponse { count: 0 });
+	};
+
 	// Work in batches
 	let mut count = 0;
 	let mut cursor = None;
@@ -605,14 +515,34 @@ pub async fn upgrade_all(
 				env_id,
 				tags: tags.clone(),
 				include_destroyed: false,
+				destroyed_after_ts: None,
 				cursor,
 				limit: 10_000,
 			})
 			.await?;
 
-		count += list_res.server_ids.len();
+		let fetched_count = list_res.server_ids.len();
 		cursor = list_res.server_ids.last().cloned();
 
+		let server_ids = if ctx.auth().restricts_datacenters() {
+			let servers_res = ctx
+				.op(ds::ops::server::get::Input {
+					server_ids: list_res.server_ids,
+					endpoint_type: None,
+				})
+				.await?;
+
+			servers_res
+				.servers
+				.into_iter()
+				.filter(|s| ctx.auth().can_access_actor(s.datacenter_id, &s.tags))
+				.map(|s| s.server_id)
+				.collect::<Vec<_>>()
+		} else {
+			list_res.server_ids
+		};
+		count += server_ids.len();
+
 		// TODO: Add back once we figure out how to cleanly handle if a wf is already complete when
 		// upgrading
 		// let subs = futures_util::stream::iter(list_res.server_ids.clone())
@@ -623,7 +553,7 @@ pub async fn upgrade_all(
 		// 	.try_collect::<Vec<_>>()
 		// 	.await?;
 
-		futures_util::stream::iter(list_res.server_ids)
+		futures_util::stream::iter(server_ids)
 			.map(|server_id| {
 				ctx.signal(ds::workflows::server::Upgrade {
 					image_id: build.build_id,
@@ -641,7 +571,7 @@ pub async fn upgrade_all(
 		// 	.try_collect::<Vec<_>>()
 		// 	.await?;
 
-		if count < 10_000 {
+		if fetched_count < 10_000 {
 			break;
 		}
 	}
@@ -652,7 +582,7 @@ pub async fn upgrade_all(
 }
 
 // MARK: GET /actors
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ListQuery {
 	#[serde(flatten)]
 	global_endpoint_type: GlobalEndpointTypeQuery,
@@ -681,6 +611,7 @@ async fn list_actors_inner(
 			CheckOpts {
 				query: &query.global_endpoint_type.global,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
 				opt_auth: false,
 			},
 		)
@@ -698,12 +629,16 @@ async fn list_actors_inner(
 		parameter = "tags_json",
 		error = "must be `Map<String, String>`"
 	);
+	let Some(tags) = ctx.auth().restrict_actor_tags(tags) else {
+		return Ok(models::ActorListActorsResponse { actors: Vec::new() });
+	};
 
 	let list_res = ctx
 		.op(ds::ops::server::list_for_env::Input {
 			env_id,
 			tags,
 			include_destroyed,
+			destroyed_after_ts: None,
 			cursor: query.cursor,
 			// HACK: Until we have webhooks, there needs to be a good way to get all of the most
 			// recent crashed actors. 10k is a high limit intentionally.
@@ -735,6 +670,7 @@ async fn list_actors_inner(
 	let servers = servers_res
 		.servers
 		.into_iter()
+		.filter(|a| ctx.auth().can_access_actor(a.datacenter_id, &a.tags))
 		.map(|a| {
 			let dc = unwrap!(dc_res
 				.datacenters
@@ -895,7 +831,164 @@ fn legacy_convert_actor_to_server(
 	})
 }
 
-async fn resolve_build(
+/// Resolves the configuration of an actor created with the given request, except for its build and volumes.
+pub(crate) async fn resolve_actor_template(
+	ctx: &Ctx<Auth>,
+	game_id: Uuid,
+	build_kind: build::types::BuildKind,
+	body: models::ActorCreateActorRequest,
+) -> GlobalResult<ds::types::ActorTemplate> {
+	let (clusters_res, game_configs_res) = tokio::try_join!(
+		ctx.op(cluster::ops::get_for_game::Input {
+			game_ids: vec![game_id],
+		}),
+		ctx.op(ds::ops::game_config::get::Input {
+			game_ids: vec![game_id],
+		}),
+	)?;
+	let cluster_id = unwrap!(clusters_res.games.first()).cluster_id;
+	let game_config = unwrap!(game_configs_res.game_configs.first());
+
+	let datacenter_id = resolve_dc_id(ctx, cluster_id, body.region.clone()).await?;
+
+	let tags = unwrap_with!(
+		serde_json::from_value(body.tags.unwrap_or_default()).ok(),
+		API_BAD_BODY,
+		error = "`tags` must be `Map<String, String>`"
+	);
+
+	ctx.auth().check_actor(datacenter_id, &tags)?;
+
+	let resources = match build_kind {
+		build::types::BuildKind::DockerImage | build::types::BuildKind::OciBundle => {
+			let resources = unwrap_with!(
+				body.resources,
+				API_BAD_BODY,
+				error = "`resources` must be set for actors using Docker builds"
+			);
+
+			(*resources).api_into()
+		}
+		build::types::BuildKind::JavaScript => {
+			ensure_with!(
+				body.resources.is_none(),
+				API_BAD_BODY,
+				error = "actors using JavaScript builds cannot set `resources`"
+			);
+
+			ds::types::ServerResources::default_isolate()
+		}
+	};
+
+	let network = body.network.unwrap_or_default();
+	let (environment, secrets) = body
+		.runtime
+		.map(|r| {
+			(
+				r.environment.unwrap_or_default(),
+				r.secrets.unwrap_or_default(),
+			)
+		})
+		.unwrap_or_default();
+
+	Ok(ds::types::ActorTemplate {
+		datacenter_id,
+		cluster_id,
+		runtime: game_config.runtime,
+		tags,
+		resources,
+		lifecycle: body.lifecycle.map(|x| (*x).api_into()).unwrap_or_else(|| {
+			ds::types::ServerLifecycle {
+				kill_timeout_ms: 0,
+				durable: false,
+			}
+		}),
+		root_user_enabled: game_config.root_user_enabled,
+		// args: body.runtime.arguments.unwrap_or_default(),
+		args: Vec::new(),
+		network_mode: network.mode.unwrap_or_default().api_into(),
+		environment,
+		secrets,
+		network_ports: unwrap!(network
+			.ports
+			.unwrap_or_default()
+			.into_iter()
+			.map(|(s, p)| GlobalResult::Ok((
+				s.clone(),
+				ds::workflows::server::Port {
+					internal_port: p.internal_port.map(TryInto::try_into).transpose()?,
+					routing: if let Some(routing) = p.routing {
+						match *routing {
+							models::ActorPortRouting {
+								guard: Some(gg),
+								host: None,
+							} => ds::types::Routing::GameGuard {
+								protocol: p.protocol.api_into(),
+								// Temporarily disabled
+								// authorization: match gg.authorization.as_deref() {
+								// 	Some(models::ActorPortAuthorization {
+								// 		bearer: Some(token),
+								// 		..
+								// 	}) => ds::types::PortAuthorization::Bearer(token.clone()),
+								// 	Some(models::ActorPortAuthorization {
+								// 		query: Some(query),
+								// 		..
+								// 	}) => ds::types::PortAuthorization::Query(
+								// 		query.key.clone(),
+								// 		query.value.clone(),
+								// 	),
+								// 	_ => ds::types::PortAuthorization::None,
+								// },
+								authorization: ds::types::PortAuthorization::None,
+								middleware: match gg.get("middleware") {
+									None | Some(serde_json::Value::Null) => None,
+									Some(middleware) => match serde_json::from_value(middleware.clone()) {
+										Ok(middleware) => Some(middleware),
+										Err(err) => {
+											bail_with!(
+												ACTOR_FAILED_TO_CREATE,
+												error = format!("network.ports[{s:?}].routing.guard.middleware: {err}")
+											);
+										}
+									},
+								},
+							},
+							models::ActorPortRouting {
+								guard: None,
+								host: Some(_),
+							} => ds::types::Routing::Host {
+								protocol: match p.protocol.api_try_into() {
+									Err(err) if GlobalError::is(&err, formatted_error::code::ACTOR_FAILED_TO_CREATE) => {
+										// Add location
+										bail_with!(
+											ACTOR_FAILED_TO_CREATE,
+											error = format!("network.ports[{s:?}].protocol: Host port protocol must be either TCP or UDP.")
+										);
+									}
+									x => x?,
+								},
+							},
+							models::ActorPortRouting { .. } => {
+								bail_with!(
+									ACTOR_FAILED_TO_CREATE,
+									error = format!("network.ports[{s:?}].routing: Must specify either `guard` or `host` routing type.")
+								);
+							}
+						}
+					} else {
+						ds::types::Routing::GameGuard {
+							protocol: p.protocol.api_into(),
+							authorization: ds::types::PortAuthorization::None,
+							middleware: None,
+						}
+					}
+				}
+			)))
+			.collect::<GlobalResult<HashMap<_, _>>>()),
+	})
+}
+
+pub(crate) async fn resolve_build(
 	ctx: &Ctx<Auth>,
 	game_id: Uuid,
 	env_id: Uuid,
@@ -991,7 +1084,7 @@ async fn resolve_build(
 	}
 }
 
-async fn resolve_dc_id(
+pub(crate) async fn resolve_dc_id(
 	ctx: &Ctx<Auth>,
 	cluster_id: Uuid,
 	region: Option<String>,
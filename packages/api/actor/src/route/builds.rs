@@ -3,8 +3,10 @@ use std::collections::HashMap;
 use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
 use futures_util::{StreamExt, TryStreamExt};
 use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
 use tivet_convert::{ApiInto, ApiTryInto};
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_json::json;
 use util::timestamp;
@@ -30,6 +32,7 @@ pub async fn get(
 			CheckOpts {
 				query: &query,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::BuildsRead),
 				opt_auth: false,
 			},
 		)
@@ -86,7 +89,7 @@ pub async fn get_deprecated(
 }
 
 // MARK: GET /builds
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ListQuery {
 	#[serde(flatten)]
 	global: GlobalQuery,
@@ -105,6 +108,7 @@ pub async fn list(
 			CheckOpts {
 				query: &query.global,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::BuildsRead),
 				opt_auth: false,
 			},
 		)
@@ -211,6 +215,7 @@ pub async fn patch_tags(
 			CheckOpts {
 				query: &query,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::BuildsWrite),
 				opt_auth: false,
 			},
 		)
@@ -309,6 +314,7 @@ pub async fn create_build(
 			CheckOpts {
 				query: &query,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::BuildsWrite),
 				opt_auth: false,
 			},
 		)
@@ -344,6 +350,11 @@ pub async fn create_build(
 			content: build::ops::create::Content::New {
 				image_file: (*body.image_file).api_try_into()?,
 				image_tag,
+				compression_dictionary_file: body
+					.compression_dictionary_file
+					.map(|file| (*file).api_try_into())
+					.transpose()?,
+				image_digest: body.image_digest,
 			},
 			kind,
 			compression: body
@@ -378,6 +389,8 @@ pub async fn create_build_deprecated(
 				models::ServersBuildCompression::Lz4 => models::ActorBuildCompression::Lz4,
 			}),
 			image_file: body.image_file,
+			compression_dictionary_file: None,
+			image_digest: None,
 			image_tag: Some(body.image_tag),
 			kind: body.kind.map(|k| match k {
 				models::ServersBuildKind::DockerImage => models::ActorBuildKind::DockerImage,
@@ -423,6 +436,7 @@ pub async fn complete_build(
 			CheckOpts {
 				query: &query,
 				allow_service_token: true,
+				scope: Some(EnvServiceScope::BuildsWrite),
 				opt_auth: false,
 			},
 		)
@@ -468,6 +482,7 @@ pub async fn complete_build(
 							build.upload_id,
 							&build::utils::file_name(build.kind, build.compression),
 						)?,
+						image_digest: build.image_digest.clone(),
 					})
 					.tag("datacenter_id", datacenter_id)
 					.send()
@@ -492,3 +507,143 @@ pub async fn complete_build_deprecated(
 	let global = build_global_query_compat(&ctx, game_id, env_id).await?;
 	complete_build(ctx, build_id, body, global).await
 }
+
+// MARK: GET /builds/retention
+pub async fn get_retention(
+	ctx: Ctx<Auth>,
+	_watch_index: WatchIndexQuery,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorGetBuildRetentionPolicyResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::BuildsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let policies_res = ctx
+		.op(build::ops::retention::get::Input {
+			env_ids: vec![env_id],
+		})
+		.await?;
+
+	Ok(models::ActorGetBuildRetentionPolicyResponse {
+		policy: policies_res
+			.policies
+			.into_iter()
+			.next()
+			.map(|x| {
+				GlobalResult::Ok(Box::new(models::ActorBuildRetentionPolicy {
+					keep_last: x.policy.keep_last.map(i32::try_from).transpose()?,
+					untagged_max_age_days: x
+						.policy
+						.untagged_max_age
+						.map(|max_age| i32::try_from(max_age / util::duration::days(1)))
+						.transpose()?,
+				}))
+			})
+			.transpose()?,
+	})
+}
+
+// MARK: PUT /builds/retention
+pub async fn set_retention(
+	ctx: Ctx<Auth>,
+	body: models::ActorBuildRetentionPolicy,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorSetBuildRetentionPolicyResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::BuildsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	ensure_with!(
+		body.keep_last.map_or(true, |x| x >= 0),
+		API_BAD_BODY,
+		error = "`keep_last` must be positive."
+	);
+	ensure_with!(
+		body.untagged_max_age_days.map_or(true, |x| x >= 0),
+		API_BAD_BODY,
+		error = "`untagged_max_age_days` must be positive."
+	);
+	let keep_last = body.keep_last.map(|x| x as u32);
+	let untagged_max_age_days = body.untagged_max_age_days.map(|x| x as u32);
+
+	// Clearing all rules disables garbage collection
+	let policy = if keep_last.is_none() && untagged_max_age_days.is_none() {
+		None
+	} else {
+		Some(build::types::RetentionPolicy {
+			keep_last,
+			untagged_max_age: untagged_max_age_days.map(|days| util::duration::days(days.into())),
+		})
+	};
+
+	ctx.op(build::ops::retention::set::Input { env_id, policy })
+		.await?;
+
+	Ok(models::ActorSetBuildRetentionPolicyResponse {})
+}
+
+// MARK: GET /builds/retention/dry-run
+pub async fn retention_dry_run(
+	ctx: Ctx<Auth>,
+	_watch_index: WatchIndexQuery,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorBuildRetentionDryRunResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::BuildsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let plan_res = ctx
+		.op(build::ops::retention::plan::Input { env_id })
+		.await?;
+
+	Ok(models::ActorBuildRetentionDryRunResponse {
+		builds: plan_res
+			.collect
+			.into_iter()
+			.map(|x| {
+				Ok(models::ActorBuildRetentionDryRunBuild {
+					id: x.build.build_id,
+					name: x.build.display_name,
+					created_at: timestamp::to_string(x.build.create_ts)?,
+					tags: x.build.tags,
+					reason: match x.reason {
+						build::types::RetentionReason::ExceedsKeepLast => {
+							models::ActorBuildRetentionReason::ExceedsKeepLast
+						}
+						build::types::RetentionReason::UntaggedExpired => {
+							models::ActorBuildRetentionReason::UntaggedExpired
+						}
+					},
+				})
+			})
+			.collect::<GlobalResult<Vec<_>>>()?,
+		kept_count: plan_res.kept_count.try_into()?,
+	})
+}
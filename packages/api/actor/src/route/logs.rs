@@ -4,9 +4,12 @@ use api_helper::{
 };
 use proto::backend::{self, pkg::*};
 use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
+use tivet_convert::ApiInto;
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use crate::{
 	assert,
@@ -17,11 +20,18 @@ use crate::{
 use super::GlobalQuery;
 
 // MARK: GET /actors/{}/logs
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetActorLogsQuery {
 	#[serde(flatten)]
 	pub global: GlobalQuery,
+	#[schemars(with = "String")]
 	pub stream: models::CloudGamesLogStream,
+	/// Only return lines with this level.
+	pub level: Option<String>,
+	/// Only return lines that have all of these fields.
+	pub fields_json: Option<String>,
+	/// Only return lines that contain this text, case insensitive.
+	pub search: Option<String>,
 }
 
 pub async fn get_logs(
@@ -37,6 +47,7 @@ pub async fn get_logs(
 			CheckOpts {
 				query: &query.global,
 				allow_service_token: false,
+				scope: Some(EnvServiceScope::LogsRead),
 				opt_auth: false,
 			},
 		)
@@ -51,6 +62,13 @@ pub async fn get_logs(
 		models::CloudGamesLogStream::StdErr => backend::job::log::StreamType::StdErr,
 	};
 
+	let fields = parse_fields(query.fields_json.as_deref())?;
+	// Levels are stored lowercase
+	let level = query
+		.level
+		.map(|level| level.to_lowercase())
+		.unwrap_or_default();
+
 	// Timestamp to start the query at
 	let before_nts = util::timestamp::now() * 1_000_000;
 
@@ -73,6 +91,9 @@ pub async fn get_logs(
 				stream_type: stream_type as i32,
 				count: 64,
 				order_asc: false,
+				level: level.clone(),
+				fields: fields.clone(),
+				search: query.search.clone().unwrap_or_default(),
 				query: Some(ds_log::read::request::Query::AfterNts(anchor))
 
 			})
@@ -111,6 +132,9 @@ pub async fn get_logs(
 			stream_type: stream_type as i32,
 			count: 256,
 			order_asc: false,
+			level: level.clone(),
+			fields: fields.clone(),
+			search: query.search.clone().unwrap_or_default(),
 			query: Some(ds_log::read::request::Query::BeforeNts(before_nts)),
 		})
 		.await?
@@ -128,15 +152,29 @@ pub async fn get_logs(
 		.map(|x| x.nts / 1_000_000)
 		.map(util::timestamp::to_string)
 		.collect::<Result<Vec<_>, _>>()?;
+	let mut levels = logs_res
+		.entries
+		.iter()
+		.map(|x| x.level.clone())
+		.collect::<Vec<_>>();
+	let mut fields = logs_res
+		.entries
+		.iter()
+		.map(|x| x.fields.clone())
+		.collect::<Vec<_>>();
 
 	// Order desc
 	lines.reverse();
 	timestamps.reverse();
+	levels.reverse();
+	fields.reverse();
 
 	let watch_nts = logs_res.entries.first().map_or(before_nts, |x| x.nts);
 	Ok(models::ActorGetActorLogsResponse {
 		lines,
 		timestamps,
+		levels,
+		fields,
 		watch: WatchResponse::new_as_model(watch_nts),
 	})
 }
@@ -157,6 +195,9 @@ pub async fn get_logs_deprecated(
 		GetActorLogsQuery {
 			global,
 			stream: query.stream,
+			level: None,
+			fields_json: None,
+			search: None,
 		},
 	)
 	.await?;
@@ -167,6 +208,142 @@ pub async fn get_logs_deprecated(
 	})
 }
 
+// MARK: GET /logs
+/// How many actors' logs to search at once.
+const SEARCH_BATCH_SIZE: usize = 1_000;
+/// How many entries to return.
+const SEARCH_LIMIT: usize = 256;
+/// How long ClickHouse keeps actor logs.
+const LOG_RETENTION: i64 = util::duration::days(3);
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchLogsQuery {
+	#[serde(flatten)]
+	pub global: GlobalQuery,
+	/// Searches both streams if not set.
+	#[schemars(with = "Option<String>")]
+	pub stream: Option<models::ActorLogStream>,
+	/// Only search the logs of actors with these tags.
+	pub tags_json: Option<String>,
+	/// Only return entries with this level.
+	pub level: Option<String>,
+	/// Only return entries that have all of these fields.
+	pub fields_json: Option<String>,
+	/// Only return entries that contain this text, case insensitive.
+	pub search: Option<String>,
+	/// Only return entries logged before this timestamp (in milliseconds). Used to paginate.
+	pub before: Option<i64>,
+}
+
+pub async fn search(
+	ctx: Ctx<Auth>,
+	_watch_index: WatchIndexQuery,
+	query: SearchLogsQuery,
+) -> GlobalResult<models::ActorSearchLogsResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query.global,
+				allow_service_token: false,
+				scope: Some(EnvServiceScope::LogsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let tags = unwrap_with!(
+		query
+			.tags_json
+			.as_deref()
+			.map_or(Ok(HashMap::new()), serde_json::from_str)
+			.ok(),
+		API_BAD_QUERY_PARAMETER,
+		parameter = "tags_json",
+		error = "must be `Map<String, String>`"
+	);
+	let Some(tags) = ctx.auth().restrict_actor_tags(tags) else {
+		return Ok(models::ActorSearchLogsResponse {
+			entries: Vec::new(),
+		});
+	};
+	let fields = parse_fields(query.fields_json.as_deref())?;
+
+	// Levels are stored lowercase
+	let level = query.level.map(|level| level.to_lowercase());
+	let before_nts = query.before.unwrap_or_else(util::timestamp::now) * 1_000_000;
+
+	// Actors destroyed before this no longer have any logs
+	let destroyed_after_ts = util::timestamp::now() - LOG_RETENTION;
+
+	// Search in batches of servers and keep the newest entries across all batches
+	let mut entries = Vec::new();
+	let mut cursor = None;
+	loop {
+		let list_res = ctx
+			.op(ds::ops::server::list_for_env::Input {
+				env_id,
+				tags: tags.clone(),
+				include_destroyed: true,
+				destroyed_after_ts: Some(destroyed_after_ts),
+				cursor,
+				limit: SEARCH_BATCH_SIZE,
+			})
+			.await?;
+
+		let fetched_count = list_res.server_ids.len();
+		cursor = list_res.server_ids.last().cloned();
+
+		let search_res = ctx
+			.op(ds::ops::log::search::Input {
+				server_ids: list_res.server_ids,
+				stream: query.stream.map(ApiInto::api_into),
+				level: level.clone(),
+				fields: fields.clone(),
+				text: query.search.clone(),
+				before_nts,
+				limit: SEARCH_LIMIT,
+			})
+			.await?;
+
+		entries.extend(search_res.entries);
+		entries.sort_by_key(|entry| std::cmp::Reverse(entry.nts));
+		entries.truncate(SEARCH_LIMIT);
+
+		if fetched_count < SEARCH_BATCH_SIZE {
+			break;
+		}
+	}
+
+	let entries = entries
+		.into_iter()
+		.map(|entry| {
+			GlobalResult::Ok(models::ActorLogEntry {
+				actor: entry.server_id,
+				stream: entry.stream.api_into(),
+				timestamp: util::timestamp::to_string(entry.nts / 1_000_000)?,
+				message: base64::encode(&entry.message),
+				level: entry.level,
+				fields: Some(entry.fields),
+			})
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(models::ActorSearchLogsResponse { entries })
+}
+
+fn parse_fields(fields_json: Option<&str>) -> GlobalResult<HashMap<String, String>> {
+	Ok(unwrap_with!(
+		fields_json
+			.map_or(Ok(HashMap::new()), serde_json::from_str)
+			.ok(),
+		API_BAD_QUERY_PARAMETER,
+		parameter = "fields_json",
+		error = "must be `Map<String, String>`"
+	))
+}
+
 use metrics::{increment_counter, histogram!};
 
 let start_ts = std::time::Instant::now();
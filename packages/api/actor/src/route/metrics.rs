@@ -0,0 +1,114 @@
+use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
+use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
+use tivet_operation::prelude::*;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::auth::{Auth, CheckOpts, CheckOutput};
+
+use super::GlobalQuery;
+
+/// Range returned when no start is given.
+const DEFAULT_RANGE: i64 = util::duration::hours(1);
+/// Longest range that can be queried at once.
+const MAX_RANGE: i64 = util::duration::days(7);
+
+// MARK: GET /actors/{}/metrics
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MetricsQuery {
+	#[serde(flatten)]
+	pub global: GlobalQuery,
+	/// Unix timestamp in milliseconds. Defaults to one hour before `end`.
+	pub start: Option<i64>,
+	/// Unix timestamp in milliseconds. Defaults to now.
+	pub end: Option<i64>,
+}
+
+pub async fn get_metrics(
+	ctx: Ctx<Auth>,
+	actor_id: Uuid,
+	_watch_index: WatchIndexQuery,
+	query: MetricsQuery,
+) -> GlobalResult<models::ActorGetActorMetricsResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query.global,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let end_ts = query.end.unwrap_or_else(util::timestamp::now);
+	let start_ts = query.start.unwrap_or(end_ts - DEFAULT_RANGE);
+	ensure_with!(
+		start_ts < end_ts,
+		API_BAD_QUERY_PARAMETER,
+		parameter = "start",
+		error = "must be before `end`"
+	);
+	ensure_with!(
+		end_ts - start_ts <= MAX_RANGE,
+		API_BAD_QUERY_PARAMETER,
+		parameter = "start",
+		error = "range must not be longer than 7 days"
+	);
+
+	// Get the server
+	let servers_res = ctx
+		.op(ds::ops::server::get::Input {
+			server_ids: vec![actor_id],
+			endpoint_type: None,
+		})
+		.await?;
+	let server = unwrap_with!(servers_res.servers.first(), ACTOR_NOT_FOUND);
+
+	// Validate token can access server
+	ensure_with!(server.env_id == env_id, ACTOR_NOT_FOUND);
+	ctx.auth().check_actor(server.datacenter_id, &server.tags)?;
+
+	let metrics_res = ctx
+		.op(pegboard::ops::actor::metrics_get::Input {
+			owner_id: actor_id,
+			start_ts,
+			end_ts,
+		})
+		.await?;
+
+	let metrics = metrics_res
+		.metrics
+		.into_iter()
+		.map(|window| {
+			let metrics = window.metrics;
+
+			GlobalResult::Ok(models::ActorActorMetrics {
+				start_ts: util::timestamp::to_string(metrics.start_ts)?,
+				end_ts: util::timestamp::to_string(metrics.end_ts)?,
+				cpu_avg: metrics.cpu_avg.try_into()?,
+				cpu_max: metrics.cpu_max.try_into()?,
+				memory_avg: metrics.memory_avg.try_into()?,
+				memory_max: metrics.memory_max.try_into()?,
+				network_rx_bytes: metrics
+					.network_rx_bytes
+					.map(TryInto::try_into)
+					.transpose()?,
+				network_tx_bytes: metrics
+					.network_tx_bytes
+					.map(TryInto::try_into)
+					.transpose()?,
+				disk_read_bytes: metrics.disk_read_bytes.map(TryInto::try_into).transpose()?,
+				disk_write_bytes: metrics
+					.disk_write_bytes
+					.map(TryInto::try_into)
+					.transpose()?,
+			})
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(models::ActorGetActorMetricsResponse { metrics })
+}
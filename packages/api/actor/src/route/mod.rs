@@ -2,17 +2,22 @@ use api_helper::{define_router, util::CorsConfigBuilder};
 use hyper::{Body, Request, Response};
 use tivet_api::models;
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use uuid::Uuid;
 
 pub mod actors;
 pub mod builds;
 pub mod logs;
+pub mod metrics;
 pub mod regions;
-pub mod metrics;   // new module example
+pub mod replica_sets;
+pub mod schedules;
+pub mod secrets;
+pub mod volumes;
 pub mod health;    // new module example
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct GlobalQuery {
     /// Slug of the project.
     ///
@@ -78,6 +83,7 @@ define_router! {
                 query: actors::GlobalEndpointTypeQuery,
                 body: models::ActorCreateActorRequest,
                 opt_auth: true,
+                idempotent: true,
                 rate_limit: {
                     buckets: [
                         { count: 1_000, bucket: duration::minutes(1) },
@@ -91,6 +97,7 @@ define_router! {
                 query: GlobalQuery,
                 body: models::ActorUpgradeAllActorsRequest,
                 opt_auth: true,
+                idempotent: true,
                 rate_limit: {
                     buckets: [
                         { count: 1_000, bucket: duration::minutes(1) },
@@ -140,6 +147,203 @@ define_router! {
             ),
         },
 
+        "actors" / Uuid / "metrics": {
+            GET: metrics::get_metrics(
+                query: metrics::MetricsQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 10_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        // MARK: Volumes
+        "volumes": {
+            GET: volumes::list_volumes(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 60_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        "volumes" / Uuid: {
+            DELETE: volumes::delete_volume(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        "volumes" / Uuid / "snapshot": {
+            POST: volumes::snapshot_volume(
+                query: GlobalQuery,
+                body: serde_json::Value,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        // MARK: Secrets
+        "secrets": {
+            GET: secrets::list_secrets(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 60_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        "secrets" / String: {
+            PUT: secrets::put(
+                query: GlobalQuery,
+                body: models::ActorPutSecretRequest,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+            DELETE: secrets::delete(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        // MARK: Replica sets
+        "replica-sets": {
+            GET: replica_sets::list_replica_sets(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 60_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+            POST: replica_sets::create(
+                query: GlobalQuery,
+                body: models::ActorCreateReplicaSetRequest,
+                opt_auth: true,
+                idempotent: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        "replica-sets" / Uuid: {
+            GET: replica_sets::get(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 60_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+            PATCH: replica_sets::update(
+                query: GlobalQuery,
+                body: models::ActorUpdateReplicaSetRequest,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+            DELETE: replica_sets::delete(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        // MARK: Schedules
+        "schedules": {
+            GET: schedules::list_schedules(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 60_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+            POST: schedules::create(
+                query: GlobalQuery,
+                body: models::ActorCreateScheduleRequest,
+                opt_auth: true,
+                idempotent: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        "schedules" / Uuid: {
+            GET: schedules::get(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 60_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+            DELETE: schedules::delete(
+                query: GlobalQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 1_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
+        "schedules" / Uuid / "runs": {
+            GET: schedules::list_runs(
+                query: schedules::ListRunsQuery,
+                opt_auth: true,
+                rate_limit: {
+                    buckets: [
+                        { count: 60_000, bucket: duration::minutes(1) },
+                    ],
+                },
+            ),
+        },
+
         // MARK: Builds
         "builds": {
             GET: builds::list(
@@ -155,6 +359,7 @@ define_router! {
                 query: GlobalQuery,
                 body: models::ActorPrepareBuildRequest,
                 opt_auth: true,
+                idempotent: true,
                 rate_limit: {
                     buckets: [
                         { count: 1_000, bucket: duration::minutes(1) },
@@ -163,6 +368,25 @@ define_router! {
             ),
         },
 
+        "builds" / "retention": {
+            GET: builds::get_retention(
+                query: GlobalQuery,
+                opt_auth: true,
+            ),
+            PUT: builds::set_retention(
+                query: GlobalQuery,
+                body: models::ActorBuildRetentionPolicy,
+                opt_auth: true,
+            ),
+        },
+
+        "builds" / "retention" / "dry-run": {
+            GET: builds::retention_dry_run(
+                query: GlobalQuery,
+                opt_auth: true,
+            ),
+        },
+
         "builds" / Uuid: {
             GET: builds::get(
                 query: GlobalQuery,
@@ -215,19 +439,6 @@ define_router! {
             ),
         },
 
-        // MARK: Metrics (new)
-        "metrics": {
-            GET: metrics::get_metrics(
-                query: metrics::MetricsQuery,
-                opt_auth: true,
-                rate_limit: {
-                    buckets: [
-                        { count: 10_000, bucket: duration::minutes(1) },
-                    ],
-                },
-            ),
-        },
-
         // MARK: Health (new)
         "health": {
             GET: health::status(
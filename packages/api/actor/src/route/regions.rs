@@ -2,6 +2,7 @@ use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
 use proto::backend;
 use tivet_api::models;
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use uuid::Uuid;
 
@@ -25,6 +26,7 @@ pub async fn list(
             CheckOpts {
                 query: &query,
                 allow_service_token: true,
+                scope: None,
                 opt_auth: true,
             },
         )
@@ -80,6 +82,7 @@ pub async fn list_deprecated(
             CheckOpts {
                 query: &query,
                 allow_service_token: true,
+                scope: None,
                 opt_auth: true,
             },
         )
@@ -122,7 +125,7 @@ pub async fn list_deprecated(
 }
 
 // MARK: GET /regions/resolve
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ResolveQuery {
     #[serde(flatten)]
     global: GlobalQuery,
@@ -142,6 +145,7 @@ pub async fn resolve(
             CheckOpts {
                 query: &query.global,
                 allow_service_token: true,
+                scope: None,
                 opt_auth: true,
             },
         )
@@ -227,6 +231,7 @@ pub async fn details(
             CheckOpts {
                 query: &query.global,
                 allow_service_token: true,
+                scope: None,
                 opt_auth: true,
             },
         )
@@ -268,6 +273,7 @@ pub async fn health(
             CheckOpts {
                 query: &query,
                 allow_service_token: true,
+                scope: None,
                 opt_auth: true,
             },
         )
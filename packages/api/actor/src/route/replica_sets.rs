@@ -0,0 +1,312 @@
+use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
+use futures_util::StreamExt;
+use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
+use tivet_convert::ApiTryInto;
+use tivet_operation::prelude::*;
+use serde_json::json;
+
+use crate::auth::{Auth, CheckOpts, CheckOutput};
+
+use super::GlobalQuery;
+
+/// Max number of replicas in a single replica set.
+const MAX_REPLICAS: i32 = 256;
+
+// MARK: GET /replica-sets
+pub async fn list_replica_sets(
+	ctx: Ctx<Auth>,
+	_watch_index: WatchIndexQuery,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorListReplicaSetsResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let list_res = ctx
+		.op(ds::ops::replica_set::list_for_env::Input {
+			env_id,
+			include_deleted: false,
+		})
+		.await?;
+
+	let replica_sets_res = ctx
+		.op(ds::ops::replica_set::get::Input {
+			replica_set_ids: list_res.replica_set_ids,
+		})
+		.await?;
+
+	let replica_sets = replica_sets_res
+		.replica_sets
+		.into_iter()
+		.map(ApiTryInto::api_try_into)
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(models::ActorListReplicaSetsResponse { replica_sets })
+}
+
+// MARK: POST /replica-sets
+pub async fn create(
+	ctx: Ctx<Auth>,
+	body: models::ActorCreateReplicaSetRequest,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorCreateReplicaSetResponse> {
+	let CheckOutput { game_id, env_id } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let replicas = validate_replicas(body.replicas)?;
+	let rolling_update = resolve_rolling_update(
+		ds::types::RollingUpdatePolicy::default(),
+		body.rolling_update.map(|x| *x),
+	)?;
+
+	let mut actor = *body.actor;
+
+	ensure_with!(
+		actor.volumes.as_ref().map_or(true, Vec::is_empty),
+		API_BAD_BODY,
+		error = "`actor.volumes` is not supported for replica sets"
+	);
+
+	let build = super::actors::resolve_build(
+		&ctx,
+		game_id,
+		env_id,
+		actor.build.take(),
+		actor.build_tags.take().flatten(),
+	)
+	.await?;
+
+	let template = super::actors::resolve_actor_template(&ctx, game_id, build.kind, actor).await?;
+
+	let replica_set_id = Uuid::new_v4();
+
+	let mut create_sub = ctx
+		.subscribe::<ds::workflows::replica_set::CreateComplete>(("replica_set_id", replica_set_id))
+		.await?;
+
+	ctx.workflow(ds::workflows::replica_set::Input {
+		replica_set_id,
+		env_id,
+		template,
+		image_id: build.build_id,
+		replicas,
+		rolling_update,
+	})
+	.tag("replica_set_id", replica_set_id)
+	.dispatch()
+	.await?;
+
+	create_sub.next().await?;
+
+	let replica_set = get_replica_set(&ctx, replica_set_id, env_id).await?;
+
+	Ok(models::ActorCreateReplicaSetResponse {
+		replica_set: Box::new(replica_set.api_try_into()?),
+	})
+}
+
+// MARK: GET /replica-sets/{}
+pub async fn get(
+	ctx: Ctx<Auth>,
+	replica_set_id: Uuid,
+	_watch_index: WatchIndexQuery,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorGetReplicaSetResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let replica_set = get_replica_set(&ctx, replica_set_id, env_id).await?;
+
+	Ok(models::ActorGetReplicaSetResponse {
+		replica_set: Box::new(replica_set.api_try_into()?),
+	})
+}
+
+// MARK: PATCH /replica-sets/{}
+pub async fn update(
+	ctx: Ctx<Auth>,
+	replica_set_id: Uuid,
+	body: models::ActorUpdateReplicaSetRequest,
+	query: GlobalQuery,
+) -> GlobalResult<serde_json::Value> {
+	let CheckOutput { game_id, env_id } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let replica_set = get_replica_set(&ctx, replica_set_id, env_id).await?;
+
+	let replicas = body.replicas.map(validate_replicas).transpose()?;
+	let rolling_update = body
+		.rolling_update
+		.map(|x| resolve_rolling_update(replica_set.rolling_update, Some(*x)))
+		.transpose()?;
+
+	// Changing the build starts a rolling upgrade
+	let build_tags = body.build_tags.flatten();
+	let image_id = if body.build.is_some() || build_tags.is_some() {
+		let build =
+			super::actors::resolve_build(&ctx, game_id, env_id, body.build, build_tags).await?;
+
+		Some(build.build_id)
+	} else {
+		None
+	};
+
+	ctx.signal(ds::workflows::replica_set::Update {
+		image_id,
+		replicas,
+		rolling_update,
+	})
+	.tag("replica_set_id", replica_set_id)
+	.send()
+	.await?;
+
+	Ok(json!({}))
+}
+
+// MARK: DELETE /replica-sets/{}
+pub async fn delete(
+	ctx: Ctx<Auth>,
+	replica_set_id: Uuid,
+	query: GlobalQuery,
+) -> GlobalResult<serde_json::Value> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	get_replica_set(&ctx, replica_set_id, env_id).await?;
+
+	ctx.signal(ds::workflows::replica_set::Delete {})
+		.tag("replica_set_id", replica_set_id)
+		.send()
+		.await?;
+
+	Ok(json!({}))
+}
+
+fn validate_replicas(replicas: i32) -> GlobalResult<u32> {
+	ensure_with!(
+		(0..=MAX_REPLICAS).contains(&replicas),
+		API_BAD_BODY,
+		error = format!("`replicas` must be between 0 and {MAX_REPLICAS}")
+	);
+
+	Ok(replicas.try_into()?)
+}
+
+/// Applies the set fields of the request on top of the given policy.
+fn resolve_rolling_update(
+	mut policy: ds::types::RollingUpdatePolicy,
+	req: Option<models::ActorReplicaSetRollingUpdate>,
+) -> GlobalResult<ds::types::RollingUpdatePolicy> {
+	let Some(req) = req else {
+		return Ok(policy);
+	};
+
+	if let Some(max_surge) = req.max_surge {
+		ensure_with!(
+			(0..=MAX_REPLICAS).contains(&max_surge),
+			API_BAD_BODY,
+			error = format!("`rolling_update.max_surge` must be between 0 and {MAX_REPLICAS}")
+		);
+		policy.max_surge = max_surge.try_into()?;
+	}
+	if let Some(max_unavailable) = req.max_unavailable {
+		ensure_with!(
+			(0..=MAX_REPLICAS).contains(&max_unavailable),
+			API_BAD_BODY,
+			error =
+				format!("`rolling_update.max_unavailable` must be between 0 and {MAX_REPLICAS}")
+		);
+		policy.max_unavailable = max_unavailable.try_into()?;
+	}
+	if let Some(min_ready) = req.min_ready {
+		ensure_with!(
+			min_ready >= 0,
+			API_BAD_BODY,
+			error = "`rolling_update.min_ready` cannot be negative"
+		);
+		policy.min_ready_ms = min_ready;
+	}
+
+	// Rollouts could never make progress
+	ensure_with!(
+		policy.max_surge != 0 || policy.max_unavailable != 0,
+		API_BAD_BODY,
+		error = "`rolling_update.max_surge` and `rolling_update.max_unavailable` cannot both be 0"
+	);
+
+	Ok(policy)
+}
+
+/// Fetches a replica set that is not deleted and belongs to the given environment.
+async fn get_replica_set(
+	ctx: &Ctx<Auth>,
+	replica_set_id: Uuid,
+	env_id: Uuid,
+) -> GlobalResult<ds::types::ReplicaSet> {
+	let replica_sets_res = ctx
+		.op(ds::ops::replica_set::get::Input {
+			replica_set_ids: vec![replica_set_id],
+		})
+		.await?;
+	let replica_set = unwrap_with!(
+		replica_sets_res.replica_sets.into_iter().next(),
+		ACTOR_REPLICA_SET_NOT_FOUND
+	);
+
+	ensure_with!(
+		replica_set.env_id == env_id && replica_set.delete_ts.is_none(),
+		ACTOR_REPLICA_SET_NOT_FOUND
+	);
+
+	Ok(replica_set)
+}
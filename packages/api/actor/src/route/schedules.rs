@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
+use futures_util::StreamExt;
+use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
+use tivet_convert::{ApiInto, ApiTryInto};
+use tivet_operation::prelude::*;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::{Auth, CheckOpts, CheckOutput};
+
+use super::GlobalQuery;
+
+// MARK: GET /schedules
+pub async fn list_schedules(
+	ctx: Ctx<Auth>,
+	_watch_index: WatchIndexQuery,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorListSchedulesResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let list_res = ctx
+		.op(ds::ops::schedule::list_for_env::Input {
+			env_id,
+			include_deleted: false,
+		})
+		.await?;
+
+	let schedules_res = ctx
+		.op(ds::ops::schedule::get::Input {
+			schedule_ids: list_res.schedule_ids,
+		})
+		.await?;
+
+	let schedules = schedules_res
+		.schedules
+		.into_iter()
+		.filter(|s| {
+			ctx.auth()
+				.can_access_actor(s.template.datacenter_id, &s.template.tags)
+		})
+		.map(ApiTryInto::api_try_into)
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(models::ActorListSchedulesResponse { schedules })
+}
+
+// MARK: POST /schedules
+pub async fn create(
+	ctx: Ctx<Auth>,
+	body: models::ActorCreateScheduleRequest,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorCreateScheduleResponse> {
+	let CheckOutput { game_id, env_id } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	if let Err(err) = ds::workflows::schedule::parse_cron(&body.cron) {
+		bail_with!(API_BAD_BODY, error = format!("`cron`: {err}"));
+	}
+
+	let mut actor = *body.actor;
+
+	// Builds are resolved at each tick so that runs pick up newly tagged builds
+	ensure_with!(
+		actor.build.is_none(),
+		API_BAD_BODY,
+		error = "`actor.build` is not supported for schedules, use `actor.build_tags`"
+	);
+	ensure_with!(
+		actor.volumes.as_ref().map_or(true, Vec::is_empty),
+		API_BAD_BODY,
+		error = "`actor.volumes` is not supported for schedules"
+	);
+	let build_tags = unwrap_with!(
+		actor.build_tags.take().flatten(),
+		API_BAD_BODY,
+		error = "`actor.build_tags` is required"
+	);
+
+	// Actors created by the schedule carry the tag restrictions of a scoped service token
+	let tags = unwrap_with!(
+		serde_json::from_value::<HashMap<String, String>>(actor.tags.take().unwrap_or_default())
+			.ok(),
+		API_BAD_BODY,
+		error = "`actor.tags` must be `Map<String, String>`"
+	);
+	let tags = unwrap_with!(
+		ctx.auth().restrict_actor_tags(tags),
+		API_FORBIDDEN,
+		reason = "Service token cannot access this actor."
+	);
+	actor.tags = Some(serde_json::to_value(tags)?);
+
+	// Validates the build tags and that a build exists for them
+	let build =
+		super::actors::resolve_build(&ctx, game_id, env_id, None, Some(build_tags.clone())).await?;
+	let build_tags = serde_json::from_value::<HashMap<String, String>>(build_tags)?;
+
+	let template = super::actors::resolve_actor_template(&ctx, game_id, build.kind, actor).await?;
+
+	let schedule_id = Uuid::new_v4();
+
+	let mut create_sub = ctx
+		.subscribe::<ds::workflows::schedule::CreateComplete>(("schedule_id", schedule_id))
+		.await?;
+
+	ctx.workflow(ds::workflows::schedule::Input {
+		schedule_id,
+		env_id,
+		cron: body.cron,
+		build_tags,
+		template,
+		concurrency_policy: body
+			.concurrency_policy
+			.map(ApiInto::api_into)
+			.unwrap_or_default(),
+	})
+	.tag("schedule_id", schedule_id)
+	.dispatch()
+	.await?;
+
+	create_sub.next().await?;
+
+	let schedule = get_schedule(&ctx, schedule_id, env_id).await?;
+
+	Ok(models::ActorCreateScheduleResponse {
+		schedule: Box::new(schedule.api_try_into()?),
+	})
+}
+
+// MARK: GET /schedules/{}
+pub async fn get(
+	ctx: Ctx<Auth>,
+	schedule_id: Uuid,
+	_watch_index: WatchIndexQuery,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorGetScheduleResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let schedule = get_schedule(&ctx, schedule_id, env_id).await?;
+
+	Ok(models::ActorGetScheduleResponse {
+		schedule: Box::new(schedule.api_try_into()?),
+	})
+}
+
+// MARK: DELETE /schedules/{}
+pub async fn delete(
+	ctx: Ctx<Auth>,
+	schedule_id: Uuid,
+	query: GlobalQuery,
+) -> GlobalResult<serde_json::Value> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	get_schedule(&ctx, schedule_id, env_id).await?;
+
+	// Actors that were already created by the schedule keep running
+	ctx.signal(ds::workflows::schedule::Delete {})
+		.tag("schedule_id", schedule_id)
+		.send()
+		.await?;
+
+	Ok(json!({}))
+}
+
+// MARK: GET /schedules/{}/runs
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ListRunsQuery {
+	#[serde(flatten)]
+	global: GlobalQuery,
+	/// Only returns runs scheduled before this timestamp, in milliseconds. Used for pagination.
+	before: Option<i64>,
+}
+
+pub async fn list_runs(
+	ctx: Ctx<Auth>,
+	schedule_id: Uuid,
+	_watch_index: WatchIndexQuery,
+	query: ListRunsQuery,
+) -> GlobalResult<models::ActorListScheduleRunsResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query.global,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	// Run history stays readable after the schedule is deleted
+	let schedules_res = ctx
+		.op(ds::ops::schedule::get::Input {
+			schedule_ids: vec![schedule_id],
+		})
+		.await?;
+	let schedule = unwrap_with!(
+		schedules_res.schedules.into_iter().next(),
+		ACTOR_SCHEDULE_NOT_FOUND
+	);
+	ensure_with!(schedule.env_id == env_id, ACTOR_SCHEDULE_NOT_FOUND);
+	ctx.auth()
+		.check_actor(schedule.template.datacenter_id, &schedule.template.tags)?;
+
+	let runs_res = ctx
+		.op(ds::ops::schedule::run_list::Input {
+			schedule_id,
+			before_ts: query.before,
+			limit: 64,
+		})
+		.await?;
+
+	let runs = runs_res
+		.runs
+		.into_iter()
+		.map(ApiTryInto::api_try_into)
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(models::ActorListScheduleRunsResponse { runs })
+}
+
+/// Fetches a schedule that is not deleted, belongs to the given environment and can be accessed by the
+/// token.
+async fn get_schedule(
+	ctx: &Ctx<Auth>,
+	schedule_id: Uuid,
+	env_id: Uuid,
+) -> GlobalResult<ds::types::Schedule> {
+	let schedules_res = ctx
+		.op(ds::ops::schedule::get::Input {
+			schedule_ids: vec![schedule_id],
+		})
+		.await?;
+	let schedule = unwrap_with!(
+		schedules_res.schedules.into_iter().next(),
+		ACTOR_SCHEDULE_NOT_FOUND
+	);
+
+	ensure_with!(
+		schedule.env_id == env_id && schedule.delete_ts.is_none(),
+		ACTOR_SCHEDULE_NOT_FOUND
+	);
+	ctx.auth()
+		.check_actor(schedule.template.datacenter_id, &schedule.template.tags)?;
+
+	Ok(schedule)
+}
@@ -0,0 +1,135 @@
+use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
+use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
+use tivet_config::secret::Secret;
+use tivet_convert::ApiTryInto;
+use tivet_operation::prelude::*;
+use serde_json::json;
+
+use crate::auth::{Auth, CheckOpts, CheckOutput};
+
+use super::GlobalQuery;
+
+/// Max size of a secret value.
+const MAX_VALUE_LEN: usize = 4096;
+
+// MARK: GET /secrets
+pub async fn list_secrets(
+	ctx: Ctx<Auth>,
+	_watch_index: WatchIndexQuery,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorListSecretsResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let list_res = ctx
+		.op(ds::ops::secret::list_for_env::Input { env_id })
+		.await?;
+
+	let secrets = list_res
+		.secrets
+		.into_iter()
+		.map(ApiTryInto::api_try_into)
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(models::ActorListSecretsResponse { secrets })
+}
+
+// MARK: PUT /secrets/{}
+pub async fn put(
+	ctx: Ctx<Auth>,
+	name: String,
+	body: models::ActorPutSecretRequest,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorPutSecretResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	ensure_with!(
+		util::check::ident_lenient(&name),
+		API_BAD_BODY,
+		error = "invalid secret name"
+	);
+	ensure_with!(
+		body.value.len() <= MAX_VALUE_LEN,
+		API_BAD_BODY,
+		error = format!("`value` too large (max {MAX_VALUE_LEN} bytes)")
+	);
+
+	// Rotates the value if the secret already exists
+	ctx.op(ds::ops::secret::upsert::Input {
+		env_id,
+		name: name.clone(),
+		value: Secret::new(body.value),
+	})
+	.await?;
+
+	let secret = get_secret(&ctx, &name, env_id).await?;
+
+	Ok(models::ActorPutSecretResponse {
+		secret: Box::new(secret.api_try_into()?),
+	})
+}
+
+// MARK: DELETE /secrets/{}
+pub async fn delete(
+	ctx: Ctx<Auth>,
+	name: String,
+	query: GlobalQuery,
+) -> GlobalResult<serde_json::Value> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let delete_res = ctx
+		.op(ds::ops::secret::delete::Input { env_id, name })
+		.await?;
+	ensure_with!(delete_res.deleted, ACTOR_SECRET_NOT_FOUND);
+
+	Ok(json!({}))
+}
+
+/// Fetches the metadata of a secret in the given environment.
+async fn get_secret(ctx: &Ctx<Auth>, name: &str, env_id: Uuid) -> GlobalResult<ds::types::Secret> {
+	let list_res = ctx
+		.op(ds::ops::secret::list_for_env::Input { env_id })
+		.await?;
+	let secret = unwrap_with!(
+		list_res
+			.secrets
+			.into_iter()
+			.find(|secret| secret.name == name),
+		ACTOR_SECRET_NOT_FOUND
+	);
+
+	Ok(secret)
+}
@@ -0,0 +1,144 @@
+use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
+use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
+use tivet_convert::ApiTryInto;
+use tivet_operation::prelude::*;
+use serde_json::json;
+
+use crate::auth::{Auth, CheckOpts, CheckOutput};
+
+use super::GlobalQuery;
+
+// MARK: GET /volumes
+pub async fn list_volumes(
+	ctx: Ctx<Auth>,
+	_watch_index: WatchIndexQuery,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorListVolumesResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsRead),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let list_res = ctx
+		.op(ds::ops::volume::list_for_env::Input {
+			env_id,
+			include_deleted: false,
+		})
+		.await?;
+
+	let volumes_res = ctx
+		.op(ds::ops::volume::get::Input {
+			volume_ids: list_res.volume_ids,
+		})
+		.await?;
+
+	let volumes = volumes_res
+		.volumes
+		.into_iter()
+		.map(ApiTryInto::api_try_into)
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(models::ActorListVolumesResponse { volumes })
+}
+
+// MARK: POST /volumes/{}/snapshot
+pub async fn snapshot_volume(
+	ctx: Ctx<Auth>,
+	volume_id: Uuid,
+	_body: serde_json::Value,
+	query: GlobalQuery,
+) -> GlobalResult<models::ActorSnapshotVolumeResponse> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let volume = get_volume(&ctx, volume_id, env_id).await?;
+
+	// The client only uploads the volume once the actor using it has exited
+	ensure_with!(volume.server_id.is_none(), ACTOR_VOLUME_IN_USE);
+
+	let snapshot_id = Uuid::new_v4();
+
+	ctx.workflow(ds::workflows::volume::snapshot::Input {
+		volume_id,
+		snapshot_id,
+		server_id: None,
+	})
+	.tag("snapshot_id", snapshot_id)
+	.dispatch()
+	.await?;
+
+	Ok(models::ActorSnapshotVolumeResponse { snapshot_id })
+}
+
+// MARK: DELETE /volumes/{}
+pub async fn delete_volume(
+	ctx: Ctx<Auth>,
+	volume_id: Uuid,
+	query: GlobalQuery,
+) -> GlobalResult<serde_json::Value> {
+	let CheckOutput { env_id, .. } = ctx
+		.auth()
+		.check(
+			ctx.op_ctx(),
+			CheckOpts {
+				query: &query,
+				allow_service_token: true,
+				scope: Some(EnvServiceScope::ActorsWrite),
+				opt_auth: false,
+			},
+		)
+		.await?;
+
+	let volume = get_volume(&ctx, volume_id, env_id).await?;
+	ensure_with!(volume.server_id.is_none(), ACTOR_VOLUME_IN_USE);
+
+	ctx.workflow(ds::workflows::volume::delete::Input { volume_id })
+		.tag("volume_id", volume_id)
+		.dispatch()
+		.await?;
+
+	Ok(json!({}))
+}
+
+/// Fetches a volume that is not deleted and belongs to the given environment.
+async fn get_volume(
+	ctx: &Ctx<Auth>,
+	volume_id: Uuid,
+	env_id: Uuid,
+) -> GlobalResult<ds::types::Volume> {
+	let volumes_res = ctx
+		.op(ds::ops::volume::get::Input {
+			volume_ids: vec![volume_id],
+		})
+		.await?;
+	let volume = unwrap_with!(
+		volumes_res.volumes.into_iter().next(),
+		ACTOR_VOLUME_NOT_FOUND
+	);
+
+	ensure_with!(
+		volume.env_id == env_id && volume.delete_ts.is_none(),
+		ACTOR_VOLUME_NOT_FOUND
+	);
+
+	Ok(volume)
+}
@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// Published spec that the SDKs are generated from.
+const SDK_SPEC: &str = include_str!("../../../../site/src/content/docs/api/spec.json");
+
+/// Generated document for every public router, regenerated by the `api-monolith-public` tests.
+const OPENAPI: &str = include_str!("../../../../site/src/content/docs/api/openapi.json");
+
+/// Collects every `$ref` in the document.
+fn collect_refs(value: &Value, refs: &mut HashSet<String>) {
+	match value {
+		Value::Object(map) => {
+			if let Some(Value::String(reference)) = map.get("$ref") {
+				refs.insert(reference.clone());
+			}
+			for value in map.values() {
+				collect_refs(value, refs);
+			}
+		}
+		Value::Array(values) => {
+			for value in values {
+				collect_refs(value, refs);
+			}
+		}
+		_ => {}
+	}
+}
+
+#[test]
+fn openapi_matches_sdk_spec() {
+	let document = api_actor::route::Router::openapi();
+	let sdk_spec = serde_json::from_str::<Value>(SDK_SPEC).expect("invalid sdk spec");
+
+	assert_eq!(document["openapi"], "3.1.0");
+
+	// Every referenced schema must exist in either the document or the published SDK spec
+	let mut refs = HashSet::new();
+	collect_refs(&document, &mut refs);
+	let mut missing = refs
+		.iter()
+		.filter_map(|reference| reference.strip_prefix("#/components/schemas/"))
+		.filter(|name| document["components"]["schemas"].get(*name).is_none())
+		.filter(|name| sdk_spec["definitions"].get(*name).is_none())
+		.collect::<Vec<_>>();
+	missing.sort();
+	assert!(
+		missing.is_empty(),
+		"schemas missing from the published sdk spec: {missing:?}"
+	);
+
+	// Operation IDs must be unique so the generated SDK methods don't collide
+	let mut operation_ids = HashSet::new();
+	for (path, path_item) in document["paths"].as_object().expect("missing paths") {
+		for (method, operation) in path_item.as_object().expect("invalid path item") {
+			let operation_id = operation["operationId"]
+				.as_str()
+				.expect("missing operation id");
+			assert!(
+				operation_ids.insert(operation_id.to_string()),
+				"duplicate operation id {operation_id} for {method} {path}"
+			);
+		}
+	}
+
+	// Core routes are documented
+	for (path, method) in [
+		("/actors", "get"),
+		("/actors", "post"),
+		("/actors/{actor_id}", "delete"),
+		("/builds", "get"),
+		("/regions", "get"),
+		("/secrets/{secret}", "put"),
+	] {
+		assert!(
+			document["paths"][path].get(method).is_some(),
+			"missing {method} {path}"
+		);
+	}
+}
+
+#[test]
+fn openapi_matches_checked_in_document() {
+	let document = api_actor::route::Router::openapi();
+	let checked_in = serde_json::from_str::<Value>(OPENAPI).expect("invalid checked in document");
+
+	// The actor router is mounted without a prefix
+	for (path, path_item) in document["paths"].as_object().expect("missing paths") {
+		assert_eq!(
+			&checked_in["paths"][path], path_item,
+			"{path} differs from the checked in document, regenerate it with `UPDATE_OPENAPI=1 cargo test -p api-monolith-public --test openapi`"
+		);
+	}
+}
+
+#[test]
+fn openapi_path_params_are_named() {
+	let document = api_actor::route::Router::openapi();
+
+	for (path, path_item) in document["paths"].as_object().expect("missing paths") {
+		assert!(!path.contains("{arg_"), "unnamed path param in {path}");
+
+		for (method, operation) in path_item.as_object().expect("invalid path item") {
+			// Every param in the template must be declared with the same name
+			let declared = operation["parameters"]
+				.as_array()
+				.expect("missing parameters")
+				.iter()
+				.filter(|param| param["in"] == "path")
+				.map(|param| format!("{{{}}}", param["name"].as_str().expect("missing name")))
+				.collect::<Vec<_>>();
+			let templated = path
+				.split('/')
+				.filter(|segment| segment.starts_with('{'))
+				.map(ToString::to_string)
+				.collect::<Vec<_>>();
+			assert_eq!(
+				templated, declared,
+				"path params mismatch for {method} {path}"
+			);
+		}
+	}
+
+	let params = document["paths"]["/actors/{actor_id}"]["get"]["parameters"]
+		.as_array()
+		.expect("missing parameters");
+	assert_eq!(params[0]["name"], "actor_id");
+	assert_eq!(params[0]["schema"]["format"], "uuid");
+}
+
+#[test]
+fn openapi_responses_have_schemas() {
+	let document = api_actor::route::Router::openapi();
+
+	for (path, path_item) in document["paths"].as_object().expect("missing paths") {
+		for (method, operation) in path_item.as_object().expect("invalid path item") {
+			assert!(
+				operation["responses"]["200"]["content"]["application/json"]
+					.get("schema")
+					.is_some(),
+				"missing response schema for {method} {path}"
+			);
+		}
+	}
+
+	assert_eq!(
+		document["paths"]["/actors/{actor_id}"]["get"]["responses"]["200"]["content"]
+			["application/json"]["schema"]["$ref"],
+		"#/components/schemas/ActorGetActorResponse"
+	);
+}
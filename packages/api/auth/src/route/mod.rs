@@ -17,6 +17,9 @@ define_router! {
 				opt_auth: true,
 			),
 		},
+		".well-known" / "jwks.json": {
+			GET: tokens::jwks(opt_auth: true),
+		},
 		"identity" / "email" / "start-verification": {
 			POST: identity::start(
 				body: models::AuthIdentityStartEmailVerificationRequest,
@@ -1,4 +1,4 @@
-use api_helper::ctx::Ctx;
+use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
 use http::response::Builder;
 use proto::backend::{self, pkg::*};
 use tivet_auth_server::models;
@@ -32,9 +32,9 @@ pub async fn identity(
 	// Prevent getting refresh token on logout, makes sure only a new guest token is returned
 	let refresh_token = if !body.logout {
 		// Find refresh token
-		let jwt_key_public = &ctx.config().server()?.jwt.public;
+		let jwt_key = &ctx.config().server()?.jwt;
 		let refresh_token_cookie = cookies.iter().flat_map(|x| x.iter()).filter(|&(k, _)| k == USER_REFRESH_TOKEN_COOKIE).filter_map(|(_k, v)| {
-			match tivet_claims::decode(jwt_key_public,v) {
+			match tivet_claims::decode(jwt_key,v) {
 				Ok(x) => Some((x, v)),
 				Err(err) => {
 					// Gracefully ignore cookies that can't be decoded.
@@ -132,7 +132,7 @@ pub async fn identity(
 
 	// Decode user token to extract user ID. We do this on the server since it adds a
 	// lot of extra complexity to the client to decode this token.
-	let user_claims = tivet_claims::decode(&ctx.config().server()?.jwt.public, &token)??;
+	let user_claims = tivet_claims::decode(&ctx.config().server()?.jwt, &token)??;
 	let user_ent = user_claims.as_user()?;
 
 	// Verify user is not deleted
@@ -320,6 +320,15 @@ fn enrich_response_for_debug(response: &mut Builder, token: &str) {
 		.insert("x-debug-token", HeaderValue::from_str(&dbg_token).unwrap());
 }
 
+// MARK: GET /.well-known/jwks.json
+/// Public keys used to verify tokens issued by this cluster.
+pub async fn jwks(
+	ctx: Ctx<Auth>,
+	_watch_index: WatchIndexQuery,
+) -> GlobalResult<tivet_claims::jwks::Jwks> {
+	tivet_claims::jwks::build(&ctx.config().server()?.jwt)
+}
+
 // Trace claims parsing for debugging
 #[cfg(debug_assertions)]
 fn trace_claims_info(claims: &tivet_claims::claims::Claims) {
@@ -7,6 +7,7 @@ use proto::backend::pkg::*;
 use tivet_api::models;
 use tivet_claims::ClaimsDecode;
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::auth::Auth;
@@ -28,7 +29,7 @@ pub async fn prepare(
 }
 
 // MARK: GET /devices/links
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetQuery {
 	device_link_token: String,
 }
@@ -41,9 +42,8 @@ pub async fn get(
 	// No auth required since the device doesn't have a token yet
 
 	// Decode device link token
-	let claims =
-		tivet_claims::decode(&ctx.config().server()?.jwt.public, &query.device_link_token)??
-			.as_cloud_device_link()?;
+	let claims = tivet_claims::decode(&ctx.config().server()?.jwt, &query.device_link_token)??
+		.as_cloud_device_link()?;
 	let link_id = claims.device_link_id;
 
 	// Check for complete message
@@ -91,9 +91,8 @@ pub async fn complete(
 		.await?;
 
 	// Decode device link token
-	let claims =
-		tivet_claims::decode(&ctx.config().server()?.jwt.public, &body.device_link_token)??
-			.as_cloud_device_link()?;
+	let claims = tivet_claims::decode(&ctx.config().server()?.jwt, &body.device_link_token)??
+		.as_cloud_device_link()?;
 	let link_id = claims.device_link_id;
 
 	// Check the link isn't complete already
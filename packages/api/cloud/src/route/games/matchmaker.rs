@@ -7,6 +7,7 @@ use api_helper::{
 use proto::backend::{self, pkg::*};
 use tivet_api::models;
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::{assert, auth::Auth};
@@ -117,8 +118,9 @@ pub async fn export_history(
 }
 
 // MARK: GET /games/{}/matchmaker/lobbies/{}/logs
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetLobbyLogsQuery {
+	#[schemars(with = "String")]
 	pub stream: models::CloudGamesLogStream,
 }
 
@@ -169,6 +169,7 @@ async fn gen_default_version_config(
 				listable: true,
 				taggable: false,
 				allow_dynamic_max_players: false,
+				skill_matchmaking: None,
 
 				runtime: Some(
 					backend::matchmaker::lobby_runtime::Docker {
@@ -8,6 +8,7 @@ use proto::{
 use tivet_api::models;
 use tivet_convert::ApiTryInto;
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -23,7 +24,7 @@ struct LobbyRunInfo {
 }
 
 // MARK: GET /games/{}/namespaces/{}/logs/lobbies
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListNamespaceLobbiesQuery {
 	pub before_create_ts: Option<chrono::DateTime<chrono::Utc>>,
 }
@@ -5,12 +5,14 @@ use tivet_api::models;
 use tivet_claims::ClaimsDecode;
 use tivet_convert::{ApiInto, ApiTryInto};
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::{assert, auth::Auth};
 
 pub mod analytics;
 pub mod logs;
+pub mod service_tokens;
 
 // MARK: GET /games/{}/namespaces/{}
 pub async fn get(
@@ -549,7 +551,7 @@ pub async fn update_mm_config(
 }
 
 // MARK: GET /games/{}/namespaces/{}/version-history
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetGameNamespaceGetVersionHistoryQuery {
 	limit: Option<u32>,
 	anchor: Option<String>,
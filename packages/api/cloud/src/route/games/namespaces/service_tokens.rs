@@ -0,0 +1,181 @@
+use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
+use proto::backend::pkg::*;
+use tivet_api::models;
+use tivet_claims::ent::EnvServiceScope;
+use tivet_operation::prelude::*;
+
+use crate::{assert, auth::Auth};
+
+const DEFAULT_TTL: i64 = util::duration::days(365);
+
+// MARK: POST /games/{}/namespaces/{}/tokens/service
+pub async fn create(
+	ctx: Ctx<Auth>,
+	game_id: Uuid,
+	namespace_id: Uuid,
+	body: models::CloudGamesNamespacesCreateGameNamespaceTokenServiceRequest,
+) -> GlobalResult<models::CloudGamesNamespacesCreateGameNamespaceTokenServiceResponse> {
+	ctx.auth()
+		.check_game_write_or_admin(ctx.op_ctx(), game_id)
+		.await?;
+	let _ns_data = assert::namespace_for_game(&ctx, game_id, namespace_id).await?;
+
+	let scopes = body
+		.scopes
+		.iter()
+		.map(|scope| {
+			let scope = unwrap_with!(
+				scope.parse::<EnvServiceScope>().ok(),
+				API_BAD_BODY,
+				error = format!("scopes[]: Unknown scope {scope:?}.")
+			);
+
+			Ok(i32::from(scope))
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	let actor_tags = body.actor_tags.unwrap_or_default();
+	ensure_with!(
+		actor_tags.len() <= 8,
+		API_BAD_BODY,
+		error = "Too many actor tags (max 8)."
+	);
+
+	let regions = body.regions.unwrap_or_default();
+	let datacenter_ids = if regions.is_empty() {
+		Vec::new()
+	} else {
+		let clusters_res = ctx
+			.op(cluster::ops::get_for_game::Input {
+				game_ids: vec![game_id],
+			})
+			.await?;
+		let cluster_id = unwrap!(clusters_res.games.first()).cluster_id;
+
+		let dcs_res = ctx
+			.op(cluster::ops::datacenter::resolve_for_name_id::Input {
+				cluster_id,
+				name_ids: regions.clone(),
+			})
+			.await?;
+
+		for region in &regions {
+			ensure_with!(
+				dcs_res.datacenters.iter().any(|dc| &dc.name_id == region),
+				API_BAD_BODY,
+				error = format!("regions[]: Region {region:?} not found.")
+			);
+		}
+
+		dcs_res
+			.datacenters
+			.iter()
+			.map(|dc| dc.datacenter_id.into())
+			.collect()
+	};
+
+	let create_res = op!([ctx] cloud_namespace_token_service_create {
+		namespace_id: Some(namespace_id.into()),
+		name: body.name,
+		scopes: scopes,
+		actor_tags: actor_tags,
+		datacenter_ids: datacenter_ids,
+		ttl: body.ttl.unwrap_or(DEFAULT_TTL),
+	})
+	.await?;
+
+	Ok(
+		models::CloudGamesNamespacesCreateGameNamespaceTokenServiceResponse {
+			token: create_res.token,
+			token_id: unwrap!(create_res.jti).as_uuid(),
+		},
+	)
+}
+
+// MARK: GET /games/{}/namespaces/{}/tokens/service
+pub async fn list(
+	ctx: Ctx<Auth>,
+	game_id: Uuid,
+	namespace_id: Uuid,
+	_watch_index: WatchIndexQuery,
+) -> GlobalResult<models::CloudGamesNamespacesListGameNamespaceTokensServiceResponse> {
+	ctx.auth()
+		.check_game_read_or_admin(ctx.op_ctx(), game_id)
+		.await?;
+	let _ns_data = assert::namespace_for_game(&ctx, game_id, namespace_id).await?;
+
+	let list_res = op!([ctx] cloud_namespace_token_service_list {
+		namespace_id: Some(namespace_id.into()),
+		include_inactive: false,
+	})
+	.await?;
+
+	// Resolve region names
+	let mut datacenter_ids = list_res
+		.tokens
+		.iter()
+		.flat_map(|token| token.datacenter_ids.iter().map(|x| x.as_uuid()))
+		.collect::<Vec<_>>();
+	datacenter_ids.sort();
+	datacenter_ids.dedup();
+	let dc_res = ctx
+		.op(cluster::ops::datacenter::get::Input { datacenter_ids })
+		.await?;
+
+	let tokens = list_res
+		.tokens
+		.into_iter()
+		.map(|token| {
+			Ok(models::CloudNamespaceServiceToken {
+				token_id: unwrap!(token.jti).as_uuid(),
+				name: token.name,
+				scopes: token
+					.scopes
+					.into_iter()
+					.map(|scope| Ok(EnvServiceScope::try_from(scope)?.as_str().to_string()))
+					.collect::<GlobalResult<Vec<_>>>()?,
+				actor_tags: token.actor_tags,
+				regions: token
+					.datacenter_ids
+					.iter()
+					.filter_map(|dc_id| {
+						dc_res
+							.datacenters
+							.iter()
+							.find(|dc| dc.datacenter_id == dc_id.as_uuid())
+							.map(|dc| dc.name_id.clone())
+					})
+					.collect(),
+				create_ts: util::timestamp::to_string(token.create_ts)?,
+				expire_ts: util::timestamp::to_string(token.expire_ts)?,
+				revoke_ts: token
+					.revoke_ts
+					.map(util::timestamp::to_string)
+					.transpose()?,
+			})
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(models::CloudGamesNamespacesListGameNamespaceTokensServiceResponse { tokens })
+}
+
+// MARK: DELETE /games/{}/namespaces/{}/tokens/service/{}
+pub async fn revoke(
+	ctx: Ctx<Auth>,
+	game_id: Uuid,
+	namespace_id: Uuid,
+	token_id: Uuid,
+) -> GlobalResult<models::CloudGamesNamespacesRevokeGameNamespaceTokenServiceResponse> {
+	ctx.auth()
+		.check_game_write_or_admin(ctx.op_ctx(), game_id)
+		.await?;
+	let _ns_data = assert::namespace_for_game(&ctx, game_id, namespace_id).await?;
+
+	op!([ctx] cloud_namespace_token_service_revoke {
+		namespace_id: Some(namespace_id.into()),
+		jtis: vec![token_id.into()],
+	})
+	.await?;
+
+	Ok(models::CloudGamesNamespacesRevokeGameNamespaceTokenServiceResponse {})
+}
@@ -66,6 +66,15 @@ define_router! {
 				body: models::CloudGamesNamespacesCreateGameNamespaceTokenDevelopmentRequest
 			),
 		},
+		"games" / Uuid / "namespaces" / Uuid / "tokens" / "service": {
+			GET: games::namespaces::service_tokens::list(),
+			POST: games::namespaces::service_tokens::create(
+				body: models::CloudGamesNamespacesCreateGameNamespaceTokenServiceRequest
+			),
+		},
+		"games" / Uuid / "namespaces" / Uuid / "tokens" / "service" / Uuid: {
+			DELETE: games::namespaces::service_tokens::revoke(),
+		},
 		"games" / Uuid / "namespaces" / Uuid / "domains": {
 			POST: games::namespaces::add_namespace_domain(
 				body: models::CloudGamesNamespacesAddNamespaceDomainRequest
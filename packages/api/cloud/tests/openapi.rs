@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// Published spec that the SDKs are generated from.
+const SDK_SPEC: &str = include_str!("../../../../site/src/content/docs/api/spec.json");
+
+/// Collects every `$ref` in the document.
+fn collect_refs(value: &Value, refs: &mut HashSet<String>) {
+	match value {
+		Value::Object(map) => {
+			if let Some(Value::String(reference)) = map.get("$ref") {
+				refs.insert(reference.clone());
+			}
+			for value in map.values() {
+				collect_refs(value, refs);
+			}
+		}
+		Value::Array(values) => {
+			for value in values {
+				collect_refs(value, refs);
+			}
+		}
+		_ => {}
+	}
+}
+
+#[test]
+fn openapi_matches_sdk_spec() {
+	let document = api_cloud::route::Router::openapi();
+	let sdk_spec = serde_json::from_str::<Value>(SDK_SPEC).expect("invalid sdk spec");
+
+	// Every referenced schema must exist in either the document or the published SDK spec
+	let mut refs = HashSet::new();
+	collect_refs(&document, &mut refs);
+	let mut missing = refs
+		.iter()
+		.filter_map(|reference| reference.strip_prefix("#/components/schemas/"))
+		.filter(|name| document["components"]["schemas"].get(*name).is_none())
+		.filter(|name| sdk_spec["definitions"].get(*name).is_none())
+		.collect::<Vec<_>>();
+	missing.sort();
+	assert!(
+		missing.is_empty(),
+		"schemas missing from the published sdk spec: {missing:?}"
+	);
+
+	for (path, method) in [
+		("/bootstrap", "get"),
+		("/games", "get"),
+		("/games/{game_id}/namespaces/{namespace_id}", "get"),
+		("/devices/links", "get"),
+	] {
+		assert!(
+			document["paths"][path].get(method).is_some(),
+			"missing {method} {path}"
+		);
+	}
+}
+
+#[test]
+fn openapi_query_params() {
+	let document = api_cloud::route::Router::openapi();
+
+	let params = document["paths"]["/devices/links"]["get"]["parameters"]
+		.as_array()
+		.expect("missing parameters");
+	let token = params
+		.iter()
+		.find(|param| param["name"] == "device_link_token")
+		.expect("missing device_link_token");
+	assert_eq!(token["in"], "query");
+	assert_eq!(token["required"], true);
+
+	// Models from the SDK are documented as strings
+	let params = document["paths"]["/games/{game_id}/matchmaker/lobbies/{lobby_id}/logs"]["get"]
+		["parameters"]
+		.as_array()
+		.expect("missing parameters");
+	let stream = params
+		.iter()
+		.find(|param| param["name"] == "stream")
+		.expect("missing stream");
+	assert_eq!(stream["schema"]["type"], "string");
+}
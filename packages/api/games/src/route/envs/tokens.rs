@@ -29,6 +29,7 @@ pub async fn create_service(
 				kind: Some(proto::claims::entitlement::Kind::EnvService(
 					proto::claims::entitlement::EnvService {
 						env_id: Some(env_id.into()),
+						..Default::default()
 					}
 				)),
 			}]},
@@ -25,7 +25,7 @@ impl ApiAuth for Auth {
 			config: config.clone(),
 			claims: if let Some(api_token) = api_token {
 				Some(as_auth_expired(tivet_claims::decode(
-					&config.server()?.jwt.public,
+					&config.server()?.jwt,
 					&api_token,
 				)?)?)
 			} else {
@@ -15,6 +15,7 @@ use tivet_config::config::tivet::AccessKind;
 use tivet_convert::{ApiInto, ApiTryInto};
 use tivet_group_server::models;
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -179,7 +180,7 @@ enum TeamMemberConsumerUpdate {
 	MemberRemove(common::Uuid),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListMembersQuery {
 	anchor: Option<String>,
 	limit: Option<u32>,
@@ -311,7 +312,7 @@ enum TeamJoinRequestConsumerUpdate {
 	JoinRequestResolve(common::Uuid),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListJoinRequestsQuery {
 	anchor: Option<String>,
 	limit: Option<u32>,
@@ -1136,7 +1137,7 @@ enum TeamBanConsumerUpdate {
 	Unban(common::Uuid),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListBansQuery {
 	anchor: Option<String>,
 	limit: Option<u32>,
@@ -7,6 +7,7 @@ use proto::backend::{self, pkg::*};
 use tivet_api::models;
 use tivet_convert::{fetch, ApiInto, ApiTryInto};
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{assert, auth::Auth};
@@ -77,7 +78,7 @@ async fn get_profile(
 }
 
 // MARK: GET /identities/batch/handle
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IdentityIdsQuery {
 	identity_ids: Vec<Uuid>,
 }
@@ -11,6 +11,7 @@ use proto::{
 use tivet_api::models;
 use tivet_convert::{ApiInto, ApiTryInto};
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_json::json;
 
@@ -77,6 +78,7 @@ pub async fn join(
 		&HashMap::new(),
 		None,
 		VerificationType::UserData(body.verification_data.flatten()),
+		SkillQuery::default(),
 	)
 	.await?;
 
@@ -259,6 +261,28 @@ pub async fn find(
 				Some(auto_create)
 			},
 		});
+	if let Some(rating) = body.rating {
+		ensure_with!(
+			rating.is_finite(),
+			API_BAD_BODY,
+			error = "`rating` must be a finite number"
+		);
+
+		// Ratings come from the game client, so they're only accepted if the find verification
+		// endpoint can vouch for them
+		for (lgc, _) in &lobby_groups {
+			ensure_with!(
+				lgc.actions
+					.as_ref()
+					.and_then(|a| a.find.as_ref())
+					.and_then(|f| f.verification.as_ref())
+					.is_some(),
+				API_BAD_BODY,
+				error = "`rating` requires find verification to be enabled for the game mode"
+			);
+		}
+	}
+
 	let FindResponse {
 		lobby,
 		ports,
@@ -272,6 +296,10 @@ pub async fn find(
 		&tags,
 		body.max_players,
 		VerificationType::UserData(body.verification_data.flatten()),
+		// `queue_start_ts` is ignored, the queue start is tracked by the server
+		SkillQuery {
+			rating: body.rating,
+		},
 	)
 	.await?;
 
@@ -421,6 +449,7 @@ pub async fn create(
 				.and_then(|o| o.as_ref().map(serde_json::to_string))
 				.transpose()?
 				.as_deref(),
+			rating: None,
 			custom_lobby_publicity: Some(publicity),
 		},
 	)
@@ -480,6 +509,7 @@ pub async fn create(
 		// Bypassing join verification because this user created the lobby (create verification
 		// already happened)
 		VerificationType::Bypass,
+		SkillQuery::default(),
 	)
 	.await?;
 
@@ -492,7 +522,7 @@ pub async fn create(
 	})
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct ListQuery {
 	#[serde(default)]
 	include_state: bool,
@@ -884,6 +914,13 @@ enum VerificationType {
 	Bypass,
 }
 
+/// Only used when the lobby group has skill matchmaking enabled.
+#[derive(Debug, Default)]
+struct SkillQuery {
+	/// Claimed by the client, replaced with the rating returned by the find verification.
+	rating: Option<f64>,
+}
+
 #[tracing::instrument(err, skip(ctx, game_ns))]
 async fn find_inner(
 	ctx: &Ctx<Auth>,
@@ -894,6 +931,7 @@ async fn find_inner(
 	tags: &HashMap<String, String>,
 	dynamic_max_players: Option<i32>,
 	verification: VerificationType,
+	skill: SkillQuery,
 ) -> GlobalResult<FindResponse> {
 	let version_config = if let Some(version_config) = version_config {
 		version_config
@@ -1020,6 +1058,7 @@ async fn find_inner(
 			player_id: Some(player_id.into()),
 			token_session_id: Some(token_session_id.into()),
 			client_info: Some(ctx.client_info()),
+			rating: skill.rating,
 		}],
 		query: Some(query),
 		user_id: None,
@@ -1036,6 +1075,7 @@ async fn find_inner(
 		dynamic_max_players: dynamic_max_players
 			.map(ApiTryInto::api_try_into)
 			.transpose()?,
+		queue_start_ts: None,
 		debug: None,
 	})
 	.await?;
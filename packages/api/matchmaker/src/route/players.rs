@@ -8,6 +8,7 @@ use proto::backend::pkg::*;
 use tivet_api::models;
 use tivet_claims::ClaimsDecode;
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_json::json;
 
@@ -103,7 +104,7 @@ fn decode_player_token(
 	config: &tivet_config::Config,
 	token: &str,
 ) -> GlobalResult<tivet_claims::ent::MatchmakerPlayer> {
-	let player_claims = tivet_claims::decode(&config.server()?.jwt.public, token)
+	let player_claims = tivet_claims::decode(&config.server()?.jwt, token)
 		.map_err(|_| err_code!(TOKEN_ERROR, error = "Malformed player token"))?
 		.map_err(|_| err_code!(TOKEN_ERROR, error = "Invalid player token"))?;
 
@@ -115,7 +116,7 @@ fn decode_player_token_dev(
 	config: &tivet_config::Config,
 	token: &str,
 ) -> GlobalResult<tivet_claims::ent::MatchmakerDevelopmentPlayer> {
-	let player_claims = tivet_claims::decode(&config.server()?.jwt.public, token)
+	let player_claims = tivet_claims::decode(&config.server()?.jwt, token)
 		.map_err(|_| err_code!(TOKEN_ERROR, error = "Malformed player token"))?
 		.map_err(|_| err_code!(TOKEN_ERROR, error = "Invalid player token"))?;
 
@@ -124,7 +125,7 @@ fn decode_player_token_dev(
 }
 
 // MARK: GET /players/statistics
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetStatisticsQuery {
 	#[serde(default)]
 	exclude_outdated: bool,
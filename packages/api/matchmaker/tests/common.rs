@@ -200,6 +200,7 @@ impl Ctx {
 						listable: true,
 						taggable: false,
 						allow_dynamic_max_players: false,
+						skill_matchmaking: None,
 
 						runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 							build_id: build_res.build_id,
@@ -280,6 +281,7 @@ impl Ctx {
 						listable: true,
 						taggable: false,
 						allow_dynamic_max_players: false,
+						skill_matchmaking: None,
 
 						runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 							build_id: build_res.build_id,
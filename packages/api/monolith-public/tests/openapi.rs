@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// Generated document for every public router, published next to the SDK spec.
+///
+/// Regenerate with `UPDATE_OPENAPI=1 cargo test -p api-monolith-public --test openapi`.
+const OPENAPI_PATH: &str = concat!(
+	env!("CARGO_MANIFEST_DIR"),
+	"/../../../site/src/content/docs/api/openapi.json"
+);
+
+#[test]
+fn openapi_matches_checked_in_document() {
+	let document = api_monolith_public::route::Router::openapi();
+
+	if std::env::var("UPDATE_OPENAPI").is_ok() {
+		let mut buf = serde_json::to_string_pretty(&document).unwrap();
+		buf.push('\n');
+		std::fs::write(OPENAPI_PATH, buf).unwrap();
+		return;
+	}
+
+	let checked_in = serde_json::from_str::<Value>(
+		&std::fs::read_to_string(OPENAPI_PATH).expect("missing checked in document"),
+	)
+	.expect("invalid checked in document");
+
+	// Info has the service name and crate version, which change independently of the routes
+	let paths = document["paths"].as_object().expect("missing paths");
+	let checked_in_paths = checked_in["paths"].as_object().expect("missing paths");
+	let mut changed = paths
+		.keys()
+		.chain(checked_in_paths.keys())
+		.filter(|path| paths.get(*path) != checked_in_paths.get(*path))
+		.collect::<HashSet<_>>()
+		.into_iter()
+		.collect::<Vec<_>>();
+	changed.sort();
+	assert!(
+		changed.is_empty(),
+		"paths differ from {OPENAPI_PATH}, regenerate it with UPDATE_OPENAPI=1: {changed:?}"
+	);
+	assert_eq!(
+		checked_in["components"], document["components"],
+		"components differ from {OPENAPI_PATH}, regenerate it with UPDATE_OPENAPI=1"
+	);
+}
+
+#[test]
+fn openapi_operation_ids_are_unique() {
+	let document = api_monolith_public::route::Router::openapi();
+
+	// Handlers are only unique within a router, mounted routers must not collide
+	let mut operation_ids = HashSet::new();
+	for (path, path_item) in document["paths"].as_object().expect("missing paths") {
+		for (method, operation) in path_item.as_object().expect("invalid path item") {
+			let operation_id = operation["operationId"]
+				.as_str()
+				.expect("missing operation id");
+			assert!(
+				operation_ids.insert(operation_id.to_string()),
+				"duplicate operation id {operation_id} for {method} {path}"
+			);
+		}
+	}
+
+	assert_eq!(
+		document["paths"]["/matchmaker/regions"]["get"]["operationId"],
+		"matchmaker_regions_list_get"
+	);
+	assert_eq!(
+		document["paths"]["/regions"]["get"]["operationId"],
+		"regions_list_get"
+	);
+}
+
+#[test]
+fn openapi_query_params_are_documented() {
+	let document = api_monolith_public::route::Router::openapi();
+
+	for (path, method, param) in [
+		("/actors", "get", "cursor"),
+		("/cloud/devices/links", "get", "device_link_token"),
+		(
+			"/cloud/games/{game_id}/namespaces/{namespace_id}/logs/lobbies",
+			"get",
+			"before_create_ts",
+		),
+		(
+			"/cloud/games/{game_id}/matchmaker/lobbies/{lobby_id}/logs",
+			"get",
+			"stream",
+		),
+		("/group/groups/{group_id}/members", "get", "limit"),
+		("/identity/identities/batch/summary", "get", "identity_ids"),
+		("/matchmaker/lobbies/list", "get", "include_state"),
+		("/status/matchmaker", "get", "region"),
+	] {
+		let params = document["paths"][path][method]["parameters"]
+			.as_array()
+			.unwrap_or_else(|| panic!("missing {method} {path}"));
+		assert!(
+			params
+				.iter()
+				.any(|p| p["in"] == "query" && p["name"] == param),
+			"missing query param {param} for {method} {path}"
+		);
+	}
+}
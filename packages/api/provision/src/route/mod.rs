@@ -30,6 +30,19 @@ define_router! {
 			),
 		},
 
+		"servers" / Ipv4Addr / "client-token": {
+			GET: servers::client_token(
+				internal_endpoint: true,
+			),
+		},
+
+		"client-tokens" / "refresh": {
+			POST: servers::client_token_refresh(
+				internal_endpoint: true,
+				body: serde_json::Value,
+			),
+		},
+
 		"tunnel" / "tls": {
 			GET: tunnel::tls(
 				internal_endpoint: true,
@@ -1,6 +1,7 @@
 use std::net::Ipv4Addr;
 
 use api_helper::{anchor::WatchIndexQuery, ctx::Ctx};
+use serde_json::json;
 use tivet_api::models;
 use tivet_operation::prelude::*;
 
@@ -65,3 +66,100 @@ pub async fn info(
 		public_ip: wan_ip,
 	})
 }
+
+// MARK: GET /servers/{}/client-token
+/// Creates a token bound to the pegboard client of the server. Servers fetch this on boot since the server
+/// token is shared by every server created from the same prebake image.
+///
+/// Requests go through the tunnel so the caller can't be matched against the public ip. Instead the
+/// token can only be fetched once per server, afterwards it's renewed with `client_token_refresh`.
+pub async fn client_token(
+	ctx: Ctx<Auth>,
+	public_ip: Ipv4Addr,
+	_watch_index: WatchIndexQuery,
+) -> GlobalResult<serde_json::Value> {
+	ctx.auth().server()?;
+
+	let servers_res = ctx
+		.op(cluster::ops::server::resolve_for_ip::Input {
+			ips: vec![public_ip],
+			include_destroyed: false,
+		})
+		.await?;
+	let server = unwrap!(servers_res.servers.first(), "server with ip not found");
+
+	let server_res = ctx
+		.op(cluster::ops::server::get::Input {
+			server_ids: vec![server.server_id],
+		})
+		.await?;
+	let server = unwrap!(server_res.servers.first(), "server not found");
+
+	ensure!(
+		matches!(
+			server.pool_type,
+			cluster::types::PoolType::Pegboard | cluster::types::PoolType::PegboardIsolate
+		),
+		"server is not a pegboard client"
+	);
+
+	let claim_res = ctx
+		.op(cluster::ops::server::client_token_claim::Input {
+			server_id: server.server_id,
+		})
+		.await?;
+	ensure_with!(
+		claim_res.claimed,
+		API_FORBIDDEN,
+		reason = "Client token already issued for this server."
+	);
+
+	let token_res = ctx
+		.op(cluster::ops::server::token_create::Input {
+			issuer: "api-provision".to_string(),
+			server_id: Some(server.server_id),
+		})
+		.await?;
+
+	Ok(json!({ "token": token_res.token }))
+}
+
+// MARK: POST /client-tokens/refresh
+/// Creates a new token for the pegboard client the current token is bound to. Called periodically by
+/// clients before their token expires.
+pub async fn client_token_refresh(
+	ctx: Ctx<Auth>,
+	_body: serde_json::Value,
+) -> GlobalResult<serde_json::Value> {
+	let Some(server_id) = ctx.auth().server()?.server_id else {
+		bail_with!(
+			API_FORBIDDEN,
+			reason = "Token is not bound to a pegboard client."
+		);
+	};
+
+	let server_res = ctx
+		.op(cluster::ops::server::get::Input {
+			server_ids: vec![server_id],
+		})
+		.await?;
+	let server = unwrap_with!(
+		server_res.servers.first(),
+		API_FORBIDDEN,
+		reason = "Server not found."
+	);
+	ensure_with!(
+		server.cloud_destroy_ts.is_none(),
+		API_FORBIDDEN,
+		reason = "Server was destroyed."
+	);
+
+	let token_res = ctx
+		.op(cluster::ops::server::token_create::Input {
+			issuer: "api-provision".to_string(),
+			server_id: Some(server_id),
+		})
+		.await?;
+
+	Ok(json!({ "token": token_res.token }))
+}
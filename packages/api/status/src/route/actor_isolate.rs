@@ -11,13 +11,14 @@ use tivet_api::{
 	models,
 };
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tokio_tungstenite::connect_async;
 
 use crate::auth::Auth;
 
 // MARK: GET /matchmaker
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct StatusQuery {
 	region: Uuid,
@@ -80,6 +81,7 @@ pub async fn status(
 				kind: Some(proto::claims::entitlement::Kind::EnvService(
 					proto::claims::entitlement::EnvService {
 						env_id: Some(ns_id.into()),
+						..Default::default()
 					}
 				)),
 			}]},
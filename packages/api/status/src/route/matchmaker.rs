@@ -10,13 +10,14 @@ use tivet_api::{
 	models,
 };
 use tivet_operation::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tokio_tungstenite::connect_async;
 
 use crate::auth::Auth;
 
 // MARK: GET /matchmaker
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct StatusQuery {
 	region: String,
@@ -2,7 +2,7 @@ use api_helper::ctx::Ctx;
 use cluster::types::GuardPublicHostname;
 use ds::{
 	types::EndpointType,
-	types::{GameGuardProtocol, PortAuthorization, PortAuthorizationType},
+	types::{GameGuardProtocol, PortAuthorization, PortAuthorizationType, PortMiddleware},
 };
 use tivet_operation::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -33,6 +33,8 @@ struct DynamicServerProxiedPort {
 	auth_type: Option<i64>,
 	auth_key: Option<String>,
 	auth_value: Option<String>,
+
+	middleware: Option<sqlx::types::Json<PortMiddleware>>,
 }
 
 pub async fn build_ds(
@@ -62,7 +64,8 @@ pub async fn build_ds(
 					gg.protocol,
 					gga.auth_type,
 					gga.key AS auth_key,
-					gga.value AS auth_value
+					gga.value AS auth_value,
+					ggm.config AS middleware
 				FROM db_ds.server_proxied_ports AS pp
 				JOIN db_ds.servers AS s
 				ON pp.server_id = s.server_id
@@ -76,6 +79,10 @@ pub async fn build_ds(
 				ON
 					gg.server_id = gga.server_id AND
 					gg.port_name = gga.port_name
+				LEFT JOIN db_ds.server_ports_gg_middleware AS ggm
+				ON
+					gg.server_id = ggm.server_id AND
+					gg.port_name = ggm.port_name
 				WHERE
 					s.datacenter_id = $1 AND
 					s.destroy_ts IS NULL
@@ -313,11 +320,6 @@ fn add_http_port(
 			guard_public_hostname,
 		)?;
 
-		let mut middlewares = vec![
-			"ds-rate-limit".to_string(),
-			"ds-in-flight".to_string(),
-			"ds-retry".to_string(),
-		];
 		let rule = format_http_rule(
 			proxied_port,
 			&hostname,
@@ -331,6 +333,20 @@ fn add_http_port(
 		unique_key.hash(&mut hasher);
 		let hash = hasher.finish();
 
+		let mut middlewares = if let Some(middleware) = &proxied_port.middleware {
+			add_port_middlewares(
+				traefik_config,
+				&format!("ds:{}:{hash:x}", proxied_port.server_id),
+				middleware,
+			)
+		} else {
+			vec![
+				"ds-rate-limit".to_string(),
+				"ds-in-flight".to_string(),
+				"ds-retry".to_string(),
+			]
+		};
+
 		// Strip path
 		if let Some(path) = path {
 			let mw_name = format!("ds:{}:{hash:x}:strip-path", proxied_port.server_id);
@@ -368,6 +384,119 @@ fn add_http_port(
 	Ok(())
 }
 
+/// Registers the middlewares requested by the port and returns the names to attach to the router,
+/// in order. The default middlewares are kept unless overridden.
+fn add_port_middlewares(
+	traefik_config: &mut types::TraefikConfigResponse,
+	prefix: &str,
+	middleware: &PortMiddleware,
+) -> Vec<String> {
+	let mut middlewares = Vec::new();
+	let mut insert = |name: &str, mw: types::TraefikMiddlewareHttp| {
+		let mw_name = format!("{prefix}:{name}");
+		traefik_config.http.middlewares.insert(mw_name.clone(), mw);
+		mw_name
+	};
+
+	// Denied ranges are carved out of the allow list so denied clients get a 403
+	if let Some(source_range) = middleware.ip_allow_ranges() {
+		middlewares.push(insert(
+			"ip-allow-list",
+			types::TraefikMiddlewareHttp::IpAllowList {
+				source_range,
+				ip_strategy: None,
+			},
+		));
+	}
+
+	// Must come before auth so preflight requests are answered
+	if let Some(cors) = &middleware.cors {
+		middlewares.push(insert(
+			"cors",
+			types::TraefikMiddlewareHttp::Headers(types::TraefikMiddlewareHeaders {
+				access_control_allow_credentials: Some(cors.allow_credentials),
+				access_control_allow_headers: Some(cors.allowed_headers.clone()),
+				access_control_allow_methods: Some(if cors.allowed_methods.is_empty() {
+					vec!["GET".into(), "POST".into(), "PUT".into(), "DELETE".into()]
+				} else {
+					cors.allowed_methods.clone()
+				}),
+				access_control_allow_origin_list: Some(cors.allowed_origins.clone()),
+				access_control_max_age: cors.max_age_s.map(|x| x as usize),
+				access_control_expose_headers: Some(cors.exposed_headers.clone()),
+				add_vary_header: Some(true),
+				..Default::default()
+			}),
+		));
+	}
+
+	if let Some(rate_limit) = &middleware.rate_limit {
+		middlewares.push(insert(
+			"rate-limit",
+			types::TraefikMiddlewareHttp::RateLimit {
+				average: rate_limit.average as usize,
+				period: format!("{}s", rate_limit.period_s),
+				burst: rate_limit.burst as usize,
+				source_criterion: types::InFlightReqSourceCriterion::IpStrategy(
+					types::IpStrategy {
+						depth: 0,
+						exclude_ips: None,
+					},
+				),
+			},
+		));
+		middlewares.push("ds-in-flight".to_string());
+	} else {
+		middlewares.push("ds-rate-limit".to_string());
+		middlewares.push("ds-in-flight".to_string());
+	}
+
+	if let Some(basic_auth) = &middleware.basic_auth {
+		middlewares.push(insert(
+			"basic-auth",
+			types::TraefikMiddlewareHttp::BasicAuth {
+				users: basic_auth.users.clone(),
+				realm: basic_auth.realm.clone(),
+				remove_header: true,
+			},
+		));
+	}
+
+	if !middleware.request_headers.is_empty() || !middleware.response_headers.is_empty() {
+		middlewares.push(insert(
+			"headers",
+			types::TraefikMiddlewareHttp::Headers(types::TraefikMiddlewareHeaders {
+				custom_request_headers: (!middleware.request_headers.is_empty()).then(|| {
+					middleware
+						.request_headers
+						.iter()
+						.map(|(k, v)| (k.clone(), v.clone()))
+						.collect()
+				}),
+				custom_response_headers: (!middleware.response_headers.is_empty()).then(|| {
+					middleware
+						.response_headers
+						.iter()
+						.map(|(k, v)| (k.clone(), v.clone()))
+						.collect()
+				}),
+				..Default::default()
+			}),
+		));
+	}
+
+	if middleware.compress {
+		middlewares.push(insert(
+			"compress",
+			types::TraefikMiddlewareHttp::Compress {},
+		));
+	}
+
+	middlewares.push("ds-retry".to_string());
+
+	middlewares
+}
+
 fn format_http_rule(
 	proxied_port: &DynamicServerProxiedPort,
 	hostname: &str,
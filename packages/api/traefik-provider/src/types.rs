@@ -212,6 +212,10 @@ pub enum TraefikMiddlewareHttp {
 #[derive(Default, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct TraefikMiddlewareHeaders {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub access_control_allow_credentials: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub access_control_allow_headers: Option<Vec<String>>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub access_control_allow_methods: Option<Vec<String>>,
 	#[serde(skip_serializing_if = "Option::is_none")]
@@ -219,6 +223,10 @@ pub struct TraefikMiddlewareHeaders {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub access_control_max_age: Option<usize>,
 	#[serde(skip_serializing_if = "Option::is_none")]
+	pub access_control_expose_headers: Option<Vec<String>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub add_vary_header: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub custom_request_headers: Option<HashMap<String, String>>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub custom_response_headers: Option<HashMap<String, String>>,
@@ -4,6 +4,12 @@ use tivet_operation::prelude::*;
 pub struct Router;
 
 impl Router {
+	/// The UI is not part of the API, so it has no routes in the OpenAPI document.
+	#[doc(hidden)]
+	pub fn __routes() -> Vec<api_helper::openapi::RouteMeta> {
+		Vec::new()
+	}
+
 	fn replace_vite_app_api_url(
 		content: &[u8],
 		config: &tivet_config::Config,
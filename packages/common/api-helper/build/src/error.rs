@@ -27,49 +27,12 @@ pub fn handle_rejection(
 	ray_id: Uuid,
 ) -> Result<Response<Body>, http::Error> {
 	// Log error
-	let err = match err {
-		GlobalError::BadRequest { .. } => {
-			tracing::warn!(?err, "bad request response");
-			err
+	match &err {
+		GlobalError::BadRequest { .. } => tracing::warn!(?err, "bad request response"),
+		GlobalError::Internal { .. } | GlobalError::Raw(_) => {
+			tracing::error!(?err, "internal error response")
 		}
-		GlobalError::Internal { .. } => {
-			tracing::error!(?err, "internal error response");
-
-			// Replace internal errors with global errors
-			// TODO: Remove panic
-			if config
-				.server()
-				.expect("missing server")
-				.tivet
-				.api_public
-				.verbose_errors()
-			{
-				err_code!(ERROR, error = err.to_string())
-			} else {
-				err_code!(ERROR, error = "An internal error has occurred.",)
-			}
-		}
-		GlobalError::Raw(err) => {
-			tracing::error!(?err, "internal error response");
-
-			// Replace internal errors with global errors
-			// TODO: Remove panic
-			if config
-				.server()
-				.expect("missing server")
-				.tivet
-				.api_public
-				.verbose_errors()
-			{
-				err_code!(ERROR, error = err.to_string())
-			} else {
-				err_code!(
-					ERROR,
-					error = format!("An internal error has occurred (ray_id {}).", ray_id)
-				)
-			}
-		}
-	};
+	}
 
 	// Modify request based on error
 	if let GlobalError::BadRequest { code, metadata, .. } = &err {
@@ -97,13 +60,46 @@ pub fn handle_rejection(
 		}
 	};
 
-	// Build response
-	let (status, code, message, documentation) = (
-		err.http_status(),
-		err.code().map(|s| s.to_string()),
-		err.message(),
-		err.documentation().map(|s| s.to_string()),
-	);
+	let (status, error_reply) = error_reply(config, &err, ray_id);
+	let body = Body::from(serde_json::to_vec(&error_reply).unwrap_or_default());
+
+	response.status(status).body(body)
+}
+
+/// Builds the reply sent to the client for an error. Internal errors are replaced with a generic
+/// error unless verbose errors are enabled.
+pub fn error_reply(
+	config: &tivet_config::Config,
+	err: &GlobalError,
+	ray_id: Uuid,
+) -> (http::StatusCode, ErrorReply) {
+	// Replace internal errors with global errors
+	// TODO: Remove panic
+	let verbose_errors = || {
+		config
+			.server()
+			.expect("missing server")
+			.tivet
+			.api_public
+			.verbose_errors()
+	};
+	let replaced = match err {
+		GlobalError::BadRequest { .. } => None,
+		GlobalError::Internal { .. } => Some(if verbose_errors() {
+			err_code!(ERROR, error = err.to_string())
+		} else {
+			err_code!(ERROR, error = "An internal error has occurred.",)
+		}),
+		GlobalError::Raw(raw_err) => Some(if verbose_errors() {
+			err_code!(ERROR, error = raw_err.to_string())
+		} else {
+			err_code!(
+				ERROR,
+				error = format!("An internal error has occurred (ray_id {}).", ray_id)
+			)
+		}),
+	};
+	let err = replaced.as_ref().unwrap_or(err);
 
 	let metadata = match err.metadata() {
 		Ok(metadata) => metadata,
@@ -113,15 +109,13 @@ pub fn handle_rejection(
 		}
 	};
 
-	// Create reply
 	let error_reply = ErrorReply {
-		code,
-		message,
+		code: err.code().map(|s| s.to_string()),
+		message: err.message(),
 		ray_id,
-		documentation,
+		documentation: err.documentation().map(|s| s.to_string()),
 		metadata,
 	};
-	let body = Body::from(serde_json::to_vec(&error_reply).unwrap_or_default());
 
-	response.status(status).body(body)
+	(err.http_status(), error_reply)
 }
@@ -3,6 +3,7 @@ pub mod auth;
 pub mod ctx;
 pub mod error;
 mod metrics;
+pub mod openapi;
 mod start;
 pub mod util;
 
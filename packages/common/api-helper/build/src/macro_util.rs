@@ -10,6 +10,7 @@ use hyper::{
 };
 use tivet_config::config::tivet::DnsProvider;
 use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
 use url::Url;
 use uuid::Uuid;
 
@@ -25,6 +26,13 @@ pub mod __metrics {
 const MAX_ALLOWED_BODY_SIZE: u64 = tivet_util::file_size::gibibytes(10);
 const BEARER: &str = "Bearer ";
 
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+/// Well above the request timeout so a retry can't take over the key while the original request is
+/// still running.
+const IDEMPOTENCY_PENDING_TTL: i64 = tivet_util::duration::minutes(5);
+const IDEMPOTENCY_TTL: i64 = tivet_util::duration::hours(24);
+
 // For code legibility
 #[doc(hidden)]
 pub struct __RouterConfig {
@@ -165,16 +173,18 @@ pub async fn __read_body_bytes(request: &mut Request<Body>) -> GlobalResult<Byte
 pub async fn __deserialize_body<T: DeserializeOwned + Send>(
 	request: &mut Request<Body>,
 ) -> GlobalResult<T> {
-	__validate_body(request)?;
+	let bytes_raw = __read_body_bytes(request).await?;
 
-	// Read bytes
-	let bytes_raw = hyper::body::to_bytes(request.body_mut()).await?;
+	__deserialize_body_bytes(&bytes_raw)
+}
 
+#[doc(hidden)]
+pub fn __deserialize_body_bytes<T: DeserializeOwned + Send>(bytes_raw: &[u8]) -> GlobalResult<T> {
 	// Add default empty JSON body if no bytes provided
 	let bytes = if bytes_raw.is_empty() {
 		b"{}"
 	} else {
-		bytes_raw.as_ref()
+		bytes_raw
 	};
 
 	// Deserialize bytes
@@ -377,3 +387,177 @@ pub async fn __with_ctx<A: auth::ApiAuth + Send>(
 		asn,
 	})
 }
+
+#[doc(hidden)]
+pub struct __Idempotency {
+	config: tivet_cache::IdempotencyConfig,
+	key: String,
+}
+
+#[doc(hidden)]
+pub enum __IdempotencyBegin {
+	/// No idempotency key was provided.
+	None,
+	Acquired(__Idempotency),
+	/// The request already completed, replay the response.
+	Replay(tivet_cache::IdempotentResponse),
+}
+
+/// Claims the idempotency key from the request if provided.
+///
+/// Keys are scoped to the endpoint and the request's bearer token so clients can't read each other's
+/// responses.
+#[doc(hidden)]
+pub async fn __idempotency_begin(
+	cache: &tivet_cache::Cache,
+	request: &Request<Body>,
+	method: &str,
+	path: &str,
+	body: &[u8],
+) -> GlobalResult<__IdempotencyBegin> {
+	let Some(key) = __deserialize_optional_header::<String, _>(request, IDEMPOTENCY_KEY_HEADER)?
+	else {
+		return Ok(__IdempotencyBegin::None);
+	};
+	ensure_with!(
+		!key.is_empty() && key.len() <= 255 && key.chars().all(|c| c.is_ascii_graphic()),
+		API_INVALID_IDEMPOTENCY_KEY,
+		error = "must be between 1 and 255 visible ascii characters"
+	);
+
+	let auth_hash = {
+		let mut hasher = Sha256::new();
+		if let Some(auth) = request.headers().get(header::AUTHORIZATION) {
+			hasher.update(auth.as_bytes());
+		}
+		hex::encode(&hasher.finalize()[..16])
+	};
+	let config = tivet_cache::IdempotencyConfig {
+		scope: format!("{}:{method}:{path}:{auth_hash}", tivet_env::service_name()),
+		pending_ttl_ms: IDEMPOTENCY_PENDING_TTL,
+		ttl_ms: IDEMPOTENCY_TTL,
+	};
+
+	// Hash everything that makes up the request so reusing a key for a different request conflicts
+	let request_hash = {
+		let mut hasher = Sha256::new();
+		hasher.update(request.method().as_str().as_bytes());
+		hasher.update(b"\n");
+		hasher.update(request.uri().path().as_bytes());
+		hasher.update(b"\n");
+		hasher.update(request.uri().query().unwrap_or_default().as_bytes());
+		hasher.update(b"\n");
+		hasher.update(body);
+		hex::encode(hasher.finalize())
+	};
+
+	match cache
+		.idempotency_begin(&config, &key, &request_hash)
+		.await?
+	{
+		tivet_cache::IdempotencyState::Acquired => {
+			Ok(__IdempotencyBegin::Acquired(__Idempotency { config, key }))
+		}
+		tivet_cache::IdempotencyState::Complete(response) => {
+			tracing::debug!(?key, status = ?response.status, "replaying idempotent response");
+			Ok(__IdempotencyBegin::Replay(response))
+		}
+		tivet_cache::IdempotencyState::InProgress => {
+			bail_with!(API_IDEMPOTENCY_KEY_IN_PROGRESS)
+		}
+		tivet_cache::IdempotencyState::Conflict => bail_with!(API_IDEMPOTENCY_KEY_CONFLICT),
+	}
+}
+
+/// Stores the response for replaying to retries. Successful responses and deterministic client errors
+/// are stored so a retry does not run the handler a second time. The key is released for timeouts,
+/// server errors and rate limits so the request can be retried.
+///
+/// This is infallible since the request itself already completed.
+#[doc(hidden)]
+pub async fn __idempotency_complete<T: AsRef<[u8]>>(
+	config: &tivet_config::Config,
+	cache: &tivet_cache::Cache,
+	ray_id: Uuid,
+	idempotency: __Idempotency,
+	res: &GlobalResult<__AsyncOption<T>>,
+) {
+	let response = match res {
+		Ok(__AsyncOption::Some(body)) => Some(tivet_cache::IdempotentResponse {
+			status: http::StatusCode::OK.as_u16(),
+			body: body.as_ref().to_vec(),
+		}),
+		Err(err) => {
+			let (status, error_reply) = crate::error::error_reply(config, err, ray_id);
+
+			if is_retryable(status) {
+				tracing::debug!(
+					?status,
+					key = ?idempotency.key,
+					"releasing idempotency key for retryable error"
+				);
+				None
+			} else {
+				Some(tivet_cache::IdempotentResponse {
+					status: status.as_u16(),
+					body: serde_json::to_vec(&error_reply).unwrap_or_default(),
+				})
+			}
+		}
+		// No response was written, let the request be retried
+		Ok(_) => None,
+	};
+
+	if let Some(response) = response {
+		if let Err(err) = cache
+			.idempotency_complete(&idempotency.config, &idempotency.key, &response)
+			.await
+		{
+			tracing::error!(?err, key = ?idempotency.key, "failed to complete idempotency key");
+		}
+	} else if let Err(err) = cache
+		.idempotency_release(&idempotency.config, &idempotency.key)
+		.await
+	{
+		tracing::error!(?err, key = ?idempotency.key, "failed to release idempotency key");
+	}
+}
+
+/// Errors that may succeed when retried. This includes `API_REQUEST_TIMEOUT`, which is a 500.
+fn is_retryable(status: http::StatusCode) -> bool {
+	status.is_server_error()
+		|| status == http::StatusCode::TOO_MANY_REQUESTS
+		|| status == http::StatusCode::REQUEST_TIMEOUT
+}
+
+/// Writes the status of a replayed idempotent response to the response builder and returns its body.
+#[doc(hidden)]
+pub fn __idempotency_replay(
+	response: &mut http::response::Builder,
+	replay: tivet_cache::IdempotentResponse,
+) -> Vec<u8> {
+	let status = http::StatusCode::from_u16(replay.status).unwrap_or(http::StatusCode::OK);
+	*response = std::mem::take(response)
+		.status(status)
+		.header(IDEMPOTENT_REPLAYED_HEADER, "true");
+
+	replay.body
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn retryable_statuses() {
+		assert!(is_retryable(http::StatusCode::INTERNAL_SERVER_ERROR));
+		assert!(is_retryable(http::StatusCode::BAD_GATEWAY));
+		assert!(is_retryable(http::StatusCode::TOO_MANY_REQUESTS));
+		assert!(is_retryable(http::StatusCode::REQUEST_TIMEOUT));
+
+		assert!(!is_retryable(http::StatusCode::OK));
+		assert!(!is_retryable(http::StatusCode::BAD_REQUEST));
+		assert!(!is_retryable(http::StatusCode::NOT_FOUND));
+		assert!(!is_retryable(http::StatusCode::CONFLICT));
+	}
+}
@@ -0,0 +1,478 @@
+//! OpenAPI document generation for routers defined with `define_router!`.
+//!
+//! The macro records a `RouteMeta` for every endpoint. Types that implement `JsonSchema` are
+//! rendered inline with `schemars`, other types (such as the generated `tivet_api` models) are
+//! referenced by name from the published SDK spec.
+
+use std::{future::Future, marker::PhantomData};
+
+use global_error::prelude::*;
+use hyper::{Body, Response};
+use schemars::{
+	gen::SchemaSettings,
+	schema::{Schema, SchemaObject},
+	JsonSchema,
+};
+use serde_json::{json, Map};
+
+pub use schemars::{self, gen::SchemaGenerator};
+pub use serde_json::Value;
+
+/// Well-known path the OpenAPI document is served at.
+pub const PATH: &str = "/openapi.json";
+
+const COMPONENTS_PATH: &str = "#/components/schemas/";
+
+pub type SchemaFn = fn(&mut SchemaGenerator) -> TypeSchema;
+
+pub enum TypeSchema {
+	/// Schema generated by `schemars`.
+	Schema(Schema),
+	/// Type does not implement `JsonSchema`, referenced by name in `#/components/schemas`.
+	Named(String),
+}
+
+impl TypeSchema {
+	fn into_value(self) -> Value {
+		match self {
+			TypeSchema::Schema(schema) => serde_json::to_value(schema).unwrap_or(Value::Bool(true)),
+			TypeSchema::Named(name) => json!({ "$ref": format!("{COMPONENTS_PATH}{name}") }),
+		}
+	}
+}
+
+pub enum PathSegment {
+	Literal(&'static str),
+	Param {
+		/// Type label as written in the router.
+		ty: &'static str,
+		schema: SchemaFn,
+	},
+}
+
+pub enum BodyMeta {
+	Json(SchemaFn),
+	Bytes,
+}
+
+/// Metadata about a single endpoint function in a router.
+pub struct RouteMeta {
+	pub method: &'static str,
+	pub path: Vec<PathSegment>,
+	/// Path of the handler function, used to derive the operation ID.
+	pub handler: &'static str,
+	pub query: Vec<SchemaFn>,
+	pub body: Option<BodyMeta>,
+	/// Schema of the handler's return type. `None` if the handler writes its own response.
+	pub response: Option<SchemaFn>,
+	pub internal: bool,
+	/// Honors the `Idempotency-Key` header.
+	pub idempotent: bool,
+	/// Prefixes of the routers this route is mounted in, outermost first.
+	pub mounts: Vec<&'static str>,
+}
+
+impl RouteMeta {
+	/// Prepends the prefix of a mounted router.
+	pub fn with_prefix(mut self, prefix: Option<&'static str>) -> Self {
+		if let Some(prefix) = prefix {
+			self.path.insert(0, PathSegment::Literal(prefix));
+			self.mounts.insert(0, prefix);
+		}
+
+		self
+	}
+
+	/// Names of the path params in order.
+	///
+	/// Params are named after the collection they index in to, for example `/actors/{actor_id}`
+	/// for a `"actors" / Uuid` segment or `/secrets/{secret}` for `"secrets" / String`.
+	pub fn param_names(&self) -> Vec<String> {
+		let mut names = Vec::<String>::new();
+		let mut prev = None;
+		for segment in &self.path {
+			match segment {
+				PathSegment::Literal(lit) => prev = Some(*lit),
+				PathSegment::Param { ty, .. } => {
+					let mut name = match prev.take() {
+						Some(lit) if *ty == "Uuid" => format!("{}_id", singular(lit)),
+						Some(lit) => singular(lit),
+						None => format!("param_{}", names.len()),
+					};
+
+					// Deduplicate
+					if names.contains(&name) {
+						name = format!("{name}_{}", names.len());
+					}
+
+					names.push(name);
+				}
+			}
+		}
+
+		names
+	}
+
+	/// OpenAPI path template.
+	pub fn path_template(&self) -> String {
+		let mut names = self.param_names().into_iter();
+		let segments = self
+			.path
+			.iter()
+			.map(|segment| match segment {
+				PathSegment::Literal(lit) => lit.to_string(),
+				PathSegment::Param { .. } => {
+					format!("{{{}}}", names.next().unwrap_or_default())
+				}
+			})
+			.collect::<Vec<_>>();
+
+		format!("/{}", segments.join("/"))
+	}
+
+	/// Handlers are only unique within their router, so the mount prefixes are included to keep
+	/// operation IDs unique in a monolith.
+	pub fn operation_id(&self) -> String {
+		let mut parts = self
+			.mounts
+			.iter()
+			.map(|prefix| prefix.replace('-', "_"))
+			.collect::<Vec<_>>();
+		parts.push(self.handler.replace("::", "_"));
+		parts.push(self.method.to_lowercase());
+
+		parts.join("_")
+	}
+}
+
+/// Builds an OpenAPI 3.1 document from the routes of a router.
+///
+/// Internal endpoints are excluded.
+pub fn document(title: &str, version: &str, routes: &[RouteMeta]) -> Value {
+	let mut gen = SchemaSettings::draft2019_09()
+		.with(|s| {
+			s.definitions_path = COMPONENTS_PATH.to_string();
+			s.meta_schema = None;
+		})
+		.into_generator();
+
+	// Query parameters are expanded in to individual parameters, so they're always inlined
+	let mut query_gen = SchemaSettings::draft2019_09()
+		.with(|s| {
+			s.inline_subschemas = true;
+			s.meta_schema = None;
+		})
+		.into_generator();
+
+	let mut paths = Map::new();
+	for route in routes.iter().filter(|r| !r.internal) {
+		let mut parameters = Vec::new();
+
+		// Path params
+		let params = route.path.iter().filter_map(|segment| match segment {
+			PathSegment::Param { ty, schema } => Some((ty, schema)),
+			PathSegment::Literal(_) => None,
+		});
+		for (name, (ty, schema)) in route.param_names().into_iter().zip(params) {
+			parameters.push(json!({
+				"name": name,
+				"in": "path",
+				"required": true,
+				"description": ty,
+				"schema": schema(&mut gen).into_value(),
+			}));
+		}
+
+		if route.idempotent {
+			parameters.push(json!({
+				"name": "Idempotency-Key",
+				"in": "header",
+				"required": false,
+				"description": "Retries with the same key replay the original response.",
+				"schema": { "type": "string", "maxLength": 255 },
+			}));
+		}
+
+		// Query params
+		for query in &route.query {
+			match query(&mut query_gen) {
+				TypeSchema::Schema(schema) => {
+					parameters.extend(query_parameters(schema.into_object()));
+				}
+				TypeSchema::Named(name) => {
+					tracing::debug!(?name, handler = ?route.handler, "query type has no schema");
+				}
+			}
+		}
+
+		let response_content = match route.response {
+			Some(schema) => json!({ "schema": schema(&mut gen).into_value() }),
+			None => json!({}),
+		};
+		let mut operation = json!({
+			"operationId": route.operation_id(),
+			"parameters": parameters,
+			"responses": {
+				"200": {
+					"description": "Success",
+					"content": {
+						"application/json": response_content,
+					},
+				},
+			},
+		});
+		if let Some(PathSegment::Literal(tag)) = route.path.first() {
+			operation["tags"] = json!([tag]);
+		}
+		match &route.body {
+			Some(BodyMeta::Json(schema)) => {
+				operation["requestBody"] = json!({
+					"required": true,
+					"content": {
+						"application/json": {
+							"schema": schema(&mut gen).into_value(),
+						},
+					},
+				});
+			}
+			Some(BodyMeta::Bytes) => {
+				operation["requestBody"] = json!({
+					"required": true,
+					"content": {
+						"application/octet-stream": {},
+					},
+				});
+			}
+			None => {}
+		}
+
+		let path_item = paths
+			.entry(route.path_template())
+			.or_insert_with(|| Value::Object(Map::new()));
+		path_item[route.method.to_lowercase()] = operation;
+	}
+
+	let schemas = gen
+		.take_definitions()
+		.into_iter()
+		.map(|(name, schema)| {
+			let value = serde_json::to_value(schema).unwrap_or(Value::Bool(true));
+			(name, value)
+		})
+		.collect::<Map<_, _>>();
+
+	json!({
+		"openapi": "3.1.0",
+		"info": {
+			"title": title,
+			"version": version,
+		},
+		"paths": paths,
+		"components": {
+			"schemas": schemas,
+		},
+	})
+}
+
+/// Splits an object schema in to one parameter per property.
+fn query_parameters(schema: SchemaObject) -> Vec<Value> {
+	let Some(object) = schema.object else {
+		return Vec::new();
+	};
+
+	object
+		.properties
+		.into_iter()
+		.map(|(name, schema)| {
+			json!({
+				"required": object.required.contains(&name),
+				"name": name,
+				"in": "query",
+				"schema": serde_json::to_value(schema).unwrap_or(Value::Bool(true)),
+			})
+		})
+		.collect()
+}
+
+/// Responds with the OpenAPI document.
+pub fn response(
+	response: http::response::Builder,
+	document: &Value,
+) -> GlobalResult<Response<Body>> {
+	let body = serde_json::to_vec(document)?;
+
+	Ok(response
+		.status(http::StatusCode::OK)
+		.header(http::header::CONTENT_TYPE, "application/json")
+		.body(Body::from(body))?)
+}
+
+/// Returns the name of a type without its module path, used to reference SDK schemas.
+fn type_name(ty: &str) -> String {
+	ty.rsplit("::").next().unwrap_or(ty).trim().to_string()
+}
+
+/// Singular form of a path literal, used to name the path param that follows it.
+fn singular(lit: &str) -> String {
+	let lit = lit.replace('-', "_");
+
+	if let Some(stem) = lit.strip_suffix("ies") {
+		format!("{stem}y")
+	} else if let Some(stem) = lit.strip_suffix('s') {
+		stem.to_string()
+	} else {
+		lit
+	}
+}
+
+// Autoref specialization so the macro can use `JsonSchema` when it's implemented and fall back to
+// a named reference otherwise.
+#[doc(hidden)]
+pub struct __SchemaProbe<T>(PhantomData<T>);
+
+#[doc(hidden)]
+impl<T> __SchemaProbe<T> {
+	pub fn new() -> Self {
+		__SchemaProbe(PhantomData)
+	}
+}
+
+#[doc(hidden)]
+pub trait __ViaJsonSchema {
+	fn __schema(&self, gen: &mut SchemaGenerator) -> TypeSchema;
+}
+
+#[doc(hidden)]
+impl<T: JsonSchema> __ViaJsonSchema for __SchemaProbe<T> {
+	fn __schema(&self, gen: &mut SchemaGenerator) -> TypeSchema {
+		TypeSchema::Schema(gen.subschema_for::<T>())
+	}
+}
+
+#[doc(hidden)]
+pub trait __ViaTypeName {
+	fn __schema(&self, gen: &mut SchemaGenerator) -> TypeSchema;
+}
+
+#[doc(hidden)]
+impl<T> __ViaTypeName for &__SchemaProbe<T> {
+	fn __schema(&self, _gen: &mut SchemaGenerator) -> TypeSchema {
+		TypeSchema::Named(type_name(std::any::type_name::<T>()))
+	}
+}
+
+/// Implemented for endpoint handlers (`async fn(..) -> GlobalResult<T>`) so the response type can
+/// be probed without knowing the handler's signature.
+#[doc(hidden)]
+pub trait __Handler<Args> {
+	type Output;
+}
+
+macro_rules! impl_handler {
+	($($arg:ident),*) => {
+		impl<F, Fut, T, $($arg),*> __Handler<($($arg,)*)> for F
+		where
+			F: Fn($($arg),*) -> Fut,
+			Fut: Future<Output = GlobalResult<T>>,
+		{
+			type Output = T;
+		}
+	};
+}
+
+impl_handler!();
+impl_handler!(A1);
+impl_handler!(A1, A2);
+impl_handler!(A1, A2, A3);
+impl_handler!(A1, A2, A3, A4);
+impl_handler!(A1, A2, A3, A4, A5);
+impl_handler!(A1, A2, A3, A4, A5, A6);
+impl_handler!(A1, A2, A3, A4, A5, A6, A7);
+impl_handler!(A1, A2, A3, A4, A5, A6, A7, A8);
+impl_handler!(A1, A2, A3, A4, A5, A6, A7, A8, A9);
+impl_handler!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
+
+/// Probes the response type of an endpoint handler.
+#[doc(hidden)]
+pub fn __response_probe<F: __Handler<Args>, Args>(_handler: &F) -> __SchemaProbe<F::Output> {
+	__SchemaProbe::new()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn route(path: Vec<PathSegment>) -> RouteMeta {
+		RouteMeta {
+			method: "GET",
+			path,
+			handler: "test::get",
+			query: Vec::new(),
+			body: None,
+			response: None,
+			internal: false,
+			idempotent: false,
+			mounts: Vec::new(),
+		}
+	}
+
+	fn param(ty: &'static str) -> PathSegment {
+		PathSegment::Param {
+			ty,
+			schema: |_| TypeSchema::Named(String::new()),
+		}
+	}
+
+	#[test]
+	fn path_param_names() {
+		let nested = route(vec![
+			PathSegment::Literal("games"),
+			param("Uuid"),
+			PathSegment::Literal("replica-sets"),
+			param("Uuid"),
+			PathSegment::Literal("secrets"),
+			param("String"),
+		]);
+		assert_eq!(
+			"/games/{game_id}/replica-sets/{replica_set_id}/secrets/{secret}",
+			nested.path_template()
+		);
+
+		// Mounted routers get the prefix
+		let mounted = route(vec![param("Uuid"), param("Uuid")]).with_prefix(Some("lobbies"));
+		assert_eq!("/lobbies/{lobby_id}/{param_1}", mounted.path_template());
+	}
+
+	#[test]
+	fn mounted_operation_id() {
+		let root = route(vec![PathSegment::Literal("regions")]);
+		assert_eq!("test_get_get", root.operation_id());
+
+		// Outermost prefix first
+		let mounted = route(vec![PathSegment::Literal("regions")])
+			.with_prefix(Some("region-tiers"))
+			.with_prefix(Some("cloud"));
+		assert_eq!("cloud_region_tiers_test_get_get", mounted.operation_id());
+	}
+
+	#[test]
+	fn response_schema() {
+		async fn handler(_id: uuid::Uuid, _name: String) -> GlobalResult<Vec<String>> {
+			Ok(Vec::new())
+		}
+
+		let mut gen = SchemaGenerator::default();
+		let schema = (&__response_probe(&handler)).__schema(&mut gen);
+		assert!(matches!(schema, TypeSchema::Schema(_)));
+
+		struct Model;
+		async fn named_handler() -> GlobalResult<Model> {
+			Ok(Model)
+		}
+
+		match (&__response_probe(&named_handler)).__schema(&mut gen) {
+			TypeSchema::Named(name) => assert_eq!("Model", name),
+			TypeSchema::Schema(_) => panic!("expected named schema"),
+		}
+	}
+}
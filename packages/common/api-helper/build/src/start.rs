@@ -64,6 +64,14 @@ where
 			// Handle request
 			let ray_id = Uuid::new_v4();
 			let req_span = tracing::info_span!("http request", method = %req.method(), uri = %req.uri(), %ray_id);
+
+			// Continue the caller's trace
+			let header_str = |name: &str| req.headers().get(name).and_then(|h| h.to_str().ok());
+			tivet_runtime::otel::set_parent(
+				&req_span,
+				header_str(tivet_runtime::otel::TRACEPARENT),
+				header_str(tivet_runtime::otel::TRACESTATE),
+			);
 			async move {
 				let method = req.method().clone();
 				let uri = req.uri().clone();
@@ -99,6 +107,10 @@ where
 								handle(shared_client, config, pools, cache, ray_id, req).await?;
 							res.headers_mut()
 								.insert("rvt-ray-id", ray_id.to_string().parse()?);
+							if let Some(traceparent) = tivet_runtime::otel::traceparent() {
+								res.headers_mut()
+									.insert(tivet_runtime::otel::TRACEPARENT, traceparent.parse()?);
+							}
 							Result::<Response<Body>, http::Error>::Ok(res)
 						}
 						.in_current_span(),
@@ -36,7 +36,13 @@ impl CorsConfigBuilder {
 		Self::default()
 			.any_origin()
 			.methods(&["GET", "POST", "PUT", "PATCH", "DELETE"])
-			.headers(&["Content-Type", "Authorization"])
+			.headers(&[
+				"Content-Type",
+				"Authorization",
+				"Idempotency-Key",
+				"traceparent",
+				"tracestate",
+			])
 			.credentials()
 	}
 
@@ -45,7 +51,13 @@ impl CorsConfigBuilder {
 		Self::default()
 			.origin_regex(hub_origin_regex(config))
 			.methods(&["GET", "POST", "PUT", "PATCH", "DELETE"])
-			.headers(&["Content-Type", "Authorization"])
+			.headers(&[
+				"Content-Type",
+				"Authorization",
+				"Idempotency-Key",
+				"traceparent",
+				"tracestate",
+			])
 			.credentials()
 	}
 }
@@ -173,9 +185,12 @@ pub fn verify_cors(request: &Request<Body>, config: &CorsConfig) -> GlobalResult
 		);
 	}
 	cors_headers.typed_insert(
-		vec![HeaderName::from_static("rvt-ray-id")]
-			.into_iter()
-			.collect::<AccessControlExposeHeaders>(),
+		vec![
+			HeaderName::from_static("rvt-ray-id"),
+			HeaderName::from_static("traceparent"),
+		]
+		.into_iter()
+		.collect::<AccessControlExposeHeaders>(),
 	);
 
 	match (headers.get(header::ORIGIN), request.method()) {
@@ -301,7 +316,7 @@ pub async fn basic_rate_limit(
 		// Decode bypass token
 		if let Some(bypass_token) = rate_limit_ctx.bypass_token {
 			as_auth_expired(tivet_claims::decode(
-				&config.server()?.jwt.public,
+				&config.server()?.jwt,
 				&bypass_token,
 			)?)?
 			.as_bypass()?;
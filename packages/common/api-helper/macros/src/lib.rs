@@ -27,6 +27,7 @@ const ENDPOINT_ARGUMENTS: &[&str] = &[
 	"cookies",
 	"raw_remote_addr",
 	"header",
+	"idempotent",
 	"opt_auth",
 	"internal_endpoint",
 	"query",
@@ -131,6 +132,15 @@ impl Parse for EndpointRouter {
 
 impl EndpointRouter {
 	fn render(self) -> syn::Result<TokenStream2> {
+		let route_metas = self
+			.routes
+			.iter()
+			.map(Endpoint::render_metas)
+			.collect::<syn::Result<Vec<_>>>()?
+			.into_iter()
+			.flatten()
+			.collect::<Vec<_>>();
+
 		let endpoints = self
 			.routes
 			.into_iter()
@@ -172,9 +182,47 @@ impl EndpointRouter {
 			})
 			.collect::<Vec<_>>();
 
+		let mount_metas = self
+			.mounts
+			.iter()
+			.map(|mount| {
+				let mount_path = &mount.path;
+				let mount_prefix = match &mount.prefix {
+					Some(prefix) => quote! { Some(#prefix) },
+					None => quote! { None },
+				};
+
+				quote! {
+					routes.extend(
+						#mount_path::__routes()
+							.into_iter()
+							.map(|route| route.with_prefix(#mount_prefix))
+					);
+				}
+			})
+			.collect::<Vec<_>>();
+
 		Ok(quote! {
 			pub struct Router;
 			impl Router {
+				/// Metadata for every route in this router, including mounted routers.
+				#[doc(hidden)]
+				pub fn __routes() -> Vec<api_helper::openapi::RouteMeta> {
+					#[allow(unused_mut)]
+					let mut routes = vec![ #(#route_metas),* ];
+					#(#mount_metas)*
+					routes
+				}
+
+				/// Generates the OpenAPI document for this router.
+				pub fn openapi() -> api_helper::openapi::Value {
+					api_helper::openapi::document(
+						tivet_env::service_name(),
+						env!("CARGO_PKG_VERSION"),
+						&Self::__routes(),
+					)
+				}
+
 				#[doc(hidden)]
 				#[tracing::instrument(skip_all)]
 				pub async fn __inner(
@@ -214,6 +262,21 @@ impl EndpointRouter {
 				) -> Result<Response<Body>, http::Error> {
 					tracing::debug!(method=?request.method(), uri=?request.uri(), "received request");
 
+					// Serve OpenAPI document
+					if request.method() == http::Method::GET
+						&& request.uri().path() == api_helper::openapi::PATH
+					{
+						return match api_helper::openapi::response(response, &Self::openapi()) {
+							Ok(res) => Ok(res),
+							Err(err) => api_helper::error::handle_rejection(
+								&config,
+								err,
+								http::response::Builder::new(),
+								ray_id,
+							),
+						};
+					}
+
 					// Create router config for complex nested routers
 					let mut router_config = match api_helper::macro_util::__RouterConfig::new(&config, request.uri()) {
 						Ok(x) => x,
@@ -466,6 +529,35 @@ impl Parse for Endpoint {
 }
 
 impl Endpoint {
+	/// Renders a `RouteMeta` for each function in this endpoint block.
+	fn render_metas(&self) -> syn::Result<Vec<TokenStream2>> {
+		let path_meta = self
+			.path_segments
+			.iter()
+			.map(|segment| match segment {
+				RequestPathSegment::LitStr(lit) => {
+					quote! { api_helper::openapi::PathSegment::Literal(#lit) }
+				}
+				RequestPathSegment::Type(ty) => {
+					let ty_label = ty.to_token_stream().to_string().replace(' ', "");
+					let schema = schema_fn(ty.to_token_stream());
+
+					quote! {
+						api_helper::openapi::PathSegment::Param {
+							ty: #ty_label,
+							schema: #schema,
+						}
+					}
+				}
+			})
+			.collect::<Vec<_>>();
+
+		self.functions
+			.iter()
+			.map(|func| func.render_meta(&path_meta))
+			.collect()
+	}
+
 	fn render(self, cors_config: Option<syn::Expr>) -> syn::Result<TokenStream2> {
 		// Check cors at the endpoint level
 		let cors = if let Some(cors_config) = cors_config {
@@ -639,6 +731,25 @@ impl Parse for EndpointFunction {
 			);
 		}
 
+		// Idempotent endpoints hash the request body, so it must be buffered
+		if let Some(idempotent) = args.iter().find(|arg| arg.label == "idempotent") {
+			if args
+				.iter()
+				.any(|arg| arg.label == "body_as_bytes" || arg.label == "body_as_stream")
+			{
+				return Err(syn::Error::new(
+					idempotent.label.span(),
+					"`idempotent` is not supported with `body_as_bytes` or `body_as_stream`",
+				));
+			}
+			if req_type == "GET" {
+				return Err(syn::Error::new(
+					idempotent.label.span(),
+					"GET endpoints are already idempotent",
+				));
+			}
+		}
+
 		Ok(EndpointFunction {
 			path,
 			req_type,
@@ -649,12 +760,105 @@ impl Parse for EndpointFunction {
 }
 
 impl EndpointFunction {
+	fn render_meta(&self, path_meta: &[TokenStream2]) -> syn::Result<TokenStream2> {
+		let method = &self.req_type;
+		let handler = self.path.to_token_stream().to_string().replace(' ', "");
+
+		let query = self
+			.args
+			.iter()
+			.filter(|arg| arg.label == "query")
+			.map(|arg| Ok(schema_fn(arg.value.expect_expr()?.to_token_stream())))
+			.collect::<syn::Result<Vec<_>>>()?;
+
+		let body = if let Some(body_type) = &self.body {
+			let schema = schema_fn(body_type.to_token_stream());
+			quote! { Some(api_helper::openapi::BodyMeta::Json(#schema)) }
+		} else if self
+			.args
+			.iter()
+			.any(|arg| arg.label == "body_as_bytes" || arg.label == "body_as_stream")
+		{
+			quote! { Some(api_helper::openapi::BodyMeta::Bytes) }
+		} else {
+			quote! { None }
+		};
+
+		// Endpoints that write to the response builder or read the body as a stream take references,
+		// so their return type can't be probed
+		let response = if self
+			.args
+			.iter()
+			.any(|arg| arg.label == "with_response" || arg.label == "body_as_stream")
+		{
+			quote! { None }
+		} else {
+			let path = &self.path;
+
+			quote! {
+				Some(|gen: &mut api_helper::openapi::SchemaGenerator| {
+					#[allow(unused_imports)]
+					use api_helper::openapi::{__ViaJsonSchema, __ViaTypeName};
+
+					(&api_helper::openapi::__response_probe(&#path)).__schema(gen)
+				})
+			}
+		};
+
+		let internal = if let Some(internal_endpoint) = self
+			.args
+			.iter()
+			.find(|arg| arg.label == "internal_endpoint")
+		{
+			let value = internal_endpoint.value.expect_expr()?;
+
+			quote! { #value }
+		} else {
+			quote! { false }
+		};
+
+		let idempotent = self.idempotent()?;
+
+		Ok(quote! {
+			api_helper::openapi::RouteMeta {
+				method: #method,
+				path: vec![ #(#path_meta),* ],
+				handler: #handler,
+				query: vec![ #(#query),* ],
+				body: #body,
+				response: #response,
+				internal: #internal,
+				idempotent: #idempotent,
+				mounts: Vec::new(),
+			}
+		})
+	}
+
+	/// If this endpoint honors the `Idempotency-Key` header.
+	fn idempotent(&self) -> syn::Result<bool> {
+		let Some(idempotent) = self.args.iter().find(|arg| arg.label == "idempotent") else {
+			return Ok(false);
+		};
+
+		let value = idempotent.value.expect_expr()?;
+		if let syn::Expr::Lit(syn::ExprLit {
+			lit: syn::Lit::Bool(syn::LitBool { value, .. }),
+			..
+		}) = value
+		{
+			Ok(*value)
+		} else {
+			Err(syn::Error::new(value.span(), "Expected boolean"))
+		}
+	}
+
 	fn render(
 		self,
 		mut arg_list: Vec<TokenStream2>,
 		metrics_path: Literal,
 	) -> syn::Result<TokenStream2> {
 		let req_type = format_ident!("{}", self.req_type);
+		let idempotent = self.idempotent()?;
 		let path = self.path;
 
 		let metrics_method = Literal::string(&self.req_type);
@@ -664,12 +868,43 @@ impl EndpointFunction {
 			arg_list.insert(0, quote! { response });
 		}
 
+		// Claim the idempotency key before running the endpoint. Replays the stored response if the
+		// request already completed.
+		let idempotency_begin = quote! {
+			let idempotency = match macro_util::__idempotency_begin(
+				&cache,
+				&request,
+				#metrics_method,
+				#metrics_path,
+				&body_bytes,
+			).await? {
+				macro_util::__IdempotencyBegin::None => None,
+				macro_util::__IdempotencyBegin::Acquired(idempotency) => Some(idempotency),
+				macro_util::__IdempotencyBegin::Replay(replay) => {
+					return Ok(__AsyncOption::Some(macro_util::__idempotency_replay(response, replay)));
+				}
+			};
+		};
+
 		// Get json body or anchor body
 		let json_or_anchor_body = if let Some(body_type) = self.body {
 			arg_list.push(format_ident!("body").to_token_stream());
 
+			if idempotent {
+				quote! {
+					let body_bytes = macro_util::__read_body_bytes(&mut request).await?;
+					#idempotency_begin
+					let body = macro_util::__deserialize_body_bytes::<#body_type>(&body_bytes)?;
+				}
+			} else {
+				quote! {
+					let body = macro_util::__deserialize_body::<#body_type>(&mut request).await?;
+				}
+			}
+		} else if idempotent {
 			quote! {
-				let body = macro_util::__deserialize_body::<#body_type>(&mut request).await?;
+				let body_bytes = macro_util::__read_body_bytes(&mut request).await?;
+				#idempotency_begin
 			}
 		} else if self.args.iter().any(|arg| arg.label == "body_as_bytes") {
 			arg_list.push(format_ident!("body").to_token_stream());
@@ -835,6 +1070,16 @@ impl EndpointFunction {
 			quote! { serde_json::to_vec(&body)? }
 		};
 
+		let idempotency_complete = if idempotent {
+			quote! {
+				if let Some(idempotency) = idempotency {
+					macro_util::__idempotency_complete(&config, &cache, ray_id, idempotency, &response).await;
+				}
+			}
+		} else {
+			quote! {}
+		};
+
 		// Collect arg lines
 		// MARK: Simple argument parsing
 		let args = self
@@ -993,12 +1238,27 @@ impl EndpointFunction {
 					tracing::warn!("metrics complete receiver dropped");
 				}
 
+				#idempotency_complete
+
 				response
 			},
 		})
 	}
 }
 
+/// Renders a function that generates the schema for a type. Falls back to referencing the type by
+/// name if it does not implement `JsonSchema`.
+fn schema_fn(ty: TokenStream2) -> TokenStream2 {
+	quote! {
+		|gen: &mut api_helper::openapi::SchemaGenerator| {
+			#[allow(unused_imports)]
+			use api_helper::openapi::{__ViaJsonSchema, __ViaTypeName};
+
+			(&api_helper::openapi::__SchemaProbe::<#ty>::new()).__schema(gen)
+		}
+	}
+}
+
 // TODO: Replace with SimpleMap
 /// A single argument inside of the `EndpointFunction` call body.
 /// ```rust
@@ -0,0 +1,25 @@
+local request_hash = ARGV[1]
+local pending_ttl_ms = tonumber(ARGV[2])
+
+local key = KEYS[1]
+
+local existing_hash = redis.call('HGET', key, 'request_hash')
+
+-- First request with this key, acquire it
+if existing_hash == false then
+	redis.call('HSET', key, 'request_hash', request_hash, 'state', 'pending')
+	redis.call('PEXPIRE', key, pending_ttl_ms)
+	return {'acquired'}
+end
+
+-- Same key used for a different request
+if existing_hash ~= request_hash then
+	return {'conflict'}
+end
+
+local state = redis.call('HGET', key, 'state')
+if state == 'complete' then
+	return {'complete', redis.call('HGET', key, 'response')}
+end
+
+return {'pending'}
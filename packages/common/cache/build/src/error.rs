@@ -13,6 +13,9 @@ pub enum Error {
 	#[error("connect redis: {0}")]
 	ConnectRedis(redis::RedisError),
 
+	#[error("redis: {0}")]
+	Redis(redis::RedisError),
+
 	#[error("getter: {0}")]
 	Getter(GlobalError),
 
@@ -30,4 +33,7 @@ pub enum Error {
 
 	#[error("optimistic lock failed too many times")]
 	OptimisticLockFailedTooManyTimes,
+
+	#[error("missing idempotent response")]
+	MissingIdempotentResponse,
 }
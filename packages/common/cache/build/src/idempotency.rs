@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+lazy_static::lazy_static! {
+	static ref BEGIN_SCRIPT: redis::Script =
+		redis::Script::new(include_str!("../redis-scripts/idempotency_begin.lua"));
+}
+
+pub struct IdempotencyConfig {
+	/// Scope of the key. Requests with the same idempotency key in different scopes are unrelated.
+	pub scope: String,
+	/// How long an in-progress request holds the key before another request can take over.
+	pub pending_ttl_ms: i64,
+	/// How long a completed response is replayed for.
+	pub ttl_ms: i64,
+}
+
+/// A response stored for replaying to retries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotentResponse {
+	pub status: u16,
+	pub body: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum IdempotencyState {
+	/// No request has been made with this key. The response must be stored with
+	/// `idempotency_complete` or released with `idempotency_release`.
+	Acquired,
+	/// A request with this key already completed.
+	Complete(IdempotentResponse),
+	/// A request with this key is still being processed.
+	InProgress,
+	/// The key was already used for a request with a different hash.
+	Conflict,
+}
+
+impl CacheInner {
+	/// Claims an idempotency key for a request or returns the state of the existing request.
+	#[tracing::instrument(skip(self, config))]
+	pub async fn idempotency_begin(
+		&self,
+		config: &IdempotencyConfig,
+		idempotency_key: &str,
+		request_hash: &str,
+	) -> Result<IdempotencyState, Error> {
+		let key = self.build_redis_idempotency_key(&config.scope, idempotency_key);
+
+		let mut conn = self.redis_conn.clone();
+		let res = BEGIN_SCRIPT
+			.key(&key)
+			.arg(request_hash)
+			.arg(config.pending_ttl_ms)
+			.invoke_async::<_, Vec<Vec<u8>>>(&mut conn)
+			.await
+			.map_err(Error::Redis)?;
+
+		let state = match res.first().map(Vec::as_slice) {
+			Some(b"acquired") => IdempotencyState::Acquired,
+			Some(b"conflict") => IdempotencyState::Conflict,
+			Some(b"pending") => IdempotencyState::InProgress,
+			Some(b"complete") => {
+				let response = res.get(1).ok_or(Error::MissingIdempotentResponse)?;
+				IdempotencyState::Complete(
+					serde_json::from_slice(response).map_err(Error::SerdeDecode)?,
+				)
+			}
+			_ => return Err(Error::MissingIdempotentResponse),
+		};
+
+		tracing::debug!(?key, ?state, "idempotency key state");
+
+		Ok(state)
+	}
+
+	/// Stores the response for a key acquired with `idempotency_begin`.
+	#[tracing::instrument(skip(self, config, response))]
+	pub async fn idempotency_complete(
+		&self,
+		config: &IdempotencyConfig,
+		idempotency_key: &str,
+		response: &IdempotentResponse,
+	) -> Result<(), Error> {
+		let key = self.build_redis_idempotency_key(&config.scope, idempotency_key);
+		let response = serde_json::to_vec(response).map_err(Error::SerdeEncode)?;
+
+		let mut conn = self.redis_conn.clone();
+		let mut pipe = redis::pipe();
+		pipe.atomic();
+		pipe.hset_multiple(
+			&key,
+			&[("state", b"complete".as_slice()), ("response", response.as_slice())],
+		)
+		.ignore();
+		pipe.pexpire(&key, config.ttl_ms as usize).ignore();
+		pipe.query_async::<_, ()>(&mut conn)
+			.await
+			.map_err(Error::Redis)?;
+
+		Ok(())
+	}
+
+	/// Releases a key acquired with `idempotency_begin` without storing a response so the request
+	/// can be retried. Used when the request failed.
+	#[tracing::instrument(skip(self, config))]
+	pub async fn idempotency_release(
+		&self,
+		config: &IdempotencyConfig,
+		idempotency_key: &str,
+	) -> Result<(), Error> {
+		let key = self.build_redis_idempotency_key(&config.scope, idempotency_key);
+
+		let mut conn = self.redis_conn.clone();
+		redis::cmd("DEL")
+			.arg(&key)
+			.query_async::<_, ()>(&mut conn)
+			.await
+			.map_err(Error::Redis)?;
+
+		Ok(())
+	}
+}
@@ -67,6 +67,10 @@ impl CacheInner {
 			bucket,
 		)
 	}
+
+	pub(crate) fn build_redis_idempotency_key(&self, scope: &str, idempotency_key: &str) -> String {
+		format!("{{global}}:cache:idempotency:{scope}:{idempotency_key}")
+	}
 }
 
 impl CacheInner {
@@ -1,5 +1,6 @@
 mod error;
 mod getter_ctx;
+mod idempotency;
 mod inner;
 mod key;
 mod metrics;
@@ -8,6 +9,7 @@ mod req_config;
 
 pub use error::*;
 pub use getter_ctx::*;
+pub use idempotency::*;
 pub use inner::*;
 pub use key::*;
 pub use rate_limit::*;
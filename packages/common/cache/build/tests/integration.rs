@@ -117,3 +117,123 @@ async fn smoke_test() {
 	}
 	futures_util::future::try_join_all(handles).await.unwrap();
 }
+
+fn idempotency_config(pending_ttl_ms: i64) -> tivet_cache::IdempotencyConfig {
+	tivet_cache::IdempotencyConfig {
+		scope: format!("test:{}", Uuid::new_v4()),
+		pending_ttl_ms,
+		ttl_ms: 60_000,
+	}
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idempotency_replay() {
+	let cache = build_cache().await;
+	let config = idempotency_config(60_000);
+
+	let state = cache
+		.idempotency_begin(&config, "key", "hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::Acquired));
+
+	// Retries while the first request is running
+	let state = cache
+		.idempotency_begin(&config, "key", "hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::InProgress));
+
+	// Failed responses are replayed with their original status
+	cache
+		.idempotency_complete(
+			&config,
+			"key",
+			&tivet_cache::IdempotentResponse {
+				status: 400,
+				body: b"{\"code\":\"ERROR\"}".to_vec(),
+			},
+		)
+		.await
+		.unwrap();
+
+	match cache
+		.idempotency_begin(&config, "key", "hash")
+		.await
+		.unwrap()
+	{
+		tivet_cache::IdempotencyState::Complete(response) => {
+			assert_eq!(400, response.status);
+			assert_eq!(b"{\"code\":\"ERROR\"}".as_slice(), response.body.as_slice());
+		}
+		state => panic!("unexpected state {state:?}"),
+	}
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idempotency_conflict() {
+	let cache = build_cache().await;
+	let config = idempotency_config(60_000);
+
+	let state = cache
+		.idempotency_begin(&config, "key", "hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::Acquired));
+
+	// Same key with a different request
+	let state = cache
+		.idempotency_begin(&config, "key", "other-hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::Conflict));
+
+	// Keys in other scopes are unrelated
+	let other_config = idempotency_config(60_000);
+	let state = cache
+		.idempotency_begin(&other_config, "key", "other-hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::Acquired));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idempotency_release() {
+	let cache = build_cache().await;
+	let config = idempotency_config(60_000);
+
+	let state = cache
+		.idempotency_begin(&config, "key", "hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::Acquired));
+
+	cache.idempotency_release(&config, "key").await.unwrap();
+
+	// Released keys can be reused, even for a different request
+	let state = cache
+		.idempotency_begin(&config, "key", "other-hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::Acquired));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idempotency_pending_expires() {
+	let cache = build_cache().await;
+	let config = idempotency_config(500);
+
+	let state = cache
+		.idempotency_begin(&config, "key", "hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::Acquired));
+
+	// The request never completed, another request can take over the key
+	tokio::time::sleep(Duration::from_secs(1)).await;
+	let state = cache
+		.idempotency_begin(&config, "key", "hash")
+		.await
+		.unwrap();
+	assert!(matches!(state, tivet_cache::IdempotencyState::Acquired));
+}
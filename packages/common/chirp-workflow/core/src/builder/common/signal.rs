@@ -86,7 +86,14 @@ impl<T: Signal + Serialize> SignalBuilder<T> {
 				tracing::debug!(signal_name=%T::NAME, to_workflow_id=%workflow_id, %signal_id, "dispatching signal");
 
 				self.db
-					.publish_signal(self.ray_id, workflow_id, signal_id, T::NAME, &input_val)
+					.publish_signal(
+						self.ray_id,
+						tivet_runtime::otel::traceparent().as_deref(),
+						workflow_id,
+						signal_id,
+						T::NAME,
+						&input_val,
+					)
 					.await
 					.map_err(GlobalError::raw)?;
 			}
@@ -96,6 +103,7 @@ impl<T: Signal + Serialize> SignalBuilder<T> {
 				self.db
 					.publish_tagged_signal(
 						self.ray_id,
+						tivet_runtime::otel::traceparent().as_deref(),
 						&serde_json::Value::Object(self.tags),
 						signal_id,
 						T::NAME,
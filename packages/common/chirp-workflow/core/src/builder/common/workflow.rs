@@ -117,6 +117,7 @@ where
 			.db
 			.dispatch_workflow(
 				self.ray_id,
+				tivet_runtime::otel::traceparent().as_deref(),
 				workflow_id,
 				workflow_name,
 				tags,
@@ -129,6 +129,7 @@ impl<'a, T: Signal + Serialize> SignalBuilder<'a, T> {
 							&location,
 							self.version,
 							self.ctx.ray_id(),
+							tivet_runtime::otel::traceparent().as_deref(),
 							workflow_id,
 							signal_id,
 							T::NAME,
@@ -155,6 +156,7 @@ impl<'a, T: Signal + Serialize> SignalBuilder<'a, T> {
 							&location,
 							self.version,
 							self.ctx.ray_id(),
+							tivet_runtime::otel::traceparent().as_deref(),
 							&serde_json::Value::Object(self.tags),
 							signal_id,
 							T::NAME,
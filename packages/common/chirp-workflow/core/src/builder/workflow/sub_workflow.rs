@@ -166,6 +166,7 @@ where
 				.db()
 				.dispatch_sub_workflow(
 					ctx.ray_id(),
+					tivet_runtime::otel::traceparent().as_deref(),
 					ctx.workflow_id(),
 					&location,
 					version,
@@ -84,6 +84,9 @@ impl<'a> ListenCtx<'a> {
 			"signal received",
 		);
 
+		// The signal's publisher belongs to a different trace than this workflow
+		tivet_runtime::otel::add_link(&tracing::Span::current(), signal.traceparent.as_deref());
+
 		Ok(signal)
 	}
 }
@@ -116,6 +116,7 @@ impl MessageCtx {
 			ray_id: self.ray_id,
 			tags: tags.as_tags()?,
 			ts,
+			traceparent: tivet_runtime::otel::traceparent(),
 			body: &body_buf,
 		};
 		let message_buf = serde_json::to_vec(&message).map_err(WorkflowError::SerializeMessage)?;
@@ -3,6 +3,7 @@ use std::{sync::Arc, time::Instant};
 use global_error::{GlobalError, GlobalResult};
 use serde::{de::DeserializeOwned, Serialize};
 use tokio::time::Duration;
+use tracing::Instrument;
 use uuid::Uuid;
 
 use crate::{
@@ -261,8 +262,11 @@ impl WorkflowCtx {
 
 		let start_instant = Instant::now();
 
-		let res = tokio::time::timeout(A::TIMEOUT, A::run(&ctx, input))
-			.await
+		let res = tokio::time::timeout(
+			A::TIMEOUT,
+			A::run(&ctx, input).instrument(tracing::info_span!("activity", name = A::NAME)),
+		)
+		.await
 			.map_err(|_| WorkflowError::ActivityTimeout(0));
 
 		let dt = start_instant.elapsed().as_secs_f64();
@@ -161,6 +161,7 @@ impl Database for DatabaseCrdbNats {
 	async fn dispatch_workflow(
 		&self,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		workflow_id: Uuid,
 		workflow_name: &str,
 		tags: Option<&serde_json::Value>,
@@ -183,9 +184,9 @@ impl Database for DatabaseCrdbNats {
 					),
 					insert_workflow AS (
 						INSERT INTO db_workflow.workflows (
-							workflow_id, workflow_name, create_ts, ray_id, tags, input, wake_immediate
+							workflow_id, workflow_name, create_ts, ray_id, tags, input, wake_immediate, traceparent
 						)
-						SELECT $1, $2, $3, $4, $5, $6, true
+						SELECT $1, $2, $3, $4, $5, $6, true, $7
 						WHERE NOT EXISTS(SELECT 1 FROM select_existing)
 						RETURNING workflow_id
 					)
@@ -199,9 +200,9 @@ impl Database for DatabaseCrdbNats {
 			indoc!(
 				"
 				INSERT INTO db_workflow.workflows (
-					workflow_id, workflow_name, create_ts, ray_id, tags, input, wake_immediate
+					workflow_id, workflow_name, create_ts, ray_id, tags, input, wake_immediate, traceparent
 				)
-				VALUES ($1, $2, $3, $4, $5, $6, true)
+				VALUES ($1, $2, $3, $4, $5, $6, true, $7)
 				RETURNING workflow_id
 				"
 			)
@@ -218,6 +219,7 @@ impl Database for DatabaseCrdbNats {
 					ray_id,
 					tags,
 					sqlx::types::Json(input),
+					traceparent,
 				)
 				.await
 			})
@@ -361,7 +363,7 @@ impl Database for DatabaseCrdbNats {
 								last_pull_ts = $3
 							FROM select_pending_workflows AS pw
 							WHERE w.workflow_id = pw.workflow_id
-							RETURNING w.workflow_id, workflow_name, create_ts, ray_id, input, wake_deadline_ts
+							RETURNING w.workflow_id, workflow_name, create_ts, ray_id, traceparent, input, wake_deadline_ts
 						),
 						-- Update last ping
 						worker_instance_update AS (
@@ -865,7 +867,7 @@ impl Database for DatabaseCrdbNats {
 						-- Finds the oldest signal matching the signal name filter in either the normal signals table
 						-- or tagged signals table
 						next_signal AS (
-							SELECT false AS tagged, signal_id, create_ts, signal_name, body
+							SELECT false AS tagged, signal_id, create_ts, signal_name, body, traceparent
 							FROM db_workflow.signals@signals_partial
 							WHERE
 								workflow_id = $1 AND
@@ -873,7 +875,7 @@ impl Database for DatabaseCrdbNats {
 								ack_ts IS NULL AND
 								silence_ts IS NULL
 							UNION ALL
-							SELECT true AS tagged, signal_id, s.create_ts, signal_name, body
+							SELECT true AS tagged, signal_id, s.create_ts, signal_name, body, s.traceparent
 							FROM db_workflow.tagged_signals@tagged_signals_partial AS s
 							JOIN db_workflow.workflows AS w
 							ON s.tags <@ w.tags
@@ -951,6 +953,7 @@ impl Database for DatabaseCrdbNats {
 	async fn publish_signal(
 		&self,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		workflow_id: Uuid,
 		signal_id: Uuid,
 		signal_name: &str,
@@ -961,9 +964,9 @@ impl Database for DatabaseCrdbNats {
 				[self]
 				"
 				INSERT INTO db_workflow.signals (
-					signal_id, workflow_id, signal_name, body, ray_id, create_ts
+					signal_id, workflow_id, signal_name, body, ray_id, create_ts, traceparent
 				)			
-				VALUES ($1, $2, $3, $4, $5, $6)
+				VALUES ($1, $2, $3, $4, $5, $6, $7)
 				",
 				signal_id,
 				workflow_id,
@@ -971,6 +974,7 @@ impl Database for DatabaseCrdbNats {
 				sqlx::types::Json(body),
 				ray_id,
 				tivet_util::timestamp::now(),
+				traceparent,
 			)
 			.await
 		})
@@ -984,6 +988,7 @@ impl Database for DatabaseCrdbNats {
 	async fn publish_tagged_signal(
 		&self,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		tags: &serde_json::Value,
 		signal_id: Uuid,
 		signal_name: &str,
@@ -994,9 +999,9 @@ impl Database for DatabaseCrdbNats {
 				[self]
 				"
 				INSERT INTO db_workflow.tagged_signals (
-					signal_id, tags, signal_name, body, ray_id, create_ts
+					signal_id, tags, signal_name, body, ray_id, create_ts, traceparent
 				)			
-				VALUES ($1, $2, $3, $4, $5, $6)
+				VALUES ($1, $2, $3, $4, $5, $6, $7)
 				",
 				signal_id,
 				tags,
@@ -1004,6 +1009,7 @@ impl Database for DatabaseCrdbNats {
 				sqlx::types::Json(body),
 				ray_id,
 				tivet_util::timestamp::now(),
+				traceparent,
 			)
 			.await
 		})
@@ -1020,6 +1026,7 @@ impl Database for DatabaseCrdbNats {
 		location: &Location,
 		version: usize,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		to_workflow_id: Uuid,
 		signal_id: Uuid,
 		signal_name: &str,
@@ -1033,9 +1040,9 @@ impl Database for DatabaseCrdbNats {
 				WITH
 					signal AS (
 						INSERT INTO db_workflow.signals (
-							signal_id, workflow_id, signal_name, body, ray_id, create_ts
+							signal_id, workflow_id, signal_name, body, ray_id, create_ts, traceparent
 						)			
-						VALUES ($1, $2, $3, $4, $5, $6)
+						VALUES ($1, $2, $3, $4, $5, $6, $11)
 						RETURNING 1
 					),
 					send_event AS (
@@ -1057,6 +1064,7 @@ impl Database for DatabaseCrdbNats {
 				location,
 				version as i64,
 				loop_location,
+				traceparent,
 			)
 			.await
 		})
@@ -1073,6 +1081,7 @@ impl Database for DatabaseCrdbNats {
 		location: &Location,
 		version: usize,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		tags: &serde_json::Value,
 		signal_id: Uuid,
 		signal_name: &str,
@@ -1086,9 +1095,9 @@ impl Database for DatabaseCrdbNats {
 				WITH
 					signal AS (
 						INSERT INTO db_workflow.tagged_signals (
-							signal_id, tags, signal_name, body, ray_id, create_ts
+							signal_id, tags, signal_name, body, ray_id, create_ts, traceparent
 						)			
-						VALUES ($1, $2, $3, $4, $5, $6)
+						VALUES ($1, $2, $3, $4, $5, $6, $11)
 						RETURNING 1
 					),
 					send_event AS (
@@ -1110,6 +1119,7 @@ impl Database for DatabaseCrdbNats {
 				location,
 				version as i64,
 				loop_location,
+				traceparent,
 			)
 			.await
 		})
@@ -1123,6 +1133,7 @@ impl Database for DatabaseCrdbNats {
 	async fn dispatch_sub_workflow(
 		&self,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		workflow_id: Uuid,
 		location: &Location,
 		version: usize,
@@ -1149,9 +1160,9 @@ impl Database for DatabaseCrdbNats {
 					),
 					insert_workflow AS (
 						INSERT INTO db_workflow.workflows (
-							workflow_id, workflow_name, create_ts, ray_id, tags, input, wake_immediate
+							workflow_id, workflow_name, create_ts, ray_id, tags, input, wake_immediate, traceparent
 						)
-						SELECT $9, $2, $3, $4, $5, $6, true
+						SELECT $9, $2, $3, $4, $5, $6, true, $11
 						WHERE NOT EXISTS(SELECT 1 FROM select_existing)
 						RETURNING workflow_id
 					),
@@ -1175,9 +1186,9 @@ impl Database for DatabaseCrdbNats {
 				WITH
 					insert_workflow AS (
 						INSERT INTO db_workflow.workflows (
-							workflow_id, workflow_name, create_ts, ray_id, tags, input, wake_immediate
+							workflow_id, workflow_name, create_ts, ray_id, tags, input, wake_immediate, traceparent
 						)
-						VALUES ($9, $2, $3, $4, $5, $6, true)
+						VALUES ($9, $2, $3, $4, $5, $6, true, $11)
 						RETURNING workflow_id
 					),
 					insert_sub_workflow_event AS (
@@ -1205,6 +1216,7 @@ impl Database for DatabaseCrdbNats {
 					.bind(version as i64)
 					.bind(sub_workflow_id)
 					.bind(loop_location)
+					.bind(traceparent)
 					.fetch_one(&mut *self.conn().await?)
 					.await
 					.map_err(WorkflowError::Sqlx)
@@ -1606,6 +1618,7 @@ mod types {
 		workflow_name: String,
 		create_ts: i64,
 		ray_id: Uuid,
+		traceparent: Option<String>,
 		input: RawJson,
 		wake_deadline_ts: Option<i64>,
 	}
@@ -1616,6 +1629,7 @@ mod types {
 		signal_name: String,
 		body: RawJson,
 		create_ts: i64,
+		traceparent: Option<String>,
 	}
 
 	impl From<SignalRow> for SignalData {
@@ -1625,6 +1639,7 @@ mod types {
 				signal_name: value.signal_name,
 				body: value.body.0,
 				create_ts: value.create_ts,
+				traceparent: value.traceparent,
 			}
 		}
 	}
@@ -2024,6 +2039,7 @@ mod types {
 					workflow_name: row.workflow_name,
 					create_ts: row.create_ts,
 					ray_id: row.ray_id,
+					traceparent: row.traceparent,
 					input: row.input.0,
 					wake_deadline_ts: row.wake_deadline_ts,
 					events: events_by_location,
@@ -33,6 +33,7 @@ pub trait Database: Send {
 	async fn dispatch_workflow(
 		&self,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		workflow_id: Uuid,
 		workflow_name: &str,
 		tags: Option<&serde_json::Value>,
@@ -102,6 +103,7 @@ pub trait Database: Send {
 	async fn publish_signal(
 		&self,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		workflow_id: Uuid,
 		signal_id: Uuid,
 		signal_name: &str,
@@ -112,6 +114,7 @@ pub trait Database: Send {
 	async fn publish_tagged_signal(
 		&self,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		tags: &serde_json::Value,
 		signal_id: Uuid,
 		signal_name: &str,
@@ -125,6 +128,7 @@ pub trait Database: Send {
 		location: &Location,
 		version: usize,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		workflow_id: Uuid,
 		signal_id: Uuid,
 		signal_name: &str,
@@ -139,6 +143,7 @@ pub trait Database: Send {
 		location: &Location,
 		version: usize,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		tags: &serde_json::Value,
 		signal_id: Uuid,
 		signal_name: &str,
@@ -150,6 +155,7 @@ pub trait Database: Send {
 	async fn dispatch_sub_workflow(
 		&self,
 		ray_id: Uuid,
+		traceparent: Option<&str>,
 		workflow_id: Uuid,
 		location: &Location,
 		version: usize,
@@ -252,6 +258,8 @@ pub struct PulledWorkflow {
 	pub workflow_name: String,
 	pub create_ts: i64,
 	pub ray_id: Uuid,
+	/// W3C trace context of the dispatcher.
+	pub traceparent: Option<String>,
 	pub input: Box<serde_json::value::RawValue>,
 	pub wake_deadline_ts: Option<i64>,
 
@@ -263,4 +271,6 @@ pub struct SignalData {
 	pub signal_name: String,
 	pub body: Box<serde_json::value::RawValue>,
 	pub create_ts: i64,
+	/// W3C trace context of the publisher.
+	pub traceparent: Option<String>,
 }
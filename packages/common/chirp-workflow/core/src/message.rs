@@ -75,6 +75,7 @@ where
 	pub(crate) ray_id: Uuid,
 	pub(crate) req_id: Uuid,
 	pub(crate) ts: i64,
+	pub(crate) traceparent: Option<String>,
 	pub(crate) body: M,
 }
 
@@ -101,6 +102,7 @@ where
 			ray_id: wrapper.ray_id,
 			req_id: wrapper.req_id,
 			ts: wrapper.ts,
+			traceparent: wrapper.traceparent,
 			body,
 		})
 	}
@@ -143,6 +145,11 @@ where
 	pub fn body(&self) -> &M {
 		&self.body
 	}
+
+	/// W3C trace context of the publisher.
+	pub fn traceparent(&self) -> Option<&str> {
+		self.traceparent.as_deref()
+	}
 }
 
 #[derive(Serialize, Deserialize)]
@@ -151,6 +158,8 @@ pub(crate) struct NatsMessageWrapper<'a> {
 	pub(crate) req_id: Uuid,
 	pub(crate) tags: serde_json::Value,
 	pub(crate) ts: i64,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub(crate) traceparent: Option<String>,
 	#[serde(borrow)]
 	pub(crate) body: &'a serde_json::value::RawValue,
 }
@@ -181,3 +190,48 @@ pub mod redis_keys {
 		pub const BODY: &str = "b";
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use uuid::Uuid;
+
+	use super::NatsMessageWrapper;
+
+	const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+	#[test]
+	fn wrapper_without_traceparent() {
+		// Published by workers without trace context propagation
+		let buf = serde_json::json!({
+			"ray_id": Uuid::nil(),
+			"req_id": Uuid::nil(),
+			"tags": {},
+			"ts": 0,
+			"body": { "foo": "bar" },
+		})
+		.to_string();
+		let wrapper = serde_json::from_str::<NatsMessageWrapper>(&buf).unwrap();
+		assert!(wrapper.traceparent.is_none());
+
+		// Omitted when not set
+		let buf = serde_json::to_value(&wrapper).unwrap();
+		assert!(buf.get("traceparent").is_none());
+	}
+
+	#[test]
+	fn wrapper_with_traceparent() {
+		let body = serde_json::value::RawValue::from_string("{}".to_string()).unwrap();
+		let wrapper = NatsMessageWrapper {
+			ray_id: Uuid::new_v4(),
+			req_id: Uuid::new_v4(),
+			tags: serde_json::json!({}),
+			ts: 0,
+			traceparent: Some(TRACEPARENT.to_string()),
+			body: &body,
+		};
+
+		let buf = serde_json::to_string(&wrapper).unwrap();
+		let wrapper = serde_json::from_str::<NatsMessageWrapper>(&buf).unwrap();
+		assert_eq!(Some(TRACEPARENT), wrapper.traceparent.as_deref());
+	}
+}
@@ -107,6 +107,15 @@ impl Worker {
 			.pull_workflows(self.worker_instance_id, &filter)
 			.await?;
 		for workflow in workflows {
+			// Every run of the workflow continues the trace of whatever dispatched it
+			let workflow_span = tracing::info_span!(
+				"workflow",
+				name = %workflow.workflow_name,
+				id = %workflow.workflow_id,
+				ray_id = %workflow.ray_id,
+			);
+			tivet_runtime::otel::set_parent(&workflow_span, workflow.traceparent.as_deref(), None);
+
 			let conn = utils::new_conn(
 				shared_client,
 				pools,
@@ -130,7 +139,7 @@ impl Worker {
 						tracing::error!(?err, "unhandled error");
 					}
 				}
-				.in_current_span(),
+				.instrument(workflow_span),
 			);
 		}
 
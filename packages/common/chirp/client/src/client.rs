@@ -389,6 +389,7 @@ impl Client {
 			body: req_body_buf,
 			debug: req_debug,
 			dont_log_body: req_dont_log_body,
+			traceparent: tivet_runtime::otel::traceparent(),
 		};
 		let mut req_buf = Vec::with_capacity(prost::Message::encoded_len(&req));
 		prost::Message::encode(&req, &mut req_buf).map_err(ClientError::EncodeRequest)?;
@@ -647,6 +648,7 @@ impl Client {
 			trace: (*self.trace).clone(),
 			allow_recursive: opts.allow_recursive,
 			body: body_buf,
+			traceparent: tivet_runtime::otel::traceparent(),
 		};
 		let mut message_buf = Vec::with_capacity(prost::Message::encoded_len(&message));
 		prost::Message::encode(&message, &mut message_buf).map_err(ClientError::EncodeMessage)?;
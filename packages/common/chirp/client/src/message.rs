@@ -62,6 +62,7 @@ where
 	pub(crate) req_id: Uuid,
 	pub(crate) ts: i64,
 	pub(crate) trace: Vec<TraceEntry>,
+	pub(crate) traceparent: Option<String>,
 	pub(crate) body: M,
 }
 
@@ -89,6 +90,7 @@ where
 			req_id,
 			ts: message.ts,
 			trace,
+			traceparent: message.traceparent,
 			body,
 		})
 	}
@@ -148,6 +150,11 @@ where
 	pub fn trace(&self) -> &[TraceEntry] {
 		&self.trace
 	}
+
+	/// W3C trace context of the publisher.
+	pub fn traceparent(&self) -> Option<&str> {
+		self.traceparent.as_deref()
+	}
 }
 
 #[derive(Debug)]
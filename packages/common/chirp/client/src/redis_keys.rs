@@ -7,6 +7,44 @@ pub fn message_topic(name: &str) -> String {
 	format!("{{topic:{name}}}:topic")
 }
 
+pub mod message_topic {
+	pub const MESSAGE: &str = "m";
+	/// If set, only the consumer group with this name handles the message. Used when replaying
+	/// dead-lettered messages.
+	pub const GROUP: &str = "g";
+}
+
+/// STREAM
+///
+/// Messages that exceeded the max delivery count for a consumer worker.
+pub fn dead_letter(worker_name: &str) -> String {
+	format!("{{dead-letter:{worker_name}}}:stream")
+}
+
+pub mod dead_letter {
+	pub const TOPIC_KEY: &str = "t";
+	pub const GROUP: &str = "g";
+	pub const MESSAGE_ID: &str = "i";
+	pub const MESSAGE: &str = "m";
+	pub const DELIVERY_COUNT: &str = "c";
+	pub const ERROR: &str = "e";
+	pub const TS: &str = "ts";
+}
+
+/// STRING
+///
+/// JSON of the last error returned by a consumer worker for a message. Expires on its own.
+pub fn dead_letter_error(worker_name: &str, message_id: &str) -> String {
+	format!("{{dead-letter:{worker_name}}}:error:{message_id}")
+}
+
+/// SET<worker name>
+///
+/// Workers with a dead-letter stream.
+pub fn dead_letter_workers() -> String {
+	"{dead-letter}:workers".to_string()
+}
+
 /// HASH
 pub fn message_tail<M, S>(parameters: &[S]) -> String
 where
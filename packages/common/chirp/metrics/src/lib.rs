@@ -34,4 +34,10 @@ lazy_static::lazy_static! {
 		&["context_name", "error_code", "error_type"],
 		*REGISTRY,
 	).unwrap();
+	pub static ref CHIRP_MESSAGE_DEAD_LETTERED: IntCounterVec = register_int_counter_vec_with_registry!(
+		"chirp_message_dead_lettered",
+		"Total number of messages moved to the dead-letter stream after too many deliveries.",
+		&["context_name"],
+		*REGISTRY,
+	).unwrap();
 }
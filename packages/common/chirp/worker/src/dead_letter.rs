@@ -0,0 +1,122 @@
+//! Moves consumer messages that keep failing to a per-worker dead-letter stream.
+
+use std::{collections::HashMap, time::Duration};
+
+use chirp_client::redis_keys;
+use redis::{self, AsyncCommands, RedisResult};
+use tivet_pools::prelude::*;
+use types_proto::tivet::chirp;
+
+/// How many times a message can be delivered to a consumer worker before it is moved to the
+/// dead-letter stream.
+pub const MAX_DELIVERY_COUNT: usize = 8;
+
+/// How long the last error of a message is kept for the dead-letter stream.
+pub const ERROR_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Approximate max length of the dead-letter stream of each worker.
+pub const MAX_LEN: usize = 10_000;
+
+/// Whether a message delivered `times_delivered` times should be dead-lettered instead of handled.
+pub fn exceeded_max_delivery(times_delivered: usize) -> bool {
+	times_delivered >= MAX_DELIVERY_COUNT
+}
+
+/// Whether a message should be handled by the consumer group. Replayed messages are only handled
+/// by the group that dead-lettered them.
+pub fn is_for_group(map: &HashMap<String, redis::Value>, group: &str) -> bool {
+	let Some(target_group) = map.get(redis_keys::message_topic::GROUP) else {
+		return true;
+	};
+
+	match redis::from_redis_value::<String>(target_group) {
+		Ok(target_group) => target_group == group,
+		Err(err) => {
+			tracing::warn!(?err, "could not decode message group, handling anyway");
+			true
+		}
+	}
+}
+
+/// Short description of a worker error to attach to dead-lettered messages.
+pub fn error_message(err: &chirp::response::Err) -> String {
+	match &err.kind {
+		Some(chirp::response::err::Kind::Internal(error)) => {
+			format!("{}: {}", error.ty, error.message)
+		}
+		Some(chirp::response::err::Kind::BadRequest(error)) => error.code.clone(),
+		None => "unknown error".to_string(),
+	}
+}
+
+/// Saves the last error of a consumer message so it can be attached to the message if it gets
+/// dead-lettered.
+pub async fn record_error(
+	redis: &mut RedisPool,
+	group: &str,
+	message_id: &str,
+	error: String,
+	traceparent: Option<String>,
+) -> RedisResult<()> {
+	let error = serde_json::json!({
+		"error": error,
+		"traceparent": traceparent,
+		"ts": tivet_util::timestamp::now(),
+	});
+
+	redis::cmd("SET")
+		.arg(redis_keys::dead_letter_error(group, message_id))
+		.arg(error.to_string())
+		.arg("EX")
+		.arg(ERROR_TTL.as_secs())
+		.query_async(redis)
+		.await
+}
+
+/// Writes a message to the worker's dead-letter stream along with its last error. Does not ack
+/// the message.
+pub async fn write(
+	redis: &mut RedisPool,
+	topic_key: &str,
+	group: &str,
+	message_id: &str,
+	msg_buf: &[u8],
+	times_delivered: usize,
+) -> RedisResult<()> {
+	let error_key = redis_keys::dead_letter_error(group, message_id);
+	let error = redis
+		.get::<_, Option<String>>(&error_key)
+		.await?
+		.unwrap_or_default();
+
+	redis::pipe()
+		.cmd("XADD")
+		.arg(redis_keys::dead_letter(group))
+		.arg("MAXLEN")
+		.arg("~")
+		.arg(MAX_LEN)
+		.arg("*")
+		.arg(redis_keys::dead_letter::TOPIC_KEY)
+		.arg(topic_key)
+		.arg(redis_keys::dead_letter::GROUP)
+		.arg(group)
+		.arg(redis_keys::dead_letter::MESSAGE_ID)
+		.arg(message_id)
+		.arg(redis_keys::dead_letter::MESSAGE)
+		.arg(msg_buf)
+		.arg(redis_keys::dead_letter::DELIVERY_COUNT)
+		.arg(times_delivered)
+		.arg(redis_keys::dead_letter::ERROR)
+		.arg(error)
+		.arg(redis_keys::dead_letter::TS)
+		.arg(tivet_util::timestamp::now())
+		.ignore()
+		.del(&error_key)
+		.ignore()
+		.query_async::<_, ()>(redis)
+		.await?;
+
+	redis
+		.sadd::<_, _, ()>(redis_keys::dead_letter_workers(), group)
+		.await
+}
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod dead_letter;
 mod error;
 mod macros;
 mod manager;
@@ -19,6 +19,7 @@ use types_proto::tivet::chirp;
 
 use crate::{
 	config::{Config as WorkerConfig, WorkerKind},
+	dead_letter,
 	error::ManagerError,
 	request::{RedisMessageMeta, Request},
 	worker::Worker,
@@ -422,18 +423,39 @@ where
 
 				// Determine whether or not to break the loop
 				claim_attempts += 1;
-				if !claimed_msgs.ids.is_empty() {
-					break claimed_msgs.ids;
-				} else if claim_attempts > 16 {
-					tracing::warn!("exceeded 16 claim attempts, breaking claim loop");
-					break claimed_msgs.ids;
+				if !claimed_msgs.ids.is_empty() || claim_attempts > 16 {
+					if claimed_msgs.ids.is_empty() {
+						tracing::warn!("exceeded 16 claim attempts, breaking claim loop");
+					}
+
+					// Pair claimed messages with how many times they've been delivered
+					break claimed_msgs
+						.ids
+						.into_iter()
+						.map(|msg| {
+							let times_delivered = pending_msgs
+								.ids
+								.iter()
+								.find(|x| x.id == msg.id)
+								.map_or(0, |x| x.times_delivered);
+
+							(msg, times_delivered)
+						})
+						.collect::<Vec<_>>();
 				} else {
 					tracing::debug!("no claimed messages, requesting more pending messages");
 				}
 			};
 
 			// Handle found messages
-			'msg: for msg in claimed_msgs {
+			'msg: for (msg, times_delivered) in claimed_msgs {
+				if self
+					.skip_for_group(redis_chirp_conn, topic_key, group, &msg.id, &msg.map)
+					.await
+				{
+					continue 'msg;
+				}
+
 				let msg_value = if let Some(x) = msg.map.get("m") {
 					x
 				} else {
@@ -455,6 +477,27 @@ where
 					}
 				};
 
+				// Move poison messages to the dead-letter stream instead of retrying them forever
+				if dead_letter::exceeded_max_delivery(times_delivered) {
+					let spawn_res = tokio::task::Builder::new()
+						.name("chirp_worker::dead_letter")
+						.spawn(self.clone().dead_letter(
+							RedisMessageMeta {
+								topic_key: topic_key.to_owned(),
+								group: group.to_owned(),
+								id: msg.id,
+								parameters: None,
+							},
+							msg_buf,
+							times_delivered,
+						));
+					if let Err(err) = spawn_res {
+						tracing::error!(?err, "failed to spawn dead_letter task");
+					}
+
+					continue 'msg;
+				}
+
 				// Process the message
 				let spawn_res = tokio::task::Builder::new()
 					.name("chirp_worker::handle_raw_msg_consumer_pending")
@@ -501,6 +544,13 @@ where
 
 			tracing::trace!(len = key.ids.len(), "read stream messages");
 			'read_id: for id in &key.ids {
+				if self
+					.skip_for_group(redis_chirp_conn, topic_key, group, &id.id, &id.map)
+					.await
+				{
+					continue 'read_id;
+				}
+
 				let msg_value = if let Some(x) = id.map.get("m") {
 					x
 				} else {
@@ -574,6 +624,7 @@ where
 			dont_log_body,
 			req_debug,
 			allow_recursive,
+			traceparent,
 		) = match &self.worker_config.worker_kind {
 			WorkerKind::Rpc { .. } => {
 				match chirp::Request::decode(raw_msg_buf.as_slice()) {
@@ -638,6 +689,7 @@ where
 							req.dont_log_body,
 							req.debug,
 							false,
+							req.traceparent,
 						)
 					}
 					Err(err) => {
@@ -680,6 +732,7 @@ where
 						false,
 						None,
 						msg.allow_recursive,
+						msg.traceparent,
 					)
 				}
 				Err(err) => {
@@ -756,6 +809,7 @@ where
 				),
 				dont_log_body,
 				allow_recursive,
+				traceparent,
 			}
 		};
 
@@ -774,6 +828,13 @@ where
 	async fn handle_req(self: Arc<Self>, req: Request<W::Request>) {
 		let worker_name = req.op_ctx.name().to_string();
 
+		// Continue the publisher's trace
+		tivet_runtime::otel::set_parent(
+			&tracing::Span::current(),
+			req.traceparent.as_deref(),
+			None,
+		);
+
 		// Record metrics
 		metrics::CHIRP_REQUEST_PENDING
 			.with_label_values(&[&worker_name])
@@ -972,13 +1033,15 @@ where
 
 					let err_proto = Into::<chirp::response::Err>::into(err);
 
-					if let Some(chirp::response::err::Kind::BadRequest(error)) = &err_proto.kind {
+					let unrecoverable = matches!(
+						&err_proto.kind,
+						Some(chirp::response::err::Kind::BadRequest(error))
+							if error.code == formatted_error::code::CHIRP_RECURSIVE_REQUEST
+					);
+					if unrecoverable {
 						// Ack the message if recursive (i.e. this will never succeed
 						// again)
-						if let (Some(msg_meta), true) = (
-							req.redis_message_meta,
-							error.code == formatted_error::code::CHIRP_RECURSIVE_REQUEST,
-						) {
+						if let Some(msg_meta) = req.redis_message_meta {
 							tracing::error!(
 								?msg_meta,
 								"acking message because we can never recover from this error"
@@ -993,6 +1056,8 @@ where
 								);
 							}
 						}
+					} else if let Some(msg_meta) = req.redis_message_meta {
+						self.clone().spawn_record_error(msg_meta, &err_proto);
 					}
 
 					(
@@ -1015,6 +1080,10 @@ where
 					let err_proto =
 						Into::<chirp::response::Err>::into(err_code!(CHIRP_REQUEST_TIMEOUT));
 
+					if let Some(msg_meta) = req.redis_message_meta {
+						self.clone().spawn_record_error(msg_meta, &err_proto);
+					}
+
 					(
 						if let WorkerKind::Rpc { .. } = &self.worker_config.worker_kind {
 							Some(chirp::Response {
@@ -1080,6 +1149,104 @@ where
 		}
 		// }
 	}
+
+	/// Acks and skips messages that were replayed for a different consumer group.
+	async fn skip_for_group(
+		&self,
+		redis_chirp_conn: &mut RedisPool,
+		topic_key: &str,
+		group: &str,
+		id: &str,
+		map: &std::collections::HashMap<String, redis::Value>,
+	) -> bool {
+		if dead_letter::is_for_group(map, group) {
+			return false;
+		}
+
+		tracing::trace!(%id, "skipping message replayed for another group");
+		if let Err(err) = redis_chirp_conn
+			.xack::<_, _, _, ()>(topic_key, group, &[id])
+			.await
+		{
+			tracing::error!(?err, "failed to ack skipped message");
+		}
+
+		true
+	}
+
+	fn spawn_record_error(self: Arc<Self>, msg_meta: RedisMessageMeta, err: &chirp::response::Err) {
+		let error = dead_letter::error_message(err);
+
+		let spawn_res = tokio::task::Builder::new()
+			.name("chirp_worker::record_error")
+			.spawn(
+				self.record_error(msg_meta, error, tivet_runtime::otel::traceparent())
+					.in_current_span(),
+			);
+		if let Err(err) = spawn_res {
+			tracing::error!(?err, "failed to spawn record_error task");
+		}
+	}
+
+	/// Saves the last error of a consumer message so it can be attached to the message if it
+	/// gets dead-lettered.
+	#[tracing::instrument(skip(self))]
+	async fn record_error(
+		self: Arc<Self>,
+		msg_meta: RedisMessageMeta,
+		error: String,
+		traceparent: Option<String>,
+	) {
+		let mut redis_chirp = self.redis_chirp.clone();
+		if let Err(err) = dead_letter::record_error(
+			&mut redis_chirp,
+			&msg_meta.group,
+			&msg_meta.id,
+			error,
+			traceparent,
+		)
+		.await
+		{
+			tracing::error!(?err, "failed to record message error");
+		}
+	}
+
+	/// Moves a message that exceeded the max delivery count to the worker's dead-letter stream.
+	#[tracing::instrument(skip(self, msg_buf))]
+	async fn dead_letter(
+		self: Arc<Self>,
+		msg_meta: RedisMessageMeta,
+		msg_buf: Vec<u8>,
+		times_delivered: usize,
+	) {
+		tracing::warn!(
+			?msg_meta,
+			"message exceeded max delivery count, moving to dead-letter stream"
+		);
+
+		// The dead-letter stream lives in a different slot than the topic, so this can't be
+		// atomic. If acking fails, the message will be dead-lettered again on the next claim.
+		let mut redis_chirp = self.redis_chirp.clone();
+		if let Err(err) = dead_letter::write(
+			&mut redis_chirp,
+			&msg_meta.topic_key,
+			&msg_meta.group,
+			&msg_meta.id,
+			&msg_buf,
+			times_delivered,
+		)
+		.await
+		{
+			tracing::error!(?err, "failed to write message to dead-letter stream");
+			return;
+		}
+
+		metrics::CHIRP_MESSAGE_DEAD_LETTERED
+			.with_label_values(&[&msg_meta.group])
+			.inc();
+
+		self.consumer_ack(msg_meta).await;
+	}
 }
 
 enum PullRedisStatus {
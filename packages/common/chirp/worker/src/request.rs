@@ -38,6 +38,8 @@ where
 	pub(crate) op_ctx: OperationContext<B>,
 	pub(crate) dont_log_body: bool,
 	pub(crate) allow_recursive: bool,
+	/// W3C trace context of the caller.
+	pub(crate) traceparent: Option<String>,
 }
 
 impl<B> Request<B>
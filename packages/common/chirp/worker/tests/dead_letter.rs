@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use chirp_client::redis_keys;
+use chirp_worker::dead_letter;
+use redis::AsyncCommands;
+use tivet_pools::prelude::RedisPool;
+use types_proto::tivet::chirp;
+use uuid::Uuid;
+
+async fn redis() -> RedisPool {
+	let config = tivet_config::Config::load::<String>(&[]).await.unwrap();
+	let pools = tivet_pools::Pools::new(config).await.unwrap();
+	pools.redis_chirp().unwrap()
+}
+
+#[test]
+fn max_delivery_count() {
+	assert!(!dead_letter::exceeded_max_delivery(0));
+	assert!(!dead_letter::exceeded_max_delivery(
+		dead_letter::MAX_DELIVERY_COUNT - 1
+	));
+	assert!(dead_letter::exceeded_max_delivery(
+		dead_letter::MAX_DELIVERY_COUNT
+	));
+	assert!(dead_letter::exceeded_max_delivery(
+		dead_letter::MAX_DELIVERY_COUNT + 1
+	));
+}
+
+#[test]
+fn replayed_message_group() {
+	let mut map = HashMap::new();
+	map.insert(
+		redis_keys::message_topic::MESSAGE.to_string(),
+		redis::Value::Data(vec![1, 2, 3]),
+	);
+
+	// Regular messages are handled by every group
+	assert!(dead_letter::is_for_group(&map, "svc--a"));
+
+	// Replayed messages are only handled by the group that dead-lettered them
+	map.insert(
+		redis_keys::message_topic::GROUP.to_string(),
+		redis::Value::Data(b"svc--a".to_vec()),
+	);
+	assert!(dead_letter::is_for_group(&map, "svc--a"));
+	assert!(!dead_letter::is_for_group(&map, "svc--b"));
+}
+
+#[test]
+fn error_message() {
+	let err = chirp::response::Err {
+		kind: Some(chirp::response::err::Kind::Internal(
+			chirp::response::err::Internal {
+				ty: "TIVET_ERROR".into(),
+				message: "broken".into(),
+				debug: String::new(),
+			},
+		)),
+	};
+	assert_eq!("TIVET_ERROR: broken", dead_letter::error_message(&err));
+
+	let err = chirp::response::Err {
+		kind: Some(chirp::response::err::Kind::BadRequest(
+			chirp::response::err::BadRequest {
+				code: "BAD_CODE".into(),
+				context: HashMap::new(),
+				metadata: None,
+			},
+		)),
+	};
+	assert_eq!("BAD_CODE", dead_letter::error_message(&err));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn write_dead_letter() {
+	let mut redis = redis().await;
+
+	let group = format!("dead-letter-test--{}", Uuid::new_v4());
+	let topic_key = redis_keys::message_topic("dead-letter-test");
+	let message_id = "1-0";
+
+	dead_letter::record_error(
+		&mut redis,
+		&group,
+		message_id,
+		"TIVET_ERROR: broken".into(),
+		None,
+	)
+	.await
+	.unwrap();
+	dead_letter::write(
+		&mut redis,
+		&topic_key,
+		&group,
+		message_id,
+		&[1, 2, 3],
+		dead_letter::MAX_DELIVERY_COUNT,
+	)
+	.await
+	.unwrap();
+
+	// Message is written with the last error
+	let res = redis
+		.xrange_all::<_, redis::streams::StreamRangeReply>(redis_keys::dead_letter(&group))
+		.await
+		.unwrap();
+	assert_eq!(1, res.ids.len());
+	let entry = &res.ids[0];
+	assert_eq!(
+		Some(topic_key.clone()),
+		entry.get::<String>(redis_keys::dead_letter::TOPIC_KEY)
+	);
+	assert_eq!(
+		Some(message_id.to_string()),
+		entry.get::<String>(redis_keys::dead_letter::MESSAGE_ID)
+	);
+	assert_eq!(
+		Some(vec![1, 2, 3]),
+		entry.get::<Vec<u8>>(redis_keys::dead_letter::MESSAGE)
+	);
+	assert_eq!(
+		Some(dead_letter::MAX_DELIVERY_COUNT),
+		entry.get::<usize>(redis_keys::dead_letter::DELIVERY_COUNT)
+	);
+	let error = entry.get::<String>(redis_keys::dead_letter::ERROR).unwrap();
+	let error = serde_json::from_str::<serde_json::Value>(&error).unwrap();
+	assert_eq!("TIVET_ERROR: broken", error["error"]);
+
+	// Error is consumed
+	let error = redis
+		.get::<_, Option<String>>(redis_keys::dead_letter_error(&group, message_id))
+		.await
+		.unwrap();
+	assert!(error.is_none());
+
+	// Worker is indexed
+	let indexed = redis
+		.sismember::<_, _, bool>(redis_keys::dead_letter_workers(), &group)
+		.await
+		.unwrap();
+	assert!(indexed);
+
+	redis
+		.del::<_, ()>(redis_keys::dead_letter(&group))
+		.await
+		.unwrap();
+	redis
+		.srem::<_, _, ()>(redis_keys::dead_letter_workers(), &group)
+		.await
+		.unwrap();
+}
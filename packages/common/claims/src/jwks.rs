@@ -0,0 +1,66 @@
+//! JSON Web Key Set of the public keys used to verify tokens. See RFC 7517 and RFC 8037.
+
+use global_error::prelude::*;
+use serde::Serialize;
+use tivet_config::config::JwtKey;
+
+use crate::keys;
+
+/// DER prefix of an Ed25519 `SubjectPublicKeyInfo`, followed by the 32 byte public key.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+	0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+#[derive(Debug, Serialize)]
+pub struct Jwks {
+	pub keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Jwk {
+	pub kty: &'static str,
+	pub crv: &'static str,
+	pub alg: &'static str,
+	#[serde(rename = "use")]
+	pub use_: &'static str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub kid: Option<String>,
+	/// Base64url encoded public key.
+	pub x: String,
+}
+
+/// Builds the key set from the active key and the keyring.
+pub fn build(jwt: &JwtKey) -> GlobalResult<Jwks> {
+	let keys = keys(jwt)
+		.map(|(kid, pem_public)| {
+			Ok(Jwk {
+				kty: "OKP",
+				crv: "Ed25519",
+				alg: "EdDSA",
+				use_: "sig",
+				kid: kid.map(ToString::to_string),
+				x: base64::encode_config(ed25519_public_key(pem_public)?, base64::URL_SAFE_NO_PAD),
+			})
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(Jwks { keys })
+}
+
+/// Reads the raw public key from a PEM encoded Ed25519 `SubjectPublicKeyInfo`.
+fn ed25519_public_key(pem_public: &str) -> GlobalResult<Vec<u8>> {
+	let body = pem_public
+		.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with("-----"))
+		.collect::<String>();
+	let der = base64::decode(body)?;
+
+	let key = unwrap!(
+		der.strip_prefix(ED25519_SPKI_PREFIX.as_slice()),
+		"jwt public key is not an ed25519 key"
+	);
+	ensure!(key.len() == 32, "invalid ed25519 public key length");
+
+	Ok(key.to_vec())
+}
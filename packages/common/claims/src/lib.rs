@@ -3,6 +3,9 @@ use std::convert::TryFrom;
 use global_error::prelude::*;
 use jsonwebtoken::{Algorithm, DecodingKey};
 use prost::Message;
+use tivet_config::config::JwtKey;
+
+pub mod jwks;
 
 mod schema {
 	pub use types_proto::tivet::{backend, claims::*};
@@ -11,7 +14,10 @@ mod schema {
 pub const ALGORITHM: Algorithm = Algorithm::EdDSA;
 
 pub mod ent {
-	use std::convert::{TryFrom, TryInto};
+	use std::{
+		collections::HashMap,
+		convert::{TryFrom, TryInto},
+	};
 
 	use global_error::prelude::*;
 	use uuid::Uuid;
@@ -288,13 +294,17 @@ pub mod ent {
 	}
 
 	#[derive(Clone, Debug)]
-	pub struct ProvisionedServer {}
+	pub struct ProvisionedServer {
+		pub server_id: Option<Uuid>,
+	}
 
 	impl TryFrom<&schema::entitlement::ProvisionedServer> for ProvisionedServer {
 		type Error = GlobalError;
 
-		fn try_from(_value: &schema::entitlement::ProvisionedServer) -> GlobalResult<Self> {
-			Ok(ProvisionedServer {})
+		fn try_from(value: &schema::entitlement::ProvisionedServer) -> GlobalResult<Self> {
+			Ok(ProvisionedServer {
+				server_id: value.server_id.as_ref().map(|x| x.as_uuid()),
+			})
 		}
 	}
 
@@ -318,6 +328,37 @@ pub mod ent {
 	#[derive(Clone, Debug)]
 	pub struct EnvService {
 		pub env_id: Uuid,
+		/// Empty for tokens issued before scopes existed, which have access to the whole
+		/// environment.
+		pub scopes: Vec<EnvServiceScope>,
+		pub actor_tags: HashMap<String, String>,
+		pub datacenter_ids: Vec<Uuid>,
+	}
+
+	impl EnvService {
+		pub fn is_scoped(&self) -> bool {
+			!self.scopes.is_empty()
+		}
+
+		pub fn has_scope(&self, scope: EnvServiceScope) -> bool {
+			self.scopes.contains(&scope)
+		}
+
+		/// Checks the actor tag and datacenter restrictions of this token.
+		pub fn can_access_actor(
+			&self,
+			datacenter_id: Uuid,
+			tags: &HashMap<String, String>,
+		) -> bool {
+			let dc_allowed =
+				self.datacenter_ids.is_empty() || self.datacenter_ids.contains(&datacenter_id);
+			let tags_allowed = self
+				.actor_tags
+				.iter()
+				.all(|(k, v)| tags.get(k).map_or(false, |x| x == v));
+
+			dc_allowed && tags_allowed
+		}
 	}
 
 	impl TryFrom<&schema::entitlement::EnvService> for EnvService {
@@ -326,9 +367,95 @@ pub mod ent {
 		fn try_from(value: &schema::entitlement::EnvService) -> GlobalResult<Self> {
 			Ok(EnvService {
 				env_id: unwrap!(value.env_id).as_uuid(),
+				scopes: value
+					.scopes
+					.iter()
+					.map(|x| EnvServiceScope::try_from(*x))
+					.collect::<GlobalResult<Vec<_>>>()?,
+				actor_tags: value.actor_tags.clone(),
+				datacenter_ids: value.datacenter_ids.iter().map(|x| x.as_uuid()).collect(),
 			})
 		}
 	}
+
+	#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+	pub enum EnvServiceScope {
+		ActorsRead,
+		ActorsWrite,
+		BuildsRead,
+		BuildsWrite,
+		LogsRead,
+	}
+
+	impl EnvServiceScope {
+		pub fn as_str(&self) -> &'static str {
+			match self {
+				EnvServiceScope::ActorsRead => "actors:read",
+				EnvServiceScope::ActorsWrite => "actors:write",
+				EnvServiceScope::BuildsRead => "builds:read",
+				EnvServiceScope::BuildsWrite => "builds:write",
+				EnvServiceScope::LogsRead => "logs:read",
+			}
+		}
+	}
+
+	impl std::str::FromStr for EnvServiceScope {
+		type Err = GlobalError;
+
+		fn from_str(s: &str) -> GlobalResult<Self> {
+			let scope = match s {
+				"actors:read" => EnvServiceScope::ActorsRead,
+				"actors:write" => EnvServiceScope::ActorsWrite,
+				"builds:read" => EnvServiceScope::BuildsRead,
+				"builds:write" => EnvServiceScope::BuildsWrite,
+				"logs:read" => EnvServiceScope::LogsRead,
+				_ => bail!("unknown env service scope: {s}"),
+			};
+
+			Ok(scope)
+		}
+	}
+
+	impl TryFrom<i32> for EnvServiceScope {
+		type Error = GlobalError;
+
+		fn try_from(value: i32) -> GlobalResult<Self> {
+			let scope = match unwrap!(schema::entitlement::env_service::Scope::from_i32(value)) {
+				schema::entitlement::env_service::Scope::Unspecified => {
+					bail!("unspecified env service scope")
+				}
+				schema::entitlement::env_service::Scope::ActorsRead => EnvServiceScope::ActorsRead,
+				schema::entitlement::env_service::Scope::ActorsWrite => {
+					EnvServiceScope::ActorsWrite
+				}
+				schema::entitlement::env_service::Scope::BuildsRead => EnvServiceScope::BuildsRead,
+				schema::entitlement::env_service::Scope::BuildsWrite => {
+					EnvServiceScope::BuildsWrite
+				}
+				schema::entitlement::env_service::Scope::LogsRead => EnvServiceScope::LogsRead,
+			};
+
+			Ok(scope)
+		}
+	}
+
+	impl From<EnvServiceScope> for i32 {
+		fn from(value: EnvServiceScope) -> i32 {
+			let scope = match value {
+				EnvServiceScope::ActorsRead => schema::entitlement::env_service::Scope::ActorsRead,
+				EnvServiceScope::ActorsWrite => {
+					schema::entitlement::env_service::Scope::ActorsWrite
+				}
+				EnvServiceScope::BuildsRead => schema::entitlement::env_service::Scope::BuildsRead,
+				EnvServiceScope::BuildsWrite => {
+					schema::entitlement::env_service::Scope::BuildsWrite
+				}
+				EnvServiceScope::LogsRead => schema::entitlement::env_service::Scope::LogsRead,
+			};
+
+			scope as i32
+		}
+	}
 }
 
 pub trait ClaimsDecode {
@@ -634,23 +761,21 @@ impl EntitlementTag for schema::Entitlement {
 	}
 }
 
-pub fn decode(pem_public: &str, token: &str) -> GlobalResult<GlobalResult<schema::Claims>> {
+/// Verifies the token against the key matching its `kid`. Tokens without a `kid` are verified
+/// against every key in the keyring.
+pub fn decode(jwt: &JwtKey, token: &str) -> GlobalResult<GlobalResult<schema::Claims>> {
 	let mut validation = jsonwebtoken::Validation::default();
 	validation.algorithms = vec![ALGORITHM];
 	validation.validate_exp = false;
 	validation.validate_nbf = false;
 
-	decode_proto(
-		token,
-		&DecodingKey::from_ed_pem(pem_public.as_bytes())?,
-		&validation,
-	)
+	decode_proto(token, jwt, &validation)
 }
 
 /// Modified from jsonwebtoken::decode with Protobuf instead.
 fn decode_proto(
 	token: &str,
-	key: &jsonwebtoken::DecodingKey,
+	jwt: &JwtKey,
 	validation: &jsonwebtoken::Validation,
 ) -> GlobalResult<GlobalResult<schema::Claims>> {
 	// TODO:
@@ -695,7 +820,24 @@ fn decode_proto(
 		bail_with!(TOKEN_INVALID, reason = "invalid algorithm");
 	}
 
-	if !jsonwebtoken::crypto::verify(signature, message.as_bytes(), key, header.alg)? {
+	let mut verified = false;
+	let mut found_key = false;
+	for (kid, pem_public) in keys(jwt) {
+		if header.kid.is_some() && header.kid.as_deref() != kid {
+			continue;
+		}
+		found_key = true;
+
+		let key = DecodingKey::from_ed_pem(pem_public.as_bytes())?;
+		if jsonwebtoken::crypto::verify(signature, message.as_bytes(), &key, header.alg)? {
+			verified = true;
+			break;
+		}
+	}
+	if !found_key {
+		bail_with!(TOKEN_INVALID, reason = "unknown key id");
+	}
+	if !verified {
 		bail_with!(TOKEN_INVALID, reason = "invalid signature");
 	}
 
@@ -708,6 +850,15 @@ fn decode_proto(
 	Ok(validate(&claims).map(|_| claims))
 }
 
+/// Returns the active key followed by the rest of the keyring as `(kid, public key PEM)`.
+pub(crate) fn keys(jwt: &JwtKey) -> impl Iterator<Item = (Option<&str>, &str)> {
+	std::iter::once((jwt.kid.as_deref(), jwt.public.as_str())).chain(
+		jwt.keyring
+			.iter()
+			.map(|key| (Some(key.kid.as_str()), key.public.as_str())),
+	)
+}
+
 fn validate(claims: &schema::Claims) -> GlobalResult<()> {
 	let now = tivet_util::timestamp::now();
 	let claims_exp = claims.exp.unwrap_or_default();
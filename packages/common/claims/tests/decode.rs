@@ -0,0 +1,139 @@
+mod common;
+
+use common::*;
+use tivet_config::{
+	config::{JwtKey, JwtPublicKey},
+	secret::Secret,
+};
+
+fn is_invalid(jwt: &JwtKey, token: &str) -> bool {
+	match tivet_claims::decode(jwt, token) {
+		Err(err) => err.code() == Some("TOKEN_INVALID"),
+		Ok(_) => false,
+	}
+}
+
+#[test]
+fn decode_without_kid() {
+	let jwt = JwtKey {
+		kid: None,
+		public: KEY_A_PUBLIC.into(),
+		private: Secret::new(KEY_A_PRIVATE.into()),
+		keyring: Vec::new(),
+	};
+
+	let claims = claims(None);
+	let decoded = tivet_claims::decode(&jwt, &token(KEY_A_PRIVATE, None, &claims))
+		.unwrap()
+		.unwrap();
+	assert_eq!(claims, decoded);
+
+	// Labeled tokens
+	let labeled = format!("label.{}", token(KEY_A_PRIVATE, None, &claims));
+	assert_eq!(
+		claims,
+		tivet_claims::decode(&jwt, &labeled).unwrap().unwrap()
+	);
+
+	// Wrong key
+	assert!(is_invalid(&jwt, &token(KEY_B_PRIVATE, None, &claims)));
+}
+
+#[test]
+fn decode_kid_routing() {
+	let jwt = keyring();
+	let claims = claims(None);
+
+	// Active key
+	assert!(
+		tivet_claims::decode(&jwt, &token(KEY_B_PRIVATE, Some("b"), &claims))
+			.unwrap()
+			.is_ok()
+	);
+
+	// Keyring
+	assert!(
+		tivet_claims::decode(&jwt, &token(KEY_A_PRIVATE, Some("a"), &claims))
+			.unwrap()
+			.is_ok()
+	);
+
+	// Only the key matching the kid is tried
+	assert!(is_invalid(&jwt, &token(KEY_A_PRIVATE, Some("b"), &claims)));
+	assert!(is_invalid(&jwt, &token(KEY_B_PRIVATE, Some("a"), &claims)));
+
+	// Unknown kid
+	assert!(is_invalid(&jwt, &token(KEY_A_PRIVATE, Some("c"), &claims)));
+
+	// Tokens issued before kids were configured are verified against every key
+	assert!(
+		tivet_claims::decode(&jwt, &token(KEY_A_PRIVATE, None, &claims))
+			.unwrap()
+			.is_ok()
+	);
+	assert!(
+		tivet_claims::decode(&jwt, &token(KEY_B_PRIVATE, None, &claims))
+			.unwrap()
+			.is_ok()
+	);
+}
+
+#[test]
+fn decode_invalid() {
+	let jwt = keyring();
+	let token = token(KEY_B_PRIVATE, Some("b"), &claims(None));
+
+	assert!(is_invalid(&jwt, "not-a-token"));
+
+	// Tampered claims
+	let mut parts = token.split('.').collect::<Vec<_>>();
+	let tampered = base64::encode_config(
+		{
+			let mut claims = base64::decode_config(parts[1], base64::URL_SAFE_NO_PAD).unwrap();
+			claims.push(0);
+			claims
+		},
+		base64::URL_SAFE_NO_PAD,
+	);
+	parts[1] = &tampered;
+	assert!(is_invalid(&jwt, &parts.join(".")));
+}
+
+#[test]
+fn decode_expired() {
+	let jwt = keyring();
+
+	let expired = claims(Some(tivet_util::timestamp::now() - 1000));
+	let err = tivet_claims::decode(&jwt, &token(KEY_B_PRIVATE, Some("b"), &expired))
+		.unwrap()
+		.unwrap_err();
+	assert_eq!(Some("TOKEN_EXPIRED"), err.code());
+
+	let valid = claims(Some(tivet_util::timestamp::now() + 60_000));
+	assert!(
+		tivet_claims::decode(&jwt, &token(KEY_B_PRIVATE, Some("b"), &valid))
+			.unwrap()
+			.is_ok()
+	);
+}
+
+#[test]
+fn duplicate_kids() {
+	assert!(keyring().validate().is_ok());
+
+	// Active key in the keyring
+	let mut jwt = keyring();
+	jwt.keyring.push(JwtPublicKey {
+		kid: "b".into(),
+		public: KEY_A_PUBLIC.into(),
+	});
+	assert!(jwt.validate().is_err());
+
+	// Duplicate in the keyring
+	let mut jwt = keyring();
+	jwt.keyring.push(JwtPublicKey {
+		kid: "a".into(),
+		public: KEY_B_PUBLIC.into(),
+	});
+	assert!(jwt.validate().is_err());
+}
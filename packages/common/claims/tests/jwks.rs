@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+use tivet_config::config::JwtPublicKey;
+
+#[test]
+fn build() {
+	let jwks = tivet_claims::jwks::build(&keyring()).unwrap();
+
+	assert_eq!(
+		serde_json::json!({
+			"keys": [
+				{
+					"kty": "OKP",
+					"crv": "Ed25519",
+					"alg": "EdDSA",
+					"use": "sig",
+					"kid": "b",
+					"x": KEY_B_X,
+				},
+				{
+					"kty": "OKP",
+					"crv": "Ed25519",
+					"alg": "EdDSA",
+					"use": "sig",
+					"kid": "a",
+					"x": KEY_A_X,
+				},
+			],
+		}),
+		serde_json::to_value(&jwks).unwrap()
+	);
+}
+
+#[test]
+fn build_without_kid() {
+	let mut jwt = keyring();
+	jwt.kid = None;
+	jwt.keyring.clear();
+
+	let jwks = serde_json::to_value(tivet_claims::jwks::build(&jwt).unwrap()).unwrap();
+	assert_eq!(1, jwks["keys"].as_array().unwrap().len());
+	assert!(jwks["keys"][0].get("kid").is_none());
+	assert_eq!(KEY_B_X, jwks["keys"][0]["x"]);
+}
+
+#[test]
+fn build_unsupported_key() {
+	let mut jwt = keyring();
+	jwt.keyring.push(JwtPublicKey {
+		kid: "ec".into(),
+		public: EC_PUBLIC.into(),
+	});
+	assert!(tivet_claims::jwks::build(&jwt).is_err());
+
+	let mut jwt = keyring();
+	jwt.public = "-----BEGIN PUBLIC KEY-----\nnot base64\n-----END PUBLIC KEY-----\n".into();
+	assert!(tivet_claims::jwks::build(&jwt).is_err());
+}
@@ -34,4 +34,13 @@ impl Root {
 	pub fn server(&self) -> GlobalResult<&server::Server> {
 		Ok(unwrap_ref!(self.server, "missing server config"))
 	}
+
+	/// Checks constraints that can't be expressed with serde.
+	pub fn validate(&self) -> GlobalResult<()> {
+		if let Some(server) = &self.server {
+			server.jwt.validate()?;
+		}
+
+		Ok(())
+	}
 }
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+};
 
 use global_error::prelude::*;
 use schemars::JsonSchema;
@@ -21,6 +24,8 @@ pub struct Server {
 	pub tls: Option<Tls>,
 	#[serde(default)]
 	pub ssh: Option<Ssh>,
+	#[serde(default)]
+	pub actor_secrets: Option<ActorSecrets>,
 
 	#[serde(default)]
 	pub tivet: tivet::Tivet,
@@ -34,6 +39,8 @@ pub struct Server {
 	pub clickhouse: Option<ClickHouse>,
 	#[serde(default)]
 	pub prometheus: Option<Prometheus>,
+	#[serde(default)]
+	pub otel: Option<Otel>,
 
 	// Services
 	#[serde(default)]
@@ -61,6 +68,8 @@ pub struct Server {
 
 	#[serde(default)]
 	pub linode: Option<Linode>,
+	#[serde(default)]
+	pub local_provider: Option<LocalProvider>,
 
 	/// Deprecated
 	#[serde(default)]
@@ -96,10 +105,18 @@ impl Server {
 		Ok(unwrap_ref!(self.ssh, "ssh disabled"))
 	}
 
+	pub fn actor_secrets(&self) -> GlobalResult<&ActorSecrets> {
+		Ok(unwrap_ref!(self.actor_secrets, "actor secrets disabled"))
+	}
+
 	pub fn linode(&self) -> GlobalResult<&Linode> {
 		Ok(unwrap_ref!(self.linode, "linode disabled"))
 	}
 
+	pub fn local_provider(&self) -> GlobalResult<&LocalProvider> {
+		Ok(unwrap_ref!(self.local_provider, "local provider disabled"))
+	}
+
 	pub fn hcaptcha(&self) -> GlobalResult<&Hcaptcha> {
 		Ok(unwrap_ref!(self.hcaptcha, "hcaptcha disabled"))
 	}
@@ -120,21 +137,76 @@ impl Server {
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct JwtKey {
+	/// ID of the active key. Written to the `kid` header of issued tokens.
+	#[serde(default)]
+	pub kid: Option<String>,
 	/// The public EdDSA key in a PEM format.
 	pub public: String,
-	/// The private EdDSA key in a PEM format.
+	/// The private EdDSA key in a PEM format. Used to sign all new tokens.
 	pub private: Secret<String>,
+	/// Additional keys accepted when verifying tokens.
+	///
+	/// To rotate keys, add the new key here first, then make it the active key once all
+	/// services have the new config and move the old key here until its tokens expire.
+	#[serde(default)]
+	pub keyring: Vec<JwtPublicKey>,
 }
 
 impl Default for JwtKey {
 	fn default() -> Self {
 		Self {
+			kid: None,
 			public: "".into(),
 			private: Secret::new("".into()),
+			keyring: Vec::new(),
+		}
+	}
+}
+
+impl JwtKey {
+	/// Key IDs must be unique so each token is verified against a single key.
+	pub fn validate(&self) -> GlobalResult<()> {
+		let mut kids = HashSet::new();
+		for kid in self.kid.iter().chain(self.keyring.iter().map(|x| &x.kid)) {
+			ensure!(kids.insert(kid), "duplicate jwt kid: {kid}");
 		}
+
+		Ok(())
 	}
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct JwtPublicKey {
+	pub kid: String,
+	/// The public EdDSA key in a PEM format.
+	pub public: String,
+}
+
+/// Keys used to encrypt actor secrets at rest with AES-256-GCM.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ActorSecrets {
+	/// ID of the active key. Stored alongside each encrypted value.
+	pub kid: String,
+	/// The active 32 byte key in hex. Used to encrypt all new values.
+	pub key: Secret<String>,
+	/// Additional keys accepted when decrypting values.
+	///
+	/// To rotate keys, make the new key the active key and move the old key here until all
+	/// secrets have been written again.
+	#[serde(default)]
+	pub keyring: Vec<ActorSecretKey>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ActorSecretKey {
+	pub kid: String,
+	/// The 32 byte key in hex.
+	pub key: Secret<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct Nomad {
@@ -160,6 +232,31 @@ pub struct Linode {
 	pub api_token: Secret<String>,
 }
 
+/// Runs servers of datacenters with the `Local` provider on this machine. Used to test
+/// provisioning end-to-end without a cloud provider.
+///
+/// Servers are not installed over SSH, the image or command must already contain everything the
+/// pool needs. `TIVET_SERVER_ID`, `TIVET_DATACENTER_ID` and `TIVET_POOL_TYPE` are passed as env vars.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum LocalProvider {
+	/// Runs each server as a Docker container.
+	Container {
+		image: String,
+		/// Docker network to attach containers to. Uses the default bridge network if not set.
+		#[serde(default)]
+		network: Option<String>,
+	},
+	/// Runs each server as a child process of the workflow worker. Each server is assigned its own
+	/// loopback address, passed as `TIVET_PUBLIC_IP`. Servers can only be destroyed by the worker
+	/// process that started them.
+	Process {
+		command: PathBuf,
+		#[serde(default)]
+		args: Vec<String>,
+	},
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct S3 {
@@ -273,6 +370,23 @@ pub struct Prometheus {
 	pub url: Url,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct Otel {
+	/// OTLP gRPC endpoint of the collector, e.g. `http://127.0.0.1:4317`.
+	pub endpoint: Url,
+	/// Ratio of new traces to sample. Traces continued from an incoming `traceparent` follow the
+	/// caller's sampling decision.
+	#[serde(default = "Otel::default_sample_ratio")]
+	pub sample_ratio: f64,
+}
+
+impl Otel {
+	fn default_sample_ratio() -> f64 {
+		1.0
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct Hcaptcha {
@@ -325,7 +439,37 @@ pub struct Neon {
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct IpInfo {
-	pub token: Secret<String>,
+	/// ipinfo.io token. Requests are rate limited without one.
+	#[serde(default)]
+	pub token: Option<Secret<String>>,
+	/// Local MaxMind-format GeoIP database. Looked up before ipinfo.io.
+	#[serde(default)]
+	pub mmdb: Option<IpInfoMmdb>,
+	/// Falls back to ipinfo.io for addresses missing from the local database.
+	#[serde(default = "IpInfo::default_ipinfo_io_fallback")]
+	pub ipinfo_io_fallback: bool,
+}
+
+impl IpInfo {
+	fn default_ipinfo_io_fallback() -> bool {
+		true
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct IpInfoMmdb {
+	/// Path to the `.mmdb` file, e.g. a GeoLite2 City database.
+	pub path: PathBuf,
+	/// How often to check the file for changes, in seconds.
+	#[serde(default = "IpInfoMmdb::default_reload_interval")]
+	pub reload_interval: u64,
+}
+
+impl IpInfoMmdb {
+	fn default_reload_interval() -> u64 {
+		60
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
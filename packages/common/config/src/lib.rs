@@ -39,6 +39,7 @@ impl Config {
 			unwrap!(settings.build(), "failed to build config").try_deserialize::<config::Root>(),
 			"failed to deserialize config"
 		);
+		config.validate()?;
 
 		Ok(Self(Arc::new(ConfigData { config })))
 	}
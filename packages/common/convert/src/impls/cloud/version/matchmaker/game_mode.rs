@@ -155,6 +155,7 @@ pub fn game_mode_to_proto(
 		listable: game_mode.listable.unwrap_or(true),
 		taggable: game_mode.taggable.unwrap_or(false),
 		allow_dynamic_max_players: game_mode.allow_dynamic_max_players.unwrap_or(false),
+		skill_matchmaking: game_mode.skill_matchmaking.clone().map(|x| (*x).api_into()),
 
 		runtime,
 
@@ -258,6 +259,7 @@ pub fn game_mode_to_openapi(
 			listable: Some(value.listable),
 			taggable: Some(value.taggable),
 			allow_dynamic_max_players: Some(value.allow_dynamic_max_players),
+			skill_matchmaking: value.skill_matchmaking.map(ApiInto::api_into).map(Box::new),
 
 			docker: Some(Box::new(docker)),
 
@@ -375,6 +377,32 @@ impl ApiTryFrom<backend::matchmaker::lobby_group::IdleLobbies>
 	}
 }
 
+impl ApiFrom<models::CloudVersionMatchmakerGameModeSkillMatchmaking>
+	for backend::matchmaker::SkillMatchmaking
+{
+	fn api_from(value: models::CloudVersionMatchmakerGameModeSkillMatchmaking) -> Self {
+		backend::matchmaker::SkillMatchmaking {
+			initial_window: value.initial_window,
+			window_growth_per_second: value.window_growth_per_second,
+			max_window: value.max_window,
+			max_spread: value.max_spread,
+		}
+	}
+}
+
+impl ApiFrom<backend::matchmaker::SkillMatchmaking>
+	for models::CloudVersionMatchmakerGameModeSkillMatchmaking
+{
+	fn api_from(value: backend::matchmaker::SkillMatchmaking) -> Self {
+		models::CloudVersionMatchmakerGameModeSkillMatchmaking {
+			initial_window: value.initial_window,
+			window_growth_per_second: value.window_growth_per_second,
+			max_window: value.max_window,
+			max_spread: value.max_spread,
+		}
+	}
+}
+
 impl ApiFrom<models::CloudVersionMatchmakerGameModeIdentityRequirement>
 	for backend::matchmaker::IdentityRequirement
 {
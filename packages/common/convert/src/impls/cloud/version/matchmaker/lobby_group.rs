@@ -38,6 +38,7 @@ impl ApiTryFrom<models::CloudVersionMatchmakerLobbyGroup> for backend::matchmake
 			listable: true,
 			taggable: false,
 			allow_dynamic_max_players: false,
+			skill_matchmaking: value.skill_matchmaking.map(|x| (*x).api_into()),
 
 			runtime: Some((*value.runtime).api_try_into()?),
 
@@ -61,6 +62,7 @@ impl ApiTryFrom<backend::matchmaker::LobbyGroup> for models::CloudVersionMatchma
 			max_players_normal: value.max_players_normal.api_try_into()?,
 			max_players_direct: value.max_players_direct.api_try_into()?,
 			max_players_party: value.max_players_party.api_try_into()?,
+			skill_matchmaking: value.skill_matchmaking.map(ApiInto::api_into).map(Box::new),
 
 			runtime: Box::new(unwrap!(value.runtime).api_try_into()?),
 		})
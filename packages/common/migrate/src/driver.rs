@@ -0,0 +1,276 @@
+//! Reads and writes migration state. Uses the same `schema_migrations` tables as
+//! golang-migrate so databases migrated by either runner stay compatible.
+
+use anyhow::*;
+use indoc::indoc;
+use sqlx::{postgres::PgConnection, Connection};
+use tivet_pools::prelude::*;
+
+use crate::{source::split_statements, SqlService, SqlServiceKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Version {
+	pub version: i64,
+	pub dirty: bool,
+}
+
+pub(crate) enum Driver {
+	CockroachDB(PgConnection),
+	ClickHouse(ClickHousePool),
+}
+
+#[derive(clickhouse::Row, serde::Deserialize)]
+struct ClickHouseVersionRow {
+	version: i64,
+	dirty: u8,
+}
+
+impl Driver {
+	/// Connects to the service's database. The database must already exist.
+	pub async fn connect(
+		crdb: &CrdbPool,
+		clickhouse: Option<&ClickHousePool>,
+		service: &SqlService,
+	) -> Result<Self> {
+		match service.kind {
+			SqlServiceKind::CockroachDB => {
+				// Use a dedicated connection since migrations are not qualified with the database
+				let opts = crdb
+					.connect_options()
+					.as_ref()
+					.clone()
+					.database(service.db_name);
+				let conn = PgConnection::connect_with(&opts)
+					.await
+					.with_context(|| format!("failed to connect to {}", service.db_name))?;
+
+				Ok(Driver::CockroachDB(conn))
+			}
+			SqlServiceKind::ClickHouse => {
+				let clickhouse = clickhouse.context("missing clickhouse")?;
+
+				Ok(Driver::ClickHouse(
+					clickhouse.clone().with_database(service.db_name),
+				))
+			}
+		}
+	}
+
+	/// Returns false if the service's database has not been created yet.
+	pub async fn database_exists(
+		crdb: &CrdbPool,
+		clickhouse: Option<&ClickHousePool>,
+		service: &SqlService,
+	) -> Result<bool> {
+		match service.kind {
+			SqlServiceKind::CockroachDB => {
+				let (exists,) = sqlx::query_as::<_, (bool,)>(
+					"SELECT EXISTS (SELECT 1 FROM pg_catalog.pg_database WHERE datname = $1)",
+				)
+				.bind(service.db_name)
+				.fetch_one(crdb)
+				.await?;
+
+				Ok(exists)
+			}
+			SqlServiceKind::ClickHouse => {
+				let count = clickhouse
+					.context("missing clickhouse")?
+					.query("SELECT count() FROM system.databases WHERE name = ?")
+					.bind(service.db_name)
+					.fetch_one::<u64>()
+					.await?;
+
+				Ok(count > 0)
+			}
+		}
+	}
+
+	pub async fn has_version_table(&mut self) -> Result<bool> {
+		match self {
+			Driver::CockroachDB(conn) => {
+				let (exists,) = sqlx::query_as::<_, (bool,)>(indoc!(
+					"
+					SELECT EXISTS (
+						SELECT 1
+						FROM information_schema.tables
+						WHERE table_schema = 'public' AND table_name = 'schema_migrations'
+					)
+					"
+				))
+				.fetch_one(&mut *conn)
+				.await?;
+
+				Ok(exists)
+			}
+			Driver::ClickHouse(clickhouse) => {
+				let count = clickhouse
+					.query(
+						"SELECT count() FROM system.tables WHERE database = currentDatabase() AND name = 'schema_migrations'",
+					)
+					.fetch_one::<u64>()
+					.await?;
+
+				Ok(count > 0)
+			}
+		}
+	}
+
+	pub async fn ensure_version_table(&mut self) -> Result<()> {
+		match self {
+			Driver::CockroachDB(conn) => {
+				sqlx::query(indoc!(
+					"
+					CREATE TABLE IF NOT EXISTS schema_migrations (
+						version INT8 NOT NULL PRIMARY KEY,
+						dirty BOOL NOT NULL
+					)
+					"
+				))
+				.execute(&mut *conn)
+				.await?;
+			}
+			Driver::ClickHouse(clickhouse) => {
+				clickhouse
+					.query(indoc!(
+						"
+						CREATE TABLE IF NOT EXISTS schema_migrations (
+							version Int64,
+							dirty UInt8,
+							sequence UInt64
+						) ENGINE = ReplicatedMergeTree ORDER BY sequence
+						"
+					))
+					.execute()
+					.await?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Returns `None` if no migrations have been applied.
+	pub async fn version(&mut self) -> Result<Option<Version>> {
+		match self {
+			Driver::CockroachDB(conn) => {
+				let row = sqlx::query_as::<_, (i64, bool)>(
+					"SELECT version, dirty FROM schema_migrations LIMIT 1",
+				)
+				.fetch_optional(&mut *conn)
+				.await?;
+
+				Ok(row.map(|(version, dirty)| Version { version, dirty }))
+			}
+			Driver::ClickHouse(clickhouse) => {
+				let row = clickhouse
+					.query(
+						"SELECT version, dirty FROM schema_migrations ORDER BY sequence DESC LIMIT 1",
+					)
+					.fetch_optional::<ClickHouseVersionRow>()
+					.await?;
+
+				// golang-migrate writes -1 when all migrations are rolled back
+				Ok(row.filter(|x| x.version >= 0).map(|x| Version {
+					version: x.version,
+					dirty: x.dirty != 0,
+				}))
+			}
+		}
+	}
+
+	pub async fn set_version(&mut self, version: Option<i64>, dirty: bool) -> Result<()> {
+		match self {
+			Driver::CockroachDB(conn) => {
+				let mut tx = conn.begin().await?;
+
+				sqlx::query("DELETE FROM schema_migrations")
+					.execute(&mut *tx)
+					.await?;
+				if let Some(version) = version {
+					sqlx::query("INSERT INTO schema_migrations (version, dirty) VALUES ($1, $2)")
+						.bind(version)
+						.bind(dirty)
+						.execute(&mut *tx)
+						.await?;
+				}
+
+				tx.commit().await?;
+			}
+			Driver::ClickHouse(clickhouse) => {
+				let sequence = std::time::SystemTime::now()
+					.duration_since(std::time::UNIX_EPOCH)?
+					.as_nanos() as u64;
+
+				clickhouse
+					.query(
+						"INSERT INTO schema_migrations (version, dirty, sequence) VALUES (?, ?, ?)",
+					)
+					.bind(version.unwrap_or(-1))
+					.bind(dirty as u8)
+					.bind(sequence)
+					.execute()
+					.await?;
+			}
+		}
+
+		Ok(())
+	}
+
+	pub async fn execute(&mut self, sql: &str) -> Result<()> {
+		match self {
+			// Executed without arguments so multiple statements can run at once
+			Driver::CockroachDB(conn) => {
+				sqlx::Executor::execute(&mut *conn, sql).await?;
+			}
+			Driver::ClickHouse(clickhouse) => {
+				for statement in split_statements(sql) {
+					clickhouse
+						.query(statement)
+						.execute()
+						.await
+						.with_context(|| format!("failed executing statement:\n{statement}"))?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Drops all tables in the database.
+	pub async fn drop_all(&mut self, db_name: &str) -> Result<()> {
+		match self {
+			Driver::CockroachDB(conn) => {
+				let tables = sqlx::query_as::<_, (String,)>(indoc!(
+					"
+					SELECT table_name
+					FROM information_schema.tables
+					WHERE table_schema = 'public' AND table_type = 'BASE TABLE'
+					"
+				))
+				.fetch_all(&mut *conn)
+				.await?;
+
+				for (table,) in tables {
+					let query = format!(r#"DROP TABLE IF EXISTS "{table}" CASCADE"#);
+					sqlx::Executor::execute(&mut *conn, query.as_str()).await?;
+				}
+			}
+			Driver::ClickHouse(clickhouse) => {
+				let tables = clickhouse
+					.query("SELECT name FROM system.tables WHERE database = ?")
+					.bind(db_name)
+					.fetch_all::<String>()
+					.await?;
+
+				for table in tables {
+					clickhouse
+						.query(&format!("DROP TABLE IF EXISTS `{db_name}`.`{table}`"))
+						.execute()
+						.await?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,175 @@
+//! CRDB lease that serializes concurrent migration runners.
+
+use std::{
+	future::Future,
+	time::{Duration, Instant},
+};
+
+use anyhow::*;
+use indoc::indoc;
+use tivet_pools::prelude::*;
+use uuid::Uuid;
+
+use crate::metadata;
+
+const LEASE_NAME: &str = "migrate";
+/// How long the lease is held without being renewed.
+const LEASE_DURATION: Duration = Duration::from_secs(30);
+const RENEW_INTERVAL: Duration = Duration::from_secs(10);
+const ACQUIRE_INTERVAL: Duration = Duration::from_secs(2);
+
+pub(crate) struct Lease {
+	crdb: CrdbPool,
+	holder_id: Uuid,
+	renew_handle: tokio::task::JoinHandle<()>,
+	/// Resolves when the lease was lost.
+	lost_rx: tokio::sync::oneshot::Receiver<()>,
+}
+
+impl Lease {
+	/// Waits until no other runner holds the lease.
+	pub async fn acquire(crdb: &CrdbPool) -> Result<Self> {
+		metadata::ensure_schema(crdb).await?;
+
+		let holder_id = Uuid::new_v4();
+		loop {
+			if try_acquire(crdb, holder_id).await? {
+				break;
+			}
+
+			tracing::info!("another migration is running, waiting for lease");
+			tokio::time::sleep(ACQUIRE_INTERVAL).await;
+		}
+		tracing::debug!(?holder_id, "acquired migration lease");
+
+		let (lost_tx, lost_rx) = tokio::sync::oneshot::channel();
+		let renew_handle = tokio::spawn({
+			let crdb = crdb.clone();
+			async move {
+				let mut last_renew = Instant::now();
+				loop {
+					tokio::time::sleep(RENEW_INTERVAL).await;
+
+					match try_acquire(&crdb, holder_id).await {
+						Result::Ok(true) => last_renew = Instant::now(),
+						Result::Ok(false) => {
+							tracing::error!("migration lease was taken by another runner");
+							break;
+						}
+						Err(err) => {
+							tracing::warn!(?err, "failed to renew migration lease");
+
+							// Another runner can take the lease once it expires
+							if last_renew.elapsed() >= LEASE_DURATION {
+								tracing::error!("migration lease expired");
+								break;
+							}
+						}
+					}
+				}
+
+				let _ = lost_tx.send(());
+			}
+		});
+
+		Ok(Lease {
+			crdb: crdb.clone(),
+			holder_id,
+			renew_handle,
+			lost_rx,
+		})
+	}
+
+	/// Runs the future while holding the lease. Aborts the future if the lease is lost, since
+	/// another runner may be migrating at the same time.
+	pub async fn run<T>(&mut self, fut: impl Future<Output = Result<T>>) -> Result<T> {
+		abort_on_lost(&mut self.lost_rx, fut).await
+	}
+
+	pub async fn release(self) -> Result<()> {
+		self.renew_handle.abort();
+
+		sqlx::query("DELETE FROM db_migrate.leases WHERE name = $1 AND holder_id = $2")
+			.bind(LEASE_NAME)
+			.bind(self.holder_id)
+			.execute(&self.crdb)
+			.await?;
+
+		tracing::debug!(holder_id=?self.holder_id, "released migration lease");
+
+		Ok(())
+	}
+}
+
+async fn abort_on_lost<T>(
+	lost_rx: &mut tokio::sync::oneshot::Receiver<()>,
+	fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+	tokio::select! {
+		res = fut => res,
+		// Also resolves if the renew task stopped unexpectedly
+		_ = lost_rx => bail!("lost migration lease, aborted migration"),
+	}
+}
+
+/// Acquires or renews the lease. Returns false if another runner holds it.
+async fn try_acquire(crdb: &CrdbPool, holder_id: Uuid) -> Result<bool> {
+	let now = tivet_util::timestamp::now();
+
+	let row = sqlx::query_as::<_, (i64,)>(indoc!(
+		"
+		INSERT INTO db_migrate.leases (name, holder_id, expire_ts)
+		VALUES ($1, $2, $3)
+		ON CONFLICT (name) DO UPDATE
+		SET holder_id = excluded.holder_id, expire_ts = excluded.expire_ts
+		WHERE leases.holder_id = $2 OR leases.expire_ts < $4
+		RETURNING 1
+		"
+	))
+	.bind(LEASE_NAME)
+	.bind(holder_id)
+	.bind(now + LEASE_DURATION.as_millis() as i64)
+	.bind(now)
+	.fetch_optional(crdb)
+	.await?;
+
+	Ok(row.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use anyhow::*;
+
+	use super::abort_on_lost;
+
+	#[tokio::test]
+	async fn completes_while_held() {
+		let (_lost_tx, mut lost_rx) = tokio::sync::oneshot::channel();
+		let res = abort_on_lost(&mut lost_rx, async { Ok(1) }).await;
+		assert_eq!(1, res.unwrap());
+	}
+
+	#[tokio::test]
+	async fn aborts_when_lost() {
+		let (lost_tx, mut lost_rx) = tokio::sync::oneshot::channel();
+		lost_tx.send(()).unwrap();
+
+		let res = abort_on_lost(&mut lost_rx, async {
+			tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+			Ok(())
+		})
+		.await;
+		assert!(res.is_err());
+	}
+
+	#[tokio::test]
+	async fn aborts_when_renew_task_stops() {
+		let (lost_tx, mut lost_rx) = tokio::sync::oneshot::channel::<()>();
+		drop(lost_tx);
+
+		let res = abort_on_lost(&mut lost_rx, std::future::pending::<Result<()>>()).await;
+		assert!(res.is_err());
+	}
+}
@@ -1,8 +1,14 @@
 use include_dir::Dir;
 
+mod driver;
+mod lease;
+mod metadata;
 mod migrate;
+mod source;
 
+pub use driver::Version;
 pub use migrate::*;
+pub use source::Migration;
 
 #[derive(Clone, Debug)]
 pub struct SqlService {
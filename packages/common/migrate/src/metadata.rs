@@ -0,0 +1,113 @@
+//! Runner metadata stored in CRDB: the migration lease and checksums of applied migrations
+//! for every service.
+
+use std::collections::HashMap;
+
+use anyhow::*;
+use indoc::indoc;
+use tivet_pools::prelude::*;
+
+use crate::source::Migration;
+
+pub(crate) async fn ensure_schema(crdb: &CrdbPool) -> Result<()> {
+	let mut conn = crdb.acquire().await.context("can't acquire crdb")?;
+
+	sqlx::Executor::execute(
+		&mut *conn,
+		indoc!(
+			"
+			CREATE DATABASE IF NOT EXISTS db_migrate;
+			CREATE TABLE IF NOT EXISTS db_migrate.leases (
+				name STRING PRIMARY KEY,
+				holder_id UUID NOT NULL,
+				expire_ts INT NOT NULL
+			);
+			CREATE TABLE IF NOT EXISTS db_migrate.checksums (
+				db_name STRING NOT NULL,
+				version INT8 NOT NULL,
+				name STRING NOT NULL,
+				checksum STRING NOT NULL,
+				applied_ts INT NOT NULL,
+				PRIMARY KEY (db_name, version)
+			);
+			"
+		),
+	)
+	.await
+	.context("failed to create migration metadata")?;
+
+	Ok(())
+}
+
+/// Returns if the metadata schema was created. It's only created by commands that apply
+/// migrations, read only commands check this instead.
+pub(crate) async fn schema_exists(crdb: &CrdbPool) -> Result<bool> {
+	let (exists,) = sqlx::query_as::<_, (bool,)>(indoc!(
+		"
+		SELECT EXISTS (
+			SELECT 1
+			FROM crdb_internal.tables
+			WHERE database_name = 'db_migrate' AND name = 'checksums' AND drop_time IS NULL
+		)
+		"
+	))
+	.fetch_one(crdb)
+	.await?;
+
+	Ok(exists)
+}
+
+/// Returns checksums of applied migrations by version.
+pub(crate) async fn checksums(crdb: &CrdbPool, db_name: &str) -> Result<HashMap<i64, String>> {
+	let rows = sqlx::query_as::<_, (i64, String)>(
+		"SELECT version, checksum FROM db_migrate.checksums WHERE db_name = $1",
+	)
+	.bind(db_name)
+	.fetch_all(crdb)
+	.await?;
+
+	Ok(rows.into_iter().collect())
+}
+
+pub(crate) async fn record_checksum(
+	crdb: &CrdbPool,
+	db_name: &str,
+	migration: &Migration,
+) -> Result<()> {
+	sqlx::query(indoc!(
+		"
+		UPSERT INTO db_migrate.checksums (db_name, version, name, checksum, applied_ts)
+		VALUES ($1, $2, $3, $4, $5)
+		"
+	))
+	.bind(db_name)
+	.bind(migration.version)
+	.bind(&migration.name)
+	.bind(&migration.checksum)
+	.bind(tivet_util::timestamp::now())
+	.execute(crdb)
+	.await?;
+
+	Ok(())
+}
+
+/// Removes checksums of migrations newer than the given version. Removes all checksums if no
+/// version is given.
+pub(crate) async fn remove_checksums_after(
+	crdb: &CrdbPool,
+	db_name: &str,
+	version: Option<i64>,
+) -> Result<()> {
+	sqlx::query(indoc!(
+		"
+		DELETE FROM db_migrate.checksums
+		WHERE db_name = $1 AND ($2::INT8 IS NULL OR version > $2)
+		"
+	))
+	.bind(db_name)
+	.bind(version)
+	.execute(crdb)
+	.await?;
+
+	Ok(())
+}
@@ -1,36 +1,52 @@
+use std::{collections::HashMap, future::Future};
+
 use anyhow::*;
 use futures_util::stream::{self, StreamExt};
 use indoc::formatdoc;
+use sqlx::prelude::*;
 use tivet_config::config::CockroachDbUserRole;
 use tivet_pools::prelude::*;
-use sqlx::prelude::*;
-use tokio::{
-	io::{AsyncBufReadExt, BufReader},
-	task::block_in_place,
-};
-use urlencoding::encode;
 
-use crate::{SqlService, SqlServiceKind};
-
-struct MigrateCmd {
-	service: SqlService,
-	args: Vec<String>,
-}
+use crate::{
+	driver::{Driver, Version},
+	lease::Lease,
+	metadata,
+	source::{read_migrations, Migration},
+	SqlService, SqlServiceKind,
+};
 
 #[tracing::instrument(skip_all)]
 pub async fn up(config: tivet_config::Config, services: &[SqlService]) -> Result<()> {
 	tracing::info!(sql_services = ?services.len(), "running sql migrations");
 
+	let (crdb, clickhouse) = setup_pools(&config).await?;
+
+	let res = with_lease(
+		&crdb,
+		up_inner(&config, services, &crdb, clickhouse.as_ref()),
+	)
+	.await;
+
+	tracing::debug!("shutting down pools");
+	crdb.close().await;
+
+	res?;
+
+	tracing::debug!("migrated");
+
+	Ok(())
+}
+
+async fn up_inner(
+	config: &tivet_config::Config,
+	services: &[SqlService],
+	crdb: &CrdbPool,
+	clickhouse: Option<&ClickHousePool>,
+) -> Result<()> {
 	let server_config = config.server.as_ref().context("missing server")?;
 	let is_development = server_config.tivet.auth.access_kind
 		== tivet_config::config::tivet::AccessKind::Development;
 
-	let crdb = tivet_pools::db::crdb::setup(config.clone())
-		.await
-		.map_err(|err| anyhow!("{err}"))?;
-	let clickhouse =
-		tivet_pools::db::clickhouse::setup(config.clone()).map_err(|err| anyhow!("{err}"))?;
-
 	let mut crdb_pre_queries = Vec::new();
 	let mut crdb_post_queries = Vec::new();
 	let mut clickhouse_pre_queries = Vec::new();
@@ -191,7 +207,7 @@ pub async fn up(config: tivet_config::Config, services: &[SqlService]) -> Result
 		},
 		async {
 			if !clickhouse_pre_queries.is_empty() {
-				let clickhouse = clickhouse.as_ref().context("missing clickhouse")?;
+				let clickhouse = clickhouse.context("missing clickhouse")?;
 
 				for query in clickhouse_pre_queries {
 					clickhouse.query(&query).execute().await.with_context(|| {
@@ -206,17 +222,12 @@ pub async fn up(config: tivet_config::Config, services: &[SqlService]) -> Result
 
 	tracing::debug!("running migrations");
 
-	let migrations = services
+	let services = services
 		.iter()
 		// Exclude ClickHouse if needed
 		.filter(|svc| clickhouse.is_some() || !matches!(&svc.kind, SqlServiceKind::ClickHouse))
-		// Create command
-		.map(|svc| MigrateCmd {
-			service: svc.clone(),
-			args: vec!["up".to_string()],
-		})
 		.collect::<Vec<_>>();
-	run_migrations(config.clone(), &migrations).await?;
+	run_migrations(config, crdb, clickhouse, &services).await?;
 
 	// Run post-migration queries in parallel
 	tracing::debug!(crdb = ?crdb_post_queries.len(), clickhouse = ?clickhouse_post_queries.len(), "running post-migrations");
@@ -232,7 +243,7 @@ pub async fn up(config: tivet_config::Config, services: &[SqlService]) -> Result
 		},
 		async {
 			if !clickhouse_post_queries.is_empty() {
-				let clickhouse = clickhouse.as_ref().context("missing clickhouse")?;
+				let clickhouse = clickhouse.context("missing clickhouse")?;
 				for query in clickhouse_post_queries {
 					clickhouse.query(&query).execute().await?;
 				}
@@ -242,50 +253,167 @@ pub async fn up(config: tivet_config::Config, services: &[SqlService]) -> Result
 		}
 	)?;
 
-	wait_for_crdb_schema_migrations(&crdb).await?;
-
-	tracing::debug!("shutting down pools");
-	crdb.close().await;
-
-	tracing::debug!("migrated");
+	wait_for_crdb_schema_migrations(crdb).await?;
 
 	Ok(())
 }
 
 pub async fn down(config: tivet_config::Config, service: &SqlService, num: usize) -> Result<()> {
-	run_migrations(
-		config,
-		&[MigrateCmd {
-			service: service.clone(),
-			args: vec!["down".into(), num.to_string()],
-		}],
+	let (crdb, clickhouse) = setup_pools(&config).await?;
+
+	let res = with_lease(
+		&crdb,
+		migrate_down(&crdb, clickhouse.as_ref(), service, num),
 	)
-	.await
+	.await;
+
+	crdb.close().await;
+
+	res
 }
 
 pub async fn force(config: tivet_config::Config, service: &SqlService, num: usize) -> Result<()> {
-	run_migrations(
-		config,
-		&[MigrateCmd {
-			service: service.clone(),
-			args: vec!["force".to_string(), num.to_string()],
-		}],
-	)
-	.await
+	let (crdb, clickhouse) = setup_pools(&config).await?;
+	let version = i64::try_from(num)?;
+
+	let res = with_lease(&crdb, async {
+		let mut driver = Driver::connect(&crdb, clickhouse.as_ref(), service).await?;
+		driver.ensure_version_table().await?;
+		driver.set_version(Some(version), false).await?;
+		metadata::remove_checksums_after(&crdb, service.db_name, Some(version)).await
+	})
+	.await;
+
+	crdb.close().await;
+
+	res
 }
 
 pub async fn drop(config: tivet_config::Config, service: &SqlService) -> Result<()> {
-	run_migrations(
-		config,
-		&[MigrateCmd {
-			service: service.clone(),
-			args: vec!["drop".to_string(), "-f".to_string()],
-		}],
-	)
-	.await
+	let (crdb, clickhouse) = setup_pools(&config).await?;
+
+	let res = with_lease(&crdb, async {
+		let mut driver = Driver::connect(&crdb, clickhouse.as_ref(), service).await?;
+		driver.drop_all(service.db_name).await?;
+		metadata::remove_checksums_after(&crdb, service.db_name, None).await
+	})
+	.await;
+
+	crdb.close().await;
+
+	res
+}
+
+/// Returns the migration state of each service without applying anything.
+pub async fn status(
+	config: tivet_config::Config,
+	services: &[SqlService],
+) -> Result<Vec<MigrationStatus>> {
+	let (crdb, clickhouse) = setup_pools(&config).await?;
+
+	// No checksums have been recorded if the metadata schema doesn't exist yet
+	let has_metadata = metadata::schema_exists(&crdb).await?;
+
+	let mut statuses = Vec::with_capacity(services.len());
+	for svc in services {
+		if clickhouse.is_none() && matches!(svc.kind, SqlServiceKind::ClickHouse) {
+			tracing::warn!("clickhouse is disabled, skipping {}", svc.db_name);
+			continue;
+		}
+
+		let migrations = read_migrations(&svc.migrations)?;
+
+		let version = if Driver::database_exists(&crdb, clickhouse.as_ref(), svc).await? {
+			let mut driver = Driver::connect(&crdb, clickhouse.as_ref(), svc).await?;
+			if driver.has_version_table().await? {
+				driver.version().await?
+			} else {
+				None
+			}
+		} else {
+			None
+		};
+
+		let checksums = if has_metadata {
+			metadata::checksums(&crdb, svc.db_name).await?
+		} else {
+			HashMap::new()
+		};
+		let (applied, pending) = migrations
+			.into_iter()
+			.partition::<Vec<_>, _>(|x| version.map_or(false, |v| x.version <= v.version));
+		let edited = applied
+			.iter()
+			.filter(|x| {
+				checksums
+					.get(&x.version)
+					.map_or(false, |checksum| *checksum != x.checksum)
+			})
+			.cloned()
+			.collect();
+
+		statuses.push(MigrationStatus {
+			db_name: svc.db_name,
+			version,
+			applied,
+			pending,
+			edited,
+		});
+	}
+
+	crdb.close().await;
+
+	Ok(statuses)
+}
+
+/// Returns the migrations that `up` would apply. Fails the same way `up` would if a database is
+/// dirty or has edited migrations.
+pub async fn plan(
+	config: tivet_config::Config,
+	services: &[SqlService],
+) -> Result<Vec<MigrationStatus>> {
+	let statuses = status(config, services).await?;
+
+	for status in &statuses {
+		if let Some(version) = status.version {
+			ensure!(
+				!version.dirty,
+				"{} is dirty at version {}, fix and force version",
+				status.db_name,
+				version.version
+			);
+		}
+
+		if let Some(migration) = status.edited.first() {
+			bail!(
+				"migration {}_{} of {} was edited after being applied",
+				migration.version,
+				migration.name,
+				status.db_name
+			);
+		}
+	}
+
+	Ok(statuses)
+}
+
+#[derive(Debug)]
+pub struct MigrationStatus {
+	pub db_name: &'static str,
+	/// `None` if no migrations have been applied.
+	pub version: Option<Version>,
+	pub applied: Vec<Migration>,
+	pub pending: Vec<Migration>,
+	/// Applied migrations whose file no longer matches the recorded checksum.
+	pub edited: Vec<Migration>,
 }
 
-async fn run_migrations(config: tivet_config::Config, migration_cmds: &[MigrateCmd]) -> Result<()> {
+async fn run_migrations(
+	config: &tivet_config::Config,
+	crdb: &CrdbPool,
+	clickhouse: Option<&ClickHousePool>,
+	services: &[&SqlService],
+) -> Result<()> {
 	let is_dev = config
 		.server()
 		.map_err(|err| anyhow!("{err}"))?
@@ -302,12 +430,7 @@ async fn run_migrations(config: tivet_config::Config, migration_cmds: &[MigrateC
 		1
 	};
 
-	let migration_futs = migration_cmds.iter().map(|cmd| {
-		let config = config.clone();
-		run_migration(config, cmd)
-	});
-
-	stream::iter(migration_futs)
+	stream::iter(services.iter().map(|svc| migrate_up(crdb, clickhouse, svc)))
 		.buffer_unordered(migration_parallelism)
 		.collect::<Vec<_>>()
 		.await
@@ -318,123 +441,160 @@ async fn run_migrations(config: tivet_config::Config, migration_cmds: &[MigrateC
 	Ok(())
 }
 
-async fn run_migration(config: tivet_config::Config, cmd: &MigrateCmd) -> Result<()> {
-	tracing::debug!(db_name=%cmd.service.db_name, "running db migration");
-
-	// Write migrations to temp path
-	let dir = tempfile::tempdir()?;
-	block_in_place(|| cmd.service.migrations.extract(dir.path()))?;
-
-	// Run migration
-	let migrate_url = migrate_db_url(config, &cmd.service).await?;
-	let mut child = tokio::process::Command::new("migrate")
-		.arg("-database")
-		.arg(migrate_url)
-		.arg("-path")
-		.arg(dir.path().join("migrations"))
-		.args(&cmd.args)
-		.stdout(std::process::Stdio::piped())
-		.stderr(std::process::Stdio::piped())
-		.spawn()
-		.context("failed to run migrate command")?;
-
-	// Log output in real-time
-	let stdout = child.stdout.take().context("Failed to capture stdout")?;
-	let stderr = child.stderr.take().context("Failed to capture stderr")?;
-
-	tokio::spawn(async move {
-		let mut stdout_reader = BufReader::new(stdout).lines();
-		while let Some(line) = stdout_reader
-			.next_line()
-			.await
-			.expect("Failed to read stdout")
-		{
-			tracing::debug!("migrate stdout: {}", line);
+/// Applies all pending migrations of a service.
+async fn migrate_up(
+	crdb: &CrdbPool,
+	clickhouse: Option<&ClickHousePool>,
+	service: &SqlService,
+) -> Result<()> {
+	tracing::debug!(db_name=%service.db_name, "running db migration");
+
+	let migrations = read_migrations(&service.migrations)?;
+
+	let mut driver = Driver::connect(crdb, clickhouse, service).await?;
+	driver.ensure_version_table().await?;
+
+	let current = driver.version().await?;
+	if let Some(current) = current {
+		ensure_clean(service, current)?;
+	}
+
+	// Refuse to run if applied migrations were edited. Migrations applied before checksums were
+	// recorded are trusted.
+	let checksums = metadata::checksums(crdb, service.db_name).await?;
+	for migration in &migrations {
+		if current.map_or(true, |x| migration.version > x.version) {
+			break;
+		}
+
+		match checksums.get(&migration.version) {
+			Some(checksum) if *checksum != migration.checksum => {
+				bail!(
+					"migration {}_{} of {} was edited after being applied",
+					migration.version,
+					migration.name,
+					service.db_name
+				);
+			}
+			Some(_) => {}
+			None => metadata::record_checksum(crdb, service.db_name, migration).await?,
 		}
-	});
-
-	tokio::spawn(async move {
-		let mut stderr_reader = BufReader::new(stderr).lines();
-		while let Some(line) = stderr_reader
-			.next_line()
-			.await
-			.expect("Failed to read stderr")
-		{
-			tracing::debug!("migrate stderr: {}", line);
+	}
+
+	for migration in &migrations {
+		if current.map_or(false, |x| migration.version <= x.version) {
+			continue;
 		}
-	});
 
-	let status = child.wait().await?;
-	if !status.success() {
-		bail!("migrate failed: {}", cmd.service.db_name);
+		tracing::info!(db_name=%service.db_name, version=%migration.version, name=%migration.name, "applying migration");
+
+		driver.set_version(Some(migration.version), true).await?;
+		driver.execute(&migration.up).await.with_context(|| {
+			format!(
+				"failed applying {}_{} of {}",
+				migration.version, migration.name, service.db_name
+			)
+		})?;
+		driver.set_version(Some(migration.version), false).await?;
+		metadata::record_checksum(crdb, service.db_name, migration).await?;
 	}
 
 	Ok(())
 }
 
-/// Returns the URL to use for database migrations.
-async fn migrate_db_url(config: tivet_config::Config, service: &SqlService) -> Result<String> {
-	let server_config = config.server.as_ref().context("missing server")?;
+fn ensure_clean(service: &SqlService, version: Version) -> Result<()> {
+	ensure!(
+		!version.dirty,
+		"{} is dirty at version {}, fix and force version",
+		service.db_name,
+		version.version
+	);
 
-	match &service.kind {
-		SqlServiceKind::CockroachDB => {
-			let crdb_url_parsed = server_config.cockroachdb.url.clone();
-			let crdb_host = crdb_url_parsed.host_str().context("crdb missing host")?;
-			let crdb_port = crdb_url_parsed
-				.port_or_known_default()
-				.context("crdb missing port")?;
-
-			let auth = if let Some(password) = &server_config.cockroachdb.password {
-				format!(
-					"{}:{}",
-					encode(&server_config.cockroachdb.username),
-					encode(password.read())
-				)
-			} else {
-				encode(&server_config.cockroachdb.username).to_string()
-			};
+	Ok(())
+}
 
-			let mut url = url::Url::parse(&format!(
-				"cockroach://{auth}@{crdb_host}:{crdb_port}/{}",
-				encode(service.db_name)
-			))?;
+/// Rolls back the last `num` migrations of a service.
+async fn migrate_down(
+	crdb: &CrdbPool,
+	clickhouse: Option<&ClickHousePool>,
+	service: &SqlService,
+	num: usize,
+) -> Result<()> {
+	let migrations = read_migrations(&service.migrations)?;
 
-			if let Some(sslmode) = crdb_url_parsed.query_pairs().find(|(k, _)| k == "sslmode") {
-				url.query_pairs_mut().append_pair("sslmode", &sslmode.1);
-			}
+	let mut driver = Driver::connect(crdb, clickhouse, service).await?;
+	driver.ensure_version_table().await?;
 
-			Ok(url.to_string())
-		}
-		SqlServiceKind::ClickHouse => {
-			let clickhouse_config = server_config
-				.clickhouse
-				.as_ref()
-				.context("missing clickhouse")?;
-			let clickhouse_url_parsed = clickhouse_config.native_url.clone();
-			let clickhouse_host = clickhouse_url_parsed
-				.host_str()
-				.context("clickhouse missing host")?;
-			let clickhouse_port = clickhouse_url_parsed
-				.port_or_known_default()
-				.context("clickhouse missing port")?;
-
-			let mut query = format!(
-				"database={db}&username={username}&x-multi-statement=true&x-migrations-table-engine=ReplicatedMergeTree&secure={secure}&skip_verify=true",
-				db = encode(service.db_name),
-				username = encode(&clickhouse_config.username),
-				secure = clickhouse_config.secure,
-			);
-			if let Some(password) = &clickhouse_config.password {
-				query += &format!("&password={}", encode(password.read()));
+	let Some(current) = driver.version().await? else {
+		bail!("no migrations applied to {}", service.db_name);
+	};
+	ensure_clean(service, current)?;
+
+	let current_idx = migrations
+		.iter()
+		.position(|x| x.version == current.version)
+		.with_context(|| {
+			format!(
+				"no migration found for version {} of {}",
+				current.version, service.db_name
+			)
+		})?;
+
+	for idx in (0..=current_idx).rev().take(num) {
+		let migration = &migrations[idx];
+		let prev_version = idx.checked_sub(1).map(|x| migrations[x].version);
+		let down = migration.down.as_ref().with_context(|| {
+			format!(
+				"missing down migration for {}_{} of {}",
+				migration.version, migration.name, service.db_name
+			)
+		})?;
+
+		tracing::info!(db_name=%service.db_name, version=%migration.version, name=%migration.name, "rolling back migration");
+
+		driver.set_version(prev_version, true).await?;
+		driver.execute(down).await.with_context(|| {
+			format!(
+				"failed rolling back {}_{} of {}",
+				migration.version, migration.name, service.db_name
+			)
+		})?;
+		driver.set_version(prev_version, false).await?;
+		metadata::remove_checksums_after(crdb, service.db_name, prev_version).await?;
+	}
+
+	Ok(())
+}
+
+/// Runs the future while holding the migration lease so concurrent runners are serialized. Errors
+/// from the future take precedence over errors releasing the lease.
+async fn with_lease<T>(crdb: &CrdbPool, fut: impl Future<Output = Result<T>>) -> Result<T> {
+	let mut lease = Lease::acquire(crdb).await?;
+	let res = lease.run(fut).await;
+	let release_res = lease.release().await;
+
+	match (res, release_res) {
+		(Result::Ok(x), release_res) => release_res.map(|_| x),
+		(Err(err), release_res) => {
+			if let Err(release_err) = release_res {
+				tracing::warn!(?release_err, "failed to release migration lease");
 			}
 
-			Ok(format!(
-				"clickhouse://{clickhouse_host}:{clickhouse_port}/?&{query}"
-			))
+			Err(err)
 		}
 	}
 }
 
+async fn setup_pools(config: &tivet_config::Config) -> Result<(CrdbPool, Option<ClickHousePool>)> {
+	let crdb = tivet_pools::db::crdb::setup(config.clone())
+		.await
+		.map_err(|err| anyhow!("{err}"))?;
+	let clickhouse =
+		tivet_pools::db::clickhouse::setup(config.clone()).map_err(|err| anyhow!("{err}"))?;
+
+	Ok((crdb, clickhouse))
+}
+
 /// Wait until all pending schema changes have finished applying.
 async fn wait_for_crdb_schema_migrations(crdb: &tivet_pools::db::crdb::CrdbPool) -> Result<()> {
 	tracing::debug!("waiting for crdb migrations to finish applying");
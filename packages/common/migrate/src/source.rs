@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+
+use anyhow::*;
+use include_dir::Dir;
+use sha2::{Digest, Sha256};
+
+/// A migration read from the embedded migrations directory.
+#[derive(Clone, Debug)]
+pub struct Migration {
+	pub version: i64,
+	pub name: String,
+	pub up: String,
+	pub down: Option<String>,
+	/// Hex SHA-256 of the up migration.
+	pub checksum: String,
+}
+
+/// Reads `migrations/{version}_{name}.{up,down}.sql` files sorted by version.
+pub(crate) fn read_migrations(dir: &Dir<'static>) -> Result<Vec<Migration>> {
+	let migrations_dir = dir
+		.get_dir("migrations")
+		.context("missing migrations directory")?;
+
+	let mut up = BTreeMap::new();
+	let mut down = BTreeMap::new();
+	for file in migrations_dir.files() {
+		let path = file.path();
+		let file_name = path
+			.file_name()
+			.and_then(|x| x.to_str())
+			.with_context(|| format!("invalid migration file name: {}", path.display()))?;
+
+		let (stem, is_up) = if let Some(stem) = file_name.strip_suffix(".up.sql") {
+			(stem, true)
+		} else if let Some(stem) = file_name.strip_suffix(".down.sql") {
+			(stem, false)
+		} else {
+			tracing::warn!(?path, "skipping non-migration file");
+			continue;
+		};
+
+		let (version, name) = stem
+			.split_once('_')
+			.with_context(|| format!("migration file missing version: {file_name}"))?;
+		let version = version
+			.parse::<i64>()
+			.with_context(|| format!("invalid migration version: {file_name}"))?;
+		let sql = file
+			.contents_utf8()
+			.with_context(|| format!("migration is not utf-8: {file_name}"))?
+			.to_string();
+
+		let target = if is_up { &mut up } else { &mut down };
+		ensure!(
+			target.insert(version, (name.to_string(), sql)).is_none(),
+			"duplicate migration version: {file_name}"
+		);
+	}
+
+	up.into_iter()
+		.map(|(version, (name, up))| {
+			let checksum = hex::encode(Sha256::digest(up.as_bytes()));
+
+			Ok(Migration {
+				version,
+				name,
+				up,
+				down: down.remove(&version).map(|(_, sql)| sql),
+				checksum,
+			})
+		})
+		.collect()
+}
+
+/// Splits a migration into individual statements for databases that don't support executing
+/// multiple statements at once. Semicolons in quotes, dollar-quoted bodies and comments don't end
+/// a statement. Statements that only contain comments are skipped.
+pub(crate) fn split_statements(sql: &str) -> Vec<&str> {
+	let bytes = sql.as_bytes();
+	let mut statements = Vec::new();
+	let mut start = 0;
+	let mut has_code = false;
+	let mut i = 0;
+
+	while i < bytes.len() {
+		match bytes[i] {
+			b';' => {
+				if has_code {
+					statements.push(sql[start..i].trim());
+				}
+
+				start = i + 1;
+				has_code = false;
+				i += 1;
+			}
+			b'-' if bytes.get(i + 1) == Some(&b'-') => {
+				i = find(bytes, i + 2, b"\n").map_or(bytes.len(), |x| x + 1);
+			}
+			b'/' if bytes.get(i + 1) == Some(&b'*') => {
+				i = find(bytes, i + 2, b"*/").map_or(bytes.len(), |x| x + 2);
+			}
+			quote @ (b'\'' | b'"' | b'`') => {
+				i = skip_quoted(bytes, i, quote);
+				has_code = true;
+			}
+			b'$' => {
+				if let Some(tag_end) = dollar_tag_end(bytes, i) {
+					let tag = &bytes[i..=tag_end];
+					i = find(bytes, tag_end + 1, tag).map_or(bytes.len(), |x| x + tag.len());
+				} else {
+					i += 1;
+				}
+				has_code = true;
+			}
+			x => {
+				if !x.is_ascii_whitespace() {
+					has_code = true;
+				}
+				i += 1;
+			}
+		}
+	}
+
+	if has_code {
+		statements.push(sql[start..].trim());
+	}
+
+	statements
+}
+
+/// Returns the index after the closing quote of the quoted string starting at `start`. Handles
+/// both doubled quotes and backslash escapes.
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8) -> usize {
+	let mut i = start + 1;
+	while i < bytes.len() {
+		if bytes[i] == b'\\' {
+			i += 2;
+		} else if bytes[i] == quote {
+			if bytes.get(i + 1) == Some(&quote) {
+				i += 2;
+			} else {
+				return i + 1;
+			}
+		} else {
+			i += 1;
+		}
+	}
+
+	bytes.len()
+}
+
+/// Returns the index of the closing `$` if a dollar quote tag (i.e. `$$` or `$body$`) starts at
+/// `start`. Positional parameters like `$1` are not tags.
+fn dollar_tag_end(bytes: &[u8], start: usize) -> Option<usize> {
+	for (i, x) in bytes.iter().enumerate().skip(start + 1) {
+		match x {
+			b'$' => return Some(i),
+			b'a'..=b'z' | b'A'..=b'Z' | b'_' => {}
+			b'0'..=b'9' if i > start + 1 => {}
+			_ => return None,
+		}
+	}
+
+	None
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+	bytes
+		.get(from..)?
+		.windows(needle.len())
+		.position(|x| x == needle)
+		.map(|x| x + from)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::split_statements;
+
+	#[test]
+	fn split() {
+		assert_eq!(
+			vec!["SELECT 1", "SELECT 2"],
+			split_statements("SELECT 1;\n\nSELECT 2;\n")
+		);
+		assert_eq!(vec!["SELECT 1"], split_statements("SELECT 1"));
+		assert!(split_statements(" ;\n; ").is_empty());
+	}
+
+	#[test]
+	fn split_quotes() {
+		assert_eq!(
+			vec![
+				"INSERT INTO t VALUES ('a;b', 'it''s;', 'c\\';d')",
+				"SELECT 1"
+			],
+			split_statements("INSERT INTO t VALUES ('a;b', 'it''s;', 'c\\';d'); SELECT 1")
+		);
+		assert_eq!(
+			vec![r#"SELECT "a;b", `c;d` FROM t"#],
+			split_statements(r#"SELECT "a;b", `c;d` FROM t;"#)
+		);
+	}
+
+	#[test]
+	fn split_comments() {
+		assert_eq!(
+			vec!["-- a; b\nSELECT 1", "/* c; d */ SELECT 2"],
+			split_statements("-- a; b\nSELECT 1; /* c; d */ SELECT 2;\n-- trailing; comment\n")
+		);
+		assert!(split_statements("-- only a comment;").is_empty());
+	}
+
+	#[test]
+	fn split_dollar_quotes() {
+		let sql = "CREATE FUNCTION f() RETURNS INT AS $$ SELECT 1; $$ LANGUAGE SQL";
+		assert_eq!(vec![sql], split_statements(&format!("{sql};")));
+
+		let sql = "CREATE FUNCTION f() RETURNS INT AS $body$ SELECT 1; $body$ LANGUAGE SQL";
+		assert_eq!(
+			vec![sql, "SELECT 2"],
+			split_statements(&format!("{sql}; SELECT 2"))
+		);
+
+		// Positional parameters
+		assert_eq!(
+			vec!["SELECT $1", "SELECT $2"],
+			split_statements("SELECT $1; SELECT $2;")
+		);
+	}
+
+	#[test]
+	fn split_unterminated() {
+		assert_eq!(vec!["SELECT 'a;b"], split_statements("SELECT 'a;b"));
+	}
+}
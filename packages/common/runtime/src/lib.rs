@@ -4,6 +4,7 @@ use thiserror::Error;
 use tracing_subscriber::{prelude::*, EnvFilter};
 
 mod metrics;
+pub mod otel;
 
 static SETUP_TRACING: Once = Once::new();
 
@@ -14,6 +15,12 @@ pub enum Error {
 
 	#[error("build tokio runtime: {0}")]
 	BuildTokioRuntime(std::io::Error),
+
+	#[error("otel exporter already enabled")]
+	OtelAlreadyEnabled,
+
+	#[error("otel: {0}")]
+	Otel(#[from] opentelemetry::trace::TraceError),
 }
 
 #[derive(Default)]
@@ -41,28 +48,26 @@ impl RunConfig {
 
 	fn setup_tracing(&self) {
 		SETUP_TRACING.call_once(|| {
-			let mut env_filter = EnvFilter::default()
-				// Default filter
-				.add_directive("info".parse().unwrap())
-				// Disable verbose logs
-				.add_directive("tokio_cron_scheduler=warn".parse().unwrap());
-
-			// Parse env filter
-			if let Ok(filter) = std::env::var("RUST_LOG") {
-				for s in filter.split(',').filter(|x| !x.is_empty()) {
-					env_filter = env_filter.add_directive(s.parse().expect("invalid env filter"));
-				}
-			}
+			let env_filter = build_env_filter("RUST_LOG");
+
+			// Spans exported with OpenTelemetry are filtered separately since they're much more
+			// expensive than log lines
+			let otel_layer = otel::layer().with_filter(build_env_filter("RUST_LOG_OTEL"));
 
 			if self.pretty_logs {
 				// Pretty print
-				tracing_subscriber::fmt()
-					.pretty()
-					.with_env_filter(env_filter)
+				tracing_subscriber::registry()
+					.with(otel_layer)
+					.with(
+						tracing_subscriber::fmt::layer()
+							.pretty()
+							.with_filter(env_filter),
+					)
 					.init();
 			} else if std::env::var("TOKIO_CONSOLE_ENABLE").is_ok() {
 				// logfmt + tokio-console
 				tracing_subscriber::registry()
+					.with(otel_layer)
 					.with(
 						console_subscriber::ConsoleLayer::builder()
 							.retention(std::time::Duration::from_secs(60))
@@ -74,6 +79,7 @@ impl RunConfig {
 			} else {
 				// logfmt
 				tracing_subscriber::registry()
+					.with(otel_layer)
 					.with(
 						tracing_logfmt::builder()
 							.with_span_name(
@@ -155,3 +161,20 @@ impl RunConfig {
 pub fn run<F: Future>(f: F) -> Result<F::Output, Error> {
 	RunConfig::default().run(f)
 }
+
+fn build_env_filter(env_var: &str) -> EnvFilter {
+	let mut env_filter = EnvFilter::default()
+		// Default filter
+		.add_directive("info".parse().unwrap())
+		// Disable verbose logs
+		.add_directive("tokio_cron_scheduler=warn".parse().unwrap());
+
+	// Parse env filter
+	if let Ok(filter) = std::env::var(env_var) {
+		for s in filter.split(',').filter(|x| !x.is_empty()) {
+			env_filter = env_filter.add_directive(s.parse().expect("invalid env filter"));
+		}
+	}
+
+	env_filter
+}
@@ -0,0 +1,271 @@
+//! OpenTelemetry span export and W3C trace context propagation.
+//!
+//! The tracer is installed when tracing is set up, before the config is loaded. Spans are dropped
+//! until `enable` is called with the exporter config.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use opentelemetry::{
+	global,
+	trace::{
+		Link, SamplingDecision, SamplingResult, SpanKind, TraceContextExt, TraceId, TraceResult,
+		TraceState, TracerProvider as _,
+	},
+	Context, KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+	export::trace::SpanData,
+	propagation::TraceContextPropagator,
+	runtime::Tokio,
+	trace::{BatchSpanProcessor, Sampler, ShouldSample, Span, SpanProcessor, TracerProvider},
+	Resource,
+};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::Registry;
+
+use crate::Error;
+
+/// W3C trace context header names.
+pub const TRACEPARENT: &str = "traceparent";
+pub const TRACESTATE: &str = "tracestate";
+
+static SAMPLER: OnceLock<Sampler> = OnceLock::new();
+static PROCESSOR: OnceLock<BatchSpanProcessor<Tokio>> = OnceLock::new();
+
+pub struct OtelConfig {
+	/// OTLP gRPC endpoint of the collector.
+	pub endpoint: String,
+	pub service_name: String,
+	/// Ratio of root traces to sample.
+	pub sample_ratio: f64,
+}
+
+/// Builds the tracing layer. Must be the first layer on the registry.
+pub(crate) fn layer() -> OpenTelemetryLayer<Registry, opentelemetry_sdk::trace::Tracer> {
+	global::set_text_map_propagator(TraceContextPropagator::new());
+
+	let provider = TracerProvider::builder()
+		.with_config(opentelemetry_sdk::trace::Config::default().with_sampler(DeferredSampler))
+		.with_span_processor(DeferredProcessor)
+		.build();
+	let tracer = provider.tracer("tivet");
+	global::set_tracer_provider(provider);
+
+	tracing_opentelemetry::layer().with_tracer(tracer)
+}
+
+/// Starts exporting spans to an OTLP collector. Must be called from within a Tokio runtime.
+pub fn enable(config: OtelConfig) -> Result<(), Error> {
+	let exporter = opentelemetry_otlp::new_exporter()
+		.tonic()
+		.with_endpoint(config.endpoint)
+		.build_span_exporter()?;
+	let mut processor = BatchSpanProcessor::builder(exporter, Tokio).build();
+	processor.set_resource(&Resource::new([KeyValue::new(
+		"service.name",
+		config.service_name,
+	)]));
+
+	PROCESSOR
+		.set(processor)
+		.map_err(|_| Error::OtelAlreadyEnabled)?;
+
+	// Respect the sampling decision of incoming trace contexts
+	let _ = SAMPLER.set(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+		config.sample_ratio,
+	))));
+
+	tracing::info!("otel exporter enabled");
+
+	Ok(())
+}
+
+/// Flushes pending spans. Should be called before the process exits.
+pub fn shutdown() {
+	if let Some(processor) = PROCESSOR.get() {
+		if let Err(err) = processor.shutdown() {
+			tracing::warn!(?err, "failed to shut down otel exporter");
+		}
+	}
+}
+
+/// Returns the W3C `traceparent` of the current span.
+pub fn traceparent() -> Option<String> {
+	span_traceparent(&tracing::Span::current())
+}
+
+/// Returns the W3C `traceparent` of the given span.
+pub fn span_traceparent(span: &tracing::Span) -> Option<String> {
+	let cx = span.context();
+	if !cx.span().span_context().is_valid() {
+		return None;
+	}
+
+	let mut carrier = HashMap::new();
+	global::get_text_map_propagator(|propagator| propagator.inject_context(&cx, &mut carrier));
+
+	carrier.remove(TRACEPARENT)
+}
+
+/// Sets the parent of the span to a remote trace context. Invalid trace contexts are ignored.
+pub fn set_parent(span: &tracing::Span, traceparent: Option<&str>, tracestate: Option<&str>) {
+	if let Some(cx) = extract(traceparent, tracestate) {
+		span.set_parent(cx);
+	}
+}
+
+/// Links the span to a remote trace context without making it the parent. Used for causes that
+/// belong to a different trace, such as signals received by a workflow.
+pub fn add_link(span: &tracing::Span, traceparent: Option<&str>) {
+	if let Some(cx) = extract(traceparent, None) {
+		span.add_link(cx.span().span_context().clone());
+	}
+}
+
+fn extract(traceparent: Option<&str>, tracestate: Option<&str>) -> Option<Context> {
+	let mut carrier = HashMap::new();
+	carrier.insert(TRACEPARENT.to_string(), traceparent?.to_string());
+	if let Some(tracestate) = tracestate {
+		carrier.insert(TRACESTATE.to_string(), tracestate.to_string());
+	}
+
+	let cx = global::get_text_map_propagator(|propagator| propagator.extract(&carrier));
+	if cx.span().span_context().is_valid() {
+		Some(cx)
+	} else {
+		tracing::debug!(?traceparent, "invalid traceparent");
+		None
+	}
+}
+
+/// Drops all spans until the exporter is enabled.
+#[derive(Clone, Debug)]
+struct DeferredSampler;
+
+impl ShouldSample for DeferredSampler {
+	fn should_sample(
+		&self,
+		parent_context: Option<&Context>,
+		trace_id: TraceId,
+		name: &str,
+		span_kind: &SpanKind,
+		attributes: &[KeyValue],
+		links: &[Link],
+	) -> SamplingResult {
+		if let Some(sampler) = SAMPLER.get() {
+			sampler.should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+		} else {
+			SamplingResult {
+				decision: SamplingDecision::Drop,
+				attributes: Vec::new(),
+				trace_state: parent_context
+					.map(|cx| cx.span().span_context().trace_state().clone())
+					.unwrap_or_else(TraceState::default),
+			}
+		}
+	}
+}
+
+/// Forwards spans to the exporter once it is enabled.
+#[derive(Debug)]
+struct DeferredProcessor;
+
+impl SpanProcessor for DeferredProcessor {
+	fn on_start(&self, span: &mut Span, cx: &Context) {
+		if let Some(processor) = PROCESSOR.get() {
+			processor.on_start(span, cx);
+		}
+	}
+
+	fn on_end(&self, span: SpanData) {
+		if let Some(processor) = PROCESSOR.get() {
+			processor.on_end(span);
+		}
+	}
+
+	fn force_flush(&self) -> TraceResult<()> {
+		PROCESSOR
+			.get()
+			.map_or(Ok(()), |processor| processor.force_flush())
+	}
+
+	fn shutdown(&self) -> TraceResult<()> {
+		PROCESSOR
+			.get()
+			.map_or(Ok(()), |processor| processor.shutdown())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use tracing_subscriber::layer::SubscriberExt;
+
+	use super::*;
+
+	const PARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+	fn with_tracer(f: impl FnOnce()) {
+		let subscriber = Registry::default().with(layer());
+		tracing::subscriber::with_default(subscriber, f);
+	}
+
+	fn trace_id(traceparent: &str) -> &str {
+		traceparent.split('-').nth(1).expect("invalid traceparent")
+	}
+
+	#[test]
+	fn propagates_parent() {
+		with_tracer(|| {
+			let span = tracing::info_span!("test");
+			set_parent(&span, Some(PARENT), None);
+
+			let traceparent = span_traceparent(&span).expect("missing traceparent");
+			assert_eq!(trace_id(PARENT), trace_id(&traceparent));
+
+			// Children inherit the trace
+			let _guard = span.enter();
+			let child = tracing::info_span!("child");
+			let child_traceparent = span_traceparent(&child).expect("missing traceparent");
+			assert_eq!(trace_id(PARENT), trace_id(&child_traceparent));
+			assert_ne!(traceparent, child_traceparent);
+		});
+	}
+
+	#[test]
+	fn ignores_invalid_parent() {
+		with_tracer(|| {
+			for invalid in [
+				"",
+				"garbage",
+				"00-00000000000000000000000000000000-b7ad6b7169203331-01",
+			] {
+				let span = tracing::info_span!("test");
+				set_parent(&span, Some(invalid), None);
+
+				// Starts a new trace instead
+				let traceparent = span_traceparent(&span).expect("missing traceparent");
+				assert_ne!(trace_id(PARENT), trace_id(&traceparent));
+				assert_ne!("00000000000000000000000000000000", trace_id(&traceparent));
+			}
+		});
+	}
+
+	#[test]
+	fn links_do_not_change_trace() {
+		with_tracer(|| {
+			let span = tracing::info_span!("test");
+			add_link(&span, Some(PARENT));
+
+			let traceparent = span_traceparent(&span).expect("missing traceparent");
+			assert_ne!(trace_id(PARENT), trace_id(&traceparent));
+		});
+	}
+
+	#[test]
+	fn no_traceparent_without_tracer() {
+		let span = tracing::info_span!("test");
+		assert!(span_traceparent(&span).is_none());
+		assert!(traceparent().is_none());
+	}
+}
@@ -12,6 +12,9 @@ pub struct Config {
 	pub metadata: protocol::Raw<protocol::ActorMetadata>,
 	pub owner: protocol::ActorOwner,
 	pub vector_socket_addr: Option<String>,
+	/// Regex matching log lines that are joined with the line before them.
+	#[serde(default)]
+	pub vector_multiline_pattern: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -27,3 +30,16 @@ pub struct Port {
 	pub target: u16,
 	pub protocol: protocol::TransportProtocol,
 }
+
+/// Resource usage of a running isolate. Periodically written to `metrics.json` by the isolate runner.
+#[derive(Serialize, Deserialize)]
+pub struct Metrics {
+	/// Unix timestamp in milliseconds.
+	pub ts: i64,
+	/// Total CPU time of the isolate's thread in microseconds.
+	pub cpu_usage_usec: u64,
+	/// Bytes.
+	pub used_heap_size: u64,
+	/// Bytes.
+	pub total_heap_size: u64,
+}
@@ -79,6 +79,14 @@ pub struct Cluster {
 	pub datacenter_id: Uuid,
 	pub api_endpoint: Url,
 	pub pegboard_endpoint: Url,
+	/// Token with the `ProvisionedServer` entitlement used to authenticate with the pegboard endpoint. Only
+	/// valid for the `client_id` it was created for. Read again from the config file on every reconnect so it
+	/// can be replaced while the client is running.
+	///
+	/// Created automatically for provisioned servers. Create one for manually provisioned clients with
+	/// `tivet-server cluster create-client-token --client-id <client_id>`. Not required if the server uses
+	/// the development access kind.
+	pub token: Option<String>,
 }
 
 #[derive(Clone, Deserialize, JsonSchema)]
@@ -121,6 +129,9 @@ impl Runner {
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct Images {
 	pub pull_addresses: Option<Addresses>,
+	/// Disk budget of the local image cache in MiB. Least recently used images are evicted once
+	/// exceeded. Defaults to 64 GiB.
+	pub max_cache_size: Option<u64>,
 }
 
 impl Images {
@@ -130,6 +141,10 @@ impl Images {
 			.map(Cow::Borrowed)
 			.unwrap_or_else(|| Cow::Owned(Addresses::Static(Vec::new())))
 	}
+
+	pub fn max_cache_size(&self) -> u64 {
+		self.max_cache_size.unwrap_or(64 * 1024)
+	}
 }
 
 #[derive(Clone, Deserialize, JsonSchema)]
@@ -272,4 +287,7 @@ pub enum Addresses {
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct Vector {
 	pub address: String,
+	/// Regex matching actor log lines that are joined with the line before them in to one entry (i.e.
+	/// stack traces). Defaults to indented lines.
+	pub multiline_pattern: Option<String>,
 }
@@ -1,6 +1,7 @@
-use std::{io::Write, net::TcpStream, sync::mpsc, thread::JoinHandle};
+use std::{collections::BTreeMap, io::Write, net::TcpStream, sync::mpsc, thread::JoinHandle};
 
 use anyhow::*;
+use pegboard_logs::structured::{self, MultilineJoiner};
 use serde::Serialize;
 use serde_json;
 
@@ -38,6 +39,9 @@ pub struct LogShipper {
 
 	pub vector_socket_addr: String,
 
+	/// Regex matching lines that are joined with the line before them.
+	pub multiline_pattern: Option<String>,
+
 	pub owner: ActorOwner,
 }
 
@@ -90,23 +94,67 @@ impl LogShipper {
 
 		println!("Log shipper connected");
 
-		while let Result::Ok(message) = self.msg_rx.recv() {
-			let vector_message = match &self.owner {
-				ActorOwner::DynamicServer { server_id } => VectorMessage::DynamicServers {
-					server_id: server_id.as_str(),
-					task: "main", // Backwards compatibility with logs
-					stream_type: message.stream_type as u8,
-					ts: message.ts,
-					message: message.message.as_str(),
-				},
-			};
-
-			serde_json::to_writer(&mut stream, &vector_message)?;
-			stream.write_all(b"\n")?;
+		// One joiner per stream type
+		let mut joiners = [
+			MultilineJoiner::new(self.multiline_pattern.as_deref())?,
+			MultilineJoiner::new(self.multiline_pattern.as_deref())?,
+		];
+
+		loop {
+			match self
+				.msg_rx
+				.recv_timeout(structured::MULTILINE_FLUSH_INTERVAL)
+			{
+				Result::Ok(message) => {
+					if let Some(entry) =
+						joiners[message.stream_type as usize].push(message.ts, message.message)
+					{
+						self.send_entry(&mut stream, message.stream_type, entry)?;
+					}
+				}
+				Err(mpsc::RecvTimeoutError::Timeout) => {}
+				Err(mpsc::RecvTimeoutError::Disconnected) => break,
+			}
+
+			for stream_type in [StreamType::StdOut, StreamType::StdErr] {
+				if let Some(entry) = joiners[stream_type as usize].flush_expired() {
+					self.send_entry(&mut stream, stream_type, entry)?;
+				}
+			}
 		}
 
 		println!("Log shipper msg_rx disconnected");
 
+		for stream_type in [StreamType::StdOut, StreamType::StdErr] {
+			if let Some(entry) = joiners[stream_type as usize].flush() {
+				self.send_entry(&mut stream, stream_type, entry)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn send_entry(
+		&self,
+		stream: &mut TcpStream,
+		stream_type: StreamType,
+		entry: structured::Entry,
+	) -> Result<()> {
+		let vector_message = match &self.owner {
+			ActorOwner::DynamicServer { server_id } => VectorMessage::DynamicServers {
+				server_id: server_id.as_str(),
+				task: "main", // Backwards compatibility with logs
+				stream_type: stream_type as u8,
+				ts: entry.ts,
+				message: entry.message.as_str(),
+				level: entry.level.as_deref(),
+				fields: &entry.fields,
+			},
+		};
+
+		serde_json::to_writer(&mut *stream, &vector_message)?;
+		stream.write_all(b"\n")?;
+
 		Ok(())
 	}
 }
@@ -122,5 +170,9 @@ enum VectorMessage<'a> {
 		stream_type: u8,
 		ts: u64,
 		message: &'a str,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		level: Option<&'a str>,
+		#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+		fields: &'a BTreeMap<String, String>,
 	},
 }
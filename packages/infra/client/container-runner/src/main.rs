@@ -36,6 +36,7 @@ fn main() -> Result<()> {
 		.map(|x| x.parse())
 		.transpose()
 		.context("failed to parse vector socket addr")?;
+	let multiline_pattern = var("VECTOR_MULTILINE_PATTERN").ok();
 	let owner = match var("OWNER").ok() {
 		Some(x) if x == "dynamic_server" => ActorOwner::DynamicServer {
 			server_id: var("SERVER_ID")?,
@@ -54,6 +55,7 @@ fn main() -> Result<()> {
 			shutdown_rx,
 			msg_rx,
 			vector_socket_addr,
+			multiline_pattern,
 			owner,
 		};
 		let log_shipper_thread = log_shipper.spawn();
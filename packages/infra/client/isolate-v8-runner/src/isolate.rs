@@ -30,7 +30,7 @@ use pegboard_config::isolate_runner as config;
 use tokio::{fs, sync::mpsc};
 use uuid::Uuid;
 
-use crate::{ext, log_shipper, metadata::JsMetadata, utils};
+use crate::{ext, log_shipper, metadata::JsMetadata, metrics, utils};
 
 pub fn run(
 	config: config::Config,
@@ -67,6 +67,7 @@ pub fn run(
 				shutdown_rx,
 				msg_rx,
 				vector_socket_addr: vector_socket_addr.clone(),
+				multiline_pattern: actor_config.vector_multiline_pattern.clone(),
 				owner: actor_config.owner.clone(),
 			};
 			let log_shipper_thread = log_shipper.spawn();
@@ -285,6 +286,14 @@ pub async fn run_inner(
 	terminate_tx.send(worker.terminate_handle().clone()).await?;
 	drop(terminate_tx);
 
+	// Periodically write resource usage for the manager
+	let (metrics_stop_tx, metrics_stop_rx) = smpsc::channel();
+	let metrics_thread = metrics::spawn(
+		&actor_path,
+		worker.v8_isolate().thread_safe_handle(),
+		metrics_stop_rx,
+	)?;
+
 	// First step preloads the module. This can throw a JS error from certain syntax.
 	match worker.preload_main_module(&index_module).await {
 		Ok(module_id) => {
@@ -379,6 +388,12 @@ pub async fn run_inner(
 	// Drop worker and writer so the stdout and stderr pipes close
 	drop(worker);
 
+	// Stop metrics thread
+	drop(metrics_stop_tx);
+	if metrics_thread.join().is_err() {
+		tracing::error!(?actor_id, "metrics thread panicked");
+	}
+
 	wait_logs_complete(actor_id, stderr_writer2, stdout_handle, stderr_handle)?;
 
 	Ok(exit_code)
@@ -609,6 +624,7 @@ mod tests {
 				server_id: actor_id,
 			},
 			vector_socket_addr: Default::default(),
+			vector_multiline_pattern: Default::default(),
 		};
 
 		let exit_code = run_inner(
@@ -28,6 +28,7 @@ mod ext;
 mod isolate;
 mod log_shipper;
 mod metadata;
+mod metrics;
 mod throttle;
 mod utils;
 
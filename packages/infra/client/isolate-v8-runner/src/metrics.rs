@@ -0,0 +1,110 @@
+use std::{
+	ffi::c_void,
+	path::{Path, PathBuf},
+	result::Result::{Err, Ok},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		mpsc as smpsc, Arc,
+	},
+	thread::JoinHandle,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::*;
+use deno_core::v8;
+use nix::libc;
+use pegboard_config::isolate_runner::actor::Metrics;
+
+/// How often the isolate's resource usage is written.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+struct State {
+	metrics_path: PathBuf,
+	/// Set while an interrupt has been requested but not yet handled by the isolate.
+	pending: AtomicBool,
+}
+
+/// Periodically writes the isolate's resource usage to `metrics.json` in the actor's directory, where it is
+/// read by the manager.
+///
+/// Heap statistics can only be read from the isolate's own thread, so the isolate is interrupted to take
+/// each sample. Runs until `stop_rx` is dropped.
+pub fn spawn(
+	actor_path: &Path,
+	handle: v8::IsolateHandle,
+	stop_rx: smpsc::Receiver<()>,
+) -> Result<JoinHandle<()>> {
+	let state = Arc::new(State {
+		metrics_path: actor_path.join("metrics.json"),
+		pending: AtomicBool::new(false),
+	});
+
+	std::thread::Builder::new()
+		.name("metrics".into())
+		.spawn(move || loop {
+			match stop_rx.recv_timeout(SAMPLE_INTERVAL) {
+				Err(smpsc::RecvTimeoutError::Timeout) => {}
+				_ => break,
+			}
+
+			// An idle isolate does not handle interrupts. Its usage has not changed so the previous sample
+			// is still accurate.
+			if state.pending.swap(true, Ordering::AcqRel) {
+				continue;
+			}
+
+			let data = Arc::into_raw(state.clone()) as *mut c_void;
+			if !handle.request_interrupt(interrupt_callback, data) {
+				// Isolate was disposed
+				// SAFETY: The callback will never run, reclaim the reference passed to it
+				drop(unsafe { Arc::from_raw(data as *const State) });
+				break;
+			}
+		})
+		.map_err(Into::into)
+}
+
+extern "C" fn interrupt_callback(isolate: &mut v8::Isolate, data: *mut c_void) {
+	// SAFETY: Created with `Arc::into_raw` in `spawn`
+	let state = unsafe { Arc::from_raw(data as *const State) };
+	state.pending.store(false, Ordering::Release);
+
+	if let Err(err) = write_metrics(isolate, &state.metrics_path) {
+		tracing::error!(?err, "failed to write isolate metrics");
+	}
+}
+
+fn write_metrics(isolate: &mut v8::Isolate, metrics_path: &Path) -> Result<()> {
+	let mut heap_stats = v8::HeapStatistics::default();
+	isolate.get_heap_statistics(&mut heap_stats);
+
+	// The interrupt runs on the isolate's thread
+	let mut cpu_time = libc::timespec {
+		tv_sec: 0,
+		tv_nsec: 0,
+	};
+	if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut cpu_time) } != 0 {
+		bail!(
+			"failed to read thread cpu time: {}",
+			std::io::Error::last_os_error()
+		);
+	}
+
+	let metrics = Metrics {
+		ts: SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.expect("time went backwards")
+			.as_millis()
+			.try_into()?,
+		cpu_usage_usec: cpu_time.tv_sec as u64 * 1_000_000 + cpu_time.tv_nsec as u64 / 1_000,
+		used_heap_size: heap_stats.used_heap_size().try_into()?,
+		total_heap_size: heap_stats.total_heap_size().try_into()?,
+	};
+
+	// Write atomically so the manager never reads a partial file
+	let tmp_path = metrics_path.with_extension("json.tmp");
+	std::fs::write(&tmp_path, serde_json::to_vec(&metrics)?)?;
+	std::fs::rename(&tmp_path, metrics_path)?;
+
+	Ok(())
+}
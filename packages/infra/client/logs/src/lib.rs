@@ -4,6 +4,8 @@ use anyhow::*;
 use chrono::{Datelike, Duration, TimeDelta, TimeZone, Utc};
 use tokio::fs;
 
+pub mod structured;
+
 pub struct Logs {
 	path: PathBuf,
 	retention: Duration,
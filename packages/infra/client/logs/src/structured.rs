@@ -0,0 +1,198 @@
+use std::{
+	collections::BTreeMap,
+	time::{Duration, Instant},
+};
+
+use anyhow::*;
+use regex::Regex;
+
+/// Matches continuation lines when no pattern is configured. Joins indented lines (i.e. stack trace
+/// frames) with the line before them.
+pub const DEFAULT_MULTILINE_PATTERN: &str = r"^[ \t]+\S";
+/// How long to wait for more continuation lines before shipping an entry.
+pub const MULTILINE_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+/// Maximum number of lines joined in to a single entry.
+const MAX_ENTRY_LINES: usize = 256;
+/// Maximum length of a joined entry.
+const MAX_ENTRY_BYTES: usize = 16 * 1024;
+/// Maximum number of fields extracted from a JSON line.
+const MAX_FIELDS: usize = 32;
+
+const LEVEL_KEYS: &[&str] = &["level", "lvl", "severity"];
+const MESSAGE_KEYS: &[&str] = &["message", "msg"];
+
+/// A log entry ready to be shipped.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+	/// Nanoseconds.
+	pub ts: u64,
+	pub message: String,
+	/// Lowercase level, if the entry is a JSON line that has one.
+	pub level: Option<String>,
+	/// Remaining keys of a JSON line. Non-string values are JSON encoded.
+	pub fields: BTreeMap<String, String>,
+}
+
+impl Entry {
+	/// Extracts the level, message and fields if the message is a JSON object. Any other message is kept
+	/// as is.
+	pub fn parse(ts: u64, message: String) -> Self {
+		let mut entry = Entry {
+			ts,
+			message,
+			level: None,
+			fields: BTreeMap::new(),
+		};
+
+		if !entry.message.trim_start().starts_with('{') {
+			return entry;
+		}
+
+		let Some(object) =
+			serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&entry.message).ok()
+		else {
+			return entry;
+		};
+
+		let mut message = None;
+		for (key, value) in object {
+			let value = match value {
+				serde_json::Value::String(value) => value,
+				value => value.to_string(),
+			};
+
+			if entry.level.is_none() && LEVEL_KEYS.contains(&key.as_str()) {
+				entry.level = Some(value.to_lowercase());
+			} else if message.is_none() && MESSAGE_KEYS.contains(&key.as_str()) {
+				message = Some(value);
+			} else if entry.fields.len() < MAX_FIELDS {
+				entry.fields.insert(key, value);
+			}
+		}
+
+		// JSON lines without a message key keep the raw line
+		if let Some(message) = message {
+			entry.message = message;
+		}
+
+		entry
+	}
+}
+
+/// Joins continuation lines (i.e. stack traces) with the line before them.
+pub struct MultilineJoiner {
+	pattern: Regex,
+	pending: Option<Pending>,
+}
+
+struct Pending {
+	ts: u64,
+	message: String,
+	lines: usize,
+	last_line: Instant,
+}
+
+impl MultilineJoiner {
+	pub fn new(pattern: Option<&str>) -> Result<Self> {
+		Ok(MultilineJoiner {
+			pattern: Regex::new(pattern.unwrap_or(DEFAULT_MULTILINE_PATTERN))
+				.context("invalid multiline pattern")?,
+			pending: None,
+		})
+	}
+
+	/// Adds a line. Returns the previous entry if this line starts a new one.
+	pub fn push(&mut self, ts: u64, line: String) -> Option<Entry> {
+		if let Some(pending) = &mut self.pending {
+			if self.pattern.is_match(&line)
+				&& pending.lines < MAX_ENTRY_LINES
+				&& pending.message.len() + line.len() < MAX_ENTRY_BYTES
+			{
+				pending.message.push('\n');
+				pending.message.push_str(&line);
+				pending.lines += 1;
+				pending.last_line = Instant::now();
+
+				return None;
+			}
+		}
+
+		let entry = self.flush();
+		self.pending = Some(Pending {
+			ts,
+			message: line,
+			lines: 1,
+			last_line: Instant::now(),
+		});
+
+		entry
+	}
+
+	/// Returns the pending entry if no continuation lines were received for `MULTILINE_FLUSH_INTERVAL`.
+	pub fn flush_expired(&mut self) -> Option<Entry> {
+		if self
+			.pending
+			.as_ref()
+			.map(|pending| pending.last_line.elapsed() >= MULTILINE_FLUSH_INTERVAL)
+			.unwrap_or_default()
+		{
+			self.flush()
+		} else {
+			None
+		}
+	}
+
+	/// Returns the pending entry.
+	pub fn flush(&mut self) -> Option<Entry> {
+		self.pending
+			.take()
+			.map(|pending| Entry::parse(pending.ts, pending.message))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_json() {
+		let entry = Entry::parse(
+			1,
+			r#"{"level":"WARN","msg":"slow request","path":"/health","ms":120}"#.to_string(),
+		);
+
+		assert_eq!(entry.level.as_deref(), Some("warn"));
+		assert_eq!(entry.message, "slow request");
+		assert_eq!(
+			entry.fields.get("path").map(String::as_str),
+			Some("/health")
+		);
+		assert_eq!(entry.fields.get("ms").map(String::as_str), Some("120"));
+	}
+
+	#[test]
+	fn parse_plain() {
+		let entry = Entry::parse(1, "{not json".to_string());
+
+		assert_eq!(entry.level, None);
+		assert_eq!(entry.message, "{not json");
+		assert!(entry.fields.is_empty());
+	}
+
+	#[test]
+	fn join_continuation_lines() {
+		let mut joiner = MultilineJoiner::new(None).unwrap();
+
+		assert_eq!(joiner.push(1, "Error: boom".to_string()), None);
+		assert_eq!(
+			joiner.push(2, "    at main (index.js:1:1)".to_string()),
+			None
+		);
+
+		let entry = joiner.push(3, "next".to_string()).unwrap();
+		assert_eq!(entry.ts, 1);
+		assert_eq!(entry.message, "Error: boom\n    at main (index.js:1:1)");
+
+		assert_eq!(joiner.flush().unwrap().message, "next");
+	}
+}
@@ -14,10 +14,11 @@ use uuid::Uuid;
 
 use crate::{ctx::Ctx, runner, utils};
 
-mod oci_config;
+pub mod oci_config;
 mod partial_oci_config;
 mod seccomp;
 mod setup;
+pub mod usage;
 
 /// How often to check for a PID when one is not present and a stop command was received.
 const STOP_PID_INTERVAL: Duration = std::time::Duration::from_millis(250);
@@ -27,19 +28,28 @@ const STOP_PID_RETRIES: usize = 32;
 pub struct Actor {
 	actor_id: Uuid,
 	config: protocol::ActorConfig,
+	/// W3C trace context of the command that started this actor. Not persisted across restarts.
+	traceparent: Option<String>,
 
 	runner: Mutex<Option<runner::Handle>>,
 	exited: Mutex<bool>,
+	usage: Mutex<usage::UsageState>,
 }
 
 impl Actor {
-	pub fn new(actor_id: Uuid, config: protocol::ActorConfig) -> Arc<Self> {
+	pub fn new(
+		actor_id: Uuid,
+		config: protocol::ActorConfig,
+		traceparent: Option<String>,
+	) -> Arc<Self> {
 		Arc::new(Actor {
 			actor_id,
 			config,
+			traceparent,
 
 			runner: Mutex::new(None),
 			exited: Mutex::new(false),
+			usage: Mutex::new(usage::UsageState::default()),
 		})
 	}
 
@@ -51,17 +61,29 @@ impl Actor {
 		Arc::new(Actor {
 			actor_id,
 			config,
+			traceparent: None,
 
 			runner: Mutex::new(Some(runner)),
 			exited: Mutex::new(false),
+			usage: Mutex::new(usage::UsageState::default()),
 		})
 	}
 
+	pub fn uses_volume(&self, volume_id: Uuid) -> bool {
+		self.config
+			.volumes
+			.iter()
+			.any(|volume| volume.volume_id == volume_id)
+	}
+
 	pub async fn start(self: &Arc<Self>, ctx: &Arc<Ctx>) -> Result<()> {
 		tracing::info!(actor_id=?self.actor_id, "starting");
 
-		// Write actor to DB
-		let config_json = serde_json::to_vec(&self.config)?;
+		// Write actor to DB. Secret values are only kept in memory
+		let config_json = serde_json::to_vec(&protocol::ActorConfig {
+			secrets: Default::default(),
+			..self.config.clone()
+		})?;
 
 		utils::sql::query(|| async {
 			// NOTE: On conflict here in case this query runs but the command is not acknowledged
@@ -84,10 +106,13 @@ impl Actor {
 		})
 		.await?;
 
-		ctx.event(protocol::Event::ActorStateUpdate {
-			actor_id: self.actor_id,
-			state: protocol::ActorState::Starting,
-		})
+		ctx.event(
+			protocol::Event::ActorStateUpdate {
+				actor_id: self.actor_id,
+				state: protocol::ActorState::Starting,
+			},
+			self.traceparent.as_deref(),
+		)
 		.await?;
 
 		// Lifecycle
@@ -139,6 +164,11 @@ impl Actor {
 
 		match self.config.image.kind {
 			protocol::ImageKind::DockerImage | protocol::ImageKind::OciBundle => {
+				// Mount persistent volumes
+				for volume in &self.config.volumes {
+					ctx.volumes.setup(&ctx, volume).await?;
+				}
+
 				self.setup_oci_bundle(&ctx, &ports).await?;
 
 				// Run CNI setup script
@@ -165,6 +195,10 @@ impl Actor {
 		)];
 		if let Some(vector) = &ctx.config().vector {
 			runner_env.push(("VECTOR_SOCKET_ADDR", vector.address.to_string()));
+
+			if let Some(multiline_pattern) = &vector.multiline_pattern {
+				runner_env.push(("VECTOR_MULTILINE_PATTERN", multiline_pattern.clone()));
+			}
 		}
 		runner_env.extend(self.config.owner.env());
 
@@ -219,13 +253,16 @@ impl Actor {
 		})
 		.await?;
 
-		ctx.event(protocol::Event::ActorStateUpdate {
-			actor_id: self.actor_id,
-			state: protocol::ActorState::Running {
-				pid: pid.as_raw().try_into()?,
-				ports,
+		ctx.event(
+			protocol::Event::ActorStateUpdate {
+				actor_id: self.actor_id,
+				state: protocol::ActorState::Running {
+					pid: pid.as_raw().try_into()?,
+					ports,
+				},
 			},
-		})
+			self.traceparent.as_deref(),
+		)
 		.await?;
 
 		Ok(())
@@ -377,10 +414,13 @@ impl Actor {
 
 			// Emit event if not stopped before
 			if stop_ts_set {
-				ctx.event(protocol::Event::ActorStateUpdate {
-					actor_id: self.actor_id,
-					state: protocol::ActorState::Stopped,
-				})
+				ctx.event(
+					protocol::Event::ActorStateUpdate {
+						actor_id: self.actor_id,
+						state: protocol::ActorState::Stopped,
+					},
+					self.traceparent.as_deref(),
+				)
 				.await?;
 			}
 		}
@@ -432,10 +472,13 @@ impl Actor {
 		})
 		.await?;
 
-		ctx.event(protocol::Event::ActorStateUpdate {
-			actor_id: self.actor_id,
-			state: protocol::ActorState::Exited { exit_code },
-		})
+		ctx.event(
+			protocol::Event::ActorStateUpdate {
+				actor_id: self.actor_id,
+				state: protocol::ActorState::Exited { exit_code },
+			},
+			self.traceparent.as_deref(),
+		)
 		.await?;
 
 		*guard = true;
@@ -453,6 +496,8 @@ impl Actor {
 			actors.remove(&self.actor_id);
 		}
 
+		self.cleanup_usage();
+
 		// Set exit code if it hasn't already been set
 		self.set_exit_code(ctx, None).await?;
 
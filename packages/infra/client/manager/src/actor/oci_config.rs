@@ -1,12 +1,16 @@
 use anyhow::*;
 use serde_json::json;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::{partial_oci_config::PartialOciConfigUser, seccomp};
 
+/// Open file descriptor limit used when the actor does not set one.
+const DEFAULT_NOFILE: u64 = 1024;
+
 pub struct ConfigOpts<'a> {
 	pub actor_path: &'a Path,
 	pub netns_path: &'a Path,
+	pub cgroups_path: String,
 	pub args: Vec<String>,
 	pub env: Vec<String>,
 	pub user: PartialOciConfigUser,
@@ -14,6 +18,16 @@ pub struct ConfigOpts<'a> {
 	pub cpu: u64,
 	pub memory: u64,
 	pub memory_max: u64,
+	pub pids: Option<u64>,
+	pub nofile: Option<u64>,
+	pub io_weight: Option<u16>,
+	pub io_read_bps: Option<u64>,
+	pub io_write_bps: Option<u64>,
+	/// Major and minor number of the block device backing the actor's file system. I/O bandwidth
+	/// limits are only applied if set.
+	pub fs_device: Option<(u64, u64)>,
+	/// Persistent volumes to bind mount into the container, as (source, destination).
+	pub volumes: Vec<(PathBuf, String)>,
 }
 
 /// Generates base config.json for an OCI bundle.
@@ -37,6 +51,8 @@ pub fn config(opts: ConfigOpts) -> Result<serde_json::Value> {
 		tracing::warn!(?cpu_shares, "cpu_shares < 1");
 	}
 
+	let nofile = opts.nofile.unwrap_or(DEFAULT_NOFILE);
+
 	// This is a modified version of the default config.json generated by actord.
 	//
 	// Some values will be overridden at runtime by the values in the OCI bundle's config.json.
@@ -63,8 +79,8 @@ pub fn config(opts: ConfigOpts) -> Result<serde_json::Value> {
 			"rlimits": [
 				{
 					"type": "RLIMIT_NOFILE",
-					"hard": 1024,
-					"soft": 1024
+					"hard": nofile,
+					"soft": nofile
 				}
 			],
 			"noNewPrivileges": true
@@ -72,7 +88,6 @@ pub fn config(opts: ConfigOpts) -> Result<serde_json::Value> {
 			// TODO: oomScoreAdj
 			// TODO: scheduler
 			// TODO: iopriority
-			// TODO: rlimit?
 		},
 		"root": {
 			"path": "rootfs",
@@ -81,6 +96,8 @@ pub fn config(opts: ConfigOpts) -> Result<serde_json::Value> {
 		},
 		"mounts": mounts(&opts)?,
 		"linux": {
+			// Read by `Actor::sample_container`
+			"cgroupsPath": opts.cgroups_path,
 			"resources": {
 				"devices": linux_resources_devices(),
 				"cpu": {
@@ -102,11 +119,11 @@ pub fn config(opts: ConfigOpts) -> Result<serde_json::Value> {
 					"reservation": opts.memory,
 					"limit": opts.memory_max,
 				},
+				"pids": opts.pids.map(|limit| json!({ "limit": limit })),
+				"blockIO": linux_resources_block_io(&opts),
 
-				// TODO: network
-				// TODO: pids
+				// Network egress is shaped by the CNI bandwidth plugin, see `setup_cni_network`
 				// TODO: hugepageLimits
-				// TODO: blockIO
 			},
 			"namespaces": [
 				{ "type": "pid" },
@@ -160,7 +177,7 @@ fn capabilities() -> Vec<&'static str> {
 }
 
 fn mounts(opts: &ConfigOpts) -> Result<serde_json::Value> {
-	Ok(json!([
+	let mut mounts = json!([
 		{
 			"destination": "/proc",
 			"type": "proc",
@@ -245,7 +262,38 @@ fn mounts(opts: &ConfigOpts) -> Result<serde_json::Value> {
 			"source": opts.actor_path.join("resolv.conf").to_str().context("resolv.conf path")?,
 			"options": ["rbind", "rprivate"]
 		},
-	]))
+	]);
+
+	let mounts_arr = mounts.as_array_mut().context("mounts")?;
+	for (source, destination) in &opts.volumes {
+		mounts_arr.push(json!({
+			"destination": destination,
+			"type": "bind",
+			"source": source.to_str().context("volume path")?,
+			"options": ["rbind", "rprivate"]
+		}));
+	}
+
+	Ok(mounts)
+}
+
+fn linux_resources_block_io(opts: &ConfigOpts) -> serde_json::Value {
+	// Throttles apply per device
+	let throttle = |rate: Option<u64>| match (opts.fs_device, rate) {
+		(Some((major, minor)), Some(rate)) => json!([{
+			"major": major,
+			"minor": minor,
+			"rate": rate,
+		}]),
+		_ => json!([]),
+	};
+
+	json!({
+		// Corresponds to io.weight in cgroups. Must be [10, 1000]
+		"weight": opts.io_weight.map(|weight| weight.clamp(10, 1000)),
+		"throttleReadBpsDevice": throttle(opts.io_read_bps),
+		"throttleWriteBpsDevice": throttle(opts.io_write_bps),
+	})
 }
 
 fn linux_resources_devices() -> serde_json::Value {
@@ -1,5 +1,6 @@
 use std::{
 	collections::HashMap,
+	os::unix::fs::MetadataExt,
 	path::{Path, PathBuf},
 	process::Stdio,
 	result::Result::{Err, Ok},
@@ -17,10 +18,11 @@ use tokio::{
 	io::{AsyncReadExt, AsyncWriteExt},
 	process::Command,
 };
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 
-use super::{oci_config, Actor};
-use crate::{ctx::Ctx, utils};
+use super::{oci_config, usage, Actor};
+use crate::{ctx::Ctx, image_cache::ArtifactKind, utils};
 
 impl Actor {
 	pub async fn make_fs(&self, ctx: &Ctx) -> Result<()> {
@@ -73,13 +75,40 @@ impl Actor {
 		let actor_path = ctx.actor_path(self.actor_id);
 		let fs_path = actor_path.join("fs");
 
-		let mut stream = utils::fetch_image_stream(
-			ctx,
-			self.config.image.id,
-			&self.config.image.artifact_url_stub,
-			self.config.image.fallback_artifact_url.as_deref(),
-		)
-		.await?;
+		// The leases are held until the image is unpacked so the cached artifacts are not evicted while
+		// in use
+		let image = ctx
+			.image_cache
+			.fetch(
+				ctx,
+				self.config.image.id,
+				ArtifactKind::Image,
+				&self.config.image.artifact_url_stub,
+				self.config.image.fallback_artifact_url.as_deref(),
+				self.config.image.digest.as_deref(),
+			)
+			.await?;
+		let dictionary = if let Some(dictionary) = &self.config.image.compression_dictionary {
+			tracing::info!(actor_id=?self.actor_id, "downloading compression dictionary");
+
+			Some(
+				ctx.image_cache
+					.fetch(
+						ctx,
+						self.config.image.id,
+						ArtifactKind::CompressionDictionary,
+						&dictionary.artifact_url_stub,
+						dictionary.fallback_artifact_url.as_deref(),
+						None,
+					)
+					.await?,
+			)
+		} else {
+			None
+		};
+		let dictionary_path = dictionary.as_ref().map(|dictionary| dictionary.path());
+
+		let mut stream = ReaderStream::new(File::open(image.path()).await?);
 
 		match self.config.image.kind {
 			protocol::ImageKind::DockerImage => {
@@ -96,37 +125,35 @@ impl Actor {
 							output_file.write_all(&chunk?).await?;
 						}
 					}
-					protocol::ImageCompression::Lz4 => {
+					protocol::ImageCompression::Lz4 | protocol::ImageCompression::Zstd => {
 						tracing::info!(actor_id=?self.actor_id, "decompressing artifact");
 
-						// Spawn the lz4 process
-						let mut lz4_child = Command::new("lz4")
-							.arg("-d")
-							.arg("-")
-							.arg(&docker_image_path)
-							.stdin(Stdio::piped())
-							.spawn()?;
+						// Spawn the decompression process
+						let (mut decompress_cmd, decompress_name) =
+							self.decompress_cmd(dictionary_path, Some(&docker_image_path))?;
+						let mut decompress_child = decompress_cmd.stdin(Stdio::piped()).spawn()?;
 
-						// Take the stdin of lz4
-						let mut lz4_stdin = lz4_child.stdin.take().context("lz4 stdin")?;
+						// Take the stdin of the decompression process
+						let mut decompress_stdin =
+							decompress_child.stdin.take().context("decompress stdin")?;
 
 						tokio::try_join!(
-							// Pipe the response body to lz4 stdin
+							// Pipe the response body to decompression stdin
 							async move {
 								while let Some(chunk) = stream.next().await {
 									let data = chunk?;
-									lz4_stdin.write_all(&data).await?;
+									decompress_stdin.write_all(&data).await?;
 								}
-								lz4_stdin.shutdown().await?;
+								decompress_stdin.shutdown().await?;
 
 								anyhow::Ok(())
 							},
 							// Wait for child process
 							async {
-								let cmd_out = lz4_child.wait_with_output().await?;
+								let cmd_out = decompress_child.wait_with_output().await?;
 								ensure!(
 									cmd_out.status.success(),
-									"failed `lz4` command\n{}",
+									"failed `{decompress_name}` command\n{}",
 									std::str::from_utf8(&cmd_out.stderr)?
 								);
 
@@ -176,12 +203,13 @@ impl Actor {
 							},
 						)?;
 					}
-					protocol::ImageCompression::Lz4 => {
+					protocol::ImageCompression::Lz4 | protocol::ImageCompression::Zstd => {
 						tracing::info!(actor_id=?self.actor_id, "decompressing and unarchiving artifact");
 
-						// Spawn the lz4 process
-						let mut lz4_child = Command::new("lz4")
-							.arg("-d")
+						// Spawn the decompression process
+						let (mut decompress_cmd, decompress_name) =
+							self.decompress_cmd(dictionary_path, None)?;
+						let mut decompress_child = decompress_cmd
 							.stdin(Stdio::piped())
 							.stdout(Stdio::piped())
 							.spawn()?;
@@ -194,27 +222,31 @@ impl Actor {
 							.stdin(Stdio::piped())
 							.spawn()?;
 
-						// Take the stdin of lz4 and tar processes
-						let mut lz4_stdin = lz4_child.stdin.take().context("lz4 stdin")?;
-						let mut lz4_stdout = lz4_child.stdout.take().context("lz4 stdout")?;
+						// Take the stdin of decompression and tar processes
+						let mut decompress_stdin =
+							decompress_child.stdin.take().context("decompress stdin")?;
+						let mut decompress_stdout = decompress_child
+							.stdout
+							.take()
+							.context("decompress stdout")?;
 						let mut tar_stdin = tar_child.stdin.take().context("tar stdin")?;
 
 						tokio::try_join!(
-							// Pipe the response body to lz4 stdin
+							// Pipe the response body to decompression stdin
 							async move {
 								while let Some(chunk) = stream.next().await {
 									let data = chunk?;
-									lz4_stdin.write_all(&data).await?;
+									decompress_stdin.write_all(&data).await?;
 								}
-								lz4_stdin.shutdown().await?;
+								decompress_stdin.shutdown().await?;
 
 								anyhow::Ok(())
 							},
-							// Pipe lz4 stdout to tar stdin
+							// Pipe decompression stdout to tar stdin
 							async move {
 								let mut buffer = [0; 8192];
 								loop {
-									let n = lz4_stdout.read(&mut buffer).await?;
+									let n = decompress_stdout.read(&mut buffer).await?;
 									if n == 0 {
 										break;
 									}
@@ -226,10 +258,10 @@ impl Actor {
 							},
 							// Wait for child processes
 							async {
-								let cmd_out = lz4_child.wait_with_output().await?;
+								let cmd_out = decompress_child.wait_with_output().await?;
 								ensure!(
 									cmd_out.status.success(),
-									"failed `lz4` command\n{}",
+									"failed `{decompress_name}` command\n{}",
 									std::str::from_utf8(&cmd_out.stderr)?
 								);
 
@@ -254,6 +286,43 @@ impl Actor {
 		Ok(())
 	}
 
+	/// Builds the command that decompresses the image from stdin. Writes to `output_path` if set,
+	/// otherwise to stdout.
+	fn decompress_cmd(
+		&self,
+		dictionary_path: Option<&Path>,
+		output_path: Option<&Path>,
+	) -> Result<(Command, &'static str)> {
+		match self.config.image.compression {
+			protocol::ImageCompression::None => bail!("image is not compressed"),
+			protocol::ImageCompression::Lz4 => {
+				let mut cmd = Command::new("lz4");
+				cmd.arg("-d");
+				if let Some(output_path) = output_path {
+					cmd.arg("-").arg(output_path);
+				}
+
+				Ok((cmd, "lz4"))
+			}
+			protocol::ImageCompression::Zstd => {
+				let mut cmd = Command::new("zstd");
+				cmd.arg("-d");
+
+				if let Some(dictionary_path) = dictionary_path {
+					cmd.arg("-D").arg(dictionary_path);
+				}
+
+				if let Some(output_path) = output_path {
+					cmd.arg("-o").arg(output_path);
+				} else {
+					cmd.arg("-c");
+				}
+
+				Ok((cmd, "zstd"))
+			}
+		}
+	}
+
 	pub async fn setup_oci_bundle(
 		&self,
 		ctx: &Ctx,
@@ -325,19 +394,54 @@ impl Actor {
 			)
 			.collect::<Vec<String>>();
 
+		// I/O bandwidth limits are applied to the block device backing the actor's fs. This is only a
+		// whole block device (which cgroups require) when the fs is a loop mount.
+		let resources = &self.config.resources;
+		let fs_device = if resources.io_read_bps.is_some() || resources.io_write_bps.is_some() {
+			if ctx.config().runner.use_mounts() {
+				let dev = fs::metadata(&fs_path).await?.dev();
+
+				Some((nix::sys::stat::major(dev), nix::sys::stat::minor(dev)))
+			} else {
+				tracing::warn!(actor_id=?self.actor_id, "cannot limit i/o bandwidth without mounts");
+
+				None
+			}
+		} else {
+			None
+		};
+
 		// Replace the config.json with a new config
 		//
 		// This config selectively uses parts from the user's OCI bundle in order to maintain security
 		let config = oci_config::config(oci_config::ConfigOpts {
 			actor_path: &actor_path,
 			netns_path: &netns_path,
+			cgroups_path: usage::cgroups_path(self.actor_id),
 			args: user_config.process.args,
 			env,
 			user: user_config.process.user,
 			cwd: user_config.process.cwd,
-			cpu: self.config.resources.cpu,
-			memory: self.config.resources.memory,
-			memory_max: self.config.resources.memory_max,
+			cpu: resources.cpu,
+			memory: resources.memory,
+			memory_max: resources.memory_max,
+			pids: resources.pids,
+			nofile: resources.nofile,
+			io_weight: resources.io_weight,
+			io_read_bps: resources.io_read_bps,
+			io_write_bps: resources.io_write_bps,
+			fs_device,
+			volumes: self
+				.config
+				.volumes
+				.iter()
+				.map(|volume| {
+					(
+						ctx.volume_path(volume.volume_id).join("fs"),
+						volume.path.clone(),
+					)
+				})
+				.collect(),
 		})?;
 		fs::write(oci_bundle_config_path, serde_json::to_vec(&config)?).await?;
 
@@ -398,7 +502,12 @@ impl Actor {
 			env: self.build_default_env(ctx, &ports),
 			metadata: self.config.metadata.clone(),
 			owner: self.config.owner.clone(),
-			vector_socket_addr: ctx.config().vector.clone().map(|x| x.address),
+			vector_socket_addr: ctx.config().vector.as_ref().map(|x| x.address.clone()),
+			vector_multiline_pattern: ctx
+				.config()
+				.vector
+				.as_ref()
+				.and_then(|x| x.multiline_pattern.clone()),
 		};
 
 		fs::write(
@@ -443,9 +552,20 @@ impl Actor {
 		//
 		// See supported args:
 		// https://github.com/actord/go-cni/blob/6603d5bd8941d7f2026bb5627f6aa4ff434f859a/namespace_opts.go#L22
-		let cni_params = json!({
+		let mut cni_params = json!({
 			"portMappings": cni_port_mappings,
 		});
+
+		// Shape egress with the bandwidth plugin. Rates and bursts are in bits.
+		//
+		// See https://www.cni.dev/plugins/current/meta/bandwidth/
+		if let Some(egress_bps) = self.config.resources.egress_bps {
+			cni_params["bandwidth"] = json!({
+				"egressRate": egress_bps * 8,
+				"egressBurst": egress_bps * 8,
+			});
+		}
+
 		let cni_params_json = serde_json::to_string(&cni_params)?;
 		fs::write(
 			actor_path.join("cni-cap-args.json"),
@@ -719,6 +839,7 @@ impl Actor {
 		self.config
 			.env
 			.iter()
+			.chain(self.config.secrets.iter())
 			.map(|(k, v)| (k.clone(), v.clone()))
 			// Add port env vars and api endpoint
 			.chain(ports.iter().map(|(label, port)| {
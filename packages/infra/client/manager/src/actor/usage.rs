@@ -0,0 +1,356 @@
+use std::{
+	path::Path,
+	result::Result::{Err, Ok},
+	time::Duration,
+};
+
+use anyhow::*;
+use pegboard::protocol;
+use pegboard_config::isolate_runner::actor as actor_config;
+use tokio::fs;
+use uuid::Uuid;
+
+use super::Actor;
+use crate::{ctx::Ctx, metrics, utils};
+
+/// How often the resource usage of each actor is sampled.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// Length of the window summarized by each `ActorMetrics` entry.
+pub const WINDOW_DURATION_MS: i64 = 60_000;
+
+/// Cgroup created by runc for the container, relative to the cgroup root.
+pub fn cgroups_path(actor_id: Uuid) -> String {
+	format!("/pegboard/{actor_id}")
+}
+
+#[derive(Default)]
+pub struct UsageState {
+	prev: Option<Sample>,
+	window: Option<Window>,
+}
+
+/// Cumulative resource usage of an actor at a point in time.
+#[derive(Clone)]
+pub struct Sample {
+	/// Milliseconds.
+	pub ts: i64,
+	pub cpu_usage_usec: u64,
+	/// Bytes.
+	pub memory: u64,
+	/// Bytes received and sent.
+	pub network: Option<(u64, u64)>,
+	/// Bytes read and written.
+	pub disk: Option<(u64, u64)>,
+}
+
+/// A sample recorded by `UsageState::record`.
+pub struct Recorded {
+	pub prev: Sample,
+	/// CPU time used since the previous sample.
+	pub cpu_usage_usec: u64,
+	/// Set if the sample completed a window.
+	pub metrics: Option<protocol::ActorMetrics>,
+}
+
+impl UsageState {
+	/// Adds a sample to the current window. Returns `None` for the first sample and for samples not newer
+	/// than the previous one.
+	pub fn record(&mut self, sample: Sample) -> Result<Option<Recorded>> {
+		let Some(prev) = self.prev.clone() else {
+			// Nothing to compare the first sample against
+			self.prev = Some(sample.clone());
+			self.window = Some(Window::new(sample));
+
+			return Ok(None);
+		};
+
+		// Isolate has not written a new sample since the last one
+		if sample.ts <= prev.ts {
+			return Ok(None);
+		}
+
+		let cpu_usage_usec = sample.cpu_usage_usec.saturating_sub(prev.cpu_usage_usec);
+		// Microseconds of CPU time per millisecond is equivalent to millicores
+		let cpu = cpu_usage_usec / u64::try_from(sample.ts - prev.ts)?;
+
+		let window = self.window.get_or_insert_with(|| Window::new(prev.clone()));
+		window.samples += 1;
+		window.cpu_sum += cpu;
+		window.cpu_max = window.cpu_max.max(cpu);
+		window.memory_sum += sample.memory;
+		window.memory_max = window.memory_max.max(sample.memory);
+
+		let metrics = if sample.ts - window.start.ts >= WINDOW_DURATION_MS {
+			let metrics = window.finish(&sample);
+			self.window = Some(Window::new(sample.clone()));
+
+			Some(metrics)
+		} else {
+			None
+		};
+
+		self.prev = Some(sample);
+
+		Ok(Some(Recorded {
+			prev,
+			cpu_usage_usec,
+			metrics,
+		}))
+	}
+}
+
+struct Window {
+	start: Sample,
+	samples: u64,
+	/// Millicores.
+	cpu_sum: u64,
+	cpu_max: u64,
+	/// Bytes.
+	memory_sum: u64,
+	memory_max: u64,
+}
+
+impl Window {
+	fn new(start: Sample) -> Self {
+		Window {
+			start,
+			samples: 0,
+			cpu_sum: 0,
+			cpu_max: 0,
+			memory_sum: 0,
+			memory_max: 0,
+		}
+	}
+
+	fn finish(&self, end: &Sample) -> protocol::ActorMetrics {
+		let samples = self.samples.max(1);
+
+		protocol::ActorMetrics {
+			start_ts: self.start.ts,
+			end_ts: end.ts,
+			cpu_avg: self.cpu_sum / samples,
+			cpu_max: self.cpu_max,
+			memory_avg: self.memory_sum / samples,
+			memory_max: self.memory_max,
+			network_rx_bytes: delta(self.start.network, end.network).map(|(rx, _)| rx),
+			network_tx_bytes: delta(self.start.network, end.network).map(|(_, tx)| tx),
+			disk_read_bytes: delta(self.start.disk, end.disk).map(|(read, _)| read),
+			disk_write_bytes: delta(self.start.disk, end.disk).map(|(_, write)| write),
+		}
+	}
+}
+
+impl Actor {
+	/// Samples the actor's resource usage. Returns the actor's metrics once a window is complete.
+	pub async fn sample_usage(&self, ctx: &Ctx) -> Option<protocol::ActorMetricsEntry> {
+		match self.sample_usage_inner(ctx).await {
+			Ok(metrics) => metrics.map(|metrics| protocol::ActorMetricsEntry {
+				actor_id: self.actor_id,
+				metrics,
+			}),
+			Err(err) => {
+				tracing::warn!(actor_id=?self.actor_id, ?err, "failed to sample actor usage");
+				None
+			}
+		}
+	}
+
+	async fn sample_usage_inner(&self, ctx: &Ctx) -> Result<Option<protocol::ActorMetrics>> {
+		// Not running yet or already exited
+		if self.runner.lock().await.is_none() || *self.exited.lock().await {
+			return Ok(None);
+		}
+
+		let sample = match self.config.image.kind {
+			protocol::ImageKind::DockerImage | protocol::ImageKind::OciBundle => {
+				self.sample_container().await?
+			}
+			protocol::ImageKind::JavaScript => self.sample_isolate(ctx).await?,
+		};
+		let Some(sample) = sample else {
+			return Ok(None);
+		};
+
+		let Some(recorded) = self.usage.lock().await.record(sample.clone())? else {
+			return Ok(None);
+		};
+
+		self.record_prometheus(&recorded.prev, &sample, recorded.cpu_usage_usec);
+
+		Ok(recorded.metrics)
+	}
+
+	fn record_prometheus(&self, prev: &Sample, sample: &Sample, cpu_usage_usec: u64) {
+		let labels = self.metric_labels();
+		let labels = &labels.iter().map(String::as_str).collect::<Vec<_>>();
+
+		metrics::ACTOR_CPU_USAGE_SECONDS
+			.with_label_values(labels)
+			.inc_by(cpu_usage_usec as f64 / 1_000_000.0);
+		metrics::ACTOR_MEMORY_USAGE
+			.with_label_values(labels)
+			.set(sample.memory.try_into().unwrap_or(i64::MAX));
+
+		if let Some((rx, tx)) = delta(prev.network, sample.network) {
+			metrics::ACTOR_NETWORK_RX_BYTES
+				.with_label_values(labels)
+				.inc_by(rx);
+			metrics::ACTOR_NETWORK_TX_BYTES
+				.with_label_values(labels)
+				.inc_by(tx);
+		}
+
+		if let Some((read, write)) = delta(prev.disk, sample.disk) {
+			metrics::ACTOR_DISK_READ_BYTES
+				.with_label_values(labels)
+				.inc_by(read);
+			metrics::ACTOR_DISK_WRITE_BYTES
+				.with_label_values(labels)
+				.inc_by(write);
+		}
+	}
+
+	fn metric_labels(&self) -> [String; 3] {
+		let (owner, owner_id) = match self.config.owner {
+			protocol::ActorOwner::DynamicServer { server_id } => ("dynamic_server", server_id),
+		};
+
+		[
+			self.actor_id.to_string(),
+			owner.to_string(),
+			owner_id.to_string(),
+		]
+	}
+
+	/// Removes the actor's prometheus metrics.
+	pub fn cleanup_usage(&self) {
+		let labels = self.metric_labels();
+		let labels = &labels.iter().map(String::as_str).collect::<Vec<_>>();
+
+		// Errors if the actor was never sampled
+		let _ = metrics::ACTOR_CPU_USAGE_SECONDS.remove_label_values(labels);
+		let _ = metrics::ACTOR_MEMORY_USAGE.remove_label_values(labels);
+		let _ = metrics::ACTOR_NETWORK_RX_BYTES.remove_label_values(labels);
+		let _ = metrics::ACTOR_NETWORK_TX_BYTES.remove_label_values(labels);
+		let _ = metrics::ACTOR_DISK_READ_BYTES.remove_label_values(labels);
+		let _ = metrics::ACTOR_DISK_WRITE_BYTES.remove_label_values(labels);
+	}
+
+	/// Reads usage from the container's cgroup.
+	async fn sample_container(&self) -> Result<Option<Sample>> {
+		let cgroup_path =
+			Path::new("/sys/fs/cgroup").join(cgroups_path(self.actor_id).trim_start_matches('/'));
+
+		// Cgroup is created by runc after the runner starts
+		if fs::metadata(&cgroup_path).await.is_err() {
+			return Ok(None);
+		}
+
+		let ts = utils::now();
+		let cpu_stat = fs::read_to_string(cgroup_path.join("cpu.stat")).await?;
+		let memory_current = fs::read_to_string(cgroup_path.join("memory.current")).await?;
+		let io_stat = fs::read_to_string(cgroup_path.join("io.stat")).await?;
+
+		let network = if let protocol::NetworkMode::Bridge = self.config.network_mode {
+			Some(read_netns_usage(&cgroup_path).await?)
+		} else {
+			// Host networking is shared with the rest of the machine
+			None
+		};
+
+		Ok(Some(Sample {
+			ts,
+			cpu_usage_usec: parse_cpu_stat(&cpu_stat)?,
+			memory: memory_current.trim().parse()?,
+			network,
+			disk: Some(parse_io_stat(&io_stat)?),
+		}))
+	}
+
+	/// Reads usage written by the isolate runner.
+	async fn sample_isolate(&self, ctx: &Ctx) -> Result<Option<Sample>> {
+		let metrics_path = ctx.actor_path(self.actor_id).join("metrics.json");
+
+		// Written periodically once the isolate starts
+		let buf = match fs::read(&metrics_path).await {
+			Ok(buf) => buf,
+			Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+			Err(err) => return Err(err.into()),
+		};
+		let metrics = serde_json::from_slice::<actor_config::Metrics>(&buf)?;
+
+		Ok(Some(Sample {
+			ts: metrics.ts,
+			cpu_usage_usec: metrics.cpu_usage_usec,
+			memory: metrics.used_heap_size,
+			network: None,
+			disk: None,
+		}))
+	}
+}
+
+/// Reads the total bytes received and sent on all interfaces of the container's network namespace, excluding
+/// loopback.
+async fn read_netns_usage(cgroup_path: &Path) -> Result<(u64, u64)> {
+	let procs = fs::read_to_string(cgroup_path.join("cgroup.procs")).await?;
+	let pid = procs.lines().next().context("cgroup has no processes")?;
+
+	// Format: https://www.kernel.org/doc/html/latest/networking/statistics.html#procfs
+	let net_dev = fs::read_to_string(Path::new("/proc").join(pid.trim()).join("net/dev")).await?;
+
+	parse_net_dev(&net_dev)
+}
+
+/// Parses the total CPU time in microseconds from a cgroup's `cpu.stat`.
+pub fn parse_cpu_stat(cpu_stat: &str) -> Result<u64> {
+	cpu_stat
+		.lines()
+		.find_map(|line| line.strip_prefix("usage_usec "))
+		.context("cpu.stat missing usage_usec")?
+		.trim()
+		.parse()
+		.map_err(Into::into)
+}
+
+/// Parses the total bytes read and written on all devices from a cgroup's `io.stat`.
+pub fn parse_io_stat(io_stat: &str) -> Result<(u64, u64)> {
+	// Each line is for one device, e.g. `8:0 rbytes=1024 wbytes=0 rios=1 wios=0 dbytes=0 dios=0`
+	let mut disk = (0, 0);
+	for field in io_stat
+		.lines()
+		.flat_map(|line| line.split_whitespace().skip(1))
+	{
+		if let Some(bytes) = field.strip_prefix("rbytes=") {
+			disk.0 += bytes.parse::<u64>()?;
+		} else if let Some(bytes) = field.strip_prefix("wbytes=") {
+			disk.1 += bytes.parse::<u64>()?;
+		}
+	}
+
+	Ok(disk)
+}
+
+/// Parses the total bytes received and sent on all interfaces except loopback from `/proc/<pid>/net/dev`.
+pub fn parse_net_dev(net_dev: &str) -> Result<(u64, u64)> {
+	let mut usage = (0, 0);
+	for line in net_dev.lines().skip(2) {
+		let Some((iface, stats)) = line.split_once(':') else {
+			continue;
+		};
+		if iface.trim() == "lo" {
+			continue;
+		}
+
+		let stats = stats.split_whitespace().collect::<Vec<_>>();
+		usage.0 += stats.first().context("missing rx bytes")?.parse::<u64>()?;
+		usage.1 += stats.get(8).context("missing tx bytes")?.parse::<u64>()?;
+	}
+
+	Ok(usage)
+}
+
+fn delta(start: Option<(u64, u64)>, end: Option<(u64, u64)>) -> Option<(u64, u64)> {
+	let (start, end) = (start?, end?);
+
+	Some((end.0.saturating_sub(start.0), end.1.saturating_sub(start.1)))
+}
@@ -35,12 +35,14 @@ use url::Url;
 use uuid::Uuid;
 
 use crate::{
-	actor::Actor,
+	actor::{usage, Actor},
 	event_sender::EventSender,
+	image_cache::ImageCache,
 	metrics,
 	pull_addr_handler::PullAddrHandler,
 	runner,
 	utils::{self, sql::SqliteConnectionExt},
+	volumes::Volumes,
 };
 
 const PING_INTERVAL: Duration = Duration::from_secs(1);
@@ -78,6 +80,8 @@ pub struct Ctx {
 	tx: Mutex<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>>,
 	event_sender: EventSender,
 	pub(crate) pull_addr_handler: PullAddrHandler,
+	pub(crate) image_cache: ImageCache,
+	pub(crate) volumes: Volumes,
 
 	pub(crate) actors: RwLock<HashMap<Uuid, Arc<Actor>>>,
 	isolate_runner: RwLock<Option<runner::Handle>>,
@@ -98,6 +102,8 @@ impl Ctx {
 			tx: Mutex::new(tx),
 			event_sender: EventSender::new(),
 			pull_addr_handler: PullAddrHandler::new(),
+			image_cache: ImageCache::new(),
+			volumes: Volumes::new(),
 
 			actors: RwLock::new(HashMap::new()),
 			isolate_runner: RwLock::new(None),
@@ -115,7 +121,7 @@ impl Ctx {
 	}
 
 	pub(crate) async fn send_packet(&self, packet: protocol::ToServer) -> Result<()> {
-		let buf = packet.serialize()?;
+		let buf = packet.serialize(protocol::PROTOCOL_VERSION)?;
 		self.tx.lock().await.send(Message::Binary(buf)).await?;
 
 		metrics::PACKET_SEND_TOTAL.with_label_values(&[]).inc();
@@ -167,10 +173,13 @@ impl Ctx {
 		Ok(index)
 	}
 
-	pub async fn event(&self, event: protocol::Event) -> Result<()> {
+	/// Publishes an event. `traceparent` is the trace context of the command that caused the event.
+	pub async fn event(&self, event: protocol::Event, traceparent: Option<&str>) -> Result<()> {
 		let index = self.write_event(&event).await?;
 
-		self.event_sender.send(self, event, index).await
+		self.event_sender
+			.send(self, event, traceparent.map(ToString::to_string), index)
+			.await
 	}
 
 	pub async fn run(
@@ -235,6 +244,39 @@ impl Ctx {
 			}
 		});
 
+		// Start usage sampling thread
+		let self2 = self.clone();
+		let usage_thread: tokio::task::JoinHandle<Result<()>> = tokio::spawn(async move {
+			loop {
+				tokio::time::sleep(usage::SAMPLE_INTERVAL).await;
+
+				let actors = self2
+					.actors
+					.read()
+					.await
+					.values()
+					.cloned()
+					.collect::<Vec<_>>();
+				let metrics = futures_util::future::join_all(
+					actors.iter().map(|actor| actor.sample_usage(&self2)),
+				)
+				.await
+				.into_iter()
+				.flatten()
+				.collect::<Vec<_>>();
+
+				// Metrics are not durable, a window lost to a disconnect is not resent
+				if !metrics.is_empty() {
+					if let Err(err) = self2
+						.send_packet(protocol::ToServer::ActorMetrics(metrics))
+						.await
+					{
+						tracing::warn!(?err, "failed to send actor metrics");
+					}
+				}
+			}
+		});
+
 		// Send init packet
 		{
 			let (last_command_idx,) = utils::sql::query(|| async {
@@ -259,6 +301,7 @@ impl Ctx {
 		tokio::try_join!(
 			async { runner_socket.await? },
 			async { ping_thread.await? },
+			async { usage_thread.await? },
 			self.receive_messages(rx),
 		)?;
 
@@ -274,7 +317,7 @@ impl Ctx {
 				Message::Binary(buf) => {
 					metrics::PACKET_RECV_TOTAL.with_label_values(&[]).inc();
 
-					let packet = protocol::ToClient::deserialize(&buf)?;
+					let packet = protocol::ToClient::deserialize(protocol::PROTOCOL_VERSION, &buf)?;
 
 					self.process_packet(packet).await?;
 				}
@@ -322,11 +365,18 @@ impl Ctx {
 			protocol::ToClient::PrewarmImage {
 				image_id,
 				image_artifact_url_stub,
+				image_digest,
 			} => {
 				let self2 = self.clone();
 
 				tokio::spawn(async move {
-					utils::prewarm_image(&self2, image_id, &image_artifact_url_stub).await
+					utils::prewarm_image(
+						&self2,
+						image_id,
+						&image_artifact_url_stub,
+						image_digest.as_deref(),
+					)
+					.await
 				});
 			}
 		}
@@ -334,6 +384,7 @@ impl Ctx {
 		Ok(())
 	}
 
+	#[tracing::instrument(skip_all, fields(index = command.index, traceparent = ?command.traceparent))]
 	async fn process_command(self: &Arc<Self>, command: protocol::CommandWrapper) -> Result<()> {
 		match command.inner.deserialize()? {
 			protocol::Command::StartActor { actor_id, config } => {
@@ -341,7 +392,7 @@ impl Ctx {
 				let mut actors = self.actors.write().await;
 				let actor = actors
 					.entry(actor_id)
-					.or_insert_with(|| Actor::new(actor_id, *config));
+					.or_insert_with(|| Actor::new(actor_id, *config, command.traceparent.clone()));
 
 				// Spawn actor
 				actor.start(&self).await?;
@@ -363,6 +414,27 @@ impl Ctx {
 					);
 				}
 			}
+			protocol::Command::SnapshotVolume {
+				volume_id,
+				snapshot_id,
+				upload_url,
+			} => {
+				let self2 = self.clone();
+
+				// Waits for the actors using the volume to exit
+				tokio::spawn(async move {
+					if let Err(err) = self2
+						.volumes
+						.snapshot(&self2, volume_id, snapshot_id, &upload_url)
+						.await
+					{
+						tracing::error!(?volume_id, ?err, "failed to send volume snapshot update");
+					}
+				});
+			}
+			protocol::Command::DeleteVolume { volume_id } => {
+				self.volumes.delete(&self, volume_id).await?;
+			}
 		}
 
 		// Ack command
@@ -422,6 +494,7 @@ impl Ctx {
 			Ok(protocol::EventWrapper {
 				index,
 				inner: protocol::Raw::from_string(String::from_utf8_lossy(&payload).into())?,
+				traceparent: None,
 			})
 		})
 		.collect::<Result<Vec<_>>>()?;
@@ -485,10 +558,13 @@ impl Ctx {
 				})
 				.await?;
 
-				self.event(protocol::Event::ActorStateUpdate {
-					actor_id: row.actor_id,
-					state: protocol::ActorState::Lost,
-				})
+				self.event(
+					protocol::Event::ActorStateUpdate {
+						actor_id: row.actor_id,
+						state: protocol::ActorState::Lost,
+					},
+					None,
+				)
 				.await?;
 			}
 		}
@@ -699,9 +775,29 @@ impl Ctx {
 		self.actors_path().join(actor_id.to_string())
 	}
 
+	pub fn volumes_path(&self) -> PathBuf {
+		self.config().data_dir().join("volumes")
+	}
+
+	pub fn volume_path(&self, volume_id: Uuid) -> PathBuf {
+		self.volumes_path().join(volume_id.to_string())
+	}
+
 	pub fn isolate_runner_path(&self) -> PathBuf {
 		self.config().data_dir().join("runner")
 	}
+
+	pub fn images_path(&self) -> PathBuf {
+		self.config().data_dir().join("images")
+	}
+
+	pub fn image_path(&self, image_id: Uuid, digest: Option<&str>) -> PathBuf {
+		if let Some(digest) = digest {
+			self.images_path().join(format!("{image_id}-{digest}"))
+		} else {
+			self.images_path().join(image_id.to_string())
+		}
+	}
 }
 
 // Test bindings
@@ -24,7 +24,13 @@ impl EventSender {
 		self.awaiting_event_idx.store(idx, Ordering::SeqCst);
 	}
 
-	pub async fn send(&self, ctx: &Ctx, event: protocol::Event, idx: i64) -> Result<()> {
+	pub async fn send(
+		&self,
+		ctx: &Ctx,
+		event: protocol::Event,
+		traceparent: Option<String>,
+		idx: i64,
+	) -> Result<()> {
 		// Subscribe before checking the idx
 		let mut rx = self.tx.subscribe();
 
@@ -44,6 +50,7 @@ impl EventSender {
 		let wrapped_event = protocol::EventWrapper {
 			index: idx,
 			inner: protocol::Raw::new(&event)?,
+			traceparent,
 		};
 
 		ctx.send_packet(protocol::ToServer::Events(vec![wrapped_event]))
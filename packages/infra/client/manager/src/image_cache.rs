@@ -0,0 +1,459 @@
+use std::{
+	collections::{HashMap, HashSet},
+	path::{Path, PathBuf},
+	result::Result::{Err, Ok},
+	sync::{Arc, Mutex as StdMutex},
+};
+
+use anyhow::*;
+use futures_util::StreamExt;
+use indoc::indoc;
+use sha2::{Digest, Sha256};
+use tokio::{
+	fs::{self, File},
+	io::AsyncWriteExt,
+	sync::Mutex,
+};
+use uuid::Uuid;
+
+use crate::{ctx::Ctx, metrics, utils};
+
+/// Artifacts of an image that are cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArtifactKind {
+	/// The image itself.
+	Image = 0,
+	/// The Zstd dictionary required to decompress the image.
+	CompressionDictionary = 1,
+}
+
+impl ArtifactKind {
+	fn from_i64(kind: i64) -> Result<Self> {
+		match kind {
+			0 => Ok(ArtifactKind::Image),
+			1 => Ok(ArtifactKind::CompressionDictionary),
+			_ => bail!("invalid artifact kind: {kind}"),
+		}
+	}
+}
+
+/// (image id, artifact kind, digest). The digest is empty for artifacts without one.
+pub type CacheKey = (Uuid, ArtifactKind, String);
+
+/// Local cache of image artifacts keyed by image id, artifact kind and content digest. Entries are
+/// tracked in the `image_cache` table and evicted LRU once the configured disk budget is exceeded.
+pub struct ImageCache {
+	/// Locks for artifacts currently being fetched or evicted, used to deduplicate concurrent downloads of
+	/// the same artifact.
+	locks: Mutex<HashMap<CacheKey, Arc<Mutex<()>>>>,
+	/// Number of leases held on each artifact. Leased artifacts are never evicted.
+	leases: Arc<StdMutex<HashMap<CacheKey, usize>>>,
+}
+
+impl ImageCache {
+	pub fn new() -> Self {
+		ImageCache {
+			locks: Mutex::new(HashMap::new()),
+			leases: Arc::new(StdMutex::new(HashMap::new())),
+		}
+	}
+
+	/// Returns a lease on the cached artifact of the given image, downloading (and verifying) it first if
+	/// it is not cached. The artifact is not evicted until the lease is dropped.
+	pub async fn fetch(
+		&self,
+		ctx: &Ctx,
+		image_id: Uuid,
+		kind: ArtifactKind,
+		image_artifact_url_stub: &str,
+		image_fallback_artifact_url: Option<&str>,
+		digest: Option<&str>,
+	) -> Result<ImageLease> {
+		let key = (image_id, kind, digest.unwrap_or_default().to_string());
+		let path = artifact_path(ctx, &key);
+
+		// Taken before the lock so a concurrent eviction either sees the lease or finishes deleting the
+		// artifact before it is looked up
+		let lease = self.lease(key.clone(), path.clone());
+
+		let lock = self.lock(&key).await;
+		let res = {
+			let _guard = lock.lock().await;
+
+			self.fetch_inner(
+				ctx,
+				&key,
+				&path,
+				image_artifact_url_stub,
+				image_fallback_artifact_url,
+			)
+			.await
+		};
+		self.unlock(&key, lock).await;
+
+		// Evict after a new image was written
+		if res? {
+			if let Err(err) = self.evict(ctx).await {
+				tracing::error!(?err, "failed to evict images from cache");
+			}
+		}
+
+		Ok(lease)
+	}
+
+	/// Leases the given artifact, preventing it from being evicted until the lease is dropped.
+	pub fn lease(&self, key: CacheKey, path: PathBuf) -> ImageLease {
+		*self
+			.leases
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.entry(key.clone())
+			.or_default() += 1;
+
+		ImageLease {
+			leases: self.leases.clone(),
+			key,
+			path,
+		}
+	}
+
+	pub fn is_leased(&self, key: &CacheKey) -> bool {
+		self.leases
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.contains_key(key)
+	}
+
+	fn leased(&self) -> HashSet<CacheKey> {
+		self.leases
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.keys()
+			.cloned()
+			.collect()
+	}
+
+	async fn lock(&self, key: &CacheKey) -> Arc<Mutex<()>> {
+		let mut locks = self.locks.lock().await;
+		locks.entry(key.clone()).or_default().clone()
+	}
+
+	async fn unlock(&self, key: &CacheKey, lock: Arc<Mutex<()>>) {
+		// Remove the lock if no other task is waiting on it. New references are only created while holding
+		// the map lock so this check cannot race.
+		let mut locks = self.locks.lock().await;
+		drop(lock);
+
+		if locks
+			.get(key)
+			.map(|lock| Arc::strong_count(lock) == 1)
+			.unwrap_or_default()
+		{
+			locks.remove(key);
+		}
+	}
+
+	/// Returns true if the image was downloaded.
+	async fn fetch_inner(
+		&self,
+		ctx: &Ctx,
+		key: &CacheKey,
+		path: &Path,
+		image_artifact_url_stub: &str,
+		image_fallback_artifact_url: Option<&str>,
+	) -> Result<bool> {
+		let (image_id, kind, digest) = key;
+
+		let cached = utils::sql::query(|| async {
+			sqlx::query_as::<_, (i64,)>(indoc!(
+				"
+				UPDATE image_cache
+				SET last_used_ts = ?4
+				WHERE image_id = ?1 AND kind = ?2 AND digest = ?3
+				RETURNING size
+				",
+			))
+			.bind(image_id)
+			.bind(*kind as i64)
+			.bind(digest)
+			.bind(utils::now())
+			.fetch_optional(&mut *ctx.sql().await?)
+			.await
+		})
+		.await?;
+
+		if cached.is_some() && fs::metadata(path).await.is_ok() {
+			tracing::debug!(?image_id, ?kind, "image cache hit");
+			metrics::IMAGE_CACHE_REQUEST_TOTAL
+				.with_label_values(&["hit"])
+				.inc();
+
+			return Ok(false);
+		}
+
+		tracing::debug!(?image_id, ?kind, "image cache miss");
+		metrics::IMAGE_CACHE_REQUEST_TOTAL
+			.with_label_values(&["miss"])
+			.inc();
+
+		// Download to a temporary file first so a partial download is never read from the cache
+		let mut tmp_path = path.as_os_str().to_owned();
+		tmp_path.push(".part");
+		let tmp_path = PathBuf::from(tmp_path);
+		let size = match download(
+			ctx,
+			*image_id,
+			image_artifact_url_stub,
+			image_fallback_artifact_url,
+			(!digest.is_empty()).then_some(digest.as_str()),
+			max_cache_size(ctx)?,
+			&tmp_path,
+		)
+		.await
+		{
+			Ok(size) => size,
+			Err(err) => {
+				if let Err(err) = fs::remove_file(&tmp_path).await {
+					if err.kind() != std::io::ErrorKind::NotFound {
+						tracing::warn!(?image_id, ?err, "failed to remove partial image");
+					}
+				}
+
+				return Err(err);
+			}
+		};
+
+		fs::rename(&tmp_path, path).await?;
+
+		utils::sql::query(|| async {
+			sqlx::query(indoc!(
+				"
+				INSERT INTO image_cache (image_id, kind, digest, size, last_used_ts)
+				VALUES (?1, ?2, ?3, ?4, ?5)
+				ON CONFLICT (image_id, kind, digest) DO UPDATE
+				SET
+					size = excluded.size,
+					last_used_ts = excluded.last_used_ts
+				",
+			))
+			.bind(image_id)
+			.bind(*kind as i64)
+			.bind(digest)
+			.bind(size)
+			.bind(utils::now())
+			.execute(&mut *ctx.sql().await?)
+			.await
+		})
+		.await?;
+
+		Ok(true)
+	}
+
+	/// Deletes least recently used images until the cache fits within the configured budget. Leased
+	/// images are never evicted.
+	async fn evict(&self, ctx: &Ctx) -> Result<()> {
+		let max_size = max_cache_size(ctx)?;
+
+		let entries = utils::sql::query(|| async {
+			sqlx::query_as::<_, (Uuid, i64, String, i64)>(indoc!(
+				"
+				SELECT image_id, kind, digest, size
+				FROM image_cache
+				ORDER BY last_used_ts ASC
+				",
+			))
+			.fetch_all(&mut *ctx.sql().await?)
+			.await
+		})
+		.await?
+		.into_iter()
+		.map(|(image_id, kind, digest, size)| {
+			Ok(CacheEntry {
+				key: (image_id, ArtifactKind::from_i64(kind)?, digest),
+				size,
+			})
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+		let mut total_size = entries.iter().map(|entry| entry.size).sum::<i64>();
+
+		for entry in plan_eviction(&entries, max_size, &self.leased()) {
+			// Hold the artifact's lock and check the lease again so an artifact leased after planning is
+			// not deleted
+			let lock = self.lock(&entry.key).await;
+			let res = {
+				let _guard = lock.lock().await;
+
+				if self.is_leased(&entry.key) {
+					Ok(false)
+				} else {
+					remove(ctx, &entry.key).await.map(|_| true)
+				}
+			};
+			self.unlock(&entry.key, lock).await;
+
+			if res? {
+				total_size -= entry.size;
+			}
+		}
+
+		metrics::IMAGE_CACHE_SIZE.set(total_size);
+
+		Ok(())
+	}
+}
+
+/// Lease on a cached artifact. The artifact is not evicted while any lease on it is held.
+pub struct ImageLease {
+	leases: Arc<StdMutex<HashMap<CacheKey, usize>>>,
+	key: CacheKey,
+	path: PathBuf,
+}
+
+impl ImageLease {
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for ImageLease {
+	fn drop(&mut self) {
+		let mut leases = self.leases.lock().unwrap_or_else(|err| err.into_inner());
+
+		if let Some(count) = leases.get_mut(&self.key) {
+			*count -= 1;
+
+			if *count == 0 {
+				leases.remove(&self.key);
+			}
+		}
+	}
+}
+
+pub struct CacheEntry {
+	pub key: CacheKey,
+	/// Bytes.
+	pub size: i64,
+}
+
+/// Returns the entries to evict so the total size fits within `max_size`. `entries` must be ordered
+/// least recently used first. Leased entries are skipped.
+pub fn plan_eviction<'a>(
+	entries: &'a [CacheEntry],
+	max_size: i64,
+	leased: &HashSet<CacheKey>,
+) -> Vec<&'a CacheEntry> {
+	let mut total_size = entries.iter().map(|entry| entry.size).sum::<i64>();
+
+	entries
+		.iter()
+		.filter(|entry| !leased.contains(&entry.key))
+		.take_while(|entry| {
+			if total_size <= max_size {
+				return false;
+			}
+
+			total_size -= entry.size;
+
+			true
+		})
+		.collect()
+}
+
+/// Disk budget of the cache in bytes.
+fn max_cache_size(ctx: &Ctx) -> Result<i64> {
+	Ok(i64::try_from(
+		ctx.config().images.max_cache_size() * 1024 * 1024,
+	)?)
+}
+
+/// Deletes a cached artifact from disk and the `image_cache` table.
+async fn remove(ctx: &Ctx, key: &CacheKey) -> Result<()> {
+	let path = artifact_path(ctx, key);
+	let (image_id, kind, digest) = key;
+
+	tracing::debug!(?image_id, ?kind, ?digest, "evicting image from cache");
+
+	match fs::remove_file(&path).await {
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+		x => x.with_context(|| format!("failed to remove {}", path.display()))?,
+	}
+
+	utils::sql::query(|| async {
+		sqlx::query(indoc!(
+			"
+			DELETE FROM image_cache
+			WHERE image_id = ?1 AND kind = ?2 AND digest = ?3
+			",
+		))
+		.bind(image_id)
+		.bind(*kind as i64)
+		.bind(digest)
+		.execute(&mut *ctx.sql().await?)
+		.await
+	})
+	.await?;
+
+	Ok(())
+}
+
+/// Path of the cached artifact on disk.
+fn artifact_path(ctx: &Ctx, (image_id, kind, digest): &CacheKey) -> PathBuf {
+	let path = ctx.image_path(*image_id, (!digest.is_empty()).then_some(digest.as_str()));
+
+	match kind {
+		ArtifactKind::Image => path,
+		ArtifactKind::CompressionDictionary => path.with_extension("dict"),
+	}
+}
+
+/// Downloads an image to the given path while computing its SHA-256 digest. Returns the size of the
+/// image in bytes. Fails if the image is larger than `max_size` since it could never be cached.
+async fn download(
+	ctx: &Ctx,
+	image_id: Uuid,
+	image_artifact_url_stub: &str,
+	image_fallback_artifact_url: Option<&str>,
+	digest: Option<&str>,
+	max_size: i64,
+	path: &Path,
+) -> Result<i64> {
+	let mut stream = utils::fetch_image_stream(
+		ctx,
+		image_id,
+		image_artifact_url_stub,
+		image_fallback_artifact_url,
+	)
+	.await?;
+
+	let mut file = File::create(path).await?;
+	let mut hasher = Sha256::new();
+	let mut size = 0;
+
+	while let Some(chunk) = stream.next().await {
+		let chunk = chunk?;
+
+		hasher.update(&chunk);
+		size += i64::try_from(chunk.len())?;
+		ensure!(
+			size <= max_size,
+			"image {image_id} is larger than the image cache ({} MiB)",
+			max_size / 1024 / 1024
+		);
+		file.write_all(&chunk).await?;
+	}
+
+	file.sync_all().await?;
+
+	if let Some(digest) = digest {
+		let actual_digest = format!("{:x}", hasher.finalize());
+
+		if actual_digest != digest {
+			metrics::IMAGE_CACHE_DIGEST_MISMATCH_TOTAL.inc();
+
+			bail!("image {image_id} failed digest verification (expected {digest}, got {actual_digest})");
+		}
+	}
+
+	Ok(size)
+}
@@ -5,10 +5,16 @@
 #[cfg(feature = "test")]
 mod actor;
 #[cfg(feature = "test")]
+pub use actor::oci_config;
+#[cfg(feature = "test")]
+pub use actor::usage;
+#[cfg(feature = "test")]
 mod ctx;
 #[cfg(feature = "test")]
 pub mod event_sender;
 #[cfg(feature = "test")]
+pub mod image_cache;
+#[cfg(feature = "test")]
 mod metrics;
 #[cfg(feature = "test")]
 pub mod pull_addr_handler;
@@ -20,4 +26,6 @@ pub mod system_info;
 #[cfg(feature = "test")]
 pub mod utils;
 #[cfg(feature = "test")]
+mod volumes;
+#[cfg(feature = "test")]
 pub use ctx::Ctx;
@@ -1,36 +1,41 @@
 use std::{
-	path::Path,
+	path::{Path, PathBuf},
 	result::Result::{Err, Ok},
 	time::Duration,
 };
 
 use anyhow::*;
 use futures_util::StreamExt;
-use pegboard::system_info::SystemInfo;
+use pegboard::{protocol::PROTOCOL_VERSION, system_info::SystemInfo};
 use pegboard_config::Config;
 use sqlx::sqlite::SqlitePool;
 use tokio::{
 	fs,
 	runtime::{Builder, Runtime},
 };
+use tokio_tungstenite::tungstenite::{
+	client::IntoClientRequest,
+	http::{header, HeaderValue},
+};
 use tracing_subscriber::prelude::*;
 use url::Url;
 
 mod actor;
 mod ctx;
 mod event_sender;
+mod image_cache;
 mod metrics;
 mod pull_addr_handler;
 mod runner;
 mod system_info;
 mod utils;
+mod volumes;
 
 use ctx::Ctx;
 
-const PROTOCOL_VERSION: u16 = 2;
-
 #[derive(Clone)]
 struct Init {
+	config_path: PathBuf,
 	config: Config,
 	system: SystemInfo,
 	pool: SqlitePool,
@@ -91,32 +96,14 @@ async fn init() -> Result<Init> {
 		}
 	};
 
-	// Read config
-	let config_data = fs::read_to_string(&config_path)
-		.await
-		.with_context(|| format!("Failed to read config file at {}", config_path.display()))?;
+	let config = read_config(&config_path).await?;
 
-	// Determine config format and parse
-	let config = match config_path.extension().and_then(|s| s.to_str()) {
-		Some("json") => serde_json::from_str::<Config>(&config_data).with_context(|| {
-			format!(
-				"Failed to parse JSON config file at {}",
-				config_path.display()
-			)
-		})?,
-		Some("yaml") | Some("yml") => {
-			serde_yaml::from_str::<Config>(&config_data).with_context(|| {
-				format!(
-					"Failed to parse YAML config file at {}",
-					config_path.display()
-				)
-			})?
-		}
-		_ => bail!(
-			"unrecognized config file extension at {}",
-			config_path.display()
-		),
-	};
+	// Runners parse the pattern when they start, fail early instead of breaking the log shipper of every
+	// actor
+	if let Some(vector) = &config.client.vector {
+		pegboard_logs::structured::MultilineJoiner::new(vector.multiline_pattern.as_deref())
+			.context("invalid `vector.multiline_pattern` config")?;
+	}
 
 	if config.client.logs.redirect_logs() {
 		pegboard_logs::Logs::new(
@@ -170,6 +157,7 @@ async fn init() -> Result<Init> {
 		.append_pair("flavor", &config.client.runner.flavor.to_string());
 
 	Ok(Init {
+		config_path,
 		config,
 		system,
 		pool,
@@ -194,8 +182,26 @@ async fn run(init: Init, first: bool) -> Result<()> {
 
 	tracing::info!("connecting to pegboard ws: {}", &init.url);
 
+	// The token is refreshed in the config file while the client is running
+	let token = match read_config(&init.config_path).await {
+		Ok(config) => config.client.cluster.token,
+		Err(err) => {
+			tracing::warn!(?err, "failed to read config, using the token from startup");
+			init.config.client.cluster.token.clone()
+		}
+	};
+
+	// Build WS request
+	let mut req = init.url.as_str().into_client_request()?;
+	if let Some(token) = &token {
+		req.headers_mut().insert(
+			header::AUTHORIZATION,
+			HeaderValue::from_str(&format!("Bearer {token}"))?,
+		);
+	}
+
 	// Connect to WS
-	let (ws_stream, _) = tokio_tungstenite::connect_async(init.url.to_string())
+	let (ws_stream, _) = tokio_tungstenite::connect_async(req)
 		.await
 		.map_err(|source| ctx::RuntimeError::ConnectionFailed {
 			url: init.url.clone(),
@@ -215,6 +221,36 @@ async fn run(init: Init, first: bool) -> Result<()> {
 	Ok(())
 }
 
+async fn read_config(config_path: &Path) -> Result<Config> {
+	let config_data = fs::read_to_string(config_path)
+		.await
+		.with_context(|| format!("Failed to read config file at {}", config_path.display()))?;
+
+	// Determine config format and parse
+	let config = match config_path.extension().and_then(|s| s.to_str()) {
+		Some("json") => serde_json::from_str::<Config>(&config_data).with_context(|| {
+			format!(
+				"Failed to parse JSON config file at {}",
+				config_path.display()
+			)
+		})?,
+		Some("yaml") | Some("yml") => {
+			serde_yaml::from_str::<Config>(&config_data).with_context(|| {
+				format!(
+					"Failed to parse YAML config file at {}",
+					config_path.display()
+				)
+			})?
+		}
+		_ => bail!(
+			"unrecognized config file extension at {}",
+			config_path.display()
+		),
+	};
+
+	Ok(config)
+}
+
 fn init_tracing() {
 	tracing_subscriber::registry()
 		.with(
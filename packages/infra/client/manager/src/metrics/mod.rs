@@ -43,4 +43,65 @@ lazy_static::lazy_static! {
 		&["error"],
 		*REGISTRY,
 	).unwrap();
+
+	pub static ref IMAGE_CACHE_REQUEST_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+		"image_cache_request_total",
+		"Total number of image cache lookups.",
+		&["result"],
+		*REGISTRY,
+	).unwrap();
+
+	pub static ref IMAGE_CACHE_DIGEST_MISMATCH_TOTAL: IntCounter = register_int_counter_with_registry!(
+		"image_cache_digest_mismatch_total",
+		"Total number of downloaded images that failed digest verification.",
+		*REGISTRY,
+	).unwrap();
+
+	pub static ref IMAGE_CACHE_SIZE: IntGauge = register_int_gauge_with_registry!(
+		"image_cache_size",
+		"Total size of all cached images in bytes.",
+		*REGISTRY,
+	).unwrap();
+
+	pub static ref ACTOR_CPU_USAGE_SECONDS: CounterVec = register_counter_vec_with_registry!(
+		"actor_cpu_usage_seconds",
+		"Total CPU time consumed by an actor in seconds.",
+		&["actor_id", "owner", "owner_id"],
+		*REGISTRY,
+	).unwrap();
+
+	pub static ref ACTOR_MEMORY_USAGE: IntGaugeVec = register_int_gauge_vec_with_registry!(
+		"actor_memory_usage",
+		"Current memory usage of an actor in bytes.",
+		&["actor_id", "owner", "owner_id"],
+		*REGISTRY,
+	).unwrap();
+
+	pub static ref ACTOR_NETWORK_RX_BYTES: IntCounterVec = register_int_counter_vec_with_registry!(
+		"actor_network_rx_bytes",
+		"Total bytes received by an actor.",
+		&["actor_id", "owner", "owner_id"],
+		*REGISTRY,
+	).unwrap();
+
+	pub static ref ACTOR_NETWORK_TX_BYTES: IntCounterVec = register_int_counter_vec_with_registry!(
+		"actor_network_tx_bytes",
+		"Total bytes sent by an actor.",
+		&["actor_id", "owner", "owner_id"],
+		*REGISTRY,
+	).unwrap();
+
+	pub static ref ACTOR_DISK_READ_BYTES: IntCounterVec = register_int_counter_vec_with_registry!(
+		"actor_disk_read_bytes",
+		"Total bytes read from disk by an actor.",
+		&["actor_id", "owner", "owner_id"],
+		*REGISTRY,
+	).unwrap();
+
+	pub static ref ACTOR_DISK_WRITE_BYTES: IntCounterVec = register_int_counter_vec_with_registry!(
+		"actor_disk_write_bytes",
+		"Total bytes written to disk by an actor.",
+		&["actor_id", "owner", "owner_id"],
+		*REGISTRY,
+	).unwrap();
 }
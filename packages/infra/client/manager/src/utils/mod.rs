@@ -78,6 +78,18 @@ pub async fn init_dir(config: &Config) -> Result<()> {
 		x => x.context("failed to create /runner dir in data dir")?,
 	}
 
+	// Create images dir
+	match fs::create_dir(data_dir.join("images")).await {
+		Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+		x => x.context("failed to create /images dir in data dir")?,
+	}
+
+	// Create volumes dir
+	match fs::create_dir(data_dir.join("volumes")).await {
+		Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+		x => x.context("failed to create /volumes dir in data dir")?,
+	}
+
 	// Create db dir
 	match fs::create_dir(data_dir.join("db")).await {
 		Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
@@ -230,6 +242,22 @@ pub async fn init_sqlite_schema(pool: &SqlitePool) -> Result<()> {
 	.execute(&mut *conn)
 	.await?;
 
+	sqlx::query(indoc!(
+		"
+		CREATE TABLE IF NOT EXISTS image_cache (
+			image_id BLOB NOT NULL, -- UUID
+			kind INTEGER NOT NULL, -- image_cache::ArtifactKind
+			digest TEXT NOT NULL, -- Empty if the artifact has no digest
+			size INTEGER NOT NULL,
+			last_used_ts INTEGER NOT NULL,
+
+			PRIMARY KEY (image_id, kind, digest)
+		) STRICT
+		",
+	))
+	.execute(&mut *conn)
+	.await?;
+
 	Ok(())
 }
 
@@ -389,14 +417,20 @@ pub async fn fetch_image_stream(
 	bail!("artifact url could not be resolved");
 }
 
-pub async fn prewarm_image(ctx: &Ctx, image_id: Uuid, image_artifact_url_stub: &str) {
+pub async fn prewarm_image(
+	ctx: &Ctx,
+	image_id: Uuid,
+	image_artifact_url_stub: &str,
+	image_digest: Option<&str>,
+) {
 	tracing::debug!(?image_id, "prewarming");
 
-	match fetch_image_stream(ctx, image_id, image_artifact_url_stub, None).await {
+	match ctx
+		.image_cache
+		.fetch(ctx, image_id, image_artifact_url_stub, None, image_digest)
+		.await
+	{
 		Ok(_) => tracing::debug!(?image_id, "prewarm complete"),
-		Err(_) => tracing::warn!(
-			?image_id,
-			"prewarm failed, artifact url could not be resolved"
-		),
+		Err(err) => tracing::warn!(?image_id, ?err, "prewarm failed"),
 	}
 }
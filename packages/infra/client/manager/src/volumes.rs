@@ -0,0 +1,352 @@
+use std::{
+	collections::HashMap,
+	path::Path,
+	result::Result::{Err, Ok},
+	sync::Arc,
+	time::Duration,
+};
+
+use anyhow::*;
+use futures_util::StreamExt;
+use pegboard::protocol;
+use tokio::{
+	fs::{self, File},
+	io::AsyncWriteExt,
+	process::Command,
+	sync::Mutex,
+};
+use uuid::Uuid;
+
+use crate::ctx::Ctx;
+
+/// How often to check if the actors using a volume have exited before snapshotting it.
+const SNAPSHOT_WAIT_INTERVAL: Duration = Duration::from_millis(500);
+/// How many times to check if the actors using a volume have exited before giving up on a snapshot.
+const SNAPSHOT_WAIT_RETRIES: usize = 60;
+
+/// Persistent volumes stored on this client. Each volume lives in `<data dir>/volumes/<volume id>` and
+/// outlives the actors that mount it.
+///
+/// Layout of a volume dir:
+/// - `volume.img`: ext4 image backing the volume, only when mounts are enabled
+/// - `fs`: the volume contents, bind mounted into actors
+/// - `snapshot_id`: id of the snapshot the contents were last restored from or uploaded to
+pub struct Volumes {
+	/// Serializes setup, snapshots and deletion of each volume.
+	locks: Mutex<HashMap<Uuid, Arc<Mutex<()>>>>,
+}
+
+impl Volumes {
+	pub fn new() -> Self {
+		Volumes {
+			locks: Mutex::new(HashMap::new()),
+		}
+	}
+
+	async fn lock(&self, volume_id: Uuid) -> Arc<Mutex<()>> {
+		self.locks
+			.lock()
+			.await
+			.entry(volume_id)
+			.or_default()
+			.clone()
+	}
+
+	/// Creates and mounts the volume if needed and restores its contents from the given snapshot if the
+	/// local copy is not already up to date.
+	pub async fn setup(&self, ctx: &Ctx, volume: &protocol::Volume) -> Result<()> {
+		let lock = self.lock(volume.volume_id).await;
+		let _guard = lock.lock().await;
+
+		tracing::info!(volume_id=?volume.volume_id, "setting up volume");
+
+		let volume_path = ctx.volume_path(volume.volume_id);
+		let fs_path = volume_path.join("fs");
+
+		if fs::metadata(&volume_path).await.is_err() {
+			// Remove the partially created volume so the next setup does not treat it as created
+			if let Err(err) = create(ctx, volume, &volume_path).await {
+				if let Err(cleanup_err) = fs::remove_dir_all(&volume_path).await {
+					tracing::warn!(volume_id=?volume.volume_id, ?cleanup_err, "failed to clean up volume dir");
+				}
+
+				return Err(err);
+			}
+		}
+
+		mount(ctx, volume.volume_id).await?;
+
+		let Some(snapshot) = &volume.snapshot else {
+			return Ok(());
+		};
+
+		// Already restored from (or uploaded) this snapshot
+		if read_snapshot_id(&volume_path).await? == Some(snapshot.snapshot_id) {
+			return Ok(());
+		}
+
+		tracing::info!(volume_id=?volume.volume_id, snapshot_id=?snapshot.snapshot_id, "restoring volume snapshot");
+
+		// Clear stale contents
+		let mut entries = fs::read_dir(&fs_path).await?;
+		while let Some(entry) = entries.next_entry().await? {
+			if entry.file_type().await?.is_dir() {
+				fs::remove_dir_all(entry.path()).await?;
+			} else {
+				fs::remove_file(entry.path()).await?;
+			}
+		}
+
+		// Download archive
+		let archive_path = volume_path.join("snapshot.tar.zst");
+		let mut stream = reqwest::get(&snapshot.url)
+			.await?
+			.error_for_status()?
+			.bytes_stream();
+		let mut archive_file = File::create(&archive_path).await?;
+		while let Some(chunk) = stream.next().await {
+			archive_file.write_all(&chunk?).await?;
+		}
+		archive_file.flush().await?;
+
+		// Unarchive
+		let cmd_out = Command::new("tar")
+			.arg("--zstd")
+			.arg("-x")
+			.arg("-f")
+			.arg(&archive_path)
+			.arg("-C")
+			.arg(&fs_path)
+			.output()
+			.await?;
+		fs::remove_file(&archive_path).await?;
+
+		ensure!(
+			cmd_out.status.success(),
+			"failed `tar` command\n{}",
+			std::str::from_utf8(&cmd_out.stderr)?
+		);
+
+		write_snapshot_id(&volume_path, snapshot.snapshot_id).await?;
+
+		Ok(())
+	}
+
+	/// Uploads an archive of the volume once no actor is using it and reports the result to the server.
+	pub async fn snapshot(
+		&self,
+		ctx: &Ctx,
+		volume_id: Uuid,
+		snapshot_id: Uuid,
+		upload_url: &str,
+	) -> Result<()> {
+		let state = match self
+			.snapshot_inner(ctx, volume_id, snapshot_id, upload_url)
+			.await
+		{
+			Ok(size) => protocol::VolumeSnapshotState::Complete { size },
+			Err(err) => {
+				tracing::error!(?volume_id, ?snapshot_id, ?err, "volume snapshot failed");
+
+				protocol::VolumeSnapshotState::Failed
+			}
+		};
+
+		ctx.event(
+			protocol::Event::VolumeSnapshotUpdate {
+				volume_id,
+				snapshot_id,
+				state,
+			},
+			None,
+		)
+		.await
+	}
+
+	async fn snapshot_inner(
+		&self,
+		ctx: &Ctx,
+		volume_id: Uuid,
+		snapshot_id: Uuid,
+		upload_url: &str,
+	) -> Result<u64> {
+		// Actors are removed from the ctx once their process has exited
+		let mut i = 0;
+		while ctx
+			.actors
+			.read()
+			.await
+			.values()
+			.any(|actor| actor.uses_volume(volume_id))
+		{
+			ensure!(
+				i < SNAPSHOT_WAIT_RETRIES,
+				"timed out waiting for actors using the volume to exit"
+			);
+			i += 1;
+
+			tokio::time::sleep(SNAPSHOT_WAIT_INTERVAL).await;
+		}
+
+		let lock = self.lock(volume_id).await;
+		let _guard = lock.lock().await;
+
+		tracing::info!(?volume_id, ?snapshot_id, "snapshotting volume");
+
+		let volume_path = ctx.volume_path(volume_id);
+		ensure!(
+			fs::metadata(&volume_path).await.is_ok(),
+			"volume does not exist on this client"
+		);
+
+		mount(ctx, volume_id).await?;
+
+		// Archive
+		let archive_path = volume_path.join("snapshot.tar.zst");
+		let cmd_out = Command::new("tar")
+			.arg("--zstd")
+			.arg("-c")
+			.arg("-f")
+			.arg(&archive_path)
+			.arg("-C")
+			.arg(volume_path.join("fs"))
+			.arg(".")
+			.output()
+			.await?;
+
+		ensure!(
+			cmd_out.status.success(),
+			"failed `tar` command\n{}",
+			std::str::from_utf8(&cmd_out.stderr)?
+		);
+
+		// Upload archive. Presigned URLs do not support chunked uploads so the content length must be set
+		let size = fs::metadata(&archive_path).await?.len();
+		let res = reqwest::Client::new()
+			.put(upload_url)
+			.header(reqwest::header::CONTENT_LENGTH, size)
+			.body(File::open(&archive_path).await?)
+			.send()
+			.await;
+		fs::remove_file(&archive_path).await?;
+		res?.error_for_status()?;
+
+		write_snapshot_id(&volume_path, snapshot_id).await?;
+
+		Ok(size)
+	}
+
+	/// Unmounts and removes the volume from this client.
+	pub async fn delete(&self, ctx: &Ctx, volume_id: Uuid) -> Result<()> {
+		let lock = self.lock(volume_id).await;
+		let _guard = lock.lock().await;
+
+		tracing::info!(?volume_id, "deleting volume");
+
+		let volume_path = ctx.volume_path(volume_id);
+		if fs::metadata(&volume_path).await.is_err() {
+			return Ok(());
+		}
+
+		if ctx.config().runner.use_mounts() {
+			let cmd_out = Command::new("umount")
+				.arg("-dl")
+				.arg(volume_path.join("fs"))
+				.output()
+				.await?;
+
+			// Not mounted if the client restarted since the volume was last used
+			if !cmd_out.status.success() {
+				tracing::warn!(
+					?volume_id,
+					stderr=%std::str::from_utf8(&cmd_out.stderr).unwrap_or("<failed to parse stderr>"),
+					"failed `umount` command",
+				);
+			}
+		}
+
+		fs::remove_dir_all(&volume_path).await?;
+
+		Ok(())
+	}
+}
+
+/// Creates the volume dir and formats its image.
+async fn create(ctx: &Ctx, volume: &protocol::Volume, volume_path: &Path) -> Result<()> {
+	let fs_path = volume_path.join("fs");
+
+	fs::create_dir(volume_path).await?;
+	fs::create_dir(&fs_path).await?;
+
+	if ctx.config().runner.use_mounts() {
+		// Create a zero-filled file
+		let volume_img = File::create(volume_path.join("volume.img")).await?;
+		volume_img.set_len(volume.size * 1024 * 1024).await?;
+
+		// Format file as ext4
+		let cmd_out = Command::new("mkfs.ext4")
+			.arg(volume_path.join("volume.img"))
+			.output()
+			.await?;
+
+		ensure!(
+			cmd_out.status.success(),
+			"failed `mkfs.ext4` command\n{}",
+			std::str::from_utf8(&cmd_out.stderr)?
+		);
+	} else {
+		tracing::warn!(volume_id=?volume.volume_id, "cannot limit volume size without mounts");
+	}
+
+	Ok(())
+}
+
+/// Loop mounts the volume image if it is not already mounted. Volumes stay mounted until deleted so that
+/// they can be bind mounted into actors.
+async fn mount(ctx: &Ctx, volume_id: Uuid) -> Result<()> {
+	if !ctx.config().runner.use_mounts() {
+		return Ok(());
+	}
+
+	let volume_path = ctx.volume_path(volume_id);
+	let fs_path = volume_path.join("fs");
+
+	let cmd_out = Command::new("mountpoint")
+		.arg("-q")
+		.arg(&fs_path)
+		.output()
+		.await?;
+	if cmd_out.status.success() {
+		return Ok(());
+	}
+
+	let cmd_out = Command::new("mount")
+		.arg("-o")
+		.arg("loop")
+		.arg(volume_path.join("volume.img"))
+		.arg(&fs_path)
+		.output()
+		.await?;
+
+	ensure!(
+		cmd_out.status.success(),
+		"failed `mount` command\n{}",
+		std::str::from_utf8(&cmd_out.stderr)?
+	);
+
+	Ok(())
+}
+
+async fn read_snapshot_id(volume_path: &Path) -> Result<Option<Uuid>> {
+	match fs::read_to_string(volume_path.join("snapshot_id")).await {
+		Ok(contents) => Ok(Some(contents.trim().parse()?)),
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+		Err(err) => Err(err.into()),
+	}
+}
+
+async fn write_snapshot_id(volume_path: &Path, snapshot_id: Uuid) -> Result<()> {
+	fs::write(volume_path.join("snapshot_id"), snapshot_id.to_string()).await?;
+
+	Ok(())
+}
@@ -127,12 +127,17 @@ async fn handle_connection(
 								.await;
 							}
 						}
+						protocol::ToServer::ActorMetrics(_) => {}
 						protocol::ToServer::Events(events) => {
 							for event in events {
 								tracing::info!(?event, "received event");
 
 								let protocol::Event::ActorStateUpdate { state, .. } =
-									event.inner.deserialize().unwrap();
+									event.inner.deserialize().unwrap()
+								else {
+									// Ignore other events
+									continue;
+								};
 
 								match state {
 									// Wait for actor to start running
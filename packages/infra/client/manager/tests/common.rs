@@ -31,7 +31,7 @@ use tracing_subscriber::prelude::*;
 use url::Url;
 use uuid::Uuid;
 
-pub const PROTOCOL_VERSION: u16 = 2;
+pub const PROTOCOL_VERSION: u16 = protocol::PROTOCOL_VERSION;
 pub const ARTIFACTS_PORT: u16 = 1234;
 
 pub async fn send_packet(
@@ -49,6 +49,7 @@ pub async fn send_command(
 	let packet = protocol::ToClient::Commands(vec![protocol::CommandWrapper {
 		index: utils::now(),
 		inner: protocol::Raw::new(&cmd).unwrap(),
+		traceparent: None,
 	}]);
 
 	send_packet(tx, packet).await
@@ -78,11 +79,14 @@ pub async fn start_echo_actor(
 				fallback_artifact_url: None,
 				kind: protocol::ImageKind::DockerImage,
 				compression: protocol::ImageCompression::None,
+				digest: None,
+				compression_dictionary: None,
 			},
 			root_user_enabled: false,
 			env: [("PORT".to_string(), port.to_string())]
 				.into_iter()
 				.collect(),
+			secrets: Default::default(),
 			ports: [(
 				"main".to_string(),
 				protocol::Port {
@@ -99,6 +103,12 @@ pub async fn start_echo_actor(
 				memory: 10 * 1024 * 1024,
 				memory_max: 15 * 1024 * 1024,
 				disk: 15,
+				pids: None,
+				io_weight: None,
+				io_read_bps: None,
+				io_write_bps: None,
+				nofile: None,
+				egress_bps: None,
 			},
 			owner: protocol::ActorOwner::DynamicServer {
 				server_id: actor_id,
@@ -131,6 +141,7 @@ pub async fn start_echo_actor(
 				},
 			})
 			.unwrap(),
+			volumes: Vec::new(),
 		}),
 	};
 
@@ -150,9 +161,12 @@ pub async fn start_js_echo_actor(
 				fallback_artifact_url: None,
 				kind: protocol::ImageKind::JavaScript,
 				compression: protocol::ImageCompression::None,
+				digest: None,
+				compression_dictionary: None,
 			},
 			root_user_enabled: false,
 			env: Default::default(),
+			secrets: Default::default(),
 			ports: [(
 				"main".to_string(),
 				protocol::Port {
@@ -169,6 +183,12 @@ pub async fn start_js_echo_actor(
 				memory: 10 * 1024 * 1024,
 				memory_max: 15 * 1024 * 1024,
 				disk: 15,
+				pids: None,
+				io_weight: None,
+				io_read_bps: None,
+				io_write_bps: None,
+				nofile: None,
+				egress_bps: None,
 			},
 			owner: protocol::ActorOwner::DynamicServer {
 				server_id: actor_id,
@@ -201,6 +221,7 @@ pub async fn start_js_echo_actor(
 				},
 			})
 			.unwrap(),
+			volumes: Vec::new(),
 		}),
 	};
 
@@ -263,6 +284,7 @@ pub async fn init_client(gen_path: &Path, working_path: &Path) -> Config {
 				pegboard_endpoint: Url::parse("ws://127.0.0.1:5030").unwrap(),
 				// Not necessary for the test
 				api_endpoint: Url::parse("http://127.0.0.1").unwrap(),
+				token: None,
 			},
 			runner: Runner {
 				// Not necessary for the test
@@ -277,6 +299,7 @@ pub async fn init_client(gen_path: &Path, working_path: &Path) -> Config {
 				pull_addresses: Some(Addresses::Static(vec![format!(
 					"http://127.0.0.1:{ARTIFACTS_PORT}"
 				)])),
+				max_cache_size: None,
 			},
 			network: Network {
 				bind_ip: "127.0.0.1".parse().unwrap(),
@@ -301,6 +324,7 @@ pub async fn init_client(gen_path: &Path, working_path: &Path) -> Config {
 			},
 			vector: Some(Vector {
 				address: "127.0.0.1:5021".into(),
+				multiline_pattern: None,
 			}),
 		},
 	};
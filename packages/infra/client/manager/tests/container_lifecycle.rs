@@ -80,12 +80,17 @@ async fn handle_connection(
 
 							start_echo_actor(&mut tx, actor_id, actor_port).await;
 						}
+						protocol::ToServer::ActorMetrics(_) => {}
 						protocol::ToServer::Events(events) => {
 							for event in events {
 								tracing::info!(?event, "received event");
 
 								let protocol::Event::ActorStateUpdate { state, .. } =
-									event.inner.deserialize().unwrap();
+									event.inner.deserialize().unwrap()
+								else {
+									// Ignore other events
+									continue;
+								};
 
 								match state {
 									protocol::ActorState::Starting => {
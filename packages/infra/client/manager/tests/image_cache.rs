@@ -0,0 +1,97 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use pegboard_manager::image_cache::{
+	plan_eviction, ArtifactKind, CacheEntry, CacheKey, ImageCache,
+};
+use uuid::Uuid;
+
+fn key() -> CacheKey {
+	(Uuid::new_v4(), ArtifactKind::Image, String::new())
+}
+
+fn entry(key: &CacheKey, size: i64) -> CacheEntry {
+	CacheEntry {
+		key: key.clone(),
+		size,
+	}
+}
+
+fn keys<'a>(entries: impl IntoIterator<Item = &'a CacheEntry>) -> Vec<CacheKey> {
+	entries.into_iter().map(|entry| entry.key.clone()).collect()
+}
+
+#[test]
+fn eviction_within_budget() {
+	let (a, b) = (key(), key());
+	let entries = vec![entry(&a, 40), entry(&b, 60)];
+
+	assert!(plan_eviction(&entries, 100, &HashSet::new()).is_empty());
+}
+
+#[test]
+fn eviction_least_recently_used_first() {
+	let (a, b, c) = (key(), key(), key());
+	let entries = vec![entry(&a, 40), entry(&b, 40), entry(&c, 40)];
+
+	// 120 -> 80 fits after evicting the oldest entry
+	assert_eq!(
+		keys(plan_eviction(&entries, 100, &HashSet::new())),
+		vec![a.clone()]
+	);
+	// 120 -> 80 -> 40
+	assert_eq!(
+		keys(plan_eviction(&entries, 50, &HashSet::new())),
+		vec![a, b]
+	);
+}
+
+#[test]
+fn eviction_skips_leased() {
+	let (a, b, c) = (key(), key(), key());
+	let entries = vec![entry(&a, 40), entry(&b, 40), entry(&c, 40)];
+	let leased = [a.clone()].into_iter().collect();
+
+	assert_eq!(keys(plan_eviction(&entries, 100, &leased)), vec![b]);
+}
+
+#[test]
+fn eviction_everything_leased() {
+	let (a, b) = (key(), key());
+	let entries = vec![entry(&a, 80), entry(&b, 80)];
+	let leased = [a.clone(), b.clone()].into_iter().collect();
+
+	assert!(plan_eviction(&entries, 100, &leased).is_empty());
+}
+
+#[test]
+fn artifact_kinds_are_distinct_keys() {
+	let image_id = Uuid::new_v4();
+	let image = (image_id, ArtifactKind::Image, String::new());
+	let dictionary = (image_id, ArtifactKind::CompressionDictionary, String::new());
+	let entries = vec![entry(&image, 80), entry(&dictionary, 80)];
+	let leased = [image.clone()].into_iter().collect();
+
+	assert_eq!(
+		keys(plan_eviction(&entries, 100, &leased)),
+		vec![dictionary]
+	);
+}
+
+#[test]
+fn leases() {
+	let cache = ImageCache::new();
+	let (a, b) = (key(), key());
+
+	let lease1 = cache.lease(a.clone(), PathBuf::from("/a"));
+	let lease2 = cache.lease(a.clone(), PathBuf::from("/a"));
+	assert_eq!(lease1.path(), PathBuf::from("/a"));
+	assert!(cache.is_leased(&a));
+	assert!(!cache.is_leased(&b));
+
+	// Held until the last lease is dropped
+	drop(lease1);
+	assert!(cache.is_leased(&a));
+
+	drop(lease2);
+	assert!(!cache.is_leased(&a));
+}
@@ -79,12 +79,17 @@ async fn handle_connection(
 
 							start_js_echo_actor(&mut tx, actor_id).await;
 						}
+						protocol::ToServer::ActorMetrics(_) => {}
 						protocol::ToServer::Events(events) => {
 							for event in events {
 								tracing::info!(?event, "received event");
 
 								let protocol::Event::ActorStateUpdate { state, .. } =
-									event.inner.deserialize().unwrap();
+									event.inner.deserialize().unwrap()
+								else {
+									// Ignore other events
+									continue;
+								};
 
 								match state {
 									protocol::ActorState::Starting => {
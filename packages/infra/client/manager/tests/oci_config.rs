@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use pegboard_manager::oci_config::{self, ConfigOpts};
+use serde_json::json;
+
+fn opts<'a>() -> ConfigOpts<'a> {
+	ConfigOpts {
+		actor_path: Path::new("/var/lib/tivet-client/actors/test"),
+		netns_path: Path::new("/var/run/netns/test"),
+		cgroups_path: "/tivet-client/test".to_string(),
+		args: vec!["/app".to_string()],
+		env: Vec::new(),
+		user: serde_json::from_value(json!({ "uid": 0, "gid": 0 })).unwrap(),
+		cwd: "/".to_string(),
+		cpu: 1_000,
+		memory: 128 * 1024 * 1024,
+		memory_max: 192 * 1024 * 1024,
+		pids: None,
+		nofile: None,
+		io_weight: None,
+		io_read_bps: None,
+		io_write_bps: None,
+		fs_device: None,
+		volumes: Vec::new(),
+	}
+}
+
+#[test]
+fn oci_config_default_limits() {
+	let config = oci_config::config(opts()).unwrap();
+	let resources = &config["linux"]["resources"];
+
+	// No pids limit unless requested
+	assert!(resources["pids"].is_null());
+
+	assert_eq!(
+		json!({
+			"weight": null,
+			"throttleReadBpsDevice": [],
+			"throttleWriteBpsDevice": [],
+		}),
+		resources["blockIO"]
+	);
+
+	assert_eq!(
+		json!([{ "type": "RLIMIT_NOFILE", "hard": 1024, "soft": 1024 }]),
+		config["process"]["rlimits"]
+	);
+}
+
+#[test]
+fn oci_config_limits() {
+	let config = oci_config::config(ConfigOpts {
+		pids: Some(256),
+		nofile: Some(4096),
+		io_weight: Some(100),
+		io_read_bps: Some(64 * 1024 * 1024),
+		io_write_bps: Some(32 * 1024 * 1024),
+		fs_device: Some((7, 3)),
+		..opts()
+	})
+	.unwrap();
+	let resources = &config["linux"]["resources"];
+
+	assert_eq!(json!({ "limit": 256 }), resources["pids"]);
+
+	assert_eq!(
+		json!({
+			"weight": 100,
+			"throttleReadBpsDevice": [{ "major": 7, "minor": 3, "rate": 64 * 1024 * 1024 }],
+			"throttleWriteBpsDevice": [{ "major": 7, "minor": 3, "rate": 32 * 1024 * 1024 }],
+		}),
+		resources["blockIO"]
+	);
+
+	assert_eq!(
+		json!([{ "type": "RLIMIT_NOFILE", "hard": 4096, "soft": 4096 }]),
+		config["process"]["rlimits"]
+	);
+}
+
+#[test]
+fn oci_config_block_io() {
+	// Throttles need a block device
+	let config = oci_config::config(ConfigOpts {
+		io_read_bps: Some(1024),
+		io_write_bps: Some(1024),
+		..opts()
+	})
+	.unwrap();
+	let block_io = &config["linux"]["resources"]["blockIO"];
+	assert_eq!(json!([]), block_io["throttleReadBpsDevice"]);
+	assert_eq!(json!([]), block_io["throttleWriteBpsDevice"]);
+
+	// Only the set throttle is applied
+	let config = oci_config::config(ConfigOpts {
+		io_write_bps: Some(1024),
+		fs_device: Some((259, 0)),
+		..opts()
+	})
+	.unwrap();
+	let block_io = &config["linux"]["resources"]["blockIO"];
+	assert_eq!(json!([]), block_io["throttleReadBpsDevice"]);
+	assert_eq!(
+		json!([{ "major": 259, "minor": 0, "rate": 1024 }]),
+		block_io["throttleWriteBpsDevice"]
+	);
+
+	// Weight is clamped to the cgroups range
+	for (weight, expected) in [(1, 10), (10, 10), (500, 500), (1000, 1000), (5000, 1000)] {
+		let config = oci_config::config(ConfigOpts {
+			io_weight: Some(weight),
+			..opts()
+		})
+		.unwrap();
+		assert_eq!(
+			json!(expected),
+			config["linux"]["resources"]["blockIO"]["weight"]
+		);
+	}
+}
@@ -0,0 +1,125 @@
+use pegboard_manager::usage::{
+	parse_cpu_stat, parse_io_stat, parse_net_dev, Sample, UsageState, WINDOW_DURATION_MS,
+};
+
+const CPU_STAT: &str = "usage_usec 1500000
+user_usec 1000000
+system_usec 500000
+nr_periods 0
+nr_throttled 0
+throttled_usec 0
+";
+
+const IO_STAT: &str = "8:0 rbytes=1024 wbytes=2048 rios=1 wios=2 dbytes=0 dios=0
+253:0 rbytes=4096 wbytes=0 rios=4 wios=0 dbytes=0 dios=0
+";
+
+const NET_DEV: &str = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:  999999      10    0    0    0     0          0         0   999999      10    0    0    0     0       0          0
+  eth0:    1000      10    0    0    0     0          0         0     2000      20    0    0    0     0       0          0
+  eth1:     500       5    0    0    0     0          0         0      250       2    0    0    0     0       0          0
+";
+
+fn sample(ts: i64, cpu_usage_usec: u64, memory: u64, network: (u64, u64)) -> Sample {
+	Sample {
+		ts,
+		cpu_usage_usec,
+		memory,
+		network: Some(network),
+		disk: None,
+	}
+}
+
+#[test]
+fn cpu_stat() {
+	assert_eq!(parse_cpu_stat(CPU_STAT).unwrap(), 1_500_000);
+	assert!(parse_cpu_stat("user_usec 10\n").is_err());
+}
+
+#[test]
+fn io_stat() {
+	assert_eq!(parse_io_stat(IO_STAT).unwrap(), (5120, 2048));
+	// No devices with io yet
+	assert_eq!(parse_io_stat("").unwrap(), (0, 0));
+	assert!(parse_io_stat("8:0 rbytes=abc wbytes=0\n").is_err());
+}
+
+#[test]
+fn net_dev() {
+	// Loopback is excluded
+	assert_eq!(parse_net_dev(NET_DEV).unwrap(), (1500, 2250));
+	assert!(parse_net_dev("header\nheader\n  eth0: 1000 10\n").is_err());
+}
+
+#[test]
+fn window() {
+	let mut state = UsageState::default();
+
+	// First sample only starts the window
+	assert!(state
+		.record(sample(0, 0, 100, (1000, 2000)))
+		.unwrap()
+		.is_none());
+
+	// 500 millicores for 30s, then 1000 millicores for 30s
+	let recorded = state
+		.record(sample(30_000, 15_000_000, 300, (1500, 2500)))
+		.unwrap()
+		.unwrap();
+	assert_eq!(recorded.cpu_usage_usec, 15_000_000);
+	assert!(recorded.metrics.is_none());
+
+	let recorded = state
+		.record(sample(WINDOW_DURATION_MS, 45_000_000, 200, (3000, 2500)))
+		.unwrap()
+		.unwrap();
+	assert_eq!(recorded.prev.ts, 30_000);
+	let metrics = recorded.metrics.unwrap();
+
+	assert_eq!(metrics.start_ts, 0);
+	assert_eq!(metrics.end_ts, WINDOW_DURATION_MS);
+	assert_eq!(metrics.cpu_avg, 750);
+	assert_eq!(metrics.cpu_max, 1000);
+	assert_eq!(metrics.memory_avg, 250);
+	assert_eq!(metrics.memory_max, 300);
+	assert_eq!(metrics.network_rx_bytes, Some(2000));
+	assert_eq!(metrics.network_tx_bytes, Some(500));
+	assert_eq!(metrics.disk_read_bytes, None);
+
+	// The next window starts at the end of the previous one
+	let recorded = state
+		.record(sample(
+			2 * WINDOW_DURATION_MS,
+			45_000_000,
+			100,
+			(3000, 2500),
+		))
+		.unwrap()
+		.unwrap();
+	let metrics = recorded.metrics.unwrap();
+
+	assert_eq!(metrics.start_ts, WINDOW_DURATION_MS);
+	assert_eq!(metrics.cpu_avg, 0);
+	assert_eq!(metrics.network_rx_bytes, Some(0));
+}
+
+#[test]
+fn window_stale_sample() {
+	let mut state = UsageState::default();
+
+	state.record(sample(5_000, 0, 100, (0, 0))).unwrap();
+
+	// Isolates rewrite the same sample until the next one is taken
+	assert!(state
+		.record(sample(5_000, 1_000, 100, (0, 0)))
+		.unwrap()
+		.is_none());
+
+	// Counter resets do not underflow
+	let recorded = state
+		.record(sample(10_000, 0, 100, (0, 0)))
+		.unwrap()
+		.unwrap();
+	assert_eq!(recorded.cpu_usage_usec, 0);
+}
@@ -0,0 +1,242 @@
+use anyhow::*;
+use chirp_workflow::prelude::StandaloneCtx;
+use clap::Parser;
+use cluster::types::{AutoscaleAction, AutoscalePolicy, PoolType};
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub enum SubCommand {
+	/// Creates or replaces the autoscale policy of a pool and starts the autoscaler.
+	Set {
+		datacenter_id: Uuid,
+		/// One of `job`, `gg`, `ats`, `pegboard` or `pegboard-isolate`.
+		#[clap(value_parser = parse_pool_type)]
+		pool_type: PoolType,
+		/// Percent of the pool's CPU and memory that should be in use.
+		#[clap(long)]
+		target_utilization: u32,
+		/// Percentage points around the target in which the desired count is not changed.
+		#[clap(long, default_value_t = 10)]
+		hysteresis: u32,
+		/// Minimum time (in ms) between scaling up.
+		#[clap(long, default_value_t = 60_000)]
+		scale_up_cooldown: u64,
+		/// Minimum time (in ms) after any scale before scaling down.
+		#[clap(long, default_value_t = 600_000)]
+		scale_down_cooldown: u64,
+	},
+	/// Stops autoscaling a pool. The pool keeps its current desired count.
+	Remove {
+		datacenter_id: Uuid,
+		#[clap(value_parser = parse_pool_type)]
+		pool_type: PoolType,
+	},
+	/// Lists recent autoscale evaluations of a datacenter, newest first.
+	Events {
+		datacenter_id: Uuid,
+		/// Only lists events of this pool.
+		#[clap(long, value_parser = parse_pool_type)]
+		pool_type: Option<PoolType>,
+		/// Max number of events to print.
+		#[clap(long, short = 'c', default_value_t = 50)]
+		count: u32,
+	},
+}
+
+impl SubCommand {
+	pub async fn execute(self, config: tivet_config::Config) -> Result<()> {
+		let ctx = ctx(config).await?;
+
+		match self {
+			Self::Set {
+				datacenter_id,
+				pool_type,
+				target_utilization,
+				hysteresis,
+				scale_up_cooldown,
+				scale_down_cooldown,
+			} => {
+				let policy = AutoscalePolicy {
+					pool_type,
+					target_utilization,
+					hysteresis,
+					scale_up_cooldown,
+					scale_down_cooldown,
+				};
+				policy.validate().map_err(|err| anyhow!("{err:?}"))?;
+				ensure_pool_exists(&ctx, datacenter_id, pool_type).await?;
+
+				ctx.signal(cluster::workflows::datacenter::AutoscaleUpdate {
+					policies: vec![policy],
+					remove: Vec::new(),
+				})
+				.tag("datacenter_id", datacenter_id)
+				.send()
+				.await
+				.map_err(|err| anyhow!("{err:?}"))?;
+
+				tivet_term::status::success("Set autoscale policy", pool_type);
+
+				Ok(())
+			}
+			Self::Remove {
+				datacenter_id,
+				pool_type,
+			} => {
+				ensure_pool_exists(&ctx, datacenter_id, pool_type).await?;
+
+				ctx.signal(cluster::workflows::datacenter::AutoscaleUpdate {
+					policies: Vec::new(),
+					remove: vec![pool_type],
+				})
+				.tag("datacenter_id", datacenter_id)
+				.send()
+				.await
+				.map_err(|err| anyhow!("{err:?}"))?;
+
+				tivet_term::status::success("Removed autoscale policy", pool_type);
+
+				Ok(())
+			}
+			Self::Events {
+				datacenter_id,
+				pool_type,
+				count,
+			} => {
+				let res = ctx
+					.op(cluster::ops::datacenter::autoscale_event_list::Input {
+						datacenter_id,
+						pool_type,
+						before_ts: None,
+						limit: count,
+					})
+					.await
+					.map_err(|err| anyhow!("{err:?}"))?;
+
+				if res.events.is_empty() {
+					tivet_term::status::success("No autoscale events found", "");
+				} else {
+					table::events(res.events)?;
+				}
+
+				Ok(())
+			}
+		}
+	}
+}
+
+async fn ctx(config: tivet_config::Config) -> Result<StandaloneCtx> {
+	let pools = tivet_pools::Pools::new(config.clone()).await?;
+	let client = chirp_client::SharedClient::from_env(pools.clone())
+		.map_err(|err| anyhow!("{err:?}"))?
+		.wrap_new("tivet-server-cli");
+	let cache =
+		tivet_cache::CacheInner::from_env(pools.clone()).map_err(|err| anyhow!("{err:?}"))?;
+
+	StandaloneCtx::new(
+		chirp_workflow::compat::db_from_pools(&pools)
+			.await
+			.map_err(|err| anyhow!("{err:?}"))?,
+		config,
+		tivet_connection::Connection::new(client, pools, cache),
+		"tivet-server-cli",
+	)
+	.await
+	.map_err(|err| anyhow!("{err:?}"))
+}
+
+async fn ensure_pool_exists(
+	ctx: &StandaloneCtx,
+	datacenter_id: Uuid,
+	pool_type: PoolType,
+) -> Result<()> {
+	let res = ctx
+		.op(cluster::ops::datacenter::get::Input {
+			datacenter_ids: vec![datacenter_id],
+		})
+		.await
+		.map_err(|err| anyhow!("{err:?}"))?;
+	let dc = res.datacenters.first().context("datacenter not found")?;
+
+	ensure!(
+		dc.pools.iter().any(|pool| pool.pool_type == pool_type),
+		"datacenter has no {pool_type} pool"
+	);
+
+	Ok(())
+}
+
+fn parse_pool_type(s: &str) -> Result<PoolType> {
+	[
+		PoolType::Job,
+		PoolType::Gg,
+		PoolType::Ats,
+		PoolType::Pegboard,
+		PoolType::PegboardIsolate,
+		PoolType::Fdb,
+	]
+	.into_iter()
+	.find(|pool_type| pool_type.to_string() == s)
+	.with_context(|| format!("invalid pool type: {s}"))
+}
+
+mod table {
+	use anyhow::*;
+	use chrono::{Local, TimeZone};
+	use cluster::types::AutoscaleEvent;
+	use tabled::Tabled;
+
+	use super::AutoscaleAction;
+
+	#[derive(Tabled)]
+	struct EventTableRow {
+		pub created_at: String,
+		pub pool: String,
+		pub action: String,
+		pub utilization: String,
+		pub servers: u32,
+		pub desired: String,
+	}
+
+	pub fn events(events: Vec<AutoscaleEvent>) -> Result<()> {
+		let rows = events
+			.into_iter()
+			.map(|event| {
+				let datetime = Local
+					.timestamp_millis_opt(event.create_ts)
+					.single()
+					.context("invalid ts")?;
+
+				Ok(EventTableRow {
+					created_at: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+					pool: event.pool_type.to_string(),
+					action: match event.action {
+						AutoscaleAction::Hold => "hold",
+						AutoscaleAction::ScaleUp => "scale up",
+						AutoscaleAction::ScaleDown => "scale down",
+						AutoscaleAction::Cooldown => "cooldown",
+						AutoscaleAction::AtLimit => "at limit",
+					}
+					.to_string(),
+					utilization: event
+						.utilization
+						.map(|x| format!("{x}%"))
+						.unwrap_or_else(|| "-".to_string()),
+					servers: event.server_count,
+					desired: if event.previous_desired_count == event.desired_count {
+						event.desired_count.to_string()
+					} else {
+						format!(
+							"{} -> {}",
+							event.previous_desired_count, event.desired_count
+						)
+					},
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		tivet_term::format::table(rows);
+
+		Ok(())
+	}
+}
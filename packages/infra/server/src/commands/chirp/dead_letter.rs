@@ -0,0 +1,95 @@
+use anyhow::*;
+use clap::Parser;
+
+use crate::util;
+
+#[derive(Parser)]
+pub enum SubCommand {
+	/// Lists dead-lettered messages of a worker. Lists all workers with dead-lettered messages if
+	/// no worker is given.
+	List {
+		/// Worker name (i.e. `{service}--{worker}`).
+		worker: Option<String>,
+		/// Max number of messages to print, newest first.
+		#[clap(long, short = 'c', default_value_t = 100)]
+		count: usize,
+	},
+	/// Prints the given dead-lettered message(s), including the last error and trace.
+	Get { worker: String, ids: Vec<String> },
+	/// Publishes the given dead-lettered message(s) to the worker again and removes them from the
+	/// dead-letter stream.
+	Replay {
+		worker: String,
+		#[clap(required_unless_present = "all")]
+		ids: Vec<String>,
+		/// Replays all dead-lettered messages of the worker.
+		#[clap(long, conflicts_with = "ids")]
+		all: bool,
+	},
+	/// Deletes the given dead-lettered message(s).
+	Purge {
+		worker: String,
+		#[clap(required_unless_present = "all")]
+		ids: Vec<String>,
+		/// Deletes all dead-lettered messages of the worker.
+		#[clap(long, conflicts_with = "ids")]
+		all: bool,
+	},
+}
+
+impl SubCommand {
+	pub async fn execute(self, config: tivet_config::Config) -> Result<()> {
+		let mut redis = util::chirp::redis(config).await?;
+
+		match self {
+			Self::List {
+				worker: Some(worker),
+				count,
+			} => {
+				let messages =
+					util::chirp::dead_letter::list_messages(&mut redis, &worker, Some(count))
+						.await?;
+				util::chirp::dead_letter::print_messages(messages, false)
+			}
+			Self::List {
+				worker: None,
+				count: _,
+			} => {
+				let workers = util::chirp::dead_letter::list_workers(&mut redis).await?;
+				util::chirp::dead_letter::print_workers(workers)
+			}
+			Self::Get { worker, ids } => {
+				let messages =
+					util::chirp::dead_letter::get_messages(&mut redis, &worker, ids).await?;
+				util::chirp::dead_letter::print_messages(messages, true)
+			}
+			Self::Replay { worker, ids, all } => {
+				let ids = if all {
+					util::chirp::dead_letter::list_messages(&mut redis, &worker, None)
+						.await?
+						.into_iter()
+						.map(|msg| msg.id)
+						.collect()
+				} else {
+					ids
+				};
+
+				let count =
+					util::chirp::dead_letter::replay_messages(&mut redis, &worker, ids).await?;
+				tivet_term::status::success("Replayed messages", count);
+
+				Ok(())
+			}
+			Self::Purge { worker, ids, all } => {
+				let count = if all {
+					util::chirp::dead_letter::purge_all(&mut redis, &worker).await?
+				} else {
+					util::chirp::dead_letter::purge_messages(&mut redis, &worker, ids).await?
+				};
+				tivet_term::status::success("Purged messages", count);
+
+				Ok(())
+			}
+		}
+	}
+}
@@ -0,0 +1,22 @@
+use anyhow::*;
+use clap::Parser;
+
+mod dead_letter;
+
+#[derive(Parser)]
+pub enum SubCommand {
+	/// Manages messages that consumer workers failed to process too many times.
+	#[clap(alias = "dlq")]
+	DeadLetter {
+		#[clap(subcommand)]
+		command: dead_letter::SubCommand,
+	},
+}
+
+impl SubCommand {
+	pub async fn execute(self, config: tivet_config::Config) -> Result<()> {
+		match self {
+			Self::DeadLetter { command } => command.execute(config).await,
+		}
+	}
+}
@@ -0,0 +1,51 @@
+use anyhow::*;
+use clap::Parser;
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub enum SubCommand {
+	/// Creates a token for a manually provisioned pegboard client. Set as `client.cluster.token` in the
+	/// client config.
+	CreateClientToken {
+		/// Must match `client.cluster.client_id` in the client config.
+		#[clap(long)]
+		client_id: Uuid,
+	},
+}
+
+impl SubCommand {
+	pub async fn execute(self, config: tivet_config::Config) -> Result<()> {
+		match self {
+			Self::CreateClientToken { client_id } => {
+				let pools = tivet_pools::Pools::new(config.clone()).await?;
+				let client = chirp_client::SharedClient::from_env(pools.clone())
+					.map_err(|err| anyhow!("{err:?}"))?
+					.wrap_new("tivet-server-cli");
+				let cache = tivet_cache::CacheInner::from_env(pools.clone())
+					.map_err(|err| anyhow!("{err:?}"))?;
+				let ctx = chirp_workflow::prelude::StandaloneCtx::new(
+					chirp_workflow::compat::db_from_pools(&pools)
+						.await
+						.map_err(|err| anyhow!("{err:?}"))?,
+					config,
+					tivet_connection::Connection::new(client, pools, cache),
+					"tivet-server-cli",
+				)
+				.await
+				.map_err(|err| anyhow!("{err:?}"))?;
+
+				let res = ctx
+					.op(cluster::ops::server::token_create::Input {
+						issuer: "tivet-server-cli".to_string(),
+						server_id: Some(client_id),
+					})
+					.await
+					.map_err(|err| anyhow!("{err:?}"))?;
+
+				println!("{}", res.token);
+
+				Ok(())
+			}
+		}
+	}
+}
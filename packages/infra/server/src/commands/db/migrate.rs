@@ -1,7 +1,7 @@
 use anyhow::*;
 use clap::Parser;
 
-use crate::run_config::RunConfig;
+use crate::{run_config::RunConfig, util};
 
 #[derive(Parser)]
 pub enum SubCommand {
@@ -15,23 +15,23 @@ pub enum SubCommand {
 	Force { service: String, num: usize },
 	/// Drops the entire database.
 	Drop { service: String },
+	/// Prints the current version and pending migrations of each database.
+	Status { services: Vec<String> },
+	/// Prints the migrations that `up` would apply without applying them.
+	Plan {
+		services: Vec<String>,
+		/// Prints the SQL of each migration.
+		#[clap(long)]
+		sql: bool,
+	},
 }
 
 impl SubCommand {
 	pub async fn execute(self, config: tivet_config::Config, run_config: &RunConfig) -> Result<()> {
 		match self {
 			Self::Up { services: names } => {
-				if names.is_empty() {
-					tivet_migrate::up(config, &run_config.sql_services).await?;
-				} else {
-					let services = run_config
-						.sql_services
-						.iter()
-						.filter(|x| names.iter().any(|y| *y == x.db_name))
-						.cloned()
-						.collect::<Vec<_>>();
-					tivet_migrate::up(config, &services).await?;
-				};
+				let services = filter_services(run_config, &names);
+				tivet_migrate::up(config, &services).await?;
 			}
 			Self::Down { service, num } => {
 				let service = run_config
@@ -57,8 +57,31 @@ impl SubCommand {
 					.context("service not found")?;
 				tivet_migrate::drop(config, service).await?;
 			}
+			Self::Status { services: names } => {
+				let services = filter_services(run_config, &names);
+				let statuses = tivet_migrate::status(config, &services).await?;
+				util::migrate::print_statuses(statuses)?;
+			}
+			Self::Plan {
+				services: names,
+				sql,
+			} => {
+				let services = filter_services(run_config, &names);
+				let statuses = tivet_migrate::plan(config, &services).await?;
+				util::migrate::print_plan(statuses, sql);
+			}
 		}
 
 		Ok(())
 	}
 }
+
+/// Returns all services if no names are given.
+fn filter_services(run_config: &RunConfig, names: &[String]) -> Vec<tivet_migrate::SqlService> {
+	run_config
+		.sql_services
+		.iter()
+		.filter(|x| names.is_empty() || names.iter().any(|y| *y == x.db_name))
+		.cloned()
+		.collect()
+}
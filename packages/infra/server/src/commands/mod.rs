@@ -1,3 +1,6 @@
+pub mod autoscale;
+pub mod chirp;
+pub mod cluster;
 pub mod config;
 pub mod db;
 pub mod provision;
@@ -44,6 +44,16 @@ impl Opts {
 		config: tivet_config::Config,
 		run_config: &RunConfig,
 	) -> Result<()> {
+		// Export traces
+		let server_config = config.server().map_err(|err| anyhow!("{err:?}"))?;
+		if let Some(otel) = &server_config.otel {
+			tivet_runtime::otel::enable(tivet_runtime::otel::OtelConfig {
+				endpoint: otel.endpoint.to_string(),
+				service_name: "tivet-server".to_string(),
+				sample_ratio: otel.sample_ratio,
+			})?;
+		}
+
 		// Provision services before starting server
 		if !self.skip_provision {
 			s3_util::provision(config.clone(), &run_config.s3_buckets).await?;
@@ -72,8 +82,10 @@ impl Opts {
 
 		// Start server
 		let pools = tivet_pools::Pools::new(config.clone()).await?;
-		tivet_service_manager::start(config, pools, services).await?;
+		let res = tivet_service_manager::start(config, pools, services).await;
+
+		tivet_runtime::otel::shutdown();
 
-		Ok(())
+		res
 	}
 }
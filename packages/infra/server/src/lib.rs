@@ -32,6 +32,21 @@ pub enum SubCommand {
 		#[clap(subcommand)]
 		command: wf::SubCommand,
 	},
+	/// Manages Chirp workers
+	Chirp {
+		#[clap(subcommand)]
+		command: chirp::SubCommand,
+	},
+	/// Manages clusters
+	Cluster {
+		#[clap(subcommand)]
+		command: cluster::SubCommand,
+	},
+	/// Manages utilization based autoscaling of datacenter pools
+	Autoscale {
+		#[clap(subcommand)]
+		command: autoscale::SubCommand,
+	},
 	/// Manage the Tivet config
 	Config {
 		#[clap(subcommand)]
@@ -51,6 +66,9 @@ impl SubCommand {
 			SubCommand::Database { command } => command.execute(config, &run_config).await,
 			SubCommand::Storage { command } => command.execute(config, &run_config).await,
 			SubCommand::Workflow { command } => command.execute(config).await,
+			SubCommand::Chirp { command } => command.execute(config).await,
+			SubCommand::Cluster { command } => command.execute(config).await,
+			SubCommand::Autoscale { command } => command.execute(config).await,
 			SubCommand::Config { command } => command.execute(config).await,
 		}
 	}
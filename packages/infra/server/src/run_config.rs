@@ -91,6 +91,9 @@ pub fn config(tivet_config: tivet_config::Config) -> Result<RunConfigData> {
 			ServiceKind::Singleton,
 			|config, pools| Box::pin(cluster_metrics_publish::start(config, pools)),
 		),
+		Service::new("build_gc", ServiceKind::Singleton, |config, pools| {
+			Box::pin(build_gc::start(config, pools))
+		}),
 		Service::new("cluster_gc", ServiceKind::Singleton, |config, pools| {
 			Box::pin(cluster_gc::start(config, pools))
 		}),
@@ -306,6 +309,13 @@ pub fn config(tivet_config: tivet_config::Config) -> Result<RunConfigData> {
 			migrations: include_dir!("$CARGO_MANIFEST_DIR/../../services/job-log/db/log"),
 			db_name: "db_job_log",
 		},
+		SqlService {
+			kind: SqlServiceKind::ClickHouse,
+			migrations: include_dir!(
+				"$CARGO_MANIFEST_DIR/../../services/pegboard/db/pegboard-analytics"
+			),
+			db_name: "db_pegboard_analytics",
+		},
 	];
 
 	let s3_buckets = vec![
@@ -345,6 +355,9 @@ pub fn config(tivet_config: tivet_config::Config) -> Result<RunConfigData> {
 		S3Bucket {
 			name: "bucket-avatar",
 		},
+		S3Bucket {
+			name: "bucket-actor-volume",
+		},
 	];
 
 	Ok(RunConfigData {
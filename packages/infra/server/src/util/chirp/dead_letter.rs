@@ -0,0 +1,322 @@
+use anyhow::*;
+use chirp_client::redis_keys;
+use chrono::{Local, TimeZone};
+use prost::Message;
+use tivet_pools::{
+	prelude::redis::{self, streams::StreamId, AsyncCommands},
+	RedisPool,
+};
+use tivet_term::console::style;
+use types_proto::tivet::chirp;
+
+use crate::util::format::{colored_json, indent_string};
+
+#[derive(Debug)]
+pub struct DeadLetterMessage {
+	pub id: String,
+	pub topic_key: String,
+	pub group: String,
+	pub message_id: String,
+	pub message: Vec<u8>,
+	pub delivery_count: usize,
+	/// JSON with the error, traceparent and timestamp of the last failed attempt.
+	pub error: Option<serde_json::Value>,
+	pub ts: i64,
+}
+
+impl DeadLetterMessage {
+	fn parse(entry: StreamId) -> Result<Self> {
+		use redis_keys::dead_letter::*;
+
+		let error = entry
+			.get::<String>(ERROR)
+			.filter(|x| !x.is_empty())
+			.map(|x| serde_json::from_str(&x))
+			.transpose()?;
+
+		Ok(DeadLetterMessage {
+			topic_key: entry.get(TOPIC_KEY).context("missing topic key")?,
+			group: entry.get(GROUP).context("missing group")?,
+			message_id: entry.get(MESSAGE_ID).context("missing message id")?,
+			message: entry.get(MESSAGE).context("missing message")?,
+			delivery_count: entry.get(DELIVERY_COUNT).unwrap_or_default(),
+			error,
+			ts: entry.get(TS).unwrap_or_default(),
+			id: entry.id,
+		})
+	}
+}
+
+pub struct DeadLetterWorker {
+	pub name: String,
+	pub len: usize,
+}
+
+pub async fn list_workers(redis: &mut RedisPool) -> Result<Vec<DeadLetterWorker>> {
+	let mut names = redis
+		.smembers::<_, Vec<String>>(redis_keys::dead_letter_workers())
+		.await?;
+	names.sort();
+
+	let mut workers = Vec::with_capacity(names.len());
+	for name in names {
+		let len = redis
+			.xlen::<_, usize>(redis_keys::dead_letter(&name))
+			.await?;
+		workers.push(DeadLetterWorker { name, len });
+	}
+
+	Ok(workers)
+}
+
+/// Lists messages newest first. Lists all messages if no count is given.
+pub async fn list_messages(
+	redis: &mut RedisPool,
+	worker: &str,
+	count: Option<usize>,
+) -> Result<Vec<DeadLetterMessage>> {
+	let key = redis_keys::dead_letter(worker);
+	let res = if let Some(count) = count {
+		redis
+			.xrevrange_count::<_, _, _, _, redis::streams::StreamRangeReply>(&key, "+", "-", count)
+			.await?
+	} else {
+		redis
+			.xrevrange::<_, _, _, redis::streams::StreamRangeReply>(&key, "+", "-")
+			.await?
+	};
+
+	res.ids.into_iter().map(DeadLetterMessage::parse).collect()
+}
+
+pub async fn get_messages(
+	redis: &mut RedisPool,
+	worker: &str,
+	ids: Vec<String>,
+) -> Result<Vec<DeadLetterMessage>> {
+	let key = redis_keys::dead_letter(worker);
+
+	let mut messages = Vec::with_capacity(ids.len());
+	for id in ids {
+		let res = redis
+			.xrange::<_, _, _, redis::streams::StreamRangeReply>(&key, &id, &id)
+			.await?;
+		let entry = res
+			.ids
+			.into_iter()
+			.next()
+			.with_context(|| format!("dead-lettered message not found: {id}"))?;
+
+		messages.push(DeadLetterMessage::parse(entry)?);
+	}
+
+	Ok(messages)
+}
+
+/// Publishes the messages to their original topic again. The messages are only handled by the
+/// worker's consumer group. Returns how many messages were replayed.
+pub async fn replay_messages(
+	redis: &mut RedisPool,
+	worker: &str,
+	ids: Vec<String>,
+) -> Result<usize> {
+	let key = redis_keys::dead_letter(worker);
+	let messages = get_messages(redis, worker, ids).await?;
+
+	for msg in &messages {
+		// Not atomic since the topic lives in a different slot. Replaying is at least once.
+		redis
+			.xadd::<_, _, _, _, ()>(
+				&msg.topic_key,
+				"*",
+				&[
+					(redis_keys::message_topic::MESSAGE, msg.message.as_slice()),
+					(redis_keys::message_topic::GROUP, msg.group.as_bytes()),
+				],
+			)
+			.await?;
+		redis.xdel::<_, _, ()>(&key, &[&msg.id]).await?;
+	}
+
+	Ok(messages.len())
+}
+
+/// Returns how many messages were deleted.
+pub async fn purge_messages(
+	redis: &mut RedisPool,
+	worker: &str,
+	ids: Vec<String>,
+) -> Result<usize> {
+	let deleted = redis
+		.xdel::<_, _, usize>(redis_keys::dead_letter(worker), &ids)
+		.await?;
+
+	Ok(deleted)
+}
+
+/// Deletes the worker's dead-letter stream. Returns how many messages were deleted.
+pub async fn purge_all(redis: &mut RedisPool, worker: &str) -> Result<usize> {
+	let key = redis_keys::dead_letter(worker);
+	let len = redis.xlen::<_, usize>(&key).await?;
+
+	redis.del::<_, ()>(&key).await?;
+	redis
+		.srem::<_, _, ()>(redis_keys::dead_letter_workers(), worker)
+		.await?;
+
+	Ok(len)
+}
+
+pub fn print_workers(workers: Vec<DeadLetterWorker>) -> Result<()> {
+	if workers.is_empty() {
+		tivet_term::status::success("No dead-lettered messages found", "");
+		return Ok(());
+	}
+
+	table::workers(workers);
+
+	Ok(())
+}
+
+pub fn print_messages(messages: Vec<DeadLetterMessage>, pretty: bool) -> Result<()> {
+	if messages.is_empty() {
+		tivet_term::status::success("No dead-lettered messages found", "");
+		return Ok(());
+	}
+
+	tivet_term::status::success("Dead-lettered messages", messages.len());
+
+	if pretty {
+		for msg in messages {
+			println!();
+
+			println!("{}", style(&msg.id).bold());
+
+			println!("  {} {}", style("topic").bold(), msg.topic_key);
+			println!("  {} {}", style("group").bold(), msg.group);
+			println!("  {} {}", style("message id").bold(), msg.message_id);
+			println!("  {} {}", style("deliveries").bold(), msg.delivery_count);
+
+			let datetime = Local
+				.timestamp_millis_opt(msg.ts)
+				.single()
+				.context("invalid ts")?;
+			let date = datetime.format("%Y-%m-%d %H:%M:%S");
+			println!(
+				"  {} {}",
+				style("dead-lettered at").bold(),
+				style(date).magenta()
+			);
+
+			if let Some(error) = &msg.error {
+				println!(
+					"  {} {}",
+					style("last error").bold(),
+					&indent_string(&colored_json(error)?, "    ", true)
+				);
+			}
+
+			match chirp::Message::decode(msg.message.as_slice()) {
+				Result::Ok(chirp_msg) => {
+					if let Some(req_id) = chirp_msg.req_id {
+						println!("  {} {}", style("req id").bold(), req_id.as_uuid());
+					}
+					if let Some(ray_id) = chirp_msg.ray_id {
+						println!("  {} {}", style("ray id").bold(), ray_id.as_uuid());
+					}
+					println!(
+						"  {} {}",
+						style("parameters").bold(),
+						chirp_msg.parameters.join(", ")
+					);
+					if let Some(traceparent) = &chirp_msg.traceparent {
+						println!("  {} {}", style("traceparent").bold(), traceparent);
+					}
+
+					println!("  {}", style("trace").bold());
+					for entry in &chirp_msg.trace {
+						println!("    {} {}", entry.context_name, style(entry.ts).dim());
+					}
+
+					println!("  {} {} bytes", style("body").bold(), chirp_msg.body.len());
+				}
+				Err(err) => {
+					println!(
+						"  {} {}",
+						style("message").bold(),
+						style(format!("failed to decode: {err}")).red()
+					);
+				}
+			}
+		}
+	} else {
+		table::messages(messages)?;
+	}
+
+	Ok(())
+}
+
+mod table {
+	use anyhow::*;
+	use chrono::{Local, TimeZone};
+	use tabled::Tabled;
+
+	use super::{DeadLetterMessage, DeadLetterWorker};
+
+	#[derive(Tabled)]
+	struct WorkerTableRow {
+		pub worker: String,
+		pub messages: usize,
+	}
+
+	#[derive(Tabled)]
+	struct MessageTableRow {
+		pub id: String,
+		pub topic: String,
+		pub deliveries: usize,
+		pub dead_lettered_at: String,
+		pub last_error: String,
+	}
+
+	pub fn workers(workers: Vec<DeadLetterWorker>) {
+		let rows = workers
+			.into_iter()
+			.map(|w| WorkerTableRow {
+				worker: w.name,
+				messages: w.len,
+			})
+			.collect::<Vec<_>>();
+
+		tivet_term::format::table(rows);
+	}
+
+	pub fn messages(messages: Vec<DeadLetterMessage>) -> Result<()> {
+		let rows = messages
+			.into_iter()
+			.map(|msg| {
+				let datetime = Local
+					.timestamp_millis_opt(msg.ts)
+					.single()
+					.context("invalid ts")?;
+
+				Ok(MessageTableRow {
+					id: msg.id,
+					topic: msg.topic_key,
+					deliveries: msg.delivery_count,
+					dead_lettered_at: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+					last_error: msg
+						.error
+						.as_ref()
+						.and_then(|x| x.get("error"))
+						.and_then(|x| x.as_str())
+						.unwrap_or_default()
+						.to_string(),
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		tivet_term::format::table(rows);
+
+		Ok(())
+	}
+}
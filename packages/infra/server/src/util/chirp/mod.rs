@@ -0,0 +1,12 @@
+use anyhow::*;
+use tivet_pools::RedisPool;
+
+pub mod dead_letter;
+
+/// Connects to the Redis instance used by Chirp.
+pub async fn redis(config: tivet_config::Config) -> Result<RedisPool> {
+	tivet_pools::db::redis::setup(config)
+		.await?
+		.remove("persistent")
+		.context("missing persistent redis")
+}
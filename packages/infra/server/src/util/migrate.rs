@@ -0,0 +1,101 @@
+use anyhow::*;
+use tivet_migrate::MigrationStatus;
+use tivet_term::console::style;
+
+pub fn print_statuses(statuses: Vec<MigrationStatus>) -> Result<()> {
+	if statuses.is_empty() {
+		tivet_term::status::success("No databases found", "");
+		return Ok(());
+	}
+
+	table::statuses(&statuses);
+
+	for status in &statuses {
+		for migration in &status.edited {
+			println!(
+				"{} {}_{} of {} was edited after being applied",
+				style("error").red().bold(),
+				migration.version,
+				migration.name,
+				status.db_name
+			);
+		}
+	}
+
+	Ok(())
+}
+
+pub fn print_plan(statuses: Vec<MigrationStatus>, sql: bool) {
+	let pending = statuses.iter().map(|x| x.pending.len()).sum::<usize>();
+	if pending == 0 {
+		tivet_term::status::success("No pending migrations", "");
+		return;
+	}
+
+	tivet_term::status::success("Pending migrations", pending);
+
+	for status in statuses {
+		if status.pending.is_empty() {
+			continue;
+		}
+
+		println!();
+		println!("{}", style(status.db_name).bold());
+
+		for migration in status.pending {
+			println!(
+				"  {} {}",
+				style(migration.version).magenta(),
+				migration.name
+			);
+
+			if sql {
+				for line in migration.up.trim().lines() {
+					println!("    {}", style(line).dim());
+				}
+				println!();
+			}
+		}
+	}
+}
+
+mod table {
+	use tabled::Tabled;
+	use tivet_migrate::MigrationStatus;
+	use tivet_term::console::style;
+
+	#[derive(Tabled)]
+	struct StatusTableRow {
+		pub database: String,
+		pub version: String,
+		pub state: String,
+		pub applied: usize,
+		pub pending: usize,
+	}
+
+	pub fn statuses(statuses: &[MigrationStatus]) {
+		let rows = statuses
+			.iter()
+			.map(|status| StatusTableRow {
+				database: status.db_name.to_string(),
+				version: status
+					.version
+					.map(|x| x.version.to_string())
+					.unwrap_or_else(|| "-".to_string()),
+				state: if !status.edited.is_empty() {
+					style("edited").red().to_string()
+				} else if status.version.map_or(false, |x| x.dirty) {
+					style("dirty").red().to_string()
+				} else if !status.pending.is_empty() {
+					style("pending").yellow().to_string()
+				} else {
+					style("up to date").green().to_string()
+				},
+				applied: status.applied.len(),
+				pending: status.pending.len(),
+			})
+			.collect::<Vec<_>>();
+
+		tivet_term::format::table(rows);
+	}
+}
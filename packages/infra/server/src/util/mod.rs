@@ -1,5 +1,7 @@
+pub mod chirp;
 pub mod db;
 pub mod format;
+pub mod migrate;
 pub mod wf;
 
 pub fn now() -> i64 {
@@ -0,0 +1,156 @@
+use chirp_client::redis_keys;
+use clap::Parser;
+use tivet_pools::{
+	prelude::redis::{self, AsyncCommands},
+	RedisPool,
+};
+use tivet_server::{util::chirp::dead_letter, SubCommand};
+use uuid::Uuid;
+
+async fn redis() -> RedisPool {
+	let config = tivet_config::Config::load::<String>(&[]).await.unwrap();
+	tivet_server::util::chirp::redis(config).await.unwrap()
+}
+
+fn parse(args: &[&str]) -> bool {
+	let args = ["tivet", "chirp", "dlq"].iter().chain(args);
+	SubCommand::try_parse_from(args).is_ok()
+}
+
+#[test]
+fn parse_args() {
+	assert!(parse(&["list"]));
+	assert!(parse(&["list", "svc--worker", "-c", "10"]));
+	assert!(parse(&["get", "svc--worker", "1-0", "2-0"]));
+
+	assert!(parse(&["replay", "svc--worker", "1-0"]));
+	assert!(parse(&["replay", "svc--worker", "--all"]));
+	assert!(!parse(&["replay", "svc--worker"]));
+	assert!(!parse(&["replay", "svc--worker", "1-0", "--all"]));
+
+	assert!(parse(&["purge", "svc--worker", "1-0", "2-0"]));
+	assert!(parse(&["purge", "svc--worker", "--all"]));
+	assert!(!parse(&["purge", "svc--worker"]));
+	assert!(!parse(&["purge", "svc--worker", "1-0", "--all"]));
+}
+
+async fn dead_letter_message(redis: &mut RedisPool, worker: &str, topic_key: &str, body: &[u8]) {
+	let message_id = Uuid::new_v4().to_string();
+
+	chirp_worker::dead_letter::record_error(
+		redis,
+		worker,
+		&message_id,
+		"TIVET_ERROR: broken".into(),
+		None,
+	)
+	.await
+	.unwrap();
+	chirp_worker::dead_letter::write(
+		redis,
+		topic_key,
+		worker,
+		&message_id,
+		body,
+		chirp_worker::dead_letter::MAX_DELIVERY_COUNT,
+	)
+	.await
+	.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_replay_purge() {
+	let mut redis = redis().await;
+
+	let worker = format!("dead-letter-test--{}", Uuid::new_v4());
+	let topic_key = redis_keys::message_topic(&format!("dead-letter-test-{}", Uuid::new_v4()));
+	for i in 0..4u8 {
+		dead_letter_message(&mut redis, &worker, &topic_key, &[i]).await;
+	}
+
+	// List
+	let workers = dead_letter::list_workers(&mut redis).await.unwrap();
+	let listed = workers.iter().find(|x| x.name == worker).unwrap();
+	assert_eq!(4, listed.len);
+
+	let messages = dead_letter::list_messages(&mut redis, &worker, None)
+		.await
+		.unwrap();
+	assert_eq!(
+		vec![vec![3], vec![2], vec![1], vec![0]],
+		messages
+			.iter()
+			.map(|x| x.message.clone())
+			.collect::<Vec<_>>(),
+		"should be newest first"
+	);
+	assert!(messages.iter().all(|x| x.topic_key == topic_key
+		&& x.group == worker
+		&& x.delivery_count == chirp_worker::dead_letter::MAX_DELIVERY_COUNT
+		&& x.error.as_ref().unwrap()["error"] == "TIVET_ERROR: broken"));
+
+	let messages = dead_letter::list_messages(&mut redis, &worker, Some(2))
+		.await
+		.unwrap();
+	assert_eq!(2, messages.len());
+
+	// Get
+	let ids = messages.iter().map(|x| x.id.clone()).collect::<Vec<_>>();
+	let fetched = dead_letter::get_messages(&mut redis, &worker, ids.clone())
+		.await
+		.unwrap();
+	assert_eq!(
+		vec![vec![3], vec![2]],
+		fetched.into_iter().map(|x| x.message).collect::<Vec<_>>()
+	);
+	assert!(
+		dead_letter::get_messages(&mut redis, &worker, vec!["0-1".into()])
+			.await
+			.is_err()
+	);
+
+	// Replay publishes to the topic for the worker's group only
+	let replayed = dead_letter::replay_messages(&mut redis, &worker, vec![ids[0].clone()])
+		.await
+		.unwrap();
+	assert_eq!(1, replayed);
+
+	let topic = redis
+		.xrange_all::<_, redis::streams::StreamRangeReply>(&topic_key)
+		.await
+		.unwrap();
+	assert_eq!(1, topic.ids.len());
+	assert_eq!(
+		Some(vec![3]),
+		topic.ids[0].get::<Vec<u8>>(redis_keys::message_topic::MESSAGE)
+	);
+	assert_eq!(
+		Some(worker.clone()),
+		topic.ids[0].get::<String>(redis_keys::message_topic::GROUP)
+	);
+
+	let messages = dead_letter::list_messages(&mut redis, &worker, None)
+		.await
+		.unwrap();
+	assert_eq!(3, messages.len());
+
+	// Purge
+	let purged = dead_letter::purge_messages(&mut redis, &worker, vec![ids[1].clone()])
+		.await
+		.unwrap();
+	assert_eq!(1, purged);
+
+	let purged = dead_letter::purge_all(&mut redis, &worker).await.unwrap();
+	assert_eq!(2, purged);
+	assert!(dead_letter::list_messages(&mut redis, &worker, None)
+		.await
+		.unwrap()
+		.is_empty());
+	assert!(!dead_letter::list_workers(&mut redis)
+		.await
+		.unwrap()
+		.iter()
+		.any(|x| x.name == worker));
+
+	redis.del::<_, ()>(&topic_key).await.unwrap();
+}
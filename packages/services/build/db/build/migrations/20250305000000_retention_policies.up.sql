@@ -0,0 +1,9 @@
+CREATE TABLE retention_policies (
+	env_id UUID PRIMARY KEY,  -- References db-game.game_namespaces
+	keep_last INT,
+	untagged_max_age INT,
+	update_ts INT NOT NULL
+);
+
+CREATE INDEX ON builds (env_id, create_ts DESC);
+CREATE INDEX ON builds (upload_id);
@@ -0,0 +1 @@
+ALTER TABLE builds ADD COLUMN compression_dictionary BOOLEAN NOT NULL DEFAULT false;
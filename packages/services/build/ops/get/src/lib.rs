@@ -13,6 +13,7 @@ struct BuildRow {
 	create_ts: i64,
 	kind: i64,
 	compression: i64,
+	compression_dictionary: bool,
 	tags: Value,
 }
 
@@ -37,6 +38,7 @@ async fn handle(ctx: OperationContext<build::get::Request>) -> GlobalResult<buil
 			create_ts,
 			kind,
 			compression,
+			compression_dictionary,
 			tags
 		FROM
 			db_build.builds
@@ -62,6 +64,7 @@ async fn handle(ctx: OperationContext<build::get::Request>) -> GlobalResult<buil
 			create_ts: build.create_ts,
 			kind: build.kind as i32,
 			compression: build.compression as i32,
+			compression_dictionary: build.compression_dictionary,
 			tags: serde_json::from_value(
 				tags.into_iter()
 					.filter(|(_, v)| !matches!(v, Value::Null))
@@ -3,6 +3,7 @@ use tivet_operation::prelude::proto::backend;
 
 const MAX_UPLOAD_SIZE: u64 = util::file_size::gigabytes(8);
 const MAX_JS_BUILD_UPLOAD_SIZE: u64 = util::file_size::megabytes(10);
+const MAX_COMPRESSION_DICTIONARY_SIZE: u64 = util::file_size::megabytes(10);
 use crate::{
 	types::{upload::PrepareFile, upload::PresignedUploadRequest, BuildCompression, BuildKind},
 	utils,
@@ -28,6 +29,10 @@ pub enum Content {
 	New {
 		image_file: PrepareFile,
 		image_tag: String,
+		/// Only valid with `BuildCompression::Zstd`.
+		compression_dictionary_file: Option<PrepareFile>,
+		/// Hex encoded SHA-256 digest of the image file.
+		image_digest: Option<String>,
 	},
 	Default {
 		build_kind: String,
@@ -72,7 +77,7 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 		}
 	};
 
-	let (image_tag, upload_id, presigned_requests) = match &input.content {
+	let (image_tag, upload_id, presigned_requests, compression_dictionary) = match &input.content {
 		Content::Default { build_kind } => {
 			let default_build_row = sql_fetch_optional!(
 				[ctx, (String, Uuid)]
@@ -84,11 +89,13 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 			let (image_tag, upload_id) =
 				unwrap!(default_build_row, "default build missing: {build_kind}");
 
-			(image_tag, upload_id, Vec::new())
+			(image_tag, upload_id, Vec::new(), false)
 		}
 		Content::New {
 			image_file,
 			image_tag,
+			compression_dictionary_file,
+			image_digest,
 		} => {
 			let tag_split = image_tag.split_once(':');
 			let (tag_base, tag) = unwrap_ref!(tag_split, "missing separator in image tag");
@@ -107,6 +114,28 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 				UPLOAD_TOO_LARGE
 			);
 
+			if let Some(image_digest) = image_digest {
+				ensure_with!(
+					image_digest.len() == 64
+						&& image_digest
+							.chars()
+							.all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+					API_BAD_BODY,
+					error = "`image_digest` must be a lowercase hex encoded SHA-256 digest"
+				);
+			}
+
+			if let Some(compression_dictionary_file) = compression_dictionary_file {
+				ensure!(
+					input.compression == BuildCompression::Zstd,
+					"compression dictionary requires zstd compression"
+				);
+				ensure_with!(
+					compression_dictionary_file.content_length < MAX_COMPRESSION_DICTIONARY_SIZE,
+					UPLOAD_TOO_LARGE
+				);
+			}
+
 			// Check if build is unique
 			let (build_exists,) = sql_fetch_one!(
 				[ctx, (bool,)]
@@ -124,13 +153,19 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 			let file_name = utils::file_name(input.kind, input.compression);
 			let upload_prepare_res = op!([ctx] upload_prepare {
 				bucket: "bucket-build".into(),
-				files: vec![
+				files: std::iter::once(backend::upload::PrepareFile {
+					path: file_name,
+					content_length: image_file.content_length,
+					..Default::default()
+				})
+				.chain(compression_dictionary_file.iter().map(|file| {
 					backend::upload::PrepareFile {
-						path: file_name,
-						content_length: image_file.content_length,
+						path: utils::COMPRESSION_DICTIONARY_FILE_NAME.to_string(),
+						content_length: file.content_length,
 						..Default::default()
-					},
-				],
+					}
+				}))
+				.collect(),
 			})
 			.await?;
 			let upload_id = unwrap_ref!(upload_prepare_res.upload_id).as_uuid();
@@ -139,10 +174,16 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 				image_tag.clone(),
 				upload_id,
 				upload_prepare_res.presigned_requests.clone(),
+				compression_dictionary_file.is_some(),
 			)
 		}
 	};
 
+	let image_digest = match &input.content {
+		Content::New { image_digest, .. } => image_digest.clone(),
+		Content::Default { .. } => None,
+	};
+
 	// Create build
 	let build_id = Uuid::new_v4();
 	sql_execute!(
@@ -158,10 +199,12 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 				image_tag,
 				create_ts,
 				kind,
-				compression
+				compression,
+				compression_dictionary,
+				image_digest
 			)
 		VALUES
-			($1, $2, $3, $4, $5, $6, $7, $8, $9)
+			($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
 		",
 		build_id,
 		game_id,
@@ -172,6 +215,8 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 		ctx.ts(),
 		input.kind as i32,
 		input.compression as i32,
+		compression_dictionary,
+		image_digest,
 	)
 	.await?;
 
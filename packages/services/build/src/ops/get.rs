@@ -24,6 +24,8 @@ pub(crate) struct BuildRow {
 	create_ts: i64,
 	kind: i64,
 	compression: i64,
+	compression_dictionary: bool,
+	image_digest: Option<String>,
 	tags: sqlx::types::Json<Box<serde_json::value::RawValue>>,
 }
 
@@ -43,6 +45,8 @@ impl TryInto<types::Build> for BuildRow {
 			compression: unwrap!(types::BuildCompression::from_repr(
 				self.compression.try_into()?
 			)),
+			compression_dictionary: self.compression_dictionary,
+			image_digest: self.image_digest,
 			// Filter out null values on tags
 			tags: serde_json::from_str::<HashMap<String, Option<String>>>(self.tags.0.get())?
 				.into_iter()
@@ -67,6 +71,8 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 			create_ts,
 			kind,
 			compression,
+			compression_dictionary,
+			image_digest,
 			tags
 		FROM db_build.builds
 		WHERE build_id = ANY($1)
@@ -2,3 +2,4 @@ pub mod create;
 pub mod get;
 pub mod patch_tags;
 pub mod resolve_for_tags;
+pub mod retention;
@@ -31,6 +31,8 @@ pub async fn get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
 			create_ts,
 			kind,
 			compression,
+			compression_dictionary,
+			image_digest,
 			tags
 		FROM db_build.builds
 		WHERE env_id = $1 AND tags @> $2
@@ -0,0 +1,58 @@
+use std::convert::TryInto;
+
+use chirp_workflow::prelude::*;
+
+use crate::types;
+
+#[derive(Debug)]
+pub struct Input {
+	pub env_ids: Vec<Uuid>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub policies: Vec<Policy>,
+}
+
+#[derive(Debug)]
+pub struct Policy {
+	pub env_id: Uuid,
+	pub policy: types::RetentionPolicy,
+	pub update_ts: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct PolicyRow {
+	env_id: Uuid,
+	keep_last: Option<i64>,
+	untagged_max_age: Option<i64>,
+	update_ts: i64,
+}
+
+#[operation]
+pub async fn build_retention_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let policies = sql_fetch_all!(
+		[ctx, PolicyRow]
+		"
+		SELECT env_id, keep_last, untagged_max_age, update_ts
+		FROM db_build.retention_policies
+		WHERE env_id = ANY($1)
+		",
+		&input.env_ids,
+	)
+	.await?
+	.into_iter()
+	.map(|row| {
+		Ok(Policy {
+			env_id: row.env_id,
+			policy: types::RetentionPolicy {
+				keep_last: row.keep_last.map(TryInto::try_into).transpose()?,
+				untagged_max_age: row.untagged_max_age,
+			},
+			update_ts: row.update_ts,
+		})
+	})
+	.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(Output { policies })
+}
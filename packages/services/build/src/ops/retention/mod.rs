@@ -0,0 +1,3 @@
+pub mod get;
+pub mod plan;
+pub mod set;
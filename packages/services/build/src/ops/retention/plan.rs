@@ -0,0 +1,155 @@
+use std::{
+	collections::{HashMap, HashSet},
+	convert::TryInto,
+};
+
+use chirp_workflow::prelude::*;
+
+use super::super::get::BuildRow;
+use crate::types;
+
+/// Tag that groups builds for `keep_last`.
+const NAME_TAG: &str = "name";
+/// Builds with this tag are never collected.
+const CURRENT_TAG: &str = "current";
+
+#[derive(Debug)]
+pub struct Input {
+	pub env_id: Uuid,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// `None` if the environment has no retention policy. Nothing will be collected.
+	pub policy: Option<types::RetentionPolicy>,
+	pub collect: Vec<Collect>,
+	pub kept_count: usize,
+}
+
+#[derive(Debug)]
+pub struct Collect {
+	pub build: types::Build,
+	pub reason: types::RetentionReason,
+}
+
+/// Determines which builds in an environment would be collected by the build GC.
+#[operation]
+pub async fn build_retention_plan(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let policies_res = ctx
+		.op(super::get::Input {
+			env_ids: vec![input.env_id],
+		})
+		.await?;
+	let Some(policy) = policies_res.policies.into_iter().next().map(|x| x.policy) else {
+		return Ok(Output {
+			policy: None,
+			collect: Vec::new(),
+			kept_count: 0,
+		});
+	};
+
+	let builds = sql_fetch_all!(
+		[ctx, BuildRow]
+		"
+		SELECT
+			build_id,
+			game_id,
+			env_id,
+			upload_id,
+			display_name,
+			image_tag,
+			create_ts,
+			kind,
+			compression,
+			compression_dictionary,
+			image_digest,
+			tags
+		FROM db_build.builds
+		WHERE env_id = $1
+		ORDER BY create_ts DESC
+		",
+		input.env_id,
+	)
+	.await?
+	.into_iter()
+	.map(|build| build.try_into())
+	.collect::<GlobalResult<Vec<types::Build>>>()?;
+
+	// Builds used by actors that haven't been destroyed yet or by replica sets that will create more
+	// actors from them
+	let running_image_ids = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		SELECT image_id
+		FROM db_ds.servers
+		WHERE env_id = $1 AND destroy_ts IS NULL
+		UNION
+		SELECT image_id
+		FROM db_ds.replica_sets
+		WHERE env_id = $1 AND delete_ts IS NULL
+		",
+		input.env_id,
+	)
+	.await?
+	.into_iter()
+	.map(|(image_id,)| image_id)
+	.collect::<HashSet<_>>();
+
+	let collect = plan(&policy, builds.iter(), &running_image_ids, ctx.ts());
+	let kept_count = builds.len() - collect.len();
+	let collect = builds
+		.into_iter()
+		.filter_map(|build| {
+			collect.get(&build.build_id).map(|reason| Collect {
+				reason: *reason,
+				build,
+			})
+		})
+		.collect();
+
+	Ok(Output {
+		policy: Some(policy),
+		collect,
+		kept_count,
+	})
+}
+
+/// Returns the builds to collect and why. Expects builds to be ordered newest first.
+pub fn plan<'a>(
+	policy: &types::RetentionPolicy,
+	builds: impl Iterator<Item = &'a types::Build>,
+	running_image_ids: &HashSet<Uuid>,
+	now: i64,
+) -> HashMap<Uuid, types::RetentionReason> {
+	let mut collect = HashMap::new();
+	let mut name_counts = HashMap::<&str, u32>::new();
+
+	for build in builds {
+		// Count protected builds towards `keep_last` so they take up a slot
+		let reason = if let Some(name) = build.tags.get(NAME_TAG) {
+			let count = name_counts.entry(name.as_str()).or_default();
+			*count += 1;
+
+			policy
+				.keep_last
+				.filter(|keep_last| *count > *keep_last)
+				.map(|_| types::RetentionReason::ExceedsKeepLast)
+		} else if build.tags.is_empty() {
+			policy
+				.untagged_max_age
+				.filter(|max_age| build.create_ts < now - *max_age)
+				.map(|_| types::RetentionReason::UntaggedExpired)
+		} else {
+			None
+		};
+
+		let protected =
+			build.tags.contains_key(CURRENT_TAG) || running_image_ids.contains(&build.build_id);
+
+		if let (Some(reason), false) = (reason, protected) {
+			collect.insert(build.build_id, reason);
+		}
+	}
+
+	collect
+}
@@ -0,0 +1,60 @@
+use chirp_workflow::prelude::*;
+
+use crate::types;
+
+#[derive(Debug)]
+pub struct Input {
+	pub env_id: Uuid,
+	/// Setting `None` removes the policy, disabling garbage collection for this environment.
+	pub policy: Option<types::RetentionPolicy>,
+}
+
+#[derive(Debug)]
+pub struct Output {}
+
+#[operation]
+pub async fn build_retention_set(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let Some(policy) = &input.policy else {
+		sql_execute!(
+			[ctx]
+			"
+			DELETE FROM db_build.retention_policies
+			WHERE env_id = $1
+			",
+			input.env_id,
+		)
+		.await?;
+
+		return Ok(Output {});
+	};
+
+	if let Some(keep_last) = policy.keep_last {
+		ensure_with!(
+			keep_last > 0,
+			API_BAD_BODY,
+			error = "`keep_last` must be greater than 0"
+		);
+	}
+	if let Some(untagged_max_age) = policy.untagged_max_age {
+		ensure_with!(
+			untagged_max_age > 0,
+			API_BAD_BODY,
+			error = "`untagged_max_age` must be greater than 0"
+		);
+	}
+
+	sql_execute!(
+		[ctx]
+		"
+		UPSERT INTO db_build.retention_policies (env_id, keep_last, untagged_max_age, update_ts)
+		VALUES ($1, $2, $3, $4)
+		",
+		input.env_id,
+		policy.keep_last.map(i64::from),
+		policy.untagged_max_age,
+		ctx.ts(),
+	)
+	.await?;
+
+	Ok(Output {})
+}
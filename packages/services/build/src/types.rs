@@ -25,6 +25,7 @@ impl ApiFrom<models::ActorBuildKind> for BuildKind {
 pub enum BuildCompression {
 	None = 0,
 	Lz4 = 1,
+	Zstd = 2,
 }
 
 impl ApiFrom<models::ActorBuildCompression> for BuildCompression {
@@ -32,6 +33,7 @@ impl ApiFrom<models::ActorBuildCompression> for BuildCompression {
 		match value {
 			models::ActorBuildCompression::None => BuildCompression::None,
 			models::ActorBuildCompression::Lz4 => BuildCompression::Lz4,
+			models::ActorBuildCompression::Zstd => BuildCompression::Zstd,
 		}
 	}
 }
@@ -47,9 +49,32 @@ pub struct Build {
 	pub create_ts: i64,
 	pub kind: BuildKind,
 	pub compression: BuildCompression,
+	/// Whether the build was compressed with a Zstd dictionary. The dictionary is uploaded next to
+	/// the build tar, see `utils::COMPRESSION_DICTIONARY_FILE_NAME`.
+	pub compression_dictionary: bool,
+	/// Hex encoded SHA-256 digest of the uploaded image file. Not set for builds uploaded by older
+	/// clients.
+	pub image_digest: Option<String>,
 	pub tags: HashMap<String, String>,
 }
 
+/// Per-environment rules for which builds get garbage collected.
+#[derive(Clone, Debug, Default)]
+pub struct RetentionPolicy {
+	/// Number of builds to keep for each value of the `name` tag.
+	pub keep_last: Option<u32>,
+	/// Max age (in ms) of builds without any tags.
+	pub untagged_max_age: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RetentionReason {
+	/// The build is older than the last N builds with the same `name` tag.
+	ExceedsKeepLast,
+	/// The build has no tags and is older than the max age.
+	UntaggedExpired,
+}
+
 // TODO: Move to upload pkg when its converted to new ops
 pub mod upload {
 	use std::convert::TryInto;
@@ -4,6 +4,9 @@ use chirp_workflow::prelude::*;
 
 use crate::types::{BuildCompression, BuildKind};
 
+/// Name of the file in the build's upload that holds the Zstd dictionary, if any.
+pub const COMPRESSION_DICTIONARY_FILE_NAME: &str = "compression.dict";
+
 /// Generates the file name that holds the build tar.
 pub fn file_name(kind: BuildKind, compression: BuildCompression) -> String {
 	let file_name = match kind {
@@ -15,10 +18,16 @@ pub fn file_name(kind: BuildKind, compression: BuildCompression) -> String {
 	let file_ext_compression = match compression {
 		BuildCompression::None => "",
 		BuildCompression::Lz4 => ".lz4",
+		BuildCompression::Zstd => ".zst",
 	};
 	format!("{file_name}.{file_ext}{file_ext_compression}")
 }
 
+/// Path of a file in a build's upload relative to an ATS server, which caches it from S3.
+pub fn artifact_url_stub(namespace: &str, upload_id: Uuid, file_name: &str) -> String {
+	format!("/s3-cache/{namespace}-bucket-build/{upload_id}/{file_name}")
+}
+
 pub fn build_hash(build_id: Uuid) -> u64 {
 	// Hash build so that the ATS server that we download the build from is always the same one. This
 	// improves cache hit rates and reduces download times.
@@ -0,0 +1,231 @@
+use std::{convert::TryInto, net::IpAddr, time::Duration};
+
+use chirp_workflow::prelude::*;
+use cluster::types::PoolType;
+use futures_util::StreamExt;
+use tivet_operation::prelude::proto::backend::pkg::*;
+
+/// How many ATS servers to purge cached artifacts from in parallel.
+const PURGE_PARALLELISM: usize = 16;
+
+pub async fn start(config: tivet_config::Config, pools: tivet_pools::Pools) -> GlobalResult<()> {
+	let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+	loop {
+		interval.tick().await;
+
+		// Keep collecting on the next tick, this is the only instance of the GC
+		if let Err(err) = run_from_env(config.clone(), pools.clone()).await {
+			tracing::error!(?err, "failed to run build gc");
+		}
+	}
+}
+
+#[derive(sqlx::FromRow)]
+struct DeletedBuildRow {
+	build_id: Uuid,
+	upload_id: Uuid,
+	kind: i64,
+	compression: i64,
+	compression_dictionary: bool,
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn run_from_env(
+	config: tivet_config::Config,
+	pools: tivet_pools::Pools,
+) -> GlobalResult<()> {
+	let client = chirp_client::SharedClient::from_env(pools.clone())?.wrap_new("build-gc");
+	let cache = tivet_cache::CacheInner::from_env(pools.clone())?;
+	let ctx = StandaloneCtx::new(
+		chirp_workflow::compat::db_from_pools(&pools).await?,
+		config,
+		tivet_connection::Connection::new(client, pools, cache),
+		"build-gc",
+	)
+	.await?;
+
+	let env_ids = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		SELECT env_id
+		FROM db_build.retention_policies
+		",
+	)
+	.await?
+	.into_iter()
+	.map(|(env_id,)| env_id)
+	.collect::<Vec<_>>();
+
+	for env_id in env_ids {
+		// Don't let a single environment block collection for all others
+		if let Err(err) = collect_env(&ctx, env_id).await {
+			tracing::error!(?err, ?env_id, "failed to collect builds");
+		}
+	}
+
+	Ok(())
+}
+
+#[tracing::instrument(skip(ctx))]
+async fn collect_env(ctx: &StandaloneCtx, env_id: Uuid) -> GlobalResult<()> {
+	let plan_res = ctx
+		.op(build::ops::retention::plan::Input { env_id })
+		.await?;
+	if plan_res.collect.is_empty() {
+		return Ok(());
+	}
+
+	// Re-check that the builds are still unused in case an actor or replica set was created or the
+	// `current` tag was moved since the plan was made
+	let deleted_builds = sql_fetch_all!(
+		[ctx, DeletedBuildRow]
+		"
+		DELETE FROM db_build.builds AS b
+		WHERE
+			build_id = ANY($1) AND
+			NOT b.tags ? 'current' AND
+			NOT EXISTS (
+				SELECT 1
+				FROM db_ds.servers AS s
+				WHERE s.image_id = b.build_id AND s.destroy_ts IS NULL
+			) AND
+			NOT EXISTS (
+				SELECT 1
+				FROM db_ds.replica_sets AS r
+				WHERE r.image_id = b.build_id AND r.delete_ts IS NULL
+			)
+		RETURNING build_id, upload_id, kind, compression, compression_dictionary
+		",
+		plan_res
+			.collect
+			.iter()
+			.map(|x| x.build.build_id)
+			.collect::<Vec<_>>(),
+	)
+	.await?;
+	if deleted_builds.is_empty() {
+		return Ok(());
+	}
+
+	tracing::info!(count = deleted_builds.len(), "deleted builds");
+
+	// Uploads can be shared between builds (i.e. default builds), only delete the ones that are no
+	// longer referenced
+	let orphaned_upload_ids = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		SELECT DISTINCT u.upload_id
+		FROM UNNEST($1::UUID[]) AS u (upload_id)
+		WHERE
+			NOT EXISTS (
+				SELECT 1
+				FROM db_build.builds AS b
+				WHERE b.upload_id = u.upload_id
+			) AND
+			NOT EXISTS (
+				SELECT 1
+				FROM db_build.default_builds AS d
+				WHERE d.upload_id = u.upload_id
+			)
+		",
+		deleted_builds
+			.iter()
+			.map(|build| build.upload_id)
+			.collect::<Vec<_>>(),
+	)
+	.await?
+	.into_iter()
+	.map(|(upload_id,)| upload_id)
+	.collect::<Vec<_>>();
+	if orphaned_upload_ids.is_empty() {
+		return Ok(());
+	}
+
+	let namespace = &ctx.config().server()?.tivet.namespace;
+	let mut artifact_paths = Vec::new();
+	for build in deleted_builds
+		.iter()
+		.filter(|build| orphaned_upload_ids.contains(&build.upload_id))
+	{
+		let kind = unwrap!(build::types::BuildKind::from_repr(build.kind.try_into()?));
+		let compression = unwrap!(build::types::BuildCompression::from_repr(
+			build.compression.try_into()?
+		));
+
+		artifact_paths.push(build::utils::artifact_url_stub(
+			namespace,
+			build.upload_id,
+			&build::utils::file_name(kind, compression),
+		));
+		if build.compression_dictionary {
+			artifact_paths.push(build::utils::artifact_url_stub(
+				namespace,
+				build.upload_id,
+				build::utils::COMPRESSION_DICTIONARY_FILE_NAME,
+			));
+		}
+	}
+
+	purge_artifacts(ctx, &artifact_paths).await?;
+
+	tracing::info!(count = orphaned_upload_ids.len(), "deleting uploads");
+
+	let request_id = Uuid::new_v4();
+	msg!([ctx] upload::msg::delete(request_id) {
+		request_id: Some(request_id.into()),
+		upload_ids: orphaned_upload_ids
+			.into_iter()
+			.map(Into::into)
+			.collect(),
+	})
+	.await?;
+
+	Ok(())
+}
+
+/// Purges prewarmed build artifacts from the ATS caches. This is best effort, artifacts that fail to
+/// purge will eventually be evicted by ATS.
+async fn purge_artifacts(ctx: &StandaloneCtx, artifact_paths: &[String]) -> GlobalResult<()> {
+	let vlan_ips = sql_fetch_all!(
+		[ctx, (IpAddr,)]
+		"
+		SELECT vlan_ip
+		FROM db_cluster.servers
+		WHERE
+			pool_type = $1 AND
+			vlan_ip IS NOT NULL AND
+			drain_ts IS NULL AND
+			cloud_destroy_ts IS NULL
+		",
+		PoolType::Ats as i32,
+	)
+	.await?;
+
+	let client = reqwest::Client::builder()
+		.timeout(Duration::from_secs(5))
+		.build()?;
+	let purge_method = unwrap!(reqwest::Method::from_bytes(b"PURGE").ok());
+
+	futures_util::stream::iter(vlan_ips.iter().flat_map(|(vlan_ip,)| {
+		artifact_paths
+			.iter()
+			.map(move |path| format!("http://{vlan_ip}:8080{path}"))
+	}))
+	.for_each_concurrent(PURGE_PARALLELISM, |url| {
+		let client = client.clone();
+		let purge_method = purge_method.clone();
+
+		async move {
+			match client.request(purge_method, &url).send().await {
+				Ok(res)
+					if res.status().is_success()
+						|| res.status() == reqwest::StatusCode::NOT_FOUND => {}
+				Ok(res) => tracing::warn!(%url, status=?res.status(), "failed to purge artifact"),
+				Err(err) => tracing::warn!(%url, ?err, "failed to purge artifact"),
+			}
+		}
+	})
+	.await;
+
+	Ok(())
+}
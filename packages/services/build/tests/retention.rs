@@ -0,0 +1,82 @@
+use chirp_workflow::prelude::*;
+use std::collections::HashMap;
+use tivet_operation::prelude::proto::backend;
+
+async fn create_build(ctx: &TestCtx, env_id: Uuid, tags: &[(&str, &str)]) -> Uuid {
+	let build_res = op!([ctx] faker_build {
+		env_id: Some(env_id.into()),
+		image: backend::faker::Image::MmLobbyAutoReady as i32,
+	})
+	.await
+	.unwrap();
+	let build_id = build_res.build_id.as_ref().unwrap().as_uuid();
+
+	ctx.op(build::ops::patch_tags::Input {
+		build_id,
+		tags: tags
+			.iter()
+			.map(|(k, v)| (k.to_string(), Some(v.to_string())))
+			.collect::<HashMap<_, _>>(),
+		exclusive_tags: None,
+	})
+	.await
+	.unwrap();
+
+	build_id
+}
+
+#[workflow_test]
+async fn retention_plan_keep_last(ctx: TestCtx) {
+	let env_id = Uuid::new_v4();
+
+	let old_build_id = create_build(&ctx, env_id, &[("name", "foo")]).await;
+	let current_build_id =
+		create_build(&ctx, env_id, &[("name", "foo"), ("current", "true")]).await;
+	let new_build_id = create_build(&ctx, env_id, &[("name", "foo")]).await;
+	let other_build_id = create_build(&ctx, env_id, &[("name", "bar")]).await;
+
+	ctx.op(build::ops::retention::set::Input {
+		env_id,
+		policy: Some(build::types::RetentionPolicy {
+			keep_last: Some(1),
+			untagged_max_age: None,
+		}),
+	})
+	.await
+	.unwrap();
+
+	let res = ctx
+		.op(build::ops::retention::plan::Input { env_id })
+		.await
+		.unwrap();
+
+	let collect = res
+		.collect
+		.iter()
+		.map(|x| x.build.build_id)
+		.collect::<Vec<_>>();
+	assert_eq!(vec![old_build_id], collect);
+	assert_eq!(
+		build::types::RetentionReason::ExceedsKeepLast,
+		res.collect[0].reason
+	);
+	assert_eq!(3, res.kept_count);
+	assert!(!collect.contains(&current_build_id));
+	assert!(!collect.contains(&new_build_id));
+	assert!(!collect.contains(&other_build_id));
+}
+
+#[workflow_test]
+async fn retention_plan_no_policy(ctx: TestCtx) {
+	let env_id = Uuid::new_v4();
+
+	create_build(&ctx, env_id, &[]).await;
+
+	let res = ctx
+		.op(build::ops::retention::plan::Input { env_id })
+		.await
+		.unwrap();
+
+	assert!(res.policy.is_none());
+	assert!(res.collect.is_empty());
+}
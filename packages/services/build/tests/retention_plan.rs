@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet};
+
+use build::{
+	ops::retention::plan::plan,
+	types::{Build, BuildCompression, BuildKind, RetentionPolicy, RetentionReason},
+};
+use chirp_workflow::prelude::*;
+
+const NOW: i64 = util::duration::days(100);
+
+fn build(age_days: i64, tags: &[(&str, &str)]) -> Build {
+	Build {
+		build_id: Uuid::new_v4(),
+		game_id: None,
+		env_id: None,
+		upload_id: Uuid::new_v4(),
+		display_name: "build".to_string(),
+		image_tag: "image:tag".to_string(),
+		create_ts: NOW - util::duration::days(age_days),
+		kind: BuildKind::DockerImage,
+		compression: BuildCompression::None,
+		compression_dictionary: false,
+		image_digest: None,
+		tags: tags
+			.iter()
+			.map(|(k, v)| (k.to_string(), v.to_string()))
+			.collect(),
+	}
+}
+
+/// Builds must be ordered newest first.
+fn run(
+	policy: RetentionPolicy,
+	builds: &[Build],
+	running: &[Uuid],
+) -> HashMap<Uuid, RetentionReason> {
+	plan(
+		&policy,
+		builds.iter(),
+		&running.iter().cloned().collect::<HashSet<_>>(),
+		NOW,
+	)
+}
+
+#[test]
+fn empty_policy() {
+	let builds = [build(0, &[("name", "a")]), build(50, &[])];
+	assert!(run(RetentionPolicy::default(), &builds, &[]).is_empty());
+}
+
+#[test]
+fn keep_last_per_name() {
+	let builds = [
+		build(0, &[("name", "a")]),
+		build(1, &[("name", "b")]),
+		build(2, &[("name", "a")]),
+		build(3, &[("name", "a")]),
+		build(4, &[("name", "b")]),
+	];
+	let policy = RetentionPolicy {
+		keep_last: Some(1),
+		untagged_max_age: None,
+	};
+
+	let collect = run(policy, &builds, &[]);
+	assert_eq!(
+		HashMap::from([
+			(builds[2].build_id, RetentionReason::ExceedsKeepLast),
+			(builds[3].build_id, RetentionReason::ExceedsKeepLast),
+			(builds[4].build_id, RetentionReason::ExceedsKeepLast),
+		]),
+		collect
+	);
+}
+
+#[test]
+fn protected_builds_take_a_slot() {
+	let builds = [
+		build(0, &[("name", "a"), ("current", "true")]),
+		build(1, &[("name", "a")]),
+		build(2, &[("name", "a")]),
+		build(3, &[("name", "a")]),
+	];
+	let policy = RetentionPolicy {
+		keep_last: Some(2),
+		untagged_max_age: None,
+	};
+
+	// The running build is kept even though it exceeds `keep_last`
+	let collect = run(policy, &builds, &[builds[2].build_id]);
+	assert_eq!(
+		HashMap::from([(builds[3].build_id, RetentionReason::ExceedsKeepLast)]),
+		collect
+	);
+}
+
+#[test]
+fn untagged_max_age() {
+	let builds = [
+		build(1, &[]),
+		build(10, &[]),
+		build(11, &[]),
+		// Tagged builds without a name are never collected
+		build(50, &[("version", "1")]),
+		build(60, &[("current", "true")]),
+	];
+	let policy = RetentionPolicy {
+		keep_last: Some(1),
+		untagged_max_age: Some(util::duration::days(10)),
+	};
+
+	let collect = run(policy, &builds, &[]);
+	assert_eq!(
+		HashMap::from([(builds[2].build_id, RetentionReason::UntaggedExpired)]),
+		collect
+	);
+
+	// Running builds are kept
+	let collect = run(
+		RetentionPolicy {
+			keep_last: None,
+			untagged_max_age: Some(util::duration::days(10)),
+		},
+		&builds,
+		&[builds[2].build_id],
+	);
+	assert!(collect.is_empty());
+}
@@ -17,6 +17,7 @@ pub fn file_name(
 	let file_ext_compression = match compression {
 		backend::build::BuildCompression::None => "",
 		backend::build::BuildCompression::Lz4 => ".lz4",
+		backend::build::BuildCompression::Zstd => ".zst",
 	};
 	format!("{file_name}.{file_ext}{file_ext_compression}")
 }
@@ -0,0 +1,14 @@
+CREATE TABLE game_namespace_service_tokens (
+    namespace_id UUID NOT NULL REFERENCES game_namespaces (namespace_id),
+    jti UUID NOT NULL,  -- References db-tokens.tokens
+    token_session_id UUID NOT NULL,  -- References db-tokens.sessions
+    name STRING NOT NULL,
+    scopes INT[] NOT NULL,  -- tivet.claims.Entitlement.EnvService.Scope[]
+    actor_tags JSONB NOT NULL,
+    datacenter_ids UUID[] NOT NULL,
+    create_ts INT NOT NULL,
+    expire_ts INT NOT NULL,
+    revoke_ts INT,
+    PRIMARY KEY (namespace_id, jti),
+    INDEX (namespace_id, create_ts DESC)
+);
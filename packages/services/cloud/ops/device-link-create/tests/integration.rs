@@ -4,7 +4,7 @@ use tivet_claims::ClaimsDecode;
 #[worker_test]
 async fn basic(ctx: TestCtx) {
 	let link_res = op!([ctx] cloud_device_link_create {}).await.unwrap();
-	let claims = tivet_claims::decode(&ctx.config().server()?.jwt.public, &link_res.token)
+	let claims = tivet_claims::decode(&ctx.config().server()?.jwt, &link_res.token)
 		.unwrap()
 		.unwrap();
 	tracing::info!(?claims, "claims");
@@ -0,0 +1,100 @@
+use proto::backend::pkg::*;
+use tivet_operation::prelude::*;
+
+const MAX_TTL: i64 = util::duration::days(365 * 15);
+
+#[operation(name = "cloud-namespace-token-service-create")]
+async fn handle(
+	ctx: OperationContext<cloud::namespace_token_service_create::Request>,
+) -> GlobalResult<cloud::namespace_token_service_create::Response> {
+	let namespace_id = unwrap_ref!(ctx.namespace_id).as_uuid();
+
+	ensure_with!(
+		!ctx.name.is_empty() && ctx.name.len() <= 64,
+		API_BAD_BODY,
+		error = "`name` must be between 1 and 64 bytes"
+	);
+	ensure_with!(
+		!ctx.scopes.is_empty(),
+		API_BAD_BODY,
+		error = "`scopes` cannot be empty"
+	);
+	ensure_with!(
+		ctx.ttl > 0 && ctx.ttl <= MAX_TTL,
+		API_BAD_BODY,
+		error = "`ttl` must be positive and at most 15 years"
+	);
+
+	let ns_res = op!([ctx] game_namespace_get {
+		namespace_ids: vec![namespace_id.into()],
+	})
+	.await?;
+	let ns_data = ns_res.namespaces.first();
+	let ns_data = unwrap_ref!(ns_data, "namespace not found");
+
+	let token_res = op!([ctx] token_create {
+		issuer: Self::NAME.into(),
+		token_config: Some(token::create::request::TokenConfig {
+			ttl: ctx.ttl,
+		}),
+		refresh_token_config: None,
+		client: None,
+		kind: Some(token::create::request::Kind::New(token::create::request::KindNew {
+			entitlements: vec![
+				proto::claims::Entitlement {
+					kind: Some(
+						proto::claims::entitlement::Kind::EnvService(proto::claims::entitlement::EnvService {
+							env_id: Some(namespace_id.into()),
+							scopes: ctx.scopes.clone(),
+							actor_tags: ctx.actor_tags.clone(),
+							datacenter_ids: ctx.datacenter_ids.clone(),
+						})
+					)
+				}
+			],
+		})),
+		label: Some(format!("env_svc_{}", ns_data.name_id.replace('-', "_"))),
+		..Default::default()
+	})
+	.await?;
+
+	let token = unwrap_ref!(token_res.token);
+	let jti = unwrap_ref!(token.jti).as_uuid();
+	let token_session_id = unwrap_ref!(token_res.session_id).as_uuid();
+
+	sql_execute!(
+		[ctx]
+		"
+		INSERT INTO db_cloud.game_namespace_service_tokens (
+			namespace_id,
+			jti,
+			token_session_id,
+			name,
+			scopes,
+			actor_tags,
+			datacenter_ids,
+			create_ts,
+			expire_ts
+		)
+		VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+		",
+		namespace_id,
+		jti,
+		token_session_id,
+		&ctx.name,
+		ctx.scopes.iter().map(|x| *x as i64).collect::<Vec<_>>(),
+		serde_json::to_string(&ctx.actor_tags)?,
+		ctx.datacenter_ids
+			.iter()
+			.map(common::Uuid::as_uuid)
+			.collect::<Vec<_>>(),
+		ctx.ts(),
+		ctx.ts() + ctx.ttl,
+	)
+	.await?;
+
+	Ok(cloud::namespace_token_service_create::Response {
+		token: token.token.clone(),
+		jti: Some(jti.into()),
+	})
+}
@@ -0,0 +1,52 @@
+use chirp_worker::prelude::*;
+use tivet_claims::ClaimsDecode;
+
+#[worker_test]
+async fn empty(ctx: TestCtx) {
+	let game_res = op!([ctx] faker_game {
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	let namespace_id = game_res.namespace_ids.first().unwrap();
+
+	let res = op!([ctx] cloud_namespace_token_service_create {
+		namespace_id: Some(*namespace_id),
+		name: "ci".into(),
+		scopes: vec![proto::claims::entitlement::env_service::Scope::BuildsWrite as i32],
+		ttl: util::duration::days(1),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+
+	let claims = tivet_claims::decode(&ctx.config().server().unwrap().jwt, &res.token)
+		.unwrap()
+		.unwrap();
+	let ent = claims.as_env_service().unwrap();
+	assert_eq!(namespace_id.as_uuid(), ent.env_id);
+	assert_eq!(
+		vec![tivet_claims::ent::EnvServiceScope::BuildsWrite],
+		ent.scopes
+	);
+}
+
+#[worker_test]
+async fn empty_scopes(ctx: TestCtx) {
+	let game_res = op!([ctx] faker_game {
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	let namespace_id = game_res.namespace_ids.first().unwrap();
+
+	op!([ctx] cloud_namespace_token_service_create {
+		namespace_id: Some(*namespace_id),
+		name: "ci".into(),
+		scopes: Vec::new(),
+		ttl: util::duration::days(1),
+		..Default::default()
+	})
+	.await
+	.unwrap_err();
+}
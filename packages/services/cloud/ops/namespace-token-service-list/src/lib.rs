@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use proto::backend::pkg::*;
+use tivet_operation::prelude::*;
+
+#[derive(sqlx::FromRow)]
+struct TokenRow {
+	jti: Uuid,
+	name: String,
+	scopes: Vec<i64>,
+	actor_tags: sqlx::types::Json<HashMap<String, String>>,
+	datacenter_ids: Vec<Uuid>,
+	create_ts: i64,
+	expire_ts: i64,
+	revoke_ts: Option<i64>,
+}
+
+impl TryFrom<TokenRow> for cloud::namespace_token_service_list::response::Token {
+	type Error = GlobalError;
+
+	fn try_from(value: TokenRow) -> GlobalResult<Self> {
+		Ok(cloud::namespace_token_service_list::response::Token {
+			jti: Some(value.jti.into()),
+			name: value.name,
+			scopes: value
+				.scopes
+				.into_iter()
+				.map(TryInto::try_into)
+				.collect::<Result<Vec<_>, _>>()?,
+			actor_tags: value.actor_tags.0,
+			datacenter_ids: value.datacenter_ids.into_iter().map(Into::into).collect(),
+			create_ts: value.create_ts,
+			expire_ts: value.expire_ts,
+			revoke_ts: value.revoke_ts,
+		})
+	}
+}
+
+#[operation(name = "cloud-namespace-token-service-list")]
+async fn handle(
+	ctx: OperationContext<cloud::namespace_token_service_list::Request>,
+) -> GlobalResult<cloud::namespace_token_service_list::Response> {
+	let namespace_id = unwrap_ref!(ctx.namespace_id).as_uuid();
+
+	let tokens = sql_fetch_all!(
+		[ctx, TokenRow]
+		"
+		SELECT
+			jti,
+			name,
+			scopes,
+			actor_tags,
+			datacenter_ids,
+			create_ts,
+			expire_ts,
+			revoke_ts
+		FROM db_cloud.game_namespace_service_tokens
+		WHERE
+			namespace_id = $1 AND
+			($2 OR (revoke_ts IS NULL AND expire_ts > $3))
+		ORDER BY create_ts DESC
+		",
+		namespace_id,
+		ctx.include_inactive,
+		ctx.ts(),
+	)
+	.await?
+	.into_iter()
+	.map(TryInto::try_into)
+	.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(cloud::namespace_token_service_list::Response { tokens })
+}
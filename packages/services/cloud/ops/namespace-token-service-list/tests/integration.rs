@@ -0,0 +1,32 @@
+use chirp_worker::prelude::*;
+
+#[worker_test]
+async fn empty(ctx: TestCtx) {
+	let game_res = op!([ctx] faker_game {
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	let namespace_id = game_res.namespace_ids.first().unwrap();
+
+	let create_res = op!([ctx] cloud_namespace_token_service_create {
+		namespace_id: Some(*namespace_id),
+		name: "ci".into(),
+		scopes: vec![proto::claims::entitlement::env_service::Scope::ActorsRead as i32],
+		ttl: util::duration::days(1),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+
+	let res = op!([ctx] cloud_namespace_token_service_list {
+		namespace_id: Some(*namespace_id),
+		include_inactive: false,
+	})
+	.await
+	.unwrap();
+
+	assert_eq!(1, res.tokens.len());
+	assert_eq!(create_res.jti, res.tokens[0].jti);
+	assert_eq!("ci", res.tokens[0].name);
+}
@@ -0,0 +1,44 @@
+use proto::backend::pkg::*;
+use tivet_operation::prelude::*;
+
+#[operation(name = "cloud-namespace-token-service-revoke")]
+async fn handle(
+	ctx: OperationContext<cloud::namespace_token_service_revoke::Request>,
+) -> GlobalResult<cloud::namespace_token_service_revoke::Response> {
+	let namespace_id = unwrap_ref!(ctx.namespace_id).as_uuid();
+	let jtis = ctx
+		.jtis
+		.iter()
+		.map(common::Uuid::as_uuid)
+		.collect::<Vec<_>>();
+
+	// Only revoke tokens that belong to this namespace
+	let revoked_jtis = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		UPDATE db_cloud.game_namespace_service_tokens
+		SET revoke_ts = $3
+		WHERE
+			namespace_id = $1 AND
+			jti = ANY($2) AND
+			revoke_ts IS NULL
+		RETURNING jti
+		",
+		namespace_id,
+		&jtis,
+		ctx.ts(),
+	)
+	.await?
+	.into_iter()
+	.map(|(jti,)| common::Uuid::from(jti))
+	.collect::<Vec<_>>();
+
+	if !revoked_jtis.is_empty() {
+		op!([ctx] token_revoke {
+			jtis: revoked_jtis.clone(),
+		})
+		.await?;
+	}
+
+	Ok(cloud::namespace_token_service_revoke::Response { jtis: revoked_jtis })
+}
@@ -0,0 +1,39 @@
+use chirp_worker::prelude::*;
+
+#[worker_test]
+async fn empty(ctx: TestCtx) {
+	let game_res = op!([ctx] faker_game {
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	let namespace_id = game_res.namespace_ids.first().unwrap();
+
+	let create_res = op!([ctx] cloud_namespace_token_service_create {
+		namespace_id: Some(*namespace_id),
+		name: "ci".into(),
+		scopes: vec![proto::claims::entitlement::env_service::Scope::ActorsRead as i32],
+		ttl: util::duration::days(1),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	let jti = create_res.jti.unwrap();
+
+	let res = op!([ctx] cloud_namespace_token_service_revoke {
+		namespace_id: Some(*namespace_id),
+		jtis: vec![jti],
+	})
+	.await
+	.unwrap();
+	assert_eq!(vec![jti], res.jtis);
+
+	// Revoking again is a no-op
+	let res = op!([ctx] cloud_namespace_token_service_revoke {
+		namespace_id: Some(*namespace_id),
+		jtis: vec![jti],
+	})
+	.await
+	.unwrap();
+	assert!(res.jtis.is_empty());
+}
@@ -48,6 +48,7 @@ async fn empty(ctx: TestCtx) {
 						listable: true,
 						taggable: false,
 						allow_dynamic_max_players: false,
+						skill_matchmaking: None,
 
 						runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 							build_id: build_res.build_id,
@@ -0,0 +1,24 @@
+CREATE TABLE autoscale_policies (
+	datacenter_id UUID NOT NULL REFERENCES datacenters,
+	pool_type INT NOT NULL, -- cluster::types::PoolType
+	target_utilization INT NOT NULL, -- Percent
+	hysteresis INT NOT NULL, -- Percentage points
+	scale_up_cooldown INT NOT NULL, -- Milliseconds
+	scale_down_cooldown INT NOT NULL, -- Milliseconds
+	update_ts INT NOT NULL,
+	PRIMARY KEY (datacenter_id, pool_type)
+);
+
+CREATE TABLE autoscale_events (
+	event_id UUID PRIMARY KEY,
+	datacenter_id UUID NOT NULL,
+	pool_type INT NOT NULL, -- cluster::types::PoolType
+	create_ts INT NOT NULL,
+	action INT NOT NULL, -- cluster::types::AutoscaleAction
+	utilization INT, -- Percent, unset if no server reported usage
+	server_count INT NOT NULL,
+	previous_desired_count INT NOT NULL,
+	desired_count INT NOT NULL,
+	expire_ts TIMESTAMPTZ NOT NULL,
+	INDEX (datacenter_id, pool_type, create_ts DESC)
+) WITH (ttl = 'on', ttl_expiration_expression = 'expire_ts', ttl_job_cron = '@hourly');
@@ -0,0 +1,2 @@
+-- Set once a pegboard client token has been issued to the server, it can only be fetched once
+ALTER TABLE servers ADD COLUMN client_token_create_ts INT;
@@ -2,6 +2,7 @@ use chirp_workflow::prelude::*;
 
 pub mod metrics;
 pub mod ops;
+pub mod provider;
 pub mod types;
 pub mod util;
 pub mod workflows;
@@ -12,6 +13,7 @@ pub fn registry() -> WorkflowResult<Registry> {
 	let mut registry = Registry::new();
 	registry.register_workflow::<cluster::Workflow>()?;
 	registry.register_workflow::<datacenter::Workflow>()?;
+	registry.register_workflow::<datacenter::autoscale::Workflow>()?;
 	registry.register_workflow::<datacenter::scale::Workflow>()?;
 	registry.register_workflow::<datacenter::tls_issue::Workflow>()?;
 	registry.register_workflow::<server::Workflow>()?;
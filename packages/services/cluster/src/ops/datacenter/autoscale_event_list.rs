@@ -0,0 +1,93 @@
+use std::convert::{TryFrom, TryInto};
+
+use chirp_workflow::prelude::*;
+
+use crate::types::{AutoscaleAction, AutoscaleEvent, PoolType};
+
+#[derive(sqlx::FromRow)]
+struct EventRow {
+	event_id: Uuid,
+	datacenter_id: Uuid,
+	pool_type: i64,
+	create_ts: i64,
+	action: i64,
+	utilization: Option<i64>,
+	server_count: i64,
+	previous_desired_count: i64,
+	desired_count: i64,
+}
+
+impl TryFrom<EventRow> for AutoscaleEvent {
+	type Error = GlobalError;
+
+	fn try_from(value: EventRow) -> GlobalResult<Self> {
+		Ok(AutoscaleEvent {
+			event_id: value.event_id,
+			datacenter_id: value.datacenter_id,
+			pool_type: unwrap!(PoolType::from_repr(value.pool_type.try_into()?)),
+			create_ts: value.create_ts,
+			action: unwrap!(AutoscaleAction::from_repr(value.action.try_into()?)),
+			utilization: value.utilization.map(TryInto::try_into).transpose()?,
+			server_count: value.server_count.try_into()?,
+			previous_desired_count: value.previous_desired_count.try_into()?,
+			desired_count: value.desired_count.try_into()?,
+		})
+	}
+}
+
+#[derive(Debug)]
+pub struct Input {
+	pub datacenter_id: Uuid,
+	/// Returns events for all pools if unset.
+	pub pool_type: Option<PoolType>,
+	/// Only returns events created before this timestamp, used for pagination.
+	pub before_ts: Option<i64>,
+	pub limit: u32,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// Sorted by newest first.
+	pub events: Vec<AutoscaleEvent>,
+}
+
+#[operation]
+pub async fn cluster_datacenter_autoscale_event_list(
+	ctx: &OperationCtx,
+	input: &Input,
+) -> GlobalResult<Output> {
+	let rows = sql_fetch_all!(
+		[ctx, EventRow]
+		"
+		SELECT
+			event_id,
+			datacenter_id,
+			pool_type,
+			create_ts,
+			action,
+			utilization,
+			server_count,
+			previous_desired_count,
+			desired_count
+		FROM db_cluster.autoscale_events
+		WHERE
+			datacenter_id = $1 AND
+			($2 IS NULL OR pool_type = $2) AND
+			($3 IS NULL OR create_ts < $3)
+		ORDER BY create_ts DESC
+		LIMIT $4
+		",
+		input.datacenter_id,
+		input.pool_type.map(|pool_type| pool_type as i64),
+		input.before_ts,
+		input.limit as i64,
+	)
+	.await?;
+
+	Ok(Output {
+		events: rows
+			.into_iter()
+			.map(TryInto::try_into)
+			.collect::<GlobalResult<Vec<_>>>()?,
+	})
+}
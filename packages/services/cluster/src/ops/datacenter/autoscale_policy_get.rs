@@ -0,0 +1,79 @@
+use std::convert::{TryFrom, TryInto};
+
+use chirp_workflow::prelude::*;
+
+use crate::types::{AutoscalePolicy, PoolType};
+
+#[derive(sqlx::FromRow)]
+struct PolicyRow {
+	datacenter_id: Uuid,
+	pool_type: i64,
+	target_utilization: i64,
+	hysteresis: i64,
+	scale_up_cooldown: i64,
+	scale_down_cooldown: i64,
+}
+
+impl TryFrom<PolicyRow> for Datacenter {
+	type Error = GlobalError;
+
+	fn try_from(value: PolicyRow) -> GlobalResult<Self> {
+		Ok(Datacenter {
+			datacenter_id: value.datacenter_id,
+			policy: AutoscalePolicy {
+				pool_type: unwrap!(PoolType::from_repr(value.pool_type.try_into()?)),
+				target_utilization: value.target_utilization.try_into()?,
+				hysteresis: value.hysteresis.try_into()?,
+				scale_up_cooldown: value.scale_up_cooldown.try_into()?,
+				scale_down_cooldown: value.scale_down_cooldown.try_into()?,
+			},
+		})
+	}
+}
+
+#[derive(Debug)]
+pub struct Input {
+	pub datacenter_ids: Vec<Uuid>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// One entry per pool with an autoscale policy.
+	pub datacenters: Vec<Datacenter>,
+}
+
+#[derive(Debug)]
+pub struct Datacenter {
+	pub datacenter_id: Uuid,
+	pub policy: AutoscalePolicy,
+}
+
+#[operation]
+pub async fn cluster_datacenter_autoscale_policy_get(
+	ctx: &OperationCtx,
+	input: &Input,
+) -> GlobalResult<Output> {
+	let rows = sql_fetch_all!(
+		[ctx, PolicyRow]
+		"
+		SELECT
+			datacenter_id,
+			pool_type,
+			target_utilization,
+			hysteresis,
+			scale_up_cooldown,
+			scale_down_cooldown
+		FROM db_cluster.autoscale_policies
+		WHERE datacenter_id = ANY($1)
+		",
+		&input.datacenter_ids,
+	)
+	.await?;
+
+	Ok(Output {
+		datacenters: rows
+			.into_iter()
+			.map(TryInto::try_into)
+			.collect::<GlobalResult<Vec<_>>>()?,
+	})
+}
@@ -1,3 +1,5 @@
+pub mod autoscale_event_list;
+pub mod autoscale_policy_get;
 pub mod get;
 pub mod list;
 pub mod location_get;
@@ -0,0 +1,38 @@
+use chirp_workflow::prelude::*;
+
+#[derive(Debug)]
+pub struct Input {
+	pub server_id: Uuid,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// False if a client token was already issued to the server.
+	pub claimed: bool,
+}
+
+/// Marks the pegboard client token of a server as issued. Every server created from the same prebake
+/// image shares the server token, so the client token can only be fetched once per server.
+#[operation]
+pub async fn cluster_server_client_token_claim(
+	ctx: &OperationCtx,
+	input: &Input,
+) -> GlobalResult<Output> {
+	let claimed = sql_fetch_optional!(
+		[ctx, (i64,)]
+		"
+		UPDATE db_cluster.servers
+		SET client_token_create_ts = $2
+		WHERE
+			server_id = $1 AND
+			client_token_create_ts IS NULL
+		RETURNING 1
+		",
+		input.server_id,
+		util::timestamp::now(),
+	)
+	.await?
+	.is_some();
+
+	Ok(Output { claimed })
+}
@@ -73,7 +73,7 @@ pub async fn cluster_server_lost_list(ctx: &OperationCtx, input: &Input) -> Glob
 	let mut servers = Vec::new();
 	for (provider, api_token) in accounts {
 		match provider {
-			Provider::Manual => {
+			Provider::Manual | Provider::Local => {
 				// Noop
 			}
 			Provider::Linode => {
@@ -1,3 +1,4 @@
+pub mod client_token_claim;
 pub mod destroy_with_filter;
 pub mod get;
 pub mod list;
@@ -5,3 +6,4 @@ pub mod lost_list;
 pub mod prune_with_filter;
 pub mod resolve_for_ip;
 pub mod taint_with_filter;
+pub mod token_create;
@@ -144,7 +144,7 @@ async fn prune_linode(
 
 	for (provider, api_token) in accounts {
 		match provider {
-			Provider::Manual => {
+			Provider::Manual | Provider::Local => {
 				// Noop
 			}
 			Provider::Linode => {
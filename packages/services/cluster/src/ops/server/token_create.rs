@@ -0,0 +1,52 @@
+use chirp_workflow::prelude::*;
+use tivet_operation::prelude::proto::{self, backend::pkg::token};
+
+#[derive(Debug)]
+pub struct Input {
+	pub issuer: String,
+	/// Binds the token to a pegboard client. Tokens without a server id cannot be used to connect to
+	/// the pegboard endpoint.
+	pub server_id: Option<Uuid>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub token: String,
+}
+
+/// Creates a token with the `ProvisionedServer` entitlement. Used by servers to authenticate API calls and
+/// pegboard client connections.
+#[operation]
+pub async fn cluster_server_token_create(
+	ctx: &OperationCtx,
+	input: &Input,
+) -> GlobalResult<Output> {
+	let token_res = op!([ctx] token_create {
+		token_config: Some(token::create::request::TokenConfig {
+			ttl: crate::util::SERVER_TOKEN_TTL,
+		}),
+		refresh_token_config: None,
+		issuer: input.issuer.clone(),
+		client: None,
+		kind: Some(token::create::request::Kind::New(token::create::request::KindNew {
+			entitlements: vec![
+				proto::claims::Entitlement {
+					kind: Some(
+						proto::claims::entitlement::Kind::ProvisionedServer(
+							proto::claims::entitlement::ProvisionedServer {
+								server_id: input.server_id.map(Into::into),
+							}
+						)
+					)
+				}
+			],
+		})),
+		label: Some("srv".to_owned()),
+		..Default::default()
+	})
+	.await?;
+
+	Ok(Output {
+		token: unwrap_ref!(token_res.token).token.clone(),
+	})
+}
@@ -0,0 +1,282 @@
+use std::collections::{HashMap, HashSet};
+
+use chirp_workflow::prelude::*;
+use linode::util::{api, client};
+use reqwest::header;
+use serde_json::json;
+use server_spec::types::ServerSpec;
+
+use super::{CreateInput, CreateOutput, DestroyInput, Image, PrebakeInput, ServerProvider};
+use crate::{
+	types::{Datacenter, PoolType},
+	workflows::prebake::UpdateDbInput,
+};
+
+/// Provisions servers through the Linode API. Each server is managed by a
+/// `linode::workflows::server` workflow for its entire lifetime.
+pub struct Linode;
+
+#[async_trait::async_trait]
+impl ServerProvider for Linode {
+	// Never destroy servers when scaling down with Linode, always drain.
+	//
+	// See _Provider Billing Internals_ in docs
+	fn always_drain(&self) -> bool {
+		true
+	}
+
+	async fn create(
+		&self,
+		ctx: &mut WorkflowCtx,
+		input: &CreateInput,
+	) -> GlobalResult<Option<CreateOutput>> {
+		let workflow_id = ctx
+			.workflow(linode::workflows::server::Input {
+				server_id: input.server_id,
+				provider_datacenter_id: input.provider_datacenter_id.clone(),
+				custom_image: input.custom_image.clone(),
+				api_token: input.api_token.clone(),
+				hardware: input.hardware.clone(),
+				firewall_preset: firewall_preset(input.pool_type),
+				vlan_ip: input.vlan_ip,
+				vlan_ip_net: input.vlan_ip_net,
+				tags: input.tags.clone(),
+			})
+			.tag("server_id", input.server_id)
+			.dispatch()
+			.await?;
+
+		match ctx.listen::<ProvisionSignal>().await? {
+			ProvisionSignal::ProvisionComplete(sig) => Ok(Some(CreateOutput {
+				provider_server_workflow_id: Some(workflow_id),
+				provider_server_id: sig.linode_id.to_string(),
+				public_ip: sig.public_ip,
+			})),
+			ProvisionSignal::ProvisionFailed(_) => {
+				tracing::error!(
+					provision_workflow_id=%workflow_id,
+					server_id=?input.server_id,
+					"failed to provision server"
+				);
+
+				Ok(None)
+			}
+		}
+	}
+
+	async fn destroy(&self, ctx: &mut WorkflowCtx, input: &DestroyInput) -> GlobalResult<()> {
+		let provider_server_workflow_id = unwrap!(
+			input.provider_server_workflow_id,
+			"linode server has no provider workflow"
+		);
+
+		tracing::info!(server_id=?input.server_id, "destroying linode server");
+
+		ctx.signal(linode::workflows::server::Destroy {})
+			.to_workflow(provider_server_workflow_id)
+			.send()
+			.await?;
+
+		// Wait for workflow to complete
+		ctx.wait_for_workflow::<linode::workflows::server::Workflow>(provider_server_workflow_id)
+			.await?;
+
+		Ok(())
+	}
+
+	async fn prebake(&self, ctx: &mut WorkflowCtx, input: &PrebakeInput) -> GlobalResult<()> {
+		let workflow_id = ctx
+			.workflow(linode::workflows::server::Input {
+				server_id: input.prebake_server_id,
+				provider_datacenter_id: input.provider_datacenter_id.clone(),
+				custom_image: None,
+				api_token: input.api_token.clone(),
+				hardware: linode::util::consts::PREBAKE_HARDWARE.to_string(),
+				firewall_preset: firewall_preset(input.pool_type),
+				vlan_ip: None,
+				vlan_ip_net: None,
+				tags: input.tags.clone(),
+			})
+			.tag("server_id", input.prebake_server_id)
+			.dispatch()
+			.await?;
+
+		match ctx.listen::<ProvisionSignal>().await? {
+			ProvisionSignal::ProvisionComplete(sig) => {
+				// Install server
+				ctx.workflow(crate::workflows::server::install::Input {
+					datacenter_id: input.datacenter_id,
+					server_id: None,
+					public_ip: sig.public_ip,
+					pool_type: input.pool_type,
+					initialize_immediately: false,
+				})
+				.output()
+				.await?;
+
+				// Create image
+				let workflow_id = ctx
+					.workflow(linode::workflows::image::Input {
+						prebake_server_id: input.prebake_server_id,
+						api_token: input.api_token.clone(),
+						linode_id: sig.linode_id,
+						boot_disk_id: sig.boot_disk_id,
+					})
+					.tag("linode_id", sig.linode_id)
+					.dispatch()
+					.await?;
+
+				// Wait for image creation
+				let image_create_res = ctx
+					.listen::<linode::workflows::image::CreateComplete>()
+					.await?;
+
+				// Write image id to db
+				ctx.activity(UpdateDbInput {
+					provider: input.provider,
+					datacenter_id: input.datacenter_id,
+					pool_type: input.pool_type,
+					install_script_hash: input.install_script_hash.clone(),
+					image_id: image_create_res.image_id,
+				})
+				.await?;
+
+				// Destroy linode server after the image is complete
+				ctx.signal(linode::workflows::server::Destroy {})
+					.tag("server_id", input.prebake_server_id)
+					.send()
+					.await?;
+
+				// Wait for image workflow to get cleaned up by linode-gc after the image expires
+				ctx.wait_for_workflow::<linode::workflows::server::Workflow>(workflow_id)
+					.await?;
+			}
+			ProvisionSignal::ProvisionFailed(_) => {
+				tracing::error!(
+					provision_workflow_id=%workflow_id,
+					"failed to provision prebake server"
+				);
+			}
+		}
+
+		Ok(())
+	}
+
+	async fn list_images(
+		&self,
+		config: &tivet_config::Config,
+		api_token: Option<&str>,
+	) -> GlobalResult<Vec<Image>> {
+		let api_token = if let Some(api_token) = api_token {
+			api_token.to_string()
+		} else {
+			config.server()?.linode()?.api_token.read().clone()
+		};
+
+		let filter = json!({
+			"status": "available",
+			"type": "manual"
+		});
+		let mut headers = header::HeaderMap::new();
+		headers.insert(
+			"X-Filter",
+			header::HeaderValue::from_str(&serde_json::to_string(&filter)?)?,
+		);
+
+		// Build HTTP client
+		let client = client::Client::new_with_headers(api_token, headers).await?;
+
+		let images = api::list_custom_images(config, &client).await?;
+
+		if images.len() == api::CUSTOM_IMAGE_LIST_SIZE {
+			// We don't need to paginate since we'll never have more than
+			// `number of regions * number of pools * 2` images which is not more than 500 (x2 is for the
+			// old + new images)
+			tracing::warn!("page limit reached, new images may not be returned");
+		}
+
+		Ok(images
+			.into_iter()
+			.map(|img| Image {
+				provider_image_id: img.id,
+				create_ts: img.created.timestamp_millis(),
+			})
+			.collect())
+	}
+
+	async fn hardware_specs(
+		&self,
+		ctx: &OperationCtx,
+		datacenters: &[&Datacenter],
+		pool_type: PoolType,
+	) -> GlobalResult<HashMap<Uuid, ServerSpec>> {
+		// Lookup hardware IDs for each dc
+		let hardware_ids = datacenters
+			.iter()
+			.map(|dc| {
+				let pool = unwrap!(
+					dc.pools.iter().find(|pool| pool.pool_type == pool_type),
+					"no {} pool",
+					pool_type
+				);
+
+				// Choose the first hardware in the list, the rest are fallback hardware
+				let hardware = unwrap!(pool.hardware.first(), "no hardware")
+					.provider_hardware
+					.clone();
+
+				Ok((dc.datacenter_id, hardware))
+			})
+			.collect::<GlobalResult<HashMap<_, _>>>()?;
+
+		if hardware_ids.is_empty() || ctx.config().server()?.linode.is_none() {
+			return Ok(HashMap::new());
+		}
+
+		// Fetch all hardware sizes from Linode
+		let instance_types = ctx
+			.op(linode::ops::instance_type_get::Input {
+				hardware_ids: hardware_ids
+					.values()
+					.cloned()
+					.collect::<HashSet<_>>()
+					.into_iter()
+					.collect(),
+			})
+			.await?
+			.instance_types;
+
+		hardware_ids
+			.into_iter()
+			.map(|(datacenter_id, hardware_id)| {
+				let instance_type = unwrap!(
+					instance_types
+						.iter()
+						.find(|it| it.hardware_id == hardware_id),
+					"datacenter linode hardware stats not found"
+				);
+
+				Ok((datacenter_id, ServerSpec::from_linode(instance_type)))
+			})
+			.collect()
+	}
+}
+
+fn firewall_preset(pool_type: PoolType) -> linode::types::FirewallPreset {
+	match pool_type {
+		PoolType::Job | PoolType::Pegboard | PoolType::PegboardIsolate => {
+			linode::types::FirewallPreset::Job
+		}
+		PoolType::Gg => linode::types::FirewallPreset::Gg,
+		PoolType::Ats => linode::types::FirewallPreset::Ats,
+		PoolType::Fdb => linode::types::FirewallPreset::Fdb,
+	}
+}
+
+// Listen for linode provision signals
+type ProvisionComplete = linode::workflows::server::ProvisionComplete;
+type ProvisionFailed = linode::workflows::server::ProvisionFailed;
+join_signal!(ProvisionSignal {
+	ProvisionComplete,
+	ProvisionFailed,
+});
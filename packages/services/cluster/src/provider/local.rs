@@ -0,0 +1,340 @@
+use std::{collections::HashMap, net::Ipv4Addr, process::Stdio, time::Duration};
+
+use chirp_workflow::prelude::*;
+use server_spec::types::ServerSpec;
+use tivet_config::config::LocalProvider;
+use tokio::{
+	process::{Child, Command},
+	sync::{oneshot, Mutex},
+	task::JoinHandle,
+};
+
+use super::{
+	manual::config_hardware_spec, CreateInput, CreateOutput, DestroyInput, Image, PrebakeInput,
+	ServerProvider,
+};
+use crate::types::{Datacenter, PoolType};
+
+/// How long a process has to exit after SIGTERM before it is killed.
+const PROCESS_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+lazy_static::lazy_static! {
+	/// Processes started by `LocalProvider::Process`, keyed by server id. Servers are only ever signalled
+	/// through their handle since the PID of an exited process can be reused.
+	static ref PROCESSES: Mutex<HashMap<Uuid, LocalProcess>> = Mutex::new(HashMap::new());
+}
+
+/// Runs servers as containers or processes on the local machine, configured with
+/// `server.local_provider`. Used to test the datacenter scale, drain and taint workflows end-to-end on
+/// a single machine.
+pub struct Local;
+
+#[async_trait::async_trait]
+impl ServerProvider for Local {
+	// The configured image or command is expected to contain all of the pool's components
+	fn requires_install(&self) -> bool {
+		false
+	}
+
+	async fn create(
+		&self,
+		ctx: &mut WorkflowCtx,
+		input: &CreateInput,
+	) -> GlobalResult<Option<CreateOutput>> {
+		let res = ctx
+			.activity(CreateLocalServerInput {
+				server_id: input.server_id,
+				datacenter_id: input.datacenter_id,
+				pool_type: input.pool_type,
+				vlan_ip: input.vlan_ip,
+			})
+			.await?;
+
+		Ok(Some(CreateOutput {
+			provider_server_workflow_id: None,
+			provider_server_id: res.provider_server_id,
+			public_ip: res.public_ip,
+		}))
+	}
+
+	async fn destroy(&self, ctx: &mut WorkflowCtx, input: &DestroyInput) -> GlobalResult<()> {
+		tracing::info!(server_id=?input.server_id, "destroying local server");
+
+		ctx.activity(DestroyLocalServerInput {
+			server_id: input.server_id,
+		})
+		.await
+	}
+
+	async fn prebake(&self, _ctx: &mut WorkflowCtx, _input: &PrebakeInput) -> GlobalResult<()> {
+		// Noop, local servers are never installed
+		Ok(())
+	}
+
+	async fn list_images(
+		&self,
+		_config: &tivet_config::Config,
+		_api_token: Option<&str>,
+	) -> GlobalResult<Vec<Image>> {
+		Ok(Vec::new())
+	}
+
+	async fn hardware_specs(
+		&self,
+		ctx: &OperationCtx,
+		datacenters: &[&Datacenter],
+		_pool_type: PoolType,
+	) -> GlobalResult<HashMap<Uuid, ServerSpec>> {
+		datacenters
+			.iter()
+			.map(|dc| Ok((dc.datacenter_id, config_hardware_spec(ctx, dc)?)))
+			.collect()
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct CreateLocalServerInput {
+	server_id: Uuid,
+	datacenter_id: Uuid,
+	pool_type: PoolType,
+	vlan_ip: Option<Ipv4Addr>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct CreateLocalServerOutput {
+	provider_server_id: String,
+	public_ip: Ipv4Addr,
+}
+
+#[activity(CreateLocalServer)]
+async fn create_local_server(
+	ctx: &ActivityCtx,
+	input: &CreateLocalServerInput,
+) -> GlobalResult<CreateLocalServerOutput> {
+	let ns = &ctx.config().server()?.tivet.namespace;
+	let env = [
+		("TIVET_SERVER_ID", input.server_id.to_string()),
+		("TIVET_DATACENTER_ID", input.datacenter_id.to_string()),
+		("TIVET_POOL_TYPE", input.pool_type.to_string()),
+	];
+
+	match ctx.config().server()?.local_provider()? {
+		LocalProvider::Container { image, network } => {
+			let name = format!("{ns}-{}", input.server_id);
+
+			// Remove container from a previous attempt of this activity
+			Command::new("docker")
+				.arg("rm")
+				.arg("--force")
+				.arg(&name)
+				.output()
+				.await?;
+
+			let mut cmd = Command::new("docker");
+			cmd.arg("run")
+				.arg("--detach")
+				.arg("--name")
+				.arg(&name)
+				.arg("--label")
+				.arg(format!("tivet.namespace={ns}"));
+
+			if let Some(network) = network {
+				cmd.arg("--network").arg(network);
+			}
+
+			for (key, value) in &env {
+				cmd.arg("--env").arg(format!("{key}={value}"));
+			}
+
+			let output = cmd.arg(image).output().await?;
+			ensure!(
+				output.status.success(),
+				"failed to run container: {}",
+				String::from_utf8_lossy(&output.stderr)
+			);
+			let container_id = String::from_utf8(output.stdout)?.trim().to_string();
+
+			// Read container ip
+			let output = Command::new("docker")
+				.arg("inspect")
+				.arg("--format")
+				.arg("{{range .NetworkSettings.Networks}}{{.IPAddress}} {{end}}")
+				.arg(&container_id)
+				.output()
+				.await?;
+			ensure!(
+				output.status.success(),
+				"failed to inspect container: {}",
+				String::from_utf8_lossy(&output.stderr)
+			);
+			let public_ip = unwrap!(
+				String::from_utf8(output.stdout)?
+					.split_whitespace()
+					.next()
+					.map(|ip| ip.parse::<Ipv4Addr>())
+					.transpose()?,
+				"container has no ip"
+			);
+
+			tracing::info!(server_id=?input.server_id, %container_id, %public_ip, "started local server");
+
+			Ok(CreateLocalServerOutput {
+				provider_server_id: container_id,
+				public_ip,
+			})
+		}
+		LocalProvider::Process { command, args } => {
+			// Public ips must be unique, map the server's vlan ip into the loopback range
+			let public_ip = input
+				.vlan_ip
+				.map(|ip| {
+					let [_, a, b, c] = ip.octets();
+					Ipv4Addr::new(127, a, b, c)
+				})
+				.unwrap_or(Ipv4Addr::LOCALHOST);
+
+			let mut processes = PROCESSES.lock().await;
+
+			// Stop process from a previous attempt of this activity
+			if let Some(process) = processes.remove(&input.server_id) {
+				process.stop().await;
+			}
+
+			let child = Command::new(command)
+				.args(args)
+				.envs(env)
+				.env("TIVET_PUBLIC_IP", public_ip.to_string())
+				.stdin(Stdio::null())
+				.spawn()?;
+			let pid = unwrap!(child.id(), "local server process exited immediately");
+
+			processes.insert(input.server_id, LocalProcess::spawn(input.server_id, child));
+
+			tracing::info!(server_id=?input.server_id, %pid, %public_ip, "started local server");
+
+			Ok(CreateLocalServerOutput {
+				provider_server_id: pid.to_string(),
+				public_ip,
+			})
+		}
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct DestroyLocalServerInput {
+	server_id: Uuid,
+}
+
+#[activity(DestroyLocalServer)]
+async fn destroy_local_server(
+	ctx: &ActivityCtx,
+	input: &DestroyLocalServerInput,
+) -> GlobalResult<()> {
+	match ctx.config().server()?.local_provider()? {
+		LocalProvider::Container { .. } => {
+			let (provider_server_id,) = sql_fetch_one!(
+				[ctx, (Option<String>,)]
+				"
+				SELECT provider_server_id
+				FROM db_cluster.servers
+				WHERE server_id = $1
+				",
+				input.server_id,
+			)
+			.await?;
+
+			let Some(provider_server_id) = provider_server_id else {
+				tracing::warn!(server_id=?input.server_id, "local server was never created");
+				return Ok(());
+			};
+
+			let output = Command::new("docker")
+				.arg("rm")
+				.arg("--force")
+				.arg(&provider_server_id)
+				.output()
+				.await?;
+
+			if !output.status.success() {
+				// The server may have already exited on its own
+				tracing::warn!(
+					server_id=?input.server_id,
+					%provider_server_id,
+					stderr=%String::from_utf8_lossy(&output.stderr),
+					"failed to destroy local server"
+				);
+			}
+		}
+		LocalProvider::Process { .. } => {
+			let process = PROCESSES.lock().await.remove(&input.server_id);
+
+			if let Some(process) = process {
+				process.stop().await;
+			} else {
+				// Either never created or started by another worker process
+				tracing::warn!(server_id=?input.server_id, "local server process not found");
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Handle to a process started by `LocalProvider::Process`. A task owns the child and reaps it once it
+/// exits, so the PID stays reserved until then.
+struct LocalProcess {
+	stop_tx: oneshot::Sender<()>,
+	handle: JoinHandle<()>,
+}
+
+impl LocalProcess {
+	fn spawn(server_id: Uuid, mut child: Child) -> Self {
+		let (stop_tx, stop_rx) = oneshot::channel();
+
+		let handle = tokio::spawn(async move {
+			tokio::select! {
+				res = child.wait() => {
+					tracing::warn!(?server_id, ?res, "local server process exited");
+					return;
+				}
+				_ = stop_rx => {}
+			}
+
+			// The child has not been reaped yet so its PID cannot have been reused
+			if let Some(pid) = child.id() {
+				if let Err(err) = Command::new("kill")
+					.arg("-TERM")
+					.arg(pid.to_string())
+					.output()
+					.await
+				{
+					tracing::warn!(?server_id, ?err, "failed to send SIGTERM to local server");
+				}
+			}
+
+			match tokio::time::timeout(PROCESS_STOP_TIMEOUT, child.wait()).await {
+				Ok(res) => tracing::info!(?server_id, ?res, "local server process stopped"),
+				Err(_) => {
+					tracing::warn!(?server_id, "local server process did not stop, killing");
+
+					// Kills and reaps the child
+					if let Err(err) = child.kill().await {
+						tracing::error!(?server_id, ?err, "failed to kill local server process");
+					}
+				}
+			}
+		});
+
+		LocalProcess { stop_tx, handle }
+	}
+
+	/// Stops the process and waits for it to be reaped.
+	async fn stop(self) {
+		// Fails if the process already exited
+		let _ = self.stop_tx.send(());
+
+		if let Err(err) = self.handle.await {
+			tracing::error!(?err, "local server process task failed");
+		}
+	}
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use chirp_workflow::prelude::*;
+use server_spec::types::ServerSpec;
+
+use super::{CreateInput, CreateOutput, DestroyInput, Image, PrebakeInput, ServerProvider};
+use crate::types::{Datacenter, PoolType};
+
+/// Servers are manually provisioned and connected, all provisioning actions are noops.
+pub struct Manual;
+
+#[async_trait::async_trait]
+impl ServerProvider for Manual {
+	async fn create(
+		&self,
+		_ctx: &mut WorkflowCtx,
+		_input: &CreateInput,
+	) -> GlobalResult<Option<CreateOutput>> {
+		// Noop
+		Ok(None)
+	}
+
+	async fn destroy(&self, _ctx: &mut WorkflowCtx, _input: &DestroyInput) -> GlobalResult<()> {
+		// Noop
+		Ok(())
+	}
+
+	async fn prebake(&self, _ctx: &mut WorkflowCtx, _input: &PrebakeInput) -> GlobalResult<()> {
+		// Noop
+		Ok(())
+	}
+
+	async fn list_images(
+		&self,
+		_config: &tivet_config::Config,
+		_api_token: Option<&str>,
+	) -> GlobalResult<Vec<Image>> {
+		Ok(Vec::new())
+	}
+
+	async fn hardware_specs(
+		&self,
+		ctx: &OperationCtx,
+		datacenters: &[&Datacenter],
+		_pool_type: PoolType,
+	) -> GlobalResult<HashMap<Uuid, ServerSpec>> {
+		datacenters
+			.iter()
+			.map(|dc| Ok((dc.datacenter_id, config_hardware_spec(ctx, dc)?)))
+			.collect()
+	}
+}
+
+/// Reads the hardware spec of a datacenter from the bootstrap config.
+pub(crate) fn config_hardware_spec(
+	ctx: &OperationCtx,
+	dc: &Datacenter,
+) -> GlobalResult<ServerSpec> {
+	let cluster_configs = ctx.config().server()?.tivet.clusters();
+
+	// TODO(RVT-4026): Switch to being stored in CRDB
+	// Look up hardware in config
+	let (_, cluster_config) = unwrap!(
+		cluster_configs.iter().find(|(_, c)| c.id == dc.cluster_id),
+		"could not find matching cluster config"
+	);
+	let (_, dc_config) = unwrap!(
+		cluster_config
+			.bootstrap_datacenters
+			.iter()
+			.find(|(_, dc_config)| dc_config.id == dc.datacenter_id),
+		"could not find matching datacenter config"
+	);
+	let hardware = unwrap_ref!(
+		dc_config.hardware,
+		"hardware not specified for datacenter with {:?} provider",
+		dc.provider
+	);
+
+	Ok(ServerSpec {
+		cpu_cores: hardware.cpu_cores,
+		cpu: hardware.cpu,
+		memory: hardware.memory,
+		disk: hardware.disk,
+		bandwidth: hardware.bandwidth,
+	})
+}
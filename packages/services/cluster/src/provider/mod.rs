@@ -0,0 +1,112 @@
+use std::{collections::HashMap, net::Ipv4Addr};
+
+use chirp_workflow::prelude::*;
+use server_spec::types::ServerSpec;
+
+use crate::types::{Datacenter, PoolType, Provider};
+
+pub mod linode;
+pub mod local;
+pub mod manual;
+
+/// Abstraction over the backend that servers of a datacenter are provisioned on.
+///
+/// Methods taking a `WorkflowCtx` are called from within workflows and must be deterministic, see
+/// `chirp-workflow` docs.
+#[async_trait::async_trait]
+pub trait ServerProvider: Send + Sync {
+	/// Whether or not servers need to be installed over SSH after they are created. Servers created from
+	/// a prebake image are always considered installed.
+	fn requires_install(&self) -> bool {
+		true
+	}
+
+	/// If true, servers are never destroyed directly when scaling down, only drained.
+	fn always_drain(&self) -> bool {
+		false
+	}
+
+	/// Provisions a new server with the given hardware. Returns `None` if provisioning failed and the next
+	/// hardware should be attempted.
+	async fn create(
+		&self,
+		ctx: &mut WorkflowCtx,
+		input: &CreateInput,
+	) -> GlobalResult<Option<CreateOutput>>;
+
+	/// Destroys a server created with `create`.
+	async fn destroy(&self, ctx: &mut WorkflowCtx, input: &DestroyInput) -> GlobalResult<()>;
+
+	/// Provisions and installs a server, then creates an image from it. The image id is written to
+	/// `db_cluster.server_images2` once the image is usable.
+	async fn prebake(&self, ctx: &mut WorkflowCtx, input: &PrebakeInput) -> GlobalResult<()>;
+
+	/// Lists all usable prebake images in the given provider account.
+	async fn list_images(
+		&self,
+		config: &tivet_config::Config,
+		api_token: Option<&str>,
+	) -> GlobalResult<Vec<Image>>;
+
+	/// Looks up the hardware spec of the first hardware of the given pool for each datacenter.
+	async fn hardware_specs(
+		&self,
+		ctx: &OperationCtx,
+		datacenters: &[&Datacenter],
+		pool_type: PoolType,
+	) -> GlobalResult<HashMap<Uuid, ServerSpec>>;
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateInput {
+	pub server_id: Uuid,
+	pub datacenter_id: Uuid,
+	pub provider_datacenter_id: String,
+	pub api_token: Option<String>,
+	pub pool_type: PoolType,
+	pub hardware: String,
+	pub custom_image: Option<String>,
+	pub vlan_ip: Option<Ipv4Addr>,
+	pub vlan_ip_net: Option<ipnet::Ipv4Net>,
+	pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateOutput {
+	/// Set if the provider runs a workflow for the lifetime of the server.
+	pub provider_server_workflow_id: Option<Uuid>,
+	pub provider_server_id: String,
+	pub public_ip: Ipv4Addr,
+}
+
+#[derive(Debug, Clone)]
+pub struct DestroyInput {
+	pub server_id: Uuid,
+	pub provider_server_workflow_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrebakeInput {
+	pub prebake_server_id: Uuid,
+	pub datacenter_id: Uuid,
+	pub provider: Provider,
+	pub provider_datacenter_id: String,
+	pub api_token: Option<String>,
+	pub pool_type: PoolType,
+	pub install_script_hash: String,
+	pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Image {
+	pub provider_image_id: String,
+	pub create_ts: i64,
+}
+
+pub fn get(provider: Provider) -> &'static dyn ServerProvider {
+	match provider {
+		Provider::Manual => &manual::Manual,
+		Provider::Linode => &linode::Linode,
+		Provider::Local => &local::Local,
+	}
+}
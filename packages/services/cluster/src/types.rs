@@ -38,6 +38,9 @@ pub enum Provider {
 	/// Servers are manually provisioned and connected.
 	Manual = 1,
 	Linode = 0,
+	/// Servers run as containers or processes on the local machine. See
+	/// `tivet_config::config::LocalProvider`.
+	Local = 2,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
@@ -115,6 +118,66 @@ pub struct PoolUpdate {
 	pub drain_timeout: Option<u64>,
 }
 
+/// Utilization-based autoscaling config for a pool. See `workflows::datacenter::autoscale`.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct AutoscalePolicy {
+	pub pool_type: PoolType,
+	/// Percent of the pool's CPU and memory that should be in use.
+	pub target_utilization: u32,
+	/// Percentage points around `target_utilization` in which the desired count is not changed.
+	pub hysteresis: u32,
+	/// Minimum time (in ms) between scaling up.
+	pub scale_up_cooldown: u64,
+	/// Minimum time (in ms) after any scale before scaling down.
+	pub scale_down_cooldown: u64,
+}
+
+impl AutoscalePolicy {
+	pub fn validate(&self) -> GlobalResult<()> {
+		ensure!(
+			!matches!(self.pool_type, PoolType::Fdb),
+			"fdb pools cannot be autoscaled"
+		);
+		ensure!(
+			(1..=100).contains(&self.target_utilization),
+			"`target_utilization` must be between 1 and 100"
+		);
+		ensure!(
+			self.hysteresis < self.target_utilization,
+			"`hysteresis` must be less than `target_utilization`"
+		);
+
+		Ok(())
+	}
+}
+
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, FromRepr)]
+pub enum AutoscaleAction {
+	/// Utilization is within the hysteresis band or no server reported usage.
+	Hold = 0,
+	ScaleUp = 1,
+	ScaleDown = 2,
+	/// The desired count should change but a cooldown has not elapsed yet.
+	Cooldown = 3,
+	/// The desired count should change but is already at `min_count` or `max_count`.
+	AtLimit = 4,
+}
+
+#[derive(Debug, Clone)]
+pub struct AutoscaleEvent {
+	pub event_id: Uuid,
+	pub datacenter_id: Uuid,
+	pub pool_type: PoolType,
+	pub create_ts: i64,
+	pub action: AutoscaleAction,
+	/// Percent.
+	pub utilization: Option<u32>,
+	pub server_count: u32,
+	pub previous_desired_count: u32,
+	/// Only applied for `ScaleUp` and `ScaleDown`.
+	pub desired_count: u32,
+}
+
 #[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, FromRepr)]
 pub enum BuildDeliveryMethod {
 	TrafficServer = 0,
@@ -9,7 +9,10 @@ pub mod test;
 // we won't be using the old image anymore
 pub const INSTALL_SCRIPT_HASH: &str = include_str!(concat!(env!("OUT_DIR"), "/hash.txt"));
 
-// TTL of the token written to prebake images. Prebake images are renewed before the token would expire
+// TTL of the token written to prebake images. Prebake images are renewed before the token would expire.
+//
+// Pegboard client tokens use the same TTL. They can only be fetched once when the server first boots and are
+// refreshed weekly by the server (`tivet_refresh_client_token.sh`) through the provision API.
 pub const SERVER_TOKEN_TTL: i64 = util::duration::days(30 * 6);
 
 pub fn server_name(
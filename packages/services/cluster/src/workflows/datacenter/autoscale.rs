@@ -0,0 +1,403 @@
+// Adjusts the desired count of pools with an autoscale policy based on their utilization.
+//
+// Utilization is the highest of the CPU and memory usage percentage of a pool. For job and pegboard
+// pools this is based on reservations (allocated resources), for all other pools it is based on
+// prometheus metrics.
+//
+// Every evaluation of a pool is recorded in `db_cluster.autoscale_events`, including ones that did not
+// change the desired count. The workflow stops once the datacenter has no policies left.
+
+use chirp_workflow::prelude::*;
+use futures_util::FutureExt;
+
+use crate::{
+	ops::datacenter::topology_get,
+	types::{AutoscaleAction, AutoscalePolicy, Pool, PoolType, PoolUpdate},
+};
+
+/// How often pools are evaluated.
+const EVALUATE_INTERVAL: i64 = util::duration::minutes(1);
+/// How long autoscale events are kept.
+const EVENT_TTL: i64 = util::duration::days(30);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Input {
+	pub datacenter_id: Uuid,
+}
+
+#[workflow]
+pub async fn cluster_datacenter_autoscale(
+	ctx: &mut WorkflowCtx,
+	input: &Input,
+) -> GlobalResult<()> {
+	ctx.repeat(|ctx| {
+		let datacenter_id = input.datacenter_id;
+
+		async move {
+			let res = ctx.activity(EvaluateInput { datacenter_id }).await?;
+
+			// Started again by the datacenter workflow once a policy is set
+			if !res.has_policies {
+				return Ok(Loop::Break(()));
+			}
+
+			// Inserted in a separate activity so the event ids are stable across retries
+			if !res.events.is_empty() {
+				ctx.activity(InsertEventsInput {
+					datacenter_id,
+					events: res.events.clone(),
+				})
+				.await?;
+			}
+
+			let pools = res
+				.events
+				.iter()
+				.filter_map(|event| event.evaluation.pool_update())
+				.collect::<Vec<_>>();
+
+			if !pools.is_empty() {
+				ctx.signal(super::Autoscale { pools })
+					.tag("datacenter_id", datacenter_id)
+					.send()
+					.await?;
+			}
+
+			ctx.sleep(EVALUATE_INTERVAL).await?;
+
+			Ok(Loop::Continue)
+		}
+		.boxed()
+	})
+	.await
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct EvaluateInput {
+	datacenter_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct EvaluateOutput {
+	has_policies: bool,
+	events: Vec<Event>,
+}
+
+#[activity(Evaluate)]
+async fn evaluate(ctx: &ActivityCtx, input: &EvaluateInput) -> GlobalResult<EvaluateOutput> {
+	let datacenter_id = input.datacenter_id;
+	let policies_res = ctx
+		.op(crate::ops::datacenter::autoscale_policy_get::Input {
+			datacenter_ids: vec![datacenter_id],
+		})
+		.await?;
+
+	// Skip fetching topology
+	if policies_res.datacenters.is_empty() {
+		return Ok(EvaluateOutput {
+			has_policies: false,
+			events: Vec::new(),
+		});
+	}
+
+	let (datacenter_res, topology_res) = tokio::try_join!(
+		ctx.op(crate::ops::datacenter::get::Input {
+			datacenter_ids: vec![datacenter_id],
+		}),
+		ctx.op(crate::ops::datacenter::topology_get::Input {
+			datacenter_ids: vec![datacenter_id],
+		}),
+	)?;
+
+	let dc = unwrap!(datacenter_res.datacenters.first());
+	let topology = unwrap!(topology_res.datacenters.first());
+	let now = util::timestamp::now();
+
+	let mut events = Vec::new();
+	for policy in policies_res.datacenters.iter().map(|x| &x.policy) {
+		let Some(pool) = dc.pools.iter().find(|p| p.pool_type == policy.pool_type) else {
+			tracing::warn!(pool_type=?policy.pool_type, "autoscale policy for pool that does not exist");
+			continue;
+		};
+
+		// Servers that have not reported usage yet are not counted
+		let servers = topology
+			.servers
+			.iter()
+			.filter(|server| server.pool_type == policy.pool_type && !server.missing)
+			.collect::<Vec<_>>();
+		let server_count = servers.len() as u32;
+		let utilization = pool_utilization(policy.pool_type, &servers);
+
+		let (last_scale_up_ts, last_scale_ts) = sql_fetch_one!(
+			[ctx, (Option<i64>, Option<i64>)]
+			"
+			SELECT
+				max(create_ts) FILTER (WHERE action = $3),
+				max(create_ts)
+			FROM db_cluster.autoscale_events
+			WHERE
+				datacenter_id = $1 AND
+				pool_type = $2 AND
+				action IN ($3, $4)
+			",
+			datacenter_id,
+			policy.pool_type as i64,
+			AutoscaleAction::ScaleUp as i64,
+			AutoscaleAction::ScaleDown as i64,
+		)
+		.await?;
+
+		let evaluation = evaluate_pool(
+			policy,
+			pool,
+			server_count,
+			utilization,
+			&Cooldowns {
+				last_scale_up_ts,
+				last_scale_ts,
+			},
+			now,
+		);
+
+		tracing::info!(
+			pool_type=?policy.pool_type,
+			action=?evaluation.action,
+			?utilization,
+			%server_count,
+			previous_desired_count=%evaluation.previous_desired_count,
+			desired_count=%evaluation.desired_count,
+			"autoscale evaluation"
+		);
+
+		events.push(Event {
+			event_id: Uuid::new_v4(),
+			create_ts: now,
+			evaluation,
+		});
+	}
+
+	Ok(EvaluateOutput {
+		has_policies: true,
+		events,
+	})
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertEventsInput {
+	datacenter_id: Uuid,
+	events: Vec<Event>,
+}
+
+#[activity(InsertEvents)]
+async fn insert_events(ctx: &ActivityCtx, input: &InsertEventsInput) -> GlobalResult<()> {
+	for event in &input.events {
+		insert_event(ctx, input.datacenter_id, event).await?;
+	}
+
+	Ok(())
+}
+
+/// Does nothing if the event was already inserted.
+async fn insert_event(ctx: &ActivityCtx, datacenter_id: Uuid, event: &Event) -> GlobalResult<()> {
+	let evaluation = &event.evaluation;
+
+	sql_execute!(
+		[ctx]
+		"
+		INSERT INTO db_cluster.autoscale_events (
+			event_id,
+			datacenter_id,
+			pool_type,
+			create_ts,
+			action,
+			utilization,
+			server_count,
+			previous_desired_count,
+			desired_count,
+			expire_ts
+		)
+		VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, to_timestamp($10::FLOAT / 1000))
+		ON CONFLICT (event_id) DO NOTHING
+		",
+		event.event_id,
+		datacenter_id,
+		evaluation.pool_type as i64,
+		event.create_ts,
+		evaluation.action as i64,
+		evaluation.utilization.map(|x| x as i64),
+		evaluation.server_count as i64,
+		evaluation.previous_desired_count as i64,
+		evaluation.desired_count as i64,
+		event.create_ts + EVENT_TTL,
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+struct Event {
+	event_id: Uuid,
+	create_ts: i64,
+	evaluation: Evaluation,
+}
+
+/// Outcome of evaluating a pool, recorded in `db_cluster.autoscale_events`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub struct Evaluation {
+	pub pool_type: PoolType,
+	pub action: AutoscaleAction,
+	/// Percent.
+	pub utilization: Option<u32>,
+	pub server_count: u32,
+	pub previous_desired_count: u32,
+	/// Clamped to the pool's `min_count` and `max_count`.
+	pub desired_count: u32,
+}
+
+impl Evaluation {
+	/// Returns the pool update to apply, if any.
+	pub fn pool_update(&self) -> Option<PoolUpdate> {
+		matches!(
+			self.action,
+			AutoscaleAction::ScaleUp | AutoscaleAction::ScaleDown
+		)
+		.then(|| PoolUpdate {
+			pool_type: self.pool_type,
+			hardware: Vec::new(),
+			desired_count: Some(self.desired_count),
+			min_count: None,
+			max_count: None,
+			drain_timeout: None,
+		})
+	}
+}
+
+/// Timestamps of the last applied autoscale events of a pool.
+#[derive(Debug, Default)]
+pub struct Cooldowns {
+	pub last_scale_up_ts: Option<i64>,
+	/// Last scale up or down.
+	pub last_scale_ts: Option<i64>,
+}
+
+/// Decides the new desired count of a pool. `server_count` and `utilization` only include servers that
+/// reported usage.
+pub fn evaluate_pool(
+	policy: &AutoscalePolicy,
+	pool: &Pool,
+	server_count: u32,
+	utilization: Option<u32>,
+	cooldowns: &Cooldowns,
+	now: i64,
+) -> Evaluation {
+	let new_desired_count = utilization.and_then(|utilization| {
+		calculate_desired_count(policy, pool.desired_count, server_count, utilization)
+	});
+
+	let (action, desired_count) = if let Some(desired_count) = new_desired_count {
+		let clamped_desired_count = desired_count.max(pool.min_count).min(pool.max_count);
+
+		if clamped_desired_count == pool.desired_count {
+			(AutoscaleAction::AtLimit, clamped_desired_count)
+		} else {
+			let scale_up = clamped_desired_count > pool.desired_count;
+			let cooldown_elapsed = if scale_up {
+				cooldowns
+					.last_scale_up_ts
+					.map_or(true, |ts| now - ts >= policy.scale_up_cooldown as i64)
+			} else {
+				cooldowns
+					.last_scale_ts
+					.map_or(true, |ts| now - ts >= policy.scale_down_cooldown as i64)
+			};
+
+			if !cooldown_elapsed {
+				(AutoscaleAction::Cooldown, clamped_desired_count)
+			} else if scale_up {
+				(AutoscaleAction::ScaleUp, clamped_desired_count)
+			} else {
+				(AutoscaleAction::ScaleDown, clamped_desired_count)
+			}
+		}
+	} else {
+		(AutoscaleAction::Hold, pool.desired_count)
+	};
+
+	Evaluation {
+		pool_type: policy.pool_type,
+		action,
+		utilization,
+		server_count,
+		previous_desired_count: pool.desired_count,
+		desired_count,
+	}
+}
+
+/// Returns the utilization percent of a pool. Returns `None` if no servers reported usage.
+fn pool_utilization(pool_type: PoolType, servers: &[&topology_get::Server]) -> Option<u32> {
+	if servers.is_empty() {
+		return None;
+	}
+
+	match pool_type {
+		// Usage is the sum of reserved resources
+		PoolType::Job | PoolType::Pegboard => {
+			let (cpu, cpu_limit, memory, memory_limit) =
+				servers
+					.iter()
+					.fold((0u64, 0u64, 0u64, 0u64), |acc, server| {
+						(
+							acc.0 + server.usage.cpu as u64,
+							acc.1 + server.limits.cpu as u64,
+							acc.2 + server.usage.memory as u64,
+							acc.3 + server.limits.memory as u64,
+						)
+					});
+
+			if cpu_limit == 0 || memory_limit == 0 {
+				return None;
+			}
+
+			Some((cpu * 100 / cpu_limit).max(memory * 100 / memory_limit) as u32)
+		}
+		// Usage is already a percentage from prometheus
+		PoolType::Gg | PoolType::Ats | PoolType::PegboardIsolate => {
+			let count = servers.len() as u64;
+			let cpu = servers.iter().map(|s| s.usage.cpu as u64).sum::<u64>() / count;
+			let memory = servers.iter().map(|s| s.usage.memory as u64).sum::<u64>() / count;
+
+			Some(cpu.max(memory) as u32)
+		}
+		// FDB pools cannot be autoscaled
+		PoolType::Fdb => None,
+	}
+}
+
+/// Calculates the amount of servers required for a pool to reach its target utilization. Returns `None`
+/// if the desired count should not change.
+///
+/// The desired count is only raised when utilization is above the hysteresis band and only lowered when
+/// below it. It is not lowered while fewer than `desired_count` servers reported usage, since newly
+/// provisioned servers would otherwise be scaled down before taking any load.
+pub fn calculate_desired_count(
+	policy: &AutoscalePolicy,
+	desired_count: u32,
+	server_count: u32,
+	utilization: u32,
+) -> Option<u32> {
+	if utilization.abs_diff(policy.target_utilization) <= policy.hysteresis {
+		return None;
+	}
+
+	let required_count = (server_count as u64 * utilization as u64)
+		.div_ceil(policy.target_utilization as u64)
+		.min(u32::MAX as u64) as u32;
+
+	if utilization > policy.target_utilization {
+		(required_count > desired_count).then_some(required_count)
+	} else {
+		(server_count >= desired_count && required_count < desired_count).then_some(required_count)
+	}
+}
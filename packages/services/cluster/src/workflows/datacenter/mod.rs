@@ -2,11 +2,13 @@ use chirp_workflow::prelude::*;
 use futures_util::FutureExt;
 use std::ops::Deref;
 
+pub mod autoscale;
 pub mod scale;
 pub mod tls_issue;
 
 use crate::types::{
-	BuildDeliveryMethod, GuardPublicHostname, Pool, PoolUpdate, Provider, TlsState,
+	AutoscalePolicy, BuildDeliveryMethod, GuardPublicHostname, Pool, PoolType, PoolUpdate,
+	Provider, TlsState,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -104,6 +106,33 @@ pub(crate) async fn cluster_datacenter(ctx: &mut WorkflowCtx, input: &Input) ->
 						.output()
 						.await?;
 				}
+				Main::AutoscaleUpdate(sig) => {
+					ctx.activity(UpdateAutoscalePoliciesInput {
+						datacenter_id,
+						policies: sig.policies,
+						remove: sig.remove,
+					})
+					.await?;
+
+					// Does nothing if the autoscaler is already running
+					ctx.workflow(autoscale::Input { datacenter_id })
+						.tag("datacenter_id", datacenter_id)
+						.unique()
+						.dispatch()
+						.await?;
+				}
+				Main::Autoscale(sig) => {
+					ctx.activity(UpdateDesiredCountsInput {
+						datacenter_id,
+						pools: sig.pools,
+					})
+					.await?;
+
+					// Scale
+					ctx.workflow(scale::Input { datacenter_id })
+						.output()
+						.await?;
+				}
 				Main::TlsRenew(_) => {
 					if ctx.config().server()?.is_tls_enabled() {
 						ctx.workflow(tls_issue::Input {
@@ -267,10 +296,28 @@ pub struct Scale {}
 #[signal("cluster_datacenter_tls_renew")]
 pub struct TlsRenew {}
 
+/// Sets the autoscale policies of pools and starts the autoscaler. Invalid policies are ignored, use
+/// `AutoscalePolicy::validate` before sending.
+#[signal("cluster_datacenter_autoscale_update")]
+pub struct AutoscaleUpdate {
+	/// Policies to create or replace.
+	pub policies: Vec<AutoscalePolicy>,
+	/// Pools to stop autoscaling.
+	pub remove: Vec<PoolType>,
+}
+
+/// Sent by the autoscaler to change the desired count of pools.
+#[signal("cluster_datacenter_autoscale")]
+pub struct Autoscale {
+	pub pools: Vec<PoolUpdate>,
+}
+
 join_signal!(Main {
 	Update,
 	Scale,
 	TlsRenew,
+	AutoscaleUpdate,
+	Autoscale,
 });
 
 #[message("cluster_datacenter_create_complete")]
@@ -377,3 +424,146 @@ async fn update_db(ctx: &ActivityCtx, input: &UpdateDbInput) -> GlobalResult<()>
 
 	Ok(())
 }
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct UpdateAutoscalePoliciesInput {
+	datacenter_id: Uuid,
+	policies: Vec<AutoscalePolicy>,
+	remove: Vec<PoolType>,
+}
+
+#[activity(UpdateAutoscalePolicies)]
+async fn update_autoscale_policies(
+	ctx: &ActivityCtx,
+	input: &UpdateAutoscalePoliciesInput,
+) -> GlobalResult<()> {
+	let policies = input
+		.policies
+		.iter()
+		.filter(|policy| {
+			if let Err(err) = policy.validate() {
+				tracing::warn!(?err, pool_type=?policy.pool_type, "ignoring invalid autoscale policy");
+				false
+			} else {
+				true
+			}
+		})
+		.cloned()
+		.collect::<Vec<_>>();
+	let remove = input
+		.remove
+		.iter()
+		.map(|pool_type| *pool_type as i64)
+		.collect::<Vec<_>>();
+
+	tivet_pools::utils::crdb::tx(&ctx.crdb().await?, |tx| {
+		let ctx = ctx.clone();
+		let datacenter_id = input.datacenter_id;
+		let policies = policies.clone();
+		let remove = remove.clone();
+
+		async move {
+			for policy in policies {
+				sql_execute!(
+					[ctx, @tx tx]
+					"
+					UPSERT INTO db_cluster.autoscale_policies (
+						datacenter_id,
+						pool_type,
+						target_utilization,
+						hysteresis,
+						scale_up_cooldown,
+						scale_down_cooldown,
+						update_ts
+					)
+					VALUES ($1, $2, $3, $4, $5, $6, $7)
+					",
+					datacenter_id,
+					policy.pool_type as i64,
+					policy.target_utilization as i64,
+					policy.hysteresis as i64,
+					policy.scale_up_cooldown as i64,
+					policy.scale_down_cooldown as i64,
+					util::timestamp::now(),
+				)
+				.await?;
+			}
+
+			sql_execute!(
+				[ctx, @tx tx]
+				"
+				DELETE FROM db_cluster.autoscale_policies
+				WHERE
+					datacenter_id = $1 AND
+					pool_type = ANY($2)
+				",
+				datacenter_id,
+				remove,
+			)
+			.await?;
+
+			Ok(())
+		}
+		.boxed()
+	})
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct UpdateDesiredCountsInput {
+	datacenter_id: Uuid,
+	pools: Vec<PoolUpdate>,
+}
+
+#[activity(UpdateDesiredCounts)]
+async fn update_desired_counts(
+	ctx: &ActivityCtx,
+	input: &UpdateDesiredCountsInput,
+) -> GlobalResult<()> {
+	// Get current pools
+	let (pools,) = sql_fetch_one!(
+		[ctx, (sqlx::types::Json<Vec<Pool>>,)]
+		"
+		SELECT pools2 FROM db_cluster.datacenters
+		WHERE datacenter_id = $1
+		",
+		input.datacenter_id,
+	)
+	.await?;
+	let mut pools = pools.0;
+
+	for pool in &input.pools {
+		let (Some(current_pool), Some(desired_count)) = (
+			pools.iter_mut().find(|p| p.pool_type == pool.pool_type),
+			pool.desired_count,
+		) else {
+			continue;
+		};
+
+		// Constrain the desired count, min and max count may have changed since the autoscaler ran
+		current_pool.desired_count = desired_count
+			.max(current_pool.min_count)
+			.min(current_pool.max_count);
+	}
+
+	sql_execute!(
+		[ctx]
+		"
+		UPDATE db_cluster.datacenters
+		SET pools2 = $2
+		WHERE datacenter_id = $1
+		",
+		input.datacenter_id,
+		serde_json::to_string(&pools)?,
+	)
+	.await?;
+
+	// Purge cache
+	ctx.cache()
+		.purge("cluster.datacenters2", [input.datacenter_id])
+		.await?;
+
+	Ok(())
+}
@@ -17,7 +17,10 @@ use std::{
 use chirp_workflow::prelude::*;
 use futures_util::{FutureExt, StreamExt, TryStreamExt};
 
-use crate::types::{Datacenter, PoolType, Provider};
+use crate::{
+	provider,
+	types::{Datacenter, PoolType, Provider},
+};
 
 #[derive(sqlx::FromRow)]
 struct ServerRow {
@@ -436,14 +439,10 @@ async fn scale_down_job_servers(
 
 	let diff = nomad_servers.len().saturating_sub(pctx.desired_count);
 
-	let destroy_count = match pctx.provider {
-		Provider::Manual => unreachable!("cannot scale manual cluster"),
-		// Never destroy servers when scaling down with Linode, always drain.
-		//
-		// See _Provider Billing Internals_ in docs
-		Provider::Linode => 0,
-		#[allow(unreachable_patterns)]
-		_ => diff.min(without_nomad_servers.len()),
+	let destroy_count = if provider::get(pctx.provider).always_drain() {
+		0
+	} else {
+		diff.min(without_nomad_servers.len())
 	};
 	let drain_count = diff - destroy_count;
 
@@ -491,14 +490,10 @@ async fn scale_down_pb_servers(
 
 	let diff = pb_servers.len().saturating_sub(pctx.desired_count);
 
-	let destroy_count = match pctx.provider {
-		Provider::Manual => unreachable!("cannot scale manual cluster"),
-		// Never destroy servers when scaling down with Linode, always drain
-		//
-		// See _Provider Billing Internals_ in docs
-		Provider::Linode => 0,
-		#[allow(unreachable_patterns)]
-		_ => diff.min(without_pb_servers.len()),
+	let destroy_count = if provider::get(pctx.provider).always_drain() {
+		0
+	} else {
+		diff.min(without_pb_servers.len())
 	};
 	let drain_count = diff - destroy_count;
 
@@ -2,8 +2,9 @@ use chirp_workflow::prelude::*;
 use serde_json::json;
 
 use crate::{
+	provider,
 	types::{PoolType, Provider},
-	workflows::server::{GetDcInput, Linode},
+	workflows::server::GetDcInput,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,93 +29,21 @@ pub async fn cluster_prebake(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResu
 	let mut tags = input.tags.clone();
 	tags.push("prebake".to_string());
 
-	match input.provider {
-		Provider::Manual => {
-			// Noop
-		}
-		Provider::Linode => {
-			let workflow_id = ctx
-				.workflow(linode::workflows::server::Input {
-					server_id: prebake_server_id,
-					provider_datacenter_id: dc.provider_datacenter_id.clone(),
-					custom_image: None,
-					api_token: dc.provider_api_token.clone(),
-					hardware: linode::util::consts::PREBAKE_HARDWARE.to_string(),
-					firewall_preset: match input.pool_type {
-						PoolType::Job | PoolType::Pegboard | PoolType::PegboardIsolate => {
-							linode::types::FirewallPreset::Job
-						}
-						PoolType::Gg => linode::types::FirewallPreset::Gg,
-						PoolType::Ats => linode::types::FirewallPreset::Ats,
-						PoolType::Fdb => linode::types::FirewallPreset::Fdb,
-					},
-					vlan_ip: None,
-					vlan_ip_net: None,
-					tags,
-				})
-				.tag("server_id", prebake_server_id)
-				.dispatch()
-				.await?;
-
-			match ctx.listen::<Linode>().await? {
-				Linode::ProvisionComplete(sig) => {
-					// Install server
-					ctx.workflow(crate::workflows::server::install::Input {
-						datacenter_id: input.datacenter_id,
-						server_id: None,
-						public_ip: sig.public_ip,
-						pool_type: input.pool_type,
-						initialize_immediately: false,
-					})
-					.output()
-					.await?;
-
-					// Create image
-					let workflow_id = ctx
-						.workflow(linode::workflows::image::Input {
-							prebake_server_id,
-							api_token: dc.provider_api_token.clone(),
-							linode_id: sig.linode_id,
-							boot_disk_id: sig.boot_disk_id,
-						})
-						.tag("linode_id", sig.linode_id)
-						.dispatch()
-						.await?;
-
-					// Wait for image creation
-					let image_create_res = ctx
-						.listen::<linode::workflows::image::CreateComplete>()
-						.await?;
-
-					// Write image id to db
-					ctx.activity(UpdateDbInput {
-						provider: input.provider,
-						datacenter_id: input.datacenter_id,
-						pool_type: input.pool_type,
-						install_script_hash: input.install_script_hash.clone(),
-						image_id: image_create_res.image_id,
-					})
-					.await?;
-
-					// Destroy linode server after the image is complete
-					ctx.signal(linode::workflows::server::Destroy {})
-						.tag("server_id", prebake_server_id)
-						.send()
-						.await?;
-
-					// Wait for image workflow to get cleaned up by linode-gc after the image expires
-					ctx.wait_for_workflow::<linode::workflows::server::Workflow>(workflow_id)
-						.await?;
-				}
-				Linode::ProvisionFailed(_) => {
-					tracing::error!(
-						provision_workflow_id=%workflow_id,
-						"failed to provision prebake server"
-					);
-				}
-			}
-		}
-	}
+	provider::get(input.provider)
+		.prebake(
+			ctx,
+			&provider::PrebakeInput {
+				prebake_server_id,
+				datacenter_id: input.datacenter_id,
+				provider: input.provider,
+				provider_datacenter_id: dc.provider_datacenter_id.clone(),
+				api_token: dc.provider_api_token.clone(),
+				pool_type: input.pool_type,
+				install_script_hash: input.install_script_hash.clone(),
+				tags,
+			},
+		)
+		.await?;
 
 	ctx.activity(SetDestroyedInput {
 		provider: input.provider,
@@ -149,16 +78,16 @@ async fn generate_server_id(
 }
 
 #[derive(Debug, Serialize, Deserialize, Hash)]
-struct UpdateDbInput {
-	datacenter_id: Uuid,
-	provider: Provider,
-	pool_type: PoolType,
-	install_script_hash: String,
-	image_id: String,
+pub(crate) struct UpdateDbInput {
+	pub datacenter_id: Uuid,
+	pub provider: Provider,
+	pub pool_type: PoolType,
+	pub install_script_hash: String,
+	pub image_id: String,
 }
 
 #[activity(UpdateDb)]
-async fn update_db(ctx: &ActivityCtx, input: &UpdateDbInput) -> GlobalResult<()> {
+pub(crate) async fn update_db(ctx: &ActivityCtx, input: &UpdateDbInput) -> GlobalResult<()> {
 	sql_execute!(
 		[ctx]
 		"
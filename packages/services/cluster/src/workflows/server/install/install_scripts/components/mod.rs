@@ -43,6 +43,12 @@ pub mod lz4 {
 	}
 }
 
+pub mod zstd {
+	pub fn install() -> String {
+		"apt-get install -y zstd".to_string()
+	}
+}
+
 pub mod skopeo {
 	pub fn install() -> String {
 		"apt-get install -y skopeo".to_string()
@@ -8,7 +8,8 @@ cat << 'EOF' > /etc/tivet-client/config.json
 			"client_id": "___SERVER_ID___",
 			"datacenter_id": "___DATACENTER_ID___",
 			"api_endpoint": "__ORIGIN_API__",
-			"pegboard_endpoint": "__PEGBOARD_ENDPOINT__"
+			"pegboard_endpoint": "__PEGBOARD_ENDPOINT__",
+			"token": "___CLIENT_TOKEN___"
 		},
 		"runner": {
 			"flavor": "__FLAVOR__"
@@ -48,6 +49,65 @@ cat << 'EOF' > /etc/tivet-client/config.json
 }
 EOF
 
+# MARK: Client token refresh
+#
+# The client token is only issued once per server, renew it before it expires
+cat << 'EOF' > /usr/bin/tivet_refresh_client_token.sh
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+config=/etc/tivet-client/config.json
+token=$(jq -r '.client.cluster.token' $config)
+
+echo 'Refreshing pegboard client token'
+response=$(
+	curl -f \
+		-X POST \
+		-H "Authorization: Bearer $token" \
+		-H "Content-Type: application/json" \
+		-d '{}' \
+		"__TUNNEL_API_EDGE_API__/provision/client-tokens/refresh"
+)
+
+# Read by the client the next time it connects
+jq --arg token "$(echo $response | jq -r '.token')" '.client.cluster.token = $token' $config > $config.tmp
+mv $config.tmp $config
+EOF
+
+chmod +x /usr/bin/tivet_refresh_client_token.sh
+
+cat << 'EOF' > /etc/systemd/system/tivet_refresh_client_token.service
+[Unit]
+Description=Tivet Client Token Refresh
+Requires=network-online.target
+After=network-online.target
+
+[Service]
+User=root
+Group=root
+Type=oneshot
+ExecStart=/usr/bin/tivet_refresh_client_token.sh
+EOF
+
+cat << 'EOF' > /etc/systemd/system/tivet_refresh_client_token.timer
+[Unit]
+Description=Refreshes the pegboard client token weekly
+
+[Timer]
+OnCalendar=weekly
+Persistent=true
+# Prevent stampeding herd
+RandomizedDelaySec=3600
+Unit=tivet_refresh_client_token.service
+
+[Install]
+WantedBy=timers.target
+EOF
+
+systemctl daemon-reload
+systemctl enable tivet_refresh_client_token.timer
+systemctl start tivet_refresh_client_token.timer
+
 # Create admin chain that only accepts traffic from the GG subnet
 #
 # See Nomad equivalent: https://github.com/hashicorp/nomad/blob/a8f0f2612ef9d283ed903721f8453a0c0c3f51c5/client/allocrunner/networking_bridge_linux.go#L73
@@ -331,6 +391,10 @@ cat << EOF > /opt/cni/config/tivet-actor.conflist
 			"type": "portmap",
 			"capabilities": { "portMappings": true },
 			"snat": true
+		},
+		{
+			"type": "bandwidth",
+			"capabilities": { "bandwidth": true }
 		}
 	]
 }
@@ -25,5 +25,16 @@ sed -i "s/___CLUSTER_ID___/$cluster_id/g" $initialize_script
 sed -i "s/___VLAN_IP___/$vlan_ip/g" $initialize_script
 sed -i "s/___PUBLIC_IP___/$public_ip/g" $initialize_script
 
+# Fetch a pegboard client token bound to this server
+if grep -q "___CLIENT_TOKEN___" $initialize_script; then
+	client_token=$(
+		curl -f \
+			-H "Authorization: Bearer __SERVER_TOKEN__" \
+			"__TUNNEL_API_EDGE_API__/provision/servers/$PUBLIC_IP/client-token" \
+			| jq -r '.token'
+	)
+	sed -i "s/___CLIENT_TOKEN___/$client_token/g" $initialize_script
+fi
+
 # Run initialize script
 "$initialize_script"
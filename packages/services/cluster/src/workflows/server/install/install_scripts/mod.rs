@@ -37,6 +37,7 @@ pub async fn gen_install(
 		PoolType::Job => {
 			script.push(components::docker::install());
 			script.push(components::lz4::install());
+			script.push(components::zstd::install());
 			script.push(components::skopeo::install());
 			script.push(components::umoci::install());
 			script.push(components::cni::tool());
@@ -59,6 +60,7 @@ pub async fn gen_install(
 		PoolType::Pegboard | PoolType::PegboardIsolate => {
 			script.push(components::docker::install());
 			script.push(components::lz4::install());
+			script.push(components::zstd::install());
 			script.push(components::skopeo::install());
 			script.push(components::umoci::install());
 			script.push(components::cni::tool());
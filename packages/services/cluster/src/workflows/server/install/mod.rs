@@ -5,7 +5,6 @@ use std::{
 };
 
 use chirp_workflow::prelude::*;
-use tivet_operation::prelude::proto::{self, backend::pkg::token};
 use ssh2::Session;
 
 use crate::{
@@ -76,33 +75,18 @@ pub(crate) async fn cluster_server_install(
 struct CreateTokenInput {}
 
 #[activity(CreateToken)]
-async fn create_token(ctx: &ActivityCtx, input: &CreateTokenInput) -> GlobalResult<String> {
-	// Create server token for authenticating API calls from the server
-	let token_res = op!([ctx] token_create {
-		token_config: Some(token::create::request::TokenConfig {
-			ttl: crate::util::SERVER_TOKEN_TTL,
-		}),
-		refresh_token_config: None,
-		issuer: "cluster-worker-server-install".to_owned(),
-		client: None,
-		kind: Some(token::create::request::Kind::New(token::create::request::KindNew {
-			entitlements: vec![
-				proto::claims::Entitlement {
-					kind: Some(
-						proto::claims::entitlement::Kind::ProvisionedServer(
-							proto::claims::entitlement::ProvisionedServer {}
-						)
-					)
-				}
-			],
-		})),
-		label: Some("srv".to_owned()),
-		..Default::default()
-	})
-	.await?;
-	let server_token = unwrap_ref!(token_res.token).token.clone();
+async fn create_token(ctx: &ActivityCtx, _input: &CreateTokenInput) -> GlobalResult<String> {
+	// Create server token for authenticating API calls from the server. This token is shared by every server
+	// created from the same prebake image so it cannot be bound to a pegboard client, see the
+	// `client-token` provision route.
+	let token_res = ctx
+		.op(crate::ops::server::token_create::Input {
+			issuer: "cluster-worker-server-install".to_owned(),
+			server_id: None,
+		})
+		.await?;
 
-	Ok(server_token)
+	Ok(token_res.token)
 }
 
 #[derive(Debug, Serialize, Deserialize, Hash)]
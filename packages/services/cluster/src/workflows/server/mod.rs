@@ -15,7 +15,7 @@ pub(crate) mod install;
 pub(crate) mod undrain;
 
 use crate::{
-	metrics,
+	metrics, provider,
 	types::{Pool, PoolType, Provider},
 };
 
@@ -101,7 +101,7 @@ pub(crate) async fn cluster_server(ctx: &mut WorkflowCtx, input: &Input) -> Glob
 async fn provision_server(
 	ctx: &mut WorkflowCtx,
 	input: &Input2,
-) -> GlobalResult<(GetDcOutput, Uuid)> {
+) -> GlobalResult<(GetDcOutput, Option<Uuid>)> {
 	let dc = ctx
 		.activity(GetDcInput {
 			datacenter_id: input.datacenter_id,
@@ -148,7 +148,8 @@ async fn provision_server(
 	} else {
 		None
 	};
-	let already_installed = custom_image.is_some();
+	let already_installed =
+		custom_image.is_some() || !provider::get(dc.provider).requires_install();
 
 	// Iterate through list of hardware and attempt to schedule a server. Goes to the next
 	// hardware if an error happens during provisioning
@@ -164,52 +165,31 @@ async fn provision_server(
 			hardware.provider_hardware,
 		);
 
-		match dc.provider {
-			Provider::Manual => {
-				// Noop
-			}
-			Provider::Linode => {
-				let workflow_id = ctx
-					.workflow(linode::workflows::server::Input {
-						server_id: input.server_id,
-						provider_datacenter_id: dc.provider_datacenter_id.clone(),
-						custom_image: custom_image.clone(),
-						api_token: dc.provider_api_token.clone(),
-						hardware: hardware.provider_hardware.clone(),
-						firewall_preset: match input.pool_type {
-							PoolType::Job | PoolType::Pegboard | PoolType::PegboardIsolate => {
-								linode::types::FirewallPreset::Job
-							}
-							PoolType::Gg => linode::types::FirewallPreset::Gg,
-							PoolType::Ats => linode::types::FirewallPreset::Ats,
-							PoolType::Fdb => linode::types::FirewallPreset::Fdb,
-						},
-						vlan_ip: Some(vlan_ip.ip()),
-						vlan_ip_net: Some(vlan_ip.ip_net()),
-						tags: input.tags.clone(),
-					})
-					.tag("server_id", input.server_id)
-					.dispatch()
-					.await?;
-
-				match ctx.listen::<Linode>().await? {
-					Linode::ProvisionComplete(sig) => {
-						break Some(ProvisionResponse {
-							provider_server_workflow_id: workflow_id,
-							provider_server_id: sig.linode_id.to_string(),
-							provider_hardware: hardware.provider_hardware.clone(),
-							public_ip: sig.public_ip,
-						});
-					}
-					Linode::ProvisionFailed(_) => {
-						tracing::error!(
-							provision_workflow_id=%workflow_id,
-							server_id=?input.server_id,
-							"failed to provision server"
-						);
-					}
-				}
-			}
+		let create_res = provider::get(dc.provider)
+			.create(
+				ctx,
+				&provider::CreateInput {
+					server_id: input.server_id,
+					datacenter_id: input.datacenter_id,
+					provider_datacenter_id: dc.provider_datacenter_id.clone(),
+					api_token: dc.provider_api_token.clone(),
+					pool_type: input.pool_type,
+					hardware: hardware.provider_hardware.clone(),
+					custom_image: custom_image.clone(),
+					vlan_ip: Some(vlan_ip.ip()),
+					vlan_ip_net: Some(vlan_ip.ip_net()),
+					tags: input.tags.clone(),
+				},
+			)
+			.await?;
+
+		if let Some(create_res) = create_res {
+			break Some(ProvisionResponse {
+				provider_server_workflow_id: create_res.provider_server_workflow_id,
+				provider_server_id: create_res.provider_server_id,
+				provider_hardware: hardware.provider_hardware.clone(),
+				public_ip: create_res.public_ip,
+			});
 		}
 	};
 
@@ -629,7 +609,7 @@ async fn get_prebake(ctx: &ActivityCtx, input: &GetPrebakeInput) -> GlobalResult
 
 #[derive(Debug, Serialize, Deserialize, Hash)]
 struct ProvisionResponse {
-	provider_server_workflow_id: Uuid,
+	provider_server_workflow_id: Option<Uuid>,
 	provider_server_id: String,
 	provider_hardware: String,
 	public_ip: Ipv4Addr,
@@ -869,7 +849,7 @@ async fn cleanup(
 	ctx: &mut WorkflowCtx,
 	input: &Input2,
 	provider: &Provider,
-	provider_server_workflow_id: Uuid,
+	provider_server_workflow_id: Option<Uuid>,
 	cleanup_dns: bool,
 ) -> GlobalResult<()> {
 	if cleanup_dns {
@@ -884,25 +864,15 @@ async fn cleanup(
 	}
 
 	// Cleanup server
-	match provider {
-		Provider::Manual => {
-			// Noop
-		}
-		Provider::Linode => {
-			tracing::info!(server_id=?input.server_id, "destroying linode server");
-
-			ctx.signal(linode::workflows::server::Destroy {})
-				.to_workflow(provider_server_workflow_id)
-				.send()
-				.await?;
-
-			// Wait for workflow to complete
-			ctx.wait_for_workflow::<linode::workflows::server::Workflow>(
+	provider::get(*provider)
+		.destroy(
+			ctx,
+			&provider::DestroyInput {
+				server_id: input.server_id,
 				provider_server_workflow_id,
-			)
-			.await?;
-		}
-	}
+			},
+		)
+		.await?;
 
 	Ok(())
 }
@@ -1008,14 +978,6 @@ impl Default for State {
 	}
 }
 
-// Listen for linode provision signals
-type ProvisionComplete = linode::workflows::server::ProvisionComplete;
-type ProvisionFailed = linode::workflows::server::ProvisionFailed;
-join_signal!(pub(crate) Linode {
-	ProvisionComplete,
-	ProvisionFailed,
-});
-
 #[signal("cluster_server_drain")]
 pub struct Drain {}
 
@@ -0,0 +1,216 @@
+use cluster::{
+	types::{AutoscaleAction, AutoscalePolicy, Hardware, Pool, PoolType},
+	workflows::datacenter::autoscale::{calculate_desired_count, evaluate_pool, Cooldowns},
+};
+
+const NOW: i64 = 1_000_000_000;
+
+fn policy() -> AutoscalePolicy {
+	AutoscalePolicy {
+		pool_type: PoolType::Pegboard,
+		target_utilization: 70,
+		hysteresis: 10,
+		scale_up_cooldown: 0,
+		scale_down_cooldown: 0,
+	}
+}
+
+fn pool(desired_count: u32, min_count: u32, max_count: u32) -> Pool {
+	Pool {
+		pool_type: PoolType::Pegboard,
+		hardware: vec![Hardware {
+			provider_hardware: "test".into(),
+		}],
+		desired_count,
+		min_count,
+		max_count,
+		drain_timeout: 0,
+	}
+}
+
+#[test]
+fn autoscale_within_hysteresis() {
+	assert_eq!(None, calculate_desired_count(&policy(), 4, 4, 62));
+	assert_eq!(None, calculate_desired_count(&policy(), 4, 4, 80));
+}
+
+#[test]
+fn autoscale_up() {
+	// 4 servers at 95% need 6 servers to be at 70%
+	assert_eq!(Some(6), calculate_desired_count(&policy(), 4, 4, 95));
+
+	// Already scaling up, waiting for servers to provision
+	assert_eq!(None, calculate_desired_count(&policy(), 6, 4, 95));
+}
+
+#[test]
+fn autoscale_down() {
+	// 4 servers at 20% need 2 servers to be at 70%
+	assert_eq!(Some(2), calculate_desired_count(&policy(), 4, 4, 20));
+	assert_eq!(Some(0), calculate_desired_count(&policy(), 4, 4, 0));
+}
+
+#[test]
+fn autoscale_down_waits_for_servers() {
+	// 2 of the 4 desired servers have not reported usage yet
+	assert_eq!(None, calculate_desired_count(&policy(), 4, 2, 20));
+}
+
+#[test]
+fn evaluate_hold() {
+	let evaluation = evaluate_pool(
+		&policy(),
+		&pool(4, 0, 10),
+		4,
+		Some(70),
+		&Cooldowns::default(),
+		NOW,
+	);
+	assert_eq!(AutoscaleAction::Hold, evaluation.action);
+	assert_eq!(4, evaluation.desired_count);
+	assert!(evaluation.pool_update().is_none());
+
+	// No server reported usage
+	let evaluation = evaluate_pool(
+		&policy(),
+		&pool(4, 0, 10),
+		0,
+		None,
+		&Cooldowns::default(),
+		NOW,
+	);
+	assert_eq!(AutoscaleAction::Hold, evaluation.action);
+}
+
+#[test]
+fn evaluate_scale_up() {
+	let evaluation = evaluate_pool(
+		&policy(),
+		&pool(4, 0, 10),
+		4,
+		Some(95),
+		&Cooldowns::default(),
+		NOW,
+	);
+	assert_eq!(AutoscaleAction::ScaleUp, evaluation.action);
+	assert_eq!(Some(95), evaluation.utilization);
+	assert_eq!(4, evaluation.server_count);
+	assert_eq!(4, evaluation.previous_desired_count);
+	assert_eq!(6, evaluation.desired_count);
+
+	let update = evaluation.pool_update().unwrap();
+	assert_eq!(PoolType::Pegboard, update.pool_type);
+	assert_eq!(Some(6), update.desired_count);
+	assert!(update.hardware.is_empty() && update.min_count.is_none() && update.max_count.is_none());
+}
+
+#[test]
+fn evaluate_clamps() {
+	// Needs 6 servers, clamped to the max count
+	let evaluation = evaluate_pool(
+		&policy(),
+		&pool(4, 0, 5),
+		4,
+		Some(95),
+		&Cooldowns::default(),
+		NOW,
+	);
+	assert_eq!(AutoscaleAction::ScaleUp, evaluation.action);
+	assert_eq!(5, evaluation.desired_count);
+
+	// Needs 2 servers, clamped to the min count
+	let evaluation = evaluate_pool(
+		&policy(),
+		&pool(4, 3, 10),
+		4,
+		Some(20),
+		&Cooldowns::default(),
+		NOW,
+	);
+	assert_eq!(AutoscaleAction::ScaleDown, evaluation.action);
+	assert_eq!(3, evaluation.desired_count);
+}
+
+#[test]
+fn evaluate_at_limit() {
+	// Records the clamped desired count
+	let evaluation = evaluate_pool(
+		&policy(),
+		&pool(5, 0, 5),
+		5,
+		Some(95),
+		&Cooldowns::default(),
+		NOW,
+	);
+	assert_eq!(AutoscaleAction::AtLimit, evaluation.action);
+	assert_eq!(5, evaluation.desired_count);
+	assert!(evaluation.pool_update().is_none());
+
+	let evaluation = evaluate_pool(
+		&policy(),
+		&pool(2, 2, 10),
+		2,
+		Some(10),
+		&Cooldowns::default(),
+		NOW,
+	);
+	assert_eq!(AutoscaleAction::AtLimit, evaluation.action);
+	assert_eq!(2, evaluation.desired_count);
+}
+
+#[test]
+fn evaluate_scale_up_cooldown() {
+	let policy = AutoscalePolicy {
+		scale_up_cooldown: 60_000,
+		..policy()
+	};
+
+	let cooldowns = Cooldowns {
+		last_scale_up_ts: Some(NOW - 30_000),
+		last_scale_ts: Some(NOW - 30_000),
+	};
+	let evaluation = evaluate_pool(&policy, &pool(4, 0, 10), 4, Some(95), &cooldowns, NOW);
+	assert_eq!(AutoscaleAction::Cooldown, evaluation.action);
+	assert_eq!(6, evaluation.desired_count);
+	assert!(evaluation.pool_update().is_none());
+
+	let cooldowns = Cooldowns {
+		last_scale_up_ts: Some(NOW - 60_000),
+		last_scale_ts: Some(NOW - 60_000),
+	};
+	let evaluation = evaluate_pool(&policy, &pool(4, 0, 10), 4, Some(95), &cooldowns, NOW);
+	assert_eq!(AutoscaleAction::ScaleUp, evaluation.action);
+
+	// Only scale ups count towards the scale up cooldown
+	let cooldowns = Cooldowns {
+		last_scale_up_ts: None,
+		last_scale_ts: Some(NOW - 1),
+	};
+	let evaluation = evaluate_pool(&policy, &pool(4, 0, 10), 4, Some(95), &cooldowns, NOW);
+	assert_eq!(AutoscaleAction::ScaleUp, evaluation.action);
+}
+
+#[test]
+fn evaluate_scale_down_cooldown() {
+	let policy = AutoscalePolicy {
+		scale_down_cooldown: 60_000,
+		..policy()
+	};
+
+	// Any scale counts towards the scale down cooldown
+	let cooldowns = Cooldowns {
+		last_scale_up_ts: Some(NOW - 30_000),
+		last_scale_ts: Some(NOW - 30_000),
+	};
+	let evaluation = evaluate_pool(&policy, &pool(4, 0, 10), 4, Some(20), &cooldowns, NOW);
+	assert_eq!(AutoscaleAction::Cooldown, evaluation.action);
+	assert_eq!(2, evaluation.desired_count);
+
+	let cooldowns = Cooldowns {
+		last_scale_up_ts: None,
+		last_scale_ts: Some(NOW - 60_000),
+	};
+	let evaluation = evaluate_pool(&policy, &pool(4, 0, 10), 4, Some(20), &cooldowns, NOW);
+	assert_eq!(AutoscaleAction::ScaleDown, evaluation.action);
+	assert_eq!(2, evaluation.desired_count);
+}
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use chirp_workflow::prelude::*;
 use serde_json::json;
 
@@ -96,3 +98,150 @@ pub async fn setup(ctx: &TestCtx, opts: Setup) -> SetupRes {
 
 	SetupRes { pools, provider }
 }
+
+pub struct SetupLocal {
+	pub datacenter_id: Uuid,
+	pub cluster_id: Uuid,
+	pub pool_type: cluster::types::PoolType,
+	pub max_count: u32,
+	pub drain_timeout: u64,
+}
+
+/// Creates a cluster with an empty datacenter that uses the `Local` provider. Requires
+/// `server.local_provider` to be configured.
+pub async fn setup_local(ctx: &TestCtx, opts: SetupLocal) {
+	let mut sub = ctx
+		.subscribe::<cluster::workflows::cluster::CreateComplete>(&json!({
+			"cluster_id": opts.cluster_id,
+		}))
+		.await
+		.unwrap();
+
+	ctx.workflow(cluster::workflows::cluster::Input {
+		cluster_id: opts.cluster_id,
+		name_id: util::faker::ident(),
+		owner_team_id: None,
+	})
+	.tag("cluster_id", opts.cluster_id)
+	.dispatch()
+	.await
+	.unwrap();
+
+	sub.next().await.unwrap();
+
+	let mut sub = ctx
+		.subscribe::<cluster::workflows::datacenter::CreateComplete>(&json!({
+			"datacenter_id": opts.datacenter_id,
+		}))
+		.await
+		.unwrap();
+
+	ctx.signal(cluster::workflows::cluster::DatacenterCreate {
+		datacenter_id: opts.datacenter_id,
+		name_id: util::faker::ident(),
+		display_name: util::faker::ident(),
+
+		provider: cluster::types::Provider::Local,
+		provider_datacenter_id: "local".to_string(),
+		provider_api_token: None,
+
+		pools: vec![cluster::types::Pool {
+			pool_type: opts.pool_type,
+			hardware: vec![cluster::types::Hardware {
+				provider_hardware: "local".to_string(),
+			}],
+			desired_count: 0,
+			min_count: 0,
+			max_count: opts.max_count,
+			drain_timeout: opts.drain_timeout,
+		}],
+
+		build_delivery_method: cluster::types::BuildDeliveryMethod::TrafficServer,
+		prebakes_enabled: false,
+	})
+	.tag("cluster_id", opts.cluster_id)
+	.send()
+	.await
+	.unwrap();
+
+	sub.next().await.unwrap();
+}
+
+/// Sets the desired count of the datacenter's pool.
+pub async fn scale(
+	ctx: &TestCtx,
+	datacenter_id: Uuid,
+	pool_type: cluster::types::PoolType,
+	desired_count: u32,
+) {
+	ctx.signal(cluster::workflows::datacenter::Update {
+		pools: vec![cluster::types::PoolUpdate {
+			pool_type,
+			hardware: Vec::new(),
+			desired_count: Some(desired_count),
+			min_count: None,
+			max_count: None,
+			drain_timeout: None,
+		}],
+		prebakes_enabled: None,
+		guard_public_hostname: None,
+	})
+	.tag("datacenter_id", datacenter_id)
+	.send()
+	.await
+	.unwrap();
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ServerRow {
+	pub server_id: Uuid,
+	pub public_ip: Option<String>,
+	pub drain_ts: Option<i64>,
+	pub taint_ts: Option<i64>,
+	pub cloud_destroy_ts: Option<i64>,
+}
+
+impl ServerRow {
+	pub fn is_provisioned(&self) -> bool {
+		self.public_ip.is_some() && self.cloud_destroy_ts.is_none()
+	}
+}
+
+/// Polls the datacenter's servers until `f` returns true. Panics after 2 minutes.
+pub async fn wait_for_servers<F>(ctx: &TestCtx, datacenter_id: Uuid, f: F) -> Vec<ServerRow>
+where
+	F: Fn(&[ServerRow]) -> bool,
+{
+	let start = std::time::Instant::now();
+
+	loop {
+		let servers = sql_fetch_all!(
+			[ctx, ServerRow]
+			"
+			SELECT
+				server_id,
+				public_ip::STRING AS public_ip,
+				drain_ts,
+				taint_ts,
+				cloud_destroy_ts
+			FROM db_cluster.servers
+			WHERE datacenter_id = $1
+			ORDER BY create_ts
+			",
+			datacenter_id,
+		)
+		.await
+		.unwrap();
+
+		if f(&servers) {
+			return servers;
+		}
+
+		assert!(
+			start.elapsed() < std::time::Duration::from_secs(120),
+			"timed out waiting for servers: {servers:#?}"
+		);
+
+		tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+	}
+}
@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+
+use chirp_workflow::prelude::*;
+
+mod common;
+use common::{scale, setup_local, wait_for_servers, SetupLocal};
+
+const POOL_TYPE: cluster::types::PoolType = cluster::types::PoolType::Ats;
+
+fn local_provider_enabled(ctx: &TestCtx) -> bool {
+	ctx.config().server().unwrap().local_provider.is_some()
+}
+
+#[workflow_test]
+async fn local_scale(ctx: TestCtx) {
+	if !local_provider_enabled(&ctx) {
+		return;
+	}
+
+	let datacenter_id = Uuid::new_v4();
+	setup_local(
+		&ctx,
+		SetupLocal {
+			datacenter_id,
+			cluster_id: Uuid::new_v4(),
+			pool_type: POOL_TYPE,
+			max_count: 2,
+			drain_timeout: 0,
+		},
+	)
+	.await;
+
+	// Scale up
+	scale(&ctx, datacenter_id, POOL_TYPE, 2).await;
+	let servers = wait_for_servers(&ctx, datacenter_id, |servers| {
+		servers.iter().filter(|s| s.is_provisioned()).count() == 2
+	})
+	.await;
+	assert_eq!(2, servers.len(), "created too many servers");
+
+	let public_ips = servers
+		.iter()
+		.filter_map(|s| s.public_ip.as_ref())
+		.collect::<HashSet<_>>();
+	assert_eq!(2, public_ips.len(), "public ips not unique");
+
+	// Scale down
+	scale(&ctx, datacenter_id, POOL_TYPE, 0).await;
+	wait_for_servers(&ctx, datacenter_id, |servers| {
+		servers.iter().all(|s| s.cloud_destroy_ts.is_some())
+	})
+	.await;
+}
+
+#[workflow_test]
+async fn local_drain(ctx: TestCtx) {
+	if !local_provider_enabled(&ctx) {
+		return;
+	}
+
+	let drain_timeout = 5000;
+	let datacenter_id = Uuid::new_v4();
+	setup_local(
+		&ctx,
+		SetupLocal {
+			datacenter_id,
+			cluster_id: Uuid::new_v4(),
+			pool_type: POOL_TYPE,
+			max_count: 1,
+			drain_timeout,
+		},
+	)
+	.await;
+
+	scale(&ctx, datacenter_id, POOL_TYPE, 1).await;
+	wait_for_servers(&ctx, datacenter_id, |servers| {
+		servers.iter().any(|s| s.is_provisioned())
+	})
+	.await;
+
+	// Scaling down drains the server before destroying it
+	scale(&ctx, datacenter_id, POOL_TYPE, 0).await;
+	let servers = wait_for_servers(&ctx, datacenter_id, |servers| {
+		servers.iter().all(|s| s.cloud_destroy_ts.is_some())
+	})
+	.await;
+
+	let server = servers.first().unwrap();
+	let drain_ts = server.drain_ts.expect("server was not drained");
+	assert!(
+		server.cloud_destroy_ts.unwrap() >= drain_ts + drain_timeout as i64,
+		"server destroyed before the drain timeout"
+	);
+}
+
+#[workflow_test]
+async fn local_taint(ctx: TestCtx) {
+	if !local_provider_enabled(&ctx) {
+		return;
+	}
+
+	let datacenter_id = Uuid::new_v4();
+	setup_local(
+		&ctx,
+		SetupLocal {
+			datacenter_id,
+			cluster_id: Uuid::new_v4(),
+			pool_type: POOL_TYPE,
+			max_count: 2,
+			drain_timeout: 0,
+		},
+	)
+	.await;
+
+	scale(&ctx, datacenter_id, POOL_TYPE, 1).await;
+	let servers = wait_for_servers(&ctx, datacenter_id, |servers| {
+		servers.iter().any(|s| s.is_provisioned())
+	})
+	.await;
+	let tainted_server_id = servers.first().unwrap().server_id;
+
+	ctx.op(cluster::ops::server::taint_with_filter::Input {
+		filter: cluster::types::Filter {
+			server_ids: Some(vec![tainted_server_id]),
+			..Default::default()
+		},
+	})
+	.await
+	.unwrap();
+
+	// The tainted server is replaced and destroyed once the replacement is up
+	let servers = wait_for_servers(&ctx, datacenter_id, |servers| {
+		servers
+			.iter()
+			.any(|s| s.server_id == tainted_server_id && s.cloud_destroy_ts.is_some())
+			&& servers
+				.iter()
+				.any(|s| s.server_id != tainted_server_id && s.is_provisioned())
+	})
+	.await;
+
+	let tainted_server = servers
+		.iter()
+		.find(|s| s.server_id == tainted_server_id)
+		.unwrap();
+	assert!(tainted_server.taint_ts.is_some());
+	assert!(servers
+		.iter()
+		.filter(|s| s.server_id != tainted_server_id)
+		.all(|s| s.taint_ts.is_none()));
+
+	// Clean up afterwards so we don't litter
+	scale(&ctx, datacenter_id, POOL_TYPE, 0).await;
+	wait_for_servers(&ctx, datacenter_id, |servers| {
+		servers.iter().all(|s| s.cloud_destroy_ts.is_some())
+	})
+	.await;
+}
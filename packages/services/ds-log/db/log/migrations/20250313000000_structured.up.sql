@@ -0,0 +1,4 @@
+ALTER TABLE server_logs
+    ADD COLUMN IF NOT EXISTS level LowCardinality (String) DEFAULT '', -- Empty if the line was not JSON or had no level
+    ADD COLUMN IF NOT EXISTS fields Map (String, String),
+    ADD INDEX IF NOT EXISTS idx_level level TYPE set (16) GRANULARITY 4;
@@ -6,6 +6,8 @@ struct LogEntry {
 	// In nanoseconds
 	ts: i64,
 	message: Vec<u8>,
+	level: String,
+	fields: Vec<(String, String)>,
 }
 
 #[operation(name = "ds-log-read")]
@@ -51,17 +53,21 @@ async fn query_all(
 	run_id: Uuid,
 	order_by: &str,
 ) -> GlobalResult<Vec<backend::ds::log::LogEntry>> {
-	let mut entries_cursor = clickhouse
+	let filters = filter_conditions(req);
+	let query = clickhouse
 		.query(&formatdoc!(
 			"
 			SELECT
 				ts,
-				message
+				message,
+				level,
+				fields
 			FROM
 				db_ds_log.server_logs
 			WHERE
 				server_id = ?
 				AND stream_type = ?
+				{filters}
 			ORDER BY
 				ts {order_by}
 			LIMIT
@@ -69,7 +75,8 @@ async fn query_all(
 			"
 		))
 		.bind(run_id)
-		.bind(req.stream_type as u8)
+		.bind(req.stream_type as u8);
+	let mut entries_cursor = bind_filters(query, req)
 		.bind(req.count)
 		.fetch::<LogEntry>()?;
 
@@ -88,22 +95,25 @@ async fn query_before_nts(
 	nts: i64,
 	order_by: &str,
 ) -> GlobalResult<Vec<backend::ds::log::LogEntry>> {
-	let mut entries_cursor = clickhouse
+	let filters = filter_conditions(req);
+	let query = clickhouse
 		.query(&formatdoc!(
 			"
-			SELECT ts, message
+			SELECT ts, message, level, fields
 			FROM db_ds_log.server_logs
 			WHERE
 				server_id = ? AND
 				stream_type = ? AND
 				ts < fromUnixTimestamp64Nano(?)
+				{filters}
 			ORDER BY ts {order_by}
 			LIMIT ?
 			"
 		))
 		.bind(run_id)
 		.bind(req.stream_type as u8)
-		.bind(nts)
+		.bind(nts);
+	let mut entries_cursor = bind_filters(query, req)
 		.bind(req.count)
 		.fetch::<LogEntry>()?;
 
@@ -122,22 +132,25 @@ async fn query_after_nts(
 	nts: i64,
 	order_by: &str,
 ) -> GlobalResult<Vec<backend::ds::log::LogEntry>> {
-	let mut entries_cursor = clickhouse
+	let filters = filter_conditions(req);
+	let query = clickhouse
 		.query(&formatdoc!(
 			"
-			SELECT ts, message
+			SELECT ts, message, level, fields
 			FROM db_ds_log.server_logs
 			WHERE
 				server_id = ? AND
 				stream_type = ? AND
 				ts > fromUnixTimestamp64Nano(?)
+				{filters}
 			ORDER BY ts {order_by}
 			LIMIT ?
 			"
 		))
 		.bind(run_id)
 		.bind(req.stream_type as u8)
-		.bind(nts)
+		.bind(nts);
+	let mut entries_cursor = bind_filters(query, req)
 		.bind(req.count)
 		.fetch::<LogEntry>()?;
 
@@ -157,16 +170,18 @@ async fn query_nts_range(
 	before_nts: i64,
 	order_by: &str,
 ) -> GlobalResult<Vec<backend::ds::log::LogEntry>> {
-	let mut entries_cursor = clickhouse
+	let filters = filter_conditions(req);
+	let query = clickhouse
 		.query(&formatdoc!(
 			"
-			SELECT ts, message
+			SELECT ts, message, level, fields
 			FROM db_ds_log.server_logs
 			WHERE
 				run_id = ? AND
 				stream_type = ? AND
 				ts > fromUnixTimestamp64Nano(?) AND
 				ts < fromUnixTimestamp64Nano(?)
+				{filters}
 			ORDER BY ts {order_by}
 			LIMIT ?
 			"
@@ -174,7 +189,8 @@ async fn query_nts_range(
 		.bind(run_id)
 		.bind(req.stream_type as u8)
 		.bind(after_nts)
-		.bind(before_nts)
+		.bind(before_nts);
+	let mut entries_cursor = bind_filters(query, req)
 		.bind(req.count)
 		.fetch::<LogEntry>()?;
 
@@ -186,9 +202,45 @@ async fn query_nts_range(
 	Ok(entries)
 }
 
+/// Conditions for the optional filters in the request. Values are bound with `bind_filters`.
+fn filter_conditions(req: &ds_log::read::Request) -> String {
+	let mut filters = String::new();
+
+	if !req.level.is_empty() {
+		filters.push_str("AND level = ? ");
+	}
+	for _ in &req.fields {
+		filters.push_str("AND fields[?] = ? ");
+	}
+	if !req.search.is_empty() {
+		filters.push_str("AND positionCaseInsensitiveUTF8(message, ?) > 0 ");
+	}
+
+	filters
+}
+
+fn bind_filters(
+	mut query: clickhouse::query::Query,
+	req: &ds_log::read::Request,
+) -> clickhouse::query::Query {
+	if !req.level.is_empty() {
+		query = query.bind(&req.level);
+	}
+	for (key, value) in &req.fields {
+		query = query.bind(key).bind(value);
+	}
+	if !req.search.is_empty() {
+		query = query.bind(&req.search);
+	}
+
+	query
+}
+
 fn convert_entry(entry: LogEntry) -> backend::ds::log::LogEntry {
 	backend::ds::log::LogEntry {
 		nts: entry.ts,
 		message: entry.message,
+		level: entry.level,
+		fields: entry.fields.into_iter().collect(),
 	}
 }
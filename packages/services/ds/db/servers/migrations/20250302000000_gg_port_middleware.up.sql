@@ -0,0 +1,8 @@
+CREATE TABLE server_ports_gg_middleware (
+	server_id UUID NOT NULL,
+	port_name TEXT NOT NULL,
+	config JSONB NOT NULL,
+
+	FOREIGN KEY (server_id, port_name) REFERENCES server_ports_gg (server_id, port_name),
+	PRIMARY KEY (server_id, port_name)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE servers
+	ADD COLUMN resources_limits JSONB NOT NULL DEFAULT '{}';
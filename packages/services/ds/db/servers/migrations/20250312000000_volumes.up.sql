@@ -0,0 +1,42 @@
+CREATE TABLE volumes (
+	volume_id UUID PRIMARY KEY,
+	env_id UUID NOT NULL,
+	name TEXT NOT NULL,
+	size_mib INT NOT NULL,
+	create_ts INT NOT NULL,
+	delete_ts INT,
+
+	UNIQUE INDEX (env_id, name) WHERE delete_ts IS NULL,
+	INDEX (env_id, create_ts DESC)
+);
+
+CREATE TABLE volume_snapshots (
+	snapshot_id UUID PRIMARY KEY,
+	volume_id UUID NOT NULL REFERENCES volumes,
+	create_ts INT NOT NULL,
+	complete_ts INT,
+	failed_ts INT,
+	-- Bytes of the compressed archive
+	size INT,
+
+	INDEX (volume_id, complete_ts DESC)
+);
+
+-- Pegboard clients that have a copy of the volume
+CREATE TABLE volume_clients (
+	volume_id UUID NOT NULL REFERENCES volumes,
+	client_id UUID NOT NULL,
+	update_ts INT NOT NULL,
+
+	PRIMARY KEY (volume_id, client_id),
+	INDEX (volume_id, update_ts DESC)
+);
+
+CREATE TABLE server_volumes (
+	server_id UUID NOT NULL REFERENCES servers,
+	volume_id UUID NOT NULL REFERENCES volumes,
+	path TEXT NOT NULL,
+
+	PRIMARY KEY (server_id, volume_id),
+	INDEX (volume_id)
+);
@@ -0,0 +1,25 @@
+CREATE TABLE schedules (
+	schedule_id UUID PRIMARY KEY,
+	env_id UUID NOT NULL,
+	cron TEXT NOT NULL,
+	build_tags JSONB NOT NULL,
+	template JSONB NOT NULL, -- ds::types::ActorTemplate
+	concurrency_policy INT NOT NULL, -- ds::types::ConcurrencyPolicy
+	create_ts INT NOT NULL,
+	delete_ts INT,
+
+	INDEX (env_id, create_ts DESC)
+);
+
+CREATE TABLE schedule_runs (
+	run_id UUID PRIMARY KEY,
+	schedule_id UUID NOT NULL REFERENCES schedules,
+	scheduled_ts INT NOT NULL,
+	create_ts INT NOT NULL,
+	-- Unset if the run was skipped
+	server_id UUID,
+	skip_reason INT, -- ds::types::ScheduleRunSkipReason
+
+	UNIQUE INDEX (schedule_id, scheduled_ts DESC),
+	INDEX (server_id)
+);
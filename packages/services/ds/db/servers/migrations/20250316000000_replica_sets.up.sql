@@ -0,0 +1,26 @@
+CREATE TABLE replica_sets (
+	replica_set_id UUID PRIMARY KEY,
+	env_id UUID NOT NULL,
+	template JSONB NOT NULL, -- ds::types::ActorTemplate
+	image_id UUID NOT NULL,
+	replicas INT NOT NULL,
+	rolling_update JSONB NOT NULL, -- ds::types::RollingUpdatePolicy
+	create_ts INT NOT NULL,
+	delete_ts INT,
+
+	INDEX (env_id, create_ts DESC)
+);
+
+CREATE TABLE replica_set_servers (
+	replica_set_id UUID NOT NULL REFERENCES replica_sets,
+	-- Incremented for each server created by the replica set
+	idx INT NOT NULL,
+	server_id UUID NOT NULL,
+	image_id UUID NOT NULL,
+	create_ts INT NOT NULL,
+	-- Set once the replica set destroys the server
+	destroy_ts INT,
+
+	PRIMARY KEY (replica_set_id, idx),
+	UNIQUE INDEX (server_id)
+);
@@ -0,0 +1,18 @@
+CREATE TABLE secrets (
+	env_id UUID NOT NULL,
+	name TEXT NOT NULL,
+	-- Incremented each time the value is rotated
+	version INT NOT NULL,
+	-- AES-256-GCM nonce followed by the ciphertext
+	value BYTES NOT NULL,
+	-- ID of the key the value was encrypted with
+	kid TEXT NOT NULL,
+	create_ts INT NOT NULL,
+	update_ts INT NOT NULL,
+
+	PRIMARY KEY (env_id, name)
+);
+
+-- Env var name -> secret name
+ALTER TABLE servers
+	ADD COLUMN secrets JSONB NOT NULL DEFAULT '{}';
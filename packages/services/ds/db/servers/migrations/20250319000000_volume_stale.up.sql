@@ -0,0 +1,4 @@
+-- Set when the volume was restored from a snapshot missing its latest changes (the client that had them was
+-- lost or failed to upload a snapshot)
+ALTER TABLE volumes
+	ADD COLUMN stale_ts INT;
@@ -0,0 +1,3 @@
+-- Set while servers created by the replica set fail validation, cleared once a retried server passes it
+ALTER TABLE replica_sets
+	ADD COLUMN degraded_ts INT;
@@ -18,6 +18,10 @@ pub fn registry() -> WorkflowResult<Registry> {
 	registry.register_workflow::<server::nomad::eval_update::Workflow>()?;
 	registry.register_workflow::<server::pegboard::Workflow>()?;
 	registry.register_workflow::<server::pegboard::destroy::Workflow>()?;
+	registry.register_workflow::<volume::snapshot::Workflow>()?;
+	registry.register_workflow::<volume::delete::Workflow>()?;
+	registry.register_workflow::<schedule::Workflow>()?;
+	registry.register_workflow::<replica_set::Workflow>()?;
 
 	Ok(registry)
 }
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use chirp_workflow::prelude::*;
+
+use crate::types::{LogEntry, LogStream};
+
+#[derive(clickhouse::Row, serde::Deserialize)]
+struct LogRow {
+	#[serde(with = "clickhouse::serde::uuid")]
+	server_id: Uuid,
+	stream_type: u8,
+	ts: i64,
+	message: Vec<u8>,
+	level: String,
+	fields: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+pub struct Input {
+	pub server_ids: Vec<Uuid>,
+	/// Searches both streams if not set.
+	pub stream: Option<LogStream>,
+	/// Only return entries with this level.
+	pub level: Option<String>,
+	/// Only return entries that have all of these fields.
+	pub fields: HashMap<String, String>,
+	/// Only return entries whose message contains this text, case insensitive.
+	pub text: Option<String>,
+	/// Only return entries logged before this timestamp (in nanoseconds).
+	pub before_nts: i64,
+	pub limit: usize,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// Ordered by timestamp, newest first.
+	pub entries: Vec<LogEntry>,
+}
+
+#[operation]
+pub async fn ds_log_search(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	// Logs are only stored if ClickHouse is enabled
+	if ctx.config().server()?.clickhouse.is_none() || input.server_ids.is_empty() {
+		return Ok(Output {
+			entries: Vec::new(),
+		});
+	}
+
+	let clickhouse = ctx.clickhouse().await?;
+
+	let server_ids = vec!["?"; input.server_ids.len()].join(", ");
+
+	let mut filters = String::new();
+	if input.stream.is_some() {
+		filters.push_str("AND stream_type = ? ");
+	}
+	if input.level.is_some() {
+		filters.push_str("AND level = ? ");
+	}
+	for _ in &input.fields {
+		filters.push_str("AND fields[?] = ? ");
+	}
+	if input.text.is_some() {
+		filters.push_str("AND positionCaseInsensitiveUTF8(message, ?) > 0 ");
+	}
+
+	let mut query = clickhouse.query(&formatdoc!(
+		"
+		SELECT
+			server_id,
+			stream_type,
+			ts,
+			message,
+			level,
+			fields
+		FROM db_ds_log.server_logs
+		WHERE
+			server_id IN ({server_ids}) AND
+			ts < fromUnixTimestamp64Nano(?)
+			{filters}
+		ORDER BY ts DESC
+		LIMIT ?
+		"
+	));
+
+	for server_id in &input.server_ids {
+		query = query.bind(server_id);
+	}
+	query = query.bind(input.before_nts);
+	if let Some(stream) = input.stream {
+		query = query.bind(stream as u8);
+	}
+	if let Some(level) = &input.level {
+		query = query.bind(level);
+	}
+	for (key, value) in &input.fields {
+		query = query.bind(key).bind(value);
+	}
+	if let Some(text) = &input.text {
+		query = query.bind(text);
+	}
+
+	let mut cursor = query.bind(i64::try_from(input.limit)?).fetch::<LogRow>()?;
+
+	let mut entries = Vec::new();
+	while let Some(row) = cursor.next().await? {
+		entries.push(LogEntry {
+			server_id: row.server_id,
+			stream: unwrap!(LogStream::from_repr(row.stream_type)),
+			nts: row.ts,
+			message: row.message,
+			level: (!row.level.is_empty()).then_some(row.level),
+			fields: row.fields.into_iter().collect(),
+		});
+	}
+
+	Ok(Output { entries })
+}
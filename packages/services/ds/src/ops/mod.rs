@@ -1,2 +1,7 @@
 pub mod game_config;
+pub mod log;
+pub mod replica_set;
+pub mod schedule;
+pub mod secret;
 pub mod server;
+pub mod volume;
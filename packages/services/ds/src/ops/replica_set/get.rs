@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use chirp_workflow::prelude::*;
+
+use crate::{
+	types::{ActorTemplate, ReplicaSet, ReplicaSetStatus, RollingUpdatePolicy},
+	workflows::replica_set::PENDING_TIMEOUT_MS,
+};
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub replica_set_ids: Vec<Uuid>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub replica_sets: Vec<ReplicaSet>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ReplicaSetRow {
+	replica_set_id: Uuid,
+	env_id: Uuid,
+	template: sqlx::types::Json<ActorTemplate>,
+	image_id: Uuid,
+	replicas: i64,
+	rolling_update: sqlx::types::Json<RollingUpdatePolicy>,
+	degraded_ts: Option<i64>,
+	create_ts: i64,
+	delete_ts: Option<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ServerRow {
+	replica_set_id: Uuid,
+	image_id: Uuid,
+	connectable_ts: Option<i64>,
+}
+
+#[operation]
+pub async fn ds_replica_set_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let now = util::timestamp::now();
+
+	let (replica_set_rows, server_rows) = tokio::try_join!(
+		sql_fetch_all!(
+			[ctx, ReplicaSetRow]
+			"
+			SELECT
+				replica_set_id,
+				env_id,
+				template,
+				image_id,
+				replicas,
+				rolling_update,
+				degraded_ts,
+				create_ts,
+				delete_ts
+			FROM db_ds.replica_sets
+			WHERE replica_set_id = ANY($1)
+			ORDER BY create_ts DESC
+			",
+			&input.replica_set_ids,
+		),
+		// Same conditions as the `GetServers` activity of the replica set workflow
+		sql_fetch_all!(
+			[ctx, ServerRow]
+			"
+			SELECT r.replica_set_id, r.image_id, s.connectable_ts
+			FROM db_ds.replica_set_servers AS r
+			LEFT JOIN db_ds.servers AS s
+			ON r.server_id = s.server_id
+			WHERE
+				r.replica_set_id = ANY($1) AND
+				r.destroy_ts IS NULL AND
+				s.destroy_ts IS NULL AND
+				(s.server_id IS NOT NULL OR r.create_ts > $2)
+			",
+			&input.replica_set_ids,
+			now - PENDING_TIMEOUT_MS,
+		),
+	)?;
+
+	let mut servers_by_replica_set = HashMap::<Uuid, Vec<ServerRow>>::new();
+	for row in server_rows {
+		servers_by_replica_set
+			.entry(row.replica_set_id)
+			.or_default()
+			.push(row);
+	}
+
+	let replica_sets = replica_set_rows
+		.into_iter()
+		.map(|row| {
+			let rolling_update = row.rolling_update.0;
+
+			let mut status = ReplicaSetStatus::default();
+			for server in servers_by_replica_set
+				.get(&row.replica_set_id)
+				.into_iter()
+				.flatten()
+			{
+				status.replicas += 1;
+				if server
+					.connectable_ts
+					.map(|ts| ts <= now - rolling_update.min_ready_ms)
+					.unwrap_or_default()
+				{
+					status.ready_replicas += 1;
+				}
+				if server.image_id == row.image_id {
+					status.updated_replicas += 1;
+				}
+			}
+
+			Ok(ReplicaSet {
+				replica_set_id: row.replica_set_id,
+				env_id: row.env_id,
+				template: row.template.0,
+				image_id: row.image_id,
+				replicas: row.replicas.try_into()?,
+				rolling_update,
+				status,
+				degraded_ts: row.degraded_ts,
+				create_ts: row.create_ts,
+				delete_ts: row.delete_ts,
+			})
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(Output { replica_sets })
+}
@@ -0,0 +1,38 @@
+use chirp_workflow::prelude::*;
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub env_id: Uuid,
+	pub include_deleted: bool,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub replica_set_ids: Vec<Uuid>,
+}
+
+#[operation]
+pub async fn ds_replica_set_list_for_env(
+	ctx: &OperationCtx,
+	input: &Input,
+) -> GlobalResult<Output> {
+	let replica_set_ids = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		SELECT replica_set_id
+		FROM db_ds.replica_sets
+		WHERE
+			env_id = $1 AND
+			($2 OR delete_ts IS NULL)
+		ORDER BY create_ts DESC, replica_set_id DESC
+		",
+		input.env_id,
+		input.include_deleted,
+	)
+	.await?
+	.into_iter()
+	.map(|(id,)| id)
+	.collect::<Vec<_>>();
+
+	Ok(Output { replica_set_ids })
+}
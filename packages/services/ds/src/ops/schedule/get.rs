@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use chirp_workflow::prelude::*;
+
+use crate::types::{ActorTemplate, ConcurrencyPolicy, Schedule};
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub schedule_ids: Vec<Uuid>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub schedules: Vec<Schedule>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ScheduleRow {
+	schedule_id: Uuid,
+	env_id: Uuid,
+	cron: String,
+	build_tags: sqlx::types::Json<HashMap<String, String>>,
+	template: sqlx::types::Json<ActorTemplate>,
+	concurrency_policy: i64,
+	create_ts: i64,
+	delete_ts: Option<i64>,
+}
+
+#[operation]
+pub async fn ds_schedule_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let rows = sql_fetch_all!(
+		[ctx, ScheduleRow]
+		"
+		SELECT
+			schedule_id,
+			env_id,
+			cron,
+			build_tags,
+			template,
+			concurrency_policy,
+			create_ts,
+			delete_ts
+		FROM db_ds.schedules
+		WHERE schedule_id = ANY($1)
+		ORDER BY create_ts DESC
+		",
+		&input.schedule_ids,
+	)
+	.await?;
+
+	let schedules = rows
+		.into_iter()
+		.map(|row| {
+			Ok(Schedule {
+				schedule_id: row.schedule_id,
+				env_id: row.env_id,
+				cron: row.cron,
+				build_tags: row.build_tags.0,
+				template: row.template.0,
+				concurrency_policy: unwrap!(ConcurrencyPolicy::from_repr(
+					row.concurrency_policy.try_into()?
+				)),
+				create_ts: row.create_ts,
+				delete_ts: row.delete_ts,
+			})
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(Output { schedules })
+}
@@ -0,0 +1,35 @@
+use chirp_workflow::prelude::*;
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub env_id: Uuid,
+	pub include_deleted: bool,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub schedule_ids: Vec<Uuid>,
+}
+
+#[operation]
+pub async fn ds_schedule_list_for_env(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let schedule_ids = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		SELECT schedule_id
+		FROM db_ds.schedules
+		WHERE
+			env_id = $1 AND
+			($2 OR delete_ts IS NULL)
+		ORDER BY create_ts DESC, schedule_id DESC
+		",
+		input.env_id,
+		input.include_deleted,
+	)
+	.await?
+	.into_iter()
+	.map(|(id,)| id)
+	.collect::<Vec<_>>();
+
+	Ok(Output { schedule_ids })
+}
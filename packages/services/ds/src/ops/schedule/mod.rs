@@ -0,0 +1,3 @@
+pub mod get;
+pub mod list_for_env;
+pub mod run_list;
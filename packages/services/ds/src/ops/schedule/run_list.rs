@@ -0,0 +1,83 @@
+use chirp_workflow::prelude::*;
+
+use crate::types::{ScheduleRun, ScheduleRunSkipReason};
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub schedule_id: Uuid,
+	/// Only return runs scheduled before this timestamp.
+	pub before_ts: Option<i64>,
+	pub limit: usize,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// Ordered by scheduled timestamp, newest first.
+	pub runs: Vec<ScheduleRun>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RunRow {
+	run_id: Uuid,
+	scheduled_ts: i64,
+	create_ts: i64,
+	server_id: Option<Uuid>,
+	skip_reason: Option<i64>,
+	destroy_ts: Option<i64>,
+	exit_code: Option<i64>,
+}
+
+#[operation]
+pub async fn ds_schedule_run_list(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let rows = sql_fetch_all!(
+		[ctx, RunRow]
+		"
+		SELECT
+			r.run_id,
+			r.scheduled_ts,
+			r.create_ts,
+			r.server_id,
+			r.skip_reason,
+			s.destroy_ts,
+			a.exit_code
+		FROM db_ds.schedule_runs AS r
+		LEFT JOIN db_ds.servers AS s
+		ON r.server_id = s.server_id
+		LEFT JOIN db_ds.servers_pegboard AS spb
+		ON r.server_id = spb.server_id
+		LEFT JOIN db_pegboard.actors AS a
+		ON spb.pegboard_actor_id = a.actor_id
+		WHERE
+			r.schedule_id = $1 AND
+			($2 IS NULL OR r.scheduled_ts < $2)
+		ORDER BY r.scheduled_ts DESC, r.run_id DESC
+		LIMIT $3
+		",
+		input.schedule_id,
+		input.before_ts,
+		i64::try_from(input.limit)?,
+	)
+	.await?;
+
+	let runs = rows
+		.into_iter()
+		.map(|row| {
+			Ok(ScheduleRun {
+				run_id: row.run_id,
+				scheduled_ts: row.scheduled_ts,
+				create_ts: row.create_ts,
+				server_id: row.server_id,
+				skip_reason: row
+					.skip_reason
+					.map(|x| {
+						GlobalResult::Ok(unwrap!(ScheduleRunSkipReason::from_repr(x.try_into()?)))
+					})
+					.transpose()?,
+				destroy_ts: row.destroy_ts,
+				exit_code: row.exit_code.map(TryInto::try_into).transpose()?,
+			})
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(Output { runs })
+}
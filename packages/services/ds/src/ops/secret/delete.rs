@@ -0,0 +1,31 @@
+use chirp_workflow::prelude::*;
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub env_id: Uuid,
+	pub name: String,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub deleted: bool,
+}
+
+/// Actors that reference a deleted secret fail the next time they are started.
+#[operation]
+pub async fn ds_secret_delete(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let deleted = sql_fetch_optional!(
+		[ctx, (String,)]
+		"
+		DELETE FROM db_ds.secrets
+		WHERE env_id = $1 AND name = $2
+		RETURNING name
+		",
+		input.env_id,
+		&input.name,
+	)
+	.await?
+	.is_some();
+
+	Ok(Output { deleted })
+}
@@ -0,0 +1,50 @@
+use chirp_workflow::prelude::*;
+
+use crate::types::Secret;
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub env_id: Uuid,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub secrets: Vec<Secret>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SecretRow {
+	env_id: Uuid,
+	name: String,
+	version: i64,
+	create_ts: i64,
+	update_ts: i64,
+}
+
+#[operation]
+pub async fn ds_secret_list_for_env(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let secrets = sql_fetch_all!(
+		[ctx, SecretRow]
+		"
+		SELECT env_id, name, version, create_ts, update_ts
+		FROM db_ds.secrets
+		WHERE env_id = $1
+		ORDER BY name ASC
+		",
+		input.env_id,
+	)
+	.await?
+	.into_iter()
+	.map(|row| {
+		Ok(Secret {
+			env_id: row.env_id,
+			name: row.name,
+			version: row.version.try_into()?,
+			create_ts: row.create_ts,
+			update_ts: row.update_ts,
+		})
+	})
+	.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(Output { secrets })
+}
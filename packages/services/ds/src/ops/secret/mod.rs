@@ -0,0 +1,4 @@
+pub mod delete;
+pub mod list_for_env;
+pub mod resolve;
+pub mod upsert;
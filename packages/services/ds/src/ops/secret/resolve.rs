@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use chirp_workflow::prelude::*;
+use tivet_config::secret::Secret;
+
+use crate::util::secrets;
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub env_id: Uuid,
+	pub names: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// Secret name -> decrypted value. Secrets that do not exist are omitted.
+	pub values: HashMap<String, Secret<String>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SecretRow {
+	name: String,
+	value: Vec<u8>,
+	kid: String,
+}
+
+#[operation]
+pub async fn ds_secret_resolve(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let rows = sql_fetch_all!(
+		[ctx, SecretRow]
+		"
+		SELECT name, value, kid
+		FROM db_ds.secrets
+		WHERE env_id = $1 AND name = ANY($2)
+		",
+		input.env_id,
+		&input.names,
+	)
+	.await?;
+
+	let config = ctx.config().server()?.actor_secrets()?;
+	let values = rows
+		.into_iter()
+		.map(|row| {
+			let value = secrets::decrypt(config, &row.kid, &row.value)?;
+
+			Ok((row.name, Secret::new(value)))
+		})
+		.collect::<GlobalResult<HashMap<_, _>>>()?;
+
+	Ok(Output { values })
+}
@@ -0,0 +1,50 @@
+use chirp_workflow::prelude::*;
+use tivet_config::secret::Secret;
+
+use crate::util::secrets;
+
+#[derive(Debug)]
+pub struct Input {
+	pub env_id: Uuid,
+	pub name: String,
+	pub value: Secret<String>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// Version of the secret after the write. Starts at 1.
+	pub version: u32,
+}
+
+/// Creates a secret or rotates its value. Actors pick up the new value the next time they are
+/// started.
+#[operation]
+pub async fn ds_secret_upsert(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let config = ctx.config().server()?.actor_secrets()?;
+	let (kid, value) = secrets::encrypt(config, input.value.read())?;
+
+	let (version,) = sql_fetch_one!(
+		[ctx, (i64,)]
+		"
+		INSERT INTO db_ds.secrets (env_id, name, version, value, kid, create_ts, update_ts)
+		VALUES ($1, $2, 1, $3, $4, $5, $5)
+		ON CONFLICT (env_id, name) DO UPDATE
+		SET
+			version = secrets.version + 1,
+			value = EXCLUDED.value,
+			kid = EXCLUDED.kid,
+			update_ts = EXCLUDED.update_ts
+		RETURNING version
+		",
+		input.env_id,
+		&input.name,
+		value,
+		kid,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	Ok(Output {
+		version: version.try_into()?,
+	})
+}
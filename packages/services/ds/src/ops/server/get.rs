@@ -7,7 +7,8 @@ use chirp_workflow::prelude::*;
 
 use crate::types::{
 	EndpointType, GameGuardProtocol, HostProtocol, NetworkMode, Port, PortAuthorization,
-	PortAuthorizationType, Routing, Server, ServerLifecycle, ServerResources,
+	PortAuthorizationType, PortMiddleware, Routing, Server, ServerLifecycle, ServerResourceLimits,
+	ServerResources,
 };
 
 #[derive(sqlx::FromRow)]
@@ -19,6 +20,7 @@ struct ServerRow {
 	tags: sqlx::types::Json<HashMap<String, String>>,
 	resources_cpu_millicores: i64,
 	resources_memory_mib: i64,
+	resources_limits: sqlx::types::Json<ServerResourceLimits>,
 	lifecycle_kill_timeout_ms: i64,
 	lifecycle_durable: bool,
 	create_ts: i64,
@@ -29,6 +31,7 @@ struct ServerRow {
 	args: Vec<String>,
 	network_mode: i64,
 	environment: sqlx::types::Json<HashMap<String, String>>,
+	secrets: sqlx::types::Json<HashMap<String, String>>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -42,6 +45,8 @@ struct ServerPortGg {
 	auth_type: Option<i64>,
 	auth_key: Option<String>,
 	auth_value: Option<String>,
+
+	middleware: Option<sqlx::types::Json<PortMiddleware>>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -109,6 +114,7 @@ pub async fn ds_server_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Ou
 				tags,
 				resources_cpu_millicores,
 				resources_memory_mib,
+				resources_limits,
 				lifecycle_kill_timeout_ms,
 				lifecycle_durable,
 				create_ts,
@@ -118,7 +124,8 @@ pub async fn ds_server_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Ou
 				image_id,
 				args,
 				network_mode,
-				environment
+				environment,
+				secrets
 			FROM db_ds.servers
 			WHERE server_id = ANY($1)
 			",
@@ -135,12 +142,17 @@ pub async fn ds_server_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Ou
 				p.protocol,
 				a.auth_type,
 				a.key AS auth_key,
-				a.value AS auth_value
+				a.value AS auth_value,
+				m.config AS middleware
 			FROM db_ds.server_ports_gg AS p
 			LEFT JOIN db_ds.server_ports_gg_auth AS a
 			ON
 				p.server_id = a.server_id AND
 				p.port_name = a.port_name
+			LEFT JOIN db_ds.server_ports_gg_middleware AS m
+			ON
+				p.server_id = m.server_id AND
+				p.port_name = m.port_name
 			WHERE p.server_id = ANY($1)
 			",
 			&input.server_ids,
@@ -312,6 +324,7 @@ pub async fn ds_server_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Ou
 				resources: ServerResources {
 					cpu_millicores: server.resources_cpu_millicores.try_into()?,
 					memory_mib: server.resources_memory_mib.try_into()?,
+					limits: server.resources_limits.0.clone(),
 				},
 				lifecycle: ServerLifecycle {
 					kill_timeout_ms: server.lifecycle_kill_timeout_ms,
@@ -319,6 +332,7 @@ pub async fn ds_server_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Ou
 				},
 				args: server.args.clone(),
 				environment: server.environment.0.clone(),
+				secrets: server.secrets.0.clone(),
 				image_id: server.image_id,
 				network_mode: unwrap!(NetworkMode::from_repr(server.network_mode.try_into()?)),
 				network_ports: ports,
@@ -379,6 +393,7 @@ fn create_port_gg(
 					),
 				}
 			},
+			middleware: gg_port.middleware.as_ref().map(|x| x.0.clone()),
 		},
 	})
 }
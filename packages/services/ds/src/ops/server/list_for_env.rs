@@ -7,6 +7,8 @@ pub struct Input {
 	pub env_id: Uuid,
 	pub tags: HashMap<String, String>,
 	pub include_destroyed: bool,
+	/// Excludes servers destroyed before this timestamp. Only applies with `include_destroyed`.
+	pub destroyed_after_ts: Option<i64>,
 	pub cursor: Option<Uuid>,
 	pub limit: usize,
 }
@@ -33,6 +35,7 @@ pub async fn ds_server_list_for_env(ctx: &OperationCtx, input: &Input) -> Global
 			env_id = $1
 			AND tags @> $2
 			AND ($3 OR destroy_ts IS NULL)
+			AND ($6 IS NULL OR destroy_ts IS NULL OR destroy_ts > $6)
 			AND (
 				$4 IS NULL OR
 				(create_ts, server_id) < (SELECT create_ts, server_id FROM after_server)
@@ -46,6 +49,7 @@ pub async fn ds_server_list_for_env(ctx: &OperationCtx, input: &Input) -> Global
 		input.cursor,
 		// TODO: Add pagination when OpenGB lobbies no longer uses polling RVTEE-492
 		i64::try_from(input.limit)?,
+		input.destroyed_after_ts,
 	)
 	.await?
 	.into_iter()
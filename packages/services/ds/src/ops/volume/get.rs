@@ -0,0 +1,109 @@
+use chirp_workflow::prelude::*;
+
+use crate::types::{Volume, VolumeSnapshot};
+
+#[derive(Debug, Default)]
+pub struct Input {
+	pub volume_ids: Vec<Uuid>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub volumes: Vec<Volume>,
+}
+
+#[derive(sqlx::FromRow)]
+struct VolumeRow {
+	volume_id: Uuid,
+	env_id: Uuid,
+	name: String,
+	size_mib: i64,
+	create_ts: i64,
+	delete_ts: Option<i64>,
+	stale_ts: Option<i64>,
+	server_id: Option<Uuid>,
+	snapshot_id: Option<Uuid>,
+	snapshot_create_ts: Option<i64>,
+	snapshot_complete_ts: Option<i64>,
+	snapshot_size: Option<i64>,
+}
+
+#[operation]
+pub async fn ds_volume_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	let rows = sql_fetch_all!(
+		[ctx, VolumeRow]
+		"
+		SELECT
+			v.volume_id,
+			v.env_id,
+			v.name,
+			v.size_mib,
+			v.create_ts,
+			v.delete_ts,
+			v.stale_ts,
+			(
+				SELECT sv.server_id
+				FROM db_ds.server_volumes AS sv
+				JOIN db_ds.servers AS s
+				ON sv.server_id = s.server_id
+				WHERE
+					sv.volume_id = v.volume_id AND
+					s.destroy_ts IS NULL
+				LIMIT 1
+			) AS server_id,
+			vs.snapshot_id,
+			vs.create_ts AS snapshot_create_ts,
+			vs.complete_ts AS snapshot_complete_ts,
+			vs.size AS snapshot_size
+		FROM db_ds.volumes AS v
+		LEFT JOIN LATERAL (
+			SELECT snapshot_id, create_ts, complete_ts, size
+			FROM db_ds.volume_snapshots
+			WHERE
+				volume_id = v.volume_id AND
+				complete_ts IS NOT NULL
+			ORDER BY complete_ts DESC
+			LIMIT 1
+		) AS vs ON true
+		WHERE v.volume_id = ANY($1)
+		",
+		&input.volume_ids,
+	)
+	.await?;
+
+	let volumes = rows
+		.into_iter()
+		.map(|row| {
+			let snapshot = match (
+				row.snapshot_id,
+				row.snapshot_create_ts,
+				row.snapshot_complete_ts,
+				row.snapshot_size,
+			) {
+				(Some(snapshot_id), Some(create_ts), Some(complete_ts), Some(size)) => {
+					Some(VolumeSnapshot {
+						snapshot_id,
+						create_ts,
+						complete_ts,
+						size: size.try_into()?,
+					})
+				}
+				_ => None,
+			};
+
+			Ok(Volume {
+				volume_id: row.volume_id,
+				env_id: row.env_id,
+				name: row.name,
+				size_mib: row.size_mib.try_into()?,
+				create_ts: row.create_ts,
+				delete_ts: row.delete_ts,
+				server_id: row.server_id,
+				snapshot,
+				stale_ts: row.stale_ts,
+			})
+		})
+		.collect::<GlobalResult<Vec<_>>>()?;
+
+	Ok(Output { volumes })
+}
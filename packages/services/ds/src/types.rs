@@ -1,6 +1,7 @@
-use std::{collections::HashMap, fmt};
+use std::{collections::HashMap, fmt, net::IpAddr};
 
 use chirp_workflow::prelude::*;
+use ipnet::IpNet;
 use tivet_api::models;
 use tivet_convert::{ApiFrom, ApiInto, ApiTryFrom};
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,8 @@ pub struct Server {
 	pub args: Vec<String>,
 	pub network_mode: NetworkMode,
 	pub environment: HashMap<String, String>,
+	/// Env var name -> secret name.
+	pub secrets: HashMap<String, String>,
 	pub network_ports: HashMap<String, Port>,
 }
 
@@ -31,6 +34,8 @@ pub struct Server {
 pub struct ServerResources {
 	pub cpu_millicores: u32,
 	pub memory_mib: u32,
+	#[serde(default)]
+	pub limits: ServerResourceLimits,
 }
 
 impl ServerResources {
@@ -38,16 +43,246 @@ impl ServerResources {
 		ServerResources {
 			cpu_millicores: 125,
 			memory_mib: 128,
+			limits: ServerResourceLimits::default(),
 		}
 	}
 }
 
+/// Limits enforced on pegboard containers. Unset limits default to the limits of the selected tier,
+/// which are also the maximum allowed values. See `tier::types::Tier`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Hash)]
+#[serde(default)]
+pub struct ServerResourceLimits {
+	/// Max number of processes and threads. Not limited if unset, the tier's limit is only the maximum.
+	pub pids: Option<u32>,
+	/// Relative block I/O weight. Must be [10, 1000].
+	pub io_weight: Option<u16>,
+	/// MiB/s
+	pub disk_read_bandwidth: Option<u32>,
+	/// MiB/s
+	pub disk_write_bandwidth: Option<u32>,
+	/// Max open file descriptors.
+	pub nofile: Option<u32>,
+	/// Kibps
+	pub egress_bandwidth: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
 pub struct ServerLifecycle {
 	pub kill_timeout_ms: i64,
 	pub durable: bool,
 }
 
+/// Persistent volume mounted in to a pegboard container. The volume is created if no volume with this name
+/// exists in the environment.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct ServerVolume {
+	pub name: String,
+	/// Absolute path in the container.
+	pub path: String,
+	/// Size of the volume. Must match the existing volume's size.
+	pub size_mib: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Volume {
+	pub volume_id: Uuid,
+	pub env_id: Uuid,
+	pub name: String,
+	pub size_mib: u32,
+	pub create_ts: i64,
+	pub delete_ts: Option<i64>,
+	/// Server that is not destroyed and has the volume mounted.
+	pub server_id: Option<Uuid>,
+	/// Latest complete snapshot.
+	pub snapshot: Option<VolumeSnapshot>,
+	/// Set if the volume was restored from a snapshot missing its latest changes.
+	pub stale_ts: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VolumeSnapshot {
+	pub snapshot_id: Uuid,
+	pub create_ts: i64,
+	pub complete_ts: i64,
+	/// Bytes of the compressed archive.
+	pub size: u64,
+}
+
+/// Metadata of an encrypted secret. The value is only decrypted when actors referencing it are
+/// started.
+#[derive(Debug, Clone)]
+pub struct Secret {
+	pub env_id: Uuid,
+	pub name: String,
+	/// Incremented each time the value is rotated.
+	pub version: u32,
+	pub create_ts: i64,
+	pub update_ts: i64,
+}
+
+/// Configuration of an actor, without its id and build. Used to create actors from schedules and replica
+/// sets, which pick the build separately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorTemplate {
+	pub datacenter_id: Uuid,
+	pub cluster_id: Uuid,
+	pub runtime: ServerRuntime,
+	pub tags: HashMap<String, String>,
+	pub resources: ServerResources,
+	pub lifecycle: ServerLifecycle,
+	pub root_user_enabled: bool,
+	pub args: Vec<String>,
+	pub network_mode: NetworkMode,
+	pub environment: HashMap<String, String>,
+	/// Env var name -> secret name.
+	#[serde(default)]
+	pub secrets: HashMap<String, String>,
+	pub network_ports: HashMap<String, crate::workflows::server::Port>,
+}
+
+impl ActorTemplate {
+	pub fn into_server_input(
+		self,
+		server_id: Uuid,
+		env_id: Uuid,
+		image_id: Uuid,
+	) -> crate::workflows::server::Input {
+		crate::workflows::server::Input {
+			server_id,
+			env_id,
+			datacenter_id: self.datacenter_id,
+			cluster_id: self.cluster_id,
+			runtime: self.runtime,
+			tags: self.tags,
+			resources: self.resources,
+			lifecycle: self.lifecycle,
+			image_id,
+			root_user_enabled: self.root_user_enabled,
+			args: self.args,
+			network_mode: self.network_mode,
+			environment: self.environment,
+			secrets: self.secrets,
+			network_ports: self.network_ports,
+			volumes: Vec::new(),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+	pub schedule_id: Uuid,
+	pub env_id: Uuid,
+	pub cron: String,
+	pub build_tags: HashMap<String, String>,
+	pub template: ActorTemplate,
+	pub concurrency_policy: ConcurrencyPolicy,
+	pub create_ts: i64,
+	pub delete_ts: Option<i64>,
+}
+
+/// What to do when a schedule ticks while actors from its previous runs are still running.
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, FromRepr, Default)]
+pub enum ConcurrencyPolicy {
+	/// Create a new actor alongside the running ones.
+	#[default]
+	Allow = 0,
+	/// Skip the run.
+	Forbid = 1,
+	/// Destroy the running actors before creating a new one.
+	Replace = 2,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduleRun {
+	pub run_id: Uuid,
+	/// Tick of the schedule this run is for.
+	pub scheduled_ts: i64,
+	pub create_ts: i64,
+	/// Unset if the run was skipped.
+	pub server_id: Option<Uuid>,
+	pub skip_reason: Option<ScheduleRunSkipReason>,
+	pub destroy_ts: Option<i64>,
+	/// Exit code of the run's latest actor. Only set for pegboard actors that have exited.
+	pub exit_code: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, FromRepr)]
+pub enum ScheduleRunSkipReason {
+	/// The schedule ticked while the workflow was not running (i.e. during an outage).
+	Missed = 0,
+	/// Actors from previous runs were still running and the concurrency policy is `Forbid`.
+	ConcurrencyForbidden = 1,
+	/// No build matched the schedule's build tags.
+	BuildNotFound = 2,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplicaSet {
+	pub replica_set_id: Uuid,
+	pub env_id: Uuid,
+	pub template: ActorTemplate,
+	/// Build that replicas are rolled out to.
+	pub image_id: Uuid,
+	/// Desired number of replicas.
+	pub replicas: u32,
+	pub rolling_update: RollingUpdatePolicy,
+	pub status: ReplicaSetStatus,
+	/// Set while the replica set's actors fail validation and are recreated with a backoff.
+	pub degraded_ts: Option<i64>,
+	pub create_ts: i64,
+	pub delete_ts: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct RollingUpdatePolicy {
+	/// Max number of replicas created above the desired count during a rollout.
+	pub max_surge: u32,
+	/// Max number of replicas that can be unavailable during a rollout.
+	pub max_unavailable: u32,
+	/// How long a replica must be ready for before it counts as available.
+	pub min_ready_ms: i64,
+}
+
+impl Default for RollingUpdatePolicy {
+	fn default() -> Self {
+		RollingUpdatePolicy {
+			max_surge: 1,
+			max_unavailable: 0,
+			min_ready_ms: 0,
+		}
+	}
+}
+
+/// Counts of the replica set's actors that are not destroyed.
+#[derive(Debug, Clone, Default)]
+pub struct ReplicaSetStatus {
+	pub replicas: u32,
+	pub ready_replicas: u32,
+	/// Replicas running the replica set's current build.
+	pub updated_replicas: u32,
+}
+
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, FromRepr)]
+#[repr(u8)]
+pub enum LogStream {
+	StdOut = 0,
+	StdErr = 1,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+	pub server_id: Uuid,
+	pub stream: LogStream,
+	/// Nanoseconds.
+	pub nts: i64,
+	pub message: Vec<u8>,
+	/// Level extracted from JSON lines.
+	pub level: Option<String>,
+	/// Remaining keys of JSON lines.
+	pub fields: HashMap<String, String>,
+}
+
 #[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, FromRepr)]
 pub enum NetworkMode {
 	Bridge = 0,
@@ -69,6 +304,8 @@ pub enum Routing {
 	GameGuard {
 		protocol: GameGuardProtocol,
 		authorization: PortAuthorization,
+		#[serde(default)]
+		middleware: Option<PortMiddleware>,
 	},
 	Host {
 		protocol: HostProtocol,
@@ -110,6 +347,117 @@ pub enum PortAuthorizationType {
 	Query = 2,
 }
 
+/// HTTP middleware applied by Game Guard in front of an HTTP(S) port.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Hash)]
+#[serde(default, deny_unknown_fields)]
+pub struct PortMiddleware {
+	pub rate_limit: Option<PortRateLimit>,
+	pub cors: Option<PortCors>,
+	/// Headers added to requests before they are forwarded to the actor.
+	pub request_headers: util::serde::HashableMap<String, String>,
+	/// Headers added to responses from the actor.
+	pub response_headers: util::serde::HashableMap<String, String>,
+	pub basic_auth: Option<PortBasicAuth>,
+	/// CIDR ranges allowed to reach the port. Empty allows all addresses.
+	pub ip_allow_list: Vec<String>,
+	/// CIDR ranges denied from reaching the port.
+	pub ip_deny_list: Vec<String>,
+	pub compress: bool,
+}
+
+impl PortMiddleware {
+	/// Ranges that are allowed to reach the port after applying both the allow and deny lists. `None` if
+	/// all addresses are allowed.
+	///
+	/// Game Guard has no deny list middleware, so denied ranges are carved out of the allowed ranges and
+	/// applied with an allow list. This way denied clients are rejected with a 403 instead of falling
+	/// through to other routers. Invalid ranges are ignored, they are rejected when the server is created.
+	pub fn ip_allow_ranges(&self) -> Option<Vec<String>> {
+		if self.ip_allow_list.is_empty() && self.ip_deny_list.is_empty() {
+			return None;
+		}
+
+		let allow = if self.ip_allow_list.is_empty() {
+			vec![IpNet::V4(Default::default()), IpNet::V6(Default::default())]
+		} else {
+			self.ip_allow_list
+				.iter()
+				.filter_map(|x| parse_ip_range(x))
+				.collect()
+		};
+		let deny = self
+			.ip_deny_list
+			.iter()
+			.filter_map(|x| parse_ip_range(x))
+			.collect::<Vec<_>>();
+
+		let mut ranges = Vec::new();
+		for range in allow {
+			subtract_ip_ranges(range, &deny, &mut ranges);
+		}
+
+		Some(ranges.into_iter().map(|x| x.to_string()).collect())
+	}
+}
+
+/// Parses an IP or CIDR range. Single IPs are treated as a range containing only that address.
+pub fn parse_ip_range(range: &str) -> Option<IpNet> {
+	if range.contains('/') {
+		range.parse::<IpNet>().ok().map(|x| x.trunc())
+	} else {
+		range.parse::<IpAddr>().ok().map(IpNet::from)
+	}
+}
+
+/// Pushes the parts of `range` that are not in any of the `deny` ranges.
+fn subtract_ip_ranges(range: IpNet, deny: &[IpNet], output: &mut Vec<IpNet>) {
+	if deny.iter().any(|x| x.contains(&range)) {
+		return;
+	}
+
+	// CIDR ranges either contain each other or don't overlap
+	if !deny.iter().any(|x| range.contains(x)) {
+		output.push(range);
+		return;
+	}
+
+	// Split in half and subtract from each half
+	if let Ok(subnets) = range.subnets(range.prefix_len() + 1) {
+		for subnet in subnets {
+			subtract_ip_ranges(subnet, deny, output);
+		}
+	}
+}
+
+/// Replaces the default per-IP rate limit.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct PortRateLimit {
+	/// Requests allowed per period.
+	pub average: u32,
+	pub period_s: u32,
+	pub burst: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Hash)]
+#[serde(default, deny_unknown_fields)]
+pub struct PortCors {
+	pub allowed_origins: Vec<String>,
+	pub allowed_methods: Vec<String>,
+	pub allowed_headers: Vec<String>,
+	pub exposed_headers: Vec<String>,
+	pub allow_credentials: bool,
+	pub max_age_s: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct PortBasicAuth {
+	/// htpasswd formatted `user:hash` entries.
+	pub users: Vec<String>,
+	pub realm: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, FromRepr)]
 pub enum HostProtocol {
 	Tcp = 0,
@@ -196,6 +544,7 @@ pub fn convert_actor_to_api(
 			build: value.image_id,
 			arguments: Some(value.args),
 			environment: Some(value.environment),
+			secrets: Some(value.secrets),
 		}),
 		network: Box::new(models::ActorNetwork {
 			mode: value.network_mode.api_into(),
@@ -215,6 +564,14 @@ impl ApiFrom<models::ActorResources> for ServerResources {
 		ServerResources {
 			cpu_millicores: value.cpu as u32,
 			memory_mib: value.memory as u32,
+			limits: ServerResourceLimits {
+				pids: value.pids.map(|x| x as u32),
+				io_weight: value.io_weight.map(|x| x as u16),
+				disk_read_bandwidth: value.disk_read_bandwidth.map(|x| x as u32),
+				disk_write_bandwidth: value.disk_write_bandwidth.map(|x| x as u32),
+				nofile: value.nofile.map(|x| x as u32),
+				egress_bandwidth: value.egress_bandwidth.map(|x| x as u32),
+			},
 		}
 	}
 }
@@ -224,6 +581,181 @@ impl ApiFrom<ServerResources> for models::ActorResources {
 		models::ActorResources {
 			cpu: value.cpu_millicores as i32,
 			memory: value.memory_mib as i32,
+			pids: value.limits.pids.map(|x| x as i32),
+			io_weight: value.limits.io_weight.map(|x| x as i32),
+			disk_read_bandwidth: value.limits.disk_read_bandwidth.map(|x| x as i32),
+			disk_write_bandwidth: value.limits.disk_write_bandwidth.map(|x| x as i32),
+			nofile: value.limits.nofile.map(|x| x as i32),
+			egress_bandwidth: value.limits.egress_bandwidth.map(|x| x as i32),
+		}
+	}
+}
+
+impl ApiFrom<models::ActorCreateActorVolumeRequest> for ServerVolume {
+	fn api_from(value: models::ActorCreateActorVolumeRequest) -> ServerVolume {
+		ServerVolume {
+			name: value.name,
+			path: value.path,
+			size_mib: value.size as u32,
+		}
+	}
+}
+
+impl ApiTryFrom<Volume> for models::ActorVolume {
+	type Error = GlobalError;
+
+	fn api_try_from(value: Volume) -> GlobalResult<models::ActorVolume> {
+		Ok(models::ActorVolume {
+			id: value.volume_id,
+			name: value.name,
+			size: value.size_mib.try_into()?,
+			created_at: util::timestamp::to_string(value.create_ts)?,
+			actor: value.server_id,
+			snapshot: value
+				.snapshot
+				.map(|snapshot| {
+					GlobalResult::Ok(Box::new(models::ActorVolumeSnapshot {
+						id: snapshot.snapshot_id,
+						created_at: util::timestamp::to_string(snapshot.create_ts)?,
+						completed_at: util::timestamp::to_string(snapshot.complete_ts)?,
+						size: snapshot.size.try_into()?,
+					}))
+				})
+				.transpose()?,
+			stale_at: value.stale_ts.map(util::timestamp::to_string).transpose()?,
+		})
+	}
+}
+
+impl ApiTryFrom<Secret> for models::ActorSecret {
+	type Error = GlobalError;
+
+	fn api_try_from(value: Secret) -> GlobalResult<models::ActorSecret> {
+		Ok(models::ActorSecret {
+			name: value.name,
+			version: value.version.try_into()?,
+			created_at: util::timestamp::to_string(value.create_ts)?,
+			updated_at: util::timestamp::to_string(value.update_ts)?,
+		})
+	}
+}
+
+impl ApiFrom<models::ActorScheduleConcurrencyPolicy> for ConcurrencyPolicy {
+	fn api_from(value: models::ActorScheduleConcurrencyPolicy) -> ConcurrencyPolicy {
+		match value {
+			models::ActorScheduleConcurrencyPolicy::Allow => ConcurrencyPolicy::Allow,
+			models::ActorScheduleConcurrencyPolicy::Forbid => ConcurrencyPolicy::Forbid,
+			models::ActorScheduleConcurrencyPolicy::Replace => ConcurrencyPolicy::Replace,
+		}
+	}
+}
+
+impl ApiFrom<ConcurrencyPolicy> for models::ActorScheduleConcurrencyPolicy {
+	fn api_from(value: ConcurrencyPolicy) -> models::ActorScheduleConcurrencyPolicy {
+		match value {
+			ConcurrencyPolicy::Allow => models::ActorScheduleConcurrencyPolicy::Allow,
+			ConcurrencyPolicy::Forbid => models::ActorScheduleConcurrencyPolicy::Forbid,
+			ConcurrencyPolicy::Replace => models::ActorScheduleConcurrencyPolicy::Replace,
+		}
+	}
+}
+
+impl ApiFrom<ScheduleRunSkipReason> for models::ActorScheduleRunSkipReason {
+	fn api_from(value: ScheduleRunSkipReason) -> models::ActorScheduleRunSkipReason {
+		match value {
+			ScheduleRunSkipReason::Missed => models::ActorScheduleRunSkipReason::Missed,
+			ScheduleRunSkipReason::ConcurrencyForbidden => {
+				models::ActorScheduleRunSkipReason::ConcurrencyForbidden
+			}
+			ScheduleRunSkipReason::BuildNotFound => {
+				models::ActorScheduleRunSkipReason::BuildNotFound
+			}
+		}
+	}
+}
+
+impl ApiTryFrom<Schedule> for models::ActorSchedule {
+	type Error = GlobalError;
+
+	fn api_try_from(value: Schedule) -> GlobalResult<models::ActorSchedule> {
+		Ok(models::ActorSchedule {
+			id: value.schedule_id,
+			cron: value.cron,
+			build_tags: Some(serde_json::to_value(value.build_tags)?),
+			concurrency_policy: value.concurrency_policy.api_into(),
+			tags: Some(serde_json::to_value(value.template.tags)?),
+			created_at: util::timestamp::to_string(value.create_ts)?,
+		})
+	}
+}
+
+impl ApiTryFrom<ScheduleRun> for models::ActorScheduleRun {
+	type Error = GlobalError;
+
+	fn api_try_from(value: ScheduleRun) -> GlobalResult<models::ActorScheduleRun> {
+		Ok(models::ActorScheduleRun {
+			id: value.run_id,
+			scheduled_at: util::timestamp::to_string(value.scheduled_ts)?,
+			created_at: util::timestamp::to_string(value.create_ts)?,
+			actor: value.server_id,
+			skip_reason: value.skip_reason.map(ApiInto::api_into),
+			destroyed_at: value
+				.destroy_ts
+				.map(util::timestamp::to_string)
+				.transpose()?,
+			exit_code: value.exit_code,
+		})
+	}
+}
+
+impl ApiFrom<RollingUpdatePolicy> for models::ActorReplicaSetRollingUpdate {
+	fn api_from(value: RollingUpdatePolicy) -> models::ActorReplicaSetRollingUpdate {
+		models::ActorReplicaSetRollingUpdate {
+			max_surge: Some(value.max_surge as i32),
+			max_unavailable: Some(value.max_unavailable as i32),
+			min_ready: Some(value.min_ready_ms),
+		}
+	}
+}
+
+impl ApiTryFrom<ReplicaSet> for models::ActorReplicaSet {
+	type Error = GlobalError;
+
+	fn api_try_from(value: ReplicaSet) -> GlobalResult<models::ActorReplicaSet> {
+		Ok(models::ActorReplicaSet {
+			id: value.replica_set_id,
+			tags: Some(serde_json::to_value(value.template.tags)?),
+			build: value.image_id,
+			replicas: value.replicas.try_into()?,
+			rolling_update: Box::new(value.rolling_update.api_into()),
+			status: Box::new(models::ActorReplicaSetStatus {
+				replicas: value.status.replicas.try_into()?,
+				ready_replicas: value.status.ready_replicas.try_into()?,
+				updated_replicas: value.status.updated_replicas.try_into()?,
+			}),
+			degraded_at: value
+				.degraded_ts
+				.map(util::timestamp::to_string)
+				.transpose()?,
+			created_at: util::timestamp::to_string(value.create_ts)?,
+		})
+	}
+}
+
+impl ApiFrom<models::ActorLogStream> for LogStream {
+	fn api_from(value: models::ActorLogStream) -> LogStream {
+		match value {
+			models::ActorLogStream::StdOut => LogStream::StdOut,
+			models::ActorLogStream::StdErr => LogStream::StdErr,
+		}
+	}
+}
+
+impl ApiFrom<LogStream> for models::ActorLogStream {
+	fn api_from(value: LogStream) -> models::ActorLogStream {
+		match value {
+			LogStream::StdOut => models::ActorLogStream::StdOut,
+			LogStream::StdErr => models::ActorLogStream::StdErr,
 		}
 	}
 }
@@ -270,6 +802,7 @@ impl ApiFrom<Port> for models::ActorPort {
 			Routing::GameGuard {
 				protocol,
 				authorization,
+				middleware,
 			} => {
 				let url = match (
 					protocol,
@@ -301,7 +834,11 @@ impl ApiFrom<Port> for models::ActorPort {
 				(
 					(*protocol).api_into(),
 					models::ActorPortRouting {
-						guard: Some(json!({})),
+						guard: Some(if let Some(middleware) = middleware {
+							json!({ "middleware": middleware })
+						} else {
+							json!({})
+						}),
 						// Temporarily disabled
 						// guard: Some(Box::new(models::ActorGuardRouting {
 						// 	authorization: match authorization {
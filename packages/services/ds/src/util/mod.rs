@@ -7,6 +7,7 @@ pub mod consts;
 pub mod nomad_job;
 mod oci_config;
 mod seccomp;
+pub mod secrets;
 pub mod test;
 
 pub const NOMAD_REGION: &str = "global";
@@ -69,9 +70,10 @@ pub fn image_artifact_url_stub(
 	upload_id: Uuid,
 	file_name: &str,
 ) -> GlobalResult<String> {
-	Ok(format!(
-		"/s3-cache/{namespace}-bucket-build/{upload_id}/{file_name}",
-		namespace = config.server()?.tivet.namespace,
+	Ok(build::utils::artifact_url_stub(
+		&config.server()?.tivet.namespace,
+		upload_id,
+		file_name,
 	))
 }
 
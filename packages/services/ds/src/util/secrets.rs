@@ -0,0 +1,56 @@
+use aes_gcm::{
+	aead::{Aead, AeadCore, KeyInit, OsRng},
+	Aes256Gcm, Key, Nonce,
+};
+use chirp_workflow::prelude::*;
+use tivet_config::config::ActorSecrets;
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypts a secret value with the active key. Returns the ID of the key and the nonce followed
+/// by the ciphertext.
+pub fn encrypt(config: &ActorSecrets, value: &str) -> GlobalResult<(String, Vec<u8>)> {
+	let cipher = cipher(config.key.read())?;
+
+	let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+	let ciphertext = unwrap!(
+		cipher.encrypt(&nonce, value.as_bytes()).ok(),
+		"failed to encrypt secret"
+	);
+
+	let mut buf = nonce.to_vec();
+	buf.extend(ciphertext);
+
+	Ok((config.kid.clone(), buf))
+}
+
+/// Decrypts a value written by `encrypt` with the active key or a key from the keyring.
+pub fn decrypt(config: &ActorSecrets, kid: &str, value: &[u8]) -> GlobalResult<String> {
+	let key = if kid == config.kid {
+		config.key.read()
+	} else {
+		unwrap!(
+			config.keyring.iter().find(|x| x.kid == kid),
+			"secret key not in keyring"
+		)
+		.key
+		.read()
+	};
+	let cipher = cipher(key)?;
+
+	ensure!(value.len() >= NONCE_LEN, "secret value too short");
+	let (nonce, ciphertext) = value.split_at(NONCE_LEN);
+	let plaintext = unwrap!(
+		cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok(),
+		"failed to decrypt secret"
+	);
+
+	Ok(String::from_utf8(plaintext)?)
+}
+
+fn cipher(key: &str) -> GlobalResult<Aes256Gcm> {
+	let key = hex::decode(key)?;
+	ensure!(key.len() == 32, "secret key must be 32 bytes");
+
+	Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
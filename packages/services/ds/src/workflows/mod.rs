@@ -1 +1,4 @@
+pub mod replica_set;
+pub mod schedule;
 pub mod server;
+pub mod volume;
@@ -0,0 +1,633 @@
+use chirp_workflow::prelude::*;
+use futures_util::FutureExt;
+
+use crate::types::{ActorTemplate, RollingUpdatePolicy};
+
+/// How often the replica set's actors are reconciled.
+const RECONCILE_INTERVAL_MS: i64 = util::duration::seconds(5);
+/// Servers that have not been inserted by their workflow after this long are considered failed (i.e. they
+/// failed validation).
+pub(crate) const PENDING_TIMEOUT_MS: i64 = util::duration::minutes(1);
+/// Max time to wait before creating servers again after servers failed validation.
+pub const MAX_CREATE_BACKOFF_MS: i64 = util::duration::minutes(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Input {
+	pub replica_set_id: Uuid,
+	pub env_id: Uuid,
+	pub template: ActorTemplate,
+	pub image_id: Uuid,
+	pub replicas: u32,
+	pub rolling_update: RollingUpdatePolicy,
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+	image_id: Uuid,
+	replicas: u32,
+	rolling_update: RollingUpdatePolicy,
+	/// Index of the next server created.
+	next_idx: i64,
+	/// Consecutive reconciles in which servers failed validation.
+	#[serde(default)]
+	failed_attempts: u32,
+	/// Servers created after this timestamp are retries of failed servers.
+	#[serde(default)]
+	last_failure_ts: i64,
+	/// Servers are not created before this timestamp.
+	#[serde(default)]
+	retry_ts: i64,
+}
+
+/// A server of the replica set that is not destroyed.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct ReplicaServer {
+	pub server_id: Uuid,
+	pub image_id: Uuid,
+	/// Ready for at least `min_ready_ms`.
+	pub ready: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Plan {
+	/// Number of servers to create with the current build.
+	pub create: u32,
+	pub destroy: Vec<Uuid>,
+}
+
+/// Determines which servers to create and destroy to move towards `replicas` servers running `image_id`
+/// without exceeding the surge and unavailability limits of the policy.
+pub fn plan_rollout(
+	servers: &[ReplicaServer],
+	image_id: Uuid,
+	replicas: u32,
+	policy: &RollingUpdatePolicy,
+) -> Plan {
+	let (updated, outdated) = servers
+		.iter()
+		.partition::<Vec<_>, _>(|server| server.image_id == image_id);
+	let mut destroy = Vec::new();
+
+	// Outdated servers that are not ready don't count towards availability
+	let (outdated_ready, outdated_unready) = outdated
+		.into_iter()
+		.partition::<Vec<_>, _>(|server| server.ready);
+	destroy.extend(outdated_unready.iter().map(|server| server.server_id));
+
+	// Destroy ready outdated servers as long as enough servers stay available
+	let ready = servers.iter().filter(|server| server.ready).count() as u32;
+	let min_available = replicas.saturating_sub(policy.max_unavailable);
+	let unavailable_budget = ready.saturating_sub(min_available) as usize;
+	destroy.extend(
+		outdated_ready
+			.iter()
+			.take(unavailable_budget)
+			.map(|server| server.server_id),
+	);
+
+	// Scale down excess updated servers, least ready first
+	let updated_len = updated.len() as u32;
+	if updated_len > replicas {
+		let mut excess = updated;
+		excess.sort_by_key(|server| server.ready);
+		destroy.extend(
+			excess
+				.iter()
+				.take((updated_len - replicas) as usize)
+				.map(|server| server.server_id),
+		);
+	}
+
+	// Scale up updated servers without exceeding the surge
+	let remaining = servers.len() as u32 - destroy.len() as u32;
+	let create = replicas
+		.saturating_sub(updated_len)
+		.min((replicas + policy.max_surge).saturating_sub(remaining));
+
+	Plan { create, destroy }
+}
+
+/// How long to wait before creating servers again after `failed_attempts` consecutive reconciles in which
+/// servers failed validation.
+pub fn create_backoff_ms(failed_attempts: u32) -> i64 {
+	if failed_attempts == 0 {
+		return 0;
+	}
+
+	RECONCILE_INTERVAL_MS
+		.saturating_mul(1 << failed_attempts.min(16))
+		.min(MAX_CREATE_BACKOFF_MS)
+}
+
+/// Keeps `replicas` actors running the replica set's build, rolling out build changes gradually. Must be
+/// tagged with `replica_set_id`.
+#[workflow]
+pub async fn ds_replica_set(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()> {
+	ctx.activity(InsertDbInput {
+		replica_set_id: input.replica_set_id,
+		env_id: input.env_id,
+		template: serde_json::to_string(&input.template)?,
+		image_id: input.image_id,
+		replicas: input.replicas,
+		rolling_update: input.rolling_update.clone(),
+	})
+	.await?;
+
+	ctx.msg(CreateComplete {})
+		.tag("replica_set_id", input.replica_set_id)
+		.send()
+		.await?;
+
+	ctx.loope(
+		State {
+			image_id: input.image_id,
+			replicas: input.replicas,
+			rolling_update: input.rolling_update.clone(),
+			next_idx: 0,
+			failed_attempts: 0,
+			last_failure_ts: 0,
+			retry_ts: 0,
+		},
+		|ctx, state| {
+			let input = input.clone();
+
+			async move {
+				let failures = ctx
+					.activity(ReapFailedServersInput {
+						replica_set_id: input.replica_set_id,
+						validated_after_ts: state.last_failure_ts,
+					})
+					.await?;
+
+				// Back off instead of recreating servers that fail validation every reconcile
+				if failures.failed != 0 {
+					state.failed_attempts += 1;
+					state.last_failure_ts = failures.now_ts;
+					state.retry_ts = failures.now_ts + create_backoff_ms(state.failed_attempts);
+
+					tracing::warn!(
+						replica_set_id=?input.replica_set_id,
+						failed=?failures.failed,
+						failed_attempts=?state.failed_attempts,
+						"replica set servers failed validation, backing off",
+					);
+
+					if state.failed_attempts == 1 {
+						ctx.activity(SetDegradedInput {
+							replica_set_id: input.replica_set_id,
+							degraded: true,
+						})
+						.await?;
+					}
+				} else if state.failed_attempts != 0 && failures.validated {
+					state.failed_attempts = 0;
+					state.retry_ts = 0;
+
+					ctx.activity(SetDegradedInput {
+						replica_set_id: input.replica_set_id,
+						degraded: false,
+					})
+					.await?;
+				}
+
+				let servers = ctx
+					.activity(GetServersInput {
+						replica_set_id: input.replica_set_id,
+						min_ready_ms: state.rolling_update.min_ready_ms,
+					})
+					.await?;
+
+				let plan = plan_rollout(
+					&servers,
+					state.image_id,
+					state.replicas,
+					&state.rolling_update,
+				);
+
+				if !plan.destroy.is_empty() {
+					destroy_servers(ctx, input.replica_set_id, plan.destroy).await?;
+				}
+
+				if plan.create != 0 && failures.now_ts >= state.retry_ts {
+					let server_ids = ctx
+						.activity(InsertServersInput {
+							replica_set_id: input.replica_set_id,
+							image_id: state.image_id,
+							idxs: (state.next_idx..state.next_idx + i64::from(plan.create))
+								.collect(),
+						})
+						.await?;
+					state.next_idx += i64::from(plan.create);
+
+					for server_id in server_ids {
+						ctx.workflow(input.template.clone().into_server_input(
+							server_id,
+							input.env_id,
+							state.image_id,
+						))
+						.tag("server_id", server_id)
+						.dispatch()
+						.await?;
+					}
+				}
+
+				match ctx
+					.listen_with_timeout::<Main>(RECONCILE_INTERVAL_MS)
+					.await?
+				{
+					Some(Main::Update(sig)) => {
+						if let Some(image_id) = sig.image_id {
+							state.image_id = image_id;
+						}
+						if let Some(replicas) = sig.replicas {
+							state.replicas = replicas;
+						}
+						if let Some(rolling_update) = sig.rolling_update {
+							state.rolling_update = rolling_update;
+						}
+
+						// Try the update right away
+						state.retry_ts = 0;
+
+						ctx.activity(UpdateDbInput {
+							replica_set_id: input.replica_set_id,
+							image_id: state.image_id,
+							replicas: state.replicas,
+							rolling_update: state.rolling_update.clone(),
+						})
+						.await?;
+					}
+					Some(Main::Delete(_)) => return Ok(Loop::Break(())),
+					None => {}
+				}
+
+				Ok(Loop::Continue)
+			}
+			.boxed()
+		},
+	)
+	.await?;
+
+	let servers = ctx
+		.activity(GetServersInput {
+			replica_set_id: input.replica_set_id,
+			min_ready_ms: 0,
+		})
+		.await?;
+	destroy_servers(
+		ctx,
+		input.replica_set_id,
+		servers.into_iter().map(|server| server.server_id).collect(),
+	)
+	.await?;
+
+	ctx.activity(SetDeletedInput {
+		replica_set_id: input.replica_set_id,
+	})
+	.await?;
+
+	Ok(())
+}
+
+async fn destroy_servers(
+	ctx: &mut WorkflowCtx,
+	replica_set_id: Uuid,
+	server_ids: Vec<Uuid>,
+) -> GlobalResult<()> {
+	ctx.activity(SetServersDestroyedInput {
+		replica_set_id,
+		server_ids: server_ids.clone(),
+	})
+	.await?;
+
+	for server_id in server_ids {
+		ctx.signal(super::server::Destroy {
+			override_kill_timeout_ms: None,
+		})
+		.tag("server_id", server_id)
+		.send()
+		.await?;
+	}
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertDbInput {
+	replica_set_id: Uuid,
+	env_id: Uuid,
+	/// JSON encoded `ActorTemplate`.
+	template: String,
+	image_id: Uuid,
+	replicas: u32,
+	rolling_update: RollingUpdatePolicy,
+}
+
+#[activity(InsertDb)]
+async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		INSERT INTO db_ds.replica_sets (
+			replica_set_id,
+			env_id,
+			template,
+			image_id,
+			replicas,
+			rolling_update,
+			create_ts
+		)
+		VALUES ($1, $2, $3, $4, $5, $6, $7)
+		ON CONFLICT (replica_set_id) DO NOTHING
+		",
+		input.replica_set_id,
+		input.env_id,
+		&input.template,
+		input.image_id,
+		i64::from(input.replicas),
+		serde_json::to_string(&input.rolling_update)?,
+		ctx.ts(),
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct ReapFailedServersInput {
+	replica_set_id: Uuid,
+	/// Servers created after this timestamp that passed validation set `validated`.
+	validated_after_ts: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct ReapFailedServersOutput {
+	now_ts: i64,
+	/// Number of servers that failed validation since the last reconcile.
+	failed: usize,
+	validated: bool,
+}
+
+/// Marks servers that were never inserted by their workflow within `PENDING_TIMEOUT_MS` as destroyed.
+#[activity(ReapFailedServers)]
+async fn reap_failed_servers(
+	ctx: &ActivityCtx,
+	input: &ReapFailedServersInput,
+) -> GlobalResult<ReapFailedServersOutput> {
+	let now_ts = util::timestamp::now();
+
+	let (failed, validated) = tokio::try_join!(
+		sql_fetch_all!(
+			[ctx, (Uuid,)]
+			"
+			UPDATE db_ds.replica_set_servers AS r
+			SET destroy_ts = $3
+			WHERE
+				r.replica_set_id = $1 AND
+				r.destroy_ts IS NULL AND
+				r.create_ts <= $2 AND
+				NOT EXISTS (
+					SELECT 1
+					FROM db_ds.servers AS s
+					WHERE s.server_id = r.server_id
+				)
+			RETURNING r.server_id
+			",
+			input.replica_set_id,
+			now_ts - PENDING_TIMEOUT_MS,
+			now_ts,
+		),
+		sql_fetch_one!(
+			[ctx, (bool,)]
+			"
+			SELECT EXISTS (
+				SELECT 1
+				FROM db_ds.replica_set_servers AS r
+				JOIN db_ds.servers AS s
+				ON r.server_id = s.server_id
+				WHERE
+					r.replica_set_id = $1 AND
+					r.create_ts > $2
+			)
+			",
+			input.replica_set_id,
+			input.validated_after_ts,
+		),
+	)?;
+
+	Ok(ReapFailedServersOutput {
+		now_ts,
+		failed: failed.len(),
+		validated: validated.0,
+	})
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct GetServersInput {
+	replica_set_id: Uuid,
+	min_ready_ms: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct ServerRow {
+	server_id: Uuid,
+	image_id: Uuid,
+	connectable_ts: Option<i64>,
+}
+
+/// Returns the servers of the replica set that are not destroyed, oldest first.
+#[activity(GetServers)]
+async fn get_servers(
+	ctx: &ActivityCtx,
+	input: &GetServersInput,
+) -> GlobalResult<Vec<ReplicaServer>> {
+	let now = util::timestamp::now();
+
+	let rows = sql_fetch_all!(
+		[ctx, ServerRow]
+		"
+		SELECT r.server_id, r.image_id, s.connectable_ts
+		FROM db_ds.replica_set_servers AS r
+		LEFT JOIN db_ds.servers AS s
+		ON r.server_id = s.server_id
+		WHERE
+			r.replica_set_id = $1 AND
+			r.destroy_ts IS NULL AND
+			s.destroy_ts IS NULL AND
+			(s.server_id IS NOT NULL OR r.create_ts > $2)
+		ORDER BY r.idx ASC
+		",
+		input.replica_set_id,
+		now - PENDING_TIMEOUT_MS,
+	)
+	.await?;
+
+	Ok(rows
+		.into_iter()
+		.map(|row| ReplicaServer {
+			server_id: row.server_id,
+			image_id: row.image_id,
+			ready: row
+				.connectable_ts
+				.map(|ts| ts <= now - input.min_ready_ms)
+				.unwrap_or_default(),
+		})
+		.collect())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertServersInput {
+	replica_set_id: Uuid,
+	image_id: Uuid,
+	idxs: Vec<i64>,
+}
+
+/// Returns the ids of the servers to create.
+#[activity(InsertServers)]
+async fn insert_servers(ctx: &ActivityCtx, input: &InsertServersInput) -> GlobalResult<Vec<Uuid>> {
+	// Returns the existing servers if this activity is retried
+	let server_ids = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		INSERT INTO db_ds.replica_set_servers (replica_set_id, idx, server_id, image_id, create_ts)
+		SELECT $1, t.idx, gen_random_uuid(), $3, $4
+		FROM unnest($2) AS t(idx)
+		ON CONFLICT (replica_set_id, idx) DO UPDATE
+		SET idx = EXCLUDED.idx
+		RETURNING server_id
+		",
+		input.replica_set_id,
+		&input.idxs,
+		input.image_id,
+		util::timestamp::now(),
+	)
+	.await?
+	.into_iter()
+	.map(|(id,)| id)
+	.collect();
+
+	Ok(server_ids)
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct SetServersDestroyedInput {
+	replica_set_id: Uuid,
+	server_ids: Vec<Uuid>,
+}
+
+#[activity(SetServersDestroyed)]
+async fn set_servers_destroyed(
+	ctx: &ActivityCtx,
+	input: &SetServersDestroyedInput,
+) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		UPDATE db_ds.replica_set_servers
+		SET destroy_ts = $3
+		WHERE
+			replica_set_id = $1 AND
+			server_id = ANY($2) AND
+			destroy_ts IS NULL
+		",
+		input.replica_set_id,
+		&input.server_ids,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct UpdateDbInput {
+	replica_set_id: Uuid,
+	image_id: Uuid,
+	replicas: u32,
+	rolling_update: RollingUpdatePolicy,
+}
+
+#[activity(UpdateDb)]
+async fn update_db(ctx: &ActivityCtx, input: &UpdateDbInput) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		UPDATE db_ds.replica_sets
+		SET
+			image_id = $2,
+			replicas = $3,
+			rolling_update = $4
+		WHERE replica_set_id = $1
+		",
+		input.replica_set_id,
+		input.image_id,
+		i64::from(input.replicas),
+		serde_json::to_string(&input.rolling_update)?,
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct SetDegradedInput {
+	replica_set_id: Uuid,
+	degraded: bool,
+}
+
+#[activity(SetDegraded)]
+async fn set_degraded(ctx: &ActivityCtx, input: &SetDegradedInput) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		UPDATE db_ds.replica_sets
+		SET degraded_ts = CASE WHEN $2 THEN $3 END
+		WHERE replica_set_id = $1
+		",
+		input.replica_set_id,
+		input.degraded,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct SetDeletedInput {
+	replica_set_id: Uuid,
+}
+
+#[activity(SetDeleted)]
+async fn set_deleted(ctx: &ActivityCtx, input: &SetDeletedInput) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		UPDATE db_ds.replica_sets
+		SET delete_ts = $2
+		WHERE replica_set_id = $1
+		",
+		input.replica_set_id,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[message("ds_replica_set_create_complete")]
+pub struct CreateComplete {}
+
+/// Unset fields are left unchanged.
+#[signal("ds_replica_set_update")]
+pub struct Update {
+	pub image_id: Option<Uuid>,
+	pub replicas: Option<u32>,
+	pub rolling_update: Option<RollingUpdatePolicy>,
+}
+
+/// Destroys the replica set's actors.
+#[signal("ds_replica_set_delete")]
+pub struct Delete {}
+
+join_signal!(Main { Update, Delete });
@@ -0,0 +1,460 @@
+use std::collections::{HashMap, VecDeque};
+
+use chirp_workflow::prelude::*;
+use chrono::{TimeZone, Utc};
+use futures_util::FutureExt;
+use util::serde::AsHashableExt;
+
+use crate::types::{ActorTemplate, ConcurrencyPolicy, ScheduleRunSkipReason};
+
+/// Ticks that passed longer than this ago are recorded as missed instead of creating an actor.
+pub const STARTING_DEADLINE_MS: i64 = util::duration::minutes(1);
+/// Max number of missed ticks recorded in the run history at once.
+pub const MAX_MISSED_RUNS: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Input {
+	pub schedule_id: Uuid,
+	pub env_id: Uuid,
+	pub cron: String,
+	pub build_tags: HashMap<String, String>,
+	pub template: ActorTemplate,
+	pub concurrency_policy: ConcurrencyPolicy,
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+	/// Timestamp of the latest tick that was handled.
+	last_tick_ts: i64,
+}
+
+/// Parses a standard five field cron expression, evaluated in UTC.
+pub fn parse_cron(cron: &str) -> GlobalResult<croner::Cron> {
+	Ok(croner::Cron::new(cron).parse()?)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Ticks {
+	/// Ticks that passed more than `STARTING_DEADLINE_MS` ago, oldest first. Only the latest
+	/// `MAX_MISSED_RUNS` are kept.
+	pub missed: Vec<i64>,
+	/// Latest tick that passed within `STARTING_DEADLINE_MS`.
+	pub due: Option<i64>,
+	/// First tick after now.
+	pub next_ts: i64,
+}
+
+/// Determines which ticks of the cron expression after `after_ts` were missed or are due at `now_ts`.
+pub fn compute_ticks(cron: &croner::Cron, after_ts: i64, now_ts: i64) -> GlobalResult<Ticks> {
+	let after = unwrap!(Utc.timestamp_millis_opt(after_ts).single());
+
+	let mut passed = VecDeque::new();
+	let mut ticks = cron.iter_after(after);
+	let next_ts = loop {
+		let tick_ts = unwrap!(ticks.next(), "cron has no upcoming ticks").timestamp_millis();
+
+		if tick_ts > now_ts {
+			break tick_ts;
+		}
+
+		// Keep one extra for `due`
+		if passed.len() > MAX_MISSED_RUNS {
+			passed.pop_front();
+		}
+		passed.push_back(tick_ts);
+	};
+
+	let due = passed
+		.back()
+		.copied()
+		.filter(|tick_ts| now_ts - tick_ts <= STARTING_DEADLINE_MS);
+	if due.is_some() {
+		passed.pop_back();
+	}
+	if passed.len() > MAX_MISSED_RUNS {
+		passed.pop_front();
+	}
+
+	Ok(Ticks {
+		missed: passed.into(),
+		due,
+		next_ts,
+	})
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RunPlan {
+	Create,
+	/// Record the tick as skipped because of the concurrency policy.
+	Skip,
+	/// Destroy these servers, then create.
+	Replace(Vec<Uuid>),
+}
+
+/// Determines how to run a tick given the servers of previous runs that are still running.
+pub fn plan_run(policy: ConcurrencyPolicy, running_server_ids: Vec<Uuid>) -> RunPlan {
+	if running_server_ids.is_empty() {
+		return RunPlan::Create;
+	}
+
+	match policy {
+		ConcurrencyPolicy::Allow => RunPlan::Create,
+		ConcurrencyPolicy::Forbid => RunPlan::Skip,
+		ConcurrencyPolicy::Replace => RunPlan::Replace(running_server_ids),
+	}
+}
+
+/// Creates an actor at each tick of the cron expression until deleted. Must be tagged with `schedule_id`.
+#[workflow]
+pub async fn ds_schedule(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()> {
+	let create_ts = ctx
+		.activity(InsertDbInput {
+			schedule_id: input.schedule_id,
+			env_id: input.env_id,
+			cron: input.cron.clone(),
+			build_tags: input.build_tags.as_hashable(),
+			template: serde_json::to_string(&input.template)?,
+			concurrency_policy: input.concurrency_policy,
+		})
+		.await?;
+
+	ctx.msg(CreateComplete {})
+		.tag("schedule_id", input.schedule_id)
+		.send()
+		.await?;
+
+	ctx.loope(
+		State {
+			last_tick_ts: create_ts,
+		},
+		|ctx, state| {
+			let input = input.clone();
+
+			async move {
+				let ticks = ctx
+					.activity(GetTicksInput {
+						cron: input.cron.clone(),
+						after_ts: state.last_tick_ts,
+					})
+					.await?;
+
+				if let Some(last_missed_ts) = ticks.missed.last() {
+					ctx.activity(InsertSkippedRunsInput {
+						schedule_id: input.schedule_id,
+						scheduled_ts: ticks.missed.clone(),
+						skip_reason: ScheduleRunSkipReason::Missed,
+					})
+					.await?;
+
+					state.last_tick_ts = *last_missed_ts;
+				}
+
+				let tick_ts = if let Some(due_ts) = ticks.due {
+					due_ts
+				} else {
+					match ctx
+						.listen_with_timeout::<Delete>(ticks.next_ts - ticks.now_ts)
+						.await?
+					{
+						Some(_) => return Ok(Loop::Break(())),
+						None => ticks.next_ts,
+					}
+				};
+
+				run(ctx, &input, tick_ts).await?;
+				state.last_tick_ts = tick_ts;
+
+				Ok(Loop::Continue)
+			}
+			.boxed()
+		},
+	)
+	.await?;
+
+	ctx.activity(SetDeletedInput {
+		schedule_id: input.schedule_id,
+	})
+	.await?;
+
+	Ok(())
+}
+
+/// Creates an actor for the given tick, following the schedule's concurrency policy.
+async fn run(ctx: &mut WorkflowCtx, input: &Input, tick_ts: i64) -> GlobalResult<()> {
+	let running_server_ids = ctx
+		.activity(GetRunningInput {
+			schedule_id: input.schedule_id,
+		})
+		.await?;
+
+	match plan_run(input.concurrency_policy, running_server_ids) {
+		RunPlan::Create => {}
+		RunPlan::Skip => {
+			ctx.activity(InsertSkippedRunsInput {
+				schedule_id: input.schedule_id,
+				scheduled_ts: vec![tick_ts],
+				skip_reason: ScheduleRunSkipReason::ConcurrencyForbidden,
+			})
+			.await?;
+
+			return Ok(());
+		}
+		RunPlan::Replace(server_ids) => {
+			for server_id in server_ids {
+				ctx.signal(super::server::Destroy {
+					override_kill_timeout_ms: None,
+				})
+				.tag("server_id", server_id)
+				.send()
+				.await?;
+			}
+		}
+	}
+
+	// Resolved at each tick so that runs pick up newly tagged builds
+	let Some(image_id) = ctx
+		.activity(ResolveBuildInput {
+			env_id: input.env_id,
+			build_tags: input.build_tags.as_hashable(),
+		})
+		.await?
+	else {
+		tracing::warn!(schedule_id=?input.schedule_id, "no build found for schedule build tags");
+
+		ctx.activity(InsertSkippedRunsInput {
+			schedule_id: input.schedule_id,
+			scheduled_ts: vec![tick_ts],
+			skip_reason: ScheduleRunSkipReason::BuildNotFound,
+		})
+		.await?;
+
+		return Ok(());
+	};
+
+	let server_id = ctx
+		.activity(InsertRunInput {
+			schedule_id: input.schedule_id,
+			scheduled_ts: tick_ts,
+		})
+		.await?;
+
+	ctx.workflow(
+		input
+			.template
+			.clone()
+			.into_server_input(server_id, input.env_id, image_id),
+	)
+	.tag("server_id", server_id)
+	.dispatch()
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertDbInput {
+	schedule_id: Uuid,
+	env_id: Uuid,
+	cron: String,
+	build_tags: util::serde::HashableMap<String, String>,
+	/// JSON encoded `ActorTemplate`.
+	template: String,
+	concurrency_policy: ConcurrencyPolicy,
+}
+
+/// Returns the create timestamp of the schedule.
+#[activity(InsertDb)]
+async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<i64> {
+	let (create_ts,) = sql_fetch_one!(
+		[ctx, (i64,)]
+		"
+		INSERT INTO db_ds.schedules (
+			schedule_id,
+			env_id,
+			cron,
+			build_tags,
+			template,
+			concurrency_policy,
+			create_ts
+		)
+		VALUES ($1, $2, $3, $4, $5, $6, $7)
+		ON CONFLICT (schedule_id) DO UPDATE
+		SET schedule_id = EXCLUDED.schedule_id
+		RETURNING create_ts
+		",
+		input.schedule_id,
+		input.env_id,
+		&input.cron,
+		serde_json::to_string(&input.build_tags)?,
+		&input.template,
+		input.concurrency_policy as i32,
+		ctx.ts(),
+	)
+	.await?;
+
+	Ok(create_ts)
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct GetTicksInput {
+	cron: String,
+	after_ts: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct GetTicksOutput {
+	now_ts: i64,
+	/// See `Ticks`.
+	missed: Vec<i64>,
+	due: Option<i64>,
+	next_ts: i64,
+}
+
+#[activity(GetTicks)]
+async fn get_ticks(_ctx: &ActivityCtx, input: &GetTicksInput) -> GlobalResult<GetTicksOutput> {
+	let cron = parse_cron(&input.cron)?;
+	let now_ts = util::timestamp::now();
+	let ticks = compute_ticks(&cron, input.after_ts, now_ts)?;
+
+	Ok(GetTicksOutput {
+		now_ts,
+		missed: ticks.missed,
+		due: ticks.due,
+		next_ts: ticks.next_ts,
+	})
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct GetRunningInput {
+	schedule_id: Uuid,
+}
+
+/// Returns the servers created by this schedule that are not destroyed.
+#[activity(GetRunning)]
+async fn get_running(ctx: &ActivityCtx, input: &GetRunningInput) -> GlobalResult<Vec<Uuid>> {
+	let server_ids = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		SELECT r.server_id
+		FROM db_ds.schedule_runs AS r
+		JOIN db_ds.servers AS s
+		ON r.server_id = s.server_id
+		WHERE
+			r.schedule_id = $1 AND
+			s.destroy_ts IS NULL
+		",
+		input.schedule_id,
+	)
+	.await?
+	.into_iter()
+	.map(|(id,)| id)
+	.collect();
+
+	Ok(server_ids)
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct ResolveBuildInput {
+	env_id: Uuid,
+	build_tags: util::serde::HashableMap<String, String>,
+}
+
+#[activity(ResolveBuild)]
+async fn resolve_build(ctx: &ActivityCtx, input: &ResolveBuildInput) -> GlobalResult<Option<Uuid>> {
+	let builds_res = ctx
+		.op(build::ops::resolve_for_tags::Input {
+			env_id: input.env_id,
+			tags: input.build_tags.clone().into_iter().collect(),
+		})
+		.await?;
+
+	Ok(builds_res.builds.first().map(|build| build.build_id))
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertRunInput {
+	schedule_id: Uuid,
+	scheduled_ts: i64,
+}
+
+/// Returns the id of the server to create for this run.
+#[activity(InsertRun)]
+async fn insert_run(ctx: &ActivityCtx, input: &InsertRunInput) -> GlobalResult<Uuid> {
+	// Returns the existing run's server if this activity is retried
+	let (server_id,) = sql_fetch_one!(
+		[ctx, (Uuid,)]
+		"
+		INSERT INTO db_ds.schedule_runs (run_id, schedule_id, scheduled_ts, create_ts, server_id)
+		VALUES ($1, $2, $3, $4, $5)
+		ON CONFLICT (schedule_id, scheduled_ts) DO UPDATE
+		SET schedule_id = EXCLUDED.schedule_id
+		RETURNING server_id
+		",
+		Uuid::new_v4(),
+		input.schedule_id,
+		input.scheduled_ts,
+		util::timestamp::now(),
+		Uuid::new_v4(),
+	)
+	.await?;
+
+	Ok(server_id)
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertSkippedRunsInput {
+	schedule_id: Uuid,
+	scheduled_ts: Vec<i64>,
+	skip_reason: ScheduleRunSkipReason,
+}
+
+#[activity(InsertSkippedRuns)]
+async fn insert_skipped_runs(
+	ctx: &ActivityCtx,
+	input: &InsertSkippedRunsInput,
+) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		INSERT INTO db_ds.schedule_runs (run_id, schedule_id, scheduled_ts, create_ts, skip_reason)
+		SELECT gen_random_uuid(), $1, t.scheduled_ts, $3, $4
+		FROM unnest($2) AS t(scheduled_ts)
+		ON CONFLICT (schedule_id, scheduled_ts) DO NOTHING
+		",
+		input.schedule_id,
+		&input.scheduled_ts,
+		util::timestamp::now(),
+		input.skip_reason as i32,
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct SetDeletedInput {
+	schedule_id: Uuid,
+}
+
+#[activity(SetDeleted)]
+async fn set_deleted(ctx: &ActivityCtx, input: &SetDeletedInput) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		UPDATE db_ds.schedules
+		SET delete_ts = $2
+		WHERE schedule_id = $1
+		",
+		input.schedule_id,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[message("ds_schedule_create_complete")]
+pub struct CreateComplete {}
+
+#[signal("ds_schedule_delete")]
+pub struct Delete {}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+	collections::{HashMap, HashSet},
+	time::Duration,
+};
 
 use build::types::{BuildCompression, BuildKind};
 use chirp_workflow::prelude::*;
@@ -8,8 +11,9 @@ use rand::Rng;
 use util::serde::AsHashableExt;
 
 use crate::types::{
-	GameGuardProtocol, NetworkMode, PortAuthorization, PortAuthorizationType, Routing,
-	ServerLifecycle, ServerResources, ServerRuntime,
+	GameGuardProtocol, HostProtocol, NetworkMode, PortAuthorization, PortAuthorizationType,
+	PortMiddleware, Routing, ServerLifecycle, ServerResourceLimits, ServerResources, ServerRuntime,
+	ServerVolume,
 };
 
 pub mod nomad;
@@ -20,6 +24,10 @@ pub mod pegboard;
 const DRAIN_PADDING_MS: i64 = 10000;
 /// Time to delay an actor from rescheduling after a rescheduling failure.
 const BASE_RETRY_TIMEOUT_MS: usize = 2000;
+/// Max size of a single volume.
+const MAX_VOLUME_SIZE_MIB: u32 = 10 * 1024;
+/// Max ranges a port's allow list may expand to after removing the deny list.
+const MAX_IP_ALLOW_RANGES: usize = 1024;
 
 // Only used by Nomad
 const TRAEFIK_GRACE_PERIOD: Duration = Duration::from_secs(2);
@@ -39,7 +47,14 @@ pub struct Input {
 	pub args: Vec<String>,
 	pub network_mode: NetworkMode,
 	pub environment: HashMap<String, String>,
+	/// Env var name -> secret name. Values are resolved when the actor is started. Only supported
+	/// by pegboard.
+	#[serde(default)]
+	pub secrets: HashMap<String, String>,
 	pub network_ports: HashMap<String, Port>,
+	/// Only supported by pegboard containers.
+	#[serde(default)]
+	pub volumes: Vec<ServerVolume>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
@@ -49,22 +64,135 @@ pub struct Port {
 	pub routing: Routing,
 }
 
+// MARK: V1 types
+// Frozen copies of types from before port middleware and resource limits were added. These are hashed in to
+// the inputs of v1 activities in workflow history, so they must never change.
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub(crate) struct PortV1 {
+	internal_port: Option<u16>,
+	routing: RoutingV1,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+enum RoutingV1 {
+	GameGuard {
+		protocol: GameGuardProtocol,
+		authorization: PortAuthorization,
+	},
+	Host {
+		protocol: HostProtocol,
+	},
+}
+
+impl From<Port> for PortV1 {
+	fn from(port: Port) -> Self {
+		PortV1 {
+			internal_port: port.internal_port,
+			routing: match port.routing {
+				Routing::GameGuard {
+					protocol,
+					authorization,
+					..
+				} => RoutingV1::GameGuard {
+					protocol,
+					authorization,
+				},
+				Routing::Host { protocol } => RoutingV1::Host { protocol },
+			},
+		}
+	}
+}
+
+impl From<PortV1> for Port {
+	fn from(port: PortV1) -> Self {
+		Port {
+			internal_port: port.internal_port,
+			routing: match port.routing {
+				RoutingV1::GameGuard {
+					protocol,
+					authorization,
+				} => Routing::GameGuard {
+					protocol,
+					authorization,
+					middleware: None,
+				},
+				RoutingV1::Host { protocol } => Routing::Host { protocol },
+			},
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub(crate) struct ServerResourcesV1 {
+	cpu_millicores: u32,
+	memory_mib: u32,
+}
+
+impl From<ServerResources> for ServerResourcesV1 {
+	fn from(resources: ServerResources) -> Self {
+		ServerResourcesV1 {
+			cpu_millicores: resources.cpu_millicores,
+			memory_mib: resources.memory_mib,
+		}
+	}
+}
+
+impl From<ServerResourcesV1> for ServerResources {
+	fn from(resources: ServerResourcesV1) -> Self {
+		ServerResources {
+			cpu_millicores: resources.cpu_millicores,
+			memory_mib: resources.memory_mib,
+			limits: ServerResourceLimits::default(),
+		}
+	}
+}
+
+pub(crate) fn ports_v1(
+	network_ports: &HashMap<String, Port>,
+) -> util::serde::HashableMap<String, PortV1> {
+	network_ports
+		.iter()
+		.map(|(k, v)| (k.clone(), v.clone().into()))
+		.collect()
+}
+
 #[workflow]
 pub async fn ds_server(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()> {
-	let validation_res = ctx
-		.activity(ValidateInput {
-			env_id: input.env_id,
-			datacenter_id: input.datacenter_id,
-			tags: input.tags.as_hashable(),
-			resources: input.resources.clone(),
-			image_id: input.image_id,
-			root_user_enabled: input.root_user_enabled,
-			args: input.args.clone(),
-			network_mode: input.network_mode,
-			environment: input.environment.as_hashable(),
-			network_ports: input.network_ports.as_hashable(),
-		})
-		.await?;
+	let validation_res = match ctx.check_version(2).await? {
+		1 => {
+			ctx.activity(ValidateInputV1 {
+				env_id: input.env_id,
+				datacenter_id: input.datacenter_id,
+				tags: input.tags.as_hashable(),
+				resources: input.resources.clone().into(),
+				image_id: input.image_id,
+				root_user_enabled: input.root_user_enabled,
+				args: input.args.clone(),
+				network_mode: input.network_mode,
+				environment: input.environment.as_hashable(),
+				network_ports: ports_v1(&input.network_ports),
+			})
+			.await?
+		}
+		_latest => {
+			ctx.activity(ValidateInputV2 {
+				env_id: input.env_id,
+				datacenter_id: input.datacenter_id,
+				tags: input.tags.as_hashable(),
+				resources: input.resources.clone(),
+				image_id: input.image_id,
+				root_user_enabled: input.root_user_enabled,
+				args: input.args.clone(),
+				network_mode: input.network_mode,
+				environment: input.environment.as_hashable(),
+				secrets: input.secrets.as_hashable(),
+				network_ports: input.network_ports.as_hashable(),
+				volumes: input.volumes.clone(),
+			})
+			.await?
+		}
+	};
 
 	if let Some(error_message) = validation_res {
 		ctx.msg(Failed {
@@ -78,11 +206,20 @@ pub async fn ds_server(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()>
 		return Ok(());
 	}
 
-	let network_ports = ctx
-		.activity(DisableTlsPortsInput {
-			network_ports: input.network_ports.as_hashable(),
-		})
-		.await?;
+	let network_ports = match ctx.check_version(2).await? {
+		1 => {
+			ctx.activity(DisableTlsPortsInputV1 {
+				network_ports: ports_v1(&input.network_ports),
+			})
+			.await?
+		}
+		_latest => {
+			ctx.activity(DisableTlsPortsInputV2 {
+				network_ports: input.network_ports.as_hashable(),
+			})
+			.await?
+		}
+	};
 
 	match input.runtime {
 		ServerRuntime::Nomad => {
@@ -118,7 +255,9 @@ pub async fn ds_server(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()>
 				args: input.args.clone(),
 				network_mode: input.network_mode,
 				environment: input.environment.clone(),
+				secrets: input.secrets.clone(),
 				network_ports: network_ports.into_iter().collect(),
+				volumes: input.volumes.clone(),
 			})
 			.output()
 			.await
@@ -127,25 +266,50 @@ pub async fn ds_server(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()>
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
-struct DisableTlsPortsInput {
+struct DisableTlsPortsInputV1 {
+	network_ports: util::serde::HashableMap<String, PortV1>,
+}
+
+#[activity(DisableTlsPorts)]
+async fn disable_tls_ports(
+	ctx: &ActivityCtx,
+	input: &DisableTlsPortsInputV1,
+) -> GlobalResult<util::serde::HashableMap<String, Port>> {
+	disable_tls_ports_inner(
+		ctx,
+		input
+			.network_ports
+			.iter()
+			.map(|(k, v)| (k.clone(), v.clone().into()))
+			.collect(),
+	)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+struct DisableTlsPortsInputV2 {
 	network_ports: util::serde::HashableMap<String, Port>,
 }
 
+#[activity(DisableTlsPortsV2)]
+async fn disable_tls_ports_v2(
+	ctx: &ActivityCtx,
+	input: &DisableTlsPortsInputV2,
+) -> GlobalResult<util::serde::HashableMap<String, Port>> {
+	disable_tls_ports_inner(ctx, input.network_ports.clone())
+}
+
 /// If TLS is not enabled in the cluster, we downgrade all protocols to the non-TLS equivalents.
 /// This allows developers to develop locally with the same code they would use in production.
-#[activity(DisableTlsPorts)]
-async fn disable_tls_ports(
+fn disable_tls_ports_inner(
 	ctx: &ActivityCtx,
-	input: &DisableTlsPortsInput,
+	network_ports: util::serde::HashableMap<String, Port>,
 ) -> GlobalResult<util::serde::HashableMap<String, Port>> {
 	if ctx.config().server()?.tivet.guard.tls_enabled() {
 		// Do nothing
-		Ok(input.network_ports.clone())
+		Ok(network_ports)
 	} else {
 		// Downgrade all TLS protocols to non-TLS protocols
-		let network_ports = input
-			.network_ports
-			.clone()
+		let network_ports = network_ports
 			.into_iter()
 			.map(|(k, p)| {
 				(
@@ -156,6 +320,7 @@ async fn disable_tls_ports(
 							Routing::GameGuard {
 								protocol,
 								authorization,
+								middleware,
 							} => Routing::GameGuard {
 								protocol: match protocol {
 									GameGuardProtocol::Https => GameGuardProtocol::Http,
@@ -165,6 +330,7 @@ async fn disable_tls_ports(
 									| GameGuardProtocol::Udp) => x,
 								},
 								authorization,
+								middleware,
 							},
 							x @ Routing::Host { .. } => x,
 						},
@@ -178,7 +344,49 @@ async fn disable_tls_ports(
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
-struct ValidateInput {
+struct ValidateInputV1 {
+	datacenter_id: Uuid,
+	env_id: Uuid,
+	tags: util::serde::HashableMap<String, String>,
+	resources: ServerResourcesV1,
+	image_id: Uuid,
+	root_user_enabled: bool,
+	args: Vec<String>,
+	network_mode: NetworkMode,
+	environment: util::serde::HashableMap<String, String>,
+	network_ports: util::serde::HashableMap<String, PortV1>,
+}
+
+#[activity(Validate)]
+async fn validate(ctx: &ActivityCtx, input: &ValidateInputV1) -> GlobalResult<Option<String>> {
+	let input = input.clone();
+
+	validate_inner(
+		ctx,
+		&ValidateInputV2 {
+			datacenter_id: input.datacenter_id,
+			env_id: input.env_id,
+			tags: input.tags,
+			resources: input.resources.into(),
+			image_id: input.image_id,
+			root_user_enabled: input.root_user_enabled,
+			args: input.args,
+			network_mode: input.network_mode,
+			environment: input.environment,
+			secrets: Default::default(),
+			network_ports: input
+				.network_ports
+				.into_iter()
+				.map(|(k, v)| (k, v.into()))
+				.collect(),
+			volumes: Vec::new(),
+		},
+	)
+	.await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+struct ValidateInputV2 {
 	datacenter_id: Uuid,
 	env_id: Uuid,
 	tags: util::serde::HashableMap<String, String>,
@@ -188,13 +396,22 @@ struct ValidateInput {
 	args: Vec<String>,
 	network_mode: NetworkMode,
 	environment: util::serde::HashableMap<String, String>,
+	secrets: util::serde::HashableMap<String, String>,
 	network_ports: util::serde::HashableMap<String, Port>,
+	volumes: Vec<ServerVolume>,
+}
+
+#[activity(ValidateV2)]
+async fn validate_v2(ctx: &ActivityCtx, input: &ValidateInputV2) -> GlobalResult<Option<String>> {
+	validate_inner(ctx, input).await
 }
 
 // TODO: Redo once a solid global error solution is established so we dont have to have validation all in one
 // place.
-#[activity(Validate)]
-async fn validate(ctx: &ActivityCtx, input: &ValidateInput) -> GlobalResult<Option<String>> {
+async fn validate_inner(
+	ctx: &ActivityCtx,
+	input: &ValidateInputV2,
+) -> GlobalResult<Option<String>> {
 	let (tier_res, upload_res, game_config_res) = tokio::try_join!(
 		async {
 			let datacenters_res = ctx
@@ -214,12 +431,22 @@ async fn validate(ctx: &ActivityCtx, input: &ValidateInput) -> GlobalResult<Opti
 				})
 				.await?;
 			let tier_dc = unwrap!(tier_res.datacenters.first());
-
-			// Find any tier that has more CPU and memory than the requested resources
-			Ok(Some(tier_dc.tiers.iter().any(|t| {
-				t.cpu_millicores >= input.resources.cpu_millicores
-					&& t.memory >= input.resources.memory_mib
-			})))
+			let mut tiers = tier_dc.tiers.iter().collect::<Vec<_>>();
+
+			// Sort the tiers by cpu
+			tiers.sort_by(|a, b| a.cpu.cmp(&b.cpu));
+
+			// Find the first tier that has more CPU and memory than the requested resources. This is the
+			// same tier that is selected when allocating the actor.
+			Ok(Some(
+				tiers
+					.into_iter()
+					.find(|t| {
+						t.cpu_millicores >= input.resources.cpu_millicores
+							&& t.memory >= input.resources.memory_mib
+					})
+					.cloned(),
+			))
 		},
 		async {
 			let builds_res = ctx
@@ -262,12 +489,16 @@ async fn validate(ctx: &ActivityCtx, input: &ValidateInput) -> GlobalResult<Opti
 		}
 	)?;
 
-	let Some(has_tier) = tier_res else {
+	let Some(tier) = tier_res else {
 		return Ok(Some("Region not found.".into()));
 	};
 
-	if !has_tier {
+	let Some(tier) = tier else {
 		return Ok(Some("Too many resources allocated.".into()));
+	};
+
+	if let Some(error_message) = validate_resource_limits(&input.resources.limits, &tier) {
+		return Ok(Some(error_message));
 	}
 
 	let Some((build, upload_complete)) = upload_res else {
@@ -282,6 +513,15 @@ async fn validate(ctx: &ActivityCtx, input: &ValidateInput) -> GlobalResult<Opti
 		return Ok(Some("Environment not found.".into()));
 	};
 
+	if build.compression_dictionary {
+		if let ServerRuntime::Nomad = game_config.runtime {
+			return Ok(Some(
+				"Builds with a compression dictionary are not supported by this environment."
+					.into(),
+			));
+		}
+	}
+
 	if matches!(input.network_mode, NetworkMode::Host) && !game_config.host_networking_enabled {
 		return Ok(Some("Host networking is not enabled for this game.".into()));
 	}
@@ -328,7 +568,7 @@ async fn validate(ctx: &ActivityCtx, input: &ValidateInput) -> GlobalResult<Opti
 		}
 	}
 
-	if input.environment.len() > 64 {
+	if input.environment.len() + input.secrets.len() > 64 {
 		return Ok(Some("Too many environment variables (max 64).".into()));
 	}
 
@@ -346,6 +586,25 @@ async fn validate(ctx: &ActivityCtx, input: &ValidateInput) -> GlobalResult<Opti
 		}
 	}
 
+	for (k, name) in &input.secrets {
+		if k.len() > 256 {
+			return Ok(Some(format!(
+				"runtime.secrets[{:?}]: Key too large (max 256 bytes).",
+				util::safe_slice(k, 0, 256),
+			)));
+		}
+		if input.environment.contains_key(k) {
+			return Ok(Some(format!(
+				"runtime.secrets[{k:?}]: Already set in `runtime.environment`."
+			)));
+		}
+		if name.is_empty() || name.len() > 64 {
+			return Ok(Some(format!(
+				"runtime.secrets[{k:?}]: Secret name must be between 1 and 64 bytes."
+			)));
+		}
+	}
+
 	if input.network_ports.len() > 8 {
 		return Ok(Some("Too many ports (max 8).".into()));
 	}
@@ -379,35 +638,329 @@ async fn validate(ctx: &ActivityCtx, input: &ValidateInput) -> GlobalResult<Opti
 		}
 
 		match &port.routing {
-			Routing::GameGuard { authorization, .. } => match authorization {
-				PortAuthorization::Bearer(token) => {
-					if token.len() > 1024 {
-						return Ok(Some(format!(
-							"runtime.ports[{name:?}].routing.guard.authorization.bearer: Bearer authorization too large (max 1024 bytes).",
-						)));
+			Routing::GameGuard {
+				protocol,
+				authorization,
+				middleware,
+			} => {
+				match authorization {
+					PortAuthorization::Bearer(token) => {
+						if token.len() > 1024 {
+							return Ok(Some(format!(
+								"runtime.ports[{name:?}].routing.guard.authorization.bearer: Bearer authorization too large (max 1024 bytes).",
+							)));
+						}
 					}
-				}
-				PortAuthorization::Query(parameter, value) => {
-					if parameter.len() > 128 {
-						return Ok(Some(format!(
-							"runtime.ports[{name:?}].routing.guard.authorization.query: Query parameter too large (max 128 bytes).",
-						)));
+					PortAuthorization::Query(parameter, value) => {
+						if parameter.len() > 128 {
+							return Ok(Some(format!(
+								"runtime.ports[{name:?}].routing.guard.authorization.query: Query parameter too large (max 128 bytes).",
+							)));
+						}
+						if value.len() > 1024 {
+							return Ok(Some(format!(
+								"runtime.ports[{name:?}].routing.guard.authorization.query: Query value too large (max 1024 bytes).",
+							)));
+						}
 					}
-					if value.len() > 1024 {
+					PortAuthorization::None => {}
+				}
+
+				if let Some(middleware) = middleware {
+					if let Some(err) = validate_middleware(*protocol, middleware) {
 						return Ok(Some(format!(
-							"runtime.ports[{name:?}].routing.guard.authorization.query: Query value too large (max 1024 bytes).",
+							"runtime.ports[{name:?}].routing.guard.middleware{err}"
 						)));
 					}
 				}
-				PortAuthorization::None => {}
-			},
+			}
 			Routing::Host { .. } => {}
 		}
 	}
 
+	if !input.secrets.is_empty() {
+		if let ServerRuntime::Nomad = game_config.runtime {
+			return Ok(Some(
+				"Secrets are not supported by this environment.".into(),
+			));
+		}
+
+		let secrets_res = ctx
+			.op(crate::ops::secret::list_for_env::Input {
+				env_id: input.env_id,
+			})
+			.await?;
+
+		let names = secrets_res
+			.secrets
+			.iter()
+			.map(|secret| &secret.name)
+			.collect::<HashSet<_>>();
+
+		for (k, name) in &input.secrets {
+			if !names.contains(name) {
+				return Ok(Some(format!(
+					"runtime.secrets[{k:?}]: Secret {name:?} does not exist."
+				)));
+			}
+		}
+	}
+
+	if !input.volumes.is_empty() {
+		if let ServerRuntime::Nomad = game_config.runtime {
+			return Ok(Some(
+				"Volumes are not supported by this environment.".into(),
+			));
+		}
+
+		if let BuildKind::JavaScript = build.kind {
+			return Ok(Some(
+				"Volumes are not supported by JavaScript builds.".into(),
+			));
+		}
+
+		if let Some(error_message) = validate_volumes(ctx, input.env_id, &input.volumes).await? {
+			return Ok(Some(error_message));
+		}
+	}
+
 	Ok(None)
 }
 
+async fn validate_volumes(
+	ctx: &ActivityCtx,
+	env_id: Uuid,
+	volumes: &[ServerVolume],
+) -> GlobalResult<Option<String>> {
+	if volumes.len() > 4 {
+		return Ok(Some("Too many volumes (max 4).".into()));
+	}
+
+	let mut names = HashSet::new();
+	let mut paths = HashSet::new();
+	for (i, volume) in volumes.iter().enumerate() {
+		if volume.name.is_empty() || volume.name.len() > 64 {
+			return Ok(Some(format!(
+				"volumes[{i}].name: Must be between 1 and 64 bytes."
+			)));
+		}
+		if !names.insert(volume.name.as_str()) {
+			return Ok(Some(format!("volumes[{i}].name: Duplicate volume.")));
+		}
+
+		if !volume.path.starts_with('/')
+			|| volume.path == "/"
+			|| volume.path.split('/').any(|x| x == "..")
+			|| volume.path.len() > 256
+		{
+			return Ok(Some(format!(
+				"volumes[{i}].path: Must be an absolute path other than `/` (max 256 bytes)."
+			)));
+		}
+		if !paths.insert(volume.path.as_str()) {
+			return Ok(Some(format!("volumes[{i}].path: Duplicate path.")));
+		}
+
+		if volume.size_mib == 0 || volume.size_mib > MAX_VOLUME_SIZE_MIB {
+			return Ok(Some(format!(
+				"volumes[{i}].size: Must be between 1 and {MAX_VOLUME_SIZE_MIB} MiB."
+			)));
+		}
+	}
+
+	// Fails early for a clear error. This is checked again atomically when the volumes are inserted since
+	// another actor may mount the volume in between.
+	let existing = sql_fetch_all!(
+		[ctx, (String, i64, bool)]
+		"
+		SELECT
+			v.name,
+			v.size_mib,
+			EXISTS (
+				SELECT 1
+				FROM db_ds.server_volumes AS sv
+				JOIN db_ds.servers AS s
+				ON sv.server_id = s.server_id
+				WHERE
+					sv.volume_id = v.volume_id AND
+					s.destroy_ts IS NULL
+			)
+		FROM db_ds.volumes AS v
+		WHERE
+			v.env_id = $1 AND
+			v.name = ANY($2) AND
+			v.delete_ts IS NULL
+		",
+		env_id,
+		names.into_iter().collect::<Vec<_>>(),
+	)
+	.await?;
+
+	for (name, size_mib, in_use) in existing {
+		let Some(i) = volumes.iter().position(|volume| volume.name == name) else {
+			continue;
+		};
+
+		if i64::from(volumes[i].size_mib) != size_mib {
+			return Ok(Some(format!(
+				"volumes[{i}].size: Volume {name:?} already exists with a size of {size_mib} MiB."
+			)));
+		}
+		if in_use {
+			return Ok(Some(format!(
+				"volumes[{i}].name: Volume {name:?} is mounted by another actor."
+			)));
+		}
+	}
+
+	Ok(None)
+}
+
+/// Validates resource limits against the limits of the tier the actor will be allocated with.
+pub fn validate_resource_limits(
+	limits: &ServerResourceLimits,
+	tier: &tier::types::Tier,
+) -> Option<String> {
+	if let Some(io_weight) = limits.io_weight {
+		if !(10..=1000).contains(&io_weight) {
+			return Some("`resources.io_weight` must be between 10 and 1000.".into());
+		}
+	}
+
+	let checks = [
+		("pids", limits.pids, tier.pids),
+		(
+			"disk_read_bandwidth",
+			limits.disk_read_bandwidth,
+			tier.disk_bandwidth,
+		),
+		(
+			"disk_write_bandwidth",
+			limits.disk_write_bandwidth,
+			tier.disk_bandwidth,
+		),
+		("nofile", limits.nofile, tier.nofile),
+		("egress_bandwidth", limits.egress_bandwidth, tier.bandwidth),
+	];
+	for (name, value, max) in checks {
+		match value {
+			Some(0) => return Some(format!("`resources.{name}` must be greater than 0.")),
+			Some(value) if value > max => {
+				return Some(format!(
+					"`resources.{name}` exceeds the maximum of {max} for the requested resources."
+				));
+			}
+			_ => {}
+		}
+	}
+
+	None
+}
+
+/// Returns the path and message of the first invalid field.
+pub fn validate_middleware(
+	protocol: GameGuardProtocol,
+	middleware: &PortMiddleware,
+) -> Option<String> {
+	if !matches!(protocol, GameGuardProtocol::Http | GameGuardProtocol::Https) {
+		return Some(": Middleware is only supported on HTTP and HTTPS ports.".into());
+	}
+
+	if let Some(rate_limit) = &middleware.rate_limit {
+		if rate_limit.average == 0 || rate_limit.period_s == 0 {
+			return Some(".rate_limit: Average and period must be greater than 0.".into());
+		}
+		if rate_limit.period_s > 60 * 60 {
+			return Some(".rate_limit.period_s: Period too large (max 1 hour).".into());
+		}
+	}
+
+	if let Some(cors) = &middleware.cors {
+		if cors.allowed_origins.is_empty() {
+			return Some(".cors.allowed_origins: Must specify at least one origin.".into());
+		}
+		for (field, values) in [
+			("allowed_origins", &cors.allowed_origins),
+			("allowed_methods", &cors.allowed_methods),
+			("allowed_headers", &cors.allowed_headers),
+			("exposed_headers", &cors.exposed_headers),
+		] {
+			if values.len() > 32 {
+				return Some(format!(".cors.{field}: Too many entries (max 32)."));
+			}
+			if values.iter().any(|x| x.len() > 256) {
+				return Some(format!(".cors.{field}: Entry too large (max 256 bytes)."));
+			}
+		}
+	}
+
+	for (field, headers) in [
+		("request_headers", &middleware.request_headers),
+		("response_headers", &middleware.response_headers),
+	] {
+		if headers.len() > 32 {
+			return Some(format!(".{field}: Too many headers (max 32)."));
+		}
+		for (k, v) in headers {
+			if http::header::HeaderName::from_bytes(k.as_bytes()).is_err() {
+				return Some(format!(".{field}[{k:?}]: Invalid header name."));
+			}
+			if v.len() > 1024 || http::header::HeaderValue::from_str(v).is_err() {
+				return Some(format!(
+					".{field}[{k:?}]: Invalid header value (max 1024 bytes)."
+				));
+			}
+		}
+	}
+
+	if let Some(basic_auth) = &middleware.basic_auth {
+		if basic_auth.users.is_empty() || basic_auth.users.len() > 32 {
+			return Some(".basic_auth.users: Must specify between 1 and 32 users.".into());
+		}
+		if basic_auth
+			.users
+			.iter()
+			.any(|x| x.len() > 256 || !x.contains(':'))
+		{
+			return Some(
+				".basic_auth.users: Users must be htpasswd `user:hash` entries (max 256 bytes)."
+					.into(),
+			);
+		}
+		if basic_auth.realm.as_ref().map_or(false, |x| x.len() > 256) {
+			return Some(".basic_auth.realm: Realm too large (max 256 bytes).".into());
+		}
+	}
+
+	for (field, ranges) in [
+		("ip_allow_list", &middleware.ip_allow_list),
+		("ip_deny_list", &middleware.ip_deny_list),
+	] {
+		if ranges.len() > 64 {
+			return Some(format!(".{field}: Too many ranges (max 64)."));
+		}
+		if let Some(range) = ranges.iter().find(|x| !is_valid_ip_range(x)) {
+			return Some(format!(".{field}: Invalid IP or CIDR range {range:?}."));
+		}
+	}
+
+	// Game Guard applies the deny list by removing it from the allowed ranges
+	if let Some(ranges) = middleware.ip_allow_ranges() {
+		if ranges.is_empty() {
+			return Some(".ip_deny_list: Denies every allowed address.".into());
+		}
+		if ranges.len() > MAX_IP_ALLOW_RANGES {
+			return Some(".ip_deny_list: Too many disjoint ranges denied.".into());
+		}
+	}
+
+	None
+}
+
+pub fn is_valid_ip_range(range: &str) -> bool {
+	crate::types::parse_ip_range(range).is_some()
+}
+
 #[derive(Clone, Debug, Default)]
 struct GameGuardUnnest {
 	pub port_names: Vec<String>,
@@ -424,6 +977,12 @@ struct GameGuardAuthUnnest {
 	pub port_auth_values: Vec<String>,
 }
 
+#[derive(Clone, Debug, Default)]
+struct GameGuardMiddlewareUnnest {
+	pub port_names: Vec<String>,
+	pub configs: Vec<String>,
+}
+
 #[derive(Clone, Debug, Default)]
 struct HostUnnest {
 	pub port_names: Vec<String>,
@@ -432,7 +991,52 @@ struct HostUnnest {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
-struct InsertDbInput {
+struct InsertDbInputV1 {
+	server_id: Uuid,
+	env_id: Uuid,
+	datacenter_id: Uuid,
+	cluster_id: Uuid,
+	tags: util::serde::HashableMap<String, String>,
+	resources: ServerResourcesV1,
+	lifecycle: ServerLifecycle,
+	image_id: Uuid,
+	args: Vec<String>,
+	network_mode: NetworkMode,
+	environment: util::serde::HashableMap<String, String>,
+	network_ports: util::serde::HashableMap<String, PortV1>,
+}
+
+#[activity(InsertDb)]
+async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInputV1) -> GlobalResult<()> {
+	let input = input.clone();
+
+	insert_db_inner(
+		ctx,
+		&InsertDbInputV2 {
+			server_id: input.server_id,
+			env_id: input.env_id,
+			datacenter_id: input.datacenter_id,
+			cluster_id: input.cluster_id,
+			tags: input.tags,
+			resources: input.resources.into(),
+			lifecycle: input.lifecycle,
+			image_id: input.image_id,
+			args: input.args,
+			network_mode: input.network_mode,
+			environment: input.environment,
+			secrets: Default::default(),
+			network_ports: input
+				.network_ports
+				.into_iter()
+				.map(|(k, v)| (k, v.into()))
+				.collect(),
+		},
+	)
+	.await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+struct InsertDbInputV2 {
 	server_id: Uuid,
 	env_id: Uuid,
 	datacenter_id: Uuid,
@@ -444,13 +1048,19 @@ struct InsertDbInput {
 	args: Vec<String>,
 	network_mode: NetworkMode,
 	environment: util::serde::HashableMap<String, String>,
+	secrets: util::serde::HashableMap<String, String>,
 	network_ports: util::serde::HashableMap<String, Port>,
 }
 
-#[activity(InsertDb)]
-async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<()> {
+#[activity(InsertDbV2)]
+async fn insert_db_v2(ctx: &ActivityCtx, input: &InsertDbInputV2) -> GlobalResult<()> {
+	insert_db_inner(ctx, input).await
+}
+
+async fn insert_db_inner(ctx: &ActivityCtx, input: &InsertDbInputV2) -> GlobalResult<()> {
 	let mut gg_unnest = GameGuardUnnest::default();
 	let mut gg_auth_unnest = GameGuardAuthUnnest::default();
+	let mut gg_middleware_unnest = GameGuardMiddlewareUnnest::default();
 	let mut host_unnest = HostUnnest::default();
 
 	for (name, port) in input.network_ports.iter() {
@@ -458,6 +1068,7 @@ async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<()>
 			Routing::GameGuard {
 				protocol,
 				ref authorization,
+				ref middleware,
 			} => {
 				gg_unnest.port_names.push(name.clone());
 				gg_unnest
@@ -487,6 +1098,13 @@ async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<()>
 						gg_auth_unnest.port_auth_values.push(value.clone());
 					}
 				}
+
+				if let Some(middleware) = middleware {
+					gg_middleware_unnest.port_names.push(name.clone());
+					gg_middleware_unnest
+						.configs
+						.push(serde_json::to_string(middleware)?);
+				}
 			}
 			Routing::Host { protocol } => {
 				host_unnest.port_names.push(name.clone());
@@ -505,6 +1123,7 @@ async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<()>
 		let host_unnest = host_unnest.clone();
 		let gg_unnest = gg_unnest.clone();
 		let gg_auth_unnest = gg_auth_unnest.clone();
+		let gg_middleware_unnest = gg_middleware_unnest.clone();
 
 		async move {
 			sql_execute!(
@@ -526,9 +1145,11 @@ async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<()>
 							image_id,
 							args,
 							network_mode,
-							environment
+							environment,
+							resources_limits,
+							secrets
 						)
-						VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+						VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $28, $29)
 						RETURNING 1
 					),
 					host_port AS (
@@ -565,6 +1186,16 @@ async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<()>
 						SELECT $1, t.*
 						FROM unnest($22, $23, $24, $25) AS t(port_name, auth_type, auth_key, auth_value)
 						RETURNING 1
+					),
+					gg_port_middleware AS (
+						INSERT INTO db_ds.server_ports_gg_middleware (
+							server_id,
+							port_name,
+							config
+						)
+						SELECT $1, t.port_name, t.config::JSONB
+						FROM unnest($26, $27) AS t(port_name, config)
+						RETURNING 1
 					)
 				SELECT 1
 				",
@@ -592,7 +1223,11 @@ async fn insert_db(ctx: &ActivityCtx, input: &InsertDbInput) -> GlobalResult<()>
 				gg_auth_unnest.port_names,
 				gg_auth_unnest.port_auth_types,
 				gg_auth_unnest.port_auth_keys, // 20
-				gg_auth_unnest.port_auth_values,
+				gg_auth_unnest.port_auth_values, // 25
+				gg_middleware_unnest.port_names,
+				gg_middleware_unnest.configs,
+				serde_json::to_string(&input.resources.limits)?,
+				serde_json::to_string(&input.secrets)?,
 			)
 			.await
 		}
@@ -626,6 +1261,10 @@ struct GetServerMetaOutput {
 	build_file_name: String,
 	build_kind: BuildKind,
 	build_compression: BuildCompression,
+	#[serde(default)]
+	build_compression_dictionary: bool,
+	#[serde(default)]
+	build_image_digest: Option<String>,
 	dc_name_id: String,
 	dc_display_name: String,
 	dc_build_delivery_method: BuildDeliveryMethod,
@@ -669,6 +1308,8 @@ async fn get_server_meta(
 		build_file_name: build::utils::file_name(build.kind, build.compression),
 		build_kind: build.kind,
 		build_compression: build.compression,
+		build_compression_dictionary: build.compression_dictionary,
+		build_image_digest: build.image_digest.clone(),
 		dc_name_id: dc.name_id.clone(),
 		dc_display_name: dc.display_name.clone(),
 		dc_build_delivery_method: dc.build_delivery_method,
@@ -16,8 +16,8 @@ use sha2::{Digest, Sha256};
 use util::serde::AsHashableExt;
 
 use super::{
-	CreateComplete, Destroy, Drain, DrainState, Failed, GetServerMetaInput, InsertDbInput, Port,
-	Upgrade, DRAIN_PADDING_MS,
+	ports_v1, CreateComplete, Destroy, Drain, DrainState, Failed, GetServerMetaInput,
+	InsertDbInputV1, InsertDbInputV2, Port, PortV1, ServerResourcesV1, Upgrade, DRAIN_PADDING_MS,
 };
 use crate::{
 	types::{NetworkMode, Routing, ServerLifecycle, ServerResources},
@@ -203,41 +203,81 @@ pub(crate) async fn ds_server_nomad(ctx: &mut WorkflowCtx, input: &Input) -> Glo
 }
 
 async fn setup(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()> {
-	let (_, prereq) = ctx
-		.join((
-			activity(InsertDbInput {
-				server_id: input.server_id,
-				env_id: input.env_id,
+	let get_server_meta = GetServerMetaInput {
+		env_id: input.env_id,
+		image_id: input.image_id,
+		datacenter_id: input.datacenter_id,
+	};
+	let (_, prereq) = match ctx.check_version(2).await? {
+		1 => {
+			ctx.join((
+				activity(InsertDbInputV1 {
+					server_id: input.server_id,
+					env_id: input.env_id,
+					datacenter_id: input.datacenter_id,
+					cluster_id: input.cluster_id,
+					tags: input.tags.as_hashable(),
+					resources: input.resources.clone().into(),
+					lifecycle: input.lifecycle.clone(),
+					image_id: input.image_id,
+					args: input.args.clone(),
+					network_mode: input.network_mode,
+					environment: input.environment.as_hashable(),
+					network_ports: ports_v1(&input.network_ports),
+				}),
+				activity(get_server_meta),
+			))
+			.await?
+		}
+		_latest => {
+			ctx.join((
+				activity(InsertDbInputV2 {
+					server_id: input.server_id,
+					env_id: input.env_id,
+					datacenter_id: input.datacenter_id,
+					cluster_id: input.cluster_id,
+					tags: input.tags.as_hashable(),
+					resources: input.resources.clone(),
+					lifecycle: input.lifecycle.clone(),
+					image_id: input.image_id,
+					args: input.args.clone(),
+					network_mode: input.network_mode,
+					environment: input.environment.as_hashable(),
+					secrets: Default::default(),
+					network_ports: input.network_ports.as_hashable(),
+				}),
+				activity(get_server_meta),
+			))
+			.await?
+		}
+	};
+
+	let job_id = match ctx.check_version(2).await? {
+		1 => {
+			ctx.activity(SubmitJobInputV1 {
+				datacenter_id: input.datacenter_id,
+				resources: input.resources.clone().into(),
+				network_mode: input.network_mode,
+				network_ports: ports_v1(&input.network_ports),
+				build_kind: prereq.build_kind,
+				build_compression: prereq.build_compression,
+				dc_name_id: prereq.dc_name_id,
+			})
+			.await?
+		}
+		_latest => {
+			ctx.activity(SubmitJobInputV2 {
 				datacenter_id: input.datacenter_id,
-				cluster_id: input.cluster_id,
-				tags: input.tags.as_hashable(),
 				resources: input.resources.clone(),
-				lifecycle: input.lifecycle.clone(),
-				image_id: input.image_id,
-				args: input.args.clone(),
 				network_mode: input.network_mode,
-				environment: input.environment.as_hashable(),
 				network_ports: input.network_ports.as_hashable(),
-			}),
-			activity(GetServerMetaInput {
-				env_id: input.env_id,
-				image_id: input.image_id,
-				datacenter_id: input.datacenter_id,
-			}),
-		))
-		.await?;
-
-	let job_id = ctx
-		.activity(SubmitJobInput {
-			datacenter_id: input.datacenter_id,
-			resources: input.resources.clone(),
-			network_mode: input.network_mode,
-			network_ports: input.network_ports.as_hashable(),
-			build_kind: prereq.build_kind,
-			build_compression: prereq.build_compression,
-			dc_name_id: prereq.dc_name_id,
-		})
-		.await?;
+				build_kind: prereq.build_kind,
+				build_compression: prereq.build_compression,
+				dc_name_id: prereq.dc_name_id,
+			})
+			.await?
+		}
+	};
 
 	let (artifacts, _) = ctx
 		.join((
@@ -275,7 +315,39 @@ async fn setup(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()> {
 }
 
 #[derive(Debug, Serialize, Deserialize, Hash)]
-struct SubmitJobInput {
+struct SubmitJobInputV1 {
+	datacenter_id: Uuid,
+	resources: ServerResourcesV1,
+	network_mode: NetworkMode,
+	network_ports: util::serde::HashableMap<String, PortV1>,
+	build_kind: BuildKind,
+	build_compression: BuildCompression,
+	dc_name_id: String,
+}
+
+#[activity(SubmitJob)]
+async fn submit_job(ctx: &ActivityCtx, input: &SubmitJobInputV1) -> GlobalResult<String> {
+	submit_job_inner(
+		ctx,
+		&SubmitJobInputV2 {
+			datacenter_id: input.datacenter_id,
+			resources: input.resources.clone().into(),
+			network_mode: input.network_mode,
+			network_ports: input
+				.network_ports
+				.iter()
+				.map(|(k, v)| (k.clone(), v.clone().into()))
+				.collect(),
+			build_kind: input.build_kind,
+			build_compression: input.build_compression,
+			dc_name_id: input.dc_name_id.clone(),
+		},
+	)
+	.await
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct SubmitJobInputV2 {
 	datacenter_id: Uuid,
 	resources: ServerResources,
 	network_mode: NetworkMode,
@@ -285,8 +357,12 @@ struct SubmitJobInput {
 	dc_name_id: String,
 }
 
-#[activity(SubmitJob)]
-async fn submit_job(ctx: &ActivityCtx, input: &SubmitJobInput) -> GlobalResult<String> {
+#[activity(SubmitJobV2)]
+async fn submit_job_v2(ctx: &ActivityCtx, input: &SubmitJobInputV2) -> GlobalResult<String> {
+	submit_job_inner(ctx, input).await
+}
+
+async fn submit_job_inner(ctx: &ActivityCtx, input: &SubmitJobInputV2) -> GlobalResult<String> {
 	let tier_res = ctx
 		.op(tier::ops::list::Input {
 			datacenter_ids: vec![input.datacenter_id],
@@ -448,6 +524,10 @@ async fn submit_job(ctx: &ActivityCtx, input: &SubmitJobInput) -> GlobalResult<S
 		BuildCompression::Lz4 => {
 			download_cmd.push_str(" | lz4 -d -");
 		}
+		// NOTE: Builds compressed with a dictionary are not supported on Nomad
+		BuildCompression::Zstd => {
+			download_cmd.push_str(" | zstd -d -");
+		}
 	}
 
 	// IMPORTANT: This job spec must be deterministic. Do not pass in parameters
@@ -12,6 +12,9 @@ pub(crate) struct Input {
 	/// Whether or not to send signals to the actor. In the case that the actor was already stopped
 	/// or exited, signals are unnecessary.
 	pub signal_actor: bool,
+	/// Whether or not to snapshot the server's volumes once the actor is stopped.
+	#[serde(default)]
+	pub snapshot_volumes: bool,
 }
 
 #[workflow]
@@ -41,6 +44,10 @@ pub(crate) async fn ds_server_pegboard_destroy(
 			)
 			.await?;
 		}
+
+		if input.snapshot_volumes {
+			super::volumes::snapshot(ctx, input.server_id).await?;
+		}
 	}
 
 	ctx.msg(DestroyComplete {})
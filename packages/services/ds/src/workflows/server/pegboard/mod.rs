@@ -13,15 +13,18 @@ use tokio::time::Instant;
 use util::serde::AsHashableExt;
 
 use super::{
-	CreateComplete, Destroy, Drain, DrainState, Failed, GetServerMetaInput, GetServerMetaOutput,
-	InsertDbInput, Port, Ready, SetConnectableInput, UpdateImageInput, UpdateRescheduleRetryInput,
-	Upgrade, UpgradeComplete, UpgradeStarted, BASE_RETRY_TIMEOUT_MS, DRAIN_PADDING_MS,
+	ports_v1, CreateComplete, Destroy, Drain, DrainState, Failed, GetServerMetaInput,
+	GetServerMetaOutput, InsertDbInputV1, InsertDbInputV2, Port, Ready, ServerResourcesV1,
+	SetConnectableInput, UpdateImageInput, UpdateRescheduleRetryInput, Upgrade, UpgradeComplete,
+	UpgradeStarted, BASE_RETRY_TIMEOUT_MS, DRAIN_PADDING_MS,
 };
 use crate::types::{
 	GameGuardProtocol, HostProtocol, NetworkMode, Routing, ServerLifecycle, ServerResources,
+	ServerVolume,
 };
 
 pub mod destroy;
+mod volumes;
 
 #[derive(Serialize, Deserialize)]
 struct StateRes {
@@ -43,14 +46,37 @@ pub(crate) struct Input {
 	pub args: Vec<String>,
 	pub network_mode: NetworkMode,
 	pub environment: HashMap<String, String>,
+	#[serde(default)]
+	pub secrets: HashMap<String, String>,
 	pub network_ports: HashMap<String, Port>,
+	#[serde(default)]
+	pub volumes: Vec<ServerVolume>,
 }
 
 #[workflow]
 pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()> {
 	let res = setup(ctx, input, SetupCtx::Init).await;
 	match ctx.catch_unrecoverable(res)? {
-		Ok(_actor_id) => {}
+		Ok(Ok(_actor_setup)) => {}
+		Ok(Err(error_message)) => {
+			ctx.msg(Failed {
+				message: error_message,
+			})
+			.tag("server_id", input.server_id)
+			.send()
+			.await?;
+
+			ctx.workflow(destroy::Input {
+				server_id: input.server_id,
+				override_kill_timeout_ms: None,
+				signal_actor: false,
+				snapshot_volumes: false,
+			})
+			.output()
+			.await?;
+
+			return Ok(());
+		}
 		Err(err) => {
 			tracing::error!(?err, "unrecoverable setup");
 
@@ -65,6 +91,7 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 				server_id: input.server_id,
 				override_kill_timeout_ms: None,
 				signal_actor: false,
+				snapshot_volumes: false,
 			})
 			.output()
 			.await?;
@@ -79,7 +106,7 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 		.send()
 		.await?;
 
-	let _client_id = match ctx.listen::<Init>().await? {
+	let client_id = match ctx.listen::<Init>().await? {
 		Init::ActorStateUpdate(sig) => match sig.state {
 			pp::ActorState::Allocated { client_id } => client_id,
 			pp::ActorState::FailedToAllocate => {
@@ -94,6 +121,7 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 					server_id: input.server_id,
 					override_kill_timeout_ms: None,
 					signal_actor: false,
+					snapshot_volumes: false,
 				})
 				.output()
 				.await?;
@@ -109,6 +137,7 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 				server_id: input.server_id,
 				override_kill_timeout_ms: sig.override_kill_timeout_ms,
 				signal_actor: true,
+				snapshot_volumes: false,
 			})
 			.output()
 			.await?;
@@ -117,6 +146,10 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 		}
 	};
 
+	if !input.volumes.is_empty() {
+		volumes::set_client(ctx, input.server_id, client_id).await?;
+	}
+
 	let state_res = ctx
 		.repeat(|ctx| {
 			let input = input.clone();
@@ -180,7 +213,11 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 
 							// Reschedule durable actor if it errored
 							if input.lifecycle.durable && failed {
-								if let Some(sig) = reschedule_actor(ctx, &input, None).await? {
+								let client_lost = matches!(sig.state, pp::ActorState::Lost);
+
+								if let Some(sig) =
+									reschedule_actor(ctx, &input, None, client_lost).await?
+								{
 									// Destroyed early
 									return Ok(Loop::Break(StateRes {
 										signal_actor: true,
@@ -242,7 +279,9 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 									)
 									.await?;
 
-									if let Some(sig) = reschedule_actor(ctx, &input, None).await? {
+									if let Some(sig) =
+										reschedule_actor(ctx, &input, None, false).await?
+									{
 										// Destroyed early
 										return Ok(Loop::Break(StateRes {
 											signal_actor: true,
@@ -277,7 +316,8 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 						// Kill old actor immediately
 						destroy::destroy_actor(ctx, input.datacenter_id, 0, true, actor_id).await?;
 
-						if let Some(sig) = reschedule_actor(ctx, &input, Some(sig.image_id)).await?
+						if let Some(sig) =
+							reschedule_actor(ctx, &input, Some(sig.image_id), false).await?
 						{
 							// Destroyed early
 							return Ok(Loop::Break(StateRes {
@@ -309,6 +349,7 @@ pub(crate) async fn ds_server_pegboard(ctx: &mut WorkflowCtx, input: &Input) ->
 		server_id: input.server_id,
 		override_kill_timeout_ms: state_res.override_kill_timeout_ms,
 		signal_actor: state_res.signal_actor,
+		snapshot_volumes: !input.volumes.is_empty(),
 	})
 	.output()
 	.await?;
@@ -328,30 +369,63 @@ struct ActorSetupCtx {
 	resources: pp::Resources,
 	artifact_url_stub: String,
 	fallback_artifact_url: Option<String>,
+	compression_dictionary: Option<ResolveArtifactsOutput>,
+	volumes: Vec<pp::Volume>,
 }
 
+/// Returns an error message if the server cannot be created with its current config.
 async fn setup(
 	ctx: &mut WorkflowCtx,
 	input: &Input,
 	setup: SetupCtx,
-) -> GlobalResult<ActorSetupCtx> {
+) -> GlobalResult<Result<ActorSetupCtx, String>> {
 	let image_id = match &setup {
 		SetupCtx::Init => {
-			ctx.activity(InsertDbInput {
-				server_id: input.server_id,
-				env_id: input.env_id,
-				datacenter_id: input.datacenter_id,
-				cluster_id: input.cluster_id,
-				tags: input.tags.as_hashable(),
-				resources: input.resources.clone(),
-				lifecycle: input.lifecycle.clone(),
-				image_id: input.image_id,
-				args: input.args.clone(),
-				network_mode: input.network_mode,
-				environment: input.environment.as_hashable(),
-				network_ports: input.network_ports.as_hashable(),
-			})
-			.await?;
+			match ctx.check_version(2).await? {
+				1 => {
+					ctx.activity(InsertDbInputV1 {
+						server_id: input.server_id,
+						env_id: input.env_id,
+						datacenter_id: input.datacenter_id,
+						cluster_id: input.cluster_id,
+						tags: input.tags.as_hashable(),
+						resources: input.resources.clone().into(),
+						lifecycle: input.lifecycle.clone(),
+						image_id: input.image_id,
+						args: input.args.clone(),
+						network_mode: input.network_mode,
+						environment: input.environment.as_hashable(),
+						network_ports: ports_v1(&input.network_ports),
+					})
+					.await?
+				}
+				_latest => {
+					ctx.activity(InsertDbInputV2 {
+						server_id: input.server_id,
+						env_id: input.env_id,
+						datacenter_id: input.datacenter_id,
+						cluster_id: input.cluster_id,
+						tags: input.tags.as_hashable(),
+						resources: input.resources.clone(),
+						lifecycle: input.lifecycle.clone(),
+						image_id: input.image_id,
+						args: input.args.clone(),
+						network_mode: input.network_mode,
+						environment: input.environment.as_hashable(),
+						secrets: input.secrets.as_hashable(),
+						network_ports: input.network_ports.as_hashable(),
+					})
+					.await?
+				}
+			}
+
+			if !input.volumes.is_empty() {
+				if let Some(error_message) =
+					volumes::insert(ctx, input.server_id, input.env_id, &input.volumes).await?
+				{
+					return Ok(Err(error_message));
+				}
+			}
 
 			input.image_id
 		}
@@ -378,22 +452,61 @@ async fn setup(
 		})
 		.await?;
 
-	let (actor_id, resources, artifacts_res) = ctx
-		.join((
-			activity(SelectActorIdInput {
-				server_id: input.server_id,
-			}),
-			activity(SelectResourcesInput {
-				datacenter_id: input.datacenter_id,
-				resources: input.resources.clone(),
-			}),
-			activity(ResolveArtifactsInput {
+	let (actor_id, resources, artifacts_res) = match ctx.check_version(2).await? {
+		1 => {
+			ctx.join((
+				activity(SelectActorIdInput {
+					server_id: input.server_id,
+				}),
+				activity(SelectResourcesInputV1 {
+					datacenter_id: input.datacenter_id,
+					resources: input.resources.clone().into(),
+				}),
+				activity(ResolveArtifactsInput {
+					build_upload_id: server_meta.build_upload_id,
+					build_file_name: server_meta.build_file_name.clone(),
+					dc_build_delivery_method: server_meta.dc_build_delivery_method,
+				}),
+			))
+			.await?
+		}
+		_latest => {
+			ctx.join((
+				activity(SelectActorIdInput {
+					server_id: input.server_id,
+				}),
+				activity(SelectResourcesInputV2 {
+					datacenter_id: input.datacenter_id,
+					resources: input.resources.clone(),
+				}),
+				activity(ResolveArtifactsInput {
+					build_upload_id: server_meta.build_upload_id,
+					build_file_name: server_meta.build_file_name.clone(),
+					dc_build_delivery_method: server_meta.dc_build_delivery_method,
+				}),
+			))
+			.await?
+		}
+	};
+
+	let compression_dictionary = if server_meta.build_compression_dictionary {
+		Some(
+			ctx.activity(ResolveArtifactsInput {
 				build_upload_id: server_meta.build_upload_id,
-				build_file_name: server_meta.build_file_name.clone(),
+				build_file_name: build::utils::COMPRESSION_DICTIONARY_FILE_NAME.to_string(),
 				dc_build_delivery_method: server_meta.dc_build_delivery_method,
-			}),
-		))
-		.await?;
+			})
+			.await?,
+		)
+	} else {
+		None
+	};
+
+	let actor_volumes = if !input.volumes.is_empty() {
+		volumes::resolve(ctx, input.server_id).await?
+	} else {
+		Vec::new()
+	};
 
 	let actor_setup = ActorSetupCtx {
 		actor_id,
@@ -401,14 +514,18 @@ async fn setup(
 		resources,
 		artifact_url_stub: artifacts_res.artifact_url_stub,
 		fallback_artifact_url: artifacts_res.fallback_artifact_url,
+		compression_dictionary,
+		volumes: actor_volumes,
 	};
 
 	// Rescheduling handles spawning the actor manually
 	if let SetupCtx::Init = setup {
-		spawn_actor(ctx, input, &actor_setup).await?;
+		if let Err(error_message) = spawn_actor(ctx, input, &actor_setup).await? {
+			return Ok(Err(error_message));
+		}
 	}
 
-	Ok(actor_setup)
+	Ok(Ok(actor_setup))
 }
 
 #[derive(Debug, Serialize, Deserialize, Hash)]
@@ -442,15 +559,43 @@ async fn select_actor_id(ctx: &ActivityCtx, input: &SelectActorIdInput) -> Globa
 }
 
 #[derive(Debug, Serialize, Deserialize, Hash)]
-struct SelectResourcesInput {
+struct SelectResourcesInputV1 {
 	datacenter_id: Uuid,
-	resources: ServerResources,
+	resources: ServerResourcesV1,
 }
 
 #[activity(SelectResources)]
 async fn select_resources(
 	ctx: &ActivityCtx,
-	input: &SelectResourcesInput,
+	input: &SelectResourcesInputV1,
+) -> GlobalResult<pp::Resources> {
+	select_resources_inner(
+		ctx,
+		&SelectResourcesInputV2 {
+			datacenter_id: input.datacenter_id,
+			resources: input.resources.clone().into(),
+		},
+	)
+	.await
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct SelectResourcesInputV2 {
+	datacenter_id: Uuid,
+	resources: ServerResources,
+}
+
+#[activity(SelectResourcesV2)]
+async fn select_resources_v2(
+	ctx: &ActivityCtx,
+	input: &SelectResourcesInputV2,
+) -> GlobalResult<pp::Resources> {
+	select_resources_inner(ctx, input).await
+}
+
+async fn select_resources_inner(
+	ctx: &ActivityCtx,
+	input: &SelectResourcesInputV2,
 ) -> GlobalResult<pp::Resources> {
 	let tier_res = ctx
 		.op(tier::ops::list::Input {
@@ -475,19 +620,50 @@ async fn select_resources(
 	let memory = tier.memory as u64 * (1024 * 1024);
 	let memory_max = tier.memory_max as u64 * (1024 * 1024);
 
+	// Unset limits default to the tier's limits
+	let limits = &input.resources.limits;
+	let disk_read_bandwidth = limits.disk_read_bandwidth.unwrap_or(tier.disk_bandwidth);
+	let disk_write_bandwidth = limits.disk_write_bandwidth.unwrap_or(tier.disk_bandwidth);
+	let egress_bandwidth = limits.egress_bandwidth.unwrap_or(tier.bandwidth);
+
 	Ok(pp::Resources {
 		cpu,
 		memory,
 		memory_max,
 		disk: tier.disk,
+		// Actors created before pids limits existed never had one, only limit when requested
+		pids: limits.pids.map(|x| x as u64),
+		io_weight: Some(limits.io_weight.unwrap_or(tier.io_weight)),
+		// MiB/s to bytes per second
+		io_read_bps: Some(disk_read_bandwidth as u64 * 1024 * 1024),
+		io_write_bps: Some(disk_write_bandwidth as u64 * 1024 * 1024),
+		nofile: Some(limits.nofile.unwrap_or(tier.nofile) as u64),
+		// Kibps to bytes per second
+		egress_bps: Some(egress_bandwidth as u64 * 1024 / 8),
 	})
 }
 
+/// Returns an error message if the actor cannot be started with its current config.
 async fn spawn_actor(
 	ctx: &mut WorkflowCtx,
 	input: &Input,
 	actor_setup: &ActorSetupCtx,
-) -> GlobalResult<()> {
+) -> GlobalResult<Result<(), String>> {
+	// Only secret names are sent with the command, pegboard decrypts them right before sending the
+	// command to the client so that values are never persisted. Checked each time the actor is started
+	// since secrets may be deleted in the meantime.
+	if !input.secrets.is_empty() {
+		if let Some(error_message) = ctx
+			.activity(CheckSecretsInput {
+				env_id: input.env_id,
+				secrets: input.secrets.as_hashable(),
+			})
+			.await?
+		{
+			return Ok(Err(error_message));
+		}
+	}
+
 	ctx.signal(pp::Command::StartActor {
 		actor_id: actor_setup.actor_id,
 		config: Box::new(pp::ActorConfig {
@@ -503,10 +679,19 @@ async fn spawn_actor(
 				compression: match actor_setup.server_meta.build_compression {
 					BuildCompression::None => pp::ImageCompression::None,
 					BuildCompression::Lz4 => pp::ImageCompression::Lz4,
+					BuildCompression::Zstd => pp::ImageCompression::Zstd,
 				},
+				digest: actor_setup.server_meta.build_image_digest.clone(),
+				compression_dictionary: actor_setup.compression_dictionary.as_ref().map(
+					|artifacts| pp::ImageArtifact {
+						artifact_url_stub: artifacts.artifact_url_stub.clone(),
+						fallback_artifact_url: artifacts.fallback_artifact_url.clone(),
+					},
+				),
 			},
 			root_user_enabled: input.root_user_enabled,
 			env: input.environment.as_hashable(),
+			secrets: input.secrets.as_hashable(),
 			ports: input
 				.network_ports
 				.iter()
@@ -572,13 +757,53 @@ async fn spawn_actor(
 					build_id: input.image_id,
 				},
 			})?,
+			volumes: actor_setup.volumes.clone(),
 		}),
 	})
 	.tag("datacenter_id", input.datacenter_id)
 	.send()
 	.await?;
 
-	Ok(())
+	Ok(Ok(()))
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct CheckSecretsInput {
+	env_id: Uuid,
+	/// Env var name -> secret name.
+	secrets: util::serde::HashableMap<String, String>,
+}
+
+/// Returns an error message if a referenced secret does not exist.
+#[activity(CheckSecrets)]
+async fn check_secrets(
+	ctx: &ActivityCtx,
+	input: &CheckSecretsInput,
+) -> GlobalResult<Option<String>> {
+	let secrets_res = ctx
+		.op(crate::ops::secret::list_for_env::Input {
+			env_id: input.env_id,
+		})
+		.await?;
+
+	let names = secrets_res
+		.secrets
+		.iter()
+		.map(|secret| &secret.name)
+		.collect::<HashSet<_>>();
+
+	let mut secrets = input.secrets.iter().collect::<Vec<_>>();
+	secrets.sort();
+
+	for (k, name) in secrets {
+		if !names.contains(name) {
+			return Ok(Some(format!(
+				"runtime.secrets[{k:?}]: Secret {name:?} does not exist."
+			)));
+		}
+	}
+
+	Ok(None)
 }
 
 #[derive(Debug, Serialize, Deserialize, Hash)]
@@ -588,7 +813,7 @@ struct ResolveArtifactsInput {
 	dc_build_delivery_method: BuildDeliveryMethod,
 }
 
-#[derive(Debug, Serialize, Deserialize, Hash)]
+#[derive(Clone, Debug, Serialize, Deserialize, Hash)]
 struct ResolveArtifactsOutput {
 	artifact_url_stub: String,
 	fallback_artifact_url: Option<String>,
@@ -599,12 +824,11 @@ async fn resolve_artifacts(
 	ctx: &ActivityCtx,
 	input: &ResolveArtifactsInput,
 ) -> GlobalResult<ResolveArtifactsOutput> {
-	let artifact_url_stub = format!(
-		"/s3-cache/{namespace}-bucket-build/{upload_id}/{file_name}",
-		namespace = ctx.config().server()?.tivet.namespace,
-		upload_id = input.build_upload_id,
-		file_name = input.build_file_name,
-	);
+	let artifact_url_stub = crate::util::image_artifact_url_stub(
+		ctx.config(),
+		input.build_upload_id,
+		&input.build_file_name,
+	)?;
 
 	let fallback_artifact_url =
 		if let BuildDeliveryMethod::S3Direct = input.dc_build_delivery_method {
@@ -723,20 +947,30 @@ async fn update_ports(ctx: &ActivityCtx, input: &UpdatePortsInput) -> GlobalResu
 	Ok(())
 }
 
+/// `client_lost` is set if the client that ran the actor is lost and cannot snapshot its volumes.
 async fn reschedule_actor(
 	ctx: &mut WorkflowCtx,
 	input: &Input,
 	new_image_id: Option<Uuid>,
+	client_lost: bool,
 ) -> GlobalResult<Option<Destroy>> {
 	tracing::info!("rescheduling actor");
 
+	// The actor may be allocated to a different client, which restores the volumes from these snapshots
+	if !input.volumes.is_empty() {
+		volumes::snapshot(ctx, input.server_id, client_lost).await?;
+	}
+
 	// Remove old proxied ports
 	ctx.activity(ClearPortsInput {
 		server_id: input.server_id,
 	})
 	.await?;
 
-	let actor_setup = setup(ctx, &input, SetupCtx::Reschedule { new_image_id }).await?;
+	// Volumes are only inserted on init, rescheduling cannot fail validation
+	let Ok(actor_setup) = setup(ctx, &input, SetupCtx::Reschedule { new_image_id }).await? else {
+		bail!("unexpected setup error on reschedule");
+	};
 
 	// Waits for the actor to be ready (or destroyed) and automatically retries if failed to allocate.
 	ctx.repeat(|ctx| {
@@ -774,13 +1008,30 @@ async fn reschedule_actor(
 				}
 			}
 
-			spawn_actor(ctx, &input, &actor_setup).await?;
+			if let Err(error_message) = spawn_actor(ctx, &input, &actor_setup).await? {
+				tracing::warn!(server_id=?input.server_id, %error_message, "failed to start actor");
+
+				ctx.msg(Failed {
+					message: error_message,
+				})
+				.tag("server_id", input.server_id)
+				.send()
+				.await?;
+
+				return Ok(Loop::Break(Some(Destroy {
+					override_kill_timeout_ms: None,
+				})));
+			}
 
 			match ctx.listen::<Init>().await? {
 				Init::ActorStateUpdate(sig) => match sig.state {
-					pp::ActorState::Allocated {
-						client_id: _client_id,
-					} => return Ok(Loop::Break(None)),
+					pp::ActorState::Allocated { client_id } => {
+						if !input.volumes.is_empty() {
+							volumes::set_client(ctx, input.server_id, client_id).await?;
+						}
+
+						return Ok(Loop::Break(None));
+					}
 					pp::ActorState::FailedToAllocate => return Ok(Loop::Continue),
 					state => bail!("unexpected actor state: {state:?}"),
 				},
@@ -0,0 +1,370 @@
+use std::time::Duration;
+
+use chirp_workflow::prelude::*;
+use pegboard::protocol as pp;
+
+use crate::{types::ServerVolume, workflows::volume};
+
+/// How long snapshot download URLs are valid for. Must outlast the backoff of failed allocations.
+const DOWNLOAD_URL_EXPIRY: Duration = Duration::from_secs(60 * 60);
+
+/// Creates volumes that do not exist yet and mounts them in to the server. Returns an error message if a
+/// volume exists with a different size or is mounted by another server.
+pub(crate) async fn insert(
+	ctx: &mut WorkflowCtx,
+	server_id: Uuid,
+	env_id: Uuid,
+	volumes: &[ServerVolume],
+) -> GlobalResult<Option<String>> {
+	ctx.activity(InsertVolumesInput {
+		server_id,
+		env_id,
+		volumes: volumes.to_vec(),
+	})
+	.await
+}
+
+/// Builds the volume config sent to the client, including where to restore the latest snapshot from.
+pub(crate) async fn resolve(
+	ctx: &mut WorkflowCtx,
+	server_id: Uuid,
+) -> GlobalResult<Vec<pp::Volume>> {
+	ctx.activity(ResolveVolumesInput { server_id }).await
+}
+
+/// Records the client the server's volumes were allocated to. This client has the latest copy of the
+/// volumes once the actor runs.
+pub(crate) async fn set_client(
+	ctx: &mut WorkflowCtx,
+	server_id: Uuid,
+	client_id: Uuid,
+) -> GlobalResult<()> {
+	ctx.activity(UpdateVolumeClientsInput {
+		server_id,
+		client_id,
+	})
+	.await
+}
+
+/// Uploads snapshots of the server's volumes from the client that last ran the actor and waits for them to
+/// finish. The actor must be stopped.
+///
+/// If the client is lost or a snapshot fails, the volume is restored from its previous snapshot instead and
+/// marked as stale.
+pub(crate) async fn snapshot(
+	ctx: &mut WorkflowCtx,
+	server_id: Uuid,
+	client_lost: bool,
+) -> GlobalResult<()> {
+	// Don't wait for a client that cannot respond
+	if client_lost {
+		tracing::warn!(
+			?server_id,
+			"client lost, restoring volumes from their previous snapshots"
+		);
+
+		return ctx
+			.activity(MarkVolumesStaleInput {
+				server_id,
+				volume_ids: None,
+			})
+			.await;
+	}
+
+	let snapshots = ctx.activity(PrepareSnapshotsInput { server_id }).await?;
+
+	for (volume_id, snapshot_id) in &snapshots {
+		ctx.workflow(volume::snapshot::Input {
+			volume_id: *volume_id,
+			snapshot_id: *snapshot_id,
+			server_id: Some(server_id),
+		})
+		.tag("snapshot_id", snapshot_id)
+		.dispatch()
+		.await?;
+	}
+
+	let mut failed_volume_ids = Vec::new();
+	for _ in &snapshots {
+		let sig = ctx.listen::<volume::snapshot::SnapshotComplete>().await?;
+
+		if !sig.complete {
+			tracing::warn!(?server_id, volume_id=?sig.volume_id, "failed to snapshot volume");
+
+			failed_volume_ids.push(sig.volume_id);
+		}
+	}
+
+	if !failed_volume_ids.is_empty() {
+		ctx.activity(MarkVolumesStaleInput {
+			server_id,
+			volume_ids: Some(failed_volume_ids),
+		})
+		.await?;
+	}
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertVolumesInput {
+	server_id: Uuid,
+	env_id: Uuid,
+	volumes: Vec<ServerVolume>,
+}
+
+#[activity(InsertVolumes)]
+async fn insert_volumes(
+	ctx: &ActivityCtx,
+	input: &InsertVolumesInput,
+) -> GlobalResult<Option<String>> {
+	let create_ts = util::timestamp::now();
+
+	for (i, volume) in input.volumes.iter().enumerate() {
+		// Validation checks the size and mounts before the server is created, but concurrent creates can
+		// race past it. Checking and mounting in one serializable statement makes sure a volume is only
+		// mounted by one server.
+		let (size_mib, in_use) = sql_fetch_one!(
+			[ctx, (i64, bool)]
+			"
+			WITH
+				insert_volume AS (
+					INSERT INTO db_ds.volumes (volume_id, env_id, name, size_mib, create_ts)
+					VALUES ($3, $2, $4, $5, $6)
+					ON CONFLICT (env_id, name) WHERE delete_ts IS NULL
+					DO NOTHING
+					RETURNING volume_id, size_mib
+				),
+				volume AS (
+					SELECT volume_id, size_mib FROM insert_volume
+					UNION ALL
+					SELECT volume_id, size_mib
+					FROM db_ds.volumes
+					WHERE
+						env_id = $2 AND
+						name = $4 AND
+						delete_ts IS NULL
+					LIMIT 1
+				),
+				in_use AS (
+					SELECT EXISTS (
+						SELECT 1
+						FROM volume AS v
+						JOIN db_ds.server_volumes AS sv
+						ON sv.volume_id = v.volume_id
+						JOIN db_ds.servers AS s
+						ON sv.server_id = s.server_id
+						WHERE
+							sv.server_id != $1 AND
+							s.destroy_ts IS NULL
+					) AS in_use
+				),
+				insert_server_volume AS (
+					INSERT INTO db_ds.server_volumes (server_id, volume_id, path)
+					SELECT $1, v.volume_id, $7
+					FROM volume AS v, in_use AS u
+					WHERE
+						v.size_mib = $5 AND
+						NOT u.in_use
+					ON CONFLICT DO NOTHING
+					RETURNING 1
+				)
+			SELECT v.size_mib, u.in_use
+			FROM volume AS v, in_use AS u
+			",
+			input.server_id,
+			input.env_id,
+			Uuid::new_v4(),
+			&volume.name,
+			i64::from(volume.size_mib),
+			create_ts,
+			&volume.path,
+		)
+		.await?;
+
+		if i64::from(volume.size_mib) != size_mib {
+			return Ok(Some(format!(
+				"volumes[{i}].size: Volume {:?} already exists with a size of {size_mib} MiB.",
+				volume.name
+			)));
+		}
+		if in_use {
+			return Ok(Some(format!(
+				"volumes[{i}].name: Volume {:?} is mounted by another actor.",
+				volume.name
+			)));
+		}
+	}
+
+	Ok(None)
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct ResolveVolumesInput {
+	server_id: Uuid,
+}
+
+#[derive(sqlx::FromRow)]
+struct VolumeRow {
+	volume_id: Uuid,
+	path: String,
+	size_mib: i64,
+	snapshot_id: Option<Uuid>,
+}
+
+#[activity(ResolveVolumes)]
+async fn resolve_volumes(
+	ctx: &ActivityCtx,
+	input: &ResolveVolumesInput,
+) -> GlobalResult<Vec<pp::Volume>> {
+	let rows = sql_fetch_all!(
+		[ctx, VolumeRow]
+		"
+		SELECT
+			sv.volume_id,
+			sv.path,
+			v.size_mib,
+			(
+				SELECT snapshot_id
+				FROM db_ds.volume_snapshots AS vs
+				WHERE
+					vs.volume_id = sv.volume_id AND
+					vs.complete_ts IS NOT NULL
+				ORDER BY vs.complete_ts DESC
+				LIMIT 1
+			) AS snapshot_id
+		FROM db_ds.server_volumes AS sv
+		JOIN db_ds.volumes AS v
+		ON sv.volume_id = v.volume_id
+		WHERE sv.server_id = $1
+		",
+		input.server_id,
+	)
+	.await?;
+
+	let s3_client = s3_util::Client::with_bucket_and_endpoint(
+		ctx.config(),
+		volume::SNAPSHOT_BUCKET,
+		s3_util::EndpointKind::EdgeInternal,
+	)
+	.await?;
+
+	let mut volumes = Vec::with_capacity(rows.len());
+	for row in rows {
+		let snapshot = if let Some(snapshot_id) = row.snapshot_id {
+			let presigned_req = s3_client
+				.get_object()
+				.bucket(s3_client.bucket())
+				.key(volume::snapshot_key(row.volume_id, snapshot_id))
+				.presigned(
+					s3_util::aws_sdk_s3::presigning::PresigningConfig::builder()
+						.expires_in(DOWNLOAD_URL_EXPIRY)
+						.build()?,
+				)
+				.await?;
+
+			Some(pp::VolumeSnapshot {
+				snapshot_id,
+				url: presigned_req.uri().to_string(),
+			})
+		} else {
+			None
+		};
+
+		volumes.push(pp::Volume {
+			volume_id: row.volume_id,
+			path: row.path,
+			size: row.size_mib.try_into()?,
+			snapshot,
+		});
+	}
+
+	Ok(volumes)
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct UpdateVolumeClientsInput {
+	server_id: Uuid,
+	client_id: Uuid,
+}
+
+#[activity(UpdateVolumeClients)]
+async fn update_volume_clients(
+	ctx: &ActivityCtx,
+	input: &UpdateVolumeClientsInput,
+) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		UPSERT INTO db_ds.volume_clients (volume_id, client_id, update_ts)
+		SELECT volume_id, $2, $3
+		FROM db_ds.server_volumes
+		WHERE server_id = $1
+		",
+		input.server_id,
+		input.client_id,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct PrepareSnapshotsInput {
+	server_id: Uuid,
+}
+
+/// Returns the volume and new snapshot ids.
+#[activity(PrepareSnapshots)]
+async fn prepare_snapshots(
+	ctx: &ActivityCtx,
+	input: &PrepareSnapshotsInput,
+) -> GlobalResult<Vec<(Uuid, Uuid)>> {
+	let volume_ids = sql_fetch_all!(
+		[ctx, (Uuid,)]
+		"
+		SELECT volume_id
+		FROM db_ds.server_volumes
+		WHERE server_id = $1
+		",
+		input.server_id,
+	)
+	.await?;
+
+	Ok(volume_ids
+		.into_iter()
+		.map(|(volume_id,)| (volume_id, Uuid::new_v4()))
+		.collect())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct MarkVolumesStaleInput {
+	server_id: Uuid,
+	/// All of the server's volumes if not set.
+	volume_ids: Option<Vec<Uuid>>,
+}
+
+#[activity(MarkVolumesStale)]
+async fn mark_volumes_stale(ctx: &ActivityCtx, input: &MarkVolumesStaleInput) -> GlobalResult<()> {
+	sql_execute!(
+		[ctx]
+		"
+		UPDATE db_ds.volumes
+		SET stale_ts = $3
+		WHERE volume_id IN (
+			SELECT volume_id
+			FROM db_ds.server_volumes
+			WHERE
+				server_id = $1 AND
+				($2 IS NULL OR volume_id = ANY($2))
+		)
+		",
+		input.server_id,
+		&input.volume_ids,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	Ok(())
+}
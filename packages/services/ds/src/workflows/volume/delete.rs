@@ -0,0 +1,151 @@
+use chirp_workflow::prelude::*;
+use pegboard::protocol as pp;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Input {
+	pub volume_id: Uuid,
+}
+
+/// Deletes a volume that is not mounted in any server, along with its snapshots and the copies on clients.
+#[workflow]
+pub async fn ds_volume_delete(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()> {
+	let Some(volume) = ctx
+		.activity(UpdateDbInput {
+			volume_id: input.volume_id,
+		})
+		.await?
+	else {
+		tracing::warn!(volume_id=?input.volume_id, "volume already deleted or in use");
+
+		return Ok(());
+	};
+
+	for client_id in volume.client_ids {
+		ctx.signal(pp::Command::DeleteVolume {
+			volume_id: input.volume_id,
+		})
+		.tag("client_id", client_id)
+		.send()
+		.await?;
+	}
+
+	if !volume.snapshot_ids.is_empty() {
+		ctx.activity(DeleteSnapshotsInput {
+			volume_id: input.volume_id,
+			snapshot_ids: volume.snapshot_ids,
+		})
+		.await?;
+	}
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct UpdateDbInput {
+	volume_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct UpdateDbOutput {
+	/// Connected clients with a copy of the volume.
+	client_ids: Vec<Uuid>,
+	snapshot_ids: Vec<Uuid>,
+}
+
+#[activity(UpdateDb)]
+async fn update_db(
+	ctx: &ActivityCtx,
+	input: &UpdateDbInput,
+) -> GlobalResult<Option<UpdateDbOutput>> {
+	let deleted = sql_fetch_optional!(
+		[ctx, (i64,)]
+		"
+		UPDATE db_ds.volumes
+		SET delete_ts = $2
+		WHERE
+			volume_id = $1 AND
+			delete_ts IS NULL AND
+			NOT EXISTS (
+				SELECT 1
+				FROM db_ds.server_volumes AS sv
+				JOIN db_ds.servers AS s
+				ON sv.server_id = s.server_id
+				WHERE
+					sv.volume_id = $1 AND
+					s.destroy_ts IS NULL
+			)
+		RETURNING 1
+		",
+		input.volume_id,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	if deleted.is_none() {
+		return Ok(None);
+	}
+
+	let (client_ids, snapshot_ids) = tokio::try_join!(
+		sql_fetch_all!(
+			[ctx, (Uuid,)]
+			"
+			SELECT vc.client_id
+			FROM db_ds.volume_clients AS vc
+			JOIN db_pegboard.clients AS c
+			ON vc.client_id = c.client_id
+			WHERE
+				vc.volume_id = $1 AND
+				c.delete_ts IS NULL
+			",
+			input.volume_id,
+		),
+		sql_fetch_all!(
+			[ctx, (Uuid,)]
+			"
+			SELECT snapshot_id
+			FROM db_ds.volume_snapshots
+			WHERE volume_id = $1
+			",
+			input.volume_id,
+		),
+	)?;
+
+	Ok(Some(UpdateDbOutput {
+		client_ids: client_ids.into_iter().map(|(id,)| id).collect(),
+		snapshot_ids: snapshot_ids.into_iter().map(|(id,)| id).collect(),
+	}))
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct DeleteSnapshotsInput {
+	volume_id: Uuid,
+	snapshot_ids: Vec<Uuid>,
+}
+
+#[activity(DeleteSnapshots)]
+async fn delete_snapshots(ctx: &ActivityCtx, input: &DeleteSnapshotsInput) -> GlobalResult<()> {
+	let s3_client = s3_util::Client::with_bucket(ctx.config(), super::SNAPSHOT_BUCKET).await?;
+
+	let delete = s3_util::aws_sdk_s3::types::Delete::builder()
+		.set_objects(Some(
+			input
+				.snapshot_ids
+				.iter()
+				.map(|snapshot_id| {
+					s3_util::aws_sdk_s3::types::ObjectIdentifier::builder()
+						.key(super::snapshot_key(input.volume_id, *snapshot_id))
+						.build()
+				})
+				.collect::<Result<Vec<_>, _>>()?,
+		))
+		.build()?;
+
+	s3_client
+		.delete_objects()
+		.bucket(s3_client.bucket())
+		.delete(delete)
+		.send()
+		.await?;
+
+	Ok(())
+}
@@ -0,0 +1,12 @@
+use chirp_workflow::prelude::*;
+
+pub mod delete;
+pub mod snapshot;
+
+/// Bucket volume snapshots are uploaded to.
+pub(crate) const SNAPSHOT_BUCKET: &str = "bucket-actor-volume";
+
+/// Key of a volume snapshot in `SNAPSHOT_BUCKET`. Snapshots are zstd compressed tar archives.
+pub(crate) fn snapshot_key(volume_id: Uuid, snapshot_id: Uuid) -> String {
+	format!("{volume_id}/{snapshot_id}.tar.zst")
+}
@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use chirp_workflow::prelude::*;
+use pegboard::protocol as pp;
+
+/// How long the client has to upload the snapshot before it is considered failed.
+const SNAPSHOT_TIMEOUT_MS: i64 = util::duration::minutes(15);
+/// How long after last ping before a client is considered unreachable and not sent the snapshot command.
+const CLIENT_PING_THRESHOLD_MS: i64 = util::duration::seconds(30);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Input {
+	pub volume_id: Uuid,
+	pub snapshot_id: Uuid,
+	/// Server to send `SnapshotComplete` to.
+	pub server_id: Option<Uuid>,
+}
+
+/// Uploads the copy of the volume on the client that most recently ran it to S3. Must be tagged with
+/// `snapshot_id`.
+#[workflow]
+pub async fn ds_volume_snapshot(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResult<()> {
+	let client_id = ctx
+		.activity(InsertSnapshotInput {
+			volume_id: input.volume_id,
+			snapshot_id: input.snapshot_id,
+		})
+		.await?;
+
+	let size = if let Some(client_id) = client_id {
+		let upload_url = ctx
+			.activity(PresignUploadInput {
+				volume_id: input.volume_id,
+				snapshot_id: input.snapshot_id,
+			})
+			.await?;
+
+		ctx.signal(pp::Command::SnapshotVolume {
+			volume_id: input.volume_id,
+			snapshot_id: input.snapshot_id,
+			upload_url,
+		})
+		.tag("client_id", client_id)
+		.send()
+		.await?;
+
+		match ctx
+			.listen_with_timeout::<pegboard::workflows::client::VolumeSnapshotUpdate>(
+				SNAPSHOT_TIMEOUT_MS,
+			)
+			.await?
+		{
+			Some(sig) => match sig.state {
+				pp::VolumeSnapshotState::Complete { size } => Some(size),
+				pp::VolumeSnapshotState::Failed => None,
+			},
+			None => {
+				tracing::warn!(volume_id=?input.volume_id, ?client_id, "volume snapshot timed out");
+
+				None
+			}
+		}
+	} else {
+		tracing::warn!(volume_id=?input.volume_id, "no client has a copy of the volume");
+
+		None
+	};
+
+	ctx.activity(UpdateSnapshotInput {
+		snapshot_id: input.snapshot_id,
+		size,
+	})
+	.await?;
+
+	if let Some(server_id) = input.server_id {
+		ctx.signal(SnapshotComplete {
+			volume_id: input.volume_id,
+			complete: size.is_some(),
+		})
+		.tag("server_id", server_id)
+		.send()
+		.await?;
+	}
+
+	Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertSnapshotInput {
+	volume_id: Uuid,
+	snapshot_id: Uuid,
+}
+
+/// Returns the client to snapshot the volume from, if it is still connected.
+#[activity(InsertSnapshot)]
+async fn insert_snapshot(
+	ctx: &ActivityCtx,
+	input: &InsertSnapshotInput,
+) -> GlobalResult<Option<Uuid>> {
+	sql_execute!(
+		[ctx]
+		"
+		INSERT INTO db_ds.volume_snapshots (snapshot_id, volume_id, create_ts)
+		VALUES ($1, $2, $3)
+		ON CONFLICT DO NOTHING
+		",
+		input.snapshot_id,
+		input.volume_id,
+		util::timestamp::now(),
+	)
+	.await?;
+
+	let client = sql_fetch_optional!(
+		[ctx, (Uuid, bool)]
+		"
+		SELECT vc.client_id, c.delete_ts IS NULL AND c.last_ping_ts > $2
+		FROM db_ds.volume_clients AS vc
+		JOIN db_ds.volumes AS v
+		ON vc.volume_id = v.volume_id
+		JOIN db_pegboard.clients AS c
+		ON vc.client_id = c.client_id
+		WHERE
+			vc.volume_id = $1 AND
+			v.delete_ts IS NULL
+		-- Only the latest client has the latest data
+		ORDER BY vc.update_ts DESC
+		LIMIT 1
+		",
+		input.volume_id,
+		util::timestamp::now() - CLIENT_PING_THRESHOLD_MS,
+	)
+	.await?;
+
+	Ok(client.and_then(|(client_id, connected)| connected.then_some(client_id)))
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct PresignUploadInput {
+	volume_id: Uuid,
+	snapshot_id: Uuid,
+}
+
+#[activity(PresignUpload)]
+async fn presign_upload(ctx: &ActivityCtx, input: &PresignUploadInput) -> GlobalResult<String> {
+	let s3_client = s3_util::Client::with_bucket_and_endpoint(
+		ctx.config(),
+		super::SNAPSHOT_BUCKET,
+		s3_util::EndpointKind::EdgeInternal,
+	)
+	.await?;
+
+	let presigned_req = s3_client
+		.put_object()
+		.bucket(s3_client.bucket())
+		.key(super::snapshot_key(input.volume_id, input.snapshot_id))
+		.presigned(
+			s3_util::aws_sdk_s3::presigning::PresigningConfig::builder()
+				.expires_in(Duration::from_millis(SNAPSHOT_TIMEOUT_MS as u64))
+				.build()?,
+		)
+		.await?;
+
+	Ok(presigned_req.uri().to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct UpdateSnapshotInput {
+	snapshot_id: Uuid,
+	/// Unset if the snapshot failed.
+	size: Option<u64>,
+}
+
+#[activity(UpdateSnapshot)]
+async fn update_snapshot(ctx: &ActivityCtx, input: &UpdateSnapshotInput) -> GlobalResult<()> {
+	if let Some(size) = input.size {
+		sql_execute!(
+			[ctx]
+			"
+			UPDATE db_ds.volume_snapshots
+			SET
+				complete_ts = $2,
+				size = $3
+			WHERE snapshot_id = $1
+			",
+			input.snapshot_id,
+			util::timestamp::now(),
+			i64::try_from(size)?,
+		)
+		.await?;
+	} else {
+		sql_execute!(
+			[ctx]
+			"
+			UPDATE db_ds.volume_snapshots
+			SET failed_ts = $2
+			WHERE snapshot_id = $1
+			",
+			input.snapshot_id,
+			util::timestamp::now(),
+		)
+		.await?;
+	}
+
+	Ok(())
+}
+
+#[signal("ds_volume_snapshot_complete")]
+pub struct SnapshotComplete {
+	pub volume_id: Uuid,
+	/// Whether the snapshot was uploaded. The previous snapshot is still used if not.
+	pub complete: bool,
+}
@@ -77,6 +77,7 @@ impl Setup {
 					routing: types::Routing::GameGuard {
 						protocol: types::GameGuardProtocol::Http,
 						authorization: types::PortAuthorization::None,
+						middleware: None,
 					},
 				},
 			),
@@ -87,6 +88,7 @@ impl Setup {
 					routing: types::Routing::GameGuard {
 						protocol: types::GameGuardProtocol::Tcp,
 						authorization: types::PortAuthorization::None,
+						middleware: None,
 					},
 				},
 			),
@@ -97,6 +99,7 @@ impl Setup {
 					routing: types::Routing::GameGuard {
 						protocol: types::GameGuardProtocol::Udp,
 						authorization: types::PortAuthorization::None,
+						middleware: None,
 					},
 				},
 			),
@@ -123,6 +126,7 @@ impl Setup {
 			resources: ds::types::ServerResources {
 				cpu_millicores: 100,
 				memory_mib: 200,
+				limits: Default::default(),
 			},
 			lifecycle: ds::types::ServerLifecycle {
 				kill_timeout_ms: 0,
@@ -132,9 +136,11 @@ impl Setup {
 			root_user_enabled: false,
 			args: Vec::new(),
 			environment: env,
+			secrets: HashMap::new(),
 			image_id: self.image_id,
 			network_mode,
 			network_ports: ports,
+			volumes: Vec::new(),
 		})
 		.tag("server_id", server_id)
 		.dispatch()
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chirp_workflow::prelude::*;
 use ds::types;
 use tivet_operation::prelude::proto::{
@@ -65,6 +67,7 @@ async fn print_test_data(ctx: TestCtx) {
 				kind: Some(proto::claims::entitlement::Kind::EnvService(
 					proto::claims::entitlement::EnvService {
 						env_id: Some(env_id),
+						..Default::default()
 					}
 				)),
 			}]},
@@ -86,6 +89,7 @@ async fn print_test_data(ctx: TestCtx) {
 				kind: Some(proto::claims::entitlement::Kind::EnvService(
 					proto::claims::entitlement::EnvService {
 						env_id: Some(env_id),
+						..Default::default()
 					}
 				)),
 			},proto::claims::Entitlement {
@@ -150,6 +154,7 @@ async fn print_test_data(ctx: TestCtx) {
 			routing: types::Routing::GameGuard {
 				protocol: types::GameGuardProtocol::Http,
 				authorization: types::PortAuthorization::None,
+				middleware: None,
 			},
 		},
 	)]
@@ -175,6 +180,7 @@ async fn print_test_data(ctx: TestCtx) {
 		resources: ds::types::ServerResources {
 			cpu_millicores: 100,
 			memory_mib: 200,
+			limits: Default::default(),
 		},
 		lifecycle: ds::types::ServerLifecycle {
 			kill_timeout_ms: 0,
@@ -186,9 +192,11 @@ async fn print_test_data(ctx: TestCtx) {
 		root_user_enabled: false,
 		args: Vec::new(),
 		environment: env,
+		secrets: HashMap::new(),
 		image_id: build_res.build_id.unwrap().as_uuid(),
 		network_mode: types::NetworkMode::Bridge,
 		network_ports: ports,
+		volumes: Vec::new(),
 	})
 	.tag("server_id", server_id)
 	.dispatch()
@@ -0,0 +1,134 @@
+use ds::{
+	types::RollingUpdatePolicy,
+	workflows::replica_set::{
+		create_backoff_ms, plan_rollout, Plan, ReplicaServer, MAX_CREATE_BACKOFF_MS,
+	},
+};
+use uuid::Uuid;
+
+fn server(image_id: Uuid, ready: bool) -> ReplicaServer {
+	ReplicaServer {
+		server_id: Uuid::new_v4(),
+		image_id,
+		ready,
+	}
+}
+
+fn policy(max_surge: u32, max_unavailable: u32) -> RollingUpdatePolicy {
+	RollingUpdatePolicy {
+		max_surge,
+		max_unavailable,
+		min_ready_ms: 0,
+	}
+}
+
+#[test]
+fn replica_set_scale() {
+	let image_id = Uuid::new_v4();
+
+	// Scale up from nothing
+	assert_eq!(
+		Plan {
+			create: 3,
+			destroy: Vec::new(),
+		},
+		plan_rollout(&[], image_id, 3, &policy(1, 0))
+	);
+
+	// Scale down, destroying servers that are not ready first
+	let servers = vec![
+		server(image_id, true),
+		server(image_id, false),
+		server(image_id, true),
+	];
+	assert_eq!(
+		Plan {
+			create: 0,
+			destroy: vec![servers[1].server_id],
+		},
+		plan_rollout(&servers, image_id, 2, &policy(1, 0))
+	);
+
+	// Steady state
+	assert_eq!(
+		Plan::default(),
+		plan_rollout(&servers[..2], image_id, 2, &policy(1, 0))
+	);
+}
+
+#[test]
+fn replica_set_rollout_surge() {
+	let old_image_id = Uuid::new_v4();
+	let new_image_id = Uuid::new_v4();
+	let old = vec![server(old_image_id, true), server(old_image_id, true)];
+
+	// Surges one new server without destroying any ready old servers
+	assert_eq!(
+		Plan {
+			create: 1,
+			destroy: Vec::new(),
+		},
+		plan_rollout(&old, new_image_id, 2, &policy(1, 0))
+	);
+
+	// Waits for the new server to be ready
+	let mut servers = old.clone();
+	servers.push(server(new_image_id, false));
+	assert_eq!(
+		Plan::default(),
+		plan_rollout(&servers, new_image_id, 2, &policy(1, 0))
+	);
+
+	// Replaces an old server once the new server is ready
+	servers[2].ready = true;
+	assert_eq!(
+		Plan {
+			create: 1,
+			destroy: vec![old[0].server_id],
+		},
+		plan_rollout(&servers, new_image_id, 2, &policy(1, 0))
+	);
+}
+
+#[test]
+fn replica_set_rollout_unavailable() {
+	let old_image_id = Uuid::new_v4();
+	let new_image_id = Uuid::new_v4();
+	let mut servers = vec![
+		server(old_image_id, true),
+		server(old_image_id, true),
+		server(old_image_id, true),
+	];
+
+	// Replaces up to `max_unavailable` ready old servers at once
+	assert_eq!(
+		Plan {
+			create: 1,
+			destroy: vec![servers[0].server_id],
+		},
+		plan_rollout(&servers, new_image_id, 3, &policy(0, 1))
+	);
+
+	// Old servers that are not ready are always replaced since they don't count towards availability
+	servers[2].ready = false;
+	assert_eq!(
+		Plan {
+			create: 1,
+			destroy: vec![servers[2].server_id],
+		},
+		plan_rollout(&servers, new_image_id, 3, &policy(0, 1))
+	);
+}
+
+#[test]
+fn replica_set_create_backoff() {
+	assert_eq!(0, create_backoff_ms(0));
+
+	// Doubles with each failed attempt
+	assert_eq!(2 * create_backoff_ms(1), create_backoff_ms(2));
+	assert_eq!(2 * create_backoff_ms(2), create_backoff_ms(3));
+
+	// Capped
+	assert_eq!(MAX_CREATE_BACKOFF_MS, create_backoff_ms(10));
+	assert_eq!(MAX_CREATE_BACKOFF_MS, create_backoff_ms(u32::MAX));
+}
@@ -0,0 +1,130 @@
+use ds::{types::ServerResourceLimits, workflows::server::validate_resource_limits};
+use tier::types::Tier;
+
+fn tier() -> Tier {
+	Tier {
+		tier_name_id: "basic-1d1".to_string(),
+		tivet_cores_numerator: 1,
+		tivet_cores_denominator: 1,
+		cpu: 1999,
+		cpu_millicores: 1000,
+		memory: 2048,
+		memory_max: 3072,
+		disk: 8192,
+		bandwidth: 1024,
+		disk_bandwidth: 64,
+		io_weight: 100,
+		pids: 1024,
+		nofile: 4096,
+	}
+}
+
+#[test]
+fn resource_limits_unset() {
+	assert_eq!(
+		None,
+		validate_resource_limits(&ServerResourceLimits::default(), &tier())
+	);
+}
+
+#[test]
+fn resource_limits_max() {
+	let limits = ServerResourceLimits {
+		pids: Some(1024),
+		io_weight: Some(1000),
+		disk_read_bandwidth: Some(64),
+		disk_write_bandwidth: Some(64),
+		nofile: Some(4096),
+		egress_bandwidth: Some(1024),
+	};
+	assert_eq!(None, validate_resource_limits(&limits, &tier()));
+}
+
+#[test]
+fn resource_limits_io_weight() {
+	for io_weight in [10, 500, 1000] {
+		let limits = ServerResourceLimits {
+			io_weight: Some(io_weight),
+			..Default::default()
+		};
+		assert_eq!(None, validate_resource_limits(&limits, &tier()));
+	}
+
+	for io_weight in [0, 9, 1001] {
+		let limits = ServerResourceLimits {
+			io_weight: Some(io_weight),
+			..Default::default()
+		};
+		assert_eq!(
+			Some("`resources.io_weight` must be between 10 and 1000.".to_string()),
+			validate_resource_limits(&limits, &tier())
+		);
+	}
+}
+
+#[test]
+fn resource_limits_zero() {
+	let limits = ServerResourceLimits {
+		nofile: Some(0),
+		..Default::default()
+	};
+	assert_eq!(
+		Some("`resources.nofile` must be greater than 0.".to_string()),
+		validate_resource_limits(&limits, &tier())
+	);
+}
+
+#[test]
+fn resource_limits_exceed_tier() {
+	let cases = [
+		(
+			"pids",
+			ServerResourceLimits {
+				pids: Some(1025),
+				..Default::default()
+			},
+			1024,
+		),
+		(
+			"disk_read_bandwidth",
+			ServerResourceLimits {
+				disk_read_bandwidth: Some(65),
+				..Default::default()
+			},
+			64,
+		),
+		(
+			"disk_write_bandwidth",
+			ServerResourceLimits {
+				disk_write_bandwidth: Some(65),
+				..Default::default()
+			},
+			64,
+		),
+		(
+			"nofile",
+			ServerResourceLimits {
+				nofile: Some(4097),
+				..Default::default()
+			},
+			4096,
+		),
+		(
+			"egress_bandwidth",
+			ServerResourceLimits {
+				egress_bandwidth: Some(1025),
+				..Default::default()
+			},
+			1024,
+		),
+	];
+
+	for (name, limits, max) in cases {
+		assert_eq!(
+			Some(format!(
+				"`resources.{name}` exceeds the maximum of {max} for the requested resources."
+			)),
+			validate_resource_limits(&limits, &tier())
+		);
+	}
+}
@@ -0,0 +1,139 @@
+use ds::{
+	types::ConcurrencyPolicy,
+	workflows::schedule::{
+		compute_ticks, parse_cron, plan_run, RunPlan, Ticks, MAX_MISSED_RUNS, STARTING_DEADLINE_MS,
+	},
+};
+use uuid::Uuid;
+
+const MINUTE: i64 = 60_000;
+const HOUR: i64 = 60 * MINUTE;
+/// 2023-11-14T22:00:00Z, aligned to the hour.
+const START_TS: i64 = 1_699_999_200_000;
+
+#[test]
+fn schedule_ticks_due() {
+	let cron = parse_cron("* * * * *").unwrap();
+
+	// Nothing passed yet
+	assert_eq!(
+		Ticks {
+			missed: Vec::new(),
+			due: None,
+			next_ts: START_TS + MINUTE,
+		},
+		compute_ticks(&cron, START_TS, START_TS + MINUTE / 2).unwrap()
+	);
+
+	// The tick itself is not included
+	assert_eq!(
+		Ticks {
+			missed: Vec::new(),
+			due: None,
+			next_ts: START_TS + MINUTE,
+		},
+		compute_ticks(&cron, START_TS, START_TS).unwrap()
+	);
+
+	// Due within the starting deadline
+	assert_eq!(
+		Ticks {
+			missed: Vec::new(),
+			due: Some(START_TS + MINUTE),
+			next_ts: START_TS + 2 * MINUTE,
+		},
+		compute_ticks(&cron, START_TS, START_TS + MINUTE + MINUTE / 2).unwrap()
+	);
+
+	// Only the latest tick is due, earlier ticks are missed
+	assert_eq!(
+		Ticks {
+			missed: (1..5).map(|i| START_TS + i * MINUTE).collect(),
+			due: Some(START_TS + 5 * MINUTE),
+			next_ts: START_TS + 6 * MINUTE,
+		},
+		compute_ticks(&cron, START_TS, START_TS + 5 * MINUTE + MINUTE / 2).unwrap()
+	);
+}
+
+#[test]
+fn schedule_ticks_starting_deadline() {
+	let cron = parse_cron("0 * * * *").unwrap();
+
+	// Still due at the deadline
+	assert_eq!(
+		Some(START_TS + HOUR),
+		compute_ticks(&cron, START_TS, START_TS + HOUR + STARTING_DEADLINE_MS)
+			.unwrap()
+			.due
+	);
+
+	// Missed after the deadline
+	assert_eq!(
+		Ticks {
+			missed: vec![START_TS + HOUR, START_TS + 2 * HOUR],
+			due: None,
+			next_ts: START_TS + 3 * HOUR,
+		},
+		compute_ticks(
+			&cron,
+			START_TS,
+			START_TS + 2 * HOUR + STARTING_DEADLINE_MS + 1
+		)
+		.unwrap()
+	);
+}
+
+#[test]
+fn schedule_ticks_max_missed_runs() {
+	let cron = parse_cron("* * * * *").unwrap();
+
+	// Only the latest missed ticks are kept
+	let ticks = compute_ticks(&cron, START_TS, START_TS + 100 * MINUTE + MINUTE / 2).unwrap();
+	assert_eq!(Some(START_TS + 100 * MINUTE), ticks.due);
+	assert_eq!(MAX_MISSED_RUNS, ticks.missed.len());
+	assert_eq!(
+		(100 - MAX_MISSED_RUNS as i64..100)
+			.map(|i| START_TS + i * MINUTE)
+			.collect::<Vec<_>>(),
+		ticks.missed
+	);
+
+	// Same without a due tick
+	let cron = parse_cron("0 * * * *").unwrap();
+	let ticks = compute_ticks(&cron, START_TS, START_TS + 100 * HOUR + HOUR / 2).unwrap();
+	assert_eq!(None, ticks.due);
+	assert_eq!(
+		(101 - MAX_MISSED_RUNS as i64..=100)
+			.map(|i| START_TS + i * HOUR)
+			.collect::<Vec<_>>(),
+		ticks.missed
+	);
+}
+
+#[test]
+fn schedule_concurrency_policy() {
+	let running = vec![Uuid::new_v4(), Uuid::new_v4()];
+
+	// Nothing running
+	for policy in [
+		ConcurrencyPolicy::Allow,
+		ConcurrencyPolicy::Forbid,
+		ConcurrencyPolicy::Replace,
+	] {
+		assert_eq!(RunPlan::Create, plan_run(policy, Vec::new()));
+	}
+
+	assert_eq!(
+		RunPlan::Create,
+		plan_run(ConcurrencyPolicy::Allow, running.clone())
+	);
+	assert_eq!(
+		RunPlan::Skip,
+		plan_run(ConcurrencyPolicy::Forbid, running.clone())
+	);
+	assert_eq!(
+		RunPlan::Replace(running.clone()),
+		plan_run(ConcurrencyPolicy::Replace, running)
+	);
+}
@@ -0,0 +1,50 @@
+use ds::util::secrets;
+use tivet_config::{
+	config::{ActorSecretKey, ActorSecrets},
+	secret::Secret,
+};
+
+fn config(kid: &str, key: &str) -> ActorSecrets {
+	ActorSecrets {
+		kid: kid.into(),
+		key: Secret::new(key.repeat(64)),
+		keyring: Vec::new(),
+	}
+}
+
+#[test]
+fn secrets_roundtrip() {
+	let config = config("a", "1");
+
+	let (kid, value) = secrets::encrypt(&config, "hunter2").unwrap();
+	assert_eq!("a", kid);
+	assert!(!value.windows(7).any(|x| x == b"hunter2"));
+	assert_eq!("hunter2", secrets::decrypt(&config, &kid, &value).unwrap());
+
+	// Nonces are random
+	let (_, value2) = secrets::encrypt(&config, "hunter2").unwrap();
+	assert_ne!(value, value2);
+
+	// Tampered values fail to decrypt
+	let mut tampered = value.clone();
+	*tampered.last_mut().unwrap() ^= 1;
+	assert!(secrets::decrypt(&config, &kid, &tampered).is_err());
+}
+
+#[test]
+fn secrets_key_rotation() {
+	let old = config("a", "1");
+	let (kid, value) = secrets::encrypt(&old, "hunter2").unwrap();
+
+	// Values encrypted with the old key are still readable once it is moved to the keyring
+	let mut new = config("b", "2");
+	new.keyring.push(ActorSecretKey {
+		kid: old.kid.clone(),
+		key: old.key.clone(),
+	});
+	assert_eq!("hunter2", secrets::decrypt(&new, &kid, &value).unwrap());
+	assert_eq!("b", secrets::encrypt(&new, "hunter2").unwrap().0);
+
+	// Unknown keys
+	assert!(secrets::decrypt(&config("b", "2"), &kid, &value).is_err());
+}
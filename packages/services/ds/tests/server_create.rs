@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chirp_workflow::prelude::*;
 use ds::types;
 use tivet_operation::prelude::proto::backend;
@@ -56,6 +58,7 @@ async fn server_create(ctx: TestCtx) {
 			routing: types::Routing::GameGuard {
 				protocol: types::GameGuardProtocol::Http,
 				authorization: types::PortAuthorization::None,
+				middleware: None,
 			},
 		},
 	)]
@@ -81,6 +84,7 @@ async fn server_create(ctx: TestCtx) {
 		resources: ds::types::ServerResources {
 			cpu_millicores: 100,
 			memory_mib: 200,
+			limits: Default::default(),
 		},
 		lifecycle: ds::types::ServerLifecycle {
 			kill_timeout_ms: 0,
@@ -92,9 +96,11 @@ async fn server_create(ctx: TestCtx) {
 		root_user_enabled: false,
 		args: Vec::new(),
 		environment: env,
+		secrets: HashMap::new(),
 		image_id: build_res.build_id.unwrap().as_uuid(),
 		network_mode: types::NetworkMode::Host,
 		network_ports: ports,
+		volumes: Vec::new(),
 	})
 	.tag("server_id", server_id)
 	.dispatch()
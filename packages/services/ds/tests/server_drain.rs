@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chirp_workflow::prelude::*;
 use ds::types;
 use tivet_operation::prelude::proto::backend;
@@ -49,6 +51,7 @@ async fn server_drain(ctx: TestCtx) {
 			routing: types::Routing::GameGuard {
 				protocol: types::GameGuardProtocol::Http,
 				authorization: types::PortAuthorization::None,
+				middleware: None,
 			},
 		},
 	)]
@@ -74,6 +77,7 @@ async fn server_drain(ctx: TestCtx) {
 		resources: ds::types::ServerResources {
 			cpu_millicores: 50,
 			memory_mib: 50,
+			limits: Default::default(),
 		},
 		lifecycle: ds::types::ServerLifecycle {
 			kill_timeout_ms: 10000,
@@ -85,9 +89,11 @@ async fn server_drain(ctx: TestCtx) {
 		root_user_enabled: false,
 		args: Vec::new(),
 		environment: env,
+		secrets: HashMap::new(),
 		image_id: build_res.build_id.unwrap().as_uuid(),
 		network_mode: types::NetworkMode::Bridge,
 		network_ports: ports,
+		volumes: Vec::new(),
 	})
 	.tag("server_id", server_id)
 	.dispatch()
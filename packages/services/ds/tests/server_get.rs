@@ -51,6 +51,7 @@ async fn server_get(ctx: TestCtx) {
 			routing: types::Routing::GameGuard {
 				protocol: types::GameGuardProtocol::Http,
 				authorization: types::PortAuthorization::None,
+				middleware: None,
 			},
 		},
 	)]
@@ -75,6 +76,7 @@ async fn server_get(ctx: TestCtx) {
 		resources: ds::types::ServerResources {
 			cpu_millicores: 100,
 			memory_mib: 200,
+			limits: Default::default(),
 		},
 		lifecycle: ds::types::ServerLifecycle {
 			kill_timeout_ms: 0,
@@ -84,9 +86,11 @@ async fn server_get(ctx: TestCtx) {
 		root_user_enabled: false,
 		args: Vec::new(),
 		environment: env,
+		secrets: HashMap::new(),
 		image_id: build_res.build_id.unwrap().as_uuid(),
 		network_mode: types::NetworkMode::Bridge,
 		network_ports: ports,
+		volumes: Vec::new(),
 	})
 	.tag("server_id", server_id)
 	.dispatch()
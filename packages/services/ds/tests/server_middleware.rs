@@ -0,0 +1,124 @@
+use ds::{
+	types::{GameGuardProtocol, PortMiddleware, PortRateLimit},
+	workflows::server::{is_valid_ip_range, validate_middleware},
+};
+
+fn ip_lists(allow: &[&str], deny: &[&str]) -> PortMiddleware {
+	PortMiddleware {
+		ip_allow_list: allow.iter().map(|x| x.to_string()).collect(),
+		ip_deny_list: deny.iter().map(|x| x.to_string()).collect(),
+		..Default::default()
+	}
+}
+
+#[test]
+fn ip_range_validation() {
+	assert!(is_valid_ip_range("10.0.0.1"));
+	assert!(is_valid_ip_range("10.0.0.0/8"));
+	assert!(is_valid_ip_range("0.0.0.0/0"));
+	assert!(is_valid_ip_range("::1"));
+	assert!(is_valid_ip_range("2001:db8::/32"));
+
+	assert!(!is_valid_ip_range(""));
+	assert!(!is_valid_ip_range("10.0.0.0/33"));
+	assert!(!is_valid_ip_range("::/129"));
+	assert!(!is_valid_ip_range("10.0.0.0/"));
+	assert!(!is_valid_ip_range("10.0.0/8"));
+	assert!(!is_valid_ip_range("example.com"));
+}
+
+#[test]
+fn ip_allow_ranges() {
+	// No lists
+	assert_eq!(None, ip_lists(&[], &[]).ip_allow_ranges());
+
+	// Allow list only, host bits are dropped
+	assert_eq!(
+		Some(vec!["10.0.0.0/8".to_string(), "1.2.3.4/32".to_string()]),
+		ip_lists(&["10.1.2.3/8", "1.2.3.4"], &[]).ip_allow_ranges()
+	);
+
+	// Deny list only is removed from all addresses
+	let ranges = ip_lists(&[], &["0.0.0.0/1", "::/1"])
+		.ip_allow_ranges()
+		.unwrap();
+	assert_eq!(vec!["128.0.0.0/1", "8000::/1"], ranges);
+
+	// Deny a range within the allow list
+	let ranges = ip_lists(&["10.0.0.0/8"], &["10.0.0.0/10"])
+		.ip_allow_ranges()
+		.unwrap();
+	assert_eq!(vec!["10.64.0.0/10", "10.128.0.0/9"], ranges);
+
+	// Deny a single address
+	let ranges = ip_lists(&["10.0.0.0/30"], &["10.0.0.2"])
+		.ip_allow_ranges()
+		.unwrap();
+	assert_eq!(vec!["10.0.0.0/31", "10.0.0.3/32"], ranges);
+
+	// Deny outside of the allow list has no effect
+	let ranges = ip_lists(&["10.0.0.0/8"], &["192.168.0.0/16"])
+		.ip_allow_ranges()
+		.unwrap();
+	assert_eq!(vec!["10.0.0.0/8"], ranges);
+
+	// Deny everything
+	let ranges = ip_lists(&["10.0.0.0/8"], &["10.0.0.0/7"])
+		.ip_allow_ranges()
+		.unwrap();
+	assert!(ranges.is_empty());
+}
+
+#[test]
+fn middleware_validation() {
+	assert_eq!(
+		None,
+		validate_middleware(GameGuardProtocol::Https, &Default::default())
+	);
+
+	// Only HTTP ports
+	assert!(validate_middleware(GameGuardProtocol::Tcp, &Default::default()).is_some());
+	assert!(validate_middleware(GameGuardProtocol::Udp, &Default::default()).is_some());
+
+	// Rate limit
+	let middleware = PortMiddleware {
+		rate_limit: Some(PortRateLimit {
+			average: 0,
+			period_s: 1,
+			burst: 0,
+		}),
+		..Default::default()
+	};
+	assert!(validate_middleware(GameGuardProtocol::Http, &middleware).is_some());
+
+	// Headers
+	let middleware = PortMiddleware {
+		request_headers: [("bad header".to_string(), "value".to_string())]
+			.into_iter()
+			.collect(),
+		..Default::default()
+	};
+	assert!(validate_middleware(GameGuardProtocol::Http, &middleware).is_some());
+
+	// IP lists
+	assert_eq!(
+		None,
+		validate_middleware(
+			GameGuardProtocol::Http,
+			&ip_lists(&["10.0.0.0/8"], &["10.0.0.1"])
+		)
+	);
+	assert!(
+		validate_middleware(GameGuardProtocol::Http, &ip_lists(&["10.0.0.0/33"], &[])).is_some()
+	);
+	assert!(validate_middleware(GameGuardProtocol::Http, &ip_lists(&[], &["nope"])).is_some());
+	assert!(validate_middleware(
+		GameGuardProtocol::Http,
+		&ip_lists(&["10.0.0.0/8"], &["0.0.0.0/0"])
+	)
+	.is_some());
+
+	let too_many = (0..65).map(|i| format!("10.0.0.{i}")).collect::<Vec<_>>();
+	let too_many = too_many.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+	assert!(validate_middleware(GameGuardProtocol::Http, &ip_lists(&too_many, &[])).is_some());
+}
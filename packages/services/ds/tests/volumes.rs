@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use chirp_workflow::prelude::*;
+use ds::types;
+use tivet_operation::prelude::proto::backend;
+use serde_json::json;
+
+struct Setup {
+	env_id: Uuid,
+	cluster_id: Uuid,
+	datacenter_id: Uuid,
+	image_id: Uuid,
+}
+
+impl Setup {
+	async fn init(ctx: &TestCtx) -> Self {
+		let game_res = op!([ctx] faker_game {
+			..Default::default()
+		})
+		.await
+		.unwrap();
+		let env_id = game_res.prod_env_id.unwrap();
+
+		// Pick an existing cluster
+		let cluster_id = ctx
+			.op(cluster::ops::list::Input {})
+			.await
+			.unwrap()
+			.cluster_ids
+			.first()
+			.unwrap()
+			.to_owned();
+
+		let build_res = op!([ctx] faker_build {
+			env_id: Some(env_id),
+			image: backend::faker::Image::DsEcho as i32,
+		})
+		.await
+		.unwrap();
+
+		let faker_region = op!([ctx] faker_region {}).await.unwrap();
+
+		Setup {
+			env_id: *env_id,
+			cluster_id,
+			datacenter_id: faker_region.region_id.unwrap().as_uuid(),
+			image_id: build_res.build_id.unwrap().as_uuid(),
+		}
+	}
+
+	/// Dispatches a durable server mounting the given volume at `/data`.
+	async fn dispatch_server(&self, ctx: &TestCtx, server_id: Uuid, name: &str, size_mib: u32) {
+		ctx.workflow(ds::workflows::server::Input {
+			server_id,
+			env_id: self.env_id,
+			datacenter_id: self.datacenter_id,
+			cluster_id: self.cluster_id,
+			runtime: ds::types::ServerRuntime::Pegboard,
+			resources: ds::types::ServerResources {
+				cpu_millicores: 50,
+				memory_mib: 50,
+				limits: Default::default(),
+			},
+			lifecycle: ds::types::ServerLifecycle {
+				kill_timeout_ms: 0,
+				durable: true,
+			},
+			tags: HashMap::new(),
+			root_user_enabled: false,
+			args: Vec::new(),
+			environment: HashMap::new(),
+			secrets: HashMap::new(),
+			image_id: self.image_id,
+			network_mode: types::NetworkMode::Bridge,
+			network_ports: HashMap::new(),
+			volumes: vec![types::ServerVolume {
+				name: name.to_string(),
+				path: "/data".to_string(),
+				size_mib,
+			}],
+		})
+		.tag("server_id", server_id)
+		.dispatch()
+		.await
+		.unwrap();
+	}
+
+	/// Creates a server and returns the error message if it failed.
+	async fn create_server(&self, ctx: &TestCtx, name: &str, size_mib: u32) -> Option<String> {
+		let server_id = Uuid::new_v4();
+
+		let mut complete_sub = ctx
+			.subscribe::<ds::workflows::server::CreateComplete>(&json!({
+				"server_id": server_id,
+			}))
+			.await
+			.unwrap();
+		let mut failed_sub = ctx
+			.subscribe::<ds::workflows::server::Failed>(&json!({
+				"server_id": server_id,
+			}))
+			.await
+			.unwrap();
+
+		self.dispatch_server(ctx, server_id, name, size_mib).await;
+
+		tokio::select! {
+			res = complete_sub.next() => {
+				res.unwrap();
+				None
+			}
+			res = failed_sub.next() => Some(res.unwrap().message.clone()),
+		}
+	}
+}
+
+#[workflow_test]
+async fn volume_size_mismatch(ctx: TestCtx) {
+	let setup = Setup::init(&ctx).await;
+	let name = Uuid::new_v4().to_string();
+
+	assert_eq!(None, setup.create_server(&ctx, &name, 64).await);
+
+	let message = setup.create_server(&ctx, &name, 128).await.unwrap();
+	assert!(
+		message.contains("already exists with a size of 64 MiB"),
+		"{message}"
+	);
+}
+
+#[workflow_test]
+async fn volume_concurrent_mount(ctx: TestCtx) {
+	let setup = Setup::init(&ctx).await;
+	let name = Uuid::new_v4().to_string();
+
+	// Both may pass validation before either inserts the volume, only one can mount it
+	let (a, b) = tokio::join!(
+		setup.create_server(&ctx, &name, 64),
+		setup.create_server(&ctx, &name, 64),
+	);
+
+	let messages = [a, b].into_iter().flatten().collect::<Vec<_>>();
+	assert_eq!(1, messages.len(), "{messages:?}");
+	assert!(
+		messages[0].contains("is mounted by another actor"),
+		"{}",
+		messages[0]
+	);
+}
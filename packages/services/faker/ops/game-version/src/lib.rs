@@ -93,6 +93,7 @@ async fn handle(
 						listable: true,
 						taggable: true,
 						allow_dynamic_max_players: false,
+						skill_matchmaking: None,
 
 						runtime: Some(
 							backend::matchmaker::lobby_runtime::Docker {
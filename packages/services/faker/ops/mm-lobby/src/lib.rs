@@ -83,6 +83,7 @@ async fn handle(
 					listable: true,
 					taggable: true,
 					allow_dynamic_max_players: true,
+					skill_matchmaking: None,
 
 					runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 						build_id: build_res.build_id,
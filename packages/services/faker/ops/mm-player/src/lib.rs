@@ -54,6 +54,7 @@ async fn handle(
 			player_id: Some(player_id.into()),
 			token_session_id: Some(token_session_id.into()),
 			client_info: Some(client.clone()),
+			rating: None,
 		}],
 		query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
 			lobby_id: ctx.lobby_id,
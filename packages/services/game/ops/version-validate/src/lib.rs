@@ -22,6 +22,7 @@ const MAX_MAX_PLAYERS: u32 = 256;
 const MAX_MIN_IDLE_LOBBY_COUNT: u32 = 16;
 const MAX_MAX_IDLE_LOBBY_COUNT: u32 = 32;
 const MAX_CUSTOM_DISPLAY_NAME_LEN: usize = 11;
+const MAX_SKILL_RATING_RANGE: f64 = 1_000_000.0;
 
 #[derive(Default)]
 struct MiddlewareCounter {
@@ -527,6 +528,62 @@ async fn handle(
 				]);
 			}
 
+			// Validate skill matchmaking
+			if let Some(skill) = &lobby_group.skill_matchmaking {
+				for (label, value) in [
+					("initial-window", skill.initial_window),
+					("window-growth-per-second", skill.window_growth_per_second),
+					("max-window", skill.max_window),
+					("max-spread", skill.max_spread),
+				] {
+					if !value.is_finite() || value < 0.0 {
+						errors.push(util::err_path![
+							"config",
+							"matchmaker",
+							"game-modes",
+							lobby_group_label,
+							"skill-matchmaking",
+							label,
+							"invalid",
+						]);
+					} else if value > MAX_SKILL_RATING_RANGE {
+						errors.push(util::err_path![
+							"config",
+							"matchmaker",
+							"game-modes",
+							lobby_group_label,
+							"skill-matchmaking",
+							label,
+							"too-high",
+						]);
+					}
+				}
+
+				if skill.initial_window > skill.max_window {
+					errors.push(util::err_path![
+						"config",
+						"matchmaker",
+						"game-modes",
+						lobby_group_label,
+						"skill-matchmaking",
+						"initial-window",
+						"greater-than-max-window",
+					]);
+				}
+
+				if skill.max_spread == 0.0 {
+					errors.push(util::err_path![
+						"config",
+						"matchmaker",
+						"game-modes",
+						lobby_group_label,
+						"skill-matchmaking",
+						"max-spread",
+						"too-low",
+					]);
+				}
+			}
+
 			// Validate region ids
 			for (region_index, region) in lobby_group.regions.iter().take(64).enumerate() {
 				let region_config = regions_res
@@ -55,6 +55,7 @@ async fn empty(ctx: TestCtx) {
 						listable: true,
 						taggable: false,
 						allow_dynamic_max_players: false,
+						skill_matchmaking: None,
 
 						runtime: Some(matchmaker::LobbyRuntime {
 							runtime: Some(matchmaker::lobby_runtime::Runtime::Docker(
@@ -147,6 +148,12 @@ async fn empty(ctx: TestCtx) {
 						listable: true,
 						taggable: false,
 						allow_dynamic_max_players: false,
+						skill_matchmaking: Some(matchmaker::SkillMatchmaking {
+							initial_window: 200.0,
+							window_growth_per_second: -1.0,
+							max_window: 100.0,
+							max_spread: 0.0,
+						}),
 
 						runtime: Some(matchmaker::LobbyRuntime {
 							runtime: Some(matchmaker::lobby_runtime::Runtime::Docker(
@@ -179,5 +186,5 @@ async fn empty(ctx: TestCtx) {
 	.await
 	.unwrap();
 
-	assert_eq!(res.errors.len(), 21, "validation failed");
+	assert_eq!(res.errors.len(), 24, "validation failed");
 }
@@ -1,6 +1,11 @@
+use std::time::Instant;
+
 use proto::backend::{self, pkg::*};
 use tivet_operation::prelude::*;
 
+mod metrics;
+mod mmdb;
+
 /// Parsed response from ipinfo.io. We can't retrieve data from bogon or anycast
 /// addresses.
 #[derive(serde::Deserialize)]
@@ -27,8 +32,34 @@ async fn handle(ctx: OperationContext<ip::info::Request>) -> GlobalResult<ip::in
 	let ip_str = ip.to_string();
 	tracing::info!(?ip, "looking up ip");
 
+	// Look up in the local database first
+	let config = ctx.config().server()?.ip_info.as_ref();
+	if let Some(mmdb_config) = config.and_then(|x| x.mmdb.as_ref()) {
+		let start = Instant::now();
+		let res = mmdb::lookup(mmdb_config, ip).await;
+		record_lookup("mmdb", start, &res.as_ref().map(Option::is_some));
+
+		match res {
+			Ok(Some(coords)) => {
+				return Ok(ip::info::Response {
+					ip_info: Some(backend::net::IpInfo {
+						ip: ip_str,
+						coords: Some(coords),
+					}),
+				});
+			}
+			Ok(None) => tracing::info!("ip not found in mmdb"),
+			Err(err) => tracing::warn!(?err, "mmdb lookup failed"),
+		}
+
+		if !config.map_or(true, |x| x.ipinfo_io_fallback) {
+			return Ok(ip::info::Response { ip_info: None });
+		}
+	}
+
 	// Fetch info
-	let ip_info = match provider {
+	let start = Instant::now();
+	let res = match provider {
 		ip::info::Provider::IpInfoIo => {
 			ctx.cache()
 				.fetch_one_proto("ipinfo.ip", ip_str, {
@@ -46,13 +77,30 @@ async fn handle(ctx: OperationContext<ip::info::Request>) -> GlobalResult<ip::in
 						}
 					}
 				})
-				.await?
+				.await
 		}
 	};
+	record_lookup("ipinfo_io", start, &res.as_ref().map(Option::is_some));
+	let ip_info = res?;
 
 	Ok(ip::info::Response { ip_info })
 }
 
+fn record_lookup<E>(provider: &str, start: Instant, res: &Result<bool, E>) {
+	let result = match res {
+		Ok(true) => "found",
+		Ok(false) => "not_found",
+		Err(_) => "error",
+	};
+
+	metrics::LOOKUP_TOTAL
+		.with_label_values(&[provider, result])
+		.inc();
+	metrics::LOOKUP_DURATION
+		.with_label_values(&[provider])
+		.observe(start.elapsed().as_secs_f64());
+}
+
 async fn fetch_ip_info_io(
 	ctx: &OperationContext<ip::info::Request>,
 	ts: i64,
@@ -75,8 +123,14 @@ async fn fetch_ip_info_io(
 		let client = reqwest::Client::new();
 		let req = client.get(format!("https://ipinfo.io/{}", ip_str));
 
-		let req = if let Some(ip_info) = &ctx.config().server()?.ip_info {
-			req.query(&[("token", ip_info.token.read().as_str())])
+		let token = ctx
+			.config()
+			.server()?
+			.ip_info
+			.as_ref()
+			.and_then(|x| x.token.as_ref());
+		let req = if let Some(token) = token {
+			req.query(&[("token", token.read().as_str())])
 		} else {
 			req
 		};
@@ -0,0 +1,25 @@
+use tivet_metrics::{prometheus::*, BUCKETS, REGISTRY};
+
+lazy_static::lazy_static! {
+	pub static ref LOOKUP_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+		"ip_info_lookup_total",
+		"Total IP info lookups by provider and result.",
+		&["provider", "result"],
+		*REGISTRY
+	).unwrap();
+
+	pub static ref LOOKUP_DURATION: HistogramVec = register_histogram_vec_with_registry!(
+		"ip_info_lookup_duration",
+		"Duration of IP info lookups by provider.",
+		&["provider"],
+		BUCKETS.to_vec(),
+		*REGISTRY
+	).unwrap();
+
+	pub static ref MMDB_RELOAD_TOTAL: IntCounterVec = register_int_counter_vec_with_registry!(
+		"ip_info_mmdb_reload_total",
+		"Total loads of the local GeoIP database.",
+		&["result"],
+		*REGISTRY
+	).unwrap();
+}
@@ -0,0 +1,377 @@
+//! Lookups against a local MaxMind-format GeoIP database.
+
+use std::{
+	net::IpAddr,
+	path::{Path, PathBuf},
+	sync::{Arc, RwLock},
+	time::{Duration, Instant, SystemTime},
+};
+
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use proto::backend;
+use tivet_operation::prelude::*;
+
+use crate::metrics;
+
+lazy_static::lazy_static! {
+	static ref DATABASE: Database<Reader<Vec<u8>>> = Database::new();
+}
+
+/// A database file that resolves IPs to coordinates.
+pub trait Provider: Sized + Send + Sync + 'static {
+	/// Opens the database file. Called on a blocking thread.
+	fn open(path: &Path) -> GlobalResult<Self>;
+
+	/// Returns the coordinates of the IP, or `None` if the database has no location for it.
+	fn lookup(&self, ip: IpAddr) -> GlobalResult<Option<backend::net::Coordinates>>;
+}
+
+impl Provider for Reader<Vec<u8>> {
+	fn open(path: &Path) -> GlobalResult<Self> {
+		match Reader::open_readfile(path) {
+			Ok(reader) => Ok(reader),
+			Err(err) => bail!("failed to open mmdb at {}: {err}", path.display()),
+		}
+	}
+
+	fn lookup(&self, ip: IpAddr) -> GlobalResult<Option<backend::net::Coordinates>> {
+		let city = match Reader::lookup::<geoip2::City>(self, ip) {
+			Ok(city) => city,
+			Err(MaxMindDBError::AddressNotFoundError(_)) => return Ok(None),
+			Err(err) => bail!("failed to look up ip in mmdb: {err}"),
+		};
+
+		let coords = city
+			.location
+			.and_then(|loc| Some((loc.latitude?, loc.longitude?)))
+			.map(|(latitude, longitude)| backend::net::Coordinates {
+				latitude,
+				longitude,
+			});
+
+		Ok(coords)
+	}
+}
+
+/// Returns the coordinates of the IP, or `None` if the database has no location for it.
+pub async fn lookup(
+	config: &tivet_config::config::IpInfoMmdb,
+	ip: IpAddr,
+) -> GlobalResult<Option<backend::net::Coordinates>> {
+	DATABASE
+		.lookup(
+			&config.path,
+			Duration::from_secs(config.reload_interval),
+			ip,
+		)
+		.await
+}
+
+/// Loaded database that is reloaded when the file changes.
+///
+/// Lookups only hold the lock long enough to clone the current provider, so a slow reload never
+/// blocks them.
+pub struct Database<P> {
+	current: RwLock<Option<Arc<Loaded<P>>>>,
+	/// Held while checking the file for changes so only one task reloads at a time.
+	reload_lock: tokio::sync::Mutex<()>,
+}
+
+struct Loaded<P> {
+	path: PathBuf,
+	provider: Arc<P>,
+	modified: Option<SystemTime>,
+	last_check: Instant,
+}
+
+impl<P: Provider> Default for Database<P> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<P: Provider> Database<P> {
+	pub fn new() -> Self {
+		Database {
+			current: RwLock::new(None),
+			reload_lock: tokio::sync::Mutex::new(()),
+		}
+	}
+
+	pub async fn lookup(
+		&self,
+		path: &Path,
+		reload_interval: Duration,
+		ip: IpAddr,
+	) -> GlobalResult<Option<backend::net::Coordinates>> {
+		self.provider(path, reload_interval).await?.lookup(ip)
+	}
+
+	/// Returns the loaded provider, reloading it if the file changed since the last check.
+	async fn provider(&self, path: &Path, reload_interval: Duration) -> GlobalResult<Arc<P>> {
+		let current = self.current(path);
+		if let Some(loaded) = &current {
+			if loaded.last_check.elapsed() < reload_interval {
+				return Ok(loaded.provider.clone());
+			}
+		}
+
+		// Keep serving the loaded database while another task checks for changes
+		let _guard = match (self.reload_lock.try_lock(), &current) {
+			(Ok(guard), _) => guard,
+			(Err(_), Some(loaded)) => return Ok(loaded.provider.clone()),
+			(Err(_), None) => self.reload_lock.lock().await,
+		};
+
+		// Another task may have reloaded while we were waiting for the lock
+		let current = self.current(path);
+		if let Some(loaded) = &current {
+			if loaded.last_check.elapsed() < reload_interval {
+				return Ok(loaded.provider.clone());
+			}
+		}
+
+		let modified = match tokio::fs::metadata(path).await {
+			Ok(metadata) => metadata.modified().ok(),
+			Err(err) => {
+				// Keep serving the previous database if the file is temporarily missing
+				if let Some(loaded) = current {
+					tracing::warn!(?err, ?path, "failed to stat mmdb, keeping loaded database");
+					return Ok(self.touch(&loaded));
+				}
+
+				bail!("failed to stat mmdb at {}: {err}", path.display());
+			}
+		};
+
+		if let Some(loaded) = &current {
+			if modified.is_some() && loaded.modified == modified {
+				return Ok(self.touch(loaded));
+			}
+		}
+
+		tracing::info!(?path, "loading mmdb");
+
+		let open_path = path.to_path_buf();
+		let provider = match tokio::task::spawn_blocking(move || P::open(&open_path)).await? {
+			Ok(provider) => {
+				metrics::MMDB_RELOAD_TOTAL
+					.with_label_values(&["success"])
+					.inc();
+
+				Arc::new(provider)
+			}
+			Err(err) => {
+				metrics::MMDB_RELOAD_TOTAL
+					.with_label_values(&["error"])
+					.inc();
+
+				// Keep serving the previous database if the new file is invalid (i.e. partially
+				// written)
+				if let Some(loaded) = current {
+					tracing::warn!(
+						?err,
+						?path,
+						"failed to reload mmdb, keeping loaded database"
+					);
+					return Ok(self.touch(&loaded));
+				}
+
+				return Err(err);
+			}
+		};
+
+		self.store(Loaded {
+			path: path.to_path_buf(),
+			provider: provider.clone(),
+			modified,
+			last_check: Instant::now(),
+		});
+
+		Ok(provider)
+	}
+
+	/// Returns the loaded database if it was loaded from `path`.
+	fn current(&self, path: &Path) -> Option<Arc<Loaded<P>>> {
+		let current = self.current.read().unwrap_or_else(|err| err.into_inner());
+		current.as_ref().filter(|x| x.path == path).cloned()
+	}
+
+	fn store(&self, loaded: Loaded<P>) {
+		*self.current.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(loaded));
+	}
+
+	/// Marks the loaded database as checked without reloading it.
+	fn touch(&self, loaded: &Loaded<P>) -> Arc<P> {
+		self.store(Loaded {
+			path: loaded.path.clone(),
+			provider: loaded.provider.clone(),
+			modified: loaded.modified,
+			last_check: Instant::now(),
+		});
+
+		loaded.provider.clone()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		collections::HashMap,
+		sync::atomic::{AtomicUsize, Ordering},
+	};
+
+	use super::*;
+
+	static TEMP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+	/// Database file with one `ip latitude longitude` entry per line.
+	struct TestProvider(HashMap<IpAddr, (f64, f64)>);
+
+	impl Provider for TestProvider {
+		fn open(path: &Path) -> GlobalResult<Self> {
+			let mut entries = HashMap::new();
+			for line in std::fs::read_to_string(path)?.lines() {
+				let parts = line.split_whitespace().collect::<Vec<_>>();
+				let [ip, latitude, longitude] = parts.as_slice() else {
+					bail!("invalid line: {line}");
+				};
+				entries.insert(ip.parse()?, (latitude.parse()?, longitude.parse()?));
+			}
+
+			Ok(TestProvider(entries))
+		}
+
+		fn lookup(&self, ip: IpAddr) -> GlobalResult<Option<backend::net::Coordinates>> {
+			Ok(self
+				.0
+				.get(&ip)
+				.map(|(latitude, longitude)| backend::net::Coordinates {
+					latitude: *latitude,
+					longitude: *longitude,
+				}))
+		}
+	}
+
+	fn temp_path() -> PathBuf {
+		std::env::temp_dir().join(format!(
+			"ip-info-test-{}-{}.db",
+			std::process::id(),
+			TEMP_COUNT.fetch_add(1, Ordering::SeqCst)
+		))
+	}
+
+	/// Writes the file with an explicit modification time so changes don't depend on the timestamp
+	/// resolution of the filesystem.
+	fn write(path: &Path, contents: &str, modified_secs: u64) {
+		std::fs::write(path, contents).unwrap();
+		std::fs::File::options()
+			.write(true)
+			.open(path)
+			.unwrap()
+			.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(modified_secs))
+			.unwrap();
+	}
+
+	fn ip(ip: &str) -> IpAddr {
+		ip.parse().unwrap()
+	}
+
+	async fn latitude(
+		db: &Database<TestProvider>,
+		path: &Path,
+		reload_interval: Duration,
+		addr: &str,
+	) -> Option<f64> {
+		db.lookup(path, reload_interval, ip(addr))
+			.await
+			.unwrap()
+			.map(|x| x.latitude)
+	}
+
+	#[tokio::test]
+	async fn reload() {
+		let path = temp_path();
+		let db = Database::<TestProvider>::new();
+
+		// Missing file without a loaded database
+		assert!(db
+			.lookup(&path, Duration::ZERO, ip("1.1.1.1"))
+			.await
+			.is_err());
+
+		// Invalid file without a loaded database
+		write(&path, "garbage", 1);
+		assert!(db
+			.lookup(&path, Duration::ZERO, ip("1.1.1.1"))
+			.await
+			.is_err());
+
+		// Load
+		write(&path, "1.1.1.1 10 20\n", 2);
+		assert_eq!(
+			Some(10.0),
+			latitude(&db, &path, Duration::ZERO, "1.1.1.1").await
+		);
+		assert_eq!(None, latitude(&db, &path, Duration::ZERO, "8.8.8.8").await);
+
+		// Unchanged file is not reopened
+		let provider = db.provider(&path, Duration::ZERO).await.unwrap();
+		let provider2 = db.provider(&path, Duration::ZERO).await.unwrap();
+		assert!(Arc::ptr_eq(&provider, &provider2));
+
+		// Changed file is not checked within the reload interval
+		write(&path, "1.1.1.1 30 40\n", 3);
+		let hour = Duration::from_secs(60 * 60);
+		assert_eq!(Some(10.0), latitude(&db, &path, hour, "1.1.1.1").await);
+
+		// Changed file is reloaded after the reload interval
+		assert_eq!(
+			Some(30.0),
+			latitude(&db, &path, Duration::ZERO, "1.1.1.1").await
+		);
+
+		// Keeps serving the loaded database if the new file is invalid
+		write(&path, "1.1.1.1 30", 4);
+		assert_eq!(
+			Some(30.0),
+			latitude(&db, &path, Duration::ZERO, "1.1.1.1").await
+		);
+
+		// Keeps serving the loaded database if the file is missing
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(
+			Some(30.0),
+			latitude(&db, &path, Duration::ZERO, "1.1.1.1").await
+		);
+
+		// Database for a different path is not reused
+		assert!(db
+			.lookup(&temp_path(), Duration::ZERO, ip("1.1.1.1"))
+			.await
+			.is_err());
+	}
+
+	#[tokio::test]
+	async fn lookups_do_not_wait_on_reload() {
+		let path = temp_path();
+		std::fs::write(&path, "1.1.1.1 10 20\n").unwrap();
+
+		let db = Database::<TestProvider>::new();
+		assert_eq!(
+			Some(10.0),
+			latitude(&db, &path, Duration::ZERO, "1.1.1.1").await
+		);
+
+		// Simulate a reload in progress
+		let _guard = db.reload_lock.lock().await;
+		let res = tokio::time::timeout(
+			Duration::from_secs(1),
+			latitude(&db, &path, Duration::ZERO, "1.1.1.1"),
+		)
+		.await;
+		assert_eq!(Ok(Some(10.0)), res.map_err(|_| ()));
+
+		std::fs::remove_file(&path).unwrap();
+	}
+}
@@ -1,11 +1,8 @@
 use std::convert::TryInto;
 
 use chirp_workflow::prelude::*;
-use cluster::types::Provider;
+use cluster::{provider::Image, types::Provider};
 use futures_util::{StreamExt, TryStreamExt};
-use linode::util::{api, client};
-use reqwest::header;
-use serde_json::json;
 
 pub async fn start(config: tivet_config::Config, pools: tivet_pools::Pools) -> GlobalResult<()> {
 	let mut interval = tokio::time::interval(std::time::Duration::from_secs(15));
@@ -44,55 +41,31 @@ pub async fn run_from_env(
 	.into_iter()
 	.chain(std::iter::once((Provider::Linode as i64, secret.clone())));
 
-	let filter = json!({
-		"status": "available",
-		"type": "manual"
-	});
-	let mut headers = header::HeaderMap::new();
-	headers.insert(
-		"X-Filter",
-		header::HeaderValue::from_str(&serde_json::to_string(&filter)?)?,
-	);
-
 	for (provider, api_token) in dc_rows {
 		let provider = unwrap!(Provider::from_repr(provider.try_into()?));
 
 		match provider {
-			Provider::Manual => {
+			Provider::Manual | Provider::Local => {
 				// Noop
 			}
-			Provider::Linode => {
-				run_for_linode_account(ctx.clone(), api_token.clone(), &headers).await?
-			}
+			Provider::Linode => run_for_linode_account(ctx.clone(), api_token.clone()).await?,
 		}
 	}
 
 	Ok(())
 }
 
-async fn run_for_linode_account(
-	ctx: StandaloneCtx,
-	api_token: String,
-	headers: &header::HeaderMap,
-) -> GlobalResult<()> {
-	// Build HTTP client
-	let client = client::Client::new_with_headers(api_token, headers.clone()).await?;
-
-	let complete_images = api::list_custom_images(ctx.config(), &client).await?;
-
-	if complete_images.len() == linode::util::api::CUSTOM_IMAGE_LIST_SIZE {
-		// We don't need to paginate since we'll never have more than
-		// `number of regions * number of pools * 2` images which is not more than 500 (x2 is for the old +
-		// new images)
-		tracing::warn!("page limit reached, new images may not be returned");
-	}
+async fn run_for_linode_account(ctx: StandaloneCtx, api_token: String) -> GlobalResult<()> {
+	let complete_images = cluster::provider::get(Provider::Linode)
+		.list_images(ctx.config(), Some(&api_token))
+		.await?;
 
 	delete_expired_images(ctx.clone(), complete_images.clone()).await?;
 
 	// Get image ids
 	let image_ids = complete_images
 		.into_iter()
-		.map(|x| x.id)
+		.map(|x| x.provider_image_id)
 		.collect::<Vec<_>>();
 
 	// Set images as complete
@@ -133,17 +106,16 @@ async fn run_for_linode_account(
 
 async fn delete_expired_images(
 	ctx: StandaloneCtx,
-	complete_images: Vec<api::CustomImage>,
+	complete_images: Vec<Image>,
 ) -> GlobalResult<()> {
 	// Prebake images have an expiration because of their server token. We add 2 days of padding here for
 	// safety
-	let expiration = chrono::Utc::now()
-		- chrono::Duration::milliseconds(cluster::util::SERVER_TOKEN_TTL)
-		+ chrono::Duration::days(2);
+	let expiration =
+		util::timestamp::now() - cluster::util::SERVER_TOKEN_TTL + util::duration::days(2);
 
 	let expired_images = complete_images
 		.iter()
-		.filter(|img| img.created < expiration);
+		.filter(|img| img.create_ts < expiration);
 
 	let expired_images_count = expired_images.clone().count();
 	if expired_images_count != 0 {
@@ -156,7 +128,7 @@ async fn delete_expired_images(
 
 			async move {
 				ctx.signal(linode::workflows::image::Destroy {})
-					.tag("image_id", img.id)
+					.tag("image_id", img.provider_image_id)
 					.send()
 					.await
 			}
@@ -139,6 +139,7 @@ fn lobby_group_config(
 		listable: true,
 		taggable: false,
 		allow_dynamic_max_players: false,
+		skill_matchmaking: None,
 
 		runtime: Some(
 			backend::matchmaker::lobby_runtime::Docker {
@@ -338,6 +338,7 @@ async fn setup_game(
 					listable: true,
 					taggable: false,
 					allow_dynamic_max_players: false,
+					skill_matchmaking: None,
 
 					runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 						build_id: build_res.build_id,
@@ -482,7 +483,7 @@ async fn connect_to_lobby(
 	let host = format!("{hostname}:{port}");
 
 	// Get player id
-	let player_claims = tivet_claims::decode(&config.server()?.jwt.public, token)
+	let player_claims = tivet_claims::decode(&config.server()?.jwt, token)
 		.map_err(|_| err_code!(TOKEN_ERROR, error = "Malformed player token"))?
 		.map_err(|_| err_code!(TOKEN_ERROR, error = "Invalid player token"))?;
 	let player_ent = player_claims.as_matchmaker_player()?;
@@ -0,0 +1,2 @@
+ALTER TABLE lobby_groups
+	ADD COLUMN skill_matchmaking_config BYTES;
@@ -26,6 +26,7 @@ struct LobbyGroup {
 	find_config: Option<Vec<u8>>,
 	join_config: Option<Vec<u8>>,
 	create_config: Option<Vec<u8>>,
+	skill_matchmaking_config: Option<Vec<u8>>,
 }
 
 #[derive(Clone, sqlx::FromRow)]
@@ -97,7 +98,8 @@ async fn fetch_versions(
 				max_players_normal, max_players_direct, max_players_party,
 				listable, taggable, allow_dynamic_max_players,
 				runtime, runtime_meta,
-				find_config, join_config, create_config
+				find_config, join_config, create_config,
+				skill_matchmaking_config
 			FROM db_mm_config.lobby_groups
 			WHERE version_id = ANY($1)
 			",
@@ -187,6 +189,12 @@ async fn fetch_versions(
 								}
 							};
 
+							let skill_matchmaking = lg
+								.skill_matchmaking_config
+								.as_ref()
+								.map(|x| backend::matchmaker::SkillMatchmaking::decode(x.as_ref()))
+								.transpose()?;
+
 							Ok(backend::matchmaker::LobbyGroup {
 								name_id: lg.name_id.clone(),
 
@@ -217,6 +225,7 @@ async fn fetch_versions(
 								listable: lg.listable,
 								taggable: lg.taggable,
 								allow_dynamic_max_players: lg.allow_dynamic_max_players,
+								skill_matchmaking,
 
 								runtime: Some(runtime),
 
@@ -43,6 +43,7 @@ impl TestVersion {
 				listable: true,
 				taggable: false,
 				allow_dynamic_max_players: false,
+				skill_matchmaking: None,
 
 				runtime: Some(
 					backend::matchmaker::lobby_runtime::Docker {
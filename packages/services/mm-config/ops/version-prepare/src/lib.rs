@@ -172,6 +172,10 @@ async fn validate_build(
 		build.compression
 	));
 	ensure_eq!(game_id, build_game_id);
+	ensure!(
+		!build.compression_dictionary,
+		"builds with a compression dictionary are not supported by matchmaker"
+	);
 
 	tracing::info!(?build);
 
@@ -107,6 +107,16 @@ async fn handle(
 				GlobalResult::Ok(buf)
 			})
 			.transpose()?;
+		let skill_matchmaking_config_buf = lobby_group
+			.skill_matchmaking
+			.as_ref()
+			.map(|config| {
+				let mut buf = Vec::with_capacity(config.encoded_len());
+				config.encode(&mut buf)?;
+
+				GlobalResult::Ok(buf)
+			})
+			.transpose()?;
 
 		sql_execute!(
 			[ctx]
@@ -128,9 +138,10 @@ async fn handle(
 				join_config,
 				create_config,
 				allow_dynamic_max_players,
-				taggable
+				taggable,
+				skill_matchmaking_config
 			)
-			VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+			VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
 			",
 			lobby_group_id,
 			version_id,
@@ -146,6 +157,7 @@ async fn handle(
 			&create_config_buf,
 			lobby_group.allow_dynamic_max_players,
 			lobby_group.taggable,
+			&skill_matchmaking_config_buf,
 		)
 		.await?;
 
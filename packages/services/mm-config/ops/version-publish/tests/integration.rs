@@ -37,6 +37,7 @@ async fn empty(ctx: TestCtx) {
 					listable: true,
 					taggable: false,
 					allow_dynamic_max_players: false,
+					skill_matchmaking: None,
 
 					runtime: Some(backend::matchmaker::LobbyRuntime {
 						runtime: Some(backend::matchmaker::lobby_runtime::Runtime::Docker(
@@ -93,6 +94,7 @@ async fn empty(ctx: TestCtx) {
 					listable: true,
 					taggable: false,
 					allow_dynamic_max_players: false,
+					skill_matchmaking: None,
 
 					runtime: Some(
 						backend::matchmaker::lobby_runtime::Docker {
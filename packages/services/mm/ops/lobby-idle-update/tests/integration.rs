@@ -79,6 +79,7 @@ impl Ctx {
 					listable: true,
 					taggable: false,
 					allow_dynamic_max_players: false,
+					skill_matchmaking: None,
 
 					runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 						build_id: build_res.build_id,
@@ -531,6 +532,7 @@ async fn test_find_idle_lobby(ctx: TestCtx) {
 					user_agent: Some("Test".into()),
 					remote_address: Some(util::faker::ip_addr_v4().to_string()),
 				}),
+				rating: None,
 			}],
 			query: Some(mm::msg::lobby_find::message::Query::LobbyGroup(backend::matchmaker::query::LobbyGroup {
 				lobby_group_ids: vec![test_ctx.lobby_group_id.into()],
@@ -67,6 +67,7 @@ async fn empty(ctx: TestCtx) {
 				player_id: Some(player.player_id.into()),
 				token_session_id: Some(Uuid::new_v4().into()),
 				client_info:None,
+				rating: None,
 			}],
 			query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
 				lobby_id: Some(player.lobby_id.into()),
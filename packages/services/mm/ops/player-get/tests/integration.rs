@@ -26,6 +26,7 @@ async fn basic(ctx: TestCtx) {
 				player_id: Some(player_id.into()),
 				token_session_id: Some(Uuid::new_v4().into()),
 				client_info: None,
+				rating: None,
 			}
 		],
 		query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
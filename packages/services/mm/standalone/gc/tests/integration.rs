@@ -112,6 +112,7 @@ async fn remove_unregistered_players(ctx: TestCtx) {
 			player_id: Some(player_id.into()),
 			token_session_id: Some(Uuid::new_v4().into()),
 			client_info:None,
+			rating: None,
 		}],
 		query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
 			lobby_id: Some(lobby_id.into()),
@@ -198,6 +199,7 @@ async fn remove_auto_remove_players(ctx: TestCtx) {
 			player_id: Some(player_id.into()),
 			token_session_id: Some(Uuid::new_v4().into()),
 			client_info:None,
+			rating: None,
 		}],
 		query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
 			lobby_id: Some(lobby_id.into()),
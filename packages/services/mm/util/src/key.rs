@@ -23,6 +23,7 @@ pub mod player_config {
 	pub const LOBBY_ID: &str = "l";
 	pub const QUERY_ID: &str = "qi";
 	pub const REMOTE_ADDRESS: &str = "ra";
+	pub const RATING: &str = "rt";
 }
 
 /// HASH
@@ -35,6 +36,19 @@ pub fn lobby_tags(lobby_id: Uuid) -> String {
 	format!("{{global}}:mm:lobby:{}:tags", lobby_id)
 }
 
+/// HASH
+///
+/// Running totals of the ratings of the players in the lobby. Used to derive the mean and spread.
+pub fn lobby_rating(lobby_id: Uuid) -> String {
+	format!("{{global}}:mm:lobby:{}:rating", lobby_id)
+}
+
+pub mod lobby_rating {
+	pub const PLAYER_COUNT: &str = "n";
+	pub const SUM: &str = "s";
+	pub const SUM_SQUARES: &str = "ss";
+}
+
 pub mod lobby_config {
 	use uuid::Uuid;
 
@@ -136,6 +150,17 @@ pub fn ns_remote_address_player_ids(namespace_id: Uuid, remote_address: &str) ->
 	)
 }
 
+/// i64
+///
+/// When a client started queueing for skill matchmaking. `identity` is the user ID or the remote
+/// address of guests.
+pub fn ns_queue_start_ts(namespace_id: Uuid, identity: &str) -> String {
+	format!(
+		"{{global}}:mm:ns:{}:queue:{}:start_ts",
+		namespace_id, identity
+	)
+}
+
 /// ZSET<player id>
 pub fn lobby_player_ids(lobby_id: Uuid) -> String {
 	format!("{{global}}:mm:lobby:{}:player_ids", lobby_id)
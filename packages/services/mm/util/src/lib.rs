@@ -1,6 +1,7 @@
 pub mod consts;
 pub mod defaults;
 pub mod key;
+pub mod skill;
 pub mod test;
 pub mod verification;
 pub mod version_migrations;
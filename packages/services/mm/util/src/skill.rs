@@ -0,0 +1,15 @@
+use proto::backend;
+use tivet_operation::prelude::*;
+
+/// Max distance from a lobby's mean rating after the players have been queueing for `queue_dt` ms.
+pub fn window(config: &backend::matchmaker::SkillMatchmaking, queue_dt: i64) -> f64 {
+	let queue_secs = queue_dt.max(0) as f64 / 1000.0;
+
+	(config.initial_window + config.window_growth_per_second * queue_secs).min(config.max_window)
+}
+
+/// Resolves when the players started queueing. Timestamps in the future are clamped to `now` so
+/// clients can't skip the initial window.
+pub fn queue_start_ts(queue_start_ts: Option<i64>, now: i64) -> i64 {
+	queue_start_ts.map_or(now, |ts| ts.min(now))
+}
@@ -4,7 +4,7 @@ use futures_util::{StreamExt, TryStreamExt};
 use http::StatusCode;
 use proto::backend::{self, pkg::*};
 use tivet_operation::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 #[derive(Serialize)]
@@ -14,6 +14,13 @@ pub struct ExternalVerificationRequest {
 	pub clients: HashMap<String, Client>,
 	pub join_kind: JoinKind,
 	pub kind: ConnectionKind,
+	/// Rating claimed by the client, only trusted if returned in the response.
+	pub rating: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct ExternalVerificationResponse {
+	rating: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -72,6 +79,7 @@ pub struct VerifyConfigOpts<'a> {
 	pub lobby_state_json: Option<&'a str>,
 
 	pub verification_data_json: Option<&'a str>,
+	pub rating: Option<f64>,
 	pub lobby_config_json: Option<&'a str>,
 	pub custom_lobby_publicity: Option<backend::matchmaker::lobby::Publicity>,
 }
@@ -82,11 +90,20 @@ struct ExternalRequestConfigAndLobby<'a> {
 	external_request_config: backend::net::ExternalRequestConfig,
 }
 
+#[derive(Default)]
+pub struct Verification {
+	/// Rating returned by the verification endpoint.
+	pub rating: Option<f64>,
+	/// If any external verification request was made.
+	pub external: bool,
+}
+
 /// Verifies everything required to make a find request or create a custom lobby.
 pub async fn verify_config(
 	ctx: &OperationContext<()>,
 	opts: &VerifyConfigOpts<'_>,
-) -> GlobalResult<()> {
+) -> GlobalResult<Verification> {
+	let mut verification = Verification::default();
 	let mut highest_identity_requirement = backend::matchmaker::IdentityRequirement::None;
 	let mut external_request_configs = Vec::new();
 
@@ -358,6 +375,7 @@ pub async fn verify_config(
 			clients,
 			join_kind: JoinKind::Normal,
 			kind: opts.kind,
+			rating: opts.rating,
 		};
 
 		// Send request
@@ -369,6 +387,7 @@ pub async fn verify_config(
 			config: Some(external_request_config),
 			timeout: util::duration::seconds(10) as u64,
 			body: Some(serde_json::to_vec(&body)?),
+			read_response_body: true,
 			..Default::default()
 		})
 		.await?;
@@ -382,10 +401,27 @@ pub async fn verify_config(
 			if !status.is_success() {
 				bail_with!(MATCHMAKER_VERIFICATION_FAILED);
 			}
+
+			// Responses without a JSON body don't return a rating
+			if let Some(body) = res.body.as_ref().filter(|body| !body.is_empty()) {
+				match serde_json::from_slice::<ExternalVerificationResponse>(body) {
+					Ok(res) => {
+						if let Some(rating) = res.rating {
+							ensure_with!(rating.is_finite(), MATCHMAKER_VERIFICATION_FAILED);
+							verification.rating = Some(rating);
+						}
+					}
+					Err(err) => {
+						tracing::debug!(?err, "user verification response is not json");
+					}
+				}
+			}
+
+			verification.external = true;
 		} else {
 			bail_with!(MATCHMAKER_VERIFICATION_REQUEST_FAILED);
 		}
 	}
 
-	Ok(())
+	Ok(verification)
 }
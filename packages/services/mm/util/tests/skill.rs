@@ -0,0 +1,44 @@
+use proto::backend;
+use tivet_operation::prelude::*;
+
+fn config() -> backend::matchmaker::SkillMatchmaking {
+	backend::matchmaker::SkillMatchmaking {
+		initial_window: 100.0,
+		window_growth_per_second: 10.0,
+		max_window: 500.0,
+		max_spread: 200.0,
+	}
+}
+
+#[test]
+fn window_grows_with_queue_time() {
+	let config = config();
+
+	assert_eq!(100.0, tivet_util_mm::skill::window(&config, 0));
+	assert_eq!(105.0, tivet_util_mm::skill::window(&config, 500));
+	assert_eq!(200.0, tivet_util_mm::skill::window(&config, 10_000));
+
+	// Stops at the max window
+	assert_eq!(500.0, tivet_util_mm::skill::window(&config, 40_000));
+	assert_eq!(500.0, tivet_util_mm::skill::window(&config, 60 * 60 * 1000));
+
+	// Negative queue times don't shrink the window
+	assert_eq!(100.0, tivet_util_mm::skill::window(&config, -10_000));
+}
+
+#[test]
+fn queue_start_ts() {
+	let now = 1_000_000;
+
+	assert_eq!(now, tivet_util_mm::skill::queue_start_ts(None, now));
+	assert_eq!(
+		now - 5000,
+		tivet_util_mm::skill::queue_start_ts(Some(now - 5000), now)
+	);
+
+	// Clamped to now
+	assert_eq!(
+		now,
+		tivet_util_mm::skill::queue_start_ts(Some(now + 5000), now)
+	);
+}
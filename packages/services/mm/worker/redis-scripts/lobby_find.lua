@@ -18,6 +18,37 @@ for tag_name, _ in pairs(query.tags) do
 	query_tag_names[#query_tag_names + 1] = tag_name
 end
 
+-- Returns the distance between the players' rating and the lobby's mean
+-- rating, or nil if the lobby is outside of the rating window or would exceed
+-- the max spread. Lobbies without rated players are treated as being at the
+-- edge of the window so closer lobbies are preferred.
+local function skill_distance(lobby_id, skill)
+	local key_lobby_rating = '{global}:mm:lobby:' .. lobby_id .. ':rating'
+	local rating = redis.call('HMGET', key_lobby_rating, 'n', 's', 'ss')
+	local count = tonumber(rating[1]) or 0
+	if count <= 0 then
+		return skill.window
+	end
+	local sum = tonumber(rating[2]) or 0
+	local sum_squares = tonumber(rating[3]) or 0
+
+	-- Check distance from the lobby's mean rating
+	local distance = math.abs(sum / count - skill.rating)
+	if distance > skill.window then
+		return nil
+	end
+
+	-- Check spread once the players join
+	local new_count = count + skill.count
+	local new_mean = (sum + skill.sum) / new_count
+	local new_variance = math.max((sum_squares + skill.sum_squares) / new_count - new_mean * new_mean, 0)
+	if math.sqrt(new_variance) > skill.max_spread then
+		return nil
+	end
+
+	return distance
+end
+
 -- MARK: Find
 local lobby_id = nil
 local lobby_auto_created = false
@@ -53,14 +84,17 @@ if query.kind.direct ~= nil then
 elseif query.kind.lobby_group ~= nil then
 	-- MARK: Lobby group
 
+	local skill = query.kind.lobby_group.skill
+
 	-- Iterate over all lobby ranked keys to find the most optimal lobby
 	local best_lobby_id = nil
 	local best_available_spots = 0
+	local best_distance = nil
 	for i = 1, available_spots_key_count do
 		local key_available_spots = KEYS[available_spots_key_idx + i]
 
-		-- No lobby tags, continue normally
-		if #query_tag_names == 0 then
+		-- No lobby tags or skill window, continue normally
+		if #query_tag_names == 0 and skill == nil then
 			-- Find the lobby with the least available spots (i.e. the most full lobby)
 			--
 			-- We always use `max_players_normal` when finding lobby groups
@@ -84,8 +118,8 @@ elseif query.kind.lobby_group ~= nil then
 			-- Max iteration count is 128 to prevent spinning
 			local MAX_LOBBY_COUNT = 128
 
-			-- With lobby tags we need to query each available spots key multiple times until we find a
-			-- lobby that matches
+			-- With lobby tags or a skill window we need to query each available spots key multiple
+			-- times until we find a lobby that matches
 			local lobbies = redis.call('ZRANGEBYSCORE', key_available_spots, #query.players, '+inf',
 				'WITHSCORES', 'LIMIT', '0', tostring(MAX_LOBBY_COUNT))
 
@@ -95,26 +129,42 @@ elseif query.kind.lobby_group ~= nil then
 				local correct_tags = 0
 
 				-- Fetch specified lobby tags
-				local key_lobby_tags = '{global}:mm:lobby:' .. lobby_id .. ':tags'
-				local lobby_tags = redis.call('HMGET', key_lobby_tags, unpack(query_tag_names))
+				if #query_tag_names > 0 then
+					local key_lobby_tags = '{global}:mm:lobby:' .. lobby_id .. ':tags'
+					local lobby_tags = redis.call('HMGET', key_lobby_tags, unpack(query_tag_names))
 						
-				-- Check that all tags are correct
-				for tag_idx, tag in ipairs(lobby_tags) do
-					if query.tags[query_tag_names[tag_idx]] ~= tag then
-						break
-					end
+					-- Check that all tags are correct
+					for tag_idx, tag in ipairs(lobby_tags) do
+						if query.tags[query_tag_names[tag_idx]] ~= tag then
+							break
+						end
 	
-					correct_tags = correct_tags + 1
+						correct_tags = correct_tags + 1
+					end
 				end
 		
 				-- Check if the correct number of tags have been parsed
-				if correct_tags == #query_tag_names and
-					(best_lobby_id == nil or available_spots < best_available_spots)
-				then
-					best_lobby_id = lobby_id
-					best_available_spots = available_spots
-
-					break
+				if correct_tags == #query_tag_names then
+					if skill == nil then
+						if best_lobby_id == nil or available_spots < best_available_spots then
+							best_lobby_id = lobby_id
+							best_available_spots = available_spots
+
+							break
+						end
+					else
+						-- Prefer the lobby closest to the players' rating, then the most full lobby
+						local distance = skill_distance(lobby_id, skill)
+						if distance ~= nil and (
+							best_lobby_id == nil or
+							distance < best_distance or
+							(distance == best_distance and available_spots < best_available_spots)
+						) then
+							best_lobby_id = lobby_id
+							best_available_spots = available_spots
+							best_distance = distance
+						end
+					end
 				end
 			end
 		end
@@ -235,6 +285,19 @@ for i, player in ipairs(query.players) do
 	end
 end
 
+-- Add player ratings to the lobby
+local key_lobby_rating = '{global}:mm:lobby:' .. lobby_id .. ':rating'
+for i, player in ipairs(query.players) do
+	if player.rating ~= nil then
+		local key_player_config = KEYS[player_config_key_idx + i]
+
+		redis.call('HSET', key_player_config, 'rt', tostring(player.rating))
+		redis.call('HINCRBYFLOAT', key_lobby_rating, 'n', 1)
+		redis.call('HINCRBYFLOAT', key_lobby_rating, 's', player.rating)
+		redis.call('HINCRBYFLOAT', key_lobby_rating, 'ss', player.rating * player.rating)
+	end
+end
+
 -- Update the available spots
 local lobby_player_count = redis.call('ZCARD', key_lobby_player_ids)
 redis.call('ZADD', key_lobby_available_spots_normal, max_players_normal - lobby_player_count, lobby_id)
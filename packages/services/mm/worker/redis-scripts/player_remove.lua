@@ -19,6 +19,15 @@ local max_players_normal = tonumber(ARGV[5])
 local max_players_party = tonumber(ARGV[6])
 local auto_remove_lobby = ARGV[7] == '1'
 
+-- Remove the player's rating from the lobby
+local rating = tonumber(redis.call('HGET', key_player_config, 'rt'))
+if rating ~= nil then
+	local key_lobby_rating = '{global}:mm:lobby:' .. lobby_id .. ':rating'
+	redis.call('HINCRBYFLOAT', key_lobby_rating, 'n', -1)
+	redis.call('HINCRBYFLOAT', key_lobby_rating, 's', -rating)
+	redis.call('HINCRBYFLOAT', key_lobby_rating, 'ss', -rating * rating)
+end
+
 -- Remove the player
 redis.call('DEL', key_player_config)
 redis.call('ZREM', key_ns_player_ids, player_id)
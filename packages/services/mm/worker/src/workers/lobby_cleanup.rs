@@ -222,6 +222,7 @@ async fn remove_from_redis(
 	pipe.atomic()
 		.unlink(util_mm::key::lobby_config(lobby_id))
 		.unlink(util_mm::key::lobby_tags(lobby_id))
+		.unlink(util_mm::key::lobby_rating(lobby_id))
 		.zrem(
 			util_mm::key::ns_lobby_ids(namespace_id),
 			lobby_id.to_string(),
@@ -267,6 +268,7 @@ async fn remove_from_redis_without_config(
 	pipe.atomic()
 		.unlink(util_mm::key::lobby_config(lobby_id))
 		.unlink(util_mm::key::lobby_tags(lobby_id))
+		.unlink(util_mm::key::lobby_rating(lobby_id))
 		.zrem(util_mm::key::lobby_unready(), lobby_id.to_string())
 		.query_async::<_, ()>(redis_mm)
 		.await?;
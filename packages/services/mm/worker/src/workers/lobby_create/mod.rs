@@ -620,6 +620,11 @@ async fn create_docker_job(
 	let build_compression = unwrap!(backend::build::BuildCompression::from_i32(
 		build.compression
 	));
+	// Rejected when the version is prepared, Nomad jobs cannot decompress with a dictionary
+	ensure!(
+		!build.compression_dictionary,
+		"builds with a compression dictionary are not supported"
+	);
 
 	// Generate the Docker job
 	let job_spec = nomad_job::gen_lobby_docker_job(
@@ -369,6 +369,9 @@ pub fn gen_lobby_docker_job(
 		backend::build::BuildCompression::Lz4 => {
 			download_cmd.push_str(" | lz4 -d -");
 		}
+		backend::build::BuildCompression::Zstd => {
+			download_cmd.push_str(" | zstd -d -");
+		}
 	}
 
 	Ok(Job {
@@ -53,6 +53,8 @@ mod redis_query {
 		LobbyGroup {
 			#[serde(skip_serializing_if = "Option::is_none")]
 			auto_create: Option<AutoCreate>,
+			#[serde(skip_serializing_if = "Option::is_none")]
+			skill: Option<Skill>,
 		},
 	}
 
@@ -73,11 +75,29 @@ mod redis_query {
 		pub ready_expire_ts: i64,
 	}
 
+	/// Only lobbies whose mean rating is within `window` of `rating` are joined. The closest lobby
+	/// is preferred.
+	#[derive(Serialize)]
+	pub struct Skill {
+		/// Mean rating of the rated players in this query.
+		pub rating: f64,
+		pub window: f64,
+		/// Max standard deviation of the lobby's ratings after the players join.
+		pub max_spread: f64,
+
+		/// Totals of the rated players in this query.
+		pub count: usize,
+		pub sum: f64,
+		pub sum_squares: f64,
+	}
+
 	#[derive(Serialize)]
 	pub struct Player {
 		pub player_id: Uuid,
 		#[serde(skip_serializing_if = "Option::is_none")]
 		pub remote_address: Option<String>,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub rating: Option<f64>,
 	}
 }
 
@@ -91,6 +111,7 @@ pub struct FindOpts<'a> {
 	pub auto_create_lobby_id: Uuid,
 	pub tags: &'a HashMap<String, String>,
 	pub dynamic_max_players: Option<u32>,
+	pub queue_start_ts: i64,
 }
 
 pub struct FindOutput {
@@ -116,6 +137,7 @@ pub async fn find(
 		auto_create_lobby_id,
 		tags,
 		dynamic_max_players,
+		queue_start_ts,
 	}: FindOpts<'_>,
 ) -> GlobalResult<Option<FindOutput>> {
 	use util_mm::key;
@@ -227,8 +249,16 @@ pub async fn find(
 			}
 			available_spots_keys.shuffle(&mut rand::thread_rng());
 
+			// Lobby groups in the same query share the policy of the first one with skill
+			// matchmaking enabled
+			let skill = lobby_group_config
+				.lobby_groups
+				.iter()
+				.find_map(|lg| lg.skill_matchmaking.as_ref())
+				.and_then(|config| build_skill(config, players, ctx.ts() - queue_start_ts));
+
 			(
-				redis_query::QueryKind::LobbyGroup { auto_create },
+				redis_query::QueryKind::LobbyGroup { auto_create, skill },
 				match join_kind {
 					JoinKind::Normal => util_mm::JoinKind::Normal,
 					JoinKind::Party => util_mm::JoinKind::Party,
@@ -248,6 +278,7 @@ pub async fn find(
 		query_players.push(redis_query::Player {
 			player_id: player.player_id,
 			remote_address: remote_address.cloned(),
+			rating: player.rating,
 		});
 
 		// Add keys to write players to
@@ -361,3 +392,27 @@ pub async fn find(
 		.await
 		.map(|_| None)
 }
+
+/// Builds the rating window for the rated players in the query. Returns `None` if no players are
+/// rated.
+fn build_skill(
+	config: &backend::matchmaker::SkillMatchmaking,
+	players: &[super::Player],
+	queue_dt: i64,
+) -> Option<redis_query::Skill> {
+	let ratings = players.iter().filter_map(|p| p.rating).collect::<Vec<_>>();
+	if ratings.is_empty() {
+		return None;
+	}
+
+	let sum = ratings.iter().sum::<f64>();
+
+	Some(redis_query::Skill {
+		rating: sum / ratings.len() as f64,
+		window: util_mm::skill::window(config, queue_dt),
+		max_spread: config.max_spread,
+		count: ratings.len(),
+		sum,
+		sum_squares: ratings.iter().map(|x| x * x).sum(),
+	})
+}
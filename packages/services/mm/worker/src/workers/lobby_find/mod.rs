@@ -9,12 +9,14 @@ use tracing::Instrument;
 
 mod find;
 mod limit;
+mod queue;
 
 #[derive(Debug, Clone)]
 pub struct Player {
 	player_id: Uuid,
 	token_session_id: Uuid,
 	client_info: Option<backend::net::ClientInfo>,
+	rating: Option<f64>,
 }
 
 /// Pass true for `override_query_state` if executed before the query has been
@@ -116,14 +118,20 @@ async fn worker(ctx: &OperationContext<mm::msg::lobby_find::Message>) -> GlobalR
 	}
 
 	// Create players
-	let players = ctx
+	let mut players = ctx
 		.players
 		.iter()
 		.map(|player| {
+			ensure!(
+				player.rating.map_or(true, f64::is_finite),
+				"player rating must be finite"
+			);
+
 			GlobalResult::Ok(Player {
 				player_id: unwrap_ref!(player.player_id).as_uuid(),
 				token_session_id: unwrap_ref!(player.token_session_id).as_uuid(),
 				client_info: player.client_info.clone(),
+				rating: player.rating,
 			})
 		})
 		.collect::<GlobalResult<Vec<_>>>()?;
@@ -187,38 +195,61 @@ async fn worker(ctx: &OperationContext<mm::msg::lobby_find::Message>) -> GlobalR
 				lobby_state_json: lobby_group_config.lobby_state_json.as_deref(),
 
 				verification_data_json: ctx.verification_data_json.as_deref(),
+				rating: players.iter().find_map(|p| p.rating),
 				lobby_config_json: None,
 				custom_lobby_publicity: None,
 			},
 		)
 		.await;
-		if let Err(err) = verification_res {
-			// Reduces verbosity
-			let err_branch = |err_code| async move {
-				fail(ctx, namespace_id, query_id, err_code, true).await?;
-				complete_request(ctx.chirp(), analytics_events).await
-			};
-
-			let res = if err.is(formatted_error::code::MATCHMAKER_FIND_DISABLED) {
-				err_branch(ErrorCode::FindDisabled).await
-			} else if err.is(formatted_error::code::MATCHMAKER_JOIN_DISABLED) {
-				err_branch(ErrorCode::JoinDisabled).await
-			} else if err.is(formatted_error::code::MATCHMAKER_REGISTRATION_REQUIRED) {
-				err_branch(ErrorCode::RegistrationRequired).await
-			} else if err.is(formatted_error::code::MATCHMAKER_IDENTITY_REQUIRED) {
-				err_branch(ErrorCode::IdentityRequired).await
-			} else if err.is(formatted_error::code::MATCHMAKER_VERIFICATION_FAILED) {
-				err_branch(ErrorCode::VerificationFailed).await
-			} else if err.is(formatted_error::code::MATCHMAKER_VERIFICATION_REQUEST_FAILED) {
-				err_branch(ErrorCode::VerificationRequestFailed).await
-			} else {
-				Err(err)
-			};
-
-			return res;
+		match verification_res {
+			// Ratings sent by the client are only used if the verification endpoint returns them
+			Ok(verification) if verification.external => {
+				for player in &mut players {
+					player.rating = verification.rating;
+				}
+			}
+			Ok(_) => {}
+			Err(err) => {
+				// Reduces verbosity
+				let err_branch = |err_code| async move {
+					fail(ctx, namespace_id, query_id, err_code, true).await?;
+					complete_request(ctx.chirp(), analytics_events).await
+				};
+
+				let res = if err.is(formatted_error::code::MATCHMAKER_FIND_DISABLED) {
+					err_branch(ErrorCode::FindDisabled).await
+				} else if err.is(formatted_error::code::MATCHMAKER_JOIN_DISABLED) {
+					err_branch(ErrorCode::JoinDisabled).await
+				} else if err.is(formatted_error::code::MATCHMAKER_REGISTRATION_REQUIRED) {
+					err_branch(ErrorCode::RegistrationRequired).await
+				} else if err.is(formatted_error::code::MATCHMAKER_IDENTITY_REQUIRED) {
+					err_branch(ErrorCode::IdentityRequired).await
+				} else if err.is(formatted_error::code::MATCHMAKER_VERIFICATION_FAILED) {
+					err_branch(ErrorCode::VerificationFailed).await
+				} else if err.is(formatted_error::code::MATCHMAKER_VERIFICATION_REQUEST_FAILED) {
+					err_branch(ErrorCode::VerificationRequestFailed).await
+				} else {
+					Err(err)
+				};
+
+				return res;
+			}
 		}
 	}
 
+	// Only skill matchmaking depends on how long the players have been queueing
+	let queue_key = if matches!(query, Query::LobbyGroup(_))
+		&& lobby_group_config
+			.lobby_groups
+			.iter()
+			.any(|lg| lg.skill_matchmaking.is_some())
+	{
+		queue::key(ctx, namespace_id, &players)
+	} else {
+		None
+	};
+	let queue_start_ts = queue::start_ts(ctx, &mut redis_mm, queue_key.as_deref()).await?;
+
 	// Find the lobby to join
 	let auto_create_lobby_id = Uuid::new_v4();
 	let find::FindOutput {
@@ -238,6 +269,7 @@ async fn worker(ctx: &OperationContext<mm::msg::lobby_find::Message>) -> GlobalR
 			auto_create_lobby_id,
 			tags: &ctx.tags,
 			dynamic_max_players: ctx.dynamic_max_players,
+			queue_start_ts,
 		},
 	)
 	.await?
@@ -248,6 +280,8 @@ async fn worker(ctx: &OperationContext<mm::msg::lobby_find::Message>) -> GlobalR
 	};
 	let auto_create_lobby = lobby_id == auto_create_lobby_id;
 
+	queue::reset(&mut redis_mm, queue_key.as_deref()).await?;
+
 	// Record analytics events
 	analytics_events.push(analytics::msg::event_create::Event {
 		event_id: Some(Uuid::new_v4().into()),
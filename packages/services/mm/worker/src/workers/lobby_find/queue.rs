@@ -0,0 +1,69 @@
+use chirp_worker::prelude::*;
+use proto::backend::pkg::*;
+use redis::AsyncCommands;
+
+/// How long a client can go without retrying a find before its queue start is reset.
+const QUEUE_IDLE_TTL: i64 = util::duration::minutes(1);
+
+/// Key the queue start of the players is tracked under. Players are identified by their user or by
+/// their remote address for guests.
+pub fn key(
+	ctx: &OperationContext<mm::msg::lobby_find::Message>,
+	namespace_id: Uuid,
+	players: &[super::Player],
+) -> Option<String> {
+	let identity = if let Some(user_id) = ctx.user_id {
+		user_id.as_uuid().to_string()
+	} else {
+		players
+			.iter()
+			.find_map(|p| p.client_info.as_ref()?.remote_address.clone())?
+	};
+
+	Some(util_mm::key::ns_queue_start_ts(namespace_id, &identity))
+}
+
+/// Resolves when the players started queueing.
+///
+/// This is tracked by the server instead of trusting the client so it can't skip ahead to a wider
+/// skill window. The start is kept as long as the client keeps retrying.
+#[tracing::instrument(skip(redis_mm))]
+pub async fn start_ts(
+	ctx: &OperationContext<mm::msg::lobby_find::Message>,
+	redis_mm: &mut RedisPool,
+	key: Option<&str>,
+) -> GlobalResult<i64> {
+	// Set by internal callers
+	if ctx.queue_start_ts.is_some() {
+		return Ok(util_mm::skill::queue_start_ts(ctx.queue_start_ts, ctx.ts()));
+	}
+
+	let Some(key) = key else {
+		return Ok(ctx.ts());
+	};
+
+	let (start_ts,) = redis::pipe()
+		.atomic()
+		.cmd("SET")
+		.arg(key)
+		.arg(ctx.ts())
+		.arg("NX")
+		.ignore()
+		.get(key)
+		.pexpire(key, QUEUE_IDLE_TTL as usize)
+		.ignore()
+		.query_async::<_, (i64,)>(redis_mm)
+		.await?;
+
+	Ok(util_mm::skill::queue_start_ts(Some(start_ts), ctx.ts()))
+}
+
+/// Resets the queue start once the players found a lobby.
+#[tracing::instrument(skip(redis_mm))]
+pub async fn reset(redis_mm: &mut RedisPool, key: Option<&str>) -> GlobalResult<()> {
+	if let Some(key) = key {
+		redis_mm.del::<_, ()>(key).await?;
+	}
+
+	Ok(())
+}
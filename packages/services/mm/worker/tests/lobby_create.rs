@@ -331,6 +331,7 @@ async fn setup(ctx: &TestCtx) -> Setup {
 				listable: true,
 				taggable: false,
 				allow_dynamic_max_players: false,
+				skill_matchmaking: None,
 
 				runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 					build_id: build_res.build_id,
@@ -393,6 +394,7 @@ async fn setup(ctx: &TestCtx) -> Setup {
 				listable: true,
 				taggable: false,
 				allow_dynamic_max_players: false,
+				skill_matchmaking: None,
 
 				runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 					build_id: build_res.build_id,
@@ -303,6 +303,7 @@ async fn max_players_per_client(ctx: TestCtx) {
 						user_agent: Some("Test".into()),
 						remote_address: Some(fake_ip.to_string()),
 					}),
+					rating: None,
 				}],
 				query: mm::msg::lobby_find::message::Query::Direct(
 					backend::matchmaker::query::Direct {
@@ -1060,6 +1061,176 @@ async fn dynamic_max_players(ctx: TestCtx) {
 	);
 }
 
+#[worker_test]
+async fn skill_closest_lobby(ctx: TestCtx) {
+	if !util::feature::job_run() {
+		return;
+	}
+
+	let lobby_group = create_lobby_group_with_skill(
+		&ctx,
+		None,
+		Some(backend::matchmaker::SkillMatchmaking {
+			initial_window: 100.0,
+			window_growth_per_second: 10.0,
+			max_window: 1000.0,
+			max_spread: 1000.0,
+		}),
+	)
+	.await;
+	let query = |auto_create: bool| {
+		mm::msg::lobby_find::message::Query::LobbyGroup(backend::matchmaker::query::LobbyGroup {
+			lobby_group_ids: vec![lobby_group.lobby_group_id.into()],
+			region_ids: vec![lobby_group.region_id.into()],
+			auto_create: auto_create.then(|| backend::matchmaker::query::AutoCreate {
+				lobby_group_id: Some(lobby_group.lobby_group_id.into()),
+				region_id: Some(lobby_group.region_id.into()),
+			}),
+		})
+	};
+
+	let find_res1 = find(
+		&ctx,
+		FindRequest {
+			namespace_id: lobby_group.namespace_id,
+			players: vec![gen_rated_player(1000.0)],
+			query: query(true),
+			user_id: None,
+		},
+	)
+	.await
+	.unwrap();
+
+	// Outside of the initial window, creates a new lobby
+	let find_res2 = find(
+		&ctx,
+		FindRequest {
+			namespace_id: lobby_group.namespace_id,
+			players: vec![gen_rated_player(2000.0)],
+			query: query(true),
+			user_id: None,
+		},
+	)
+	.await
+	.unwrap();
+	assert_ne!(
+		find_res1.lobby_id, find_res2.lobby_id,
+		"joined lobby outside of window"
+	);
+
+	// Both lobbies are within the max window, joins the closest one
+	let find_res3 = find_with_queue_start_ts(
+		&ctx,
+		FindRequest {
+			namespace_id: lobby_group.namespace_id,
+			players: vec![gen_rated_player(1600.0)],
+			query: query(false),
+			user_id: None,
+		},
+		util::timestamp::now() - util::duration::minutes(5),
+	)
+	.await
+	.unwrap();
+	assert_eq!(
+		find_res2.lobby_id, find_res3.lobby_id,
+		"did not join closest lobby"
+	);
+}
+
+#[worker_test]
+async fn skill_window_widens(ctx: TestCtx) {
+	if !util::feature::job_run() {
+		return;
+	}
+
+	let lobby_group = create_lobby_group_with_skill(
+		&ctx,
+		None,
+		Some(backend::matchmaker::SkillMatchmaking {
+			initial_window: 100.0,
+			window_growth_per_second: 10.0,
+			max_window: 1000.0,
+			max_spread: 1000.0,
+		}),
+	)
+	.await;
+	let query = |auto_create: bool| {
+		mm::msg::lobby_find::message::Query::LobbyGroup(backend::matchmaker::query::LobbyGroup {
+			lobby_group_ids: vec![lobby_group.lobby_group_id.into()],
+			region_ids: vec![lobby_group.region_id.into()],
+			auto_create: auto_create.then(|| backend::matchmaker::query::AutoCreate {
+				lobby_group_id: Some(lobby_group.lobby_group_id.into()),
+				region_id: Some(lobby_group.region_id.into()),
+			}),
+		})
+	};
+
+	let find_res = find(
+		&ctx,
+		FindRequest {
+			namespace_id: lobby_group.namespace_id,
+			players: vec![gen_rated_player(1000.0)],
+			query: query(true),
+			user_id: None,
+		},
+	)
+	.await
+	.unwrap();
+
+	// Just started queueing, outside of the window
+	let err = find(
+		&ctx,
+		FindRequest {
+			namespace_id: lobby_group.namespace_id,
+			players: vec![gen_rated_player(1500.0)],
+			query: query(false),
+			user_id: None,
+		},
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		backend::matchmaker::lobby_find::ErrorCode::NoAvailableLobbies as i32,
+		err.error_code
+	);
+
+	// Timestamps in the future don't skip the initial window
+	let err = find_with_queue_start_ts(
+		&ctx,
+		FindRequest {
+			namespace_id: lobby_group.namespace_id,
+			players: vec![gen_rated_player(1500.0)],
+			query: query(false),
+			user_id: None,
+		},
+		util::timestamp::now() + util::duration::minutes(5),
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(
+		backend::matchmaker::lobby_find::ErrorCode::NoAvailableLobbies as i32,
+		err.error_code
+	);
+
+	// Window has widened after queueing for 60 seconds
+	let find_res2 = find_with_queue_start_ts(
+		&ctx,
+		FindRequest {
+			namespace_id: lobby_group.namespace_id,
+			players: vec![gen_rated_player(1500.0)],
+			query: query(false),
+			user_id: None,
+		},
+		util::timestamp::now() - util::duration::seconds(60),
+	)
+	.await
+	.unwrap();
+	assert_eq!(
+		find_res.lobby_id, find_res2.lobby_id,
+		"window did not widen"
+	);
+}
+
 fn gen_players(count: usize) -> Vec<mm::msg::lobby_find::Player> {
 	let mut players = Vec::new();
 	for _ in 0..count {
@@ -1070,11 +1241,19 @@ fn gen_players(count: usize) -> Vec<mm::msg::lobby_find::Player> {
 				user_agent: Some("Test".into()),
 				remote_address: Some(util::faker::ip_addr_v4().to_string()),
 			}),
+			rating: None,
 		});
 	}
 	players
 }
 
+fn gen_rated_player(rating: f64) -> mm::msg::lobby_find::Player {
+	mm::msg::lobby_find::Player {
+		rating: Some(rating),
+		..gen_players(1).pop().unwrap()
+	}
+}
+
 async fn gen_verification_lobby(
 	ctx: &TestCtx,
 	identity_requirement: backend::matchmaker::IdentityRequirement,
@@ -1118,6 +1297,7 @@ async fn gen_verification_lobby(
 				listable: true,
 				taggable: false,
 				allow_dynamic_max_players: false,
+				skill_matchmaking: None,
 
 				runtime: Some(
 					backend::matchmaker::lobby_runtime::Docker {
@@ -1204,6 +1384,7 @@ async fn gen_disabled_lobby(ctx: &TestCtx) -> (Uuid, Uuid) {
 				listable: true,
 				taggable: false,
 				allow_dynamic_max_players: false,
+				skill_matchmaking: None,
 
 				runtime: Some(
 					backend::matchmaker::lobby_runtime::Docker {
@@ -1261,6 +1442,14 @@ struct TestLobbyGroup {
 }
 
 async fn create_lobby_group(ctx: &TestCtx, image: Option<backend::faker::Image>) -> TestLobbyGroup {
+	create_lobby_group_with_skill(ctx, image, None).await
+}
+
+async fn create_lobby_group_with_skill(
+	ctx: &TestCtx,
+	image: Option<backend::faker::Image>,
+	skill_matchmaking: Option<backend::matchmaker::SkillMatchmaking>,
+) -> TestLobbyGroup {
 	let region_res = op!([ctx] faker_region {}).await.unwrap();
 	let region_id = region_res.region_id.as_ref().unwrap().as_uuid();
 
@@ -1295,6 +1484,7 @@ async fn create_lobby_group(ctx: &TestCtx, image: Option<backend::faker::Image>)
 				listable: true,
 				taggable: false,
 				allow_dynamic_max_players: false,
+				skill_matchmaking: skill_matchmaking.clone(),
 
 				runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 					// We can't use `curlimages/curl` here because it doesn't allow for
@@ -1331,6 +1521,7 @@ async fn create_lobby_group(ctx: &TestCtx, image: Option<backend::faker::Image>)
 				listable: true,
 				taggable: false,
 				allow_dynamic_max_players: false,
+				skill_matchmaking,
 
 				runtime: Some(backend::matchmaker::lobby_runtime::Docker {
 					build_id: build_res.build_id,
@@ -1415,6 +1606,7 @@ async fn find(
 		bypass_verification: false,
 		tags: HashMap::new(),
 		dynamic_max_players: None,
+		queue_start_ts: None,
 
 		debug: None,
 	})
@@ -1444,6 +1636,7 @@ async fn find_with_verification(
 		bypass_verification: bypass_verification,
 		tags: HashMap::new(),
 		dynamic_max_players: None,
+		queue_start_ts: None,
 
 		debug: None,
 	})
@@ -1472,6 +1665,7 @@ async fn find_with_tags(
 		bypass_verification: false,
 		tags: tags,
 		dynamic_max_players: None,
+		queue_start_ts: None,
 
 		debug: None,
 	})
@@ -1500,6 +1694,36 @@ async fn find_with_dynamic_max_players(
 		bypass_verification: false,
 		tags: HashMap::new(),
 		dynamic_max_players: Some(dynamic_max_players),
+		queue_start_ts: None,
+
+		debug: None,
+	})
+	.await
+	.unwrap()
+}
+
+async fn find_with_queue_start_ts(
+	ctx: &TestCtx,
+	req: FindRequest,
+	queue_start_ts: i64,
+) -> Result<
+	chirp_client::message::ReceivedMessage<mm::msg::lobby_find_complete::Message>,
+	chirp_client::message::ReceivedMessage<mm::msg::lobby_find_fail::Message>,
+> {
+	let query_id = Uuid::new_v4();
+	msg!([ctx] @notrace mm::msg::lobby_find(req.namespace_id, query_id) -> Result<mm::msg::lobby_find_complete, mm::msg::lobby_find_fail> {
+		namespace_id: Some(req.namespace_id.into()),
+		query_id: Some(query_id.into()),
+		join_kind: backend::matchmaker::query::JoinKind::Normal as i32,
+		players: req.players,
+		query: Some(req.query),
+
+		user_id: req.user_id.map(Into::into),
+		verification_data_json: None,
+		bypass_verification: false,
+		tags: HashMap::new(),
+		dynamic_max_players: None,
+		queue_start_ts: Some(queue_start_ts),
 
 		debug: None,
 	})
@@ -25,6 +25,7 @@ async fn player_register(ctx: TestCtx) {
 			player_id: Some(player_id.into()),
 			token_session_id: Some(Uuid::new_v4().into()),
 			client_info:None,
+			rating: None,
 		}],
 		query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
 			lobby_id: Some(lobby_id.into()),
@@ -103,6 +104,7 @@ async fn wrong_lobby(ctx: TestCtx) {
 			player_id: Some(player_id.into()),
 			token_session_id: Some(Uuid::new_v4().into()),
 			client_info:None,
+			rating: None,
 		}],
 		query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
 			lobby_id: Some(lobby_id.into()),
@@ -33,6 +33,7 @@ async fn player_remove(ctx: TestCtx) {
 				player_id: Some(player_id.into()),
 				token_session_id: Some(Uuid::new_v4().into()),
 				client_info:None,
+				rating: None,
 			})
 			.collect(),
 		query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
@@ -124,6 +125,7 @@ async fn player_remove_redis_fallback(ctx: TestCtx) {
 				player_id: Some(player_id.into()),
 				token_session_id: Some(Uuid::new_v4().into()),
 				client_info:None,
+				rating: None,
 			})
 			.collect(),
 		query: Some(mm::msg::lobby_find::message::Query::Direct(backend::matchmaker::query::Direct {
@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS actor_metrics (
+    actor_id UUID,
+    client_id UUID,
+    owner_id UUID, -- pegboard::protocol::ActorOwner, i.e. the dynamic server id
+    start_ts DateTime64 (3),
+    end_ts DateTime64 (3),
+    cpu_avg UInt64, -- Millicores
+    cpu_max UInt64, -- Millicores
+    memory_avg UInt64, -- Bytes
+    memory_max UInt64, -- Bytes
+    network_rx_bytes Nullable (UInt64),
+    network_tx_bytes Nullable (UInt64),
+    disk_read_bytes Nullable (UInt64),
+    disk_write_bytes Nullable (UInt64)
+) ENGINE = ReplicatedMergeTree ()
+PARTITION BY
+    toStartOfDay (end_ts)
+ORDER BY (
+    owner_id,
+    toUnixTimestamp (end_ts)
+)
+TTL toDate (end_ts + toIntervalDay (30))
+SETTINGS index_granularity = 8192, ttl_only_drop_parts = 1;
@@ -0,0 +1,102 @@
+use chirp_workflow::prelude::*;
+
+use crate::protocol;
+
+#[derive(clickhouse::Row, serde::Deserialize)]
+struct MetricsRow {
+	#[serde(with = "clickhouse::serde::uuid")]
+	actor_id: Uuid,
+	start_ts: i64,
+	end_ts: i64,
+	cpu_avg: u64,
+	cpu_max: u64,
+	memory_avg: u64,
+	memory_max: u64,
+	network_rx_bytes: Option<u64>,
+	network_tx_bytes: Option<u64>,
+	disk_read_bytes: Option<u64>,
+	disk_write_bytes: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct Input {
+	/// Id of the actor's owner (i.e. the dynamic server id).
+	pub owner_id: Uuid,
+	/// Timestamp in milliseconds.
+	pub start_ts: i64,
+	/// Timestamp in milliseconds.
+	pub end_ts: i64,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	/// Ordered by `end_ts`, oldest first.
+	pub metrics: Vec<Window>,
+}
+
+#[derive(Debug)]
+pub struct Window {
+	pub actor_id: Uuid,
+	pub metrics: protocol::ActorMetrics,
+}
+
+#[operation]
+pub async fn pegboard_actor_metrics_get(ctx: &OperationCtx, input: &Input) -> GlobalResult<Output> {
+	// Metrics are only stored if ClickHouse is enabled
+	if ctx.config().server()?.clickhouse.is_none() {
+		return Ok(Output {
+			metrics: Vec::new(),
+		});
+	}
+
+	let clickhouse = ctx.clickhouse().await?;
+
+	let mut cursor = clickhouse
+		.query(indoc!(
+			"
+			SELECT
+				actor_id,
+				toUnixTimestamp64Milli(start_ts) AS start_ts,
+				toUnixTimestamp64Milli(end_ts) AS end_ts,
+				cpu_avg,
+				cpu_max,
+				memory_avg,
+				memory_max,
+				network_rx_bytes,
+				network_tx_bytes,
+				disk_read_bytes,
+				disk_write_bytes
+			FROM db_pegboard_analytics.actor_metrics
+			WHERE
+				owner_id = ? AND
+				end_ts > fromUnixTimestamp64Milli(?) AND
+				start_ts < fromUnixTimestamp64Milli(?)
+			ORDER BY end_ts ASC
+			"
+		))
+		.bind(input.owner_id)
+		.bind(input.start_ts)
+		.bind(input.end_ts)
+		.fetch::<MetricsRow>()?;
+
+	let mut metrics = Vec::new();
+	while let Some(row) = cursor.next().await? {
+		metrics.push(Window {
+			actor_id: row.actor_id,
+			metrics: protocol::ActorMetrics {
+				start_ts: row.start_ts,
+				end_ts: row.end_ts,
+				cpu_avg: row.cpu_avg,
+				cpu_max: row.cpu_max,
+				memory_avg: row.memory_avg,
+				memory_max: row.memory_max,
+				network_rx_bytes: row.network_rx_bytes,
+				network_tx_bytes: row.network_tx_bytes,
+				disk_read_bytes: row.disk_read_bytes,
+				disk_write_bytes: row.disk_write_bytes,
+			},
+		});
+	}
+
+	Ok(Output { metrics })
+}
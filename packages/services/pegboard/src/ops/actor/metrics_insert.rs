@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use chirp_workflow::prelude::*;
+
+use crate::protocol;
+
+#[derive(clickhouse::Row, Serialize)]
+struct MetricsRow {
+	#[serde(with = "clickhouse::serde::uuid")]
+	actor_id: Uuid,
+	#[serde(with = "clickhouse::serde::uuid")]
+	client_id: Uuid,
+	#[serde(with = "clickhouse::serde::uuid")]
+	owner_id: Uuid,
+	start_ts: i64,
+	end_ts: i64,
+	cpu_avg: u64,
+	cpu_max: u64,
+	memory_avg: u64,
+	memory_max: u64,
+	network_rx_bytes: Option<u64>,
+	network_tx_bytes: Option<u64>,
+	disk_read_bytes: Option<u64>,
+	disk_write_bytes: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct Input {
+	pub client_id: Uuid,
+	pub metrics: Vec<protocol::ActorMetricsEntry>,
+}
+
+/// Writes actor metrics windows reported by a client to ClickHouse. Windows of unknown actors and actors
+/// not running on the client are dropped.
+#[operation]
+pub async fn pegboard_actor_metrics_insert(ctx: &OperationCtx, input: &Input) -> GlobalResult<()> {
+	// Metrics are only stored if ClickHouse is enabled
+	if ctx.config().server()?.clickhouse.is_none() || input.metrics.is_empty() {
+		return Ok(());
+	}
+
+	let owners = sql_fetch_all!(
+		[ctx, (Uuid, sqlx::types::Json<protocol::ActorOwner>)]
+		"
+		SELECT actor_id, config->'owner'
+		FROM db_pegboard.actors
+		WHERE
+			actor_id = ANY($1) AND
+			client_id = $2
+		",
+		input.metrics.iter().map(|x| x.actor_id).collect::<Vec<_>>(),
+		input.client_id,
+	)
+	.await?
+	.into_iter()
+	.map(|(actor_id, owner)| match owner.0 {
+		protocol::ActorOwner::DynamicServer { server_id } => (actor_id, server_id),
+	})
+	.collect::<HashMap<_, _>>();
+
+	let clickhouse = ctx.clickhouse().await?;
+	let mut insert = clickhouse.insert("db_pegboard_analytics.actor_metrics")?;
+
+	for entry in &input.metrics {
+		let Some(owner_id) = owners.get(&entry.actor_id) else {
+			tracing::warn!(actor_id=?entry.actor_id, client_id=?input.client_id, "metrics for unknown actor");
+			continue;
+		};
+
+		let metrics = &entry.metrics;
+		insert
+			.write(&MetricsRow {
+				actor_id: entry.actor_id,
+				client_id: input.client_id,
+				owner_id: *owner_id,
+				start_ts: metrics.start_ts,
+				end_ts: metrics.end_ts,
+				cpu_avg: metrics.cpu_avg,
+				cpu_max: metrics.cpu_max,
+				memory_avg: metrics.memory_avg,
+				memory_max: metrics.memory_max,
+				network_rx_bytes: metrics.network_rx_bytes,
+				network_tx_bytes: metrics.network_tx_bytes,
+				disk_read_bytes: metrics.disk_read_bytes,
+				disk_write_bytes: metrics.disk_write_bytes,
+			})
+			.await?;
+	}
+
+	insert.end().await?;
+
+	Ok(())
+}
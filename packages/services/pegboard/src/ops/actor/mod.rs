@@ -0,0 +1,2 @@
+pub mod metrics_get;
+pub mod metrics_insert;
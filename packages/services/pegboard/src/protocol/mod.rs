@@ -0,0 +1,591 @@
+//! Packets sent between pegboard clients and the pegboard ws server.
+//!
+//! The types in this module are the in-memory model and are never encoded directly. Every protocol
+//! version has a frozen wire schema in its own module:
+//!
+//! - `v2`: JSON
+//! - `v3`: bincode
+//! - `v4`: bincode, adds resource limits and Zstd images
+//! - `v5`: bincode, adds actor metrics events
+//! - `v6`: bincode, adds persistent volumes
+//! - `v7`: bincode, adds actor metrics packets and secrets
+//!
+//! Packets are converted from the model to the latest schema and then down converted to the negotiated
+//! version (and the reverse when decoding). To add a version, copy the latest schema into a new module,
+//! write up/down converters between it and the previous version, move the model conversions to it and
+//! add golden files to `tests/protocol`.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::FromRepr;
+use uuid::Uuid;
+
+#[cfg(feature = "chirp")]
+use chirp_workflow::prelude::*;
+
+// Reexport for ease of use in pegboard manager
+pub use ::util::serde::{HashableMap, Raw};
+
+mod v2;
+mod v3;
+mod v4;
+mod v5;
+mod v6;
+mod v7;
+
+/// Latest protocol version.
+pub const PROTOCOL_VERSION: u16 = 7;
+/// Oldest protocol version still supported.
+pub const MIN_PROTOCOL_VERSION: u16 = 2;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PegboardProtocolError {
+	#[error("ser/de error: {0}")]
+	Serde(#[from] serde_json::Error),
+	#[error("bincode error: {0}")]
+	Bincode(#[from] bincode::Error),
+	#[error("unsupported protocol version: {0}")]
+	UnsupportedVersion(u16),
+	#[error("packet not supported by protocol version {0}")]
+	UnsupportedPacket(u16),
+	#[error("invalid client flavor: {0}")]
+	InvalidClientFlavor(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToClient {
+	Init {
+		last_event_idx: i64,
+	},
+	Commands(Vec<CommandWrapper>),
+	PrewarmImage {
+		image_id: Uuid,
+		image_artifact_url_stub: String,
+		/// Hex encoded SHA-256 digest of the image artifact.
+		#[serde(default)]
+		image_digest: Option<String>,
+	},
+}
+
+impl ToClient {
+	pub fn serialize(&self, protocol_version: u16) -> Result<Vec<u8>, PegboardProtocolError> {
+		match protocol_version {
+			2 => v2::encode(&v2::ToClient::try_from(v3::ToClient::try_from(
+				v4::ToClient::from(v5::ToClient::try_from(v6::ToClient::try_from(
+					v7::ToClient::try_from(self)?,
+				)?)?),
+			)?)?),
+			3 => v3::encode(&v3::ToClient::try_from(v4::ToClient::from(
+				v5::ToClient::try_from(v6::ToClient::try_from(v7::ToClient::try_from(self)?)?)?,
+			))?),
+			4 => v4::encode(&v4::ToClient::from(v5::ToClient::try_from(
+				v6::ToClient::try_from(v7::ToClient::try_from(self)?)?,
+			)?)),
+			5 => v5::encode(&v5::ToClient::try_from(v6::ToClient::try_from(
+				v7::ToClient::try_from(self)?,
+			)?)?),
+			6 => v6::encode(&v6::ToClient::try_from(v7::ToClient::try_from(self)?)?),
+			7 => v7::encode(&v7::ToClient::try_from(self)?),
+			_ => Err(PegboardProtocolError::UnsupportedVersion(protocol_version)),
+		}
+	}
+
+	pub fn deserialize(protocol_version: u16, buf: &[u8]) -> Result<Self, PegboardProtocolError> {
+		let packet: v7::ToClient = match protocol_version {
+			2 => v6::ToClient::from(v5::ToClient::from(v4::ToClient::from(
+				v3::ToClient::try_from(v2::decode::<v2::ToClient>(buf)?)?,
+			)))
+			.into(),
+			3 => v6::ToClient::from(v5::ToClient::from(v4::ToClient::from(v3::decode::<
+				v3::ToClient,
+			>(buf)?)))
+			.into(),
+			4 => v6::ToClient::from(v5::ToClient::from(v4::decode::<v4::ToClient>(buf)?)).into(),
+			5 => v6::ToClient::from(v5::decode::<v5::ToClient>(buf)?).into(),
+			6 => v6::decode::<v6::ToClient>(buf)?.into(),
+			7 => v7::decode::<v7::ToClient>(buf)?,
+			_ => return Err(PegboardProtocolError::UnsupportedVersion(protocol_version)),
+		};
+
+		packet.try_into()
+	}
+}
+
+#[cfg_attr(feature = "chirp", signal("pegboard_forward_to_server"))]
+#[cfg_attr(not(feature = "chirp"), derive(Serialize, Deserialize))]
+#[derive(Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ToServer {
+	Init {
+		last_command_idx: i64,
+		config: crate::client_config::ClientConfig,
+		system: crate::system_info::SystemInfo,
+	},
+	Events(Vec<EventWrapper>),
+	/// Usage metrics of actors. Unlike events these are not persisted or acknowledged, a lost packet only
+	/// loses one sampling window.
+	ActorMetrics(Vec<ActorMetricsEntry>),
+}
+
+impl ToServer {
+	pub fn serialize(&self, protocol_version: u16) -> Result<Vec<u8>, PegboardProtocolError> {
+		match protocol_version {
+			2 => v2::encode(&v2::ToServer::try_from(v3::ToServer::from(
+				v4::ToServer::from(v5::ToServer::try_from(v6::ToServer::try_from(
+					v7::ToServer::try_from(self)?,
+				)?)?),
+			))?),
+			3 => v3::encode(&v3::ToServer::from(v4::ToServer::from(
+				v5::ToServer::try_from(v6::ToServer::try_from(v7::ToServer::try_from(self)?)?)?,
+			))),
+			4 => v4::encode(&v4::ToServer::from(v5::ToServer::try_from(
+				v6::ToServer::try_from(v7::ToServer::try_from(self)?)?,
+			)?)),
+			5 => v5::encode(&v5::ToServer::try_from(v6::ToServer::try_from(
+				v7::ToServer::try_from(self)?,
+			)?)?),
+			6 => v6::encode(&v6::ToServer::try_from(v7::ToServer::try_from(self)?)?),
+			7 => v7::encode(&v7::ToServer::try_from(self)?),
+			_ => Err(PegboardProtocolError::UnsupportedVersion(protocol_version)),
+		}
+	}
+
+	pub fn deserialize(protocol_version: u16, buf: &[u8]) -> Result<Self, PegboardProtocolError> {
+		let packet: v7::ToServer = match protocol_version {
+			2 => v6::ToServer::from(v5::ToServer::from(v4::ToServer::from(
+				v3::ToServer::try_from(v2::decode::<v2::ToServer>(buf)?)?,
+			)))
+			.into(),
+			3 => v6::ToServer::from(v5::ToServer::from(v4::ToServer::from(v3::decode::<
+				v3::ToServer,
+			>(buf)?)))
+			.into(),
+			4 => v6::ToServer::from(v5::ToServer::from(v4::decode::<v4::ToServer>(buf)?)).into(),
+			5 => v6::ToServer::from(v5::decode::<v5::ToServer>(buf)?).into(),
+			6 => v6::decode::<v6::ToServer>(buf)?.into(),
+			7 => v7::decode::<v7::ToServer>(buf)?,
+			_ => return Err(PegboardProtocolError::UnsupportedVersion(protocol_version)),
+		};
+
+		packet.try_into()
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandWrapper {
+	pub index: i64,
+	pub inner: Raw<Command>,
+	/// W3C trace context of the workflow that issued the command.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub traceparent: Option<String>,
+}
+
+#[cfg_attr(feature = "chirp", signal("pegboard_client_command"))]
+#[cfg_attr(not(feature = "chirp"), derive(Serialize, Deserialize))]
+#[derive(Debug, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+	StartActor {
+		actor_id: Uuid,
+		config: Box<ActorConfig>,
+	},
+	SignalActor {
+		actor_id: Uuid,
+		// See nix::sys::signal::Signal
+		signal: i32,
+		/// Whether or not to delete related data (KV store).
+		persist_storage: bool,
+		/// Whether or not to publish any state update signals after this command.
+		ignore_future_state: bool,
+	},
+	/// Uploads a snapshot of the client's copy of a volume. Answered with
+	/// `Event::VolumeSnapshotUpdate`.
+	SnapshotVolume {
+		volume_id: Uuid,
+		snapshot_id: Uuid,
+		/// Presigned URL to upload the compressed archive to.
+		upload_url: String,
+	},
+	/// Deletes the client's copy of a volume.
+	DeleteVolume { volume_id: Uuid },
+}
+
+#[derive(Serialize, Deserialize, Clone, Hash)]
+pub struct ActorConfig {
+	pub image: Image,
+	pub root_user_enabled: bool,
+	pub resources: Resources,
+	/// Values are omitted from `Debug`.
+	pub env: HashableMap<String, String>,
+	/// Env var name -> secret name. Only names are persisted, the server replaces them with the decrypted
+	/// values right before the command is sent to the client. Clients must not persist the values.
+	#[serde(default)]
+	pub secrets: HashableMap<String, String>,
+	pub ports: HashableMap<String, Port>,
+	pub network_mode: NetworkMode,
+	pub owner: ActorOwner,
+	pub metadata: Raw<ActorMetadata>,
+	/// Only supported by container actors.
+	#[serde(default)]
+	pub volumes: Vec<Volume>,
+}
+
+impl std::fmt::Debug for ActorConfig {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ActorConfig")
+			.field("image", &self.image)
+			.field("root_user_enabled", &self.root_user_enabled)
+			.field("resources", &self.resources)
+			.field("env", &self.env.keys().collect::<Vec<_>>())
+			.field("secrets", &self.secrets.keys().collect::<Vec<_>>())
+			.field("ports", &self.ports)
+			.field("network_mode", &self.network_mode)
+			.field("owner", &self.owner)
+			.field("metadata", &self.metadata)
+			.field("volumes", &self.volumes)
+			.finish()
+	}
+}
+
+/// Persistent volume mounted in to a container actor. Stored on the client outside of the actor's
+/// directory so it outlives the actor.
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct Volume {
+	pub volume_id: Uuid,
+	/// Absolute path in the container to mount the volume at.
+	pub path: String,
+	/// MiB.
+	pub size: u64,
+	/// Latest snapshot of the volume. Restored if the client's copy was not created from or snapshotted to
+	/// it. Unset if the volume has never been snapshotted.
+	pub snapshot: Option<VolumeSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct VolumeSnapshot {
+	pub snapshot_id: Uuid,
+	/// Presigned URL to download the compressed archive from.
+	pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct Image {
+	pub id: Uuid,
+	/// Appended to the ATS url to fetch the image.
+	pub artifact_url_stub: String,
+	/// Direct S3 url to download the image from without ATS.
+	pub fallback_artifact_url: Option<String>,
+	pub kind: ImageKind,
+	pub compression: ImageCompression,
+	/// Hex encoded SHA-256 digest of the image artifact. Verified after downloading.
+	#[serde(default)]
+	pub digest: Option<String>,
+	/// Dictionary required to decompress the image. Only set for `ImageCompression::Zstd`.
+	#[serde(default)]
+	pub compression_dictionary: Option<ImageArtifact>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct ImageArtifact {
+	/// Appended to the ATS url to fetch the artifact.
+	pub artifact_url_stub: String,
+	/// Direct S3 url to download the artifact from without ATS.
+	pub fallback_artifact_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageKind {
+	DockerImage,
+	OciBundle,
+	JavaScript,
+}
+
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageCompression {
+	None,
+	Lz4,
+	Zstd,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct Port {
+	/// Must be set with bridge networking, unset with host networking.
+	pub target: Option<u16>,
+	pub protocol: TransportProtocol,
+	pub routing: PortRouting,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PortRouting {
+	GameGuard,
+	Host,
+}
+
+#[derive(
+	Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, FromRepr,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportProtocol {
+	Tcp = 0,
+	Udp = 1,
+}
+
+impl std::fmt::Display for TransportProtocol {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			TransportProtocol::Tcp => write!(f, "tcp"),
+			TransportProtocol::Udp => write!(f, "udp"),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkMode {
+	Bridge,
+	Host,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct Resources {
+	/// Millicore (1/1000 of a core).
+	pub cpu: u64,
+	/// Bytes.
+	pub memory: u64,
+	/// Bytes.
+	pub memory_max: u64,
+	/// MiB.
+	pub disk: u32,
+	/// Max number of processes and threads. Unset for no limit.
+	#[serde(default)]
+	pub pids: Option<u64>,
+	/// Relative block I/O weight. Must be [10, 1000]. Unset for the default weight.
+	#[serde(default)]
+	pub io_weight: Option<u16>,
+	/// Bytes per second read from the actor's disk. Unset for no limit.
+	#[serde(default)]
+	pub io_read_bps: Option<u64>,
+	/// Bytes per second written to the actor's disk. Unset for no limit.
+	#[serde(default)]
+	pub io_write_bps: Option<u64>,
+	/// Max open file descriptors. Defaults to 1024.
+	#[serde(default)]
+	pub nofile: Option<u64>,
+	/// Bytes per second sent from the actor. Only enforced with bridge networking. Unset for no limit.
+	#[serde(default)]
+	pub egress_bps: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorOwner {
+	DynamicServer { server_id: Uuid },
+}
+
+impl ActorOwner {
+	pub fn env(&self) -> Vec<(&str, String)> {
+		match self {
+			ActorOwner::DynamicServer { server_id } => {
+				vec![
+					("OWNER", "dynamic_server".to_string()),
+					("SERVER_ID", server_id.to_string()),
+				]
+			}
+		}
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct ActorMetadata {
+	pub actor: ActorMetadataActor,
+	pub project: ActorMetadataProject,
+	pub environment: ActorMetadataEnvironment,
+	pub datacenter: ActorMetadataDatacenter,
+	pub cluster: ActorMetadataCluster,
+	pub build: ActorMetadataBuild,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct ActorMetadataActor {
+	pub actor_id: Uuid,
+	pub tags: HashableMap<String, String>,
+	pub create_ts: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct ActorMetadataProject {
+	pub project_id: Uuid,
+	pub slug: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct ActorMetadataEnvironment {
+	pub env_id: Uuid,
+	pub slug: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct ActorMetadataDatacenter {
+	pub name_id: String,
+	pub display_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct ActorMetadataCluster {
+	pub cluster_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash)]
+pub struct ActorMetadataBuild {
+	pub build_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct EventWrapper {
+	pub index: i64,
+	pub inner: Raw<Event>,
+	/// W3C trace context of the command that caused the event.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+	ActorStateUpdate {
+		actor_id: Uuid,
+		state: ActorState,
+	},
+	/// Only sent by clients older than protocol version 7, newer clients send `ToServer::ActorMetrics`.
+	ActorMetrics {
+		actor_id: Uuid,
+		metrics: ActorMetrics,
+	},
+	VolumeSnapshotUpdate {
+		volume_id: Uuid,
+		snapshot_id: Uuid,
+		state: VolumeSnapshotState,
+	},
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct ActorMetricsEntry {
+	pub actor_id: Uuid,
+	pub metrics: ActorMetrics,
+}
+
+/// Summary of an actor's resource usage over a sampling window.
+/// Sent by pegboard client.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct ActorMetrics {
+	/// Start of the window in milliseconds.
+	pub start_ts: i64,
+	/// End of the window in milliseconds.
+	pub end_ts: i64,
+	/// Average CPU usage in millicores.
+	pub cpu_avg: u64,
+	/// Peak CPU usage in millicores between two samples.
+	pub cpu_max: u64,
+	/// Average memory usage in bytes. For isolates, this is the used V8 heap.
+	pub memory_avg: u64,
+	/// Peak memory usage in bytes.
+	pub memory_max: u64,
+	/// Bytes received during the window. Unset if not measured (isolates and host networking).
+	pub network_rx_bytes: Option<u64>,
+	/// Bytes transmitted during the window.
+	pub network_tx_bytes: Option<u64>,
+	/// Bytes read from disk during the window. Unset if not measured (isolates).
+	pub disk_read_bytes: Option<u64>,
+	/// Bytes written to disk during the window.
+	pub disk_write_bytes: Option<u64>,
+}
+
+/// Sent by pegboard client.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeSnapshotState {
+	Complete {
+		/// Bytes of the compressed archive.
+		size: u64,
+	},
+	Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorState {
+	/// Actor planned, not yet started.
+	/// Sent by pegboard dc.
+	Allocated { client_id: Uuid },
+	/// Actor starting on client.
+	/// Sent by pegboard client.
+	Starting,
+	/// Actor has a running process.
+	/// Sent by pegboard client.
+	Running {
+		pid: usize,
+		ports: HashableMap<String, ProxiedPort>,
+	},
+	/// Actor planned to stop.
+	/// Sent by pegboard dc.
+	Stopping,
+	/// Actor stopped on client, process not yet exited.
+	/// Sent by pegboard client.
+	Stopped,
+	/// Actor was lost in some way and will never be marked as stopped (if not already) and will never exit.
+	/// Sent by pegboard client and pegboard gc.
+	Lost,
+	/// Actor process exited.
+	/// Sent by pegboard client.
+	Exited {
+		/// Unset if the exit code could not be read (usually from SIGKILL or lost process)
+		exit_code: Option<i32>,
+	},
+	/// Datacenter failed to allocate the actor to a client.
+	/// Sent by pegboard dc.
+	FailedToAllocate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct ProxiedPort {
+	/// Port on the host.
+	pub source: u16,
+	/// Port in the container.
+	pub target: u16,
+	/// LAN hostname of the node running the container.
+	pub lan_hostname: String,
+	pub protocol: TransportProtocol,
+}
+
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, Copy, PartialEq, Eq, FromRepr, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientFlavor {
+	Container = 0,
+	Isolate = 1,
+}
+
+impl std::fmt::Display for ClientFlavor {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ClientFlavor::Container => write!(f, "container"),
+			ClientFlavor::Isolate => write!(f, "isolate"),
+		}
+	}
+}
+
+impl std::str::FromStr for ClientFlavor {
+	type Err = PegboardProtocolError;
+
+	fn from_str(s: &str) -> Result<Self, PegboardProtocolError> {
+		match s {
+			"container" => Ok(ClientFlavor::Container),
+			"isolate" => Ok(ClientFlavor::Isolate),
+			x => Err(PegboardProtocolError::InvalidClientFlavor(x.to_string())),
+		}
+	}
+}
@@ -0,0 +1,194 @@
+//! Wire schema of protocol version 2. Encoded with JSON.
+//!
+//! These types are frozen. Commands and events are nested as raw JSON, their schema did not change in
+//! version 3.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{v3, v4, PegboardProtocolError, Raw};
+use crate::{client_config::ClientConfig, system_info::SystemInfo};
+
+pub fn encode<T: Serialize>(packet: &T) -> Result<Vec<u8>, PegboardProtocolError> {
+	serde_json::to_vec(packet).map_err(PegboardProtocolError::Serde)
+}
+
+pub fn decode<T: DeserializeOwned>(buf: &[u8]) -> Result<T, PegboardProtocolError> {
+	serde_json::from_slice(buf).map_err(PegboardProtocolError::Serde)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToClient {
+	Init {
+		last_event_idx: i64,
+	},
+	Commands(Vec<CommandWrapper>),
+	PrewarmImage {
+		image_id: Uuid,
+		image_artifact_url_stub: String,
+		#[serde(default)]
+		image_digest: Option<String>,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandWrapper {
+	pub index: i64,
+	pub inner: Raw<v3::Command>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToServer {
+	Init {
+		last_command_idx: i64,
+		config: ClientConfig,
+		system: SystemInfo,
+	},
+	Events(Vec<EventWrapper>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventWrapper {
+	pub index: i64,
+	pub inner: Raw<v4::Event>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub traceparent: Option<String>,
+}
+
+// Up converters (v2 -> v3)
+
+impl TryFrom<ToClient> for v3::ToClient {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: ToClient) -> Result<Self, Self::Error> {
+		let packet = match value {
+			ToClient::Init { last_event_idx } => v3::ToClient::Init { last_event_idx },
+			ToClient::Commands(commands) => v3::ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| {
+						Ok(v3::CommandWrapper {
+							index: wrapper.index,
+							inner: wrapper.inner.deserialize()?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => v3::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<ToServer> for v3::ToServer {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: ToServer) -> Result<Self, Self::Error> {
+		let packet = match value {
+			ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => v3::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			ToServer::Events(events) => v3::ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| {
+						Ok(v3::EventWrapper {
+							index: wrapper.index,
+							inner: wrapper.inner.deserialize()?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+		};
+
+		Ok(packet)
+	}
+}
+
+// Down converters (v3 -> v2)
+
+impl TryFrom<v3::ToClient> for ToClient {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v3::ToClient) -> Result<Self, Self::Error> {
+		let packet = match value {
+			v3::ToClient::Init { last_event_idx } => ToClient::Init { last_event_idx },
+			v3::ToClient::Commands(commands) => ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| {
+						Ok(CommandWrapper {
+							index: wrapper.index,
+							inner: Raw::new(&wrapper.inner)?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			v3::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<v3::ToServer> for ToServer {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v3::ToServer) -> Result<Self, Self::Error> {
+		let packet = match value {
+			v3::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			v3::ToServer::Events(events) => ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| {
+						Ok(EventWrapper {
+							index: wrapper.index,
+							inner: Raw::new(&wrapper.inner)?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+		};
+
+		Ok(packet)
+	}
+}
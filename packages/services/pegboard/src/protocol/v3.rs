@@ -0,0 +1,352 @@
+//! Wire schema of protocol version 3. Encoded with bincode.
+//!
+//! These types are frozen once released. Types not redefined here are shared with the model and must be
+//! copied into this module before they are changed.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+	v4, ActorMetadata, ActorOwner, HashableMap, ImageKind, NetworkMode, PegboardProtocolError, Port,
+};
+use crate::{client_config::ClientConfig, system_info::SystemInfo};
+
+pub fn encode<T: Serialize>(packet: &T) -> Result<Vec<u8>, PegboardProtocolError> {
+	bincode::serialize(packet).map_err(PegboardProtocolError::Bincode)
+}
+
+pub fn decode<T: DeserializeOwned>(buf: &[u8]) -> Result<T, PegboardProtocolError> {
+	bincode::deserialize(buf).map_err(PegboardProtocolError::Bincode)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToClient {
+	Init {
+		last_event_idx: i64,
+	},
+	Commands(Vec<CommandWrapper>),
+	PrewarmImage {
+		image_id: Uuid,
+		image_artifact_url_stub: String,
+		image_digest: Option<String>,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandWrapper {
+	pub index: i64,
+	pub inner: Command,
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+	StartActor {
+		actor_id: Uuid,
+		config: Box<ActorConfig>,
+	},
+	SignalActor {
+		actor_id: Uuid,
+		signal: i32,
+		persist_storage: bool,
+		ignore_future_state: bool,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActorConfig {
+	pub image: Image,
+	pub root_user_enabled: bool,
+	pub resources: Resources,
+	pub env: HashableMap<String, String>,
+	pub ports: HashableMap<String, Port>,
+	pub network_mode: NetworkMode,
+	pub owner: ActorOwner,
+	pub metadata: ActorMetadata,
+}
+
+/// Clients before v4 can't decompress Zstd images.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Image {
+	pub id: Uuid,
+	pub artifact_url_stub: String,
+	pub fallback_artifact_url: Option<String>,
+	pub kind: ImageKind,
+	pub compression: ImageCompression,
+	#[serde(default)]
+	pub digest: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageCompression {
+	None,
+	Lz4,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Resources {
+	pub cpu: u64,
+	pub memory: u64,
+	pub memory_max: u64,
+	pub disk: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToServer {
+	Init {
+		last_command_idx: i64,
+		config: ClientConfig,
+		system: SystemInfo,
+	},
+	Events(Vec<EventWrapper>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventWrapper {
+	pub index: i64,
+	pub inner: v4::Event,
+	pub traceparent: Option<String>,
+}
+
+// Up converters (v3 -> v4)
+
+impl From<ToClient> for v4::ToClient {
+	fn from(value: ToClient) -> Self {
+		match value {
+			ToClient::Init { last_event_idx } => v4::ToClient::Init { last_event_idx },
+			ToClient::Commands(commands) => v4::ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| v4::CommandWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner.into(),
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+			ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => v4::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		}
+	}
+}
+
+impl From<Command> for v4::Command {
+	fn from(value: Command) -> Self {
+		match value {
+			Command::StartActor { actor_id, config } => v4::Command::StartActor {
+				actor_id,
+				config: Box::new(v4::ActorConfig {
+					image: config.image.into(),
+					root_user_enabled: config.root_user_enabled,
+					resources: super::Resources {
+						cpu: config.resources.cpu,
+						memory: config.resources.memory,
+						memory_max: config.resources.memory_max,
+						disk: config.resources.disk,
+						pids: None,
+						io_weight: None,
+						io_read_bps: None,
+						io_write_bps: None,
+						nofile: None,
+						egress_bps: None,
+					},
+					env: config.env,
+					ports: config.ports,
+					network_mode: config.network_mode,
+					owner: config.owner,
+					metadata: config.metadata,
+				}),
+			},
+			Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			} => v4::Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			},
+		}
+	}
+}
+
+impl From<Image> for super::Image {
+	fn from(value: Image) -> Self {
+		super::Image {
+			id: value.id,
+			artifact_url_stub: value.artifact_url_stub,
+			fallback_artifact_url: value.fallback_artifact_url,
+			kind: value.kind,
+			compression: match value.compression {
+				ImageCompression::None => super::ImageCompression::None,
+				ImageCompression::Lz4 => super::ImageCompression::Lz4,
+			},
+			digest: value.digest,
+			compression_dictionary: None,
+		}
+	}
+}
+
+impl From<ToServer> for v4::ToServer {
+	fn from(value: ToServer) -> Self {
+		match value {
+			ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => v4::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			ToServer::Events(events) => v4::ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| v4::EventWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner,
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+		}
+	}
+}
+
+// Down converters (v4 -> v3). Resource limits added in v4 are dropped. Actors with Zstd images can't be
+// started.
+
+impl TryFrom<v4::ToClient> for ToClient {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v4::ToClient) -> Result<Self, Self::Error> {
+		let packet = match value {
+			v4::ToClient::Init { last_event_idx } => ToClient::Init { last_event_idx },
+			v4::ToClient::Commands(commands) => ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| {
+						Ok(CommandWrapper {
+							index: wrapper.index,
+							inner: wrapper.inner.try_into()?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			v4::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<v4::Command> for Command {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v4::Command) -> Result<Self, Self::Error> {
+		let packet = match value {
+			v4::Command::StartActor { actor_id, config } => Command::StartActor {
+				actor_id,
+				config: Box::new(ActorConfig {
+					image: config.image.try_into()?,
+					root_user_enabled: config.root_user_enabled,
+					resources: Resources {
+						cpu: config.resources.cpu,
+						memory: config.resources.memory,
+						memory_max: config.resources.memory_max,
+						disk: config.resources.disk,
+					},
+					env: config.env,
+					ports: config.ports,
+					network_mode: config.network_mode,
+					owner: config.owner,
+					metadata: config.metadata,
+				}),
+			},
+			v4::Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			} => Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			},
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<super::Image> for Image {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: super::Image) -> Result<Self, Self::Error> {
+		let compression = match value.compression {
+			super::ImageCompression::None => ImageCompression::None,
+			super::ImageCompression::Lz4 => ImageCompression::Lz4,
+			super::ImageCompression::Zstd => {
+				return Err(PegboardProtocolError::UnsupportedPacket(3))
+			}
+		};
+
+		Ok(Image {
+			id: value.id,
+			artifact_url_stub: value.artifact_url_stub,
+			fallback_artifact_url: value.fallback_artifact_url,
+			kind: value.kind,
+			compression,
+			digest: value.digest,
+		})
+	}
+}
+
+impl From<v4::ToServer> for ToServer {
+	fn from(value: v4::ToServer) -> Self {
+		match value {
+			v4::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			v4::ToServer::Events(events) => ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| EventWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner,
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+		}
+	}
+}
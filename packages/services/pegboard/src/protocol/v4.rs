@@ -0,0 +1,278 @@
+//! Wire schema of protocol version 4. Encoded with bincode.
+//!
+//! These types are frozen once released. Types not redefined here are shared with the model and must be
+//! copied into this module before they are changed.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+	v5, ActorMetadata, ActorOwner, ActorState, HashableMap, Image, NetworkMode,
+	PegboardProtocolError, Port, Resources,
+};
+use crate::{client_config::ClientConfig, system_info::SystemInfo};
+
+pub fn encode<T: Serialize>(packet: &T) -> Result<Vec<u8>, PegboardProtocolError> {
+	bincode::serialize(packet).map_err(PegboardProtocolError::Bincode)
+}
+
+pub fn decode<T: DeserializeOwned>(buf: &[u8]) -> Result<T, PegboardProtocolError> {
+	bincode::deserialize(buf).map_err(PegboardProtocolError::Bincode)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToClient {
+	Init {
+		last_event_idx: i64,
+	},
+	Commands(Vec<CommandWrapper>),
+	PrewarmImage {
+		image_id: Uuid,
+		image_artifact_url_stub: String,
+		image_digest: Option<String>,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandWrapper {
+	pub index: i64,
+	pub inner: Command,
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+	StartActor {
+		actor_id: Uuid,
+		config: Box<ActorConfig>,
+	},
+	SignalActor {
+		actor_id: Uuid,
+		signal: i32,
+		persist_storage: bool,
+		ignore_future_state: bool,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActorConfig {
+	pub image: Image,
+	pub root_user_enabled: bool,
+	pub resources: Resources,
+	pub env: HashableMap<String, String>,
+	pub ports: HashableMap<String, Port>,
+	pub network_mode: NetworkMode,
+	pub owner: ActorOwner,
+	pub metadata: ActorMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToServer {
+	Init {
+		last_command_idx: i64,
+		config: ClientConfig,
+		system: SystemInfo,
+	},
+	Events(Vec<EventWrapper>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventWrapper {
+	pub index: i64,
+	pub inner: Event,
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+	ActorStateUpdate { actor_id: Uuid, state: ActorState },
+}
+
+// Up converters (v4 -> v5)
+
+impl From<ToClient> for v5::ToClient {
+	fn from(value: ToClient) -> Self {
+		match value {
+			ToClient::Init { last_event_idx } => v5::ToClient::Init { last_event_idx },
+			ToClient::Commands(commands) => v5::ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| v5::CommandWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner.into(),
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+			ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => v5::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		}
+	}
+}
+
+impl From<Command> for v5::Command {
+	fn from(value: Command) -> Self {
+		match value {
+			Command::StartActor { actor_id, config } => v5::Command::StartActor {
+				actor_id,
+				config: Box::new(v5::ActorConfig {
+					image: config.image,
+					root_user_enabled: config.root_user_enabled,
+					resources: config.resources,
+					env: config.env,
+					ports: config.ports,
+					network_mode: config.network_mode,
+					owner: config.owner,
+					metadata: config.metadata,
+				}),
+			},
+			Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			} => v5::Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			},
+		}
+	}
+}
+
+impl From<ToServer> for v5::ToServer {
+	fn from(value: ToServer) -> Self {
+		match value {
+			ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => v5::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			ToServer::Events(events) => v5::ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| v5::EventWrapper {
+						index: wrapper.index,
+						inner: match wrapper.inner {
+							Event::ActorStateUpdate { actor_id, state } => {
+								v5::Event::ActorStateUpdate { actor_id, state }
+							}
+						},
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+		}
+	}
+}
+
+// Down converters (v5 -> v4). Actor metrics events added in v5 are dropped.
+
+impl From<v5::ToClient> for ToClient {
+	fn from(value: v5::ToClient) -> Self {
+		match value {
+			v5::ToClient::Init { last_event_idx } => ToClient::Init { last_event_idx },
+			v5::ToClient::Commands(commands) => ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| CommandWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner.into(),
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+			v5::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		}
+	}
+}
+
+impl From<v5::Command> for Command {
+	fn from(value: v5::Command) -> Self {
+		match value {
+			v5::Command::StartActor { actor_id, config } => Command::StartActor {
+				actor_id,
+				config: Box::new(ActorConfig {
+					image: config.image,
+					root_user_enabled: config.root_user_enabled,
+					resources: config.resources,
+					env: config.env,
+					ports: config.ports,
+					network_mode: config.network_mode,
+					owner: config.owner,
+					metadata: config.metadata,
+				}),
+			},
+			v5::Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			} => Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			},
+		}
+	}
+}
+
+impl From<v5::ToServer> for ToServer {
+	fn from(value: v5::ToServer) -> Self {
+		match value {
+			v5::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			v5::ToServer::Events(events) => ToServer::Events(
+				events
+					.into_iter()
+					.filter_map(|wrapper| {
+						let inner = match wrapper.inner {
+							v5::Event::ActorStateUpdate { actor_id, state } => {
+								Event::ActorStateUpdate { actor_id, state }
+							}
+							v5::Event::ActorMetrics { .. } => return None,
+						};
+
+						Some(EventWrapper {
+							index: wrapper.index,
+							inner,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect(),
+			),
+		}
+	}
+}
@@ -0,0 +1,318 @@
+//! Wire schema of protocol version 5. Encoded with bincode.
+//!
+//! These types are frozen once released. Types not redefined here are shared with the model and must be
+//! copied into this module before they are changed.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+	v6, ActorMetadata, ActorMetrics, ActorOwner, ActorState, HashableMap, Image, NetworkMode,
+	PegboardProtocolError, Port, Resources,
+};
+use crate::{client_config::ClientConfig, system_info::SystemInfo};
+
+pub fn encode<T: Serialize>(packet: &T) -> Result<Vec<u8>, PegboardProtocolError> {
+	bincode::serialize(packet).map_err(PegboardProtocolError::Bincode)
+}
+
+pub fn decode<T: DeserializeOwned>(buf: &[u8]) -> Result<T, PegboardProtocolError> {
+	bincode::deserialize(buf).map_err(PegboardProtocolError::Bincode)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToClient {
+	Init {
+		last_event_idx: i64,
+	},
+	Commands(Vec<CommandWrapper>),
+	PrewarmImage {
+		image_id: Uuid,
+		image_artifact_url_stub: String,
+		image_digest: Option<String>,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandWrapper {
+	pub index: i64,
+	pub inner: Command,
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+	StartActor {
+		actor_id: Uuid,
+		config: Box<ActorConfig>,
+	},
+	SignalActor {
+		actor_id: Uuid,
+		signal: i32,
+		persist_storage: bool,
+		ignore_future_state: bool,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActorConfig {
+	pub image: Image,
+	pub root_user_enabled: bool,
+	pub resources: Resources,
+	pub env: HashableMap<String, String>,
+	pub ports: HashableMap<String, Port>,
+	pub network_mode: NetworkMode,
+	pub owner: ActorOwner,
+	pub metadata: ActorMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToServer {
+	Init {
+		last_command_idx: i64,
+		config: ClientConfig,
+		system: SystemInfo,
+	},
+	Events(Vec<EventWrapper>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventWrapper {
+	pub index: i64,
+	pub inner: Event,
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+	ActorStateUpdate {
+		actor_id: Uuid,
+		state: ActorState,
+	},
+	ActorMetrics {
+		actor_id: Uuid,
+		metrics: ActorMetrics,
+	},
+}
+
+// Up converters (v5 -> v6)
+
+impl From<ToClient> for v6::ToClient {
+	fn from(value: ToClient) -> Self {
+		match value {
+			ToClient::Init { last_event_idx } => v6::ToClient::Init { last_event_idx },
+			ToClient::Commands(commands) => v6::ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| v6::CommandWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner.into(),
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+			ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => v6::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		}
+	}
+}
+
+impl From<Command> for v6::Command {
+	fn from(value: Command) -> Self {
+		match value {
+			Command::StartActor { actor_id, config } => v6::Command::StartActor {
+				actor_id,
+				config: Box::new(v6::ActorConfig {
+					image: config.image,
+					root_user_enabled: config.root_user_enabled,
+					resources: config.resources,
+					env: config.env,
+					ports: config.ports,
+					network_mode: config.network_mode,
+					owner: config.owner,
+					metadata: config.metadata,
+					volumes: Vec::new(),
+				}),
+			},
+			Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			} => v6::Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			},
+		}
+	}
+}
+
+impl From<ToServer> for v6::ToServer {
+	fn from(value: ToServer) -> Self {
+		match value {
+			ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => v6::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			ToServer::Events(events) => v6::ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| v6::EventWrapper {
+						index: wrapper.index,
+						inner: match wrapper.inner {
+							Event::ActorStateUpdate { actor_id, state } => {
+								super::Event::ActorStateUpdate { actor_id, state }
+							}
+							Event::ActorMetrics { actor_id, metrics } => {
+								super::Event::ActorMetrics { actor_id, metrics }
+							}
+						},
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+		}
+	}
+}
+
+// Down converters (v6 -> v5). Volumes, volume commands and volume snapshot events added in v6 are not
+// supported.
+
+impl TryFrom<v6::ToClient> for ToClient {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v6::ToClient) -> Result<Self, Self::Error> {
+		let packet = match value {
+			v6::ToClient::Init { last_event_idx } => ToClient::Init { last_event_idx },
+			v6::ToClient::Commands(commands) => ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| {
+						Ok(CommandWrapper {
+							index: wrapper.index,
+							inner: wrapper.inner.try_into()?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			v6::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<v6::Command> for Command {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v6::Command) -> Result<Self, Self::Error> {
+		let command = match value {
+			v6::Command::StartActor { actor_id, config } => {
+				if !config.volumes.is_empty() {
+					return Err(PegboardProtocolError::UnsupportedPacket(5));
+				}
+
+				Command::StartActor {
+					actor_id,
+					config: Box::new(ActorConfig {
+						image: config.image,
+						root_user_enabled: config.root_user_enabled,
+						resources: config.resources,
+						env: config.env,
+						ports: config.ports,
+						network_mode: config.network_mode,
+						owner: config.owner,
+						metadata: config.metadata,
+					}),
+				}
+			}
+			v6::Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			} => Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			},
+			v6::Command::SnapshotVolume { .. } | v6::Command::DeleteVolume { .. } => {
+				return Err(PegboardProtocolError::UnsupportedPacket(5));
+			}
+		};
+
+		Ok(command)
+	}
+}
+
+impl TryFrom<v6::ToServer> for ToServer {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v6::ToServer) -> Result<Self, Self::Error> {
+		let packet = match value {
+			v6::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			v6::ToServer::Events(events) => ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| {
+						let inner = match wrapper.inner {
+							super::Event::ActorStateUpdate { actor_id, state } => {
+								Event::ActorStateUpdate { actor_id, state }
+							}
+							super::Event::ActorMetrics { actor_id, metrics } => {
+								Event::ActorMetrics { actor_id, metrics }
+							}
+							super::Event::VolumeSnapshotUpdate { .. } => {
+								return Err(PegboardProtocolError::UnsupportedPacket(5));
+							}
+						};
+
+						Ok(EventWrapper {
+							index: wrapper.index,
+							inner,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+		};
+
+		Ok(packet)
+	}
+}
@@ -0,0 +1,314 @@
+//! Wire schema of protocol version 6. Encoded with bincode.
+//!
+//! These types are frozen once released. Types not redefined here are shared with the model and must be
+//! copied into this module before they are changed.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+	v7, ActorMetadata, ActorOwner, Event, HashableMap, Image, NetworkMode, PegboardProtocolError,
+	Port, Resources, Volume,
+};
+use crate::{client_config::ClientConfig, system_info::SystemInfo};
+
+pub fn encode<T: Serialize>(packet: &T) -> Result<Vec<u8>, PegboardProtocolError> {
+	bincode::serialize(packet).map_err(PegboardProtocolError::Bincode)
+}
+
+pub fn decode<T: DeserializeOwned>(buf: &[u8]) -> Result<T, PegboardProtocolError> {
+	bincode::deserialize(buf).map_err(PegboardProtocolError::Bincode)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToClient {
+	Init {
+		last_event_idx: i64,
+	},
+	Commands(Vec<CommandWrapper>),
+	PrewarmImage {
+		image_id: Uuid,
+		image_artifact_url_stub: String,
+		image_digest: Option<String>,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandWrapper {
+	pub index: i64,
+	pub inner: Command,
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+	StartActor {
+		actor_id: Uuid,
+		config: Box<ActorConfig>,
+	},
+	SignalActor {
+		actor_id: Uuid,
+		signal: i32,
+		persist_storage: bool,
+		ignore_future_state: bool,
+	},
+	SnapshotVolume {
+		volume_id: Uuid,
+		snapshot_id: Uuid,
+		upload_url: String,
+	},
+	DeleteVolume {
+		volume_id: Uuid,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActorConfig {
+	pub image: Image,
+	pub root_user_enabled: bool,
+	pub resources: Resources,
+	pub env: HashableMap<String, String>,
+	pub ports: HashableMap<String, Port>,
+	pub network_mode: NetworkMode,
+	pub owner: ActorOwner,
+	pub metadata: ActorMetadata,
+	pub volumes: Vec<Volume>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToServer {
+	Init {
+		last_command_idx: i64,
+		config: ClientConfig,
+		system: SystemInfo,
+	},
+	Events(Vec<EventWrapper>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventWrapper {
+	pub index: i64,
+	pub inner: Event,
+	pub traceparent: Option<String>,
+}
+
+// Up converters (v6 -> v7)
+
+impl From<ToClient> for v7::ToClient {
+	fn from(value: ToClient) -> Self {
+		match value {
+			ToClient::Init { last_event_idx } => v7::ToClient::Init { last_event_idx },
+			ToClient::Commands(commands) => v7::ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| v7::CommandWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner.into(),
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+			ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => v7::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		}
+	}
+}
+
+impl From<Command> for v7::Command {
+	fn from(value: Command) -> Self {
+		match value {
+			Command::StartActor { actor_id, config } => v7::Command::StartActor {
+				actor_id,
+				config: Box::new(v7::ActorConfig {
+					image: config.image,
+					root_user_enabled: config.root_user_enabled,
+					resources: config.resources,
+					env: config.env,
+					secrets: HashableMap::new(),
+					ports: config.ports,
+					network_mode: config.network_mode,
+					owner: config.owner,
+					metadata: config.metadata,
+					volumes: config.volumes,
+				}),
+			},
+			Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			} => v7::Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			},
+			Command::SnapshotVolume {
+				volume_id,
+				snapshot_id,
+				upload_url,
+			} => v7::Command::SnapshotVolume {
+				volume_id,
+				snapshot_id,
+				upload_url,
+			},
+			Command::DeleteVolume { volume_id } => v7::Command::DeleteVolume { volume_id },
+		}
+	}
+}
+
+impl From<ToServer> for v7::ToServer {
+	fn from(value: ToServer) -> Self {
+		match value {
+			ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => v7::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			ToServer::Events(events) => v7::ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| v7::EventWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner,
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+		}
+	}
+}
+
+// Down converters (v7 -> v6). Secrets and actor metrics packets added in v7 are not supported.
+
+impl TryFrom<v7::ToClient> for ToClient {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v7::ToClient) -> Result<Self, Self::Error> {
+		let packet = match value {
+			v7::ToClient::Init { last_event_idx } => ToClient::Init { last_event_idx },
+			v7::ToClient::Commands(commands) => ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| {
+						Ok(CommandWrapper {
+							index: wrapper.index,
+							inner: wrapper.inner.try_into()?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			v7::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<v7::Command> for Command {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v7::Command) -> Result<Self, Self::Error> {
+		let command = match value {
+			v7::Command::StartActor { actor_id, config } => {
+				if !config.secrets.is_empty() {
+					return Err(PegboardProtocolError::UnsupportedPacket(6));
+				}
+
+				Command::StartActor {
+					actor_id,
+					config: Box::new(ActorConfig {
+						image: config.image,
+						root_user_enabled: config.root_user_enabled,
+						resources: config.resources,
+						env: config.env,
+						ports: config.ports,
+						network_mode: config.network_mode,
+						owner: config.owner,
+						metadata: config.metadata,
+						volumes: config.volumes,
+					}),
+				}
+			}
+			v7::Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			} => Command::SignalActor {
+				actor_id,
+				signal,
+				persist_storage,
+				ignore_future_state,
+			},
+			v7::Command::SnapshotVolume {
+				volume_id,
+				snapshot_id,
+				upload_url,
+			} => Command::SnapshotVolume {
+				volume_id,
+				snapshot_id,
+				upload_url,
+			},
+			v7::Command::DeleteVolume { volume_id } => Command::DeleteVolume { volume_id },
+		};
+
+		Ok(command)
+	}
+}
+
+impl TryFrom<v7::ToServer> for ToServer {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: v7::ToServer) -> Result<Self, Self::Error> {
+		let packet = match value {
+			v7::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			v7::ToServer::Events(events) => ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| EventWrapper {
+						index: wrapper.index,
+						inner: wrapper.inner,
+						traceparent: wrapper.traceparent,
+					})
+					.collect(),
+			),
+			v7::ToServer::ActorMetrics(_) => {
+				return Err(PegboardProtocolError::UnsupportedPacket(6));
+			}
+		};
+
+		Ok(packet)
+	}
+}
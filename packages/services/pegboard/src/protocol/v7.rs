@@ -0,0 +1,235 @@
+//! Wire schema of protocol version 7. Encoded with bincode.
+//!
+//! These types are frozen once released. Types not redefined here are shared with the model and must be
+//! copied into this module before they are changed.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+	ActorMetadata, ActorMetricsEntry, ActorOwner, Event, HashableMap, Image, NetworkMode,
+	PegboardProtocolError, Port, Raw, Resources, Volume,
+};
+use crate::{client_config::ClientConfig, system_info::SystemInfo};
+
+pub fn encode<T: Serialize>(packet: &T) -> Result<Vec<u8>, PegboardProtocolError> {
+	bincode::serialize(packet).map_err(PegboardProtocolError::Bincode)
+}
+
+pub fn decode<T: DeserializeOwned>(buf: &[u8]) -> Result<T, PegboardProtocolError> {
+	bincode::deserialize(buf).map_err(PegboardProtocolError::Bincode)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToClient {
+	Init {
+		last_event_idx: i64,
+	},
+	Commands(Vec<CommandWrapper>),
+	PrewarmImage {
+		image_id: Uuid,
+		image_artifact_url_stub: String,
+		image_digest: Option<String>,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandWrapper {
+	pub index: i64,
+	pub inner: Command,
+	pub traceparent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+	StartActor {
+		actor_id: Uuid,
+		config: Box<ActorConfig>,
+	},
+	SignalActor {
+		actor_id: Uuid,
+		signal: i32,
+		persist_storage: bool,
+		ignore_future_state: bool,
+	},
+	SnapshotVolume {
+		volume_id: Uuid,
+		snapshot_id: Uuid,
+		upload_url: String,
+	},
+	DeleteVolume {
+		volume_id: Uuid,
+	},
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActorConfig {
+	pub image: Image,
+	pub root_user_enabled: bool,
+	pub resources: Resources,
+	pub env: HashableMap<String, String>,
+	/// Env var name -> decrypted secret value.
+	pub secrets: HashableMap<String, String>,
+	pub ports: HashableMap<String, Port>,
+	pub network_mode: NetworkMode,
+	pub owner: ActorOwner,
+	pub metadata: ActorMetadata,
+	pub volumes: Vec<Volume>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToServer {
+	Init {
+		last_command_idx: i64,
+		config: ClientConfig,
+		system: SystemInfo,
+	},
+	Events(Vec<EventWrapper>),
+	ActorMetrics(Vec<ActorMetricsEntry>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventWrapper {
+	pub index: i64,
+	pub inner: Event,
+	pub traceparent: Option<String>,
+}
+
+// Conversions from/to the model. `Command` has the same JSON representation as `super::Command`.
+
+impl TryFrom<&super::ToClient> for ToClient {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: &super::ToClient) -> Result<Self, Self::Error> {
+		let packet = match value {
+			super::ToClient::Init { last_event_idx } => ToClient::Init {
+				last_event_idx: *last_event_idx,
+			},
+			super::ToClient::Commands(commands) => ToClient::Commands(
+				commands
+					.iter()
+					.map(|wrapper| {
+						Ok(CommandWrapper {
+							index: wrapper.index,
+							inner: serde_json::from_str(wrapper.inner.get())?,
+							traceparent: wrapper.traceparent.clone(),
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			super::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => ToClient::PrewarmImage {
+				image_id: *image_id,
+				image_artifact_url_stub: image_artifact_url_stub.clone(),
+				image_digest: image_digest.clone(),
+			},
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<ToClient> for super::ToClient {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: ToClient) -> Result<Self, Self::Error> {
+		let packet = match value {
+			ToClient::Init { last_event_idx } => super::ToClient::Init { last_event_idx },
+			ToClient::Commands(commands) => super::ToClient::Commands(
+				commands
+					.into_iter()
+					.map(|wrapper| {
+						Ok(super::CommandWrapper {
+							index: wrapper.index,
+							inner: Raw::from_string(serde_json::to_string(&wrapper.inner)?)?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			} => super::ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub,
+				image_digest,
+			},
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<&super::ToServer> for ToServer {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: &super::ToServer) -> Result<Self, Self::Error> {
+		let packet = match value {
+			super::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => ToServer::Init {
+				last_command_idx: *last_command_idx,
+				config: config.clone(),
+				system: system.clone(),
+			},
+			super::ToServer::Events(events) => ToServer::Events(
+				events
+					.iter()
+					.map(|wrapper| {
+						Ok(EventWrapper {
+							index: wrapper.index,
+							inner: wrapper.inner.deserialize()?,
+							traceparent: wrapper.traceparent.clone(),
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			super::ToServer::ActorMetrics(entries) => ToServer::ActorMetrics(entries.clone()),
+		};
+
+		Ok(packet)
+	}
+}
+
+impl TryFrom<ToServer> for super::ToServer {
+	type Error = PegboardProtocolError;
+
+	fn try_from(value: ToServer) -> Result<Self, Self::Error> {
+		let packet = match value {
+			ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			} => super::ToServer::Init {
+				last_command_idx,
+				config,
+				system,
+			},
+			ToServer::Events(events) => super::ToServer::Events(
+				events
+					.into_iter()
+					.map(|wrapper| {
+						Ok(super::EventWrapper {
+							index: wrapper.index,
+							inner: Raw::new(&wrapper.inner)?,
+							traceparent: wrapper.traceparent,
+						})
+					})
+					.collect::<Result<_, PegboardProtocolError>>()?,
+			),
+			ToServer::ActorMetrics(entries) => super::ToServer::ActorMetrics(entries),
+		};
+
+		Ok(packet)
+	}
+}
@@ -81,28 +81,58 @@ pub async fn pegboard_client(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResu
 								))
 								.await?;
 
+							let mut metrics = Vec::new();
+							for event in &events {
+								if let protocol::Event::ActorMetrics {
+									actor_id,
+									metrics: actor_metrics,
+								} = event.inner.deserialize()?
+								{
+									metrics.push((actor_id, actor_metrics));
+								}
+							}
+
+							// Write metrics to ClickHouse. Only sent as events by clients older than
+							// protocol version 7, newer clients send `ToServer::ActorMetrics`.
+							if !metrics.is_empty() {
+								ctx.activity(InsertActorMetricsInput { client_id, metrics })
+									.await?;
+							}
+
 							// NOTE: This should not be parallelized because signals should be sent in order
 							for event in events {
-								#[allow(irrefutable_let_patterns)]
-								if let protocol::Event::ActorStateUpdate { actor_id, state } =
-									event.inner.deserialize()?
-								{
-									// Skip ignored actor ids
-									if update_actor_state_res
-										.ignore_actor_ids
-										.iter()
-										.any(|id| &actor_id == id)
-									{
-										continue;
+								match event.inner.deserialize()? {
+									protocol::Event::ActorStateUpdate { actor_id, state } => {
+										// Skip ignored actor ids
+										if update_actor_state_res
+											.ignore_actor_ids
+											.iter()
+											.any(|id| &actor_id == id)
+										{
+											continue;
+										}
+
+										ctx.signal(ActorStateUpdate { state })
+											.tag("actor_id", actor_id)
+											.send()
+											.await?;
 									}
-
-									ctx.signal(ActorStateUpdate { state })
-										.tag("actor_id", actor_id)
-										.send()
-										.await?;
+									protocol::Event::VolumeSnapshotUpdate {
+										volume_id,
+										snapshot_id,
+										state,
+									} => {
+										ctx.signal(VolumeSnapshotUpdate { volume_id, state })
+											.tag("snapshot_id", snapshot_id)
+											.send()
+											.await?;
+									}
+									protocol::Event::ActorMetrics { .. } => {}
 								}
 							}
 						}
+						// Written to ClickHouse by the ws and never forwarded
+						protocol::ToServer::ActorMetrics(_) => {}
 					}
 				}
 				Main::Command(command) => {
@@ -114,6 +144,7 @@ pub async fn pegboard_client(ctx: &mut WorkflowCtx, input: &Input) -> GlobalResu
 						inner: protocol::ToClient::PrewarmImage {
 							image_id: sig.image_id,
 							image_artifact_url_stub: sig.image_artifact_url_stub,
+							image_digest: sig.image_digest,
 						},
 					})
 					.send()
@@ -229,6 +260,7 @@ async fn process_init(
 				Ok(protocol::CommandWrapper {
 					index,
 					inner: protocol::Raw::from_string(payload)?,
+					traceparent: None,
 				})
 			})
 			.collect::<GlobalResult<_>>()?,
@@ -429,6 +461,10 @@ async fn update_actor_state(
 					ignore_actor_ids.push(actor_id);
 				}
 			}
+			// Written by `insert_actor_metrics`
+			protocol::Event::ActorMetrics { .. } => {}
+			// Forwarded to the workflow that requested the snapshot
+			protocol::Event::VolumeSnapshotUpdate { .. } => {}
 		}
 	}
 
@@ -438,6 +474,31 @@ async fn update_actor_state(
 	})
 }
 
+#[derive(Debug, Serialize, Deserialize, Hash)]
+struct InsertActorMetricsInput {
+	client_id: Uuid,
+	metrics: Vec<(Uuid, protocol::ActorMetrics)>,
+}
+
+#[activity(InsertActorMetrics)]
+async fn insert_actor_metrics(
+	ctx: &ActivityCtx,
+	input: &InsertActorMetricsInput,
+) -> GlobalResult<()> {
+	ctx.op(crate::ops::actor::metrics_insert::Input {
+		client_id: input.client_id,
+		metrics: input
+			.metrics
+			.iter()
+			.map(|(actor_id, metrics)| protocol::ActorMetricsEntry {
+				actor_id: *actor_id,
+				metrics: metrics.clone(),
+			})
+			.collect(),
+	})
+	.await
+}
+
 pub async fn handle_commands(
 	ctx: &mut WorkflowCtx,
 	client_id: Uuid,
@@ -465,6 +526,7 @@ pub async fn handle_commands(
 		let wrapped_command = protocol::CommandWrapper {
 			index: index + i as i64,
 			inner: raw_command,
+			traceparent: tivet_runtime::otel::traceparent(),
 		};
 
 		// Forward signal to ws as message
@@ -630,6 +692,12 @@ pub struct ActorStateUpdate {
 	pub state: protocol::ActorState,
 }
 
+#[signal("pegboard_volume_snapshot_update")]
+pub struct VolumeSnapshotUpdate {
+	pub volume_id: Uuid,
+	pub state: protocol::VolumeSnapshotState,
+}
+
 #[signal("pegboard_client_drain")]
 pub struct Drain {}
 
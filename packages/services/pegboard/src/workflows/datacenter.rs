@@ -77,6 +77,16 @@ pub async fn pegboard_datacenter(ctx: &mut WorkflowCtx, input: &Input) -> Global
 						);
 					}
 				}
+				// Volumes are stored on the clients that ran them, the owner sends these directly to the client
+				Main::Command(
+					command @ (protocol::Command::SnapshotVolume { .. }
+					| protocol::Command::DeleteVolume { .. }),
+				) => {
+					tracing::error!(
+						?command,
+						"volume commands cannot be routed by the datacenter"
+					);
+				}
 				Main::PrewarmImage(sig) => {
 					let client_id = ctx.activity(GetClientFromDcInput { datacenter_id }).await?;
 
@@ -7,4 +7,6 @@ pub mod datacenter;
 pub struct PrewarmImage {
 	pub image_id: Uuid,
 	pub image_artifact_url_stub: String,
+	#[serde(default)]
+	pub image_digest: Option<String>,
 }
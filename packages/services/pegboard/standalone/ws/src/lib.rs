@@ -11,17 +11,24 @@ use std::{
 use chirp_workflow::prelude::*;
 use futures_util::{stream::SplitSink, SinkExt, StreamExt};
 use serde_json::json;
+use tivet_claims::ClaimsDecode;
+use tivet_config::config::{tivet::AccessKind, JwtKey};
 use tokio::{
 	net::{TcpListener, TcpStream},
 	sync::{Mutex, RwLock},
 };
 use tokio_tungstenite::{
-	tungstenite::protocol::{
-		frame::{coding::CloseCode, CloseFrame},
-		Message,
+	tungstenite::{
+		handshake::server::ErrorResponse,
+		http::{header, HeaderMap, StatusCode},
+		protocol::{
+			frame::{coding::CloseCode, CloseFrame},
+			Message,
+		},
 	},
 	WebSocketStream,
 };
+use tracing::Instrument;
 
 use pegboard::protocol;
 
@@ -108,7 +115,7 @@ async fn handle_connection(
 	let ctx = ctx.clone();
 
 	tokio::spawn(async move {
-		let (ws_stream, url_data) = match setup_connection(raw_stream, addr).await {
+		let (ws_stream, url_data) = match setup_connection(ctx.config(), raw_stream, addr).await {
 			Ok(x) => x,
 			Err(err) => {
 				tracing::error!(?addr, ?err, "setup connection failed");
@@ -144,26 +151,56 @@ async fn handle_connection(
 
 #[tracing::instrument(skip_all)]
 async fn setup_connection(
+	config: &tivet_config::Config,
 	raw_stream: TcpStream,
 	addr: SocketAddr,
 ) -> GlobalResult<(WebSocketStream<TcpStream>, UrlData)> {
-	let mut uri = None;
-	let ws_stream = tokio_tungstenite::accept_hdr_async(
+	let mut url_data = None;
+	let mut setup_err = None;
+	let ws_res = tokio_tungstenite::accept_hdr_async(
 		raw_stream,
 		|req: &tokio_tungstenite::tungstenite::handshake::server::Request, res| {
-			// Bootleg way of reading the uri
-			uri = Some(req.uri().clone());
+			let uri = req.uri().clone();
 
 			tracing::debug!(?addr, ?uri, "handshake");
 
-			Ok(res)
+			// Reject invalid and unauthenticated clients before upgrading the connection
+			let verify_res = parse_url(addr, uri).and_then(|x| {
+				verify_token(
+					&config.server()?.jwt,
+					&config.server()?.tivet.auth.access_kind,
+					req.headers(),
+					x.client_id,
+				)?;
+
+				Ok(x)
+			});
+
+			match verify_res {
+				Ok(x) => {
+					url_data = Some(x);
+
+					Ok(res)
+				}
+				Err(err) => {
+					setup_err = Some(err);
+
+					let mut err_res = ErrorResponse::new(Some("unauthorized".to_string()));
+					*err_res.status_mut() = StatusCode::UNAUTHORIZED;
+
+					Err(err_res)
+				}
+			}
 		},
 	)
-	.await?;
+	.await;
+
+	if let Some(err) = setup_err {
+		return Err(err);
+	}
+	let ws_stream = ws_res?;
 
-	// Parse URL
-	let uri = unwrap!(uri, "socket has no associated request");
-	let url_data = parse_url(addr, uri)?;
+	let url_data = unwrap!(url_data, "socket has no associated request");
 
 	Ok((ws_stream, url_data))
 }
@@ -243,10 +280,45 @@ async fn handle_connection_inner(
 			Message::Binary(buf) => {
 				let packet = protocol::ToServer::deserialize(protocol_version, &buf)?;
 
+				// Metrics are not durable, write them directly instead of going through the client wf. Spawned
+				// so slow inserts don't block reading events from the socket.
+				if let protocol::ToServer::ActorMetrics(metrics) = packet {
+					let ctx = ctx.clone();
+					tokio::spawn(
+						async move {
+							if let Err(err) = ctx
+								.op(pegboard::ops::actor::metrics_insert::Input {
+									client_id,
+									metrics,
+								})
+								.await
+							{
+								tracing::error!(?client_id, ?err, "failed to insert actor metrics");
+							}
+						}
+						.in_current_span(),
+					);
+
+					continue;
+				}
+
+				// Continue the trace of the commands that caused the events
+				let forward_span = tracing::info_span!("forward_packet", ?client_id);
+				if let protocol::ToServer::Events(events) = &packet {
+					let mut traceparents = events
+						.iter()
+						.filter_map(|event| event.traceparent.as_deref());
+					tivet_runtime::otel::set_parent(&forward_span, traceparents.next(), None);
+					for traceparent in traceparents {
+						tivet_runtime::otel::add_link(&forward_span, Some(traceparent));
+					}
+				}
+
 				// Forward to client wf
 				ctx.signal(packet)
 					.tag("client_id", client_id)
 					.send()
+					.instrument(forward_span)
 					.await?;
 			}
 			Message::Ping(_) => {
@@ -432,21 +504,20 @@ async fn msg_thread_inner(
 	loop {
 		tokio::select! {
 			msg = sub.next() => {
-				let msg = msg?;
-
-				{
-					let conns = conns.read().await;
-
-					// Send command to socket
-					if let Some(conn) = conns.get(&msg.client_id) {
-						let buf = msg.inner.serialize(conn.protocol_version)?;
-						conn.tx.lock().await.send(Message::Binary(buf)).await?;
-					} else {
-						tracing::debug!(
-							client_id=?msg.client_id,
-							"received command for client that isn't connected, ignoring"
-						);
-					}
+				let mut msg = msg?;
+
+				let Some(conn) = conns.read().await.get(&msg.client_id).cloned() else {
+					tracing::debug!(
+						client_id=?msg.client_id,
+						"received command for client that isn't connected, ignoring"
+					);
+					continue;
+				};
+
+				// Failing to send a single packet (e.g. a secret failed to resolve or the packet is not
+				// supported by the client's protocol version) should not stop other packets from being sent
+				if let Err(err) = send_packet(ctx, &conn, &mut msg.inner).await {
+					tracing::error!(client_id=?msg.client_id, ?err, "failed to send packet to client");
 				}
 			}
 			msg = close_sub.next() => {
@@ -476,6 +547,113 @@ async fn msg_thread_inner(
 	}
 }
 
+async fn send_packet(
+	ctx: &StandaloneCtx,
+	conn: &Connection,
+	packet: &mut protocol::ToClient,
+) -> GlobalResult<()> {
+	resolve_secrets(ctx, packet).await?;
+
+	let buf = packet.serialize(conn.protocol_version)?;
+	conn.tx.lock().await.send(Message::Binary(buf)).await?;
+
+	Ok(())
+}
+
+/// Replaces the secret names of start actor commands with their decrypted values. Secrets are only decrypted
+/// here so the values are never persisted in workflow history or the database.
+async fn resolve_secrets(ctx: &StandaloneCtx, packet: &mut protocol::ToClient) -> GlobalResult<()> {
+	let protocol::ToClient::Commands(commands) = packet else {
+		return Ok(());
+	};
+
+	for wrapper in commands {
+		let mut command = wrapper.inner.deserialize()?;
+		let protocol::Command::StartActor { actor_id, config } = &mut command else {
+			continue;
+		};
+		if config.secrets.is_empty() {
+			continue;
+		}
+
+		let env_id = config.metadata.deserialize()?.environment.env_id;
+		let values = ctx
+			.op(ds::ops::secret::resolve::Input {
+				env_id,
+				names: config.secrets.values().cloned().collect(),
+			})
+			.await?
+			.values;
+
+		config.secrets = std::mem::take(&mut config.secrets)
+			.into_iter()
+			.filter_map(|(env_name, secret_name)| {
+				let Some(value) = values.get(&secret_name) else {
+					// Secrets are validated when the actor is created, it was deleted since
+					tracing::warn!(
+						?actor_id,
+						?env_name,
+						?secret_name,
+						"secret does not exist, omitting"
+					);
+					return None;
+				};
+
+				Some((env_name, value.read().clone()))
+			})
+			.collect();
+
+		wrapper.inner = protocol::Raw::new(&command)?;
+	}
+
+	Ok(())
+}
+
+/// Verifies that the client has a token with the `ProvisionedServer` entitlement bound to its client id.
+/// Clients without a token are only allowed with the development access kind.
+pub fn verify_token(
+	jwt: &JwtKey,
+	access_kind: &AccessKind,
+	headers: &HeaderMap,
+	client_id: Uuid,
+) -> GlobalResult<()> {
+	let token = headers
+		.get(header::AUTHORIZATION)
+		.map(|value| value.to_str())
+		.transpose()?
+		.map(|value| {
+			Ok(unwrap!(
+				value.strip_prefix("Bearer "),
+				"invalid authorization header"
+			))
+		})
+		.transpose()?;
+
+	let Some(token) = token else {
+		ensure!(
+			*access_kind == AccessKind::Development,
+			"missing client token"
+		);
+
+		return Ok(());
+	};
+
+	let claims = match tivet_claims::decode(jwt, token)? {
+		Ok(claims) => claims,
+		// See `cluster::util::SERVER_TOKEN_TTL`
+		Err(err) if err.is(formatted_error::code::TOKEN_EXPIRED) => bail!("client token expired"),
+		Err(err) => return Err(err),
+	};
+	let ent = claims.as_provisioned_server()?;
+
+	ensure!(
+		ent.server_id == Some(client_id),
+		"client token does not belong to this client"
+	);
+
+	Ok(())
+}
+
 #[derive(Clone, Copy)]
 struct UrlData {
 	protocol_version: u16,
@@ -494,6 +672,10 @@ fn parse_url(addr: SocketAddr, uri: hyper::Uri) -> GlobalResult<UrlData> {
 	);
 	ensure!(last_segment.starts_with('v'), "invalid protocol version");
 	let protocol_version = last_segment[1..].parse::<u16>()?;
+	ensure!(
+		(protocol::MIN_PROTOCOL_VERSION..=protocol::PROTOCOL_VERSION).contains(&protocol_version),
+		"unsupported protocol version"
+	);
 
 	// Read client_id and datacenter_id from query parameters
 	let client_id = unwrap!(
@@ -0,0 +1,122 @@
+use chirp_workflow::prelude::*;
+use pegboard_ws::verify_token;
+use tivet_config::config::tivet::AccessKind;
+use tivet_operation::prelude::proto::{self, backend::pkg::token};
+use tokio_tungstenite::tungstenite::http::{header, HeaderMap, HeaderValue};
+
+fn headers(authorization: &str) -> HeaderMap {
+	let mut headers = HeaderMap::new();
+	headers.insert(
+		header::AUTHORIZATION,
+		HeaderValue::from_str(authorization).unwrap(),
+	);
+
+	headers
+}
+
+async fn client_token(ctx: &TestCtx, server_id: Option<Uuid>) -> String {
+	ctx.op(cluster::ops::server::token_create::Input {
+		issuer: "test".to_string(),
+		server_id,
+	})
+	.await
+	.unwrap()
+	.token
+}
+
+#[workflow_test]
+async fn missing_token(ctx: TestCtx) {
+	let jwt = &ctx.config().server().unwrap().jwt;
+	let client_id = Uuid::new_v4();
+
+	assert!(verify_token(jwt, &AccessKind::Development, &HeaderMap::new(), client_id).is_ok());
+	assert!(verify_token(jwt, &AccessKind::Public, &HeaderMap::new(), client_id).is_err());
+	assert!(verify_token(jwt, &AccessKind::Private, &HeaderMap::new(), client_id).is_err());
+}
+
+#[workflow_test]
+async fn bad_prefix(ctx: TestCtx) {
+	let jwt = &ctx.config().server().unwrap().jwt;
+	let client_id = Uuid::new_v4();
+	let token = client_token(&ctx, Some(client_id)).await;
+
+	assert!(verify_token(
+		jwt,
+		&AccessKind::Public,
+		&headers(&format!("Bearer {token}")),
+		client_id
+	)
+	.is_ok());
+
+	// A malformed header is not treated as a missing token, even in development
+	for authorization in [
+		token.clone(),
+		format!("Basic {token}"),
+		format!("bearer {token}"),
+	] {
+		assert!(verify_token(
+			jwt,
+			&AccessKind::Development,
+			&headers(&authorization),
+			client_id
+		)
+		.is_err());
+	}
+}
+
+#[workflow_test]
+async fn wrong_client(ctx: TestCtx) {
+	let jwt = &ctx.config().server().unwrap().jwt;
+	let client_id = Uuid::new_v4();
+
+	let other_token = client_token(&ctx, Some(Uuid::new_v4())).await;
+	assert!(verify_token(
+		jwt,
+		&AccessKind::Public,
+		&headers(&format!("Bearer {other_token}")),
+		client_id
+	)
+	.is_err());
+
+	// Server tokens shared by prebake images are not bound to a client
+	let unbound_token = client_token(&ctx, None).await;
+	assert!(verify_token(
+		jwt,
+		&AccessKind::Public,
+		&headers(&format!("Bearer {unbound_token}")),
+		client_id
+	)
+	.is_err());
+}
+
+#[workflow_test]
+async fn wrong_entitlement(ctx: TestCtx) {
+	let jwt = &ctx.config().server().unwrap().jwt;
+
+	let token_res = op!([ctx] token_create {
+		token_config: Some(token::create::request::TokenConfig {
+			ttl: util::duration::hours(1),
+		}),
+		issuer: "test".to_owned(),
+		kind: Some(token::create::request::Kind::New(
+			token::create::request::KindNew { entitlements: vec![proto::claims::Entitlement {
+				kind: Some(proto::claims::entitlement::Kind::Bypass(
+					proto::claims::entitlement::Bypass {}
+				)),
+			}]},
+		)),
+		label: Some("byp".to_owned()),
+		..Default::default()
+	})
+	.await
+	.unwrap();
+	let token = token_res.token.unwrap().token;
+
+	assert!(verify_token(
+		jwt,
+		&AccessKind::Public,
+		&headers(&format!("Bearer {token}")),
+		Uuid::new_v4()
+	)
+	.is_err());
+}
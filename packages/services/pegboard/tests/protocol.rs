@@ -0,0 +1,514 @@
+// Golden file tests for the pegboard wire protocol. Files in `tests/protocol/v*` are what clients of that
+// protocol version send and receive. Golden files of released versions must never be changed.
+
+use std::{net::Ipv4Addr, path::PathBuf};
+
+use pegboard::{
+	client_config::{ClientConfig, Network, ReservedResources},
+	protocol::*,
+	system_info::{
+		Cpu, Memory, Network as SystemNetwork, NetworkData, Os, Storage, StorageDisk, System,
+		SystemInfo,
+	},
+};
+use uuid::Uuid;
+
+const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+const DIGEST: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+enum Packet {
+	ToClient(ToClient),
+	ToServer(ToServer),
+}
+
+impl Packet {
+	fn serialize(&self, protocol_version: u16) -> Vec<u8> {
+		match self {
+			Packet::ToClient(packet) => packet.serialize(protocol_version).unwrap(),
+			Packet::ToServer(packet) => packet.serialize(protocol_version).unwrap(),
+		}
+	}
+
+	fn deserialize_as(&self, protocol_version: u16, buf: &[u8]) -> Packet {
+		match self {
+			Packet::ToClient(_) => {
+				Packet::ToClient(ToClient::deserialize(protocol_version, buf).unwrap())
+			}
+			Packet::ToServer(_) => {
+				Packet::ToServer(ToServer::deserialize(protocol_version, buf).unwrap())
+			}
+		}
+	}
+}
+
+#[test]
+fn encode() {
+	for protocol_version in MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION {
+		for (name, packet) in packets(protocol_version) {
+			let buf = packet.serialize(protocol_version);
+
+			assert_golden(protocol_version, name, &buf);
+		}
+	}
+}
+
+#[test]
+fn decode() {
+	for protocol_version in MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION {
+		for (name, packet) in packets(protocol_version) {
+			let buf = std::fs::read(golden_path(protocol_version, name)).unwrap();
+
+			// Decoding and encoding again must be lossless
+			let decoded = packet.deserialize_as(protocol_version, &buf);
+			assert_golden(protocol_version, name, &decoded.serialize(protocol_version));
+		}
+	}
+}
+
+#[test]
+fn unsupported_version() {
+	let packet = ToClient::Init { last_event_idx: 0 };
+
+	assert!(matches!(
+		packet.serialize(MIN_PROTOCOL_VERSION - 1),
+		Err(PegboardProtocolError::UnsupportedVersion(_))
+	));
+	assert!(matches!(
+		ToClient::deserialize(PROTOCOL_VERSION + 1, &[]),
+		Err(PegboardProtocolError::UnsupportedVersion(_))
+	));
+}
+
+#[test]
+fn unsupported_image_compression() {
+	let packet = ToClient::Commands(vec![CommandWrapper {
+		index: 1,
+		inner: Raw::new(&Command::StartActor {
+			actor_id: Uuid::from_u128(1),
+			config: Box::new(actor_config(4)),
+		})
+		.unwrap(),
+		traceparent: None,
+	}]);
+
+	for protocol_version in MIN_PROTOCOL_VERSION..4 {
+		assert!(matches!(
+			packet.serialize(protocol_version),
+			Err(PegboardProtocolError::UnsupportedPacket(3))
+		));
+	}
+}
+
+#[test]
+fn unsupported_packet() {
+	let packet = ToServer::ActorMetrics(vec![ActorMetricsEntry {
+		actor_id: Uuid::from_u128(1),
+		metrics: actor_metrics(),
+	}]);
+
+	for protocol_version in MIN_PROTOCOL_VERSION..7 {
+		assert!(matches!(
+			packet.serialize(protocol_version),
+			Err(PegboardProtocolError::UnsupportedPacket(_))
+		));
+	}
+
+	let volume_id = Uuid::from_u128(8);
+	let packets = [
+		ToClient::Commands(vec![CommandWrapper {
+			index: 1,
+			inner: Raw::new(&Command::StartActor {
+				actor_id: Uuid::from_u128(1),
+				config: Box::new(actor_config(6)),
+			})
+			.unwrap(),
+			traceparent: None,
+		}]),
+		ToClient::Commands(vec![CommandWrapper {
+			index: 1,
+			inner: Raw::new(&Command::DeleteVolume { volume_id }).unwrap(),
+			traceparent: None,
+		}]),
+	];
+
+	for packet in packets {
+		for protocol_version in MIN_PROTOCOL_VERSION..6 {
+			assert!(matches!(
+				packet.serialize(protocol_version),
+				Err(PegboardProtocolError::UnsupportedPacket(5))
+			));
+		}
+	}
+
+	let packet = ToClient::Commands(vec![CommandWrapper {
+		index: 1,
+		inner: Raw::new(&Command::StartActor {
+			actor_id: Uuid::from_u128(1),
+			config: Box::new(actor_config(7)),
+		})
+		.unwrap(),
+		traceparent: None,
+	}]);
+
+	for protocol_version in MIN_PROTOCOL_VERSION..7 {
+		assert!(matches!(
+			packet.serialize(protocol_version),
+			Err(PegboardProtocolError::UnsupportedPacket(6))
+		));
+	}
+}
+
+fn golden_path(protocol_version: u16, name: &str) -> PathBuf {
+	let ext = if protocol_version == 2 { "json" } else { "bin" };
+
+	PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+		.join("tests")
+		.join("protocol")
+		.join(format!("v{protocol_version}"))
+		.join(format!("{name}.{ext}"))
+}
+
+fn assert_golden(protocol_version: u16, name: &str, buf: &[u8]) {
+	let golden = std::fs::read(golden_path(protocol_version, name)).unwrap();
+
+	if protocol_version == 2 {
+		// Compare JSON by value, golden files are pretty printed
+		let golden = serde_json::from_slice::<serde_json::Value>(&golden).unwrap();
+		let actual = serde_json::from_slice::<serde_json::Value>(buf).unwrap();
+		assert_eq!(
+			golden, actual,
+			"v{protocol_version} {name} does not match golden file"
+		);
+	} else {
+		assert_eq!(
+			golden, buf,
+			"v{protocol_version} {name} does not match golden file"
+		);
+	}
+}
+
+/// Packets to test for the given protocol version. Packets that cannot be represented in older versions
+/// are only included from the version that added them.
+fn packets(protocol_version: u16) -> Vec<(&'static str, Packet)> {
+	let actor_id = Uuid::from_u128(1);
+	let image_id = Uuid::from_u128(2);
+	let volume_id = Uuid::from_u128(8);
+	let snapshot_id = Uuid::from_u128(10);
+
+	let start_actor = Command::StartActor {
+		actor_id,
+		config: Box::new(actor_config(protocol_version)),
+	};
+	let signal_actor = Command::SignalActor {
+		actor_id,
+		signal: 15,
+		persist_storage: true,
+		ignore_future_state: false,
+	};
+	// Not supported before v6
+	let snapshot_volume = Command::SnapshotVolume {
+		volume_id,
+		snapshot_id,
+		upload_url: "https://s3.example.com/volumes/10".to_string(),
+	};
+
+	let running = Event::ActorStateUpdate {
+		actor_id,
+		state: ActorState::Running {
+			pid: 4242,
+			ports: [(
+				"http".to_string(),
+				ProxiedPort {
+					source: 20001,
+					target: 8080,
+					lan_hostname: "10.0.0.2".to_string(),
+					protocol: TransportProtocol::Tcp,
+				},
+			)]
+			.into_iter()
+			.collect(),
+		},
+	};
+	let exited = Event::ActorStateUpdate {
+		actor_id,
+		state: ActorState::Exited { exit_code: Some(0) },
+	};
+	// Dropped before v5
+	let metrics = Event::ActorMetrics {
+		actor_id,
+		metrics: actor_metrics(),
+	};
+	// Not supported before v6
+	let volume_snapshot = Event::VolumeSnapshotUpdate {
+		volume_id,
+		snapshot_id,
+		state: VolumeSnapshotState::Complete { size: 1048576 },
+	};
+
+	let mut commands = vec![
+		CommandWrapper {
+			index: 1,
+			inner: Raw::new(&start_actor).unwrap(),
+			traceparent: None,
+		},
+		CommandWrapper {
+			index: 2,
+			inner: Raw::new(&signal_actor).unwrap(),
+			traceparent: Some(TRACEPARENT.to_string()),
+		},
+	];
+	let mut events = vec![
+		EventWrapper {
+			index: 1,
+			inner: Raw::new(&running).unwrap(),
+			traceparent: Some(TRACEPARENT.to_string()),
+		},
+		EventWrapper {
+			index: 2,
+			inner: Raw::new(&exited).unwrap(),
+			traceparent: None,
+		},
+		EventWrapper {
+			index: 3,
+			inner: Raw::new(&metrics).unwrap(),
+			traceparent: None,
+		},
+	];
+	if protocol_version >= 6 {
+		commands.push(CommandWrapper {
+			index: 3,
+			inner: Raw::new(&snapshot_volume).unwrap(),
+			traceparent: None,
+		});
+		events.push(EventWrapper {
+			index: 4,
+			inner: Raw::new(&volume_snapshot).unwrap(),
+			traceparent: None,
+		});
+	}
+
+	let mut packets = vec![
+		(
+			"to_client_init",
+			Packet::ToClient(ToClient::Init { last_event_idx: 2 }),
+		),
+		(
+			"to_client_commands",
+			Packet::ToClient(ToClient::Commands(commands)),
+		),
+		(
+			"to_client_prewarm_image",
+			Packet::ToClient(ToClient::PrewarmImage {
+				image_id,
+				image_artifact_url_stub: "/s3-cache/images/2".to_string(),
+				image_digest: Some(DIGEST.to_string()),
+			}),
+		),
+		(
+			"to_server_init",
+			Packet::ToServer(ToServer::Init {
+				last_command_idx: 2,
+				config: ClientConfig {
+					network: Network {
+						bind_ip: Ipv4Addr::new(10, 0, 0, 2).into(),
+						lan_hostname: "10.0.0.2".to_string(),
+						wan_hostname: "203.0.113.1".to_string(),
+						lan_port_range_min: 20000,
+						lan_port_range_max: 20100,
+						wan_port_range_min: 20200,
+						wan_port_range_max: 20300,
+					},
+					reserved_resources: ReservedResources {
+						cpu: 500,
+						memory: 512,
+					},
+				},
+				system: SystemInfo {
+					system: System {
+						boot_time: 1700000000,
+					},
+					cpu: Cpu {
+						vendor_id: Some("GenuineIntel".to_string()),
+						frequency: Some(2400),
+						cpu_arch: None,
+						physical_core_count: 4,
+					},
+					memory: Memory {
+						total_memory: 8589934592,
+						total_swap: 0,
+					},
+					os: Os {
+						name: Some("Debian GNU/Linux".to_string()),
+						distribution_id: "debian".to_string(),
+						long_os_version: None,
+						os_version: Some("12".to_string()),
+						kernel_version: Some("6.1.0".to_string()),
+					},
+					network: SystemNetwork {
+						hostname: Some("node-1".to_string()),
+						networks: vec![NetworkData {
+							name: "eth0".to_string(),
+							ip_networks: vec!["10.0.0.2/24".to_string()],
+							mac_address: "02:00:00:00:00:01".to_string(),
+						}],
+					},
+					storage: Storage {
+						disks: vec![StorageDisk {
+							name: "/dev/sda1".to_string(),
+							file_system: "ext4".to_string(),
+							kind: "SSD".to_string(),
+							available_space: 100000000000,
+							total_space: 200000000000,
+						}],
+					},
+				},
+			}),
+		),
+		(
+			"to_server_events",
+			Packet::ToServer(ToServer::Events(events)),
+		),
+	];
+
+	if protocol_version >= 7 {
+		packets.push((
+			"to_server_actor_metrics",
+			Packet::ToServer(ToServer::ActorMetrics(vec![ActorMetricsEntry {
+				actor_id,
+				metrics: actor_metrics(),
+			}])),
+		));
+	}
+
+	packets
+}
+
+fn actor_config(protocol_version: u16) -> ActorConfig {
+	let actor_id = Uuid::from_u128(1);
+	let volume_id = Uuid::from_u128(8);
+
+	ActorConfig {
+		image: image(protocol_version),
+		root_user_enabled: false,
+		resources: Resources {
+			cpu: 1000,
+			memory: 268435456,
+			memory_max: 536870912,
+			disk: 512,
+			pids: Some(1024),
+			io_weight: Some(100),
+			io_read_bps: Some(104857600),
+			io_write_bps: None,
+			nofile: Some(4096),
+			egress_bps: Some(12500000),
+		},
+		env: [("PORT".to_string(), "8080".to_string())]
+			.into_iter()
+			.collect(),
+		// Not supported before v7
+		secrets: if protocol_version >= 7 {
+			[("API_KEY".to_string(), "hunter2".to_string())]
+				.into_iter()
+				.collect()
+		} else {
+			Default::default()
+		},
+		ports: [(
+			"http".to_string(),
+			Port {
+				target: Some(8080),
+				protocol: TransportProtocol::Tcp,
+				routing: PortRouting::GameGuard,
+			},
+		)]
+		.into_iter()
+		.collect(),
+		network_mode: NetworkMode::Bridge,
+		owner: ActorOwner::DynamicServer {
+			server_id: Uuid::from_u128(3),
+		},
+		metadata: Raw::new(&ActorMetadata {
+			actor: ActorMetadataActor {
+				actor_id,
+				tags: [("name".to_string(), "test".to_string())]
+					.into_iter()
+					.collect(),
+				create_ts: 1700000000000,
+			},
+			project: ActorMetadataProject {
+				project_id: Uuid::from_u128(4),
+				slug: "project".to_string(),
+			},
+			environment: ActorMetadataEnvironment {
+				env_id: Uuid::from_u128(5),
+				slug: "prod".to_string(),
+			},
+			datacenter: ActorMetadataDatacenter {
+				name_id: "local".to_string(),
+				display_name: "Local".to_string(),
+			},
+			cluster: ActorMetadataCluster {
+				cluster_id: Uuid::from_u128(6),
+			},
+			build: ActorMetadataBuild {
+				build_id: Uuid::from_u128(7),
+			},
+		})
+		.unwrap(),
+		// Not supported before v6
+		volumes: if protocol_version >= 6 {
+			vec![Volume {
+				volume_id,
+				path: "/data".to_string(),
+				size: 1024,
+				snapshot: Some(VolumeSnapshot {
+					snapshot_id: Uuid::from_u128(9),
+					url: "https://s3.example.com/volumes/9".to_string(),
+				}),
+			}]
+		} else {
+			Vec::new()
+		},
+	}
+}
+
+/// Zstd images can only be started from v4.
+fn image(protocol_version: u16) -> Image {
+	let image = Image {
+		id: Uuid::from_u128(2),
+		artifact_url_stub: "/s3-cache/images/2".to_string(),
+		fallback_artifact_url: Some("https://s3.example.com/images/2".to_string()),
+		kind: ImageKind::DockerImage,
+		compression: ImageCompression::Zstd,
+		digest: Some(DIGEST.to_string()),
+		compression_dictionary: Some(ImageArtifact {
+			artifact_url_stub: "/s3-cache/dictionaries/3".to_string(),
+			fallback_artifact_url: None,
+		}),
+	};
+
+	if protocol_version < 4 {
+		Image {
+			compression: ImageCompression::Lz4,
+			compression_dictionary: None,
+			..image
+		}
+	} else {
+		image
+	}
+}
+
+fn actor_metrics() -> ActorMetrics {
+	ActorMetrics {
+		start_ts: 1700000000000,
+		end_ts: 1700000060000,
+		cpu_avg: 250,
+		cpu_max: 1000,
+		memory_avg: 134217728,
+		memory_max: 201326592,
+		network_rx_bytes: Some(4096),
+		network_tx_bytes: Some(8192),
+		disk_read_bytes: None,
+		disk_write_bytes: None,
+	}
+}
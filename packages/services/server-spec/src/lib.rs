@@ -10,3 +10,8 @@ pub const PEGBOARD_ISOLATE_RESERVE_MEMORY_MIB: u32 = 64;
 
 pub const LINODE_CPU_PER_CORE: u32 = 1999;
 pub const LINODE_DISK_PER_CORE: u32 = 8192;
+/// MiB/s
+pub const LINODE_DISK_BANDWIDTH_PER_CORE: u32 = 64;
+
+pub const PEGBOARD_CONTAINER_PIDS_PER_CORE: u32 = 1024;
+pub const PEGBOARD_CONTAINER_NOFILE_PER_CORE: u32 = 4096;
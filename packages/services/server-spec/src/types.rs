@@ -1,6 +1,6 @@
 use crate::{
-	LINODE_CPU_PER_CORE, LINODE_DISK_PER_CORE, NOMAD_RESERVE_MEMORY_MIB,
-	PEGBOARD_CONTAINER_RESERVE_MEMORY_MIB, RESERVE_LB_MEMORY_MIB,
+	LINODE_CPU_PER_CORE, LINODE_DISK_BANDWIDTH_PER_CORE, LINODE_DISK_PER_CORE,
+	NOMAD_RESERVE_MEMORY_MIB, PEGBOARD_CONTAINER_RESERVE_MEMORY_MIB, RESERVE_LB_MEMORY_MIB,
 };
 
 /// Provider agnostic hardware specs.
@@ -52,6 +52,11 @@ impl ServerSpec {
 		LINODE_DISK_PER_CORE
 	}
 
+	/// MiB/s
+	pub fn disk_bandwidth_per_core(&self) -> u32 {
+		LINODE_DISK_BANDWIDTH_PER_CORE
+	}
+
 	pub fn bandwidth_per_core(&self) -> u32 {
 		self.bandwidth / self.cpu_cores
 	}
@@ -1,5 +1,7 @@
 use chirp_workflow::prelude::*;
-use server_spec::types::ServerSpec;
+use server_spec::{
+	types::ServerSpec, PEGBOARD_CONTAINER_NOFILE_PER_CORE, PEGBOARD_CONTAINER_PIDS_PER_CORE,
+};
 
 use crate::types::Tier;
 
@@ -77,5 +79,14 @@ fn generate_tier(
 		),
 		disk: c.disk_per_core() * numerator / denominator,
 		bandwidth: c.bandwidth_per_core() * numerator / denominator,
+		disk_bandwidth: c.disk_bandwidth_per_core() * numerator / denominator,
+		// Same ratio as cpu shares, see pegboard manager's oci config
+		io_weight: (100 * numerator / denominator).clamp(10, 1000) as u16,
+		pids: PEGBOARD_CONTAINER_PIDS_PER_CORE * numerator / denominator,
+		// Never lower than the previous fixed limit of 1024
+		nofile: u32::max(
+			PEGBOARD_CONTAINER_NOFILE_PER_CORE * numerator / denominator,
+			1024,
+		),
 	}
 }
@@ -15,4 +15,10 @@ pub struct Tier {
 	pub disk: u32,
 	// MiB
 	pub bandwidth: u32,
+	// MiB/s
+	pub disk_bandwidth: u32,
+	// Relative block I/O weight [10, 1000]
+	pub io_weight: u16,
+	pub pids: u32,
+	pub nofile: u32,
 }
@@ -38,7 +38,7 @@ async fn handle(
 
 			// Validate the token
 			let refresh_token_claims =
-				tivet_claims::decode(&ctx.config().server()?.jwt.public, refresh_token)??;
+				tivet_claims::decode(&ctx.config().server()?.jwt, refresh_token)??;
 			let refresh_jti = unwrap!(refresh_token_claims.jti).as_uuid();
 			let refresh_ent = refresh_token_claims.as_refresh()?;
 
@@ -246,14 +246,16 @@ async fn create_token(
 
 	// Encode token
 	tracing::debug!(?claims, "encoding");
+	let jwt = &ctx.config().server()?.jwt;
 	let token = encode(
 		label,
 		&Header {
 			alg: tivet_claims::ALGORITHM,
+			kid: jwt.kid.clone(),
 			..Default::default()
 		},
 		&claims,
-		&EncodingKey::from_ed_pem(ctx.config().server()?.jwt.private.read().as_bytes())?,
+		&EncodingKey::from_ed_pem(jwt.private.read().as_bytes())?,
 	)?;
 
 	// Write to database
@@ -37,12 +37,12 @@ async fn new_and_reload(ctx: TestCtx) {
 	.expect("create new token");
 
 	let _ = tivet_claims::decode(
-		&ctx.config().server()?.jwt.public,
+		&ctx.config().server()?.jwt,
 		&new_res.token.as_ref().unwrap().token,
 	)
 	.expect("decode new token");
 	let _ = tivet_claims::decode(
-		&ctx.config().server()?.jwt.public,
+		&ctx.config().server()?.jwt,
 		&new_res.refresh_token.as_ref().unwrap().token,
 	)
 	.expect("decode refresh token");
@@ -96,7 +96,7 @@ async fn new_and_reload(ctx: TestCtx) {
 	// Decode and validate the tokens
 	{
 		let user_token = tivet_claims::decode(
-			&ctx.config().server()?.jwt.public,
+			&ctx.config().server()?.jwt,
 			&new_res.token.as_ref().unwrap().token,
 		)
 		.expect("decode user token")
@@ -105,7 +105,7 @@ async fn new_and_reload(ctx: TestCtx) {
 		assert_eq!(user_id, user_ent.user_id, "mismatching user id");
 
 		let refresh_token = tivet_claims::decode(
-			&ctx.config().server()?.jwt.public,
+			&ctx.config().server()?.jwt,
 			&new_res.refresh_token.as_ref().unwrap().token,
 		)
 		.expect("decode user token")
@@ -148,7 +148,7 @@ async fn new_and_reload_combined(ctx: TestCtx) {
 	.expect("create new token");
 
 	let _ = tivet_claims::decode(
-		&ctx.config().server()?.jwt.public,
+		&ctx.config().server()?.jwt,
 		&new_res.token.as_ref().unwrap().token,
 	)
 	.expect("decode new token");
@@ -191,7 +191,7 @@ async fn new_and_reload_combined(ctx: TestCtx) {
 	// Decode and validate the tokens
 	{
 		let user_token = tivet_claims::decode(
-			&ctx.config().server()?.jwt.public,
+			&ctx.config().server()?.jwt,
 			&new_res.token.as_ref().unwrap().token,
 		)
 		.expect("decode user token")
@@ -0,0 +1,8 @@
+ALTER TABLE workflows
+	ADD COLUMN traceparent TEXT;
+
+ALTER TABLE signals
+	ADD COLUMN traceparent TEXT;
+
+ALTER TABLE tagged_signals
+	ADD COLUMN traceparent TEXT;
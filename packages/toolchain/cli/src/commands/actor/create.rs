@@ -55,12 +55,20 @@ pub struct Opts {
 	#[clap(long = "env-var")]
 	env_vars: Option<Vec<String>>,
 
+	/// Env var set to the value of a secret, e.g. `DATABASE_URL=db-url`. See `tivet secret`.
+	#[clap(long = "secret")]
+	secrets: Option<Vec<String>>,
+
 	#[clap(long, value_enum)]
 	network_mode: Option<NetworkMode>,
 
 	#[clap(long = "port", short = 'p')]
 	ports: Option<Vec<String>>,
 
+	/// Persistent volume to mount, e.g. `name=data,path=/data,size=1024`. Size is in MiB.
+	#[clap(long = "volume")]
+	volumes: Option<Vec<String>>,
+
 	#[clap(long)]
 	cpu: Option<i32>,
 
@@ -147,6 +155,20 @@ impl Opts {
 			})
 			.transpose()?;
 
+		// Parse volumes
+		let volumes = self
+			.volumes
+			.as_ref()
+			.map(|volumes| {
+				volumes
+					.iter()
+					.map(|volume_str| {
+						kv_str::from_str::<models::ActorCreateActorVolumeRequest>(volume_str)
+					})
+					.collect::<Result<Vec<_>>>()
+			})
+			.transpose()?;
+
 		// Parse environment variables
 		let env_vars = self
 			.env_vars
@@ -163,6 +185,23 @@ impl Opts {
 			})
 			.transpose()?;
 
+		// Parse secret references
+		let secrets = self
+			.secrets
+			.as_ref()
+			.map(|secrets| {
+				secrets
+					.iter()
+					.map(|secret| {
+						secret
+							.split_once('=')
+							.map(|(k, v)| (k.to_string(), v.to_string()))
+							.with_context(|| anyhow!("invalid secret value: {secret}"))
+					})
+					.collect::<Result<HashMap<String, String>>>()
+			})
+			.transpose()?;
+
 		// Auto-deploy
 		if self.deploy {
 			// Remove build tags, since we'll be using the build ID
@@ -174,6 +213,8 @@ impl Opts {
 			let deploy_build_ids = crate::util::deploy::deploy(crate::util::deploy::DeployOpts {
 				environment: &env,
 				build_tags: Some(build_tags),
+				plan: false,
+				force: false,
 			})
 			.await?;
 
@@ -227,7 +268,16 @@ impl Opts {
 		};
 
 		let resources = match (self.cpu, self.memory) {
-			(Some(cpu), Some(memory)) => Some(Box::new(models::ActorResources { cpu, memory })),
+			(Some(cpu), Some(memory)) => Some(Box::new(models::ActorResources {
+				cpu,
+				memory,
+				pids: None,
+				io_weight: None,
+				disk_read_bandwidth: None,
+				disk_write_bandwidth: None,
+				nofile: None,
+				egress_bandwidth: None,
+			})),
 			(Some(_), None) | (None, Some(_)) => {
 				return Err(errors::UserError::new("Must define both --cpu and --memory").into())
 			}
@@ -241,6 +291,7 @@ impl Opts {
 			build_tags: build_tags.map(|bt| Some(serde_json::json!(bt))),
 			runtime: Some(Box::new(models::ActorCreateActorRuntimeRequest {
 				environment: env_vars,
+				secrets,
 			})),
 			network: Some(Box::new(models::ActorCreateActorNetworkRequest {
 				mode: self.network_mode.as_ref().map(|mode| match mode {
@@ -254,6 +305,7 @@ impl Opts {
 				durable: Some(self.durable),
 				kill_timeout: self.kill_timeout,
 			})),
+			volumes,
 		};
 
 		let response = apis::actor_api::actor_create(
@@ -0,0 +1,118 @@
+use anyhow::*;
+use clap::Parser;
+use toolchain::tivet_api::apis;
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub struct Opts {
+	#[clap(index = 1)]
+	id: String,
+
+	#[clap(long, alias = "env", short = 'e')]
+	environment: Option<String>,
+
+	/// Unix timestamp in milliseconds. Defaults to one hour before `end`.
+	#[clap(long)]
+	start: Option<i64>,
+
+	/// Unix timestamp in milliseconds. Defaults to now.
+	#[clap(long)]
+	end: Option<i64>,
+}
+
+impl Opts {
+	pub async fn execute(&self) -> Result<()> {
+		let ctx = crate::util::login::load_or_login().await?;
+
+		let env = crate::util::env::get_or_select(&ctx, self.environment.as_ref()).await?;
+
+		let actor_id = Uuid::parse_str(&self.id).context("invalid id uuid")?;
+
+		let res = apis::actor_metrics_api::actor_metrics_get(
+			&ctx.openapi_config_cloud,
+			&actor_id.to_string(),
+			Some(&ctx.project.name_id),
+			Some(&env),
+			self.start,
+			self.end,
+		)
+		.await?;
+
+		if res.metrics.is_empty() {
+			eprintln!("No metrics in range");
+			return Ok(());
+		}
+
+		table::metrics(res.metrics);
+
+		Ok(())
+	}
+}
+
+mod table {
+	use tabled::Tabled;
+	use toolchain::tivet_api::models;
+
+	#[derive(Tabled)]
+	struct MetricsTableRow {
+		pub start: String,
+		pub end: String,
+		#[tabled(rename = "cpu avg (cores)")]
+		pub cpu_avg: String,
+		#[tabled(rename = "cpu max (cores)")]
+		pub cpu_max: String,
+		#[tabled(rename = "memory avg")]
+		pub memory_avg: String,
+		#[tabled(rename = "memory max")]
+		pub memory_max: String,
+		#[tabled(rename = "net rx")]
+		pub network_rx: String,
+		#[tabled(rename = "net tx")]
+		pub network_tx: String,
+		#[tabled(rename = "disk read")]
+		pub disk_read: String,
+		#[tabled(rename = "disk write")]
+		pub disk_write: String,
+	}
+
+	pub fn metrics(metrics: Vec<models::ActorActorMetrics>) {
+		let rows = metrics
+			.into_iter()
+			.map(|window| MetricsTableRow {
+				start: window.start_ts,
+				end: window.end_ts,
+				cpu_avg: cores(window.cpu_avg),
+				cpu_max: cores(window.cpu_max),
+				memory_avg: bytes(Some(window.memory_avg)),
+				memory_max: bytes(Some(window.memory_max)),
+				network_rx: bytes(window.network_rx_bytes),
+				network_tx: bytes(window.network_tx_bytes),
+				disk_read: bytes(window.disk_read_bytes),
+				disk_write: bytes(window.disk_write_bytes),
+			})
+			.collect::<Vec<_>>();
+
+		tivet_term::format::table(rows);
+	}
+
+	fn cores(x: impl Into<i64>) -> String {
+		format!("{:.2}", x.into() as f64 / 1000.0)
+	}
+
+	/// Formats bytes with a binary unit. Unset values were not measured.
+	fn bytes(x: Option<impl Into<i64>>) -> String {
+		let Some(x) = x else {
+			return "-".to_string();
+		};
+
+		let mut value = x.into() as f64;
+		for unit in ["B", "KiB", "MiB", "GiB"] {
+			if value < 1024.0 {
+				return format!("{value:.1} {unit}");
+			}
+			value /= 1024.0;
+		}
+
+		format!("{value:.1} TiB")
+	}
+}
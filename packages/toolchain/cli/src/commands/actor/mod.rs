@@ -3,6 +3,7 @@ pub mod destroy;
 pub mod get;
 pub mod list;
 pub mod logs;
+pub mod metrics;
 
 use anyhow::*;
 use clap::Subcommand;
@@ -14,6 +15,7 @@ pub enum SubCommand {
 	Destroy(destroy::Opts),
 	List(list::Opts),
 	Logs(logs::Opts),
+	Metrics(metrics::Opts),
 }
 
 impl SubCommand {
@@ -24,6 +26,7 @@ impl SubCommand {
 			SubCommand::Destroy(opts) => opts.execute().await,
 			SubCommand::List(opts) => opts.execute().await,
 			SubCommand::Logs(opts) => opts.execute().await,
+			SubCommand::Metrics(opts) => opts.execute().await,
 		}
 	}
 }
@@ -146,6 +146,7 @@ impl Opts {
 				build_name: self.name.clone(),
 				runtime,
 				access: self.access.clone().unwrap_or(config::BuildAccess::Private),
+				mode: build_publish::Mode::Always,
 			},
 		)
 		.await?;
@@ -9,6 +9,14 @@ pub struct Opts {
 
 	#[clap(long, short = 't')]
 	tags: Option<String>,
+
+	/// Show which builds would be uploaded & re-tagged without deploying.
+	#[clap(long)]
+	plan: bool,
+
+	/// Upload every build, even if it's unchanged from the current build.
+	#[clap(long, conflicts_with = "plan")]
+	force: bool,
 }
 
 impl Opts {
@@ -27,6 +35,8 @@ impl Opts {
 		crate::util::deploy::deploy(crate::util::deploy::DeployOpts {
 			environment: &env,
 			build_tags,
+			plan: self.plan,
+			force: self.force,
 		})
 		.await?;
 
@@ -10,6 +10,9 @@ pub mod manager;
 pub mod metadata;
 pub mod project;
 pub mod region;
+pub mod replica_set;
+pub mod rollback;
+pub mod secret;
 
 use anyhow::*;
 use clap::Parser;
@@ -25,6 +28,7 @@ pub enum SubCommand {
 	Deploy(deploy::Opts),
 	#[clap(alias = "p")]
 	Publish(build::publish::Opts),
+	Rollback(rollback::Opts),
 	#[clap(alias = "e", alias = "env")]
 	Environment {
 		#[clap(subcommand)]
@@ -49,6 +53,15 @@ pub enum SubCommand {
 		#[clap(subcommand)]
 		subcommand: region::SubCommand,
 	},
+	#[clap(alias = "rs")]
+	ReplicaSet {
+		#[clap(subcommand)]
+		subcommand: replica_set::SubCommand,
+	},
+	Secret {
+		#[clap(subcommand)]
+		subcommand: secret::SubCommand,
+	},
 	Manager {
 		#[clap(subcommand)]
 		subcommand: manager::SubCommand,
@@ -75,11 +88,14 @@ impl SubCommand {
 			SubCommand::Logout(opts) => opts.execute().await,
 			SubCommand::Deploy(opts) => opts.execute().await,
 			SubCommand::Publish(opts) => opts.execute().await,
+			SubCommand::Rollback(opts) => opts.execute().await,
 			SubCommand::Environment { subcommand } => subcommand.execute().await,
 			SubCommand::Project { subcommand } => subcommand.execute().await,
 			SubCommand::Actor { subcommand } => subcommand.execute().await,
 			SubCommand::Build { subcommand } => subcommand.execute().await,
 			SubCommand::Region { subcommand } => subcommand.execute().await,
+			SubCommand::ReplicaSet { subcommand } => subcommand.execute().await,
+			SubCommand::Secret { subcommand } => subcommand.execute().await,
 			SubCommand::Manager { subcommand } => subcommand.execute().await,
 			SubCommand::Metadata { subcommand } => subcommand.execute().await,
 			SubCommand::Deno(opts) => opts.execute().await,
@@ -0,0 +1,180 @@
+use anyhow::*;
+use clap::Parser;
+use std::collections::HashMap;
+use toolchain::{
+	errors,
+	tivet_api::{apis, models},
+};
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub struct Opts {
+	#[clap(long, alias = "env", short = 'e')]
+	environment: Option<String>,
+
+	#[clap(long, short = 'r')]
+	region: String,
+
+	/// Tags to use for both the actor & build tags.
+	#[clap(long = "tags", short = 't')]
+	universal_tags: Option<String>,
+
+	#[clap(long, short = 'a')]
+	actor_tags: Option<String>,
+
+	/// Build ID.
+	#[clap(long)]
+	build: Option<String>,
+
+	#[clap(long, short = 'b')]
+	build_tags: Option<String>,
+
+	#[clap(long = "env-var")]
+	env_vars: Option<Vec<String>>,
+
+	/// Env var set to the value of a secret, e.g. `DATABASE_URL=db-url`. See `tivet secret`.
+	#[clap(long = "secret")]
+	secrets: Option<Vec<String>>,
+
+	#[clap(long)]
+	cpu: Option<i32>,
+
+	#[clap(long)]
+	memory: Option<i32>,
+
+	#[clap(long)]
+	kill_timeout: Option<i64>,
+
+	/// Number of actors to keep running.
+	#[clap(long)]
+	replicas: i32,
+
+	/// Max number of actors created above `replicas` while rolling out a new build.
+	#[clap(long)]
+	max_surge: Option<i32>,
+
+	/// Max number of actors that can be unavailable while rolling out a new build.
+	#[clap(long)]
+	max_unavailable: Option<i32>,
+
+	/// How long in milliseconds an actor must be ready for before it counts as available.
+	#[clap(long)]
+	min_ready: Option<i64>,
+}
+
+impl Opts {
+	pub async fn execute(&self) -> Result<()> {
+		let ctx = crate::util::login::load_or_login().await?;
+
+		let env = crate::util::env::get_or_select(&ctx, self.environment.as_ref()).await?;
+
+		// Parse tags
+		let actor_tags = self
+			.actor_tags
+			.as_ref()
+			.or(self.universal_tags.as_ref())
+			.map(|t| kv_str::from_str::<HashMap<String, String>>(t))
+			.transpose()?
+			.unwrap_or_default();
+		let build_tags = self
+			.build_tags
+			.as_ref()
+			.or(self.universal_tags.as_ref())
+			.map(|t| kv_str::from_str::<HashMap<String, String>>(t))
+			.transpose()?;
+
+		let build_id = self
+			.build
+			.as_ref()
+			.map(|b| Uuid::parse_str(&b))
+			.transpose()
+			.context("invalid build uuid")?;
+
+		// Parse environment variables
+		let env_vars = self
+			.env_vars
+			.as_ref()
+			.map(|env_vars| {
+				env_vars
+					.iter()
+					.map(|env| {
+						env.split_once('=')
+							.map(|(k, v)| (k.to_string(), v.to_string()))
+							.with_context(|| anyhow!("invalid env value: {env}"))
+					})
+					.collect::<Result<HashMap<String, String>>>()
+			})
+			.transpose()?;
+
+		// Parse secret references
+		let secrets = self
+			.secrets
+			.as_ref()
+			.map(|secrets| {
+				secrets
+					.iter()
+					.map(|secret| {
+						secret
+							.split_once('=')
+							.map(|(k, v)| (k.to_string(), v.to_string()))
+							.with_context(|| anyhow!("invalid secret value: {secret}"))
+					})
+					.collect::<Result<HashMap<String, String>>>()
+			})
+			.transpose()?;
+
+		let resources = match (self.cpu, self.memory) {
+			(Some(cpu), Some(memory)) => Some(Box::new(models::ActorResources {
+				cpu,
+				memory,
+				pids: None,
+				io_weight: None,
+				disk_read_bandwidth: None,
+				disk_write_bandwidth: None,
+				nofile: None,
+				egress_bandwidth: None,
+			})),
+			(Some(_), None) | (None, Some(_)) => {
+				return Err(errors::UserError::new("Must define both --cpu and --memory").into())
+			}
+			(None, None) => None,
+		};
+
+		let request = models::ActorCreateReplicaSetRequest {
+			replicas: self.replicas,
+			rolling_update: Some(Box::new(models::ActorReplicaSetRollingUpdate {
+				max_surge: self.max_surge,
+				max_unavailable: self.max_unavailable,
+				min_ready: self.min_ready,
+			})),
+			actor: Box::new(models::ActorCreateActorRequest {
+				region: Some(self.region.clone()),
+				tags: Some(serde_json::json!(actor_tags)),
+				build: build_id,
+				build_tags: build_tags.map(|bt| Some(serde_json::json!(bt))),
+				runtime: Some(Box::new(models::ActorCreateActorRuntimeRequest {
+					environment: env_vars,
+					secrets,
+				})),
+				network: None,
+				resources,
+				lifecycle: Some(Box::new(models::ActorLifecycle {
+					durable: None,
+					kill_timeout: self.kill_timeout,
+				})),
+				volumes: None,
+			}),
+		};
+
+		let response = apis::actor_replica_sets_api::actor_replica_sets_create(
+			&ctx.openapi_config_cloud,
+			request,
+			Some(&ctx.project.name_id),
+			Some(&env),
+		)
+		.await?;
+		println!("Created replica set:\n{:#?}", response.replica_set);
+
+		Ok(())
+	}
+}
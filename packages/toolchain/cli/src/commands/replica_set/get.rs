@@ -0,0 +1,34 @@
+use anyhow::*;
+use clap::Parser;
+use toolchain::tivet_api::apis;
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub struct Opts {
+	#[clap(index = 1)]
+	id: String,
+
+	#[clap(long, alias = "env", short = 'e')]
+	environment: Option<String>,
+}
+
+impl Opts {
+	pub async fn execute(&self) -> Result<()> {
+		let ctx = crate::util::login::load_or_login().await?;
+
+		let env = crate::util::env::get_or_select(&ctx, self.environment.as_ref()).await?;
+
+		let replica_set_id = Uuid::parse_str(&self.id).context("invalid id uuid")?;
+
+		let res = apis::actor_replica_sets_api::actor_replica_sets_get(
+			&ctx.openapi_config_cloud,
+			&replica_set_id.to_string(),
+			Some(&ctx.project.name_id),
+			Some(&env),
+		)
+		.await?;
+
+		println!("{:#?}", res.replica_set);
+		Ok(())
+	}
+}
@@ -0,0 +1,29 @@
+pub mod create;
+pub mod delete;
+pub mod get;
+pub mod list;
+pub mod update;
+
+use anyhow::*;
+use clap::Subcommand;
+
+#[derive(Subcommand)]
+pub enum SubCommand {
+	Create(create::Opts),
+	Get(get::Opts),
+	Update(update::Opts),
+	Delete(delete::Opts),
+	List(list::Opts),
+}
+
+impl SubCommand {
+	pub async fn execute(&self) -> Result<()> {
+		match &self {
+			SubCommand::Create(opts) => opts.execute().await,
+			SubCommand::Get(opts) => opts.execute().await,
+			SubCommand::Update(opts) => opts.execute().await,
+			SubCommand::Delete(opts) => opts.execute().await,
+			SubCommand::List(opts) => opts.execute().await,
+		}
+	}
+}
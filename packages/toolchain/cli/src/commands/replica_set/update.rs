@@ -0,0 +1,88 @@
+use anyhow::*;
+use clap::Parser;
+use std::collections::HashMap;
+use toolchain::tivet_api::{apis, models};
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub struct Opts {
+	#[clap(index = 1)]
+	id: String,
+
+	#[clap(long, alias = "env", short = 'e')]
+	environment: Option<String>,
+
+	/// Build ID to roll out.
+	#[clap(long)]
+	build: Option<String>,
+
+	/// Tags of the build to roll out.
+	#[clap(long, short = 'b')]
+	build_tags: Option<String>,
+
+	/// Number of actors to keep running.
+	#[clap(long)]
+	replicas: Option<i32>,
+
+	/// Max number of actors created above `replicas` while rolling out a new build.
+	#[clap(long)]
+	max_surge: Option<i32>,
+
+	/// Max number of actors that can be unavailable while rolling out a new build.
+	#[clap(long)]
+	max_unavailable: Option<i32>,
+
+	/// How long in milliseconds an actor must be ready for before it counts as available.
+	#[clap(long)]
+	min_ready: Option<i64>,
+}
+
+impl Opts {
+	pub async fn execute(&self) -> Result<()> {
+		let ctx = crate::util::login::load_or_login().await?;
+
+		let env = crate::util::env::get_or_select(&ctx, self.environment.as_ref()).await?;
+
+		let replica_set_id = Uuid::parse_str(&self.id).context("invalid id uuid")?;
+
+		let build_id = self
+			.build
+			.as_ref()
+			.map(|b| Uuid::parse_str(&b))
+			.transpose()
+			.context("invalid build uuid")?;
+		let build_tags = self
+			.build_tags
+			.as_ref()
+			.map(|t| kv_str::from_str::<HashMap<String, String>>(t))
+			.transpose()?;
+
+		let rolling_update = (self.max_surge.is_some()
+			|| self.max_unavailable.is_some()
+			|| self.min_ready.is_some())
+		.then(|| {
+			Box::new(models::ActorReplicaSetRollingUpdate {
+				max_surge: self.max_surge,
+				max_unavailable: self.max_unavailable,
+				min_ready: self.min_ready,
+			})
+		});
+
+		apis::actor_replica_sets_api::actor_replica_sets_update(
+			&ctx.openapi_config_cloud,
+			&replica_set_id.to_string(),
+			models::ActorUpdateReplicaSetRequest {
+				build: build_id,
+				build_tags: build_tags.map(|bt| Some(serde_json::json!(bt))),
+				replicas: self.replicas,
+				rolling_update,
+			},
+			Some(&ctx.project.name_id),
+			Some(&env),
+		)
+		.await?;
+
+		println!("Updated replica set: {replica_set_id}");
+		Ok(())
+	}
+}
@@ -0,0 +1,63 @@
+use anyhow::*;
+use clap::Parser;
+use toolchain::{errors, tasks::rollback};
+
+use crate::util::task::{run_task, TaskOutputStyle};
+
+/// Moves the `current` tag back to the previous build.
+#[derive(Parser)]
+pub struct Opts {
+	/// Name of the build to roll back. Defaults to every build in the config.
+	#[clap(index = 1)]
+	build: Option<String>,
+
+	#[clap(long, alias = "env", short = 'e')]
+	environment: Option<String>,
+
+	/// Upgrade all actors running the build to the previous build.
+	#[clap(long)]
+	upgrade_actors: bool,
+}
+
+impl Opts {
+	pub async fn execute(&self) -> Result<()> {
+		let ctx = crate::util::login::load_or_login().await?;
+
+		let env = crate::util::env::get_or_select(&ctx, self.environment.as_ref()).await?;
+		let env_id = ctx
+			.project
+			.namespaces
+			.iter()
+			.find(|x| x.name_id == env)
+			.map(|x| x.namespace_id)
+			.ok_or_else(|| errors::UserError::new(format!("environment not found: {env}")))?;
+
+		let build_names = if let Some(build) = &self.build {
+			vec![build.clone()]
+		} else {
+			let config = toolchain::config::Config::load(None).await?;
+			let mut build_names = config.builds.keys().cloned().collect::<Vec<_>>();
+			build_names.sort();
+			build_names
+		};
+
+		let output = run_task::<rollback::Task>(
+			TaskOutputStyle::PlainNoResult,
+			rollback::Input {
+				environment_id: env_id,
+				build_names,
+				upgrade_actors: self.upgrade_actors,
+			},
+		)
+		.await?;
+
+		for rollback in output.rollbacks {
+			println!(
+				"Rolled back {}: {} -> {}",
+				rollback.build_name, rollback.from_build_id, rollback.to_build_id
+			);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,31 @@
+use anyhow::*;
+use clap::Parser;
+use toolchain::tivet_api::apis;
+
+#[derive(Parser)]
+pub struct Opts {
+	#[clap(index = 1)]
+	name: String,
+
+	#[clap(long, alias = "env", short = 'e')]
+	environment: Option<String>,
+}
+
+impl Opts {
+	pub async fn execute(&self) -> Result<()> {
+		let ctx = crate::util::login::load_or_login().await?;
+
+		let env = crate::util::env::get_or_select(&ctx, self.environment.as_ref()).await?;
+
+		apis::actor_secrets_api::actor_secrets_delete(
+			&ctx.openapi_config_cloud,
+			&self.name,
+			Some(&ctx.project.name_id),
+			Some(&env),
+		)
+		.await?;
+
+		println!("Deleted secret: {}", self.name);
+		Ok(())
+	}
+}
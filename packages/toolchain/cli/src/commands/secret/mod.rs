@@ -0,0 +1,24 @@
+pub mod delete;
+pub mod list;
+pub mod set;
+
+use anyhow::*;
+use clap::Subcommand;
+
+#[derive(Subcommand)]
+pub enum SubCommand {
+	/// Creates a secret or rotates its value
+	Set(set::Opts),
+	Delete(delete::Opts),
+	List(list::Opts),
+}
+
+impl SubCommand {
+	pub async fn execute(&self) -> Result<()> {
+		match &self {
+			SubCommand::Set(opts) => opts.execute().await,
+			SubCommand::Delete(opts) => opts.execute().await,
+			SubCommand::List(opts) => opts.execute().await,
+		}
+	}
+}
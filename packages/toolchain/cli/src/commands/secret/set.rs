@@ -0,0 +1,52 @@
+use std::io::Read;
+
+use anyhow::*;
+use clap::Parser;
+use toolchain::tivet_api::{apis, models};
+
+#[derive(Parser)]
+pub struct Opts {
+	#[clap(index = 1)]
+	name: String,
+
+	/// Value of the secret. Read from stdin if not set.
+	#[clap(long)]
+	value: Option<String>,
+
+	#[clap(long, alias = "env", short = 'e')]
+	environment: Option<String>,
+}
+
+impl Opts {
+	pub async fn execute(&self) -> Result<()> {
+		let ctx = crate::util::login::load_or_login().await?;
+
+		let env = crate::util::env::get_or_select(&ctx, self.environment.as_ref()).await?;
+
+		let value = match &self.value {
+			Some(value) => value.clone(),
+			None => {
+				let mut value = String::new();
+				std::io::stdin()
+					.read_to_string(&mut value)
+					.context("failed to read secret from stdin")?;
+				value.trim_end_matches('\n').to_string()
+			}
+		};
+
+		let res = apis::actor_secrets_api::actor_secrets_put(
+			&ctx.openapi_config_cloud,
+			&self.name,
+			models::ActorPutSecretRequest { value },
+			Some(&ctx.project.name_id),
+			Some(&env),
+		)
+		.await?;
+
+		println!(
+			"Set secret: {} (version {})",
+			res.secret.name, res.secret.version
+		);
+		Ok(())
+	}
+}
@@ -11,6 +11,8 @@ use crate::util::task::{run_task, TaskOutputStyle};
 pub struct DeployOpts<'a> {
 	pub environment: &'a str,
 	pub build_tags: Option<HashMap<String, String>>,
+	pub plan: bool,
+	pub force: bool,
 }
 
 pub async fn deploy(opts: DeployOpts<'_>) -> Result<Vec<Uuid>> {
@@ -49,6 +51,8 @@ pub async fn deploy(opts: DeployOpts<'_>) -> Result<Vec<Uuid>> {
 			config,
 			environment_id: environment.id,
 			build_tags: opts.build_tags,
+			plan: opts.plan,
+			force: opts.force,
 		},
 	)
 	.await?;
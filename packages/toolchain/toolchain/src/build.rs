@@ -3,4 +3,6 @@ pub mod tags {
 	pub const ACCESS: &str = "access";
 	pub const VERSION: &str = "version";
 	pub const CURRENT: &str = "current";
+	/// Hash of the build's contents, used to skip uploading unchanged builds.
+	pub const CONTENT_HASH: &str = "content_hash";
 }
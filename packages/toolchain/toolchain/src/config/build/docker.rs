@@ -41,6 +41,10 @@ pub struct Unstable {
 	pub build_method: Option<BuildMethod>,
 	pub bundle: Option<BundleKind>,
 	pub compression: Option<Compression>,
+	/// Zstd compression level. Only valid with `zstd` compression.
+	pub compression_level: Option<i32>,
+	/// Path to a Zstd dictionary to compress with. Only valid with `zstd` compression.
+	pub compression_dictionary: Option<String>,
 }
 
 impl Unstable {
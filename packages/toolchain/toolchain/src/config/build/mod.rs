@@ -24,6 +24,11 @@ pub enum Compression {
 	/// LZ4 compression. Fast compression optimized for fast lobby start times.
 	#[strum(serialize = "lz4")]
 	Lz4,
+
+	/// Zstandard compression. Smaller builds than LZ4 while still decompressing quickly. Supports
+	/// a custom level and dictionary.
+	#[strum(serialize = "zstd")]
+	Zstd,
 }
 
 impl Compression {
@@ -1,11 +1,12 @@
 use anyhow::*;
 use std::collections::HashMap;
-use std::path::Path;
-use uuid::Uuid;
+use std::path::{Path, PathBuf};
+use tokio::fs;
 
 use crate::{
 	config, paths,
 	project::environment::TEMPEnvironment,
+	tasks::build_publish::{LocalBuildOutput, UploadFilter},
 	toolchain_ctx::ToolchainCtx,
 	util::{
 		cmd::{self, shell_cmd},
@@ -18,14 +19,15 @@ pub struct BuildAndUploadOpts {
 	pub env: TEMPEnvironment,
 	pub tags: HashMap<String, String>,
 	pub build_config: config::build::docker::Build,
+	pub filter: UploadFilter,
 }
 
-/// Builds image if not specified and returns the build ID.
+/// Builds image if not specified and uploads it if the filter allows it.
 pub async fn build_and_upload(
 	ctx: &ToolchainCtx,
 	task: task::TaskCtx,
 	opts: BuildAndUploadOpts,
-) -> Result<Uuid> {
+) -> Result<LocalBuildOutput> {
 	task.log(format!("[Building] {}", kv_str::to_str(&opts.tags)?));
 
 	let project_root = paths::project_root()?;
@@ -36,25 +38,32 @@ pub async fn build_and_upload(
 	let compression = build_config_unstable
 		.compression
 		.unwrap_or_else(|| config::build::Compression::default_from_bundle_kind(bundle));
-
-	// Deploy Docker build
-	let build_id = if let Some(image) = &opts.build_config.image {
-		let push_output = docker_push(
-			ctx,
-			task.clone(),
-			&DockerPushOpts {
-				env: opts.env,
-				docker_tag: image.clone(),
-				bundle,
-				compression,
-				allow_root: build_config_unstable.allow_root(),
-			},
-		)
-		.await?;
-
-		task.log(format!("[Created Build] {}", push_output.build_id));
-
-		push_output.build_id
+	let allow_root = build_config_unstable.allow_root();
+	let compression_level = build_config_unstable.compression_level;
+	let compression_dictionary = build_config_unstable
+		.compression_dictionary
+		.as_ref()
+		.map(|x| project_root.join(x));
+	if !matches!(compression, config::build::Compression::Zstd) {
+		ensure!(
+			compression_level.is_none() && compression_dictionary.is_none(),
+			"`compression_level` and `compression_dictionary` require zstd compression"
+		);
+	}
+
+	// Resolve the image with a unique tag
+	let image_tag = if let Some(image) = &opts.build_config.image {
+		// Re-tag image with unique tag
+		let unique_image_tag = generate_unique_image_tag();
+		let mut tag_cmd = shell_cmd("docker");
+		tag_cmd
+			.arg("image")
+			.arg("tag")
+			.arg(image)
+			.arg(&unique_image_tag);
+		cmd::execute_docker_cmd_silent(tag_cmd, "failed to tag Docker image").await?;
+
+		unique_image_tag
 	} else {
 		let dockerfile = opts
 			.build_config
@@ -68,139 +77,132 @@ pub async fn build_and_upload(
 			.map(|x| x.as_str())
 			.unwrap_or(".");
 
-		let push_output = docker_build_and_push(
+		let build_args = opts
+			.build_config
+			.build_args
+			.iter()
+			.flatten()
+			.map(|(k, v)| format!("{k}={v}"))
+			.collect::<Vec<_>>();
+
+		docker::build::build_image(
 			ctx,
 			task.clone(),
 			&project_root.join(path),
-			&DockerBuildPushOpts {
-				env: opts.env.clone(),
-				dockerfile: dockerfile.clone(),
-				build_args: Some(
-					opts.build_config
-						.build_args
-						.iter()
-						.flatten()
-						.map(|(k, v)| format!("{k}={v}"))
-						.collect(),
-				),
-				build_target: opts.build_config.build_target.clone(),
-				build_method: build_config_unstable.build_method(),
-				bundle,
-				compression,
-				allow_root: build_config_unstable.allow_root(),
-			},
+			Path::new(&dockerfile),
+			build_config_unstable.build_method(),
+			Some(build_args.as_slice()),
+			opts.build_config.build_target.as_deref(),
 		)
-		.await?;
-
-		task.log(format!("[Created Build] {}", push_output.build_id));
-
-		push_output.build_id
+		.await?
+		.tag
 	};
 
-	Ok(build_id)
-}
-
-pub struct DockerBuildPushOpts {
-	pub env: TEMPEnvironment,
-
-	/// Path to Dockerfile
-	pub dockerfile: String,
-
-	/// Docker build args
-	pub build_args: Option<Vec<String>>,
-
-	/// Target build stage to build.
-	pub build_target: Option<String>,
-
-	pub build_method: config::build::docker::BuildMethod,
-	pub bundle: config::build::docker::BundleKind,
-	pub compression: config::build::Compression,
-	pub allow_root: bool,
-}
-
-/// Build and push a Dockerfile.
-pub async fn docker_build_and_push(
-	ctx: &ToolchainCtx,
-	task: task::TaskCtx,
-	current_dir: &Path,
-	push_opts: &DockerBuildPushOpts,
-) -> Result<docker::push::PushOutput> {
-	// Build image
-	let build_output = docker::build::build_image(
-		ctx,
-		task.clone(),
-		current_dir,
-		&Path::new(&push_opts.dockerfile),
-		push_opts.build_method,
-		push_opts.bundle,
-		push_opts.compression,
-		push_opts.build_args.as_ref().map(|x| x.as_slice()),
-		push_opts.build_target.as_ref().map(String::as_str),
-		push_opts.allow_root,
-	)
-	.await?;
-
-	// Upload build
-	docker::push::push_tar(
+	let res = upload_image(
 		ctx,
 		task.clone(),
-		&docker::push::PushOpts {
-			env: push_opts.env.clone(),
-			path: build_output.path.to_owned(),
-			docker_tag: build_output.tag,
-			bundle: push_opts.bundle,
-			compression: push_opts.compression,
+		&UploadImageOpts {
+			env: opts.env,
+			image_tag: image_tag.clone(),
+			bundle,
+			compression,
+			compression_level,
+			compression_dictionary,
+			allow_root,
+			filter: opts.filter,
 		},
 	)
-	.await
+	.await;
+
+	// Clean up image from the registry
+	docker::remove_image(&image_tag).await?;
+
+	res
 }
 
-pub struct DockerPushOpts {
-	pub env: TEMPEnvironment,
+struct UploadImageOpts {
+	env: TEMPEnvironment,
+
+	/// Unique tag of the image to upload.
+	image_tag: String,
 
-	pub docker_tag: String,
+	bundle: config::build::docker::BundleKind,
+	compression: config::build::Compression,
+	compression_level: Option<i32>,
+	compression_dictionary: Option<PathBuf>,
+	allow_root: bool,
 
-	pub bundle: config::build::docker::BundleKind,
-	pub compression: config::build::Compression,
-	pub allow_root: bool,
+	filter: UploadFilter,
 }
 
-/// Push an image that's already built.
-pub async fn docker_push(
+/// Archives and pushes an image that's already built.
+async fn upload_image(
 	ctx: &ToolchainCtx,
 	task: task::TaskCtx,
-	push_opts: &DockerPushOpts,
-) -> Result<docker::push::PushOutput> {
-	// Re-tag image with unique tag
-	let unique_image_tag = generate_unique_image_tag();
-	let mut tag_cmd = shell_cmd("docker");
-	tag_cmd
-		.arg("image")
-		.arg("tag")
-		.arg(&push_opts.docker_tag)
-		.arg(&unique_image_tag);
-	cmd::execute_docker_cmd_silent(tag_cmd, "failed to tag Docker image").await?;
+	opts: &UploadImageOpts,
+) -> Result<LocalBuildOutput> {
+	// The image ID is a digest of the image config, so it only changes when the image changes
+	let image_id = docker::image_id(&opts.image_tag).await?;
+	let mut hash_parts = vec![
+		image_id.as_bytes().to_vec(),
+		opts.bundle.as_ref().as_bytes().to_vec(),
+		opts.compression.as_ref().as_bytes().to_vec(),
+		if opts.allow_root { "root" } else { "" }
+			.as_bytes()
+			.to_vec(),
+	];
+	// Only hash compression options when set so existing builds keep the same hash
+	if let Some(compression_level) = opts.compression_level {
+		hash_parts.push(compression_level.to_string().into_bytes());
+	}
+	if let Some(compression_dictionary) = &opts.compression_dictionary {
+		hash_parts.push(fs::read(compression_dictionary).await.with_context(|| {
+			anyhow!(
+				"failed to read compression dictionary: {}",
+				compression_dictionary.display()
+			)
+		})?);
+	}
+	let content_hash = crate::util::build::content_hash(hash_parts);
+
+	if !opts.filter.should_upload(&content_hash) {
+		return Ok(LocalBuildOutput {
+			content_hash,
+			build_id: None,
+		});
+	}
 
 	// Archive image
 	let archive_path = docker::archive::create_archive(
 		task.clone(),
-		&unique_image_tag,
-		push_opts.bundle,
-		push_opts.compression,
-		push_opts.allow_root,
+		&opts.image_tag,
+		opts.bundle,
+		opts.compression,
+		opts.compression_level,
+		opts.compression_dictionary.as_deref(),
+		opts.allow_root,
 	)
 	.await?;
 
-	docker::push::push_tar(
+	// Upload build
+	let push_output = docker::push::push_tar(
 		ctx,
 		task.clone(),
 		&docker::push::PushOpts {
-			env: push_opts.env.clone(),
-			path: archive_path.to_owned(),
-			docker_tag: unique_image_tag,
-			bundle: push_opts.bundle,
-			compression: push_opts.compression,
+			env: opts.env.clone(),
+			path: archive_path.to_path_buf(),
+			docker_tag: opts.image_tag.clone(),
+			bundle: opts.bundle,
+			compression: opts.compression,
+			compression_dictionary: opts.compression_dictionary.clone(),
 		},
 	)
-	.await
+	.await?;
+
+	task.log(format!("[Created Build] {}", push_output.build_id));
+
+	Ok(LocalBuildOutput {
+		content_hash,
+		build_id: Some(push_output.build_id),
+	})
 }
@@ -8,6 +8,7 @@ use uuid::Uuid;
 use crate::{
 	config, paths,
 	project::environment::TEMPEnvironment,
+	tasks::build_publish::{LocalBuildOutput, UploadFilter},
 	toolchain_ctx::ToolchainCtx,
 	util::{js_utils, net::upload, task, term},
 };
@@ -19,14 +20,15 @@ pub struct BuildAndUploadOpts {
 	pub env: TEMPEnvironment,
 	pub tags: HashMap<String, String>,
 	pub build_config: config::build::javascript::Build,
+	pub filter: UploadFilter,
 }
 
-/// Builds image if not specified and returns the build ID.
+/// Bundles the script and uploads it if the filter allows it.
 pub async fn build_and_upload(
 	ctx: &ToolchainCtx,
 	task: task::TaskCtx,
 	opts: BuildAndUploadOpts,
-) -> Result<Uuid> {
+) -> Result<LocalBuildOutput> {
 	task.log(format!("[Building] {}", opts.build_config.script));
 
 	let project_root = paths::project_root()?;
@@ -104,24 +106,37 @@ pub async fn build_and_upload(
 		fs::copy(&script_path, build_dir.path().join(BUILD_INDEX_NAME)).await?;
 	};
 
+	let compression = opts.build_config.unstable.compression();
+	let content_hash =
+		crate::util::build::content_hash_dir(build_dir.path(), &[compression.as_ref()]).await?;
+
 	// Deploy JS build
-	let build_id = upload_bundle(
-		ctx,
-		task.clone(),
-		&UploadBundleOpts {
-			env: opts.env,
-			build_path: build_dir.path().into(),
-			compression: opts.build_config.unstable.compression(),
-		},
-	)
-	.await?;
+	let build_id = if opts.filter.should_upload(&content_hash) {
+		Some(
+			upload_bundle(
+				ctx,
+				task.clone(),
+				&UploadBundleOpts {
+					env: opts.env,
+					build_path: build_dir.path().into(),
+					compression,
+				},
+			)
+			.await?,
+		)
+	} else {
+		None
+	};
 
 	// Retain build folder
 	if opts.build_config.unstable.dump_build() {
 		let _ = build_dir.into_path();
 	}
 
-	Ok(build_id)
+	Ok(LocalBuildOutput {
+		content_hash,
+		build_id,
+	})
 }
 
 // struct CheckOpts<'a> {
@@ -205,14 +220,16 @@ async fn upload_bundle(
 	let build_tar_file = build_archive.into_inner()?;
 
 	let build_kind = models::ActorBuildKind::Javascript;
-	let build_compression = match push_opts.compression {
-		config::build::Compression::None => models::ActorBuildCompression::None,
-		config::build::Compression::Lz4 => models::ActorBuildCompression::Lz4,
-	};
+	let build_compression = crate::util::build::api_compression(push_opts.compression);
 
 	// Compress build
-	let compressed_path =
-		crate::util::build::compress_build(build_tar_file.as_ref(), push_opts.compression).await?;
+	let compressed_path = crate::util::build::compress_build(
+		build_tar_file.as_ref(),
+		push_opts.compression,
+		None,
+		None,
+	)
+	.await?;
 
 	let image_file = upload::prepare_upload_file(
 		&compressed_path,
@@ -237,6 +254,8 @@ async fn upload_bundle(
 			image_file: Box::new(image_file.prepared),
 			kind: Some(build_kind),
 			compression: Some(build_compression),
+			compression_dictionary_file: None,
+			image_digest: Some(crate::util::build::file_digest(&compressed_path).await?),
 		},
 		Some(&ctx.project.name_id),
 		Some(&push_opts.env.slug),
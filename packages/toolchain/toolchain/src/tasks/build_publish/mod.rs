@@ -33,11 +33,86 @@ pub struct Input {
 	pub build_name: String,
 	pub runtime: config::build::Runtime,
 	pub access: config::BuildAccess,
+	#[serde(default)]
+	pub mode: Mode,
+}
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+	/// Always upload & tag the build.
+	#[default]
+	Always,
+	/// Skip uploading & tagging if the current build has the same content hash.
+	SkipUnchanged,
+	/// Build locally and compare against the current build without uploading or tagging anything.
+	Plan,
 }
 
 #[derive(Serialize)]
 pub struct Output {
-	pub build_id: Uuid,
+	/// The build that is tagged as current after publishing.
+	///
+	/// This is `None` when planning a build that would be uploaded.
+	pub build_id: Option<Uuid>,
+	pub plan: BuildPlan,
+}
+
+#[derive(Clone, Serialize)]
+pub struct BuildPlan {
+	pub build_name: String,
+	pub content_hash: String,
+	/// The build currently tagged as current, if any.
+	pub current_build_id: Option<Uuid>,
+	pub action: PlanAction,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanAction {
+	/// No build is tagged as current, a new build will be uploaded.
+	Create,
+	/// The current build has different contents, a new build will be uploaded & re-tagged.
+	Update,
+	/// The current build has the same contents.
+	Unchanged,
+}
+
+impl PlanAction {
+	pub fn symbol(&self) -> &'static str {
+		match self {
+			PlanAction::Create => "+",
+			PlanAction::Update => "~",
+			PlanAction::Unchanged => "=",
+		}
+	}
+}
+
+/// Decides if a locally built artifact needs to be uploaded.
+///
+/// Passed to the runtime-specific build functions so they can skip archiving & uploading once the
+/// content hash is known.
+#[derive(Clone)]
+pub struct UploadFilter {
+	pub mode: Mode,
+	pub current_content_hash: Option<String>,
+}
+
+impl UploadFilter {
+	pub fn should_upload(&self, content_hash: &str) -> bool {
+		match self.mode {
+			Mode::Always => true,
+			Mode::SkipUnchanged => self.current_content_hash.as_deref() != Some(content_hash),
+			Mode::Plan => false,
+		}
+	}
+}
+
+/// Output of a runtime-specific build.
+pub struct LocalBuildOutput {
+	pub content_hash: String,
+	/// `None` if the upload was skipped by the `UploadFilter`.
+	pub build_id: Option<Uuid>,
 }
 
 pub struct Task;
@@ -62,7 +137,7 @@ impl task::Task for Task {
 		let env = crate::project::environment::get_env(&ctx, input.environment_id).await?;
 
 		// Build
-		let build_id = build_and_upload(
+		build_and_upload(
 			&ctx,
 			task.clone(),
 			&env,
@@ -70,16 +145,15 @@ impl task::Task for Task {
 			input.build_name.clone(),
 			&input.runtime,
 			&input.access,
+			input.mode,
 		)
-		.await?;
-
-		Ok(Output { build_id })
+		.await
 	}
 }
 
 /// Builds the required resources and uploads it to Tivet.
 ///
-/// Returns the resulting build ID.
+/// Returns the resulting build ID and what was done with the build.
 async fn build_and_upload(
 	ctx: &ToolchainCtx,
 	task: task::TaskCtx,
@@ -88,15 +162,36 @@ async fn build_and_upload(
 	build_name: String,
 	runtime: &Runtime,
 	access: &config::BuildAccess,
-) -> Result<Uuid> {
+	mode: Mode,
+) -> Result<Output> {
 	task.log("");
 
+	// Find existing builds with current tag
+	let list_res = apis::actor_builds_api::actor_builds_list(
+		&ctx.openapi_config_cloud,
+		Some(&ctx.project.name_id),
+		Some(&env.slug),
+		Some(&serde_json::to_string(&json!({
+			build::tags::NAME: build_name,
+			build::tags::CURRENT: "true",
+		}))?),
+	)
+	.await?;
+	let current_build = list_res.builds.first();
+	let current_content_hash = current_build
+		.and_then(|b| b.tags.get(build::tags::CONTENT_HASH))
+		.cloned();
+	let filter = UploadFilter {
+		mode,
+		current_content_hash: current_content_hash.clone(),
+	};
+
 	// Build & upload
 	let build_tags = full_tags(&build_name)
 		.into_iter()
 		.map(|(k, v)| (k.to_string(), v.to_string()))
 		.collect::<HashMap<_, _>>();
-	let build_id = match &runtime {
+	let local_build = match &runtime {
 		config::build::Runtime::Docker(docker) => {
 			docker::build_and_upload(
 				&ctx,
@@ -105,6 +200,7 @@ async fn build_and_upload(
 					env: env.clone(),
 					tags: build_tags.clone(),
 					build_config: docker.clone(),
+					filter,
 				},
 			)
 			.await?
@@ -117,36 +213,55 @@ async fn build_and_upload(
 					env: env.clone(),
 					tags: build_tags.clone(),
 					build_config: js.clone(),
+					filter,
 				},
 			)
 			.await?
 		}
 	};
 
+	let plan = BuildPlan {
+		build_name: build_name.clone(),
+		content_hash: local_build.content_hash.clone(),
+		current_build_id: current_build.map(|b| b.id),
+		action: if current_build.is_none() {
+			PlanAction::Create
+		} else if current_content_hash.as_deref() == Some(local_build.content_hash.as_str()) {
+			PlanAction::Unchanged
+		} else {
+			PlanAction::Update
+		},
+	};
+
+	let Some(build_id) = local_build.build_id else {
+		// Nothing was uploaded, leave the tags untouched
+		let build_id = match plan.action {
+			PlanAction::Unchanged if mode != Mode::Plan => {
+				task.log(format!("[Unchanged] {build_name}"));
+				plan.current_build_id
+			}
+			_ => None,
+		};
+
+		return Ok(Output { build_id, plan });
+	};
+
 	let mut tags = HashMap::from([
 		(build::tags::NAME.to_string(), build_name.to_string()),
 		(build::tags::ACCESS.to_string(), access.as_ref().to_string()),
 		(build::tags::VERSION.to_string(), version_name.to_string()),
 		(build::tags::CURRENT.to_string(), "true".to_string()),
+		(
+			build::tags::CONTENT_HASH.to_string(),
+			local_build.content_hash.clone(),
+		),
 	]);
 	if !build_tags.is_empty() {
 		tags.extend(build_tags.clone());
 	}
 
-	// Find existing builds with current tag
-	let list_res = apis::actor_builds_api::actor_builds_list(
-		&ctx.openapi_config_cloud,
-		Some(&ctx.project.name_id),
-		Some(&env.slug),
-		Some(&serde_json::to_string(&json!({
-			build::tags::NAME: build_name,
-			build::tags::CURRENT: "true",
-		}))?),
-	)
-	.await?;
-
 	// Remove current tag if needed
-	for build in list_res.builds {
+	for build in &list_res.builds {
 		apis::actor_builds_api::actor_builds_patch_tags(
 			&ctx.openapi_config_cloud,
 			&build.id.to_string(),
@@ -200,5 +315,8 @@ async fn build_and_upload(
 		"[Build Published] {hub_origin}/projects/{project_slug}/environments/{env_slug}/builds",
 	));
 
-	Ok(build_id)
+	Ok(Output {
+		build_id: Some(build_id),
+		plan,
+	})
 }
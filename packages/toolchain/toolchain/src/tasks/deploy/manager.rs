@@ -10,6 +10,7 @@ use crate::{
 pub struct DeployOpts {
 	pub env: TEMPEnvironment,
 	pub manager_config: config::ManagerUnstable,
+	pub mode: crate::tasks::build_publish::Mode,
 }
 
 pub struct DeployOutput {
@@ -46,10 +47,12 @@ pub async fn deploy(
 				},
 			}),
 			access: config::BuildAccess::Private,
+			mode: opts.mode,
 		},
 	)
 	.await?
-	.build_id;
+	.build_id
+	.context("manager build not published")?;
 
 	// Check if manager exists
 	let res = apis::actor_api::actor_list(
@@ -125,6 +128,7 @@ pub async fn deploy(
 					"TIVET_SERVICE_TOKEN".to_string(),
 					service_token.token,
 				)])),
+				secrets: None,
 			})),
 			network: Some(Box::new(models::ActorCreateActorNetworkRequest {
 				mode: Some(models::ActorNetworkMode::Bridge),
@@ -145,6 +149,7 @@ pub async fn deploy(
 				durable: Some(true),
 				kill_timeout: None,
 			})),
+			volumes: None,
 		};
 		let response = apis::actor_api::actor_create(
 			&ctx.openapi_config_cloud,
@@ -13,11 +13,18 @@ pub struct Input {
 	pub config: config::Config,
 	pub environment_id: Uuid,
 	pub build_tags: Option<HashMap<String, String>>,
+	/// Only show what would be uploaded & re-tagged without deploying anything.
+	#[serde(default)]
+	pub plan: bool,
+	/// Upload every build, even if its contents match the current build.
+	#[serde(default)]
+	pub force: bool,
 }
 
 #[derive(Serialize)]
 pub struct Output {
 	pub build_ids: Vec<Uuid>,
+	pub plan: Vec<build_publish::BuildPlan>,
 }
 
 pub struct Task;
@@ -41,17 +48,28 @@ impl task::Task for Task {
 
 		let env = crate::project::environment::get_env(&ctx, input.environment_id).await?;
 
-		// Reserve version name
-		let reserve_res =
+		let mode = if input.plan {
+			build_publish::Mode::Plan
+		} else if input.force {
+			build_publish::Mode::Always
+		} else {
+			build_publish::Mode::SkipUnchanged
+		};
+
+		// Reserve version name. Planning doesn't tag builds, so no version is needed.
+		let version_name = if input.plan {
+			String::new()
+		} else {
 			apis::cloud_games_versions_api::cloud_games_versions_reserve_version_name(
 				&ctx.openapi_config_cloud,
 				&ctx.project.game_id.to_string(),
 			)
-			.await?;
-		let version_name = reserve_res.version_display_name;
+			.await?
+			.version_display_name
+		};
 
 		// Manager
-		let manager_res = if input.config.unstable().manager.enable() {
+		let manager_res = if input.config.unstable().manager.enable() && !input.plan {
 			Some(
 				manager::deploy(
 					&ctx,
@@ -59,6 +77,7 @@ impl task::Task for Task {
 					manager::DeployOpts {
 						env: env.clone(),
 						manager_config: input.config.unstable().manager,
+						mode,
 					},
 				)
 				.await?,
@@ -69,6 +88,7 @@ impl task::Task for Task {
 
 		// Build
 		let mut build_ids = Vec::new();
+		let mut plan = Vec::new();
 		let mut example_build = None; // Build to use for the example code
 		for (build_name, build) in &input.config.builds {
 			// Filter out builds that match the tags
@@ -86,7 +106,7 @@ impl task::Task for Task {
 			}
 
 			// Build using build publish task
-			let build_id = build_publish::Task::run(
+			let output = build_publish::Task::run(
 				task.clone(),
 				build_publish::Input {
 					environment_id: env.id,
@@ -95,14 +115,20 @@ impl task::Task for Task {
 					build_name: build_name.to_string(),
 					runtime: build.runtime.clone(),
 					access: build.access.clone(),
+					mode,
 				},
 			)
-			.await?
-			.build_id;
-			build_ids.push(build_id);
+			.await?;
+			build_ids.extend(output.build_id);
+			plan.push(output.plan);
 		}
 
-		ensure!(!build_ids.is_empty(), "No builds matched build tags");
+		ensure!(!plan.is_empty(), "No builds matched build tags");
+
+		if input.plan {
+			log_plan(&task, &plan);
+			return Ok(Output { build_ids, plan });
+		}
 
 		let hub_origin = &ctx.bootstrap.origins.hub;
 		let project_slug = &ctx.project.name_id;
@@ -140,6 +166,44 @@ impl task::Task for Task {
 			task.log("");
 		}
 
-		Ok(Output { build_ids })
+		Ok(Output { build_ids, plan })
+	}
+}
+
+fn log_plan(task: &task::TaskCtx, plan: &[build_publish::BuildPlan]) {
+	let name_width = plan.iter().map(|x| x.build_name.len()).max().unwrap_or(0);
+
+	task.log("");
+	task.log("Plan:");
+	task.log("");
+	for build in plan {
+		let summary = match build.action {
+			build_publish::PlanAction::Create => "upload & tag as current".to_string(),
+			build_publish::PlanAction::Update => format!(
+				"upload & re-tag as current (replaces {})",
+				build
+					.current_build_id
+					.map(|x| x.to_string())
+					.unwrap_or_default()
+			),
+			build_publish::PlanAction::Unchanged => "unchanged".to_string(),
+		};
+		task.log(format!(
+			"  {} {:name_width$}  {}  {summary}",
+			build.action.symbol(),
+			build.build_name,
+			&build.content_hash[..12.min(build.content_hash.len())],
+		));
 	}
+
+	let changes = plan
+		.iter()
+		.filter(|x| x.action != build_publish::PlanAction::Unchanged)
+		.count();
+	task.log("");
+	task.log(format!(
+		"{changes} to upload, {} unchanged",
+		plan.len() - changes
+	));
+	task.log("");
 }
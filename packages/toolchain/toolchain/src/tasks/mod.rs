@@ -4,6 +4,7 @@ pub mod deploy;
 pub mod env;
 pub mod get_bootstrap_data;
 pub mod manager;
+pub mod rollback;
 
 crate::task_registry!(
 	auth::check_state::Task,
@@ -13,4 +14,5 @@ crate::task_registry!(
 	env::select::Task,
 	deploy::Task,
 	get_bootstrap_data::Task,
+	rollback::Task,
 );
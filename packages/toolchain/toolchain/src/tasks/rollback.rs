@@ -0,0 +1,131 @@
+use anyhow::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tivet_api::{apis, models};
+use uuid::Uuid;
+
+use crate::{build, util::task};
+
+#[derive(Deserialize)]
+pub struct Input {
+	pub environment_id: Uuid,
+	pub build_names: Vec<String>,
+	/// Upgrade all actors running the rolled back build to the previous build.
+	pub upgrade_actors: bool,
+}
+
+#[derive(Serialize)]
+pub struct Output {
+	pub rollbacks: Vec<Rollback>,
+}
+
+#[derive(Serialize)]
+pub struct Rollback {
+	pub build_name: String,
+	pub from_build_id: Uuid,
+	pub to_build_id: Uuid,
+}
+
+pub struct Task;
+
+impl task::Task for Task {
+	type Input = Input;
+	type Output = Output;
+
+	fn name() -> &'static str {
+		"rollback"
+	}
+
+	async fn run(task: task::TaskCtx, input: Self::Input) -> Result<Self::Output> {
+		let ctx = crate::toolchain_ctx::load().await?;
+
+		let env = crate::project::environment::get_env(&ctx, input.environment_id).await?;
+
+		ensure!(!input.build_names.is_empty(), "no builds to roll back");
+
+		let mut rollbacks = Vec::new();
+		for build_name in &input.build_names {
+			// List all builds with this name, sorted by newest first
+			let list_res = apis::actor_builds_api::actor_builds_list(
+				&ctx.openapi_config_cloud,
+				Some(&ctx.project.name_id),
+				Some(&env.slug),
+				Some(&serde_json::to_string(&json!({
+					build::tags::NAME: build_name,
+				}))?),
+			)
+			.await?;
+
+			let current_idx = list_res
+				.builds
+				.iter()
+				.position(|b| {
+					b.tags.get(build::tags::CURRENT).map(String::as_str) == Some("true")
+				})
+				.with_context(|| anyhow!("no current build for {build_name}"))?;
+			let current = &list_res.builds[current_idx];
+			let previous = list_res
+				.builds
+				.get(current_idx + 1)
+				.with_context(|| anyhow!("no build before the current build for {build_name}"))?;
+
+			task.log(format!(
+				"[Rolling Back] {build_name}: {} -> {}",
+				current.id, previous.id
+			));
+
+			// Move current tag to the previous build
+			apis::actor_builds_api::actor_builds_patch_tags(
+				&ctx.openapi_config_cloud,
+				&current.id.to_string(),
+				models::ActorPatchBuildTagsRequest {
+					tags: Some(json!({
+						build::tags::CURRENT: null
+					})),
+					exclusive_tags: None,
+				},
+				Some(&ctx.project.name_id),
+				Some(&env.slug),
+			)
+			.await?;
+			apis::actor_builds_api::actor_builds_patch_tags(
+				&ctx.openapi_config_cloud,
+				&previous.id.to_string(),
+				models::ActorPatchBuildTagsRequest {
+					tags: Some(json!({
+						build::tags::CURRENT: "true"
+					})),
+					exclusive_tags: None,
+				},
+				Some(&ctx.project.name_id),
+				Some(&env.slug),
+			)
+			.await?;
+
+			if input.upgrade_actors {
+				task.log("[Upgrading Actors]");
+				apis::actor_api::actor_upgrade_all(
+					&ctx.openapi_config_cloud,
+					models::ActorUpgradeAllActorsRequest {
+						tags: Some(json!({
+							build::tags::NAME: build_name,
+						})),
+						build: Some(previous.id),
+						build_tags: None,
+					},
+					Some(&ctx.project.name_id),
+					Some(&env.slug),
+				)
+				.await?;
+			}
+
+			rollbacks.push(Rollback {
+				build_name: build_name.clone(),
+				from_build_id: current.id,
+				to_build_id: previous.id,
+			});
+		}
+
+		Ok(Output { rollbacks })
+	}
+}
@@ -1,8 +1,12 @@
 use anyhow::*;
+use sha1::{Digest, Sha1};
 use tivet_api::models::{ActorBuildCompression, ActorBuildKind};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::{config, util::lz4};
+use crate::{
+	config,
+	util::{lz4, zstd},
+};
 
 /// Generates the file name that holds the build tar.
 pub fn file_name(kind: ActorBuildKind, compression: ActorBuildCompression) -> String {
@@ -15,15 +19,29 @@ pub fn file_name(kind: ActorBuildKind, compression: ActorBuildCompression) -> St
 	let file_ext_compression = match compression {
 		ActorBuildCompression::None => "",
 		ActorBuildCompression::Lz4 => ".lz4",
+		ActorBuildCompression::Zstd => ".zst",
 	};
 	format!("{file_name}.{file_ext}{file_ext_compression}")
 }
 
+/// Converts the config compression to the API compression.
+pub fn api_compression(compression: config::build::Compression) -> ActorBuildCompression {
+	match compression {
+		config::build::Compression::None => ActorBuildCompression::None,
+		config::build::Compression::Lz4 => ActorBuildCompression::Lz4,
+		config::build::Compression::Zstd => ActorBuildCompression::Zstd,
+	}
+}
+
 /// Compresses a given file with the given build compression. This is used as the last step in the
 /// build process before uploading the output file.
+///
+/// `level` and `dictionary_path` only apply to Zstd.
 pub async fn compress_build(
 	input_path: &Path,
 	compression: config::build::Compression,
+	level: Option<i32>,
+	dictionary_path: Option<&Path>,
 ) -> Result<tempfile::TempPath> {
 	// Compress the bundle
 	let compressed_file = tempfile::NamedTempFile::new()?;
@@ -38,7 +56,149 @@ pub async fn compress_build(
 			tokio::task::spawn_blocking(move || lz4::compress(&input_path, &compressed_file_path))
 				.await??;
 		}
+		config::build::Compression::Zstd => {
+			let input_path = input_path.to_owned();
+			let compressed_file_path = compressed_file_path.to_owned();
+			let level = level.unwrap_or(zstd::DEFAULT_LEVEL);
+			let dictionary_path = dictionary_path.map(Path::to_owned);
+			tokio::task::spawn_blocking(move || {
+				zstd::compress(
+					&input_path,
+					&compressed_file_path,
+					level,
+					dictionary_path.as_deref(),
+				)
+			})
+			.await??;
+		}
 	}
 
 	Ok(compressed_file_path)
 }
+
+/// Hex encoded SHA-256 digest of a file. Sent with the build so clients can verify the image they
+/// download.
+pub async fn file_digest(path: &Path) -> Result<String> {
+	let path = path.to_owned();
+
+	tokio::task::spawn_blocking(move || {
+		let mut file = std::fs::File::open(&path)?;
+		let mut hasher = sha2::Sha256::new();
+		std::io::copy(&mut file, &mut hasher)?;
+
+		Ok(format!("{:x}", hasher.finalize()))
+	})
+	.await?
+}
+
+/// Hashes the parts that uniquely identify the contents of a build.
+///
+/// Builds with the same content hash are considered unchanged and do not need to be uploaded
+/// again.
+pub fn content_hash<I, T>(parts: I) -> String
+where
+	I: IntoIterator<Item = T>,
+	T: AsRef<[u8]>,
+{
+	let mut hasher = Sha1::new();
+	for part in parts {
+		let part = part.as_ref();
+
+		// Prefix with the length so adjacent parts can't be shifted in to each other
+		hasher.update((part.len() as u64).to_le_bytes());
+		hasher.update(part);
+	}
+	format!("{:x}", hasher.finalize())
+}
+
+/// Hashes the relative paths and contents of every file in a directory.
+///
+/// File metadata (such as mtime) is ignored, so rebuilding the same sources produces the same hash.
+pub async fn content_hash_dir(path: &Path, extra: &[&str]) -> Result<String> {
+	let path = path.to_owned();
+	let extra = extra.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+	tokio::task::spawn_blocking(move || {
+		let mut files = Vec::new();
+		collect_files(&path, &mut files)?;
+		files.sort();
+
+		let mut parts = extra
+			.into_iter()
+			.map(String::into_bytes)
+			.collect::<Vec<_>>();
+		for file in files {
+			let relative_path = file.strip_prefix(&path)?;
+			parts.push(
+				relative_path
+					.to_string_lossy()
+					.replace('\\', "/")
+					.into_bytes(),
+			);
+			parts.push(std::fs::read(&file)?);
+		}
+
+		Ok(content_hash(parts))
+	})
+	.await?
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+	for entry in std::fs::read_dir(dir)? {
+		let entry = entry?;
+		if entry.file_type()?.is_dir() {
+			collect_files(&entry.path(), files)?;
+		} else {
+			files.push(entry.path());
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_content_hash() {
+		assert_eq!(content_hash(["ab", "c"]), content_hash(["ab", "c"]));
+		assert_ne!(content_hash(["ab", "c"]), content_hash(["c", "ab"]));
+
+		// Parts are length prefixed so they can't be shifted in to each other
+		assert_ne!(content_hash(["ab", "c"]), content_hash(["a", "bc"]));
+		assert_ne!(content_hash(["abc"]), content_hash(["abc", ""]));
+	}
+
+	#[tokio::test]
+	async fn test_content_hash_dir() {
+		let dir = tempfile::tempdir().unwrap();
+		std::fs::create_dir(dir.path().join("sub")).unwrap();
+		std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+		std::fs::write(dir.path().join("sub/b.txt"), "b").unwrap();
+
+		let hash = content_hash_dir(dir.path(), &["extra"]).await.unwrap();
+
+		// Stable across runs and independent of metadata
+		std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+		assert_eq!(
+			hash,
+			content_hash_dir(dir.path(), &["extra"]).await.unwrap()
+		);
+
+		// Extra parts are included
+		assert_ne!(hash, content_hash_dir(dir.path(), &[]).await.unwrap());
+
+		// Contents are included
+		std::fs::write(dir.path().join("sub/b.txt"), "c").unwrap();
+		let hash_changed = content_hash_dir(dir.path(), &["extra"]).await.unwrap();
+		assert_ne!(hash, hash_changed);
+
+		// Paths are included
+		std::fs::rename(dir.path().join("sub/b.txt"), dir.path().join("sub/c.txt")).unwrap();
+		assert_ne!(
+			hash_changed,
+			content_hash_dir(dir.path(), &["extra"]).await.unwrap()
+		);
+	}
+}
@@ -1,7 +1,7 @@
 use anyhow::*;
 use serde::Deserialize;
 use serde_json::json;
-use std::io::Read;
+use std::{io::Read, path::Path};
 use typed_path::{TryAsRef, UnixPath};
 use uuid::Uuid;
 
@@ -18,6 +18,8 @@ pub async fn create_archive(
 	image_tag: &str,
 	build_kind: config::build::docker::BundleKind,
 	build_compression: config::build::Compression,
+	compression_level: Option<i32>,
+	compression_dictionary: Option<&Path>,
 	allow_root: bool,
 ) -> Result<tempfile::TempPath> {
 	task.log(format!(
@@ -37,8 +39,13 @@ pub async fn create_archive(
 	};
 
 	// Compress archive
-	let compressed_path =
-		crate::util::build::compress_build(build_tar_path.as_ref(), build_compression).await?;
+	let compressed_path = crate::util::build::compress_build(
+		build_tar_path.as_ref(),
+		build_compression,
+		compression_level,
+		compression_dictionary,
+	)
+	.await?;
 
 	Ok(compressed_path)
 }
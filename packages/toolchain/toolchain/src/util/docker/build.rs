@@ -12,21 +12,19 @@ use crate::{
 
 pub struct BuildImageOutput {
 	pub tag: String,
-	pub path: tempfile::TempPath,
 }
 
-/// Builds an image and archives it to a path.
+/// Builds an image with a unique tag.
+///
+/// The caller is responsible for archiving the image and removing the tag once done.
 pub async fn build_image(
 	_ctx: &ToolchainCtx,
 	task: task::TaskCtx,
 	build_path: &Path,
 	dockerfile: &Path,
 	build_method: config::build::docker::BuildMethod,
-	build_kind: config::build::docker::BundleKind,
-	build_compression: config::build::Compression,
 	build_args: Option<&[String]>,
 	build_target: Option<&str>,
-	allow_root: bool,
 ) -> Result<BuildImageOutput> {
 	// Determine build method
 	let build_method = if build_method == config::build::docker::BuildMethod::Buildx {
@@ -169,27 +167,5 @@ pub async fn build_image(
 		}
 	}
 
-	// Build archive
-	let build_tar_path = super::archive::create_archive(
-		task.clone(),
-		&image_tag,
-		build_kind,
-		build_compression,
-		allow_root,
-	)
-	.await?;
-
-	// Clean up image from the registry
-	let mut remove_img_cmd = shell_cmd("docker");
-	remove_img_cmd
-		.arg("image")
-		.arg("rm")
-		.arg("--force")
-		.arg(&image_tag);
-	cmd::execute_docker_cmd_silent_fallible(remove_img_cmd).await?;
-
-	Ok(BuildImageOutput {
-		tag: image_tag,
-		path: build_tar_path,
-	})
+	Ok(BuildImageOutput { tag: image_tag })
 }
@@ -3,9 +3,42 @@ pub mod build;
 pub mod push;
 pub mod users;
 
+use anyhow::*;
 use uuid::Uuid;
 
+use crate::util::cmd::{self, shell_cmd};
+
 /// Generates a unique image tag for the image being pushed or built.
 pub fn generate_unique_image_tag() -> String {
 	format!("tivet-game:{}", Uuid::new_v4())
 }
+
+/// Returns the content-addressable ID of a local image.
+pub async fn image_id(image_tag: &str) -> Result<String> {
+	let mut inspect_cmd = shell_cmd("docker");
+	inspect_cmd
+		.arg("image")
+		.arg("inspect")
+		.arg("--format")
+		.arg("{{.Id}}")
+		.arg(image_tag);
+	let output = cmd::execute_docker_cmd_silent(inspect_cmd, "failed to inspect Docker image").await?;
+
+	let image_id = String::from_utf8(output.stdout)?.trim().to_string();
+	ensure!(!image_id.is_empty(), "Docker image has no id: {image_tag}");
+
+	Ok(image_id)
+}
+
+/// Removes a tag created by the toolchain from the local registry.
+pub async fn remove_image(image_tag: &str) -> Result<()> {
+	let mut remove_img_cmd = shell_cmd("docker");
+	remove_img_cmd
+		.arg("image")
+		.arg("rm")
+		.arg("--force")
+		.arg(image_tag);
+	cmd::execute_docker_cmd_silent_fallible(remove_img_cmd).await?;
+
+	Ok(())
+}
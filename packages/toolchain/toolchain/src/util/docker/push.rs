@@ -25,6 +25,9 @@ pub struct PushOpts {
 	pub bundle: config::build::docker::BundleKind,
 
 	pub compression: config::build::Compression,
+
+	/// Zstd dictionary the tar was compressed with.
+	pub compression_dictionary: Option<PathBuf>,
 }
 
 #[derive(Serialize)]
@@ -57,22 +60,48 @@ pub async fn push_tar(
 		config::build::docker::BundleKind::OciBundle => models::ActorBuildKind::OciBundle,
 	};
 
-	let build_compression = match push_opts.compression {
-		config::build::Compression::None => models::ActorBuildCompression::None,
-		config::build::Compression::Lz4 => models::ActorBuildCompression::Lz4,
-	};
+	let build_compression = crate::util::build::api_compression(push_opts.compression);
+	let image_digest = crate::util::build::file_digest(&push_opts.path).await?;
+
+	let compression_dictionary_file =
+		if let Some(compression_dictionary) = &push_opts.compression_dictionary {
+			let meta = fs::metadata(compression_dictionary)
+				.await
+				.with_context(|| {
+					anyhow!(
+						"failed to open compression dictionary: {}",
+						compression_dictionary.display()
+					)
+				})?;
+
+			// The build service picks the dictionary's path in the upload, see below
+			Some(Box::new(models::UploadPrepareFile {
+				path: compression_dictionary
+					.file_name()
+					.and_then(|x| x.to_str())
+					.context("invalid compression dictionary path")?
+					.into(),
+				content_type: Some(content_type.into()),
+				content_length: meta.len() as i64,
+			}))
+		} else {
+			None
+		};
 
+	let image_file_name = crate::util::build::file_name(build_kind, build_compression);
 	let build_res = apis::actor_builds_api::actor_builds_prepare(
 		&ctx.openapi_config_cloud,
 		models::ActorPrepareBuildRequest {
 			image_tag: Some(push_opts.docker_tag.clone()),
 			image_file: Box::new(models::UploadPrepareFile {
-				path: crate::util::build::file_name(build_kind, build_compression),
+				path: image_file_name.clone(),
 				content_type: Some(content_type.into()),
 				content_length: image_file_meta.len() as i64,
 			}),
 			kind: Some(build_kind),
 			compression: Some(build_compression),
+			compression_dictionary_file,
+			image_digest: Some(image_digest),
 		},
 		Some(&ctx.project.name_id),
 		Some(&push_opts.env.slug),
@@ -91,13 +120,25 @@ pub async fn push_tar(
 			let task = task.clone();
 			let reqwest_client = reqwest_client.clone();
 			let pb = pb.clone();
+			let image_file_name = &image_file_name;
 
 			async move {
+				// The dictionary is uploaded alongside the image, any file other than the image is the
+				// dictionary
+				let path = if presigned_request.path == *image_file_name {
+					&push_opts.path
+				} else {
+					push_opts
+						.compression_dictionary
+						.as_ref()
+						.context("missing compression dictionary")?
+				};
+
 				upload::upload_file(
 					task.clone(),
 					&reqwest_client,
 					&presigned_request,
-					&push_opts.path,
+					path,
 					Some(content_type),
 					pb,
 				)
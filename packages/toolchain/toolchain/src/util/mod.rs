@@ -9,3 +9,4 @@ pub mod net;
 pub mod show_term;
 pub mod task;
 pub mod term;
+pub mod zstd;
@@ -0,0 +1,47 @@
+use anyhow::*;
+use std::{
+	fs::File,
+	io::{BufReader, BufWriter},
+	path::Path,
+};
+
+/// Default level when none is configured. Favors fast decompression over compression ratio.
+pub const DEFAULT_LEVEL: i32 = 3;
+
+pub fn compress(
+	input_path: &Path,
+	output_path: &Path,
+	level: i32,
+	dictionary_path: Option<&Path>,
+) -> Result<()> {
+	ensure!(
+		zstd::compression_level_range().contains(&level),
+		"invalid zstd compression level: {level}"
+	);
+
+	let input_file = File::open(&input_path)?;
+	let mut reader = BufReader::new(input_file);
+
+	let output_file = File::create(&output_path)?;
+	let writer = BufWriter::new(output_file);
+
+	let mut encoder = if let Some(dictionary_path) = dictionary_path {
+		let dictionary = std::fs::read(dictionary_path).with_context(|| {
+			anyhow!(
+				"failed to read compression dictionary: {}",
+				dictionary_path.display()
+			)
+		})?;
+		zstd::Encoder::with_dictionary(writer, level, &dictionary)?
+	} else {
+		zstd::Encoder::new(writer, level)?
+	};
+	encoder.include_checksum(true)?;
+
+	// Pipe the bytes through the encoder
+	std::io::copy(&mut reader, &mut encoder)?;
+
+	encoder.finish()?;
+
+	Ok(())
+}
@@ -0,0 +1,74 @@
+use std::io::Read;
+
+use tivet_toolchain::util::zstd;
+
+/// Payload with enough repetition to train a dictionary from.
+fn sample(i: usize) -> Vec<u8> {
+	format!(
+		"{{\"id\":{i},\"kind\":\"docker_image\",\"layers\":[\"sha256:{i:064x}\"],\"env\":[\"PATH=/usr/local/bin:/usr/bin:/bin\"]}}\n"
+	)
+	.repeat(4)
+	.into_bytes()
+}
+
+fn dictionary() -> Vec<u8> {
+	let samples = (0..256).map(sample).collect::<Vec<_>>();
+	::zstd::dict::from_samples(&samples, 4096).unwrap()
+}
+
+#[test]
+fn dictionary_round_trip() {
+	let dir = tempfile::tempdir().unwrap();
+	let input_path = dir.path().join("image.tar");
+	let output_path = dir.path().join("image.tar.zst");
+	let dictionary_path = dir.path().join("compression.dict");
+
+	let input = (0..32).flat_map(sample).collect::<Vec<_>>();
+	let dictionary = dictionary();
+	std::fs::write(&input_path, &input).unwrap();
+	std::fs::write(&dictionary_path, &dictionary).unwrap();
+
+	zstd::compress(
+		&input_path,
+		&output_path,
+		zstd::DEFAULT_LEVEL,
+		Some(&dictionary_path),
+	)
+	.unwrap();
+
+	let compressed = std::fs::read(&output_path).unwrap();
+
+	// Decompresses with the dictionary, same as `zstd -d -D` on the client
+	let mut decoder = ::zstd::Decoder::with_dictionary(compressed.as_slice(), &dictionary).unwrap();
+	let mut output = Vec::new();
+	decoder.read_to_end(&mut output).unwrap();
+	assert_eq!(input, output);
+
+	// Fails without the dictionary
+	let mut decoder = ::zstd::Decoder::new(compressed.as_slice()).unwrap();
+	assert!(decoder.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn round_trip_without_dictionary() {
+	let dir = tempfile::tempdir().unwrap();
+	let input_path = dir.path().join("image.tar");
+	let output_path = dir.path().join("image.tar.zst");
+
+	let input = (0..32).flat_map(sample).collect::<Vec<_>>();
+	std::fs::write(&input_path, &input).unwrap();
+
+	zstd::compress(&input_path, &output_path, zstd::DEFAULT_LEVEL, None).unwrap();
+
+	let output = ::zstd::decode_all(std::fs::File::open(&output_path).unwrap()).unwrap();
+	assert_eq!(input, output);
+}
+
+#[test]
+fn invalid_level() {
+	let dir = tempfile::tempdir().unwrap();
+	let input_path = dir.path().join("image.tar");
+	std::fs::write(&input_path, sample(0)).unwrap();
+
+	assert!(zstd::compress(&input_path, &dir.path().join("image.tar.zst"), 1000, None).is_err());
+}